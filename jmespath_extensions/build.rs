@@ -15,6 +15,7 @@ use std::path::Path;
 
 fn main() {
     println!("cargo:rerun-if-changed=functions.toml");
+    println!("cargo:rerun-if-changed=src");
 
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let toml_path = Path::new(&manifest_dir).join("functions.toml");
@@ -50,6 +51,58 @@ fn main() {
 
     // Generate test data for runtime example validation
     generate_example_test_data(&out_dir, &data.functions);
+
+    // Generate the list of extension function names
+    generate_registered_names(&manifest_dir, &out_dir, &data.functions);
+}
+
+/// Collect every extension function name: each non-standard function and
+/// alias in functions.toml, plus legacy names registered in the source.
+///
+/// functions.toml is authoritative, so registrations built from computed or
+/// prefixed names are still listed. The source scan only adds undocumented
+/// `register_function("name", ...)` literals. Feature-gated registrations are
+/// filtered at runtime.
+fn generate_registered_names(manifest_dir: &str, out_dir: &str, functions: &[Function]) {
+    let mut names = std::collections::BTreeSet::new();
+    for func in functions.iter().filter(|f| !f.is_standard.unwrap_or(false)) {
+        names.insert(func.name.clone());
+        names.extend(func.aliases.iter().flatten().cloned());
+    }
+
+    let src_dir = Path::new(manifest_dir).join("src");
+    let mut entries: Vec<_> = fs::read_dir(&src_dir)
+        .expect("Failed to read src directory")
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let content = fs::read_to_string(&path).expect("Failed to read source file");
+        let mut rest = content.as_str();
+        while let Some(pos) = rest.find("register_function(") {
+            rest = &rest[pos + "register_function(".len()..];
+            let trimmed = rest.trim_start();
+            if let Some(stripped) = trimmed.strip_prefix('"') {
+                if let Some(end) = stripped.find('"') {
+                    names.insert(stripped[..end].to_string());
+                }
+            }
+        }
+    }
+
+    let mut code = String::new();
+    code.push_str("// Auto-generated from functions.toml and src/*.rs - DO NOT EDIT\n\n");
+    code.push_str("pub const REGISTERED_NAMES: &[&str] = &[\n");
+    for name in &names {
+        code.push_str(&format!("    {:?},\n", name));
+    }
+    code.push_str("];\n");
+
+    let names_path = Path::new(out_dir).join("registered_names.rs");
+    fs::write(names_path, code).expect("Failed to write registered_names.rs");
 }
 
 fn generate_module_docs(out_dir: &str, by_category: &BTreeMap<String, Vec<&Function>>) {
@@ -281,6 +334,8 @@ fn category_to_module(category: &str) -> String {
         "standard" => "functions".to_string(),
         "multi-match" | "multimatch" => "mod@multi_match".to_string(),
         "jsonpatch" => "mod@jsonpatch".to_string(),
+        "jsonpath" => "mod@jsonpath".to_string(),
+        "jsonlogic" => "mod@jsonlogic".to_string(),
         "datetime" => "mod@datetime".to_string(),
         "regex" => "mod@regex_fns".to_string(),
         "url" => "mod@url_fns".to_string(),
@@ -298,6 +353,8 @@ fn category_title(category: &str) -> String {
         "standard" => "Standard JMESPath".to_string(),
         "multi-match" => "Multi-Match".to_string(),
         "jsonpatch" => "JSON Patch".to_string(),
+        "jsonpath" => "JSONPath".to_string(),
+        "jsonlogic" => "JsonLogic".to_string(),
         _ => {
             // Title case
             let mut chars = category.chars();
@@ -16,22 +16,26 @@
 //! array::register(&mut runtime);
 //! ```
 
+use crate::common::Rc;
 use std::collections::HashSet;
-use std::rc::Rc;
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
+    exact_integer,
 };
 use crate::define_function;
 
 /// Register all array functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
     runtime.register_function("unique", Box::new(UniqueFn::new()));
+    runtime.register_function("dedup", Box::new(DedupFn::new()));
     runtime.register_function("zip", Box::new(ZipFn::new()));
     runtime.register_function("chunk", Box::new(ChunkFn::new()));
     runtime.register_function("take", Box::new(TakeFn::new()));
     runtime.register_function("drop", Box::new(DropFn::new()));
     runtime.register_function("flatten_deep", Box::new(FlattenDeepFn::new()));
+    runtime.register_function("flatten_depth", Box::new(FlattenDepthFn::new()));
+    runtime.register_function("flatten_with_paths", Box::new(FlattenWithPathsFn::new()));
     runtime.register_function("flatten", Box::new(FlattenFn::new()));
     runtime.register_function("compact", Box::new(CompactFn::new()));
     runtime.register_function("range", Box::new(RangeFn::new()));
@@ -61,6 +65,11 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("combinations", Box::new(CombinationsFn::new()));
     runtime.register_function("transpose", Box::new(TransposeFn::new()));
     runtime.register_function("pairwise", Box::new(PairwiseFn::new()));
+    runtime.register_function("binary_search", Box::new(BinarySearchFn::new()));
+    runtime.register_function("sorted_index", Box::new(SortedIndexFn::new()));
+    runtime.register_function("is_sorted", Box::new(IsSortedFn::new()));
+    runtime.register_function("rle_encode", Box::new(RleEncodeFn::new()));
+    runtime.register_function("rle_decode", Box::new(RleDecodeFn::new()));
     // Alias for window (sliding_window is a common name)
     runtime.register_function("sliding_window", Box::new(WindowFn::new()));
 }
@@ -97,6 +106,29 @@ impl Function for UniqueFn {
     }
 }
 
+// =============================================================================
+// dedup(array) -> array (remove consecutive duplicates only)
+// =============================================================================
+
+define_function!(DedupFn, vec![ArgumentType::Array], None);
+
+impl Function for DedupFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+
+        let mut result: Vec<Rcvar> = Vec::with_capacity(arr.len());
+        for item in arr {
+            if result.last() != Some(item) {
+                result.push(item.clone());
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
 // =============================================================================
 // zip(array1, array2) -> array of pairs
 // =============================================================================
@@ -282,6 +314,94 @@ impl Function for FlattenDeepFn {
     }
 }
 
+// =============================================================================
+// flatten_depth(array, depth) -> array (flatten up to depth levels)
+// =============================================================================
+
+define_function!(
+    FlattenDepthFn,
+    vec![ArgumentType::Array, ArgumentType::Number],
+    None
+);
+
+fn flatten_to_depth(arr: &[Rcvar], depth: usize, result: &mut Vec<Rcvar>) {
+    for item in arr {
+        match item.as_array() {
+            Some(inner) if depth > 0 => flatten_to_depth(inner, depth - 1, result),
+            _ => result.push(item.clone()),
+        }
+    }
+}
+
+impl Function for FlattenDepthFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+        let depth = args[1].as_number().unwrap();
+
+        if depth < 0.0 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("flatten_depth() depth must be non-negative".to_owned()),
+            ));
+        }
+
+        let mut result = Vec::with_capacity(arr.len());
+        flatten_to_depth(arr, depth as usize, &mut result);
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// flatten_with_paths(value) -> array of {path, value} (path = array indices)
+// =============================================================================
+
+define_function!(FlattenWithPathsFn, vec![ArgumentType::Any], None);
+
+fn flatten_paths_recursive(
+    value: &Rcvar,
+    path: &mut Vec<usize>,
+    result: &mut Vec<(Vec<usize>, Rcvar)>,
+) {
+    match value.as_array() {
+        Some(arr) => {
+            for (idx, item) in arr.iter().enumerate() {
+                path.push(idx);
+                flatten_paths_recursive(item, path, result);
+                path.pop();
+            }
+        }
+        None => result.push((path.clone(), value.clone())),
+    }
+}
+
+impl Function for FlattenWithPathsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let mut flat = Vec::new();
+        flatten_paths_recursive(&args[0], &mut Vec::new(), &mut flat);
+
+        let result: Vec<Rcvar> = flat
+            .into_iter()
+            .map(|(path, value)| {
+                let path: Vec<Rcvar> = path
+                    .into_iter()
+                    .map(|i| Rc::new(Variable::Number(serde_json::Number::from(i))))
+                    .collect();
+                let mut obj = std::collections::BTreeMap::new();
+                obj.insert("path".to_string(), Rc::new(Variable::Array(path)));
+                obj.insert("value".to_string(), value);
+                Rc::new(Variable::Object(obj))
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
 // =============================================================================
 // flatten(array) -> array (single-level flatten)
 // =============================================================================
@@ -391,11 +511,20 @@ impl Function for RangeFn {
             ));
         }
 
+        const MAX_RANGE: usize = 10000;
+
+        let len = if (step > 0 && start < end) || (step < 0 && start > end) {
+            (end as i128 - start as i128)
+                .unsigned_abs()
+                .div_ceil(step.unsigned_abs() as u128)
+        } else {
+            0
+        };
+        crate::limits::check_array_len(ctx, len.min(MAX_RANGE as u128) as usize)?;
+
         let mut result = Vec::new();
         let mut current = start;
 
-        const MAX_RANGE: usize = 10000;
-
         if step > 0 {
             while current < end && result.len() < MAX_RANGE {
                 result.push(Rc::new(Variable::Number(serde_json::Number::from(current))) as Rcvar);
@@ -1090,7 +1219,10 @@ impl Function for CartesianFn {
             )
         })?;
 
-        let mut result = Vec::with_capacity(arr1.len() * arr2.len());
+        let len = arr1.len().saturating_mul(arr2.len());
+        crate::limits::check_array_len(ctx, len)?;
+
+        let mut result = Vec::with_capacity(len);
 
         for a in arr1 {
             for b in arr2 {
@@ -1671,6 +1803,233 @@ impl Function for PairwiseFn {
     }
 }
 
+// =============================================================================
+// binary_search(sorted_array, value) -> number | null
+// =============================================================================
+
+// Find the index of a value in an array sorted in ascending order.
+//
+// Uses the same ordering as the builtin `sort` function (numbers numerically,
+// strings lexicographically). Returns the index of the first matching element,
+// or null if the value is not present. The result is unspecified if the array
+// is not sorted.
+//
+// # Example
+// binary_search([1, 3, 5, 7], `5`) -> 2
+// binary_search([1, 3, 5, 7], `4`) -> null
+define_function!(
+    BinarySearchFn,
+    vec![ArgumentType::Array, ArgumentType::Any],
+    None
+);
+
+impl Function for BinarySearchFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let target = &*args[1];
+        let idx = arr.partition_point(|item| item.as_ref() < target);
+
+        if idx < arr.len() && arr[idx].as_ref() == target {
+            Ok(Rc::new(Variable::Number(serde_json::Number::from(idx))))
+        } else {
+            Ok(Rc::new(Variable::Null))
+        }
+    }
+}
+
+// =============================================================================
+// sorted_index(sorted_array, value) -> number
+// =============================================================================
+
+// Find the lowest index at which a value could be inserted into a sorted array
+// while keeping it sorted.
+//
+// # Example
+// sorted_index([10, 20, 30], `25`) -> 2
+// sorted_index([10, 20, 30], `20`) -> 1
+define_function!(
+    SortedIndexFn,
+    vec![ArgumentType::Array, ArgumentType::Any],
+    None
+);
+
+impl Function for SortedIndexFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let target = &*args[1];
+        let idx = arr.partition_point(|item| item.as_ref() < target);
+
+        Ok(Rc::new(Variable::Number(serde_json::Number::from(idx))))
+    }
+}
+
+// =============================================================================
+// is_sorted(array) -> boolean
+// =============================================================================
+
+// Check whether an array is sorted in ascending (non-decreasing) order.
+//
+// # Example
+// is_sorted([1, 2, 2, 3]) -> true
+// is_sorted(['b', 'a']) -> false
+define_function!(IsSortedFn, vec![ArgumentType::Array], None);
+
+impl Function for IsSortedFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let sorted = arr.windows(2).all(|w| w[0].as_ref() <= w[1].as_ref());
+
+        Ok(Rc::new(Variable::Bool(sorted)))
+    }
+}
+
+// =============================================================================
+// rle_encode(array) -> array of [value, count] pairs
+// =============================================================================
+
+// Run-length encode an array, collapsing runs of consecutive equal values.
+//
+// # Example
+// rle_encode(['a', 'a', 'b', 'a']) -> [['a', 2], ['b', 1], ['a', 1]]
+define_function!(RleEncodeFn, vec![ArgumentType::Array], None);
+
+impl Function for RleEncodeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let mut runs: Vec<(Rcvar, usize)> = Vec::new();
+        for item in arr {
+            match runs.last_mut() {
+                Some((value, count)) if value == item => *count += 1,
+                _ => runs.push((item.clone(), 1)),
+            }
+        }
+
+        let result: Vec<Rcvar> = runs
+            .into_iter()
+            .map(|(value, count)| {
+                Rc::new(Variable::Array(vec![
+                    value,
+                    Rc::new(Variable::Number(serde_json::Number::from(count))),
+                ])) as Rcvar
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// rle_decode(pairs) -> array
+// =============================================================================
+
+// Expand an array of [value, count] pairs produced by rle_encode.
+//
+// # Example
+// rle_decode([['a', 2], ['b', 1]]) -> ['a', 'a', 'b']
+define_function!(RleDecodeFn, vec![ArgumentType::Array], None);
+
+/// Longest array `rle_decode()` will expand to.
+const MAX_RLE_DECODE_LEN: usize = 1_000_000;
+
+impl Function for RleDecodeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let pairs = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let mut runs = Vec::with_capacity(pairs.len());
+        for pair in pairs {
+            let (value, count) = match pair.as_array().map(|p| p.as_slice()) {
+                Some([value, count]) => match exact_integer(count).map(usize::try_from) {
+                    Ok(Ok(n)) => (value, n),
+                    _ => {
+                        return Err(JmespathError::new(
+                            ctx.expression,
+                            0,
+                            ErrorReason::Parse(
+                                "rle_decode count must be a non-negative integer".to_owned(),
+                            ),
+                        ));
+                    }
+                },
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse("rle_decode expects [value, count] pairs".to_owned()),
+                    ));
+                }
+            };
+            runs.push((value, count));
+        }
+
+        // Size the output before building it, so a huge count fails up front
+        let total = runs
+            .iter()
+            .fold(0usize, |total, (_, count)| total.saturating_add(*count));
+        if total > MAX_RLE_DECODE_LEN {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "rle_decode would produce {} elements (limit {})",
+                    total, MAX_RLE_DECODE_LEN
+                )),
+            ));
+        }
+        crate::limits::check_array_len(ctx, total)?;
+
+        let mut result = Vec::with_capacity(total);
+        for (value, count) in runs {
+            result.extend(std::iter::repeat_n(value.clone(), count));
+        }
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1683,6 +2042,19 @@ mod tests {
         runtime
     }
 
+    #[test]
+    fn test_dedup() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("dedup(@)").unwrap();
+        let data =
+            Variable::from_json(r#"[1, 1, 2, 2, 2, 1, "a", "a", {"x": 1}, {"x": 1}]"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            *result,
+            Variable::from_json(r#"[1, 2, 1, "a", {"x": 1}]"#).unwrap()
+        );
+    }
+
     #[test]
     fn test_unique() {
         let runtime = setup_runtime();
@@ -2225,6 +2597,49 @@ mod tests {
         assert_eq!(arr.len(), 6);
     }
 
+    #[test]
+    fn test_flatten_depth() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[1, [2, [3, [4]]]]"#).unwrap();
+
+        let expr = runtime.compile("flatten_depth(@, `1`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, Variable::from_json(r#"[1, 2, [3, [4]]]"#).unwrap());
+
+        let expr = runtime.compile("flatten_depth(@, `2`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, Variable::from_json(r#"[1, 2, 3, [4]]"#).unwrap());
+
+        let expr = runtime.compile("flatten_depth(@, `0`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, data);
+    }
+
+    #[test]
+    fn test_flatten_depth_negative() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("flatten_depth(`[[1]]`, `-1`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_flatten_with_paths() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[[1, [2]], 3, {"a": [4]}]"#).unwrap();
+        let expr = runtime.compile("flatten_with_paths(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"[
+                {"path": [0, 0], "value": 1},
+                {"path": [0, 1, 0], "value": 2},
+                {"path": [1], "value": 3},
+                {"path": [2], "value": {"a": [4]}}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
     // =========================================================================
     // flatten tests (single-level)
     // =========================================================================
@@ -2861,4 +3276,125 @@ mod tests {
         assert_eq!(win0.len(), 3);
         assert_eq!(win0[0].as_number().unwrap() as i64, 1);
     }
+
+    #[test]
+    fn test_binary_search_found() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[1, 3, 5, 7, 9]"#).unwrap();
+        let expr = runtime.compile("binary_search(@, `7`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 3);
+    }
+
+    #[test]
+    fn test_binary_search_first_duplicate() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[1, 2, 2, 2, 3]"#).unwrap();
+        let expr = runtime.compile("binary_search(@, `2`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 1);
+    }
+
+    #[test]
+    fn test_binary_search_not_found() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"["apple", "banana", "cherry"]"#).unwrap();
+        let expr = runtime.compile("binary_search(@, 'blueberry')").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn test_sorted_index() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[10, 20, 30]"#).unwrap();
+        let expr = runtime.compile("sorted_index(@, `25`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 2);
+
+        let expr = runtime.compile("sorted_index(@, `5`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 0);
+
+        let expr = runtime.compile("sorted_index(@, `99`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 3);
+    }
+
+    #[test]
+    fn test_is_sorted() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("is_sorted(@)").unwrap();
+
+        let data = Variable::from_json(r#"[1, 2, 2, 3]"#).unwrap();
+        assert!(expr.search(&data).unwrap().as_boolean().unwrap());
+
+        let data = Variable::from_json(r#"[3, 1, 2]"#).unwrap();
+        assert!(!expr.search(&data).unwrap().as_boolean().unwrap());
+
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        assert!(expr.search(&data).unwrap().as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_rle_encode() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"["up", "up", "down", "up", "up", "up"]"#).unwrap();
+        let expr = runtime.compile("rle_encode(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"[["up", 2], ["down", 1], ["up", 3]]"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_rle_encode_empty() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime.compile("rle_encode(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_array().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_rle_round_trip() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[1, 1, {"a": 1}, {"a": 1}, null, 1]"#).unwrap();
+        let expr = runtime.compile("rle_decode(rle_encode(@))").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, data);
+    }
+
+    #[test]
+    fn test_rle_decode_invalid_pair() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[["a"]]"#).unwrap();
+        let expr = runtime.compile("rle_decode(@)").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_rle_decode_rejects_fractional_count() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("rle_decode(@)").unwrap();
+        for json in [r#"[["a", 2.9]]"#, r#"[["a", 0.5]]"#, r#"[["a", -1]]"#] {
+            let data = Variable::from_json(json).unwrap();
+            let err = expr.search(&data).unwrap_err().to_string();
+            assert!(err.contains("non-negative integer"), "{}: {}", json, err);
+        }
+        let data = Variable::from_json(r#"[["a", 2.0]]"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rle_decode_total_length_cap() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("rle_decode(@)").unwrap();
+        // Each run is small, but together they exceed the cap
+        let data = Variable::from_json(r#"[["a", 600000], ["b", 600000]]"#).unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("limit 1000000"), "{}", err);
+        let data = Variable::from_json(r#"[["a", 1e300]]"#).unwrap();
+        assert!(expr.search(&data).is_err());
+    }
 }
@@ -104,7 +104,7 @@ impl Function for RgbToHexFn {
 define_function!(
     LightenFn,
     vec![ArgumentType::String, ArgumentType::Number],
-    None
+    Some(ArgumentType::String)
 );
 
 impl Function for LightenFn {
@@ -133,6 +133,11 @@ impl Function for LightenFn {
         };
 
         let factor = (amount / 100.0).clamp(0.0, 1.0);
+        if use_oklch(args.get(2), ctx)? {
+            let (l, c, h) = rgb_to_oklch(r, g, b);
+            let (r, g, b) = oklch_to_rgb(l + (1.0 - l) * factor, c, h);
+            return Ok(rcvar(Variable::String(to_hex(r, g, b))));
+        }
         let r = (r as f64 + (255.0 - r as f64) * factor).round() as u8;
         let g = (g as f64 + (255.0 - g as f64) * factor).round() as u8;
         let b = (b as f64 + (255.0 - b as f64) * factor).round() as u8;
@@ -145,7 +150,7 @@ impl Function for LightenFn {
 define_function!(
     DarkenFn,
     vec![ArgumentType::String, ArgumentType::Number],
-    None
+    Some(ArgumentType::String)
 );
 
 impl Function for DarkenFn {
@@ -174,6 +179,11 @@ impl Function for DarkenFn {
         };
 
         let factor = 1.0 - (amount / 100.0).clamp(0.0, 1.0);
+        if use_oklch(args.get(2), ctx)? {
+            let (l, c, h) = rgb_to_oklch(r, g, b);
+            let (r, g, b) = oklch_to_rgb(l * factor, c, h);
+            return Ok(rcvar(Variable::String(to_hex(r, g, b))));
+        }
         let r = (r as f64 * factor).round() as u8;
         let g = (g as f64 * factor).round() as u8;
         let b = (b as f64 * factor).round() as u8;
@@ -320,6 +330,10 @@ impl Function for ColorComplementFn {
 /// Parse a hex color string into RGB components.
 fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8)> {
     let hex = hex.trim().trim_start_matches('#');
+    // Hex digits are ASCII; anything else would not slice on char boundaries
+    if !hex.is_ascii() {
+        return None;
+    }
 
     match hex.len() {
         3 => {
@@ -416,6 +430,1120 @@ fn hue_to_rgb(p: f64, q: f64, mut t: f64) -> f64 {
     p
 }
 
+/// Parse the optional color space argument of lighten/darken ("rgb" or "oklch").
+fn use_oklch(space: Option<&Rcvar>, ctx: &Context<'_>) -> Result<bool, JmespathError> {
+    match space.and_then(|s| s.as_string()).map(|s| s.as_str()) {
+        None | Some("rgb") => Ok(false),
+        Some("oklch") => Ok(true),
+        Some(other) => Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "Unknown color space '{}': expected rgb or oklch",
+                other
+            )),
+        )),
+    }
+}
+
+// Color space conversions
+
+/// Parse a color given as a hex string or an `{r, g, b}` object.
+fn color_arg(value: &Variable) -> Option<(u8, u8, u8)> {
+    match value {
+        Variable::String(s) => parse_hex_color(s),
+        Variable::Object(map) => {
+            let channel = |key: &str| {
+                map.get(key)
+                    .and_then(|v| v.as_number())
+                    .filter(|n| (0.0..=255.0).contains(n))
+                    .map(|n| n.round() as u8)
+            };
+            Some((channel("r")?, channel("g")?, channel("b")?))
+        }
+        _ => None,
+    }
+}
+
+/// Read three components either from three number arguments or from a single
+/// object argument with the given keys.
+fn components_arg(
+    args: &[Rcvar],
+    keys: [&str; 3],
+    ctx: &Context<'_>,
+) -> Result<Option<(f64, f64, f64)>, JmespathError> {
+    let values: Option<Vec<f64>> = match args {
+        [single] => match &**single {
+            Variable::Object(map) => keys
+                .iter()
+                .map(|key| map.get(*key).and_then(|v| v.as_number()))
+                .collect(),
+            Variable::Null => return Ok(None),
+            _ => None,
+        },
+        [a, b, c] => [a, b, c].iter().map(|v| v.as_number()).collect(),
+        _ => None,
+    };
+    match values {
+        Some(v) => Ok(Some((v[0], v[1], v[2]))),
+        None => Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "Expected an object with {}, {} and {}, or three numbers",
+                keys[0], keys[1], keys[2]
+            )),
+        )),
+    }
+}
+
+fn round_to(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
+}
+
+/// Build an object from three named, rounded components.
+fn components_var(keys: [&str; 3], values: (f64, f64, f64), places: [i32; 3]) -> Rcvar {
+    let mut map = BTreeMap::new();
+    for (i, value) in [values.0, values.1, values.2].into_iter().enumerate() {
+        // Avoid "-0" in output
+        let rounded = round_to(value, places[i]) + 0.0;
+        map.insert(
+            keys[i].to_string(),
+            rcvar(Variable::Number(
+                serde_json::Number::from_f64(rounded)
+                    .unwrap_or_else(|| serde_json::Number::from(0)),
+            )),
+        );
+    }
+    rcvar(Variable::Object(map))
+}
+
+fn to_hex(r: u8, g: u8, b: u8) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Convert unit-range float channels to 8-bit, clamping out-of-gamut values.
+fn unit_to_u8(c: f64) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn srgb_to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> u8 {
+    let c = if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    unit_to_u8(c)
+}
+
+/// Convert RGB to HSV (h in degrees, s and v in 0..1).
+fn rgb_to_hsv(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let (h, _, _) = rgb_to_hsl(r, g, b);
+    let max = r.max(g).max(b) as f64 / 255.0;
+    let min = r.min(g).min(b) as f64 / 255.0;
+    let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+    (h, s, max)
+}
+
+/// Convert HSV (h in degrees, s and v in 0..1) to RGB.
+fn hsv_to_rgb(h: f64, s: f64, v: f64) -> (u8, u8, u8) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    (unit_to_u8(r + m), unit_to_u8(g + m), unit_to_u8(b + m))
+}
+
+// CIE D65 reference white
+const D65: (f64, f64, f64) = (0.95047, 1.0, 1.08883);
+
+/// Convert RGB to CIE L*a*b* (D65).
+fn rgb_to_lab(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+    let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+
+    let f = |t: f64| {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x / D65.0), f(y / D65.1), f(z / D65.2));
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// Convert CIE L*a*b* (D65) to RGB.
+fn lab_to_rgb(l: f64, a: f64, b: f64) -> (u8, u8, u8) {
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let inv = |t: f64| {
+        if t.powi(3) > 216.0 / 24389.0 {
+            t.powi(3)
+        } else {
+            (116.0 * t - 16.0) * 27.0 / 24389.0
+        }
+    };
+    let (x, y, z) = (inv(fx) * D65.0, inv(fy) * D65.1, inv(fz) * D65.2);
+
+    let r = 3.2404542 * x - 1.5371385 * y - 0.4985314 * z;
+    let g = -0.9692660 * x + 1.8760108 * y + 0.0415560 * z;
+    let b = 0.0556434 * x - 0.2040259 * y + 1.0572252 * z;
+    (linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+}
+
+/// Convert RGB to OKLCH (l in 0..1, chroma, hue in degrees).
+fn rgb_to_oklch(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let (r, g, b) = (srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b));
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    let lightness = 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s;
+    let a = 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s;
+    let bb = 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s;
+
+    let chroma = (a * a + bb * bb).sqrt();
+    // Hue is meaningless for achromatic colors; report 0 instead of noise
+    let hue = if chroma < 1e-4 {
+        0.0
+    } else {
+        bb.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+    (lightness, chroma, hue)
+}
+
+/// Convert OKLCH to linear sRGB (may be out of gamut).
+fn oklch_to_linear(lightness: f64, chroma: f64, hue: f64) -> (f64, f64, f64) {
+    let (a, b) = (
+        chroma * hue.to_radians().cos(),
+        chroma * hue.to_radians().sin(),
+    );
+    let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+    (
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    )
+}
+
+/// Convert OKLCH to RGB. Out-of-gamut colors are mapped into sRGB by reducing
+/// chroma while keeping lightness and hue, as CSS Color 4 does.
+fn oklch_to_rgb(lightness: f64, chroma: f64, hue: f64) -> (u8, u8, u8) {
+    const EPSILON: f64 = 1e-4;
+    let in_gamut = |(r, g, b): (f64, f64, f64)| {
+        [r, g, b]
+            .iter()
+            .all(|c| (-EPSILON..=1.0 + EPSILON).contains(c))
+    };
+
+    if lightness <= 0.0 {
+        return (0, 0, 0);
+    }
+    if lightness >= 1.0 {
+        return (255, 255, 255);
+    }
+
+    let mut rgb = oklch_to_linear(lightness, chroma, hue);
+    if !in_gamut(rgb) {
+        let (mut low, mut high) = (0.0, chroma);
+        rgb = oklch_to_linear(lightness, 0.0, hue);
+        for _ in 0..24 {
+            let mid = (low + high) / 2.0;
+            let candidate = oklch_to_linear(lightness, mid, hue);
+            if in_gamut(candidate) {
+                low = mid;
+                rgb = candidate;
+            } else {
+                high = mid;
+            }
+        }
+    }
+    (
+        linear_to_srgb(rgb.0),
+        linear_to_srgb(rgb.1),
+        linear_to_srgb(rgb.2),
+    )
+}
+
+define_function!(RgbToHslFn, vec![ArgumentType::Any], None);
+
+impl Function for RgbToHslFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some((r, g, b)) => {
+                let (h, s, l) = rgb_to_hsl(r, g, b);
+                Ok(components_var(
+                    ["h", "s", "l"],
+                    (h, s * 100.0, l * 100.0),
+                    [2, 2, 2],
+                ))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    HslToRgbFn,
+    vec![ArgumentType::Any],
+    Some(ArgumentType::Number)
+);
+
+impl Function for HslToRgbFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match components_arg(args, ["h", "s", "l"], ctx)? {
+            Some((h, s, l)) => {
+                let s = (s / 100.0).clamp(0.0, 1.0);
+                let l = (l / 100.0).clamp(0.0, 1.0);
+                let (r, g, b) = hsl_to_rgb(h.rem_euclid(360.0), s, l);
+                Ok(rcvar(Variable::String(to_hex(r, g, b))))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(RgbToHsvFn, vec![ArgumentType::Any], None);
+
+impl Function for RgbToHsvFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some((r, g, b)) => {
+                let (h, s, v) = rgb_to_hsv(r, g, b);
+                Ok(components_var(
+                    ["h", "s", "v"],
+                    (h, s * 100.0, v * 100.0),
+                    [2, 2, 2],
+                ))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    HsvToRgbFn,
+    vec![ArgumentType::Any],
+    Some(ArgumentType::Number)
+);
+
+impl Function for HsvToRgbFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match components_arg(args, ["h", "s", "v"], ctx)? {
+            Some((h, s, v)) => {
+                let s = (s / 100.0).clamp(0.0, 1.0);
+                let v = (v / 100.0).clamp(0.0, 1.0);
+                let (r, g, b) = hsv_to_rgb(h, s, v);
+                Ok(rcvar(Variable::String(to_hex(r, g, b))))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(RgbToLabFn, vec![ArgumentType::Any], None);
+
+impl Function for RgbToLabFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some((r, g, b)) => Ok(components_var(
+                ["l", "a", "b"],
+                rgb_to_lab(r, g, b),
+                [2, 2, 2],
+            )),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    LabToRgbFn,
+    vec![ArgumentType::Any],
+    Some(ArgumentType::Number)
+);
+
+impl Function for LabToRgbFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match components_arg(args, ["l", "a", "b"], ctx)? {
+            Some((l, a, b)) => {
+                let (r, g, b) = lab_to_rgb(l, a, b);
+                Ok(rcvar(Variable::String(to_hex(r, g, b))))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(RgbToOklchFn, vec![ArgumentType::Any], None);
+
+impl Function for RgbToOklchFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some((r, g, b)) => Ok(components_var(
+                ["l", "c", "h"],
+                rgb_to_oklch(r, g, b),
+                [4, 4, 2],
+            )),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    OklchToRgbFn,
+    vec![ArgumentType::Any],
+    Some(ArgumentType::Number)
+);
+
+impl Function for OklchToRgbFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match components_arg(args, ["l", "c", "h"], ctx)? {
+            Some((l, c, h)) => {
+                let (r, g, b) = oklch_to_rgb(l.clamp(0.0, 1.0), c.max(0.0), h);
+                Ok(rcvar(Variable::String(to_hex(r, g, b))))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+// Accessibility (WCAG 2.x)
+
+/// Relative luminance as defined by WCAG 2.x (0 for black, 1 for white).
+fn relative_luminance(r: u8, g: u8, b: u8) -> f64 {
+    0.2126 * srgb_to_linear(r) + 0.7152 * srgb_to_linear(g) + 0.0722 * srgb_to_linear(b)
+}
+
+/// Contrast ratio between two colors, from 1 (identical) to 21 (black on white).
+fn contrast(fg: (u8, u8, u8), bg: (u8, u8, u8)) -> f64 {
+    let l1 = relative_luminance(fg.0, fg.1, fg.2);
+    let l2 = relative_luminance(bg.0, bg.1, bg.2);
+    (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+}
+
+define_function!(
+    ContrastRatioFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+impl Function for ContrastRatioFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match (color_arg(&args[0]), color_arg(&args[1])) {
+            (Some(fg), Some(bg)) => Ok(rcvar(Variable::Number(
+                serde_json::Number::from_f64(round_to(contrast(fg, bg), 2)).unwrap(),
+            ))),
+            _ => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    IsWcagAaFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    Some(ArgumentType::String)
+);
+
+impl Function for IsWcagAaFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        // AA requires 4.5:1 for body text and 3:1 for large (18pt, or 14pt bold) text
+        let threshold = match args.get(2).and_then(|s| s.as_string()).map(|s| s.as_str()) {
+            None | Some("normal") => 4.5,
+            Some("large") => 3.0,
+            Some(other) => {
+                return Err(JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse(format!(
+                        "Unknown text size '{}': expected normal or large",
+                        other
+                    )),
+                ));
+            }
+        };
+
+        match (color_arg(&args[0]), color_arg(&args[1])) {
+            (Some(fg), Some(bg)) => Ok(rcvar(Variable::Bool(contrast(fg, bg) >= threshold))),
+            _ => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(AccessibleTextColorFn, vec![ArgumentType::Any], None);
+
+impl Function for AccessibleTextColorFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some(bg) => {
+                let text = if contrast((0, 0, 0), bg) >= contrast((255, 255, 255), bg) {
+                    "#000000"
+                } else {
+                    "#ffffff"
+                };
+                Ok(rcvar(Variable::String(text.to_string())))
+            }
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+// Palette generation
+
+/// Rotate the hue of a color in HSL space by the given number of degrees.
+fn rotate_hue(color: (u8, u8, u8), degrees: f64) -> String {
+    let (h, s, l) = rgb_to_hsl(color.0, color.1, color.2);
+    let (r, g, b) = hsl_to_rgb((h + degrees).rem_euclid(360.0), s, l);
+    to_hex(r, g, b)
+}
+
+fn palette_var(colors: Vec<String>) -> Rcvar {
+    rcvar(Variable::Array(
+        colors
+            .into_iter()
+            .map(|c| rcvar(Variable::String(c)))
+            .collect(),
+    ))
+}
+
+/// Parse the palette size argument, which must be a positive integer.
+fn palette_size(value: &Variable, ctx: &Context<'_>) -> Result<usize, JmespathError> {
+    match value.as_number() {
+        Some(n) if n >= 1.0 && n.fract() == 0.0 && n <= 360.0 => Ok(n as usize),
+        _ => Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse("Palette size must be an integer between 1 and 360".to_owned()),
+        )),
+    }
+}
+
+define_function!(ComplementaryFn, vec![ArgumentType::Any], None);
+
+impl Function for ComplementaryFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some(color) => Ok(palette_var(vec![
+                rotate_hue(color, 0.0),
+                rotate_hue(color, 180.0),
+            ])),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(TriadicFn, vec![ArgumentType::Any], None);
+
+impl Function for TriadicFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match color_arg(&args[0]) {
+            Some(color) => Ok(palette_var(vec![
+                rotate_hue(color, 0.0),
+                rotate_hue(color, 120.0),
+                rotate_hue(color, 240.0),
+            ])),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    AnalogousFn,
+    vec![ArgumentType::Any, ArgumentType::Number],
+    None
+);
+
+impl Function for AnalogousFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = palette_size(&args[1], ctx)?;
+        let Some(color) = color_arg(&args[0]) else {
+            return Ok(rcvar(Variable::Null));
+        };
+
+        // Neighbours are 30 degrees apart, centered on the input color
+        let center = (n - 1) as f64 / 2.0;
+        let colors = (0..n)
+            .map(|i| rotate_hue(color, (i as f64 - center) * 30.0))
+            .collect();
+        Ok(palette_var(colors))
+    }
+}
+
+define_function!(
+    ShadesFn,
+    vec![ArgumentType::Any, ArgumentType::Number],
+    None
+);
+
+impl Function for ShadesFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = palette_size(&args[1], ctx)?;
+        let Some((r, g, b)) = color_arg(&args[0]) else {
+            return Ok(rcvar(Variable::Null));
+        };
+
+        // Step OKLCH lightness evenly from the input color toward black
+        let (l, c, h) = rgb_to_oklch(r, g, b);
+        let colors = (0..n)
+            .map(|i| {
+                let (r, g, b) = oklch_to_rgb(l * (1.0 - i as f64 / n as f64), c, h);
+                to_hex(r, g, b)
+            })
+            .collect();
+        Ok(palette_var(colors))
+    }
+}
+
+// CSS color parsing
+
+/// The 148 CSS Color Module Level 4 named colors, alphabetically. Where two
+/// names share a value (gray/grey, aqua/cyan) the first one is preferred by
+/// `color_name`.
+const CSS_NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+fn named_color(name: &str) -> Option<(u8, u8, u8)> {
+    CSS_NAMED_COLORS
+        .binary_search_by(|(n, _)| n.cmp(&name))
+        .ok()
+        .map(|i| {
+            let v = CSS_NAMED_COLORS[i].1;
+            ((v >> 16) as u8, (v >> 8) as u8, v as u8)
+        })
+}
+
+/// Parse a CSS number that may be given as a percentage of `full`.
+fn css_number(token: &str, full: f64) -> Option<f64> {
+    match token.strip_suffix('%') {
+        Some(pct) => pct.parse::<f64>().ok().map(|p| p / 100.0 * full),
+        None => token.parse::<f64>().ok(),
+    }
+    .filter(|n| n.is_finite())
+}
+
+/// Parse `rgb()`, `rgba()`, `hsl()` and `hsla()` in either the comma or the
+/// space-separated syntax (with an optional `/ alpha`).
+fn parse_css_function(s: &str) -> Option<(u8, u8, u8, f64)> {
+    let (name, rest) = s.split_once('(')?;
+    let body = rest.strip_suffix(')')?;
+    let parts: Vec<&str> = body
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if parts.len() != 3 && parts.len() != 4 {
+        return None;
+    }
+    let alpha = match parts.get(3) {
+        Some(a) => css_number(a, 1.0)?.clamp(0.0, 1.0),
+        None => 1.0,
+    };
+
+    match name.trim() {
+        "rgb" | "rgba" => {
+            let channel = |t: &str| css_number(t, 255.0).map(|n| n.clamp(0.0, 255.0).round() as u8);
+            Some((
+                channel(parts[0])?,
+                channel(parts[1])?,
+                channel(parts[2])?,
+                alpha,
+            ))
+        }
+        "hsl" | "hsla" => {
+            let hue = parts[0]
+                .strip_suffix("deg")
+                .unwrap_or(parts[0])
+                .parse::<f64>()
+                .ok()?;
+            let percent = |t: &str| {
+                t.strip_suffix('%')
+                    .unwrap_or(t)
+                    .parse::<f64>()
+                    .ok()
+                    .map(|n| (n / 100.0).clamp(0.0, 1.0))
+            };
+            let (r, g, b) = hsl_to_rgb(
+                hue.rem_euclid(360.0),
+                percent(parts[1])?,
+                percent(parts[2])?,
+            );
+            Some((r, g, b, alpha))
+        }
+        _ => None,
+    }
+}
+
+/// Parse any supported CSS color string into RGB plus alpha.
+fn parse_css(s: &str) -> Option<(u8, u8, u8, f64)> {
+    let s = s.trim().to_ascii_lowercase();
+    if s == "transparent" {
+        return Some((0, 0, 0, 0.0));
+    }
+    if let Some(hex) = s.strip_prefix('#') {
+        // Hex digits are ASCII; anything else would not slice on char boundaries
+        if !hex.is_ascii() {
+            return None;
+        }
+        // #rgba and #rrggbbaa carry alpha in the last digit(s)
+        let (rgb, alpha) = match hex.len() {
+            4 => (
+                &hex[..3],
+                Some(u8::from_str_radix(&hex[3..], 16).ok()? * 17),
+            ),
+            8 => (&hex[..6], Some(u8::from_str_radix(&hex[6..], 16).ok()?)),
+            _ => (hex, None),
+        };
+        let (r, g, b) = parse_hex_color(rgb)?;
+        return Some((
+            r,
+            g,
+            b,
+            alpha.map_or(1.0, |a| round_to(a as f64 / 255.0, 3)),
+        ));
+    }
+    if s.contains('(') {
+        return parse_css_function(&s);
+    }
+    named_color(&s).map(|(r, g, b)| (r, g, b, 1.0))
+}
+
+define_function!(ParseCssColorFn, vec![ArgumentType::String], None);
+
+impl Function for ParseCssColorFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let Some((r, g, b, alpha)) = parse_css(args[0].as_string().unwrap()) else {
+            return Ok(rcvar(Variable::Null));
+        };
+        let mut map = BTreeMap::new();
+        for (key, value) in [("r", r), ("g", g), ("b", b)] {
+            map.insert(
+                key.to_string(),
+                rcvar(Variable::Number(serde_json::Number::from(value))),
+            );
+        }
+        map.insert(
+            "alpha".to_string(),
+            rcvar(Variable::Number(
+                serde_json::Number::from_f64(alpha).unwrap_or_else(|| serde_json::Number::from(1)),
+            )),
+        );
+        map.insert("hex".to_string(), rcvar(Variable::String(to_hex(r, g, b))));
+        Ok(rcvar(Variable::Object(map)))
+    }
+}
+
+define_function!(ColorNameFn, vec![ArgumentType::Any], None);
+
+impl Function for ColorNameFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let Some((r, g, b)) = color_arg(&args[0]) else {
+            return Ok(rcvar(Variable::Null));
+        };
+
+        // Nearest by Euclidean distance in Lab (CIE76 delta E)
+        let target = rgb_to_lab(r, g, b);
+        let distance = |v: u32| {
+            let (l, a, b) = rgb_to_lab((v >> 16) as u8, (v >> 8) as u8, v as u8);
+            (l - target.0).powi(2) + (a - target.1).powi(2) + (b - target.2).powi(2)
+        };
+        let mut best = CSS_NAMED_COLORS[0];
+        let mut best_distance = distance(best.1);
+        for &(name, value) in &CSS_NAMED_COLORS[1..] {
+            let d = distance(value);
+            if d < best_distance {
+                best = (name, value);
+                best_distance = d;
+            }
+        }
+        Ok(rcvar(Variable::String(best.0.to_string())))
+    }
+}
+
+// Gradients
+
+#[derive(Clone, Copy)]
+enum InterpolationSpace {
+    Rgb,
+    Lab,
+    Oklch,
+}
+
+/// Parse the optional interpolation space argument ("rgb", "lab" or "oklch").
+fn interpolation_space(
+    space: Option<&Rcvar>,
+    ctx: &Context<'_>,
+) -> Result<InterpolationSpace, JmespathError> {
+    match space.and_then(|s| s.as_string()).map(|s| s.as_str()) {
+        None | Some("rgb") => Ok(InterpolationSpace::Rgb),
+        Some("lab") => Ok(InterpolationSpace::Lab),
+        Some("oklch") => Ok(InterpolationSpace::Oklch),
+        Some(other) => Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "Unknown interpolation space '{}': expected rgb, lab or oklch",
+                other
+            )),
+        )),
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+/// Interpolate between two colors, `t` running from 0 (start) to 1 (end).
+fn interpolate(
+    start: (u8, u8, u8),
+    end: (u8, u8, u8),
+    t: f64,
+    space: InterpolationSpace,
+) -> (u8, u8, u8) {
+    match space {
+        InterpolationSpace::Rgb => {
+            let channel = |a: u8, b: u8| lerp(a as f64, b as f64, t).round() as u8;
+            (
+                channel(start.0, end.0),
+                channel(start.1, end.1),
+                channel(start.2, end.2),
+            )
+        }
+        InterpolationSpace::Lab => {
+            let a = rgb_to_lab(start.0, start.1, start.2);
+            let b = rgb_to_lab(end.0, end.1, end.2);
+            lab_to_rgb(lerp(a.0, b.0, t), lerp(a.1, b.1, t), lerp(a.2, b.2, t))
+        }
+        InterpolationSpace::Oklch => {
+            let (l1, c1, mut h1) = rgb_to_oklch(start.0, start.1, start.2);
+            let (l2, c2, mut h2) = rgb_to_oklch(end.0, end.1, end.2);
+            // Grays have no meaningful hue, so borrow the other color's
+            if c1 < 1e-4 {
+                h1 = h2;
+            }
+            if c2 < 1e-4 {
+                h2 = h1;
+            }
+            // Take the shorter way around the hue circle
+            let mut dh = h2 - h1;
+            if dh > 180.0 {
+                dh -= 360.0;
+            } else if dh < -180.0 {
+                dh += 360.0;
+            }
+            oklch_to_rgb(
+                lerp(l1, l2, t),
+                lerp(c1, c2, t),
+                (h1 + dh * t).rem_euclid(360.0),
+            )
+        }
+    }
+}
+
+fn color_error(ctx: &Context<'_>, msg: String) -> JmespathError {
+    JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg))
+}
+
+/// A gradient stop: position (0 to 1) and color.
+type GradientStop = (f64, (u8, u8, u8));
+
+/// Parse gradient stops: either an array of colors spaced evenly from 0 to 1,
+/// or an array of `{position, color}` objects.
+fn gradient_stops(value: &Variable, ctx: &Context<'_>) -> Result<Vec<GradientStop>, JmespathError> {
+    let items = value.as_array().unwrap();
+    if items.is_empty() {
+        return Err(color_error(
+            ctx,
+            "Gradient needs at least one stop".to_owned(),
+        ));
+    }
+    let last = (items.len() - 1).max(1) as f64;
+    let mut stops = items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let (position, color) = match item.as_object() {
+                Some(obj) if obj.contains_key("color") => (
+                    obj.get("position").and_then(|p| p.as_number()),
+                    obj.get("color").and_then(|c| color_arg(c)),
+                ),
+                _ => (Some(i as f64 / last), color_arg(item)),
+            };
+            match (position, color) {
+                (Some(p), Some(c)) => Ok((p, c)),
+                _ => Err(color_error(
+                    ctx,
+                    format!("Invalid gradient stop at index {}", i),
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(stops)
+}
+
+define_function!(
+    ColorScaleFn,
+    vec![ArgumentType::Any, ArgumentType::Any, ArgumentType::Number],
+    Some(ArgumentType::String)
+);
+
+impl Function for ColorScaleFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let steps = palette_size(&args[2], ctx)?;
+        let space = interpolation_space(args.get(3), ctx)?;
+        let (Some(start), Some(end)) = (color_arg(&args[0]), color_arg(&args[1])) else {
+            return Ok(rcvar(Variable::Null));
+        };
+
+        let last = (steps - 1).max(1) as f64;
+        let colors = (0..steps)
+            .map(|i| {
+                let (r, g, b) = interpolate(start, end, i as f64 / last, space);
+                to_hex(r, g, b)
+            })
+            .collect();
+        Ok(palette_var(colors))
+    }
+}
+
+define_function!(
+    ColorAtFn,
+    vec![ArgumentType::Array, ArgumentType::Number],
+    Some(ArgumentType::String)
+);
+
+impl Function for ColorAtFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let stops = gradient_stops(&args[0], ctx)?;
+        let t = args[1].as_number().unwrap();
+        let space = interpolation_space(args.get(2), ctx)?;
+
+        // Positions outside the stops take the nearest end color
+        let first = stops[0];
+        let last = stops[stops.len() - 1];
+        let (r, g, b) = if t <= first.0 {
+            first.1
+        } else if t >= last.0 {
+            last.1
+        } else {
+            let upper = stops.iter().position(|s| s.0 >= t).unwrap();
+            let (p1, c1) = stops[upper - 1];
+            let (p2, c2) = stops[upper];
+            interpolate(c1, c2, (t - p1) / (p2 - p1), space)
+        };
+        Ok(rcvar(Variable::String(to_hex(r, g, b))))
+    }
+}
+
 /// Register all color functions with the runtime.
 pub fn register(runtime: &mut crate::Runtime) {
     runtime.register_function("hex_to_rgb", Box::new(HexToRgbFn::new()));
@@ -426,6 +1554,28 @@ pub fn register(runtime: &mut crate::Runtime) {
     runtime.register_function("color_invert", Box::new(ColorInvertFn::new()));
     runtime.register_function("color_grayscale", Box::new(ColorGrayscaleFn::new()));
     runtime.register_function("color_complement", Box::new(ColorComplementFn::new()));
+    runtime.register_function("rgb_to_hsl", Box::new(RgbToHslFn::new()));
+    runtime.register_function("hsl_to_rgb", Box::new(HslToRgbFn::new()));
+    runtime.register_function("rgb_to_hsv", Box::new(RgbToHsvFn::new()));
+    runtime.register_function("hsv_to_rgb", Box::new(HsvToRgbFn::new()));
+    runtime.register_function("rgb_to_lab", Box::new(RgbToLabFn::new()));
+    runtime.register_function("lab_to_rgb", Box::new(LabToRgbFn::new()));
+    runtime.register_function("rgb_to_oklch", Box::new(RgbToOklchFn::new()));
+    runtime.register_function("oklch_to_rgb", Box::new(OklchToRgbFn::new()));
+    runtime.register_function("contrast_ratio", Box::new(ContrastRatioFn::new()));
+    runtime.register_function("is_wcag_aa", Box::new(IsWcagAaFn::new()));
+    runtime.register_function(
+        "accessible_text_color",
+        Box::new(AccessibleTextColorFn::new()),
+    );
+    runtime.register_function("complementary", Box::new(ComplementaryFn::new()));
+    runtime.register_function("triadic", Box::new(TriadicFn::new()));
+    runtime.register_function("analogous", Box::new(AnalogousFn::new()));
+    runtime.register_function("shades", Box::new(ShadesFn::new()));
+    runtime.register_function("parse_css_color", Box::new(ParseCssColorFn::new()));
+    runtime.register_function("color_name", Box::new(ColorNameFn::new()));
+    runtime.register_function("color_scale", Box::new(ColorScaleFn::new()));
+    runtime.register_function("color_at", Box::new(ColorAtFn::new()));
 }
 
 #[cfg(test)]
@@ -440,6 +1590,8 @@ mod tests {
         assert_eq!(parse_hex_color("#000000"), Some((0, 0, 0)));
         assert_eq!(parse_hex_color("#ffffff"), Some((255, 255, 255)));
         assert_eq!(parse_hex_color("invalid"), None);
+        assert_eq!(parse_hex_color("😀é"), None);
+        assert_eq!(parse_hex_color("#é5"), None);
     }
 
     #[test]
@@ -474,4 +1626,369 @@ mod tests {
             );
         }
     }
+
+    fn setup_runtime() -> crate::Runtime {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    fn eval(expr: &str) -> Rcvar {
+        let runtime = setup_runtime();
+        runtime
+            .compile(expr)
+            .unwrap()
+            .search(Variable::Null)
+            .unwrap()
+    }
+
+    fn component(result: &Rcvar, key: &str) -> f64 {
+        result
+            .as_object()
+            .unwrap()
+            .get(key)
+            .unwrap()
+            .as_number()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rgb_to_hsl_and_hsv() {
+        let hsl = eval("rgb_to_hsl('#ff0000')");
+        assert_eq!(component(&hsl, "h"), 0.0);
+        assert_eq!(component(&hsl, "s"), 100.0);
+        assert_eq!(component(&hsl, "l"), 50.0);
+
+        let hsv = eval("rgb_to_hsv(`{\"r\": 0, \"g\": 128, \"b\": 255}`)");
+        assert_eq!(component(&hsv, "h"), 209.88);
+        assert_eq!(component(&hsv, "s"), 100.0);
+        assert_eq!(component(&hsv, "v"), 100.0);
+
+        assert_eq!(
+            eval("hsl_to_rgb(`120`, `100`, `25`)").as_string().unwrap(),
+            "#008000"
+        );
+        assert_eq!(
+            eval("hsv_to_rgb(rgb_to_hsv('#3366cc'))")
+                .as_string()
+                .unwrap(),
+            "#3366cc"
+        );
+    }
+
+    #[test]
+    fn test_rgb_to_lab() {
+        let lab = eval("rgb_to_lab('#ff0000')");
+        assert!((component(&lab, "l") - 53.24).abs() < 0.01);
+        assert!((component(&lab, "a") - 80.09).abs() < 0.01);
+        assert!((component(&lab, "b") - 67.2).abs() < 0.01);
+
+        let white = eval("rgb_to_lab('#ffffff')");
+        assert!((component(&white, "l") - 100.0).abs() < 0.01);
+        assert!(component(&white, "a").abs() < 0.01);
+    }
+
+    #[test]
+    fn test_rgb_to_oklch() {
+        let oklch = eval("rgb_to_oklch('#ff0000')");
+        assert!((component(&oklch, "l") - 0.628).abs() < 0.001);
+        assert!((component(&oklch, "c") - 0.2577).abs() < 0.001);
+        assert!((component(&oklch, "h") - 29.23).abs() < 0.05);
+
+        let gray = eval("rgb_to_oklch('#808080')");
+        assert_eq!(component(&gray, "c"), 0.0);
+        assert_eq!(component(&gray, "h"), 0.0);
+    }
+
+    #[test]
+    fn test_color_space_roundtrips() {
+        for color in [
+            "#ff0000", "#3366cc", "#12ab34", "#000000", "#ffffff", "#808080",
+        ] {
+            for (to, from) in [
+                ("rgb_to_hsl", "hsl_to_rgb"),
+                ("rgb_to_hsv", "hsv_to_rgb"),
+                ("rgb_to_lab", "lab_to_rgb"),
+                ("rgb_to_oklch", "oklch_to_rgb"),
+            ] {
+                let expr = format!("{}({}('{}'))", from, to, color);
+                assert_eq!(eval(&expr).as_string().unwrap(), color, "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn test_color_space_invalid() {
+        assert!(eval("rgb_to_oklch('nope')").is_null());
+        for f in ["rgb_to_lab", "rgb_to_hsv", "rgb_to_oklch"] {
+            assert!(eval(&format!("{}('😀é')", f)).is_null(), "{}", f);
+        }
+        let runtime = setup_runtime();
+        for expr in [
+            "hsl_to_rgb('red')",
+            "hsl_to_rgb(`1`, `2`)",
+            "lighten('#336699', `10`, 'cmyk')",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_lighten_darken_oklch() {
+        assert_eq!(
+            eval("lighten('#000000', `100`, 'oklch')")
+                .as_string()
+                .unwrap(),
+            "#ffffff"
+        );
+        assert_eq!(
+            eval("darken('#3366cc', `100`, 'oklch')")
+                .as_string()
+                .unwrap(),
+            "#000000"
+        );
+
+        // Lightening in OKLCH keeps the hue; RGB lightening drifts toward white
+        let lighter = eval("rgb_to_oklch(lighten('#3366cc', `20`, 'oklch'))");
+        let original = eval("rgb_to_oklch('#3366cc')");
+        assert!(component(&lighter, "l") > component(&original, "l"));
+        assert!((component(&lighter, "h") - component(&original, "h")).abs() < 2.0);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert_eq!(
+            eval("contrast_ratio('#000000', '#ffffff')").as_number(),
+            Some(21.0)
+        );
+        assert_eq!(
+            eval("contrast_ratio('#777777', '#777777')").as_number(),
+            Some(1.0)
+        );
+        // Order of foreground and background does not matter
+        assert_eq!(
+            eval("contrast_ratio('#3366cc', '#ffffff')").as_number(),
+            eval("contrast_ratio('#ffffff', '#3366cc')").as_number()
+        );
+        assert!(eval("contrast_ratio('#000', 'nope')").is_null());
+    }
+
+    #[test]
+    fn test_is_wcag_aa() {
+        // #767676 on white is the classic 4.54:1 boundary case
+        assert_eq!(
+            eval("is_wcag_aa('#767676', '#ffffff')").as_boolean(),
+            Some(true)
+        );
+        assert_eq!(
+            eval("is_wcag_aa('#777777', '#ffffff')").as_boolean(),
+            Some(false)
+        );
+        assert_eq!(
+            eval("is_wcag_aa('#777777', '#ffffff', 'large')").as_boolean(),
+            Some(true)
+        );
+
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("is_wcag_aa('#000000', '#ffffff', 'huge')")
+            .unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_accessible_text_color() {
+        let pick = |bg: &str| {
+            eval(&format!("accessible_text_color('{}')", bg))
+                .as_string()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(pick("#ffff00"), "#000000");
+        assert_eq!(pick("#000080"), "#ffffff");
+        assert_eq!(pick("#3366cc"), "#ffffff");
+        assert!(eval("accessible_text_color('nope')").is_null());
+    }
+
+    fn palette(expr: &str) -> Vec<String> {
+        eval(expr)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c.as_string().unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_complementary_triadic() {
+        assert_eq!(
+            palette("complementary('#ff0000')"),
+            vec!["#ff0000", "#00ffff"]
+        );
+        assert_eq!(
+            palette("triadic('#ff0000')"),
+            vec!["#ff0000", "#00ff00", "#0000ff"]
+        );
+        assert!(eval("triadic('nope')").is_null());
+    }
+
+    #[test]
+    fn test_analogous() {
+        assert_eq!(
+            palette("analogous('#ff0000', `3`)"),
+            vec!["#ff0080", "#ff0000", "#ff8000"]
+        );
+        assert_eq!(palette("analogous('#ff0000', `1`)"), vec!["#ff0000"]);
+    }
+
+    #[test]
+    fn test_shades() {
+        let shades = palette("shades('#3366cc', `4`)");
+        assert_eq!(shades.len(), 4);
+        assert_eq!(shades[0], "#3366cc");
+        // Each step is darker than the one before
+        let lightness: Vec<f64> = shades
+            .iter()
+            .map(|c| component(&eval(&format!("rgb_to_oklch('{}')", c)), "l"))
+            .collect();
+        assert!(lightness.windows(2).all(|w| w[1] < w[0]));
+
+        let runtime = setup_runtime();
+        let expr = runtime.compile("shades('#3366cc', `0`)").unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_css_named_colors_sorted() {
+        assert!(CSS_NAMED_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_parse_css_color() {
+        let hex = |s: &str| {
+            eval(&format!("parse_css_color('{}').hex", s))
+                .as_string()
+                .unwrap()
+                .clone()
+        };
+        let alpha = |s: &str| {
+            eval(&format!("parse_css_color('{}').alpha", s))
+                .as_number()
+                .unwrap()
+        };
+
+        assert_eq!(hex("rebeccapurple"), "#663399");
+        assert_eq!(hex("RED"), "#ff0000");
+        assert_eq!(hex("#abc"), "#aabbcc");
+        assert_eq!(hex("rgb(10, 20, 30)"), "#0a141e");
+        assert_eq!(hex("rgb(100% 0% 50%)"), "#ff0080");
+        assert_eq!(hex("hsl(220, 60%, 50%)"), "#3366cc");
+        assert_eq!(hex("hsl(220deg 60% 50% / 0.5)"), "#3366cc");
+
+        assert_eq!(alpha("rgba(10,20,30,.5)"), 0.5);
+        assert_eq!(alpha("rgb(10 20 30 / 25%)"), 0.25);
+        assert_eq!(alpha("#ff000080"), 0.502);
+        assert_eq!(alpha("transparent"), 0.0);
+        assert_eq!(alpha("navy"), 1.0);
+    }
+
+    #[test]
+    fn test_parse_css_color_invalid() {
+        for input in [
+            "notacolor",
+            "rgb(1, 2)",
+            "hsl(a, b, c)",
+            "#12345",
+            "#😀",
+            "cmyk(1,2,3,4)",
+        ] {
+            assert!(
+                eval(&format!("parse_css_color('{}')", input)).is_null(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_color_name() {
+        let name = |s: &str| {
+            eval(&format!("color_name('{}')", s))
+                .as_string()
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(name("#663399"), "rebeccapurple");
+        assert_eq!(name("#808080"), "gray");
+        assert_eq!(name("#fe0101"), "red");
+        assert_eq!(name("#3366cc"), "royalblue");
+        assert!(eval("color_name('nope')").is_null());
+    }
+
+    #[test]
+    fn test_color_scale() {
+        assert_eq!(
+            palette("color_scale('#000000', '#ffffff', `3`)"),
+            vec!["#000000", "#808080", "#ffffff"]
+        );
+        assert_eq!(
+            palette("color_scale('#ff0000', '#0000ff', `1`)"),
+            vec!["#ff0000"]
+        );
+
+        // Every space hits both endpoints exactly
+        for space in ["rgb", "lab", "oklch"] {
+            let scale = palette(&format!(
+                "color_scale('#ff0000', '#0000ff', `5`, '{}')",
+                space
+            ));
+            assert_eq!(scale.len(), 5);
+            assert_eq!(scale[0], "#ff0000", "{}", space);
+            assert_eq!(scale[4], "#0000ff", "{}", space);
+        }
+
+        // OKLCH keeps the midpoint saturated where RGB goes muddy
+        let rgb_mid = eval("rgb_to_oklch(color_scale('#ff0000', '#0000ff', `3`)[1]).c");
+        let oklch_mid = eval("rgb_to_oklch(color_scale('#ff0000', '#0000ff', `3`, 'oklch')[1]).c");
+        assert!(oklch_mid.as_number().unwrap() > rgb_mid.as_number().unwrap());
+    }
+
+    #[test]
+    fn test_color_at() {
+        let at = |expr: &str| eval(expr).as_string().unwrap().clone();
+        assert_eq!(
+            at(r##"color_at(`["#000000", "#ffffff"]`, `0.5`)"##),
+            "#808080"
+        );
+        assert_eq!(
+            at(r##"color_at(`["#ff0000", "#00ff00", "#0000ff"]`, `0.5`)"##),
+            "#00ff00"
+        );
+        assert_eq!(
+            at(r##"color_at(`["#000000", "#ffffff"]`, `-1`)"##),
+            "#000000"
+        );
+        assert_eq!(
+            at(r##"color_at(`["#000000", "#ffffff"]`, `2`)"##),
+            "#ffffff"
+        );
+        assert_eq!(
+            at(
+                r##"color_at(`[{"position": 0, "color": "#000000"}, {"position": 0.2, "color": "#ffffff"}]`, `0.1`)"##
+            ),
+            "#808080"
+        );
+
+        let runtime = setup_runtime();
+        for expr in [
+            "color_at(`[]`, `0.5`)",
+            r#"color_at(`["nope"]`, `0.5`)"#,
+            r##"color_at(`["#000000"]`, `0.5`, 'cmyk')"##,
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
 }
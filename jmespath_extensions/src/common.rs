@@ -30,12 +30,19 @@
 //! }
 //! ```
 
-use std::rc::Rc;
-
 pub use jmespath::RuntimeError;
 pub use jmespath::functions::{ArgumentType, Function, Signature};
 pub use jmespath::{Context, ErrorReason, JmespathError, Rcvar, Runtime, Variable};
 
+/// The reference-counted pointer behind [`Rcvar`].
+///
+/// This is `std::rc::Rc` by default and `std::sync::Arc` with the `sync`
+/// feature, so functions written against `Rc::new` build either way.
+#[cfg(not(feature = "sync"))]
+pub use std::rc::Rc;
+#[cfg(feature = "sync")]
+pub use std::sync::Arc as Rc;
+
 /// Creates a JmespathError for an invalid argument type.
 ///
 /// This produces a structured `RuntimeError::InvalidType` error which provides
@@ -84,6 +91,45 @@ pub fn custom_error(ctx: &Context<'_>, message: &str) -> JmespathError {
     JmespathError::from_ctx(ctx, ErrorReason::Parse(message.to_owned()))
 }
 
+/// Largest integer an f64 can represent without gaps (2^53).
+#[cfg(any(
+    feature = "array",
+    feature = "math",
+    feature = "computing",
+    feature = "network"
+))]
+pub(crate) const MAX_SAFE_F64_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Extract an integer operand, rejecting fractions and floats that already lost precision.
+#[cfg(any(
+    feature = "array",
+    feature = "math",
+    feature = "computing",
+    feature = "network"
+))]
+pub(crate) fn exact_integer(value: &Variable) -> Result<i64, String> {
+    let Variable::Number(n) = value else {
+        return Err("expected integer operand".to_owned());
+    };
+    if let Some(i) = n.as_i64() {
+        return Ok(i);
+    }
+    if n.is_u64() {
+        return Err(format!("operand {} does not fit in a 64-bit integer", n));
+    }
+    match n.as_f64() {
+        Some(f) if f.fract() == 0.0 && f.abs() <= MAX_SAFE_F64_INTEGER => Ok(f as i64),
+        Some(f) if f.fract() == 0.0 => Err(format!("operand {} is not an exact integer", n)),
+        _ => Err(format!("operand {} is not an integer", n)),
+    }
+}
+
+/// Read an argument that must be a whole number, via [`exact_integer`].
+#[cfg(any(feature = "math", feature = "computing"))]
+pub(crate) fn integer_arg(value: &Rcvar, ctx: &Context<'_>) -> Result<i64, JmespathError> {
+    exact_integer(value).map_err(|_| custom_error(ctx, "Expected integer argument"))
+}
+
 /// Helper macro for defining JMESPath custom functions.
 ///
 /// This macro creates a struct with a signature field and implements
@@ -111,6 +157,437 @@ macro_rules! define_function {
     };
 }
 
+/// Define a custom extension function together with its registry metadata.
+///
+/// Generates a struct implementing [`Function`] that validates its
+/// arguments against the declared signature before running the body, plus:
+///
+/// - `NAME`: the name the function is called by in expressions
+/// - `info()`: a [`FunctionInfo`](crate::registry::FunctionInfo) in
+///   [`Category::Custom`](crate::registry::Category::Custom), for
+///   [`FunctionRegistry::register_custom`](crate::registry::FunctionRegistry::register_custom)
+/// - `register(runtime)`: registers the function on a runtime under `NAME`
+///
+/// Registering the metadata makes the function show up wherever the registry
+/// is used to list, describe or complete functions.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath::{Runtime, Variable};
+/// use jmespath_extensions::common::{ArgumentType, Rc};
+/// use jmespath_extensions::define_extension_fn;
+/// use jmespath_extensions::registry::FunctionRegistry;
+///
+/// define_extension_fn! {
+///     /// Uppercase a string and add an exclamation mark.
+///     pub struct ShoutFn {
+///         name: "shout",
+///         args: [ArgumentType::String],
+///         variadic: None,
+///         signature: "string -> string",
+///         description: "Uppercase a string and add an exclamation mark",
+///         example: "shout('hi') -> 'HI!'",
+///     }
+///     fn evaluate(args, ctx) {
+///         let s = args[0].as_string().unwrap();
+///         Ok(Rc::new(Variable::String(format!("{}!", s.to_uppercase()))))
+///     }
+/// }
+///
+/// let mut runtime = Runtime::new();
+/// runtime.register_builtin_functions();
+/// ShoutFn::register(&mut runtime);
+///
+/// let mut registry = FunctionRegistry::new();
+/// registry.register_all();
+/// registry.register_custom(ShoutFn::info());
+/// assert!(registry.get_function("shout").is_some());
+///
+/// let expr = runtime.compile("shout('hi')").unwrap();
+/// let result = expr.search(&Variable::Null).unwrap();
+/// assert_eq!(result.as_string().unwrap(), "HI!");
+/// ```
+#[macro_export]
+macro_rules! define_extension_fn {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            name: $fname:literal,
+            args: [$($arg:expr),* $(,)?],
+            variadic: $variadic:expr,
+            signature: $sig:literal,
+            description: $desc:literal,
+            example: $example:literal $(,)?
+        }
+        fn evaluate($args:ident, $ctx:ident) $body:block
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            signature: $crate::common::Signature,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl $name {
+            /// Name the function is called by in expressions.
+            pub const NAME: &'static str = $fname;
+
+            pub fn new() -> $name {
+                $name {
+                    signature: $crate::common::Signature::new(vec![$($arg),*], $variadic),
+                }
+            }
+
+            /// Registry metadata for this function.
+            pub fn info() -> $crate::registry::FunctionInfo {
+                $crate::registry::FunctionInfo {
+                    name: $fname,
+                    category: $crate::registry::Category::Custom,
+                    description: $desc,
+                    signature: $sig,
+                    example: $example,
+                    is_standard: false,
+                    jep: None,
+                    aliases: &[],
+                    features: &[],
+                }
+            }
+
+            /// Register this function on `runtime` under its name.
+            pub fn register(runtime: &mut $crate::common::Runtime) {
+                runtime.register_function($fname, Box::new(Self::new()));
+            }
+        }
+
+        impl $crate::common::Function for $name {
+            fn evaluate(
+                &self,
+                $args: &[$crate::common::Rcvar],
+                $ctx: &mut $crate::common::Context<'_>,
+            ) -> Result<$crate::common::Rcvar, $crate::common::JmespathError> {
+                self.signature.validate($args, $ctx)?;
+                $body
+            }
+        }
+    };
+}
+
+/// A small least-recently-used cache keyed by string, for per-thread caches
+/// of compiled expressions and patterns.
+///
+/// Eviction scans for the oldest entry, which is cheap at the capacities
+/// these caches use and avoids a dependency.
+#[cfg(any(feature = "expression", feature = "regex"))]
+pub(crate) struct LruCache<V> {
+    capacity: usize,
+    clock: u64,
+    entries: std::collections::HashMap<String, (V, u64)>,
+}
+
+#[cfg(any(feature = "expression", feature = "regex"))]
+impl<V: Clone> LruCache<V> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            entries: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Look up `key`, marking it as most recently used.
+    pub(crate) fn get(&mut self, key: &str) -> Option<V> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|(value, used)| {
+            *used = clock;
+            value.clone()
+        })
+    }
+
+    /// Insert `value`, evicting the least recently used entry when full.
+    pub(crate) fn insert(&mut self, key: &str, value: V) {
+        self.clock += 1;
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(key) {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key.to_string(), (value, self.clock));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+}
+
+#[cfg(any(feature = "expression", feature = "rand"))]
+thread_local! {
+    /// Searches started by extension functions that are still running.
+    static NESTED_SEARCHES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(feature = "expression")]
+thread_local! {
+    /// The top-level search that per-search state currently belongs to.
+    static TOP_LEVEL_SEARCH: std::cell::RefCell<Option<SearchIdentity>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Identifies a top-level search by its context and query text.
+#[cfg(feature = "expression")]
+#[derive(PartialEq, Eq)]
+struct SearchIdentity {
+    context: usize,
+    expression: usize,
+    query: String,
+}
+
+/// Restores the nested search count when dropped, even on error.
+#[cfg(any(feature = "expression", feature = "rand"))]
+struct NestedSearchGuard;
+
+#[cfg(any(feature = "expression", feature = "rand"))]
+impl Drop for NestedSearchGuard {
+    fn drop(&mut self) {
+        NESTED_SEARCHES.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// An expression evaluated by an extension function on behalf of the
+/// current search.
+///
+/// Searching it counts as nested, so thread-local state that is scoped to
+/// the top-level search (`define` helpers, the `memoize_expr` cache) is not
+/// discarded by the inner evaluation.
+#[cfg(any(feature = "expression", feature = "rand"))]
+pub(crate) struct NestedExpression<'a>(jmespath::Expression<'a>);
+
+#[cfg(any(feature = "expression", feature = "rand"))]
+impl<'a> NestedExpression<'a> {
+    pub(crate) fn new(expression: jmespath::Expression<'a>) -> Self {
+        Self(expression)
+    }
+
+    pub(crate) fn search<T: jmespath::ToJmespath>(&self, data: T) -> Result<Rcvar, JmespathError> {
+        crate::limits::check_nested_deadline(&self.0)?;
+        NESTED_SEARCHES.with(|depth| depth.set(depth.get() + 1));
+        let _guard = NestedSearchGuard;
+        self.0.search(data)
+    }
+}
+
+#[cfg(any(feature = "expression", feature = "rand"))]
+impl<'a> std::ops::Deref for NestedExpression<'a> {
+    type Target = jmespath::Expression<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Whether `ctx` belongs to a different top-level search than the previous
+/// call on this thread.
+///
+/// `Context` is created by the upstream `jmespath` crate for every search
+/// and cannot signal when one finishes, so per-search state is instead
+/// discarded by the first call made from the next top-level search. Calls
+/// made while a [`NestedExpression`] is being searched never start one.
+#[cfg(feature = "expression")]
+pub(crate) fn starts_new_search(ctx: &Context<'_>) -> bool {
+    if NESTED_SEARCHES.with(|depth| depth.get()) > 0 {
+        return false;
+    }
+    let context = ctx as *const Context<'_> as usize;
+    let expression = ctx.expression.as_ptr() as usize;
+    TOP_LEVEL_SEARCH.with(|current| {
+        let mut current = current.borrow_mut();
+        if let Some(identity) = current.as_ref()
+            && identity.context == context
+            && identity.expression == expression
+            && identity.query == ctx.expression
+        {
+            return false;
+        }
+        *current = Some(SearchIdentity {
+            context,
+            expression,
+            query: ctx.expression.to_string(),
+        });
+        true
+    })
+}
+
+/// A source of the current time, installed with [`set_clock_provider`].
+pub type ClockProvider = std::sync::Arc<dyn Fn() -> std::time::SystemTime + Send + Sync>;
+
+static CLOCK_PROVIDER: std::sync::RwLock<Option<ClockProvider>> = std::sync::RwLock::new(None);
+
+/// Read the current time from `provider` instead of the system clock.
+///
+/// This pins `now()`, `now_ms()`, `now_millis()` and the functions that
+/// compare against the current time, so tests and reproducible pipelines get
+/// the same answer on every run. The provider is process-wide and applies to
+/// every runtime.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use jmespath_extensions::common::{clear_clock_provider, set_clock_provider};
+///
+/// set_clock_provider(|| UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+/// // ... evaluate queries ...
+/// clear_clock_provider();
+/// ```
+pub fn set_clock_provider<F>(provider: F)
+where
+    F: Fn() -> std::time::SystemTime + Send + Sync + 'static,
+{
+    *CLOCK_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) =
+        Some(std::sync::Arc::new(provider));
+}
+
+/// Restore reading the current time from the system clock.
+pub fn clear_clock_provider() {
+    *CLOCK_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The current time, from the installed clock provider or the system clock.
+pub fn now() -> std::time::SystemTime {
+    // Clone the provider out so a provider that reads the clock cannot deadlock
+    let provider = CLOCK_PROVIDER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    match provider {
+        Some(provider) => provider(),
+        None => std::time::SystemTime::now(),
+    }
+}
+
+/// The random number generator used by the random and id functions.
+///
+/// The default source draws from the thread RNG. A seeded source is a single
+/// generator shared by every function it is handed to, so a pipeline that
+/// calls `random()`, `shuffle()` and `nanoid()` in the same order produces the
+/// same values on every run.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath_extensions::common::RngSource;
+///
+/// let draw = |source: RngSource| source.with(|rng| rng.next_u64());
+/// assert_eq!(draw(RngSource::seeded(42)), draw(RngSource::seeded(42)));
+/// ```
+#[cfg(any(feature = "rand", feature = "ids"))]
+#[derive(Clone, Default)]
+pub struct RngSource {
+    seeded: Option<std::sync::Arc<std::sync::Mutex<rand::rngs::StdRng>>>,
+}
+
+#[cfg(any(feature = "rand", feature = "ids"))]
+impl RngSource {
+    /// A deterministic source seeded with `seed`.
+    pub fn seeded(seed: u64) -> Self {
+        use rand::SeedableRng;
+
+        Self {
+            seeded: Some(std::sync::Arc::new(std::sync::Mutex::new(
+                rand::rngs::StdRng::seed_from_u64(seed),
+            ))),
+        }
+    }
+
+    /// Whether draws come from a seeded generator, either this source's own
+    /// or the one installed with [`set_rng_provider`].
+    #[cfg(feature = "ids")]
+    pub(crate) fn is_seeded(&self) -> bool {
+        self.seeded.is_some()
+            || RNG_PROVIDER
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .as_ref()
+                .is_some_and(|source| source.seeded.is_some())
+    }
+
+    /// Run `f` with this source's generator.
+    ///
+    /// The default source uses the generator installed with
+    /// [`set_rng_provider`], falling back to the thread RNG.
+    pub fn with<T>(&self, f: impl FnOnce(&mut dyn rand::RngCore) -> T) -> T {
+        let provided;
+        let seeded = match &self.seeded {
+            Some(rng) => Some(rng),
+            None => {
+                provided = RNG_PROVIDER
+                    .read()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .as_ref()
+                    .and_then(|source| source.seeded.clone());
+                provided.as_ref()
+            }
+        };
+        match seeded {
+            // A panic mid-draw cannot leave the generator in an invalid state
+            Some(rng) => f(&mut *rng.lock().unwrap_or_else(|e| e.into_inner())),
+            None => f(&mut rand::thread_rng()),
+        }
+    }
+}
+
+#[cfg(any(feature = "rand", feature = "ids"))]
+static RNG_PROVIDER: std::sync::RwLock<Option<RngSource>> = std::sync::RwLock::new(None);
+
+/// Draw from `source` in every random function that was registered without
+/// its own source.
+///
+/// This pins `random()`, `shuffle()`, `uuid()`, `nanoid()` and the other
+/// functions registered with the default source, without rebuilding the
+/// runtime. Functions registered with an explicit source and calls with an
+/// explicit seed argument are unaffected. The provider is process-wide and applies to
+/// every runtime.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath_extensions::common::{RngSource, clear_rng_provider, set_rng_provider};
+///
+/// set_rng_provider(RngSource::seeded(42));
+/// // ... evaluate queries ...
+/// clear_rng_provider();
+/// ```
+#[cfg(any(feature = "rand", feature = "ids"))]
+pub fn set_rng_provider(source: RngSource) {
+    *RNG_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = Some(source);
+}
+
+/// Restore drawing from the thread RNG in functions without their own source.
+#[cfg(any(feature = "rand", feature = "ids"))]
+pub fn clear_rng_provider() {
+    *RNG_PROVIDER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
 /// Helper to create an Rcvar from a Variable
 #[inline]
 pub fn rcvar(v: Variable) -> Rcvar {
@@ -17,9 +17,11 @@
 //! ```
 
 use crate::common::{
-    ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable, rcvar,
+    ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable, integer_arg,
+    rcvar,
 };
 use crate::define_function;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64_STANDARD};
 
 // Decimal units (SI): KB, MB, GB, TB, PB
 const DECIMAL_UNITS: &[(&str, f64)] = &[
@@ -232,13 +234,7 @@ impl Function for BitShiftLeftFn {
             )
         })? as i64;
 
-        let n = args[1].as_number().ok_or_else(|| {
-            JmespathError::new(
-                ctx.expression,
-                0,
-                ErrorReason::Parse("Expected non-negative integer".to_owned()),
-            )
-        })? as u32;
+        let n = bit_position_arg(&args[1], ctx)?;
 
         Ok(rcvar(Variable::Number(serde_json::Number::from(a << n))))
     }
@@ -262,20 +258,561 @@ impl Function for BitShiftRightFn {
             )
         })? as i64;
 
-        let n = args[1].as_number().ok_or_else(|| {
+        let n = bit_position_arg(&args[1], ctx)?;
+
+        Ok(rcvar(Variable::Number(serde_json::Number::from(a >> n))))
+    }
+}
+
+define_function!(
+    BitRotateFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    Some(ArgumentType::Number)
+);
+
+impl Function for BitRotateFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let width = match args.get(2) {
+            Some(w) => match w.as_number() {
+                Some(w) if [8.0, 16.0, 32.0, 64.0].contains(&w) => w as u32,
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse("Width must be 8, 16, 32 or 64".to_owned()),
+                    ));
+                }
+            },
+            None => 32,
+        };
+        let mask = if width == 64 {
+            u64::MAX
+        } else {
+            (1u64 << width) - 1
+        };
+
+        let n = integer_arg(&args[0], ctx)?;
+        if n < 0 || n as u64 > mask {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!("Value must fit in {} unsigned bits", width)),
+            ));
+        }
+        // Positive amounts rotate left, negative amounts rotate right
+        let k = integer_arg(&args[1], ctx)?.rem_euclid(width as i64) as u32;
+        let n = n as u64;
+        let rotated = if k == 0 {
+            n
+        } else {
+            ((n << k) | (n >> (width - k))) & mask
+        };
+
+        Ok(rcvar(Variable::Number(serde_json::Number::from(rotated))))
+    }
+}
+
+define_function!(PopcountFn, vec![ArgumentType::Number], None);
+
+impl Function for PopcountFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = integer_arg(&args[0], ctx)?;
+
+        Ok(rcvar(Variable::Number(serde_json::Number::from(
+            n.count_ones(),
+        ))))
+    }
+}
+
+define_function!(
+    BitTestFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for BitTestFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = integer_arg(&args[0], ctx)?;
+        let pos = bit_position_arg(&args[1], ctx)?;
+
+        Ok(rcvar(Variable::Bool((n >> pos) & 1 == 1)))
+    }
+}
+
+define_function!(
+    BitSetFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for BitSetFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = integer_arg(&args[0], ctx)?;
+        let pos = bit_position_arg(&args[1], ctx)?;
+
+        Ok(rcvar(Variable::Number(serde_json::Number::from(
+            n | (1i64 << pos),
+        ))))
+    }
+}
+
+define_function!(
+    BitClearFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for BitClearFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = integer_arg(&args[0], ctx)?;
+        let pos = bit_position_arg(&args[1], ctx)?;
+
+        Ok(rcvar(Variable::Number(serde_json::Number::from(
+            n & !(1i64 << pos),
+        ))))
+    }
+}
+
+define_function!(
+    SwapEndianFn,
+    vec![ArgumentType::String, ArgumentType::Number],
+    None
+);
+
+impl Function for SwapEndianFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let width = byte_width_arg(&args[1], 1, 64, ctx)?;
+        let bytes = match decode_hex_arg(args[0].as_string().unwrap()) {
+            Some(bytes) if bytes.len() % width == 0 => bytes,
+            _ => return Ok(rcvar(Variable::Null)),
+        };
+
+        // Reverse the byte order of each width-sized word
+        let swapped: Vec<u8> = bytes
+            .chunks(width)
+            .flat_map(|word| word.iter().rev().copied())
+            .collect();
+        Ok(rcvar(Variable::String(hex::encode(swapped))))
+    }
+}
+
+define_function!(
+    HexToIntFn,
+    vec![ArgumentType::String],
+    Some(ArgumentType::String)
+);
+
+impl Function for HexToIntFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let little = little_endian_arg(args.get(1), ctx)?;
+        let mut bytes = match decode_hex_arg(args[0].as_string().unwrap()) {
+            Some(bytes) if !bytes.is_empty() && bytes.len() <= 8 => bytes,
+            _ => return Ok(rcvar(Variable::Null)),
+        };
+        if little {
+            bytes.reverse();
+        }
+
+        let value = bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+        Ok(rcvar(Variable::Number(serde_json::Number::from(value))))
+    }
+}
+
+define_function!(
+    IntToHexFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    Some(ArgumentType::String)
+);
+
+impl Function for IntToHexFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = integer_arg(&args[0], ctx)?;
+        let width = byte_width_arg(&args[1], 1, 8, ctx)?;
+        let little = little_endian_arg(args.get(2), ctx)?;
+
+        // Accept anything representable in `width` bytes, signed or unsigned;
+        // negative values are written in two's complement
+        let bits = width as u32 * 8;
+        let fits = if bits == 64 {
+            true
+        } else {
+            n >= -(1i64 << (bits - 1)) && n < (1i64 << bits)
+        };
+        if !fits {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!("{} does not fit in {} bytes", n, width)),
+            ));
+        }
+
+        let mut bytes = n.to_be_bytes()[8 - width..].to_vec();
+        if little {
+            bytes.reverse();
+        }
+        Ok(rcvar(Variable::String(hex::encode(bytes))))
+    }
+}
+
+define_function!(HexdumpFn, vec![ArgumentType::String], None);
+
+impl Function for HexdumpFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match BASE64_STANDARD.decode(args[0].as_string().unwrap().trim()) {
+            Ok(bytes) => Ok(rcvar(Variable::String(format_hexdump(&bytes)))),
+            Err(_) => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(ChmodToSymbolicFn, vec![ArgumentType::Any], None);
+
+impl Function for ChmodToSymbolicFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match parse_octal_mode(&args[0]) {
+            Some(mode) => Ok(rcvar(Variable::String(mode_to_symbolic(mode)))),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(SymbolicToChmodFn, vec![ArgumentType::String], None);
+
+impl Function for SymbolicToChmodFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match symbolic_to_mode(args[0].as_string().unwrap()) {
+            Some(mode) => Ok(rcvar(Variable::String(format!("{:03o}", mode)))),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    UmaskApplyFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+impl Function for UmaskApplyFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match (parse_octal_mode(&args[0]), parse_octal_mode(&args[1])) {
+            (Some(mode), Some(umask)) => {
+                Ok(rcvar(Variable::String(format!("{:03o}", mode & !umask))))
+            }
+            _ => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+define_function!(
+    ToBaseFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for ToBaseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = args[0]
+            .as_number()
+            .filter(|n| n.fract() == 0.0 && n.abs() <= i64::MAX as f64)
+            .ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("Expected integer".to_owned()),
+                )
+            })? as i64;
+
+        let base = parse_base_arg(&args[1], ctx)?;
+
+        Ok(rcvar(Variable::String(format_radix(n, base))))
+    }
+}
+
+define_function!(
+    FromBaseFn,
+    vec![ArgumentType::String, ArgumentType::Number],
+    None
+);
+
+impl Function for FromBaseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().ok_or_else(|| {
             JmespathError::new(
                 ctx.expression,
                 0,
-                ErrorReason::Parse("Expected non-negative integer".to_owned()),
+                ErrorReason::Parse("Expected string".to_owned()),
             )
-        })? as u32;
+        })?;
 
-        Ok(rcvar(Variable::Number(serde_json::Number::from(a >> n))))
+        let base = parse_base_arg(&args[1], ctx)?;
+
+        match parse_radix(s, base) {
+            Some(n) => Ok(rcvar(Variable::Number(serde_json::Number::from(n)))),
+            None => Ok(rcvar(Variable::Null)),
+        }
     }
 }
 
 // Helper functions
 
+/// Decode a hex string, ignoring an optional `0x` prefix and whitespace.
+fn decode_hex_arg(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    let digits: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    hex::decode(digits).ok()
+}
+
+/// Read a byte width argument within `min..=max`.
+fn byte_width_arg(
+    value: &Rcvar,
+    min: usize,
+    max: usize,
+    ctx: &Context<'_>,
+) -> Result<usize, JmespathError> {
+    value
+        .as_number()
+        .filter(|n| n.fract() == 0.0 && (min as f64..=max as f64).contains(n))
+        .map(|n| n as usize)
+        .ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "Width must be a number of bytes between {} and {}",
+                    min, max
+                )),
+            )
+        })
+}
+
+/// Parse an optional endianness argument ("big", the default, or "little").
+fn little_endian_arg(value: Option<&Rcvar>, ctx: &Context<'_>) -> Result<bool, JmespathError> {
+    match value.and_then(|v| v.as_string()).map(|s| s.as_str()) {
+        None | Some("big") => Ok(false),
+        Some("little") => Ok(true),
+        Some(other) => Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "Unknown endianness '{}': expected big or little",
+                other
+            )),
+        )),
+    }
+}
+
+/// Format bytes like `hexdump -C`: offset, 16 hex bytes in two groups of
+/// eight, and the printable ASCII column.
+fn format_hexdump(bytes: &[u8]) -> String {
+    let mut lines = Vec::new();
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let mut hex_col = String::new();
+        for j in 0..16 {
+            match chunk.get(j) {
+                Some(b) => hex_col.push_str(&format!("{:02x} ", b)),
+                None => hex_col.push_str("   "),
+            }
+            if j == 7 {
+                hex_col.push(' ');
+            }
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        lines.push(format!("{:08x}  {} |{}|", i * 16, hex_col, ascii));
+    }
+    lines.join("\n")
+}
+
+/// Read a permission mode written in octal digits, given either as a number
+/// (`755`) or a string (`"0755"`, `"0o755"`). Up to four digits, so the
+/// setuid, setgid and sticky bits can be included.
+fn parse_octal_mode(value: &Variable) -> Option<u32> {
+    let text = match value {
+        Variable::Number(n) => n.as_u64()?.to_string(),
+        Variable::String(s) => s.trim().to_string(),
+        _ => return None,
+    };
+    let digits = text.strip_prefix("0o").unwrap_or(&text);
+    let digits = if digits.len() > 1 {
+        digits.trim_start_matches('0')
+    } else {
+        digits
+    };
+    if digits.len() > 4 {
+        return None;
+    }
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u32::from_str_radix(digits, 8).ok()
+}
+
+/// Render a mode as `rwxr-xr-x`, using s/S and t/T for the special bits.
+fn mode_to_symbolic(mode: u32) -> String {
+    let mut out = String::with_capacity(9);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        out.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    out
+}
+
+/// Parse `rwxr-xr-x` (optionally prefixed with a file type such as `d` or `-`,
+/// as printed by `ls -l`) back into a mode.
+fn symbolic_to_mode(s: &str) -> Option<u32> {
+    let chars: Vec<char> = s.trim().chars().collect();
+    let perms = match chars.len() {
+        9 => &chars[..],
+        10 => &chars[1..],
+        _ => return None,
+    };
+
+    let mut mode = 0;
+    for (i, triple) in perms.chunks(3).enumerate() {
+        let shift = 6 - 3 * i as u32;
+        let special = 0o4000 >> i;
+        let special_char = if i == 2 { 't' } else { 's' };
+        match triple[0] {
+            'r' => mode |= 0o4 << shift,
+            '-' => {}
+            _ => return None,
+        }
+        match triple[1] {
+            'w' => mode |= 0o2 << shift,
+            '-' => {}
+            _ => return None,
+        }
+        match triple[2] {
+            'x' => mode |= 0o1 << shift,
+            '-' => {}
+            c if c == special_char => mode |= special | (0o1 << shift),
+            c if c == special_char.to_ascii_uppercase() => mode |= special,
+            _ => return None,
+        }
+    }
+    Some(mode)
+}
+
+/// Read a bit position or shift amount (0 to 63 inclusive).
+fn bit_position_arg(value: &Rcvar, ctx: &Context<'_>) -> Result<u32, JmespathError> {
+    value
+        .as_number()
+        .filter(|n| n.fract() == 0.0 && (0.0..=63.0).contains(n))
+        .map(|n| n as u32)
+        .ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected bit position between 0 and 63".to_owned()),
+            )
+        })
+}
+
+/// Validate a radix argument (2 to 36 inclusive).
+fn parse_base_arg(value: &Rcvar, ctx: &Context<'_>) -> Result<u32, JmespathError> {
+    value
+        .as_number()
+        .filter(|b| b.fract() == 0.0 && (2.0..=36.0).contains(b))
+        .map(|b| b as u32)
+        .ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Base must be an integer from 2 to 36".to_owned()),
+            )
+        })
+}
+
+/// Format an integer in the given base using lowercase digits.
+fn format_radix(n: i64, base: u32) -> String {
+    if n == 0 {
+        return "0".to_string();
+    }
+    let mut magnitude = n.unsigned_abs();
+    let mut digits = Vec::new();
+    while magnitude > 0 {
+        let digit = (magnitude % base as u64) as u32;
+        digits.push(std::char::from_digit(digit, base).unwrap());
+        magnitude /= base as u64;
+    }
+    if n < 0 {
+        digits.push('-');
+    }
+    digits.iter().rev().collect()
+}
+
+/// Parse an integer in the given base, accepting an optional sign and the
+/// conventional `0x`/`0o`/`0b` prefix when it matches the base.
+fn parse_radix(s: &str, base: u32) -> Option<i64> {
+    let s = s.trim();
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let prefix = match base {
+        16 => Some(["0x", "0X"]),
+        8 => Some(["0o", "0O"]),
+        2 => Some(["0b", "0B"]),
+        _ => None,
+    };
+    let digits = prefix
+        .and_then(|p| s.strip_prefix(p[0]).or_else(|| s.strip_prefix(p[1])))
+        .unwrap_or(s);
+    if digits.is_empty() || digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let value = i64::from_str_radix(digits, base).ok()?;
+    Some(if negative { -value } else { value })
+}
+
 /// Parse a byte string like "1.5 GB" or "100 MiB" into bytes.
 fn parse_bytes_str(s: &str) -> Option<f64> {
     let s = s.trim();
@@ -356,6 +893,20 @@ pub fn register(runtime: &mut crate::Runtime) {
     runtime.register_function("bit_not", Box::new(BitNotFn::new()));
     runtime.register_function("bit_shift_left", Box::new(BitShiftLeftFn::new()));
     runtime.register_function("bit_shift_right", Box::new(BitShiftRightFn::new()));
+    runtime.register_function("bit_rotate", Box::new(BitRotateFn::new()));
+    runtime.register_function("popcount", Box::new(PopcountFn::new()));
+    runtime.register_function("bit_test", Box::new(BitTestFn::new()));
+    runtime.register_function("bit_set", Box::new(BitSetFn::new()));
+    runtime.register_function("bit_clear", Box::new(BitClearFn::new()));
+    runtime.register_function("swap_endian", Box::new(SwapEndianFn::new()));
+    runtime.register_function("hex_to_int", Box::new(HexToIntFn::new()));
+    runtime.register_function("int_to_hex", Box::new(IntToHexFn::new()));
+    runtime.register_function("hexdump", Box::new(HexdumpFn::new()));
+    runtime.register_function("chmod_to_symbolic", Box::new(ChmodToSymbolicFn::new()));
+    runtime.register_function("symbolic_to_chmod", Box::new(SymbolicToChmodFn::new()));
+    runtime.register_function("umask_apply", Box::new(UmaskApplyFn::new()));
+    runtime.register_function("to_base", Box::new(ToBaseFn::new()));
+    runtime.register_function("from_base", Box::new(FromBaseFn::new()));
 }
 
 #[cfg(test)]
@@ -400,4 +951,255 @@ mod tests {
             "1 GiB"
         );
     }
+
+    #[test]
+    fn test_format_radix() {
+        assert_eq!(format_radix(255, 16), "ff");
+        assert_eq!(format_radix(5, 2), "101");
+        assert_eq!(format_radix(-8, 8), "-10");
+        assert_eq!(format_radix(0, 36), "0");
+        assert_eq!(format_radix(35, 36), "z");
+        assert_eq!(format_radix(i64::MIN, 2).len(), 65);
+    }
+
+    #[test]
+    fn test_parse_radix() {
+        assert_eq!(parse_radix("ff", 16), Some(255));
+        assert_eq!(parse_radix("0xFF", 16), Some(255));
+        assert_eq!(parse_radix("0b101", 2), Some(5));
+        assert_eq!(parse_radix("0o17", 8), Some(15));
+        assert_eq!(parse_radix("-z", 36), Some(-35));
+        assert_eq!(parse_radix("12", 2), None);
+        assert_eq!(parse_radix("0x", 16), None);
+        assert_eq!(parse_radix("--1", 10), None);
+    }
+
+    #[test]
+    fn test_to_base_from_base_functions() {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+
+        let expr = runtime
+            .compile("from_base(to_base(`1234`, `36`), `36`)")
+            .unwrap();
+        let result = expr.search(Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1234.0);
+
+        let expr = runtime.compile("from_base('xyz', `16`)").unwrap();
+        assert!(expr.search(Variable::Null).unwrap().is_null());
+
+        let expr = runtime.compile("to_base(`10`, `37`)").unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+
+        let expr = runtime.compile("to_base(`1.5`, `2`)").unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+    }
+
+    fn eval_number(runtime: &crate::Runtime, expr: &str) -> f64 {
+        let compiled = runtime.compile(expr).unwrap();
+        compiled
+            .search(Variable::Null)
+            .unwrap()
+            .as_number()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bit_flags() {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+
+        assert_eq!(eval_number(&runtime, "popcount(`255`)"), 8.0);
+        assert_eq!(eval_number(&runtime, "popcount(`0`)"), 0.0);
+        assert_eq!(eval_number(&runtime, "popcount(`-1`)"), 64.0);
+        assert_eq!(eval_number(&runtime, "bit_set(`8`, `0`)"), 9.0);
+        assert_eq!(eval_number(&runtime, "bit_clear(`9`, `3`)"), 1.0);
+        assert_eq!(eval_number(&runtime, "bit_clear(`9`, `2`)"), 9.0);
+
+        let expr = runtime.compile("bit_test(`5`, `2`)").unwrap();
+        assert!(expr.search(Variable::Null).unwrap().as_boolean().unwrap());
+        let expr = runtime.compile("bit_test(`5`, `1`)").unwrap();
+        assert!(!expr.search(Variable::Null).unwrap().as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_bit_rotate() {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+
+        assert_eq!(eval_number(&runtime, "bit_rotate(`129`, `1`, `8`)"), 3.0);
+        assert_eq!(eval_number(&runtime, "bit_rotate(`3`, `-1`, `8`)"), 129.0);
+        assert_eq!(eval_number(&runtime, "bit_rotate(`1`, `-1`)"), 2147483648.0);
+        assert_eq!(eval_number(&runtime, "bit_rotate(`6`, `16`, `8`)"), 6.0);
+    }
+
+    #[test]
+    fn test_bit_invalid_arguments() {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+
+        for expr in [
+            "bit_shift_left(`1`, `64`)",
+            "bit_shift_right(`1`, `-1`)",
+            "bit_test(`1`, `64`)",
+            "bit_set(`1.5`, `0`)",
+            "bit_rotate(`256`, `1`, `8`)",
+            "bit_rotate(`1`, `1`, `12`)",
+            "popcount(`0.5`)",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    fn eval_string(runtime: &crate::Runtime, expr: &str) -> Option<String> {
+        let compiled = runtime.compile(expr).unwrap();
+        compiled
+            .search(Variable::Null)
+            .unwrap()
+            .as_string()
+            .cloned()
+    }
+
+    #[test]
+    fn test_endianness() {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+
+        assert_eq!(
+            eval_string(&runtime, "swap_endian('0x12345678', `4`)").as_deref(),
+            Some("78563412")
+        );
+        assert_eq!(
+            eval_string(&runtime, "swap_endian('1234 5678', `2`)").as_deref(),
+            Some("34127856")
+        );
+        assert_eq!(eval_string(&runtime, "swap_endian('123456', `4`)"), None);
+
+        assert_eq!(eval_number(&runtime, "hex_to_int('0x0102')"), 258.0);
+        assert_eq!(eval_number(&runtime, "hex_to_int('0201', 'little')"), 258.0);
+        assert_eq!(
+            eval_number(&runtime, "hex_to_int('ffffffffffffffff')"),
+            u64::MAX as f64
+        );
+        assert_eq!(eval_string(&runtime, "hex_to_int('zz')"), None);
+
+        assert_eq!(
+            eval_string(&runtime, "int_to_hex(`258`, `4`)").as_deref(),
+            Some("00000102")
+        );
+        assert_eq!(
+            eval_string(&runtime, "int_to_hex(`258`, `4`, 'little')").as_deref(),
+            Some("02010000")
+        );
+        assert_eq!(
+            eval_string(&runtime, "int_to_hex(`-2`, `2`)").as_deref(),
+            Some("fffe")
+        );
+        assert_eq!(
+            eval_number(
+                &runtime,
+                "hex_to_int(int_to_hex(`1234567`, `4`, 'little'), 'little')"
+            ),
+            1234567.0
+        );
+
+        for expr in [
+            "int_to_hex(`256`, `1`)",
+            "int_to_hex(`1`, `9`)",
+            "hex_to_int('01', 'middle')",
+            "swap_endian('0102', `0`)",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_hexdump() {
+        assert_eq!(
+            format_hexdump(b"Hello, World!\n"),
+            "00000000  48 65 6c 6c 6f 2c 20 57  6f 72 6c 64 21 0a        |Hello, World!.|"
+        );
+        let dump = format_hexdump(&[0u8; 17]);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("00000010  00 "));
+        assert_eq!(format_hexdump(&[]), "");
+
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        assert!(
+            eval_string(&runtime, "hexdump('SGk=')")
+                .unwrap()
+                .ends_with("|Hi|")
+        );
+        assert_eq!(eval_string(&runtime, "hexdump('not base64!')"), None);
+    }
+
+    #[test]
+    fn test_permission_modes() {
+        for (octal, symbolic) in [
+            (0o755, "rwxr-xr-x"),
+            (0o644, "rw-r--r--"),
+            (0o000, "---------"),
+            (0o4755, "rwsr-xr-x"),
+            (0o2644, "rw-r-Sr--"),
+            (0o1777, "rwxrwxrwt"),
+            (0o1776, "rwxrwxrwT"),
+        ] {
+            assert_eq!(mode_to_symbolic(octal), symbolic);
+            assert_eq!(symbolic_to_mode(symbolic), Some(octal), "{}", symbolic);
+        }
+        assert_eq!(symbolic_to_mode("drwxr-x---"), Some(0o750));
+        assert_eq!(symbolic_to_mode("rwxr-xr-"), None);
+        assert_eq!(symbolic_to_mode("rwxr-xr-q"), None);
+
+        assert_eq!(
+            parse_octal_mode(&Variable::from_json("755").unwrap()),
+            Some(0o755)
+        );
+        assert_eq!(
+            parse_octal_mode(&Variable::String("0o644".into())),
+            Some(0o644)
+        );
+        assert_eq!(
+            parse_octal_mode(&Variable::String("0755".into())),
+            Some(0o755)
+        );
+        assert_eq!(parse_octal_mode(&Variable::String("0".into())), Some(0));
+        assert_eq!(parse_octal_mode(&Variable::String("789".into())), None);
+        assert_eq!(parse_octal_mode(&Variable::String("17777".into())), None);
+    }
+
+    #[test]
+    fn test_permission_functions() {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+
+        assert_eq!(
+            eval_string(&runtime, "chmod_to_symbolic(`755`)").as_deref(),
+            Some("rwxr-xr-x")
+        );
+        assert_eq!(
+            eval_string(&runtime, "symbolic_to_chmod('-rwsr-xr-x')").as_deref(),
+            Some("4755")
+        );
+        assert_eq!(
+            eval_string(&runtime, "umask_apply('777', '022')").as_deref(),
+            Some("755")
+        );
+        assert_eq!(
+            eval_string(&runtime, "umask_apply(`666`, '027')").as_deref(),
+            Some("640")
+        );
+        assert_eq!(eval_string(&runtime, "chmod_to_symbolic('999')"), None);
+    }
 }
@@ -16,7 +16,7 @@
 //! datetime::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use chrono::{DateTime, Datelike, NaiveDateTime, TimeDelta, TimeZone, Utc, Weekday};
 use chrono_tz::Tz;
@@ -60,13 +60,18 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("epoch_ms", Box::new(NowMillisFn::new()));
 }
 
+/// The current time, honoring any installed clock provider.
+fn utc_now() -> DateTime<Utc> {
+    crate::common::now().into()
+}
+
 // now() -> number
 define_function!(NowFn, vec![], None);
 
 impl Function for NowFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
-        let ts = Utc::now().timestamp();
+        let ts = utc_now().timestamp();
         Ok(Rc::new(Variable::Number(
             serde_json::Number::from_f64(ts as f64).unwrap(),
         )))
@@ -79,7 +84,7 @@ define_function!(NowMillisFn, vec![], None);
 impl Function for NowMillisFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
-        let ts = Utc::now().timestamp_millis();
+        let ts = utc_now().timestamp_millis();
         Ok(Rc::new(Variable::Number(
             serde_json::Number::from_f64(ts as f64).unwrap(),
         )))
@@ -397,7 +402,7 @@ impl Function for RelativeTimeFn {
         self.signature.validate(args, ctx)?;
 
         let ts = args[0].as_number().unwrap() as i64;
-        let now = Utc::now().timestamp();
+        let now = utc_now().timestamp();
         let diff = ts - now;
 
         let (abs_diff, is_future) = if diff >= 0 {
@@ -559,7 +564,7 @@ impl Function for TimeAgoFn {
             None => return Ok(Rc::new(Variable::Null)),
         };
 
-        let now = Utc::now().timestamp();
+        let now = utc_now().timestamp();
         let diff = now - ts;
         let abs_diff = diff.abs();
 
@@ -698,7 +703,7 @@ impl Function for DurationSinceFn {
             Some(t) => t,
             None => return Ok(Rc::new(Variable::Null)),
         };
-        let now = Utc::now().timestamp();
+        let now = utc_now().timestamp();
         let diff = now - ts;
 
         // Calculate components
@@ -0,0 +1,290 @@
+//! Decimal (fixed-point) arithmetic functions.
+//!
+//! This module provides decimal functions for JMESPath queries. Arguments may be
+//! numbers or numeric strings and results are returned as strings, so values such
+//! as prices keep their exact digits (`dec_add('0.1', '0.2')` is `"0.3"`, not
+//! `0.30000000000000004`). Use `to_number()` on the result if a JSON number is needed.
+//!
+//! For complete function reference with signatures and examples, see the
+//! [`functions`](crate::functions) module documentation or use `jpx --list-category decimal`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jmespath::{Runtime, Variable};
+//! use jmespath_extensions::decimal;
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! decimal::register(&mut runtime);
+//!
+//! let expr = runtime.compile("dec_add(`0.1`, `0.2`)").unwrap();
+//! let result = expr.search(&Variable::Null).unwrap();
+//! assert_eq!(result.as_string().unwrap(), "0.3");
+//! ```
+
+use crate::common::Rc;
+use std::str::FromStr;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::common::{ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable};
+use crate::define_function;
+use jmespath::Runtime;
+
+/// Register all decimal functions with the runtime.
+pub fn register(runtime: &mut Runtime) {
+    runtime.register_function("dec_add", Box::new(DecAddFn::new()));
+    runtime.register_function("dec_sub", Box::new(DecSubFn::new()));
+    runtime.register_function("dec_mul", Box::new(DecMulFn::new()));
+    runtime.register_function("dec_div", Box::new(DecDivFn::new()));
+    runtime.register_function("dec_round", Box::new(DecRoundFn::new()));
+}
+
+fn decimal_error(ctx: &Context<'_>, msg: String) -> JmespathError {
+    JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg))
+}
+
+/// Convert a number or numeric string to a `Decimal` without going through f64.
+fn to_decimal(value: &Variable, ctx: &Context<'_>) -> Result<Decimal, JmespathError> {
+    let text = match value {
+        Variable::Number(n) => n.to_string(),
+        Variable::String(s) => s.trim().to_string(),
+        _ => {
+            return Err(decimal_error(
+                ctx,
+                "Expected number or numeric string".to_owned(),
+            ));
+        }
+    };
+    let parsed = if text.contains(['e', 'E']) {
+        Decimal::from_scientific(&text)
+    } else {
+        Decimal::from_str(&text)
+    };
+    parsed.map_err(|e| decimal_error(ctx, format!("Invalid decimal '{}': {}", text, e)))
+}
+
+fn decimal_var(value: Decimal) -> Rcvar {
+    Rc::new(Variable::String(value.normalize().to_string()))
+}
+
+/// Parse an optional scale argument (number of digits after the point).
+fn to_scale(value: Option<&Rcvar>, ctx: &Context<'_>) -> Result<Option<u32>, JmespathError> {
+    match value.map(|v| v.as_ref()) {
+        None | Some(Variable::Null) => Ok(None),
+        Some(Variable::Number(n)) => match n.as_f64() {
+            Some(f) if f >= 0.0 && f.fract() == 0.0 && f <= 28.0 => Ok(Some(f as u32)),
+            _ => Err(decimal_error(
+                ctx,
+                "Scale must be an integer between 0 and 28".to_owned(),
+            )),
+        },
+        Some(_) => Err(decimal_error(ctx, "Scale must be a number".to_owned())),
+    }
+}
+
+fn round_to_scale(value: Decimal, scale: u32, strategy: RoundingStrategy) -> Rcvar {
+    // Keep trailing zeros so fixed-point results read naturally ("2.50")
+    let mut rounded = value.round_dp_with_strategy(scale, strategy);
+    rounded.rescale(scale);
+    Rc::new(Variable::String(rounded.to_string()))
+}
+
+/// Shared evaluation for the binary operators.
+fn binary_op(
+    args: &[Rcvar],
+    ctx: &Context<'_>,
+    name: &str,
+    op: fn(Decimal, Decimal) -> Option<Decimal>,
+) -> Result<Rcvar, JmespathError> {
+    let a = to_decimal(&args[0], ctx)?;
+    let b = to_decimal(&args[1], ctx)?;
+    op(a, b)
+        .map(decimal_var)
+        .ok_or_else(|| decimal_error(ctx, format!("{}() overflowed", name)))
+}
+
+// =============================================================================
+// dec_add(a, b) -> string
+// =============================================================================
+
+define_function!(DecAddFn, vec![ArgumentType::Any, ArgumentType::Any], None);
+
+impl Function for DecAddFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        binary_op(args, ctx, "dec_add", Decimal::checked_add)
+    }
+}
+
+// =============================================================================
+// dec_sub(a, b) -> string
+// =============================================================================
+
+define_function!(DecSubFn, vec![ArgumentType::Any, ArgumentType::Any], None);
+
+impl Function for DecSubFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        binary_op(args, ctx, "dec_sub", Decimal::checked_sub)
+    }
+}
+
+// =============================================================================
+// dec_mul(a, b) -> string
+// =============================================================================
+
+define_function!(DecMulFn, vec![ArgumentType::Any, ArgumentType::Any], None);
+
+impl Function for DecMulFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        binary_op(args, ctx, "dec_mul", Decimal::checked_mul)
+    }
+}
+
+// =============================================================================
+// dec_div(a, b, scale?) -> string
+// =============================================================================
+
+define_function!(
+    DecDivFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    Some(ArgumentType::Any)
+);
+
+impl Function for DecDivFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let a = to_decimal(&args[0], ctx)?;
+        let b = to_decimal(&args[1], ctx)?;
+        if b.is_zero() {
+            return Err(decimal_error(ctx, "Division by zero".to_owned()));
+        }
+        let quotient = a
+            .checked_div(b)
+            .ok_or_else(|| decimal_error(ctx, "dec_div() overflowed".to_owned()))?;
+
+        match to_scale(args.get(2), ctx)? {
+            Some(scale) => Ok(round_to_scale(
+                quotient,
+                scale,
+                RoundingStrategy::MidpointAwayFromZero,
+            )),
+            None => Ok(decimal_var(quotient)),
+        }
+    }
+}
+
+// =============================================================================
+// dec_round(value, scale, mode?) -> string
+// =============================================================================
+
+define_function!(
+    DecRoundFn,
+    vec![ArgumentType::Any, ArgumentType::Number],
+    Some(ArgumentType::String)
+);
+
+impl Function for DecRoundFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let value = to_decimal(&args[0], ctx)?;
+        let scale = to_scale(args.get(1), ctx)?.unwrap_or(0);
+        let strategy = match args.get(2).and_then(|m| m.as_string()).map(|s| s.as_str()) {
+            None | Some("half_up") => RoundingStrategy::MidpointAwayFromZero,
+            Some("half_even") => RoundingStrategy::MidpointNearestEven,
+            Some("half_down") => RoundingStrategy::MidpointTowardZero,
+            Some("up") => RoundingStrategy::AwayFromZero,
+            Some("down") => RoundingStrategy::ToZero,
+            Some("ceiling") => RoundingStrategy::ToPositiveInfinity,
+            Some("floor") => RoundingStrategy::ToNegativeInfinity,
+            Some(other) => {
+                return Err(decimal_error(
+                    ctx,
+                    format!(
+                        "Unknown rounding mode '{}': expected half_up, half_even, half_down, up, down, ceiling or floor",
+                        other
+                    ),
+                ));
+            }
+        };
+
+        Ok(round_to_scale(value, scale, strategy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    fn eval(expr: &str) -> String {
+        let runtime = setup_runtime();
+        let result = runtime
+            .compile(expr)
+            .unwrap()
+            .search(Variable::Null)
+            .unwrap();
+        result.as_string().unwrap().clone()
+    }
+
+    #[test]
+    fn test_dec_add_avoids_float_artifacts() {
+        assert_eq!(eval("dec_add(`0.1`, `0.2`)"), "0.3");
+        assert_eq!(eval("dec_add('19.99', '0.01')"), "20");
+    }
+
+    #[test]
+    fn test_dec_sub_mul() {
+        assert_eq!(eval("dec_sub(`1.1`, `0.2`)"), "0.9");
+        assert_eq!(eval("dec_mul('0.1', `3`)"), "0.3");
+        assert_eq!(eval("dec_mul('1e2', '1.5')"), "150");
+    }
+
+    #[test]
+    fn test_dec_div() {
+        assert_eq!(eval("dec_div(`10`, `4`)"), "2.5");
+        assert_eq!(eval("dec_div(`1`, `3`, `4`)"), "0.3333");
+        assert_eq!(eval("dec_div(`2`, `3`, `2`)"), "0.67");
+    }
+
+    #[test]
+    fn test_dec_div_by_zero() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("dec_div(`1`, `0`)").unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_dec_round_modes() {
+        assert_eq!(eval("dec_round('2.345', `2`)"), "2.35");
+        assert_eq!(eval("dec_round('2.345', `2`, 'half_even')"), "2.34");
+        assert_eq!(eval("dec_round('2.5', `0`, 'half_even')"), "2");
+        assert_eq!(eval("dec_round('-2.341', `2`, 'floor')"), "-2.35");
+        assert_eq!(eval("dec_round('2.5', `2`)"), "2.50");
+    }
+
+    #[test]
+    fn test_dec_invalid_input() {
+        let runtime = setup_runtime();
+        for expr in [
+            "dec_add('abc', `1`)",
+            "dec_add(`[1]`, `1`)",
+            "dec_round(`1`, `-1`)",
+            "dec_round(`1`, `2`, 'nearest')",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
+}
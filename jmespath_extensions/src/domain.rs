@@ -0,0 +1,162 @@
+//! Domain name functions backed by the public suffix list.
+//!
+//! This module provides domain functions for JMESPath queries. Splitting a host
+//! into its registrable domain needs the [public suffix list](https://publicsuffix.org/),
+//! otherwise `foo.co.uk` would be treated as a subdomain of `co.uk`. A snapshot of
+//! the list is compiled into the crate (`data/public_suffix_list.dat`).
+//!
+//! For complete function reference with signatures and examples, see the
+//! [`functions`](crate::functions) module documentation or use `jpx --list-category domain`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jmespath::{Runtime, Variable};
+//! use jmespath_extensions::domain;
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! domain::register(&mut runtime);
+//!
+//! let expr = runtime.compile("domain_parse('www.foo.co.uk').registrable_domain").unwrap();
+//! let result = expr.search(&Variable::Null).unwrap();
+//! assert_eq!(result.as_string().unwrap(), "foo.co.uk");
+//! ```
+
+use crate::common::Rc;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use publicsuffix::{List, Psl};
+
+use crate::common::{ArgumentType, Context, Function, JmespathError, Rcvar, Variable};
+use crate::define_function;
+use jmespath::Runtime;
+
+static PUBLIC_SUFFIX_LIST: LazyLock<List> = LazyLock::new(|| {
+    include_str!("../data/public_suffix_list.dat")
+        .parse()
+        .expect("bundled public suffix list is valid")
+});
+
+/// Register all domain functions with the runtime.
+pub fn register(runtime: &mut Runtime) {
+    runtime.register_function("domain_parse", Box::new(DomainParseFn::new()));
+}
+
+fn string_or_null(value: Option<&str>) -> Rcvar {
+    Rc::new(value.map_or(Variable::Null, |s| Variable::String(s.to_string())))
+}
+
+// =============================================================================
+// domain_parse(host) -> object
+// Split a host into {subdomain, registrable_domain, tld}, where `tld` is the
+// public suffix (e.g. "co.uk"). Hosts that are themselves a public suffix have
+// a null registrable_domain; IP addresses and malformed hosts return null.
+// =============================================================================
+
+define_function!(DomainParseFn, vec![ArgumentType::String], None);
+
+impl Function for DomainParseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let host = args[0].as_string().unwrap().trim().to_lowercase();
+        let host = host.strip_suffix('.').unwrap_or(&host);
+        let is_valid = !host.is_empty()
+            && host.parse::<std::net::IpAddr>().is_err()
+            && host.split('.').all(|label| !label.is_empty());
+        if !is_valid {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        let Some(suffix) = PUBLIC_SUFFIX_LIST.suffix(host.as_bytes()) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+        let tld = std::str::from_utf8(suffix.as_bytes()).ok();
+        let registrable = PUBLIC_SUFFIX_LIST
+            .domain(host.as_bytes())
+            .and_then(|d| std::str::from_utf8(d.as_bytes()).ok());
+        let subdomain = registrable.and_then(|domain| {
+            host.strip_suffix(domain)
+                .and_then(|rest| rest.strip_suffix('.'))
+                .filter(|rest| !rest.is_empty())
+        });
+
+        let mut map = BTreeMap::new();
+        map.insert("subdomain".to_string(), string_or_null(subdomain));
+        map.insert(
+            "registrable_domain".to_string(),
+            string_or_null(registrable),
+        );
+        map.insert("tld".to_string(), string_or_null(tld));
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    fn parse(host: &str) -> Rcvar {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("domain_parse(@)").unwrap();
+        expr.search(Variable::String(host.to_string())).unwrap()
+    }
+
+    fn field(result: &Rcvar, key: &str) -> Option<String> {
+        result
+            .as_object()
+            .unwrap()
+            .get(key)
+            .unwrap()
+            .as_string()
+            .cloned()
+    }
+
+    #[test]
+    fn test_domain_parse_multi_label_suffix() {
+        let result = parse("www.foo.co.uk");
+        assert_eq!(field(&result, "subdomain").as_deref(), Some("www"));
+        assert_eq!(
+            field(&result, "registrable_domain").as_deref(),
+            Some("foo.co.uk")
+        );
+        assert_eq!(field(&result, "tld").as_deref(), Some("co.uk"));
+    }
+
+    #[test]
+    fn test_domain_parse_simple() {
+        let result = parse("API.Eu.Example.COM.");
+        assert_eq!(field(&result, "subdomain").as_deref(), Some("api.eu"));
+        assert_eq!(
+            field(&result, "registrable_domain").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(field(&result, "tld").as_deref(), Some("com"));
+
+        let result = parse("example.com");
+        assert_eq!(field(&result, "subdomain"), None);
+    }
+
+    #[test]
+    fn test_domain_parse_suffix_only() {
+        let result = parse("co.uk");
+        assert_eq!(field(&result, "registrable_domain"), None);
+        assert_eq!(field(&result, "tld").as_deref(), Some("co.uk"));
+    }
+
+    #[test]
+    fn test_domain_parse_invalid() {
+        assert!(parse("192.168.1.1").is_null());
+        assert!(parse("").is_null());
+        assert!(parse("foo..com").is_null());
+    }
+}
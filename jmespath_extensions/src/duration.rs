@@ -1,6 +1,8 @@
 //! Duration parsing and formatting functions.
 //!
-//! This module provides duration functions for JMESPath queries.
+//! This module provides duration functions for JMESPath queries. Durations are
+//! plain numbers of seconds; they can be read from and written as human strings
+//! (`"1h30m"`) or ISO 8601 durations (`"PT1H30M"`).
 //!
 //! For complete function reference with signatures and examples, see the
 //! [`functions`](crate::functions) module documentation or use `jpx --list-category duration`.
@@ -20,6 +22,7 @@ use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable, rcvar,
 };
 use crate::define_function;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 
 define_function!(ParseDurationFn, vec![ArgumentType::String], None);
 
@@ -219,6 +222,421 @@ fn format_duration_secs(total_secs: u64) -> String {
     result
 }
 
+/// Seconds in the calendar units of an ISO 8601 duration. Years and months have
+/// no fixed length, so they are taken as 365 and 30 days.
+const ISO_DAY: f64 = 86400.0;
+
+/// Parse an ISO 8601 duration (`P1Y2M3W4DT5H6M7.5S`, optionally negated with a
+/// leading `-`) into seconds.
+fn parse_iso_duration_str(s: &str) -> Option<f64> {
+    let s = s.trim().to_ascii_uppercase();
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.strip_prefix('+').unwrap_or(&s)),
+    };
+    let body = s.strip_prefix('P')?;
+    let (date, time) = match body.split_once('T') {
+        Some((date, time)) if !time.is_empty() => (date, Some(time)),
+        Some(_) => return None,
+        None => (body, None),
+    };
+
+    let mut total = 0.0;
+    let mut components = 0;
+    for (part, units) in [
+        (
+            date,
+            &[
+                ('Y', 365.0 * ISO_DAY),
+                ('M', 30.0 * ISO_DAY),
+                ('W', 7.0 * ISO_DAY),
+                ('D', ISO_DAY),
+            ][..],
+        ),
+        (
+            time.unwrap_or(""),
+            &[('H', 3600.0), ('M', 60.0), ('S', 1.0)][..],
+        ),
+    ] {
+        let mut rest = part;
+        let mut next_unit = 0;
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))?;
+            let value: f64 = rest[..end].replace(',', ".").parse().ok()?;
+            let unit = rest[end..].chars().next()?;
+            // Units must appear in order, each at most once
+            let offset = units[next_unit..].iter().position(|(u, _)| *u == unit)?;
+            total += value * units[next_unit + offset].1;
+            next_unit += offset + 1;
+            components += 1;
+            rest = &rest[end + 1..];
+        }
+    }
+    if components == 0 {
+        return None;
+    }
+    Some(sign * total)
+}
+
+/// Format seconds as an ISO 8601 duration using days, hours, minutes and
+/// seconds (never years or months, which have no fixed length).
+///
+/// Returns `None` when the duration does not fit in a u64 of milliseconds.
+fn format_iso_duration_secs(secs: f64) -> Option<String> {
+    let sign = if secs < 0.0 { "-" } else { "" };
+    // Work in milliseconds so fractional seconds survive without float noise
+    let total_ms = (secs.abs() * 1000.0).round();
+    if total_ms.is_nan() || total_ms >= u64::MAX as f64 {
+        return None;
+    }
+    let total_ms = total_ms as u64;
+    let days = total_ms / 86_400_000;
+    let hours = (total_ms / 3_600_000) % 24;
+    let minutes = (total_ms / 60_000) % 60;
+    let millis = total_ms % 60_000;
+
+    let mut result = format!("{}P", sign);
+    if days > 0 {
+        result.push_str(&format!("{}D", days));
+    }
+    if hours > 0 || minutes > 0 || millis > 0 || days == 0 {
+        result.push('T');
+        if hours > 0 {
+            result.push_str(&format!("{}H", hours));
+        }
+        if minutes > 0 {
+            result.push_str(&format!("{}M", minutes));
+        }
+        if millis > 0 || (hours == 0 && minutes == 0) {
+            if millis % 1000 == 0 {
+                result.push_str(&format!("{}S", millis / 1000));
+            } else {
+                let secs = format!("{}.{:03}", millis / 1000, millis % 1000);
+                result.push_str(secs.trim_end_matches('0'));
+                result.push('S');
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Read a duration given as seconds, an ISO 8601 string or a human string.
+fn duration_value(value: &Variable) -> Option<f64> {
+    match value {
+        Variable::Number(n) => n.as_f64(),
+        Variable::String(s) => {
+            let trimmed = s.trim_start_matches(['-', '+']);
+            if trimmed.starts_with(['P', 'p']) {
+                parse_iso_duration_str(s)
+            } else {
+                parse_duration_str(s).map(|secs| secs as f64)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Read a timestamp given as Unix seconds or an RFC 3339 / ISO 8601 date string.
+fn timestamp_value(value: &Variable) -> Option<f64> {
+    match value {
+        Variable::Number(n) => n.as_f64(),
+        Variable::String(s) => {
+            let millis = if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                dt.timestamp_millis()
+            } else if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+                dt.and_utc().timestamp_millis()
+            } else {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                    .ok()?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc()
+                    .timestamp_millis()
+            };
+            Some(millis as f64 / 1000.0)
+        }
+        _ => None,
+    }
+}
+
+fn seconds_var(secs: f64) -> Rcvar {
+    rcvar(Variable::Number(
+        serde_json::Number::from_f64(secs).unwrap_or_else(|| serde_json::Number::from(0)),
+    ))
+}
+
+// parse_iso_duration(s) -> number
+define_function!(ParseIsoDurationFn, vec![ArgumentType::String], None);
+
+impl Function for ParseIsoDurationFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        match parse_iso_duration_str(args[0].as_string().unwrap()) {
+            Some(secs) => Ok(seconds_var(secs)),
+            None => Ok(rcvar(Variable::Null)),
+        }
+    }
+}
+
+// format_iso_duration(seconds) -> string
+define_function!(FormatIsoDurationFn, vec![ArgumentType::Number], None);
+
+impl Function for FormatIsoDurationFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let secs = args[0].as_number().unwrap();
+        let formatted = format_iso_duration_secs(secs).ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "format_iso_duration(): {} seconds is out of range",
+                    secs
+                )),
+            )
+        })?;
+        Ok(rcvar(Variable::String(formatted)))
+    }
+}
+
+// duration_add(a, b) -> string
+// Sum two durations (seconds, ISO 8601 or human strings) as an ISO 8601 duration.
+define_function!(
+    DurationAddFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+impl Function for DurationAddFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let sum = match (duration_value(&args[0]), duration_value(&args[1])) {
+            (Some(a), Some(b)) => format_iso_duration_secs(a + b),
+            _ => None,
+        };
+        Ok(rcvar(sum.map_or(Variable::Null, Variable::String)))
+    }
+}
+
+// duration_between(start, end) -> string
+// ISO 8601 duration from start to end (negative if end is earlier). Timestamps
+// may be Unix seconds or date strings.
+define_function!(
+    DurationBetweenFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+impl Function for DurationBetweenFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let between = match (timestamp_value(&args[0]), timestamp_value(&args[1])) {
+            (Some(start), Some(end)) => format_iso_duration_secs(end - start),
+            _ => None,
+        };
+        Ok(rcvar(between.map_or(Variable::Null, Variable::String)))
+    }
+}
+
+/// Unit names for `humanize_duration`, largest first: year, day, hour, minute, second.
+struct DurationLocale {
+    units: [(&'static str, &'static str); 5],
+    /// Whether a count of zero takes the singular form (as in French).
+    zero_is_singular: bool,
+}
+
+fn duration_locale(tag: &str) -> Option<DurationLocale> {
+    // "de-DE" and "de_DE" both fall back to the language subtag
+    let language = tag
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let (units, zero_is_singular) = match language.as_str() {
+        "en" => (
+            [
+                ("year", "years"),
+                ("day", "days"),
+                ("hour", "hours"),
+                ("minute", "minutes"),
+                ("second", "seconds"),
+            ],
+            false,
+        ),
+        "de" => (
+            [
+                ("Jahr", "Jahre"),
+                ("Tag", "Tage"),
+                ("Stunde", "Stunden"),
+                ("Minute", "Minuten"),
+                ("Sekunde", "Sekunden"),
+            ],
+            false,
+        ),
+        "es" => (
+            [
+                ("año", "años"),
+                ("día", "días"),
+                ("hora", "horas"),
+                ("minuto", "minutos"),
+                ("segundo", "segundos"),
+            ],
+            false,
+        ),
+        "fr" => (
+            [
+                ("an", "ans"),
+                ("jour", "jours"),
+                ("heure", "heures"),
+                ("minute", "minutes"),
+                ("seconde", "secondes"),
+            ],
+            true,
+        ),
+        "it" => (
+            [
+                ("anno", "anni"),
+                ("giorno", "giorni"),
+                ("ora", "ore"),
+                ("minuto", "minuti"),
+                ("secondo", "secondi"),
+            ],
+            false,
+        ),
+        "nl" => (
+            [
+                ("jaar", "jaar"),
+                ("dag", "dagen"),
+                ("uur", "uur"),
+                ("minuut", "minuten"),
+                ("seconde", "seconden"),
+            ],
+            false,
+        ),
+        "pt" => (
+            [
+                ("ano", "anos"),
+                ("dia", "dias"),
+                ("hora", "horas"),
+                ("minuto", "minutos"),
+                ("segundo", "segundos"),
+            ],
+            false,
+        ),
+        _ => return None,
+    };
+    Some(DurationLocale {
+        units,
+        zero_is_singular,
+    })
+}
+
+/// Spell out a duration using at most `precision` units, largest first.
+/// Smaller units beyond the precision are dropped, not rounded.
+fn humanize_duration_secs(secs: u64, locale: &DurationLocale, precision: usize) -> String {
+    const UNIT_SECS: [u64; 5] = [365 * 86400, 86400, 3600, 60, 1];
+
+    let name = |count: u64, unit: usize| {
+        let (singular, plural) = locale.units[unit];
+        let singular_form = count == 1 || (count == 0 && locale.zero_is_singular);
+        format!(
+            "{} {}",
+            count,
+            if singular_form { singular } else { plural }
+        )
+    };
+
+    let mut remaining = secs;
+    let mut parts = Vec::new();
+    for (unit, size) in UNIT_SECS.iter().enumerate() {
+        let count = remaining / size;
+        remaining %= size;
+        if count > 0 {
+            parts.push(name(count, unit));
+            if parts.len() == precision {
+                break;
+            }
+        }
+    }
+    if parts.is_empty() {
+        return name(0, UNIT_SECS.len() - 1);
+    }
+    parts.join(", ")
+}
+
+// humanize_duration(seconds, locale?, precision?) -> string
+// Spell out a duration such as "2 days, 3 hours" in English (default), German,
+// Spanish, French, Italian, Dutch or Portuguese.
+define_function!(
+    HumanizeDurationFn,
+    vec![ArgumentType::Number],
+    Some(ArgumentType::Any)
+);
+
+impl Function for HumanizeDurationFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let secs = args[0].as_number().unwrap().abs().round();
+        if secs >= u64::MAX as f64 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "humanize_duration(): {} seconds is out of range",
+                    secs
+                )),
+            ));
+        }
+        let secs = secs as u64;
+
+        let locale = match args.get(1).map(|l| &**l) {
+            None | Some(Variable::Null) => duration_locale("en"),
+            Some(Variable::String(tag)) => duration_locale(tag),
+            Some(_) => None,
+        }
+        .ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(
+                    "Unsupported locale: expected one of en, de, es, fr, it, nl, pt".to_owned(),
+                ),
+            )
+        })?;
+
+        let precision = match args.get(2).map(|p| &**p) {
+            None | Some(Variable::Null) => 2,
+            Some(Variable::Number(n)) => match n.as_f64() {
+                Some(p) if (1.0..=5.0).contains(&p) && p.fract() == 0.0 => p as usize,
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse(
+                            "Precision must be an integer between 1 and 5".to_owned(),
+                        ),
+                    ));
+                }
+            },
+            Some(_) => {
+                return Err(JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("Expected number for precision".to_owned()),
+                ));
+            }
+        };
+
+        Ok(rcvar(Variable::String(humanize_duration_secs(
+            secs, &locale, precision,
+        ))))
+    }
+}
+
 /// Register all duration functions with the runtime.
 pub fn register(runtime: &mut crate::Runtime) {
     runtime.register_function("parse_duration", Box::new(ParseDurationFn::new()));
@@ -226,6 +644,11 @@ pub fn register(runtime: &mut crate::Runtime) {
     runtime.register_function("duration_hours", Box::new(DurationHoursFn::new()));
     runtime.register_function("duration_minutes", Box::new(DurationMinutesFn::new()));
     runtime.register_function("duration_seconds", Box::new(DurationSecondsFn::new()));
+    runtime.register_function("parse_iso_duration", Box::new(ParseIsoDurationFn::new()));
+    runtime.register_function("format_iso_duration", Box::new(FormatIsoDurationFn::new()));
+    runtime.register_function("duration_add", Box::new(DurationAddFn::new()));
+    runtime.register_function("duration_between", Box::new(DurationBetweenFn::new()));
+    runtime.register_function("humanize_duration", Box::new(HumanizeDurationFn::new()));
 }
 
 #[cfg(test)]
@@ -272,4 +695,149 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_parse_iso_duration() {
+        assert_eq!(parse_iso_duration_str("P3DT4H"), Some(273600.0));
+        assert_eq!(parse_iso_duration_str("PT1M30.5S"), Some(90.5));
+        assert_eq!(parse_iso_duration_str("PT0,5S"), Some(0.5));
+        assert_eq!(parse_iso_duration_str("P1W"), Some(604800.0));
+        assert_eq!(parse_iso_duration_str("P1M"), Some(2592000.0));
+        assert_eq!(parse_iso_duration_str("-PT2H"), Some(-7200.0));
+        assert_eq!(parse_iso_duration_str("pt5m"), Some(300.0));
+        for invalid in ["P", "PT", "P1H", "PT1D", "P1D2Y", "P1DT", "1D", "P1.2.3D"] {
+            assert_eq!(parse_iso_duration_str(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_format_iso_duration() {
+        assert_eq!(format_iso_duration_secs(0.0).unwrap(), "PT0S");
+        assert_eq!(format_iso_duration_secs(273600.0).unwrap(), "P3DT4H");
+        assert_eq!(format_iso_duration_secs(86400.0).unwrap(), "P1D");
+        assert_eq!(format_iso_duration_secs(90.5).unwrap(), "PT1M30.5S");
+        assert_eq!(format_iso_duration_secs(3661.0).unwrap(), "PT1H1M1S");
+        assert_eq!(format_iso_duration_secs(-7200.0).unwrap(), "-PT2H");
+        for secs in [0.0, 45.0, 90.5, 273600.0, 788645.0] {
+            let formatted = format_iso_duration_secs(secs).unwrap();
+            assert_eq!(
+                parse_iso_duration_str(&formatted),
+                Some(secs),
+                "{}",
+                formatted
+            );
+        }
+        assert_eq!(format_iso_duration_secs(1e300), None);
+        assert_eq!(format_iso_duration_secs(-1e300), None);
+    }
+
+    fn eval(expr: &str) -> Rcvar {
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+            .compile(expr)
+            .unwrap()
+            .search(Variable::Null)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_duration_add() {
+        assert_eq!(
+            eval("duration_add('PT1H', '30m')").as_string().unwrap(),
+            "PT1H30M"
+        );
+        assert_eq!(
+            eval("duration_add('P1D', `-3600`)").as_string().unwrap(),
+            "PT23H"
+        );
+        assert!(eval("duration_add('P1D', 'soon')").is_null());
+        assert!(eval("duration_add(`1e300`, `1`)").is_null());
+    }
+
+    #[test]
+    fn test_format_iso_duration_out_of_range() {
+        assert_eq!(
+            eval("format_iso_duration(`90`)").as_string().unwrap(),
+            "PT1M30S"
+        );
+        let mut runtime = crate::Runtime::new();
+        register(&mut runtime);
+        let expr = runtime.compile("format_iso_duration(`1e300`)").unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_duration_between() {
+        assert_eq!(
+            eval("duration_between('2024-01-01T00:00:00Z', '2024-01-04T04:00:00Z')")
+                .as_string()
+                .unwrap(),
+            "P3DT4H"
+        );
+        assert_eq!(
+            eval("duration_between('2024-01-02', '2024-01-01')")
+                .as_string()
+                .unwrap(),
+            "-P1D"
+        );
+        assert_eq!(
+            eval("duration_between(`0`, '1970-01-01T00:00:01.250Z')")
+                .as_string()
+                .unwrap(),
+            "PT1.25S"
+        );
+        assert!(eval("duration_between('yesterday', `0`)").is_null());
+    }
+
+    #[test]
+    fn test_humanize_duration() {
+        let en = duration_locale("en").unwrap();
+        assert_eq!(humanize_duration_secs(183600, &en, 2), "2 days, 3 hours");
+        assert_eq!(humanize_duration_secs(183661, &en, 2), "2 days, 3 hours");
+        assert_eq!(
+            humanize_duration_secs(183661, &en, 5),
+            "2 days, 3 hours, 1 minute, 1 second"
+        );
+        assert_eq!(humanize_duration_secs(183661, &en, 1), "2 days");
+        assert_eq!(
+            humanize_duration_secs(31536000 + 60, &en, 2),
+            "1 year, 1 minute"
+        );
+        assert_eq!(humanize_duration_secs(0, &en, 2), "0 seconds");
+
+        let fr = duration_locale("fr-CA").unwrap();
+        assert_eq!(humanize_duration_secs(0, &fr, 2), "0 seconde");
+        assert_eq!(humanize_duration_secs(7260, &fr, 2), "2 heures, 1 minute");
+        assert!(duration_locale("xx").is_none());
+    }
+
+    #[test]
+    fn test_humanize_duration_fn() {
+        assert_eq!(
+            eval("humanize_duration(`183600`)").as_string().unwrap(),
+            "2 days, 3 hours"
+        );
+        assert_eq!(
+            eval("humanize_duration(`183661`, 'de_DE', `3`)")
+                .as_string()
+                .unwrap(),
+            "2 Tage, 3 Stunden, 1 Minute"
+        );
+
+        let mut runtime = crate::Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        for expr in [
+            "humanize_duration(`60`, 'klingon')",
+            "humanize_duration(`60`, 'en', `0`)",
+            "humanize_duration(`60`, 'en', 'two')",
+            "humanize_duration(`1e300`)",
+            "humanize_duration(`-1e300`)",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
 }
@@ -16,7 +16,7 @@
 //! encoding::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
@@ -5,6 +5,10 @@
 //! For complete function reference with signatures and examples, see the
 //! [`functions`](crate::functions) module documentation or use `jpx --list-category expression`.
 //!
+//! Functions that apply an expression per element accept it either as a
+//! string (`map_expr('name', @)`) or as a native expression reference
+//! (`map_expr(&name, @)`), which avoids quoting and escaping.
+//!
 //! # Example
 //!
 //! ```rust
@@ -16,17 +20,28 @@
 //! expression::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
+use std::cell::RefCell;
+use std::collections::HashMap;
 
-use crate::common::Function;
+use crate::common::{Function, LruCache, NestedExpression};
 use crate::{
     ArgumentType, Context, ErrorReason, JmespathError, Rcvar, Runtime, Signature, Variable,
 };
+use jmespath::Expression;
+use jmespath::ast::Ast;
 
 /// Register all expression functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
     runtime.register_function("map_expr", Box::new(MapExprFn::new()));
+    runtime.register_function("map_expr_safe", Box::new(MapExprSafeFn::new()));
+    #[cfg(feature = "parallel")]
+    runtime.register_function("parallel_map_expr", Box::new(ParallelMapExprFn::new()));
+    runtime.register_function("try_expr", Box::new(TryExprFn::new()));
+    runtime.register_function("tap", Box::new(TapFn::new()));
+    runtime.register_function("memoize_expr", Box::new(MemoizeExprFn::new()));
     runtime.register_function("filter_expr", Box::new(FilterExprFn::new()));
+    runtime.register_function("filter", Box::new(FilterExprFn::new()));
     runtime.register_function("any_expr", Box::new(AnyExprFn::new()));
     runtime.register_function("all_expr", Box::new(AllExprFn::new()));
     runtime.register_function("find_expr", Box::new(FindExprFn::new()));
@@ -34,10 +49,16 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("count_expr", Box::new(CountExprFn::new()));
     runtime.register_function("sort_by_expr", Box::new(SortByExprFn::new()));
     runtime.register_function("group_by_expr", Box::new(GroupByExprFn::new()));
+    runtime.register_function("aggregate", Box::new(AggregateFn::new()));
+    runtime.register_function("pivot_expr", Box::new(PivotExprFn::new()));
+    runtime.register_function("crosstab", Box::new(PivotExprFn::new()));
     runtime.register_function("partition_expr", Box::new(PartitionExprFn::new()));
     runtime.register_function("min_by_expr", Box::new(MinByExprFn::new()));
     runtime.register_function("max_by_expr", Box::new(MaxByExprFn::new()));
+    runtime.register_function("top_k", Box::new(TopKFn::new()));
+    runtime.register_function("bottom_k", Box::new(BottomKFn::new()));
     runtime.register_function("unique_by_expr", Box::new(UniqueByExprFn::new()));
+    runtime.register_function("dedup_by", Box::new(DedupByFn::new()));
     runtime.register_function("flat_map_expr", Box::new(FlatMapExprFn::new()));
 
     // Lodash-style aliases
@@ -46,9 +67,14 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("reject", Box::new(RejectFn::new()));
     runtime.register_function("map_keys", Box::new(MapKeysFn::new()));
     runtime.register_function("map_values", Box::new(MapValuesFn::new()));
+    runtime.register_function("pick_by", Box::new(PickByFn::new()));
+    runtime.register_function("omit_by", Box::new(OmitByFn::new()));
     runtime.register_function("order_by", Box::new(OrderByFn::new()));
     runtime.register_function("reduce_expr", Box::new(ReduceExprFn::new()));
     runtime.register_function("scan_expr", Box::new(ScanExprFn::new()));
+    runtime.register_function("window_expr", Box::new(WindowExprFn::new()));
+    runtime.register_function("iterate_expr", Box::new(IterateExprFn::new()));
+    runtime.register_function("fixpoint_expr", Box::new(FixpointExprFn::new()));
     // Alias for reduce_expr (lodash-style)
     runtime.register_function("fold", Box::new(ReduceExprFn::new()));
     runtime.register_function("count_by", Box::new(CountByFn::new()));
@@ -60,10 +86,131 @@ pub fn register(runtime: &mut Runtime) {
     // Functional array operations
     runtime.register_function("take_while", Box::new(TakeWhileFn::new()));
     runtime.register_function("drop_while", Box::new(DropWhileFn::new()));
+    runtime.register_function("span_expr", Box::new(SpanExprFn::new()));
     runtime.register_function("zip_with", Box::new(ZipWithFn::new()));
+    runtime.register_function("zip_longest_with", Box::new(ZipLongestWithFn::new()));
+    runtime.register_function("split_when", Box::new(SplitWhenFn::new()));
+    runtime.register_function("chunk_by", Box::new(ChunkByFn::new()));
+    // Alias for chunk_by, matching the other *_expr names
+    runtime.register_function("chunk_by_expr", Box::new(ChunkByFn::new()));
+
+    // Keyed set operations
+    runtime.register_function("difference_by", Box::new(DifferenceByFn::new()));
+    runtime.register_function("intersection_by", Box::new(IntersectionByFn::new()));
+    runtime.register_function("union_by", Box::new(UnionByFn::new()));
+    runtime.register_function("merge_by_expr", Box::new(MergeByExprFn::new()));
+    runtime.register_function("array_diff", Box::new(ArrayDiffFn::new()));
+
+    // Joins between arrays of objects
+    runtime.register_function("join_inner", Box::new(JoinInnerFn::new()));
+    runtime.register_function("join_left", Box::new(JoinLeftFn::new()));
+    runtime.register_function("lookup", Box::new(LookupFn::new()));
 
     // Recursive transformation
     runtime.register_function("walk", Box::new(WalkFn::new()));
+
+    // Dynamic evaluation of expressions stored in data
+    runtime.register_function("eval", Box::new(EvalFn::new()));
+    runtime.register_function("evaluate_rules", Box::new(EvaluateRulesFn::new()));
+    runtime.register_function("pipe_expr", Box::new(PipeExprFn::new()));
+    runtime.register_function("compose", Box::new(ComposeFn::new()));
+    runtime.register_function("let_expr", Box::new(LetExprFn::new()));
+
+    // Named helpers declared within queries
+    runtime.register_function("define", Box::new(DefineFn::new()));
+    runtime.register_function("call", Box::new(CallFn::new()));
+
+    // Assertions
+    runtime.register_function("ensure", Box::new(EnsureFn::new()));
+}
+
+/// Maximum number of parsed expressions kept by [`compile_cached`] on each thread.
+const COMPILE_CACHE_CAPACITY: usize = 256;
+
+thread_local! {
+    /// Parsed sub-expressions keyed by source.
+    static COMPILE_CACHE: RefCell<LruCache<Ast>> =
+        RefCell::new(LruCache::new(COMPILE_CACHE_CAPACITY));
+}
+
+/// Drop every parsed expression cached on the current thread.
+pub fn clear_compile_cache() {
+    COMPILE_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+/// Compile `expression` against `runtime`, reusing the parsed AST when the
+/// same source was compiled recently on this thread.
+///
+/// Parsing does not depend on the runtime, so one cache serves every runtime.
+/// Only successful parses are cached.
+fn compile_cached<'a>(
+    runtime: &'a Runtime,
+    expression: &str,
+) -> Result<Expression<'a>, JmespathError> {
+    if let Some(ast) = COMPILE_CACHE.with(|cache| cache.borrow_mut().get(expression)) {
+        return Ok(Expression::new(expression, ast, runtime));
+    }
+
+    let ast = jmespath::parse(expression)?;
+    COMPILE_CACHE.with(|cache| cache.borrow_mut().insert(expression, ast.clone()));
+    Ok(Expression::new(expression, ast, runtime))
+}
+
+/// Compile an expression string that is evaluated as part of the current search.
+fn compile_nested<'a>(
+    ctx: &Context<'a>,
+    expression: &str,
+) -> Result<NestedExpression<'a>, JmespathError> {
+    enter_search_scope(ctx);
+    compile_cached(ctx.runtime, expression).map(NestedExpression::new)
+}
+
+/// Discard state left over from a previous top-level search on this thread.
+fn enter_search_scope(ctx: &Context<'_>) {
+    if crate::common::starts_new_search(ctx) {
+        clear_defined_functions();
+        clear_memo_cache();
+    }
+}
+
+/// Argument type for an expression parameter: a JMESPath string or a native
+/// expression reference (`&expr`).
+fn expr_arg_type() -> ArgumentType {
+    ArgumentType::Union(vec![ArgumentType::String, ArgumentType::Expref])
+}
+
+/// Compile an expression argument accepted by [`expr_arg_type`].
+///
+/// Strings are compiled against the calling runtime; `&expr` references are
+/// already parsed and are wrapped as-is, so error offsets point into the
+/// outer query.
+fn compile_expr_arg<'a>(
+    arg: &Rcvar,
+    ctx: &Context<'a>,
+    error_prefix: &str,
+) -> Result<NestedExpression<'a>, JmespathError> {
+    match &**arg {
+        Variable::Expref(ast) => {
+            enter_search_scope(ctx);
+            Ok(NestedExpression::new(Expression::new(
+                ctx.expression,
+                ast.clone(),
+                ctx.runtime,
+            )))
+        }
+        Variable::String(expr_str) => compile_nested(ctx, expr_str).map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!("{}: {}", error_prefix, e)),
+            )
+        }),
+        _ => Err(JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(format!("{}: expected a string or &expr", error_prefix)),
+        )),
+    }
 }
 
 // =============================================================================
@@ -97,7 +244,7 @@ impl Default for MapExprFn {
 impl MapExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
@@ -106,16 +253,9 @@ impl Function for MapExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in map_expr: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in map_expr")?;
 
         let results: Result<Vec<Rcvar>, _> = arr
             .iter()
@@ -127,62 +267,90 @@ impl Function for MapExprFn {
 }
 
 // =============================================================================
-// filter_expr(expr, array) -> array
+// parallel_map_expr(expr, array) -> array
 // =============================================================================
 
-/// Filter an array, keeping elements where the expression evaluates to a truthy value.
+/// Apply a JMESPath expression to each element of an array on a thread pool.
+///
+/// Produces the same result as `map_expr`, but elements are evaluated in
+/// parallel using rayon's global pool. Worthwhile for large arrays where the
+/// per-element expression dominates; for small inputs the conversion overhead
+/// makes `map_expr` faster.
+///
+/// Workers evaluate against the calling runtime, so deny lists, prefixes and
+/// custom functions apply exactly as they do to `map_expr`, and the caller's
+/// active [`Limits`](crate::Limits) and deadline are enforced on every
+/// worker. Helpers declared with `define` before the call are visible to
+/// `call` on the workers; the `memoize_expr` cache is per thread and not
+/// shared.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to filter
+/// * `expr` - A JMESPath expression string to evaluate against each element
+/// * `array` - The array to map over
 ///
 /// # Returns
-/// A new array containing only elements where the expression was truthy.
+/// An array of results in the original order. If any element fails, the
+/// error for the lowest index is returned.
 ///
 /// # Example
 /// ```text
-/// filter_expr('age >= `18`', [{"age": 25}, {"age": 17}]) -> [{"age": 25}]
-/// filter_expr('@ > `2`', [1, 2, 3, 4]) -> [3, 4]
+/// parallel_map_expr('upper(@)', ['a', 'b']) -> ["A", "B"]
+/// parallel_map_expr('name', [{name: 'x'}, {name: 'y'}]) -> ["x", "y"]
 /// ```
-pub struct FilterExprFn {
+#[cfg(feature = "parallel")]
+pub struct ParallelMapExprFn {
     signature: Signature,
 }
 
-impl Default for FilterExprFn {
+#[cfg(feature = "parallel")]
+impl Default for ParallelMapExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl FilterExprFn {
+#[cfg(feature = "parallel")]
+impl ParallelMapExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for FilterExprFn {
+#[cfg(feature = "parallel")]
+impl Function for ParallelMapExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rayon::prelude::*;
+
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in filter_expr: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in parallel_map_expr")?;
+
+        let parallel_error = |message: String| {
+            JmespathError::new(ctx.expression, ctx.offset, ErrorReason::Parse(message))
+        };
+
+        // Collect per-element results so the error reported is the one for
+        // the lowest index, regardless of which worker finished first
+        let limits = crate::limits::ActiveSnapshot::capture();
+        let definitions = DefinitionsSnapshot::capture();
+        let outcomes: Vec<Result<Rcvar, JmespathError>> = arr
+            .par_iter()
+            .map(|item| {
+                let _limits = limits.enter();
+                let _definitions = definitions.enter();
+                compiled.search(item.clone())
+            })
+            .collect();
 
-        let mut results = Vec::new();
-        for item in arr {
-            let result = compiled.search(item.clone())?;
-            if is_truthy(&result) {
-                results.push(item.clone());
-            }
+        let mut results = Vec::with_capacity(arr.len());
+        for (idx, outcome) in outcomes.into_iter().enumerate() {
+            results.push(outcome.map_err(|e| {
+                parallel_error(format!("parallel_map_expr: element {}: {}", idx, e))
+            })?);
         }
 
         Ok(Rc::new(Variable::Array(results)))
@@ -190,1818 +358,1610 @@ impl Function for FilterExprFn {
 }
 
 // =============================================================================
-// any_expr(expr, array) -> bool
+// map_expr_safe(expr, array) -> array
 // =============================================================================
 
-/// Check if any element in the array matches the expression.
+/// Apply a JMESPath expression to each element, capturing per-element errors.
+///
+/// Unlike `map_expr`, a failure on one element does not fail the whole call,
+/// which allows partial success when transforming dirty data.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to check
+/// * `expr` - A JMESPath expression string to evaluate against each element
+/// * `array` - The array to map over
 ///
 /// # Returns
-/// `true` if at least one element produces a truthy result, `false` otherwise.
+/// An array with one result object per element: `{"ok": true, "value": ...}`
+/// on success, or `{"ok": false, "error": "..."}` when evaluation failed.
 ///
 /// # Example
 /// ```text
-/// any_expr('@ > `5`', [1, 2, 10]) -> true
-/// any_expr('@ > `5`', [1, 2, 3]) -> false
+/// map_expr_safe('to_number(@)', ["1", "x"])
+///   -> [{"ok": true, "value": 1}, {"ok": true, "value": null}]
+/// map_expr_safe('abs(@)', [-1, "x"])
+///   -> [{"ok": true, "value": 1}, {"ok": false, "error": "..."}]
 /// ```
-pub struct AnyExprFn {
+pub struct MapExprSafeFn {
     signature: Signature,
 }
 
-impl Default for AnyExprFn {
+impl Default for MapExprSafeFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl AnyExprFn {
+impl MapExprSafeFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for AnyExprFn {
+impl Function for MapExprSafeFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in any_expr: {}", e)),
-            )
-        })?;
+        // An invalid expression is a caller error, not a per-element one
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in map_expr_safe")?;
 
-        for item in arr {
-            let result = compiled.search(item.clone())?;
-            if is_truthy(&result) {
-                return Ok(Rc::new(Variable::Bool(true)));
-            }
-        }
+        let results: Vec<Rcvar> = arr
+            .iter()
+            .map(|item| {
+                let mut entry = std::collections::BTreeMap::new();
+                match compiled.search(item.clone()) {
+                    Ok(value) => {
+                        entry.insert("ok".to_string(), Rc::new(Variable::Bool(true)));
+                        entry.insert("value".to_string(), value);
+                    }
+                    Err(e) => {
+                        entry.insert("ok".to_string(), Rc::new(Variable::Bool(false)));
+                        entry.insert(
+                            "error".to_string(),
+                            Rc::new(Variable::String(e.to_string())),
+                        );
+                    }
+                }
+                Rc::new(Variable::Object(entry))
+            })
+            .collect();
 
-        Ok(Rc::new(Variable::Bool(false)))
+        Ok(Rc::new(Variable::Array(results)))
     }
 }
 
 // =============================================================================
-// all_expr(expr, array) -> bool
+// try_expr(expr, value, fallback?) -> any
 // =============================================================================
 
-/// Check if all elements in the array match the expression.
+/// Evaluate an expression, returning a fallback instead of failing.
+///
+/// Wrapping a per-element transform in `try_expr` keeps one malformed element
+/// from aborting a whole `map_expr`.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to check
+/// * `expr` - A JMESPath expression string
+/// * `value` - The value to evaluate against
+/// * `fallback` - Returned when evaluation fails (defaults to `null`)
 ///
 /// # Returns
-/// `true` if every element produces a truthy result, `false` otherwise.
-/// Returns `true` for empty arrays.
+/// The expression result, or `fallback` if evaluation raised an error. An
+/// expression that does not compile is still an error.
 ///
 /// # Example
 /// ```text
-/// all_expr('@ > `0`', [1, 2, 3]) -> true
-/// all_expr('@ > `0`', [1, -1, 3]) -> false
+/// try_expr('abs(@)', `-3`) -> 3
+/// try_expr('abs(@)', 'x', `0`) -> 0
+/// map_expr('try_expr(`"abs(@)"`, @, `0`)', [-1, "x"]) -> [1, 0]
 /// ```
-pub struct AllExprFn {
+pub struct TryExprFn {
     signature: Signature,
 }
 
-impl Default for AllExprFn {
+impl Default for TryExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl AllExprFn {
+impl TryExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Any],
+                Some(ArgumentType::Any),
+            ),
         }
     }
 }
 
-impl Function for AllExprFn {
+impl Function for TryExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr = args[1].as_array().unwrap();
-
-        // Empty array returns true (vacuous truth)
-        if arr.is_empty() {
-            return Ok(Rc::new(Variable::Bool(true)));
-        }
-
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
+        if args.len() > 3 {
+            return Err(JmespathError::new(
                 ctx.expression,
                 ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in all_expr: {}", e)),
-            )
-        })?;
-
-        for item in arr {
-            let result = compiled.search(item.clone())?;
-            if !is_truthy(&result) {
-                return Ok(Rc::new(Variable::Bool(false)));
-            }
+                ErrorReason::Parse("try_expr() takes 2 or 3 arguments".into()),
+            ));
         }
 
-        Ok(Rc::new(Variable::Bool(true)))
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in try_expr")?;
+
+        Ok(compiled.search(args[1].clone()).unwrap_or_else(|_| {
+            args.get(2)
+                .cloned()
+                .unwrap_or_else(|| Rc::new(Variable::Null))
+        }))
     }
 }
 
 // =============================================================================
-// find_expr(expr, array) -> element | null
+// tap(expr, value) -> any
 // =============================================================================
 
-/// Find the first element in the array that matches the expression.
+/// Evaluate a side expression against a value and return the value unchanged.
+///
+/// Intended for debugging long pipelines together with `trace()`: the side
+/// expression can log a derived view of the data without altering what flows
+/// to the next stage.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to search
+/// * `expr` - A JMESPath expression string or `&expr` evaluated for its side effects
+/// * `value` - The value passed through
 ///
 /// # Returns
-/// The first element where the expression is truthy, or `null` if none match.
+/// `value`, unchanged. Errors from the side expression are propagated.
 ///
 /// # Example
 /// ```text
-/// find_expr('@ > `5`', [1, 3, 7, 9]) -> 7
-/// find_expr('@ > `10`', [1, 3, 7, 9]) -> null
+/// tap(&trace(length(@), 'count'), items) | [0]  // logs "count: 3", returns items[0]
 /// ```
-pub struct FindExprFn {
+pub struct TapFn {
     signature: Signature,
 }
 
-impl Default for FindExprFn {
+impl Default for TapFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl FindExprFn {
+impl TapFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Any], None),
         }
     }
 }
 
-impl Function for FindExprFn {
+impl Function for TapFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr = args[1].as_array().unwrap();
-
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in find_expr: {}", e)),
-            )
-        })?;
-
-        for item in arr {
-            let result = compiled.search(item.clone())?;
-            if is_truthy(&result) {
-                return Ok(item.clone());
-            }
-        }
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in tap")?;
+        compiled.search(args[1].clone())?;
 
-        Ok(Rc::new(Variable::Null))
+        Ok(args[1].clone())
     }
 }
 
 // =============================================================================
-// find_index_expr(expr, array) -> number | null
+// memoize_expr(expr, value) -> any
 // =============================================================================
 
-/// Find the index of the first element that matches the expression.
+/// Maximum number of results kept by `memoize_expr` on each thread.
+const MEMO_CAPACITY: usize = 4096;
+
+thread_local! {
+    /// Results of `memoize_expr`, keyed by outer query, expression and input.
+    static MEMO_CACHE: RefCell<HashMap<(String, String, String), Rcvar>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Drop every result cached by `memoize_expr` on the current thread.
+///
+/// The cache is emptied automatically when the next top-level search starts
+/// on the thread; call this to release the memory sooner, or within a
+/// search if the memoized expression is not pure (for example when it reads
+/// `now()`).
+pub fn clear_memo_cache() {
+    MEMO_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+/// Evaluate an expression once per distinct input value.
+///
+/// Inside `map_expr` over thousands of elements with repeated values, costly
+/// sub-expressions such as `regex_extract` or `parse_date` run once per
+/// distinct value instead of once per element.
+///
+/// `Context` belongs to the upstream `jmespath` crate and cannot carry extra
+/// state, so the cache is kept per thread and discarded when the next
+/// top-level search starts. It holds at most 4096 results and is emptied
+/// when full; see [`clear_memo_cache`] to reset it by hand.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to search
+/// * `expr` - A JMESPath expression string; it should not depend on anything but its input
+/// * `value` - The value to evaluate against
 ///
 /// # Returns
-/// The zero-based index of the first matching element, or `-1` if none match.
+/// The result of evaluating `expr` against `value`, cached after the first call.
 ///
 /// # Example
 /// ```text
-/// find_index_expr('@ > `5`', [1, 3, 7, 9]) -> 2
-/// find_index_expr('@ > `10`', [1, 3, 7, 9]) -> -1
+/// map_expr('memoize_expr(`"parse_date(@)"`, created)', events)
 /// ```
-pub struct FindIndexExprFn {
+pub struct MemoizeExprFn {
     signature: Signature,
 }
 
-impl Default for FindIndexExprFn {
+impl Default for MemoizeExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl FindIndexExprFn {
+impl MemoizeExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Any], None),
         }
     }
 }
 
-impl Function for FindIndexExprFn {
+impl Function for MemoizeExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr = args[1].as_array().unwrap();
+        enter_search_scope(ctx);
+        // An `&expr` is keyed by its parsed form, which includes its offset
+        // in the outer query
+        let expr_key = match &*args[0] {
+            Variable::Expref(ast) => format!("{:?}", ast),
+            other => other.as_string().cloned().unwrap_or_default(),
+        };
+        let key = (
+            ctx.expression.to_string(),
+            expr_key,
+            serde_json::to_string(&*args[1]).unwrap_or_default(),
+        );
+        if let Some(hit) = MEMO_CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+            return Ok(hit);
+        }
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in find_index_expr: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in memoize_expr")?;
+        let result = compiled.search(args[1].clone())?;
 
-        for (i, item) in arr.iter().enumerate() {
-            let result = compiled.search(item.clone())?;
-            if is_truthy(&result) {
-                return Ok(Rc::new(Variable::Number(
-                    serde_json::Number::from_f64(i as f64).unwrap(),
-                )));
+        MEMO_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.len() >= MEMO_CAPACITY {
+                cache.clear();
             }
-        }
-
-        Ok(Rc::new(Variable::Number(
-            serde_json::Number::from_f64(-1.0).unwrap(),
-        )))
+            cache.insert(key, result.clone());
+        });
+        Ok(result)
     }
 }
 
 // =============================================================================
-// count_expr(expr, array) -> number
+// filter_expr(expr, array) -> array
 // =============================================================================
 
-/// Count elements in the array where the expression is truthy.
+/// Filter an array, keeping elements where the expression evaluates to a truthy value.
 ///
 /// # Arguments
 /// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to count matches in
+/// * `array` - The array to filter
 ///
 /// # Returns
-/// The number of elements where the expression evaluated to a truthy value.
+/// A new array containing only elements where the expression was truthy.
 ///
 /// # Example
 /// ```text
-/// count_expr('@ > `2`', [1, 2, 3, 4, 5]) -> 3
-/// count_expr('active', [{"active": true}, {"active": false}]) -> 1
+/// filter_expr('age >= `18`', [{"age": 25}, {"age": 17}]) -> [{"age": 25}]
+/// filter_expr('@ > `2`', [1, 2, 3, 4]) -> [3, 4]
 /// ```
-pub struct CountExprFn {
+pub struct FilterExprFn {
     signature: Signature,
 }
 
-impl Default for CountExprFn {
+impl Default for FilterExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl CountExprFn {
+impl FilterExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for CountExprFn {
+impl Function for FilterExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in count_expr: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in filter_expr")?;
 
-        let mut count = 0;
+        let mut results = Vec::new();
         for item in arr {
             let result = compiled.search(item.clone())?;
             if is_truthy(&result) {
-                count += 1;
+                results.push(item.clone());
             }
         }
 
-        Ok(Rc::new(Variable::Number(
-            serde_json::Number::from_f64(count as f64).unwrap(),
-        )))
+        Ok(Rc::new(Variable::Array(results)))
     }
 }
 
 // =============================================================================
-// sort_by_expr(expr, array) -> array
+// any_expr(expr, array) -> bool
 // =============================================================================
 
-/// Sort an array by the result of applying an expression to each element.
+/// Check if any element in the array matches the expression.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that extracts a sort key from each element
-/// * `array` - The array to sort
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to check
 ///
 /// # Returns
-/// A new array sorted by the expression result in ascending order.
+/// `true` if at least one element produces a truthy result, `false` otherwise.
 ///
 /// # Example
 /// ```text
-/// sort_by_expr('age', [{"age": 30}, {"age": 20}]) -> [{"age": 20}, {"age": 30}]
-/// sort_by_expr('name', [{"name": "Bob"}, {"name": "Alice"}]) -> [{"name": "Alice"}, {"name": "Bob"}]
+/// any_expr('@ > `5`', [1, 2, 10]) -> true
+/// any_expr('@ > `5`', [1, 2, 3]) -> false
 /// ```
-pub struct SortByExprFn {
+pub struct AnyExprFn {
     signature: Signature,
 }
 
-impl Default for SortByExprFn {
+impl Default for AnyExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl SortByExprFn {
+impl AnyExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for SortByExprFn {
+impl Function for AnyExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in sort_by_expr: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in any_expr")?;
 
-        // Compute sort keys for each element
-        let mut keyed: Vec<(Rcvar, Rcvar)> = Vec::with_capacity(arr.len());
         for item in arr {
-            let key = compiled.search(item.clone())?;
-            keyed.push((item.clone(), key));
+            let result = compiled.search(item.clone())?;
+            if is_truthy(&result) {
+                return Ok(Rc::new(Variable::Bool(true)));
+            }
         }
 
-        // Sort by key
-        keyed.sort_by(|a, b| compare_values(&a.1, &b.1));
-
-        let results: Vec<Rcvar> = keyed.into_iter().map(|(item, _)| item).collect();
-        Ok(Rc::new(Variable::Array(results)))
+        Ok(Rc::new(Variable::Bool(false)))
     }
 }
 
 // =============================================================================
-// group_by_expr(expr, array) -> object
+// all_expr(expr, array) -> bool
 // =============================================================================
 
-/// Group array elements by the result of applying an expression.
+/// Check if all elements in the array match the expression.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that extracts a grouping key from each element
-/// * `array` - The array to group
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to check
 ///
 /// # Returns
-/// An object where keys are the stringified expression results and values are arrays of matching elements.
+/// `true` if every element produces a truthy result, `false` otherwise.
+/// Returns `true` for empty arrays.
 ///
 /// # Example
 /// ```text
-/// group_by_expr('type', [{"type": "a", "v": 1}, {"type": "b", "v": 2}, {"type": "a", "v": 3}])
-///   -> {"a": [{"type": "a", "v": 1}, {"type": "a", "v": 3}], "b": [{"type": "b", "v": 2}]}
+/// all_expr('@ > `0`', [1, 2, 3]) -> true
+/// all_expr('@ > `0`', [1, -1, 3]) -> false
 /// ```
-pub struct GroupByExprFn {
+pub struct AllExprFn {
     signature: Signature,
 }
 
-impl Default for GroupByExprFn {
+impl Default for AllExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl GroupByExprFn {
+impl AllExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for GroupByExprFn {
+impl Function for AllExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in group_by_expr: {}", e)),
-            )
-        })?;
+        // Empty array returns true (vacuous truth)
+        if arr.is_empty() {
+            return Ok(Rc::new(Variable::Bool(true)));
+        }
 
-        let mut groups: std::collections::BTreeMap<String, Vec<Rcvar>> =
-            std::collections::BTreeMap::new();
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in all_expr")?;
 
         for item in arr {
-            let key_val = compiled.search(item.clone())?;
-            let key = value_to_string(&key_val);
-            groups.entry(key).or_default().push(item.clone());
+            let result = compiled.search(item.clone())?;
+            if !is_truthy(&result) {
+                return Ok(Rc::new(Variable::Bool(false)));
+            }
         }
 
-        let result: serde_json::Map<String, serde_json::Value> = groups
-            .into_iter()
-            .map(|(k, v)| {
-                let arr: Vec<serde_json::Value> =
-                    v.into_iter().map(|item| variable_to_json(&item)).collect();
-                (k, serde_json::Value::Array(arr))
-            })
-            .collect();
-
-        Ok(Rc::new(
-            Variable::from_json(&serde_json::to_string(&result).unwrap()).unwrap(),
-        ))
+        Ok(Rc::new(Variable::Bool(true)))
     }
 }
 
 // =============================================================================
-// count_by(expr, array) -> object (count occurrences by expression result)
+// find_expr(expr, array) -> element | null
 // =============================================================================
 
-/// Count occurrences of elements grouped by an expression result.
-///
-/// Similar to `frequencies` but allows extracting a key via expression.
-/// Similar to `group_by_expr` but returns counts instead of grouped elements.
+/// Find the first element in the array that matches the expression.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string to extract the grouping key
-/// * `array` - The array to count
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to search
 ///
 /// # Returns
-/// An object mapping each unique key to its count.
+/// The first element where the expression is truthy, or `null` if none match.
 ///
 /// # Example
 /// ```text
-/// count_by('type', [{"type": "a"}, {"type": "b"}, {"type": "a"}])
-///   -> {"a": 2, "b": 1}
-/// count_by('@', ['a', 'b', 'a', 'c', 'a']) -> {"a": 3, "b": 1, "c": 1}
+/// find_expr('@ > `5`', [1, 3, 7, 9]) -> 7
+/// find_expr('@ > `10`', [1, 3, 7, 9]) -> null
 /// ```
-pub struct CountByFn {
+pub struct FindExprFn {
     signature: Signature,
 }
 
-impl Default for CountByFn {
+impl Default for FindExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl CountByFn {
+impl FindExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for CountByFn {
+impl Function for FindExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in count_by: {}", e)),
-            )
-        })?;
-
-        let mut counts: std::collections::BTreeMap<String, i64> = std::collections::BTreeMap::new();
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in find_expr")?;
 
         for item in arr {
-            let key_val = compiled.search(item.clone())?;
-            let key = value_to_string(&key_val);
-            *counts.entry(key).or_insert(0) += 1;
+            let result = compiled.search(item.clone())?;
+            if is_truthy(&result) {
+                return Ok(item.clone());
+            }
         }
 
-        let result: serde_json::Map<String, serde_json::Value> = counts
-            .into_iter()
-            .map(|(k, v)| (k, serde_json::Value::Number(serde_json::Number::from(v))))
-            .collect();
-
-        Ok(Rc::new(
-            Variable::from_json(&serde_json::to_string(&result).unwrap()).unwrap(),
-        ))
+        Ok(Rc::new(Variable::Null))
     }
 }
 
 // =============================================================================
-// partition_expr(expr, array) -> [matches, non_matches]
+// find_index_expr(expr, array) -> number | null
 // =============================================================================
 
-/// Partition an array into two arrays based on an expression.
+/// Find the index of the first element that matches the expression.
 ///
 /// # Arguments
 /// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to partition
+/// * `array` - The array to search
 ///
 /// # Returns
-/// A two-element array: `[matches, non_matches]` where `matches` contains elements
-/// where the expression was truthy, and `non_matches` contains the rest.
+/// The zero-based index of the first matching element, or `-1` if none match.
 ///
 /// # Example
 /// ```text
-/// partition_expr('@ > `2`', [1, 2, 3, 4]) -> [[3, 4], [1, 2]]
-/// partition_expr('active', [{active: true}, {active: false}]) -> [[{active: true}], [{active: false}]]
+/// find_index_expr('@ > `5`', [1, 3, 7, 9]) -> 2
+/// find_index_expr('@ > `10`', [1, 3, 7, 9]) -> -1
 /// ```
-pub struct PartitionExprFn {
+pub struct FindIndexExprFn {
     signature: Signature,
 }
 
-impl Default for PartitionExprFn {
+impl Default for FindIndexExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl PartitionExprFn {
+impl FindIndexExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for PartitionExprFn {
+impl Function for FindIndexExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in partition_expr: {}", e)),
-            )
-        })?;
-
-        let mut matches = Vec::new();
-        let mut non_matches = Vec::new();
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in find_index_expr")?;
 
-        for item in arr {
+        for (i, item) in arr.iter().enumerate() {
             let result = compiled.search(item.clone())?;
             if is_truthy(&result) {
-                matches.push(item.clone());
-            } else {
-                non_matches.push(item.clone());
+                return Ok(Rc::new(Variable::Number(
+                    serde_json::Number::from_f64(i as f64).unwrap(),
+                )));
             }
         }
 
-        Ok(Rc::new(Variable::Array(vec![
-            Rc::new(Variable::Array(matches)),
-            Rc::new(Variable::Array(non_matches)),
-        ])))
+        Ok(Rc::new(Variable::Number(
+            serde_json::Number::from_f64(-1.0).unwrap(),
+        )))
     }
 }
 
 // =============================================================================
-// min_by_expr(expr, array) -> element | null
+// count_expr(expr, array) -> number
 // =============================================================================
 
-/// Find the element with the minimum value when applying an expression.
+/// Count elements in the array where the expression is truthy.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that extracts a comparable value from each element
-/// * `array` - The array to search
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to count matches in
 ///
 /// # Returns
-/// The element with the smallest expression result, or `null` for empty arrays.
+/// The number of elements where the expression evaluated to a truthy value.
 ///
 /// # Example
 /// ```text
-/// min_by_expr('age', [{"age": 30}, {"age": 20}, {"age": 25}]) -> {"age": 20}
-/// min_by_expr('@', [5, 2, 8, 1]) -> 1
+/// count_expr('@ > `2`', [1, 2, 3, 4, 5]) -> 3
+/// count_expr('active', [{"active": true}, {"active": false}]) -> 1
 /// ```
-pub struct MinByExprFn {
+pub struct CountExprFn {
     signature: Signature,
 }
 
-impl Default for MinByExprFn {
+impl Default for CountExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MinByExprFn {
+impl CountExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for MinByExprFn {
+impl Function for CountExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        if arr.is_empty() {
-            return Ok(Rc::new(Variable::Null));
-        }
-
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in min_by_expr: {}", e)),
-            )
-        })?;
-
-        let mut min_item = arr[0].clone();
-        let mut min_key = compiled.search(arr[0].clone())?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in count_expr")?;
 
-        for item in arr.iter().skip(1) {
-            let key = compiled.search(item.clone())?;
-            if compare_values(&key, &min_key) == std::cmp::Ordering::Less {
-                min_item = item.clone();
-                min_key = key;
+        let mut count = 0;
+        for item in arr {
+            let result = compiled.search(item.clone())?;
+            if is_truthy(&result) {
+                count += 1;
             }
         }
 
-        Ok(min_item)
+        Ok(Rc::new(Variable::Number(
+            serde_json::Number::from_f64(count as f64).unwrap(),
+        )))
     }
 }
 
 // =============================================================================
-// max_by_expr(expr, array) -> element | null
+// sort_by_expr(expr, array) -> array
 // =============================================================================
 
-/// Find the element with the maximum value when applying an expression.
+/// Sort an array by the result of applying an expression to each element.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that extracts a comparable value from each element
-/// * `array` - The array to search
+/// * `expr` - A JMESPath expression string that extracts a sort key from each element
+/// * `array` - The array to sort
 ///
 /// # Returns
-/// The element with the largest expression result, or `null` for empty arrays.
+/// A new array sorted by the expression result in ascending order.
 ///
 /// # Example
 /// ```text
-/// max_by_expr('age', [{"age": 30}, {"age": 20}, {"age": 25}]) -> {"age": 30}
-/// max_by_expr('@', [5, 2, 8, 1]) -> 8
+/// sort_by_expr('age', [{"age": 30}, {"age": 20}]) -> [{"age": 20}, {"age": 30}]
+/// sort_by_expr('name', [{"name": "Bob"}, {"name": "Alice"}]) -> [{"name": "Alice"}, {"name": "Bob"}]
 /// ```
-pub struct MaxByExprFn {
+pub struct SortByExprFn {
     signature: Signature,
 }
 
-impl Default for MaxByExprFn {
+impl Default for SortByExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MaxByExprFn {
+impl SortByExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for MaxByExprFn {
+impl Function for SortByExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        if arr.is_empty() {
-            return Ok(Rc::new(Variable::Null));
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in sort_by_expr")?;
+
+        // Compute sort keys for each element
+        let mut keyed: Vec<(Rcvar, Rcvar)> = Vec::with_capacity(arr.len());
+        for item in arr {
+            let key = compiled.search(item.clone())?;
+            keyed.push((item.clone(), key));
         }
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in max_by_expr: {}", e)),
-            )
-        })?;
-
-        let mut max_item = arr[0].clone();
-        let mut max_key = compiled.search(arr[0].clone())?;
-
-        for item in arr.iter().skip(1) {
-            let key = compiled.search(item.clone())?;
-            if compare_values(&key, &max_key) == std::cmp::Ordering::Greater {
-                max_item = item.clone();
-                max_key = key;
-            }
-        }
+        // Sort by key
+        keyed.sort_by(|a, b| compare_values(&a.1, &b.1));
 
-        Ok(max_item)
+        let results: Vec<Rcvar> = keyed.into_iter().map(|(item, _)| item).collect();
+        Ok(Rc::new(Variable::Array(results)))
     }
 }
 
 // =============================================================================
-// unique_by_expr(expr, array) -> array
+// group_by_expr(expr, array) -> object
 // =============================================================================
 
-/// Remove duplicate elements based on the result of an expression.
+/// Group array elements by the result of applying an expression.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that extracts a uniqueness key from each element
-/// * `array` - The array to deduplicate
+/// * `expr` - A JMESPath expression string that extracts a grouping key from each element
+/// * `array` - The array to group
 ///
 /// # Returns
-/// A new array with duplicates removed, keeping the first occurrence of each unique key.
+/// An object where keys are the stringified expression results and values are arrays of matching elements.
 ///
 /// # Example
 /// ```text
-/// unique_by_expr('id', [{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 1, "v": "c"}])
-///   -> [{"id": 1, "v": "a"}, {"id": 2, "v": "b"}]
+/// group_by_expr('type', [{"type": "a", "v": 1}, {"type": "b", "v": 2}, {"type": "a", "v": 3}])
+///   -> {"a": [{"type": "a", "v": 1}, {"type": "a", "v": 3}], "b": [{"type": "b", "v": 2}]}
 /// ```
-pub struct UniqueByExprFn {
+pub struct GroupByExprFn {
     signature: Signature,
 }
 
-impl Default for UniqueByExprFn {
+impl Default for GroupByExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl UniqueByExprFn {
+impl GroupByExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for UniqueByExprFn {
+impl Function for GroupByExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in unique_by_expr: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in group_by_expr")?;
 
-        let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
-        let mut results = Vec::new();
+        let mut groups: std::collections::BTreeMap<String, Vec<Rcvar>> =
+            std::collections::BTreeMap::new();
 
         for item in arr {
             let key_val = compiled.search(item.clone())?;
             let key = value_to_string(&key_val);
-            if seen.insert(key) {
-                results.push(item.clone());
-            }
+            groups.entry(key).or_default().push(item.clone());
         }
 
-        Ok(Rc::new(Variable::Array(results)))
+        let result: serde_json::Map<String, serde_json::Value> = groups
+            .into_iter()
+            .map(|(k, v)| {
+                let arr: Vec<serde_json::Value> =
+                    v.into_iter().map(|item| variable_to_json(&item)).collect();
+                (k, serde_json::Value::Array(arr))
+            })
+            .collect();
+
+        Ok(Rc::new(
+            Variable::from_json(&serde_json::to_string(&result).unwrap()).unwrap(),
+        ))
     }
 }
 
 // =============================================================================
-// flat_map_expr(expr, array) -> array
+// aggregate(array, group_expr, aggregations) -> object
 // =============================================================================
 
-/// Apply an expression to each element and flatten the results.
+/// Group array elements and compute several aggregates per group in one call.
+///
+/// Each aggregation is evaluated once per group, against the group's
+/// columns: an object mapping each field to its non-null values across the
+/// group, so `sum(price)` and `max(score)` work as written. The group's
+/// elements themselves are under the reserved `_group` key, which replaces
+/// any field of that name, so `length(_group)` counts them and
+/// `sum(_group[*].a.b)` reaches nested fields. All expressions are compiled
+/// once up front.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns an array for each element
-/// * `array` - The array to flat-map over
+/// * `array` - The array to group
+/// * `group_expr` - A JMESPath expression string that extracts a grouping key from each element
+/// * `aggregations` - An object mapping output field names to aggregation expression strings,
+///   evaluated against the group's columns
 ///
 /// # Returns
-/// A single array containing all elements from the results concatenated together.
+/// An object where keys are the stringified group keys and values are objects
+/// holding the result of each aggregation for that group.
 ///
 /// # Example
 /// ```text
-/// flat_map_expr('tags', [{"tags": ["a", "b"]}, {"tags": ["c"]}]) -> ["a", "b", "c"]
-/// flat_map_expr('@', [[1, 2], [3, 4]]) -> [1, 2, 3, 4]
+/// aggregate(
+///   [{"cat": "a", "price": 1}, {"cat": "b", "price": 5}, {"cat": "a", "price": 2}],
+///   'cat',
+///   {"total": 'sum(price)', "n": 'length(_group)'}
+/// ) -> {"a": {"n": 2, "total": 3}, "b": {"n": 1, "total": 5}}
 /// ```
-pub struct FlatMapExprFn {
+pub struct AggregateFn {
     signature: Signature,
 }
 
-impl Default for FlatMapExprFn {
+/// Key under which `aggregate` exposes the elements of the current group.
+const AGGREGATE_GROUP_KEY: &str = "_group";
+
+impl Default for AggregateFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl FlatMapExprFn {
+impl AggregateFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(
+                vec![ArgumentType::Array, expr_arg_type(), ArgumentType::Object],
+                None,
+            ),
         }
     }
 }
 
-impl Function for FlatMapExprFn {
+impl Function for AggregateFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr = args[1].as_array().unwrap();
+        let arr = args[0].as_array().unwrap();
+        let aggregations = args[2].as_object().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in flat_map_expr: {}", e)),
-            )
-        })?;
+        let group_expr = compile_expr_arg(&args[1], ctx, "Invalid expression in aggregate")?;
 
-        let mut results = Vec::new();
-        for item in arr {
-            let result = compiled.search(item.clone())?;
-            match result.as_ref() {
-                Variable::Array(inner) => {
-                    results.extend(inner.iter().cloned());
-                }
-                Variable::Null => {
-                    // Skip nulls
-                }
-                _ => {
-                    results.push(result);
-                }
-            }
+        let mut compiled_aggs = Vec::with_capacity(aggregations.len());
+        for (name, agg) in aggregations {
+            let prefix = format!("Invalid expression for aggregation '{}'", name);
+            let compiled = compile_expr_arg(agg, ctx, &prefix)?;
+            compiled_aggs.push((name.clone(), compiled));
         }
 
-        Ok(Rc::new(Variable::Array(results)))
-    }
-}
-
-// =============================================================================
-// Helper functions
-// =============================================================================
-
-/// Convert a Variable to a string key for grouping/deduplication
-fn value_to_string(value: &Rcvar) -> String {
-    match value.as_ref() {
-        Variable::String(s) => s.clone(),
-        Variable::Number(n) => n.to_string(),
-        Variable::Bool(b) => b.to_string(),
-        Variable::Null => "null".to_string(),
-        _ => serde_json::to_string(&variable_to_json(value)).unwrap_or_default(),
-    }
-}
-
-/// Convert a Variable to a serde_json::Value for JSON serialization.
-///
-/// Handles all Variable types including nested arrays and objects.
-/// Expression references are converted to null.
-fn variable_to_json(value: &Rcvar) -> serde_json::Value {
-    match value.as_ref() {
-        Variable::String(s) => serde_json::Value::String(s.clone()),
-        Variable::Number(n) => serde_json::Value::Number(n.clone()),
-        Variable::Bool(b) => serde_json::Value::Bool(*b),
-        Variable::Null => serde_json::Value::Null,
-        Variable::Array(arr) => {
-            serde_json::Value::Array(arr.iter().map(variable_to_json).collect())
-        }
-        Variable::Object(obj) => {
-            let map: serde_json::Map<String, serde_json::Value> = obj
-                .iter()
-                .map(|(k, v)| (k.clone(), variable_to_json(v)))
-                .collect();
-            serde_json::Value::Object(map)
+        let mut groups: std::collections::BTreeMap<String, Vec<Rcvar>> =
+            std::collections::BTreeMap::new();
+        for item in arr {
+            let key = value_to_string(&group_expr.search(item.clone())?);
+            groups.entry(key).or_default().push(item.clone());
         }
-        Variable::Expref(_) => serde_json::Value::Null,
-    }
-}
-
-/// Check if a value is truthy according to JMESPath semantics.
-///
-/// JMESPath truthiness rules:
-/// - `null` is falsy
-/// - `false` is falsy
-/// - Empty string `""` is falsy
-/// - Empty array `[]` is falsy
-/// - Empty object `{}` is falsy
-/// - All other values (numbers, non-empty strings/arrays/objects, true) are truthy
-fn is_truthy(value: &Rcvar) -> bool {
-    match value.as_ref() {
-        Variable::Null => false,
-        Variable::Bool(b) => *b,
-        Variable::String(s) => !s.is_empty(),
-        Variable::Array(a) => !a.is_empty(),
-        Variable::Object(o) => !o.is_empty(),
-        Variable::Number(_) => true,
-        Variable::Expref(_) => true,
-    }
-}
 
-/// Compare two values for sorting purposes.
-///
-/// Comparison rules:
-/// - Numbers are compared numerically
-/// - Strings are compared lexicographically
-/// - `null` sorts before all other values
-/// - Mixed types compare as equal (stable sort preserves original order)
-fn compare_values(a: &Rcvar, b: &Rcvar) -> std::cmp::Ordering {
-    use std::cmp::Ordering;
+        let mut result = std::collections::BTreeMap::new();
+        for (key, members) in groups {
+            let mut columns: std::collections::BTreeMap<String, Vec<Rcvar>> =
+                std::collections::BTreeMap::new();
+            for (field, value) in members.iter().filter_map(|m| m.as_object()).flatten() {
+                let column = columns.entry(field.clone()).or_default();
+                if !value.is_null() {
+                    column.push(value.clone());
+                }
+            }
+            let mut context: std::collections::BTreeMap<String, Rcvar> = columns
+                .into_iter()
+                .map(|(field, values)| (field, Rc::new(Variable::Array(values))))
+                .collect();
+            context.insert(
+                AGGREGATE_GROUP_KEY.to_string(),
+                Rc::new(Variable::Array(members)),
+            );
+            let context = Rc::new(Variable::Object(context));
 
-    match (a.as_ref(), b.as_ref()) {
-        (Variable::Number(an), Variable::Number(bn)) => {
-            let a_f = an.as_f64().unwrap_or(0.0);
-            let b_f = bn.as_f64().unwrap_or(0.0);
-            a_f.partial_cmp(&b_f).unwrap_or(Ordering::Equal)
+            let mut row = std::collections::BTreeMap::new();
+            for (name, compiled) in &compiled_aggs {
+                row.insert(name.clone(), compiled.search(context.clone())?);
+            }
+            result.insert(key, Rc::new(Variable::Object(row)));
         }
-        (Variable::String(as_), Variable::String(bs)) => as_.cmp(bs),
-        (Variable::Null, Variable::Null) => Ordering::Equal,
-        (Variable::Null, _) => Ordering::Less,
-        (_, Variable::Null) => Ordering::Greater,
-        _ => Ordering::Equal,
+
+        Ok(Rc::new(Variable::Object(result)))
     }
 }
 
 // =============================================================================
-// reject(expr, array) -> array (inverse of filter_expr)
+// pivot_expr(row_expr, col_expr, value_expr, array) -> object
 // =============================================================================
 
-/// Filter an array, keeping elements where the expression is falsy (inverse of filter_expr).
+/// Cross-tabulate an array into a nested `row -> column -> value` object.
+///
+/// Elements are grouped by the pair of row and column keys, and the value
+/// expression is evaluated against the array of elements in each cell, so
+/// `sum([*].amount)` or `length(@)` can be used to aggregate. Cells with no
+/// elements are omitted.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to filter
+/// * `row_expr` - A JMESPath expression string that extracts the row key
+/// * `col_expr` - A JMESPath expression string that extracts the column key
+/// * `value_expr` - A JMESPath expression string evaluated against each cell's elements
+/// * `array` - The array to pivot
 ///
 /// # Returns
-/// A new array containing only elements where the expression was falsy.
+/// An object keyed by stringified row keys, each holding an object keyed by
+/// stringified column keys.
 ///
 /// # Example
 /// ```text
-/// reject('@ > `2`', [1, 2, 3, 4]) -> [1, 2]
-/// reject('active', [{"active": true}, {"active": false}]) -> [{"active": false}]
+/// pivot_expr('region', 'quarter', 'sum([*].sales)', [
+///   {"region": "east", "quarter": "q1", "sales": 10},
+///   {"region": "east", "quarter": "q1", "sales": 5},
+///   {"region": "west", "quarter": "q2", "sales": 7}
+/// ]) -> {"east": {"q1": 15}, "west": {"q2": 7}}
 /// ```
-pub struct RejectFn {
+pub struct PivotExprFn {
     signature: Signature,
 }
 
-impl Default for RejectFn {
+impl Default for PivotExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl RejectFn {
+impl PivotExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(
+                vec![
+                    expr_arg_type(),
+                    expr_arg_type(),
+                    expr_arg_type(),
+                    ArgumentType::Array,
+                ],
+                None,
+            ),
         }
     }
 }
 
-impl Function for RejectFn {
+impl Function for PivotExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr = args[1].as_array().unwrap();
-
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
-        })?;
+        let row_expr = compile_expr_arg(&args[0], ctx, "Invalid row expression in pivot_expr")?;
+        let col_expr = compile_expr_arg(&args[1], ctx, "Invalid column expression in pivot_expr")?;
+        let value_expr = compile_expr_arg(&args[2], ctx, "Invalid value expression in pivot_expr")?;
+        let arr = args[3].as_array().unwrap();
 
-        let mut result = Vec::new();
+        let mut cells: std::collections::BTreeMap<
+            String,
+            std::collections::BTreeMap<String, Vec<Rcvar>>,
+        > = std::collections::BTreeMap::new();
         for item in arr {
-            let matched = compiled.search(item).map_err(|e| {
-                JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
-            })?;
-            // Keep items where expression is falsy (inverse of filter)
-            if !is_truthy(&matched) {
-                result.push(item.clone());
+            let row = value_to_string(&row_expr.search(item.clone())?);
+            let col = value_to_string(&col_expr.search(item.clone())?);
+            cells
+                .entry(row)
+                .or_default()
+                .entry(col)
+                .or_default()
+                .push(item.clone());
+        }
+
+        let mut result = std::collections::BTreeMap::new();
+        for (row, cols) in cells {
+            let mut row_obj = std::collections::BTreeMap::new();
+            for (col, members) in cols {
+                let value = value_expr.search(Rc::new(Variable::Array(members)))?;
+                row_obj.insert(col, value);
             }
+            result.insert(row, Rc::new(Variable::Object(row_obj)));
         }
 
-        Ok(Rc::new(Variable::Array(result)))
+        Ok(Rc::new(Variable::Object(result)))
     }
 }
 
 // =============================================================================
-// map_keys(expr, object) -> object
+// count_by(expr, array) -> object (count occurrences by expression result)
 // =============================================================================
 
-use std::collections::BTreeMap;
-
-/// Transform the keys of an object by applying an expression to each key.
+/// Count occurrences of elements grouped by an expression result.
+///
+/// Similar to `frequencies` but allows extracting a key via expression.
+/// Similar to `group_by_expr` but returns counts instead of grouped elements.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that transforms each key (key is passed as `@`)
-/// * `object` - The object whose keys to transform
+/// * `expr` - A JMESPath expression string to extract the grouping key
+/// * `array` - The array to count
 ///
 /// # Returns
-/// A new object with transformed keys and original values.
+/// An object mapping each unique key to its count.
 ///
 /// # Example
 /// ```text
-/// map_keys('upper(@)', {"a": 1, "b": 2}) -> {"A": 1, "B": 2}
-/// map_keys('@ & "_suffix"', {"foo": 1}) -> {"foo_suffix": 1}
+/// count_by('type', [{"type": "a"}, {"type": "b"}, {"type": "a"}])
+///   -> {"a": 2, "b": 1}
+/// count_by('@', ['a', 'b', 'a', 'c', 'a']) -> {"a": 3, "b": 1, "c": 1}
 /// ```
-pub struct MapKeysFn {
+pub struct CountByFn {
     signature: Signature,
 }
 
-impl Default for MapKeysFn {
+impl Default for CountByFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MapKeysFn {
+impl CountByFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Object], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for MapKeysFn {
+impl Function for CountByFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let obj = args[1].as_object().unwrap();
-
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
-        })?;
+        let arr = args[1].as_array().unwrap();
 
-        let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
-        for (key, value) in obj.iter() {
-            // Apply expression to the key
-            let key_var = Rc::new(Variable::String(key.clone()));
-            let new_key = compiled.search(&key_var).map_err(|e| {
-                JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
-            })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in count_by")?;
 
-            let new_key_str = match &*new_key {
-                Variable::String(s) => s.clone(),
-                Variable::Number(n) => n.to_string(),
-                _ => key.clone(), // Keep original if result isn't a string/number
-            };
+        let mut counts: std::collections::BTreeMap<String, i64> = std::collections::BTreeMap::new();
 
-            result.insert(new_key_str, value.clone());
+        for item in arr {
+            let key_val = compiled.search(item.clone())?;
+            let key = value_to_string(&key_val);
+            *counts.entry(key).or_insert(0) += 1;
         }
 
-        Ok(Rc::new(Variable::Object(result)))
+        let result: serde_json::Map<String, serde_json::Value> = counts
+            .into_iter()
+            .map(|(k, v)| (k, serde_json::Value::Number(serde_json::Number::from(v))))
+            .collect();
+
+        Ok(Rc::new(
+            Variable::from_json(&serde_json::to_string(&result).unwrap()).unwrap(),
+        ))
     }
 }
 
 // =============================================================================
-// map_values(expr, object) -> object
+// partition_expr(expr, array) -> [matches, non_matches]
 // =============================================================================
 
-/// Transform the values of an object by applying an expression to each value.
+/// Partition an array into two arrays based on an expression.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that transforms each value (value is passed as `@`)
-/// * `object` - The object whose values to transform
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to partition
 ///
 /// # Returns
-/// A new object with original keys and transformed values.
+/// A two-element array: `[matches, non_matches]` where `matches` contains elements
+/// where the expression was truthy, and `non_matches` contains the rest.
 ///
 /// # Example
 /// ```text
-/// map_values('@ * `2`', {"a": 1, "b": 2}) -> {"a": 2, "b": 4}
-/// map_values('upper(@)', {"x": "hello", "y": "world"}) -> {"x": "HELLO", "y": "WORLD"}
+/// partition_expr('@ > `2`', [1, 2, 3, 4]) -> [[3, 4], [1, 2]]
+/// partition_expr('active', [{active: true}, {active: false}]) -> [[{active: true}], [{active: false}]]
 /// ```
-pub struct MapValuesFn {
+pub struct PartitionExprFn {
     signature: Signature,
 }
 
-impl Default for MapValuesFn {
+impl Default for PartitionExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl MapValuesFn {
+impl PartitionExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Object], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for MapValuesFn {
+impl Function for PartitionExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let obj = args[1].as_object().unwrap();
+        let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in partition_expr")?;
 
-        let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
-        for (key, value) in obj.iter() {
-            // Apply expression to the value
-            let new_value = compiled.search(value).map_err(|e| {
-                JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
-            })?;
+        let mut matches = Vec::new();
+        let mut non_matches = Vec::new();
 
-            result.insert(key.clone(), new_value);
+        for item in arr {
+            let result = compiled.search(item.clone())?;
+            if is_truthy(&result) {
+                matches.push(item.clone());
+            } else {
+                non_matches.push(item.clone());
+            }
         }
 
-        Ok(Rc::new(Variable::Object(result)))
+        Ok(Rc::new(Variable::Array(vec![
+            Rc::new(Variable::Array(matches)),
+            Rc::new(Variable::Array(non_matches)),
+        ])))
     }
 }
 
 // =============================================================================
-// order_by(array, criteria) -> array
+// min_by_expr(expr, array) -> element | null
 // =============================================================================
 
-/// Sort an array by multiple criteria with direction control.
+/// Find the element with the minimum value when applying an expression.
 ///
 /// # Arguments
-/// * `array` - The array to sort
-/// * `criteria` - Array of [field, direction] pairs where direction is "asc" or "desc"
-///   Use JMESPath literal syntax with backticks: `` `[["field", "asc"]]` ``
+/// * `expr` - A JMESPath expression string that extracts a comparable value from each element
+/// * `array` - The array to search
 ///
 /// # Returns
-/// A new sorted array.
+/// The element with the smallest expression result, or `null` for empty arrays.
 ///
 /// # Example
 /// ```text
-/// order_by(@, `[["name", "asc"]]`)  // Sort by name ascending
-/// order_by(@, `[["age", "desc"], ["name", "asc"]]`)  // Sort by age desc, then name asc
+/// min_by_expr('age', [{"age": 30}, {"age": 20}, {"age": 25}]) -> {"age": 20}
+/// min_by_expr('@', [5, 2, 8, 1]) -> 1
 /// ```
-pub struct OrderByFn {
+pub struct MinByExprFn {
     signature: Signature,
 }
 
-impl Default for OrderByFn {
+impl Default for MinByExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl OrderByFn {
+impl MinByExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for OrderByFn {
+impl Function for MinByExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let arr = args[0].as_array().unwrap();
-        let criteria = args[1].as_array().unwrap();
+        let arr = args[1].as_array().unwrap();
 
         if arr.is_empty() {
-            return Ok(Rc::new(Variable::Array(vec![])));
+            return Ok(Rc::new(Variable::Null));
         }
 
-        // Parse criteria: each element should be [field, direction]
-        let mut sort_specs: Vec<(String, bool)> = Vec::new(); // (field, ascending)
-        for criterion in criteria {
-            let crit_arr = criterion.as_array().ok_or_else(|| {
-                JmespathError::new(
-                    ctx.expression,
-                    ctx.offset,
-                    ErrorReason::Parse("Each criterion must be an array [field, direction]".into()),
-                )
-            })?;
-
-            if crit_arr.len() < 2 {
-                return Err(JmespathError::new(
-                    ctx.expression,
-                    ctx.offset,
-                    ErrorReason::Parse("Each criterion must have [field, direction]".into()),
-                ));
-            }
-
-            let field = crit_arr[0].as_string().ok_or_else(|| {
-                JmespathError::new(
-                    ctx.expression,
-                    ctx.offset,
-                    ErrorReason::Parse("Field name must be a string".into()),
-                )
-            })?;
-
-            let direction = crit_arr[1].as_string().ok_or_else(|| {
-                JmespathError::new(
-                    ctx.expression,
-                    ctx.offset,
-                    ErrorReason::Parse("Direction must be 'asc' or 'desc'".into()),
-                )
-            })?;
-
-            let ascending = match direction.to_lowercase().as_str() {
-                "asc" | "ascending" => true,
-                "desc" | "descending" => false,
-                _ => {
-                    return Err(JmespathError::new(
-                        ctx.expression,
-                        ctx.offset,
-                        ErrorReason::Parse("Direction must be 'asc' or 'desc'".into()),
-                    ));
-                }
-            };
-
-            sort_specs.push((field.to_string(), ascending));
-        }
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in min_by_expr")?;
 
-        // Clone and sort the array
-        let mut result: Vec<Rcvar> = arr.clone();
-        result.sort_by(|a, b| {
-            for (field, ascending) in &sort_specs {
-                let a_val = a
-                    .as_object()
-                    .and_then(|o| o.get(field))
-                    .cloned()
-                    .unwrap_or_else(|| Rc::new(Variable::Null));
-                let b_val = b
-                    .as_object()
-                    .and_then(|o| o.get(field))
-                    .cloned()
-                    .unwrap_or_else(|| Rc::new(Variable::Null));
+        let mut min_item = arr[0].clone();
+        let mut min_key = compiled.search(arr[0].clone())?;
 
-                let cmp = compare_values(&a_val, &b_val);
-                if cmp != std::cmp::Ordering::Equal {
-                    return if *ascending { cmp } else { cmp.reverse() };
-                }
+        for item in arr.iter().skip(1) {
+            let key = compiled.search(item.clone())?;
+            if compare_values(&key, &min_key) == std::cmp::Ordering::Less {
+                min_item = item.clone();
+                min_key = key;
             }
-            std::cmp::Ordering::Equal
-        });
+        }
 
-        Ok(Rc::new(Variable::Array(result)))
+        Ok(min_item)
     }
 }
 
 // =============================================================================
-// reduce_expr(expr, array, initial) -> any
+// max_by_expr(expr, array) -> element | null
 // =============================================================================
 
-/// Reduce an array to a single value using an expression.
-///
-/// The expression is evaluated with a special context where:
-/// - `accumulator` is the current accumulated value
-/// - `current` is the current element being processed
-/// - `index` is the current index (0-based)
+/// Find the element with the maximum value when applying an expression.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string. Use `accumulator` and `current` in the expression.
-/// * `array` - The array to reduce
-/// * `initial` - The initial value for the accumulator
+/// * `expr` - A JMESPath expression string that extracts a comparable value from each element
+/// * `array` - The array to search
 ///
 /// # Returns
-/// The final accumulated value.
+/// The element with the largest expression result, or `null` for empty arrays.
 ///
 /// # Example
 /// ```text
-/// reduce_expr('accumulator + current', [1, 2, 3], `0`)  // Sum: 6
-/// reduce_expr('max([accumulator, current])', [3, 1, 4], `0`)  // Max: 4
+/// max_by_expr('age', [{"age": 30}, {"age": 20}, {"age": 25}]) -> {"age": 30}
+/// max_by_expr('@', [5, 2, 8, 1]) -> 8
 /// ```
-pub struct ReduceExprFn {
+pub struct MaxByExprFn {
     signature: Signature,
 }
 
-impl Default for ReduceExprFn {
+impl Default for MaxByExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl ReduceExprFn {
+impl MaxByExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(
-                vec![ArgumentType::String, ArgumentType::Array, ArgumentType::Any],
-                None,
-            ),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for ReduceExprFn {
+impl Function for MaxByExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
-        let initial = args[2].clone();
 
         if arr.is_empty() {
-            return Ok(initial);
+            return Ok(Rc::new(Variable::Null));
         }
 
-        // Compile the expression
-        let runtime = ctx.runtime;
-        let compiled = runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid reduce expression: {}", e)),
-            )
-        })?;
-
-        let mut accumulator = initial;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in max_by_expr")?;
 
-        for (idx, item) in arr.iter().enumerate() {
-            // Create context object with accumulator, current, and index
-            let mut context_map: std::collections::BTreeMap<String, Rcvar> =
-                std::collections::BTreeMap::new();
-            context_map.insert("accumulator".to_string(), accumulator.clone());
-            context_map.insert("current".to_string(), item.clone());
-            context_map.insert(
-                "index".to_string(),
-                Rc::new(Variable::Number(serde_json::Number::from(idx as i64))),
-            );
-            let context_var = Rc::new(Variable::Object(context_map));
+        let mut max_item = arr[0].clone();
+        let mut max_key = compiled.search(arr[0].clone())?;
 
-            accumulator = compiled.search(&context_var).map_err(|e| {
-                JmespathError::new(
-                    ctx.expression,
-                    ctx.offset,
-                    ErrorReason::Parse(format!("Reduce expression evaluation error: {}", e)),
-                )
-            })?;
+        for item in arr.iter().skip(1) {
+            let key = compiled.search(item.clone())?;
+            if compare_values(&key, &max_key) == std::cmp::Ordering::Greater {
+                max_item = item.clone();
+                max_key = key;
+            }
         }
 
-        Ok(accumulator)
+        Ok(max_item)
     }
 }
 
 // =============================================================================
-// scan_expr(expr, array, initial) -> array
+// top_k(array, k, expr?) -> array
 // =============================================================================
 
-/// Scan (cumulative reduce) an array, returning all intermediate accumulated values.
+/// Select the `k` largest elements of an array, optionally keyed by an expression.
 ///
-/// Similar to reduce_expr, but returns an array of all intermediate results.
+/// Uses a bounded heap, so only `k` elements are held at a time instead of
+/// sorting the whole array.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string. Use `accumulator` and `current` in the expression.
-/// * `array` - The array to scan
-/// * `initial` - The initial value for the accumulator
+/// * `array` - The array to select from
+/// * `k` - Number of elements to return
+/// * `expr` - Optional JMESPath expression string that extracts the comparison key (defaults to `@`)
 ///
 /// # Returns
-/// An array of all accumulated values (including each intermediate step).
+/// Up to `k` elements in descending key order. Ties keep their original order.
 ///
 /// # Example
 /// ```text
-/// scan_expr('accumulator + current', [1, 2, 3], `0`)  // Running sum: [1, 3, 6]
+/// top_k([5, 2, 8, 1, 9], `2`) -> [9, 8]
+/// top_k([{"n": "a", "s": 3}, {"n": "b", "s": 7}], `1`, 's') -> [{"n": "b", "s": 7}]
 /// ```
-pub struct ScanExprFn {
+pub struct TopKFn {
     signature: Signature,
 }
 
-impl Default for ScanExprFn {
+impl Default for TopKFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl ScanExprFn {
+impl TopKFn {
     pub fn new() -> Self {
         Self {
             signature: Signature::new(
-                vec![ArgumentType::String, ArgumentType::Array, ArgumentType::Any],
-                None,
+                vec![ArgumentType::Array, ArgumentType::Number],
+                Some(expr_arg_type()),
             ),
         }
     }
 }
 
-impl Function for ScanExprFn {
+impl Function for TopKFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
+        select_k(args, ctx, "top_k", true)
+    }
+}
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr = args[1].as_array().unwrap();
-        let initial = args[2].clone();
+// =============================================================================
+// bottom_k(array, k, expr?) -> array
+// =============================================================================
 
-        if arr.is_empty() {
-            return Ok(Rc::new(Variable::Array(vec![])));
+/// Select the `k` smallest elements of an array, optionally keyed by an expression.
+///
+/// Uses a bounded heap, so only `k` elements are held at a time instead of
+/// sorting the whole array.
+///
+/// # Arguments
+/// * `array` - The array to select from
+/// * `k` - Number of elements to return
+/// * `expr` - Optional JMESPath expression string that extracts the comparison key (defaults to `@`)
+///
+/// # Returns
+/// Up to `k` elements in ascending key order. Ties keep their original order.
+///
+/// # Example
+/// ```text
+/// bottom_k([5, 2, 8, 1, 9], `2`) -> [1, 2]
+/// bottom_k([{"n": "a", "s": 3}, {"n": "b", "s": 7}], `1`, 's') -> [{"n": "a", "s": 3}]
+/// ```
+pub struct BottomKFn {
+    signature: Signature,
+}
+
+impl Default for BottomKFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BottomKFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::Array, ArgumentType::Number],
+                Some(expr_arg_type()),
+            ),
         }
+    }
+}
 
-        // Compile the expression
-        let runtime = ctx.runtime;
-        let compiled = runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid scan expression: {}", e)),
-            )
-        })?;
+impl Function for BottomKFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        select_k(args, ctx, "bottom_k", false)
+    }
+}
 
-        let mut accumulator = initial;
-        let mut results: Vec<Rcvar> = Vec::with_capacity(arr.len());
+/// Heap entry for `top_k` / `bottom_k`. An entry compares greater when it
+/// ranks better: a larger (or smaller, for `bottom_k`) key, then an earlier index.
+struct RankedEntry {
+    key: Rcvar,
+    index: usize,
+    item: Rcvar,
+    largest: bool,
+}
 
-        for (idx, item) in arr.iter().enumerate() {
-            // Create context object with accumulator, current, and index
-            let mut context_map: std::collections::BTreeMap<String, Rcvar> =
-                std::collections::BTreeMap::new();
-            context_map.insert("accumulator".to_string(), accumulator.clone());
-            context_map.insert("current".to_string(), item.clone());
-            context_map.insert(
-                "index".to_string(),
-                Rc::new(Variable::Number(serde_json::Number::from(idx as i64))),
-            );
-            let context_var = Rc::new(Variable::Object(context_map));
+impl Ord for RankedEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let by_key = compare_values(&self.key, &other.key);
+        let by_key = if self.largest {
+            by_key
+        } else {
+            by_key.reverse()
+        };
+        by_key.then_with(|| other.index.cmp(&self.index))
+    }
+}
 
-            accumulator = compiled.search(&context_var).map_err(|e| {
-                JmespathError::new(
-                    ctx.expression,
-                    ctx.offset,
-                    ErrorReason::Parse(format!("Scan expression evaluation error: {}", e)),
-                )
-            })?;
+impl PartialOrd for RankedEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-            results.push(accumulator.clone());
-        }
+impl PartialEq for RankedEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
 
-        Ok(Rc::new(Variable::Array(results)))
+impl Eq for RankedEntry {}
+
+/// Shared implementation of `top_k` and `bottom_k`.
+fn select_k(
+    args: &[Rcvar],
+    ctx: &mut Context<'_>,
+    name: &str,
+    largest: bool,
+) -> Result<Rcvar, JmespathError> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    if args.len() > 3 {
+        return Err(JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(format!("{}() takes 2 or 3 arguments", name)),
+        ));
+    }
+
+    let arr = args[0].as_array().unwrap();
+    let k = args[1].as_number().unwrap();
+    if !k.is_finite() || k < 0.0 {
+        return Err(JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(format!("{}() k must be a non-negative number", name)),
+        ));
+    }
+    // Asking for more than the array holds just returns all of it
+    let k = (k as usize).min(arr.len());
+
+    let compiled = match args.get(2) {
+        Some(expr) => Some(compile_expr_arg(
+            expr,
+            ctx,
+            &format!("Invalid expression in {}", name),
+        )?),
+        None => None,
+    };
+
+    if k == 0 {
+        return Ok(Rc::new(Variable::Array(vec![])));
+    }
+
+    // Min-heap of the best k entries seen so far; the root is the worst of them
+    let mut heap: BinaryHeap<Reverse<RankedEntry>> = BinaryHeap::with_capacity(k + 1);
+    for (index, item) in arr.iter().enumerate() {
+        let key = match &compiled {
+            Some(expr) => expr.search(item.clone())?,
+            None => item.clone(),
+        };
+        heap.push(Reverse(RankedEntry {
+            key,
+            index,
+            item: item.clone(),
+            largest,
+        }));
+        if heap.len() > k {
+            heap.pop();
+        }
     }
+
+    // Ascending order of Reverse<_> is best-first
+    let results: Vec<Rcvar> = heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(entry)| entry.item)
+        .collect();
+    Ok(Rc::new(Variable::Array(results)))
 }
 
 // =============================================================================
-// partial(fn_name, ...args) -> partial object
+// dedup_by(expr, array) -> array
 // =============================================================================
 
-/// Create a partial function with some arguments pre-filled.
+/// Remove consecutive elements whose expression result equals the previous one.
 ///
-/// Returns an object that can be used with `apply()` to invoke the function
-/// with the remaining arguments. This enables currying and reusable function
-/// configurations.
+/// Unlike `unique_by_expr`, only runs of adjacent duplicates are collapsed, so
+/// a key that reappears later is kept. This is useful for collapsing repeated
+/// states in event streams.
 ///
 /// # Arguments
-/// * `fn_name` - The name of the function to partially apply
-/// * `...args` - Zero or more arguments to pre-fill
+/// * `expr` - A JMESPath expression string that extracts a comparison key from each element
+/// * `array` - The array to deduplicate
 ///
 /// # Returns
-/// A partial object: `{"__partial__": true, "fn": "fn_name", "args": [...]}`
-///
-/// # Examples
-///
-/// ## Basic Usage
-/// ```text
-/// partial('join', `"-"`)  // Create a dash-joiner
-/// // -> {"__partial__": true, "fn": "join", "args": ["-"]}
-/// ```
-///
-/// ## Reusable String Operations
-/// ```text
-/// // Create a comma-joiner for CSV-like output
-/// csv_joiner = partial('join', `","`)
-/// apply(csv_joiner, `["name", "age", "city"]`)  // -> "name,age,city"
-/// ```
-///
-/// ## Pre-configured Search
-/// ```text
-/// // Create a contains checker with pre-filled haystack
-/// has_hello = partial('contains', `"hello world"`)
-/// apply(has_hello, `"world"`)  // -> true
-/// apply(has_hello, `"xyz"`)    // -> false
-/// ```
+/// A new array keeping the first element of each run of equal keys.
 ///
-/// ## Date Formatting
+/// # Example
 /// ```text
-/// // Create a reusable ISO date formatter
-/// iso_formatter = partial('format_date', `"%Y-%m-%d"`)
-/// apply(iso_formatter, `"2024-01-15T10:30:00Z"`)  // -> "2024-01-15"
+/// dedup_by('state', [{"state": "up", "t": 1}, {"state": "up", "t": 2}, {"state": "down", "t": 3}, {"state": "up", "t": 4}])
+///   -> [{"state": "up", "t": 1}, {"state": "down", "t": 3}, {"state": "up", "t": 4}]
 /// ```
-pub struct PartialFn {
-    #[allow(dead_code)]
+pub struct DedupByFn {
     signature: Signature,
 }
 
-impl Default for PartialFn {
+impl Default for DedupByFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl PartialFn {
+impl DedupByFn {
     pub fn new() -> Self {
         Self {
-            // At least function name required, then variadic args
-            signature: Signature::new(vec![ArgumentType::String], Some(ArgumentType::Any)),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for PartialFn {
+impl Function for DedupByFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
-        if args.is_empty() {
-            return Err(JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse("partial() requires at least a function name".into()),
-            ));
-        }
+        self.signature.validate(args, ctx)?;
 
-        let fn_name = args[0].as_string().ok_or_else(|| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(
-                    "partial() first argument must be a function name string".into(),
-                ),
-            )
-        })?;
+        let arr = args[1].as_array().unwrap();
 
-        // Collect the pre-filled arguments
-        let prefilled_args: Vec<serde_json::Value> =
-            args[1..].iter().map(variable_to_json).collect();
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in dedup_by")?;
 
-        // Create the partial object
-        let mut partial_obj = serde_json::Map::new();
-        partial_obj.insert("__partial__".to_string(), serde_json::Value::Bool(true));
-        partial_obj.insert(
-            "fn".to_string(),
-            serde_json::Value::String(fn_name.to_string()),
-        );
-        partial_obj.insert("args".to_string(), serde_json::Value::Array(prefilled_args));
+        let mut results = Vec::new();
+        let mut last_key: Option<Rcvar> = None;
 
-        Ok(Rc::new(
-            Variable::from_json(&serde_json::to_string(&partial_obj).unwrap()).unwrap(),
-        ))
+        for item in arr {
+            let key = compiled.search(item.clone())?;
+            if last_key.as_ref() != Some(&key) {
+                results.push(item.clone());
+            }
+            last_key = Some(key);
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
     }
 }
 
 // =============================================================================
-// apply(partial_or_fn, ...args) -> result
+// unique_by_expr(expr, array) -> array
 // =============================================================================
 
-/// Apply a partial function or regular function with arguments.
-///
-/// If the first argument is a partial object (from `partial()`), combines
-/// the pre-filled arguments with the provided arguments and invokes the function.
-/// If it's a string, treats it as a function name and invokes directly.
-///
-/// This function is the complement to `partial()` - use `partial()` to create
-/// reusable function configurations, then `apply()` to execute them.
+/// Remove duplicate elements based on the result of an expression.
 ///
 /// # Arguments
-/// * `partial_or_fn` - Either a partial object or a function name string
-/// * `...args` - Additional arguments to pass to the function
+/// * `expr` - A JMESPath expression string that extracts a uniqueness key from each element
+/// * `array` - The array to deduplicate
 ///
 /// # Returns
-/// The result of invoking the function with all arguments.
-///
-/// # Examples
-///
-/// ## Apply a Partial
-/// ```text
-/// // Create and apply a dash-joiner
-/// apply(partial('join', `"-"`), `["a", "b", "c"]`)  // -> "a-b-c"
-/// ```
-///
-/// ## Direct Function Call by Name
-/// ```text
-/// // Call any function by its string name
-/// apply('length', `"hello"`)  // -> 5
-/// apply('upper', `"hello"`)   // -> "HELLO"
-/// ```
-///
-/// ## Dynamic Function Dispatch
-/// ```text
-/// // Useful when the function name comes from data or configuration
-/// fn_name = 'sum'
-/// apply(fn_name, `[1, 2, 3, 4]`)  // -> 10
-/// ```
-///
-/// ## Combining with Partials
-/// ```text
-/// // Pre-configure a contains check, then apply multiple times
-/// checker = partial('contains', `"The quick brown fox"`)
-/// apply(checker, `"quick"`)  // -> true
-/// apply(checker, `"slow"`)   // -> false
-/// ```
+/// A new array with duplicates removed, keeping the first occurrence of each unique key.
 ///
-/// ## Building Pipelines
+/// # Example
 /// ```text
-/// // Create specialized validators
-/// email_pattern = partial('regex_match', `"^[a-z]+@[a-z]+\\.[a-z]+$"`)
-/// apply(email_pattern, `"test@example.com"`)  // -> true
+/// unique_by_expr('id', [{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 1, "v": "c"}])
+///   -> [{"id": 1, "v": "a"}, {"id": 2, "v": "b"}]
 /// ```
-pub struct ApplyFn {
-    #[allow(dead_code)]
+pub struct UniqueByExprFn {
     signature: Signature,
 }
 
-impl Default for ApplyFn {
+impl Default for UniqueByExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl ApplyFn {
+impl UniqueByExprFn {
     pub fn new() -> Self {
         Self {
-            // First arg is partial or fn name, then variadic args
-            signature: Signature::new(vec![ArgumentType::Any], Some(ArgumentType::Any)),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for ApplyFn {
+impl Function for UniqueByExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
-        if args.is_empty() {
-            return Err(JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse("apply() requires at least one argument".into()),
-            ));
-        }
+        self.signature.validate(args, ctx)?;
 
-        let first_arg = &args[0];
-        let additional_args = &args[1..];
+        let arr = args[1].as_array().unwrap();
 
-        // Check if it's a partial object
-        if let Some(obj) = first_arg.as_object() {
-            if obj.get("__partial__").map(|v| v.as_boolean()) == Some(Some(true)) {
-                // It's a partial - extract fn name and pre-filled args
-                let fn_name = obj.get("fn").and_then(|v| v.as_string()).ok_or_else(|| {
-                    JmespathError::new(
-                        ctx.expression,
-                        ctx.offset,
-                        ErrorReason::Parse("Invalid partial object: missing 'fn' field".into()),
-                    )
-                })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in unique_by_expr")?;
 
-                let prefilled = obj.get("args").and_then(|v| v.as_array()).ok_or_else(|| {
-                    JmespathError::new(
-                        ctx.expression,
-                        ctx.offset,
-                        ErrorReason::Parse("Invalid partial object: missing 'args' field".into()),
-                    )
-                })?;
+        let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut results = Vec::new();
 
-                // Build the full expression: fn_name(prefilled_args..., additional_args...)
-                return invoke_function(fn_name, prefilled, additional_args, ctx);
+        for item in arr {
+            let key_val = compiled.search(item.clone())?;
+            let key = value_to_string(&key_val);
+            if seen.insert(key) {
+                results.push(item.clone());
             }
         }
 
-        // If it's a string, treat as function name
-        if let Some(fn_name) = first_arg.as_string() {
-            return invoke_function(fn_name, &[], additional_args, ctx);
-        }
-
-        Err(JmespathError::new(
-            ctx.expression,
-            ctx.offset,
-            ErrorReason::Parse(
-                "apply() first argument must be a partial object or function name string".into(),
-            ),
-        ))
-    }
-}
-
-/// Helper to invoke a function by name with pre-filled and additional arguments
-fn invoke_function(
-    fn_name: &str,
-    prefilled: &[Rcvar],
-    additional: &[Rcvar],
-    ctx: &mut Context<'_>,
-) -> Result<Rcvar, JmespathError> {
-    // Build the argument list for the expression
-    let mut all_args_json: Vec<String> = Vec::new();
-
-    // Add pre-filled args as literals
-    for arg in prefilled {
-        let json = variable_to_json(arg);
-        all_args_json.push(format!("`{}`", serde_json::to_string(&json).unwrap()));
-    }
-
-    // Add additional args as literals
-    for arg in additional {
-        let json = variable_to_json(arg);
-        all_args_json.push(format!("`{}`", serde_json::to_string(&json).unwrap()));
+        Ok(Rc::new(Variable::Array(results)))
     }
-
-    // Build and execute the expression
-    let expr_str = format!("{}({})", fn_name, all_args_json.join(", "));
-
-    let compiled = ctx.runtime.compile(&expr_str).map_err(|e| {
-        JmespathError::new(
-            ctx.expression,
-            ctx.offset,
-            ErrorReason::Parse(format!(
-                "Failed to compile function call '{}': {}",
-                expr_str, e
-            )),
-        )
-    })?;
-
-    // Execute with null input since all args are literals
-    compiled.search(Rc::new(Variable::Null)).map_err(|e| {
-        JmespathError::new(
-            ctx.expression,
-            ctx.offset,
-            ErrorReason::Parse(format!("Failed to execute '{}': {}", fn_name, e)),
-        )
-    })
 }
 
 // =============================================================================
-// take_while(expr, array) -> array
+// flat_map_expr(expr, array) -> array
 // =============================================================================
 
-/// Take elements from the beginning of an array while the expression is truthy.
+/// Apply an expression to each element and flatten the results.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to process
+/// * `expr` - A JMESPath expression string that returns an array for each element
+/// * `array` - The array to flat-map over
 ///
 /// # Returns
-/// A new array containing elements from the start until the predicate returns false.
+/// A single array containing all elements from the results concatenated together.
 ///
 /// # Example
 /// ```text
-/// take_while('@ < `4`', [1, 2, 3, 5, 1, 2]) -> [1, 2, 3]
-/// take_while('@ > `0`', [3, 2, 1, 0, -1]) -> [3, 2, 1]
+/// flat_map_expr('tags', [{"tags": ["a", "b"]}, {"tags": ["c"]}]) -> ["a", "b", "c"]
+/// flat_map_expr('@', [[1, 2], [3, 4]]) -> [1, 2, 3, 4]
 /// ```
-pub struct TakeWhileFn {
+pub struct FlatMapExprFn {
     signature: Signature,
 }
 
-impl Default for TakeWhileFn {
+impl Default for FlatMapExprFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl TakeWhileFn {
+impl FlatMapExprFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for TakeWhileFn {
+impl Function for FlatMapExprFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in take_while: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in flat_map_expr")?;
 
         let mut results = Vec::new();
         for item in arr {
             let result = compiled.search(item.clone())?;
-            if is_truthy(&result) {
-                results.push(item.clone());
-            } else {
-                break;
+            match result.as_ref() {
+                Variable::Array(inner) => {
+                    results.extend(inner.iter().cloned());
+                }
+                Variable::Null => {
+                    // Skip nulls
+                }
+                _ => {
+                    results.push(result);
+                }
             }
         }
 
@@ -2010,399 +1970,4553 @@ impl Function for TakeWhileFn {
 }
 
 // =============================================================================
-// drop_while(expr, array) -> array
+// Helper functions
 // =============================================================================
 
-/// Drop elements from the beginning of an array while the expression is truthy.
+/// Convert a Variable to a string key for grouping/deduplication
+fn value_to_string(value: &Rcvar) -> String {
+    match value.as_ref() {
+        Variable::String(s) => s.clone(),
+        Variable::Number(n) => n.to_string(),
+        Variable::Bool(b) => b.to_string(),
+        Variable::Null => "null".to_string(),
+        _ => serde_json::to_string(&variable_to_json(value)).unwrap_or_default(),
+    }
+}
+
+/// Convert a Variable to a serde_json::Value for JSON serialization.
+///
+/// Handles all Variable types including nested arrays and objects.
+/// Expression references are converted to null.
+fn variable_to_json(value: &Rcvar) -> serde_json::Value {
+    match value.as_ref() {
+        Variable::String(s) => serde_json::Value::String(s.clone()),
+        Variable::Number(n) => serde_json::Value::Number(n.clone()),
+        Variable::Bool(b) => serde_json::Value::Bool(*b),
+        Variable::Null => serde_json::Value::Null,
+        Variable::Array(arr) => {
+            serde_json::Value::Array(arr.iter().map(variable_to_json).collect())
+        }
+        Variable::Object(obj) => {
+            let map: serde_json::Map<String, serde_json::Value> = obj
+                .iter()
+                .map(|(k, v)| (k.clone(), variable_to_json(v)))
+                .collect();
+            serde_json::Value::Object(map)
+        }
+        Variable::Expref(_) => serde_json::Value::Null,
+    }
+}
+
+/// Check if a value is truthy according to JMESPath semantics.
+///
+/// JMESPath truthiness rules:
+/// - `null` is falsy
+/// - `false` is falsy
+/// - Empty string `""` is falsy
+/// - Empty array `[]` is falsy
+/// - Empty object `{}` is falsy
+/// - All other values (numbers, non-empty strings/arrays/objects, true) are truthy
+fn is_truthy(value: &Rcvar) -> bool {
+    match value.as_ref() {
+        Variable::Null => false,
+        Variable::Bool(b) => *b,
+        Variable::String(s) => !s.is_empty(),
+        Variable::Array(a) => !a.is_empty(),
+        Variable::Object(o) => !o.is_empty(),
+        Variable::Number(_) => true,
+        Variable::Expref(_) => true,
+    }
+}
+
+/// Compare two values for sorting purposes.
+///
+/// Comparison rules:
+/// - Numbers are compared numerically
+/// - Strings are compared lexicographically
+/// - `null` sorts before all other values
+/// - Mixed types compare as equal (stable sort preserves original order)
+fn compare_values(a: &Rcvar, b: &Rcvar) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    match (a.as_ref(), b.as_ref()) {
+        (Variable::Number(an), Variable::Number(bn)) => {
+            let a_f = an.as_f64().unwrap_or(0.0);
+            let b_f = bn.as_f64().unwrap_or(0.0);
+            a_f.partial_cmp(&b_f).unwrap_or(Ordering::Equal)
+        }
+        (Variable::String(as_), Variable::String(bs)) => as_.cmp(bs),
+        (Variable::Null, Variable::Null) => Ordering::Equal,
+        (Variable::Null, _) => Ordering::Less,
+        (_, Variable::Null) => Ordering::Greater,
+        _ => Ordering::Equal,
+    }
+}
+
+// =============================================================================
+// reject(expr, array) -> array (inverse of filter_expr)
+// =============================================================================
+
+/// Filter an array, keeping elements where the expression is falsy (inverse of filter_expr).
 ///
 /// # Arguments
 /// * `expr` - A JMESPath expression string that returns a truthy/falsy value
-/// * `array` - The array to process
+/// * `array` - The array to filter
 ///
 /// # Returns
-/// A new array with leading elements removed until the predicate returns false.
+/// A new array containing only elements where the expression was falsy.
 ///
 /// # Example
 /// ```text
-/// drop_while('@ < `4`', [1, 2, 3, 5, 1, 2]) -> [5, 1, 2]
-/// drop_while('@ > `0`', [3, 2, 1, 0, -1]) -> [0, -1]
+/// reject('@ > `2`', [1, 2, 3, 4]) -> [1, 2]
+/// reject('active', [{"active": true}, {"active": false}]) -> [{"active": false}]
 /// ```
-pub struct DropWhileFn {
+pub struct RejectFn {
     signature: Signature,
 }
 
-impl Default for DropWhileFn {
+impl Default for RejectFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl DropWhileFn {
+impl RejectFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Array], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
         }
     }
 }
 
-impl Function for DropWhileFn {
+impl Function for RejectFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
         let arr = args[1].as_array().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in drop_while: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in reject")?;
 
-        let mut dropping = true;
-        let mut results = Vec::new();
+        let mut result = Vec::new();
         for item in arr {
-            if dropping {
-                let result = compiled.search(item.clone())?;
-                if !is_truthy(&result) {
-                    dropping = false;
-                    results.push(item.clone());
-                }
-            } else {
-                results.push(item.clone());
+            let matched = compiled.search(item).map_err(|e| {
+                JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
+            })?;
+            // Keep items where expression is falsy (inverse of filter)
+            if !is_truthy(&matched) {
+                result.push(item.clone());
             }
         }
 
-        Ok(Rc::new(Variable::Array(results)))
+        Ok(Rc::new(Variable::Array(result)))
     }
 }
 
 // =============================================================================
-// zip_with(expr, array1, array2) -> array
+// map_keys(expr, object) -> object
 // =============================================================================
 
-/// Zip two arrays together using a custom combiner expression.
+use std::collections::BTreeMap;
+
+/// Transform the keys of an object by applying an expression to each key.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression that receives `[element1, element2]` as input
-/// * `array1` - The first array
-/// * `array2` - The second array
+/// * `expr` - A JMESPath expression string that transforms each key (key is passed as `@`)
+/// * `object` - The object whose keys to transform
 ///
 /// # Returns
-/// A new array with elements combined using the expression.
-/// The result length is the minimum of the two input array lengths.
+/// A new object with transformed keys and original values.
 ///
 /// # Example
 /// ```text
-/// zip_with('add([0], [1])', [1, 2, 3], [10, 20, 30]) -> [11, 22, 33]
-/// zip_with('[0] * [1]', [2, 3, 4], [5, 6, 7]) -> [10, 18, 28]
+/// map_keys('upper(@)', {"a": 1, "b": 2}) -> {"A": 1, "B": 2}
+/// map_keys('@ & "_suffix"', {"foo": 1}) -> {"foo_suffix": 1}
 /// ```
-pub struct ZipWithFn {
+pub struct MapKeysFn {
     signature: Signature,
 }
 
-impl Default for ZipWithFn {
+impl Default for MapKeysFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl ZipWithFn {
+impl MapKeysFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(
-                vec![
-                    ArgumentType::String,
-                    ArgumentType::Array,
-                    ArgumentType::Array,
-                ],
-                None,
-            ),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Object], None),
         }
     }
 }
 
-impl Function for ZipWithFn {
+impl Function for MapKeysFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
-        let arr1 = args[1].as_array().unwrap();
-        let arr2 = args[2].as_array().unwrap();
+        let obj = args[1].as_object().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in zip_with: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in map_keys")?;
 
-        let min_len = arr1.len().min(arr2.len());
-        let mut results = Vec::with_capacity(min_len);
+        let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
+        for (key, value) in obj.iter() {
+            // Apply expression to the key
+            let key_var = Rc::new(Variable::String(key.clone()));
+            let new_key = compiled.search(&key_var).map_err(|e| {
+                JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
+            })?;
 
-        for i in 0..min_len {
-            // Create a pair array [element1, element2] as input to the expression
-            let pair = Rc::new(Variable::Array(vec![arr1[i].clone(), arr2[i].clone()]));
-            let result = compiled.search(pair)?;
-            results.push(result);
+            let new_key_str = match &*new_key {
+                Variable::String(s) => s.clone(),
+                Variable::Number(n) => n.to_string(),
+                _ => key.clone(), // Keep original if result isn't a string/number
+            };
+
+            result.insert(new_key_str, value.clone());
         }
 
-        Ok(Rc::new(Variable::Array(results)))
+        Ok(Rc::new(Variable::Object(result)))
     }
 }
 
 // =============================================================================
-// walk(expr, value) -> value (recursive transformation)
+// map_values(expr, object) -> object
 // =============================================================================
 
-/// Recursively apply a transformation to every component of a data structure.
-///
-/// The transformation is applied bottom-up: for arrays and objects, children
-/// are transformed first, then the expression is applied to the result.
+/// Transform the values of an object by applying an expression to each value.
 ///
 /// # Arguments
-/// * `expr` - A JMESPath expression string to apply at each node
-/// * `value` - The value to walk
+/// * `expr` - A JMESPath expression string that transforms each value (value is passed as `@`)
+/// * `object` - The object whose values to transform
 ///
 /// # Returns
-/// The transformed value.
+/// A new object with original keys and transformed values.
 ///
 /// # Example
 /// ```text
-/// walk('if(is_array(@), sort(@), @)', {a: [3, 1, 2]}) -> {a: [1, 2, 3]}
-/// walk('if(is_object(@), merge(@, {visited: `true`}), @)', data) -> all objects get visited: true
+/// map_values('@ * `2`', {"a": 1, "b": 2}) -> {"a": 2, "b": 4}
+/// map_values('upper(@)', {"x": "hello", "y": "world"}) -> {"x": "HELLO", "y": "WORLD"}
 /// ```
-pub struct WalkFn {
+pub struct MapValuesFn {
     signature: Signature,
 }
 
-impl Default for WalkFn {
+impl Default for MapValuesFn {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl WalkFn {
+impl MapValuesFn {
     pub fn new() -> Self {
         Self {
-            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Any], None),
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Object], None),
         }
     }
 }
 
-/// Recursively walk a value, applying the expression bottom-up
-fn walk_value(value: &Rcvar, compiled: &jmespath::Expression<'_>) -> Result<Rcvar, JmespathError> {
-    match &**value {
-        Variable::Array(arr) => {
-            // First, recursively walk all elements
-            let walked_elements: Result<Vec<Rcvar>, _> =
-                arr.iter().map(|elem| walk_value(elem, compiled)).collect();
-            let new_array = Rc::new(Variable::Array(walked_elements?));
-            // Then apply the expression to the array itself
-            compiled.search(new_array)
-        }
-        Variable::Object(obj) => {
-            // First, recursively walk all values
-            let walked_entries: Result<std::collections::BTreeMap<String, Rcvar>, _> = obj
-                .iter()
-                .map(|(k, v)| walk_value(v, compiled).map(|walked| (k.clone(), walked)))
-                .collect();
-            let new_object = Rc::new(Variable::Object(walked_entries?));
-            // Then apply the expression to the object itself
-            compiled.search(new_object)
-        }
-        // For scalars (string, number, bool, null), just apply the expression
-        _ => compiled.search(value.clone()),
-    }
-}
-
-impl Function for WalkFn {
+impl Function for MapValuesFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let expr_str = args[0].as_string().unwrap();
+        let obj = args[1].as_object().unwrap();
 
-        let compiled = ctx.runtime.compile(expr_str).map_err(|e| {
-            JmespathError::new(
-                ctx.expression,
-                ctx.offset,
-                ErrorReason::Parse(format!("Invalid expression in walk: {}", e)),
-            )
-        })?;
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in map_values")?;
 
-        walk_value(&args[1], &compiled)
+        let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
+        for (key, value) in obj.iter() {
+            // Apply expression to the value
+            let new_value = compiled.search(value).map_err(|e| {
+                JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
+            })?;
+
+            result.insert(key.clone(), new_value);
+        }
+
+        Ok(Rc::new(Variable::Object(result)))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+// =============================================================================
+// pick_by(expr, object) -> object
+// omit_by(expr, object) -> object
+// =============================================================================
 
-    fn setup() -> Runtime {
-        let mut runtime = Runtime::new();
-        runtime.register_builtin_functions();
-        register(&mut runtime);
-        runtime
-    }
+/// Keep the object entries for which an expression is truthy.
+///
+/// The expression is evaluated against a `{key, value}` object for each entry,
+/// so predicates can inspect the key, the value, or both.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string evaluated against `{key, value}`
+/// * `object` - The object to filter
+///
+/// # Returns
+/// A new object containing only the matching entries.
+///
+/// # Example
+/// ```text
+/// pick_by('value != `null`', {"a": 1, "b": null}) -> {"a": 1}
+/// pick_by('starts_with(key, `"x_"`)', {"x_a": 1, "b": 2}) -> {"x_a": 1}
+/// ```
+pub struct PickByFn {
+    signature: Signature,
+}
 
-    #[test]
-    fn test_map_expr_field() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"name": "Alice"}, {"name": "Bob"}]"#).unwrap();
-        let expr = runtime.compile("map_expr('name', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
-        assert_eq!(arr[0].as_string().unwrap(), "Alice");
-        assert_eq!(arr[1].as_string().unwrap(), "Bob");
+impl Default for PickByFn {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    #[test]
-    fn test_map_expr_transform() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"["hello", "world"]"#).unwrap();
-        let expr = runtime.compile("map_expr('length(@)', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr[0].as_number().unwrap(), 5.0);
-        assert_eq!(arr[1].as_number().unwrap(), 5.0);
+impl PickByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Object], None),
+        }
     }
+}
 
-    #[test]
-    fn test_filter_expr() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"age": 25}, {"age": 17}, {"age": 30}]"#).unwrap();
-        let expr = runtime.compile("filter_expr('age >= `18`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
+impl Function for PickByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        filter_object_entries(args, ctx, true)
     }
+}
 
-    #[test]
-    fn test_filter_expr_empty() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
-        let expr = runtime.compile("filter_expr('@ > `10`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 0);
-    }
+/// Drop the object entries for which an expression is truthy.
+///
+/// The inverse of `pick_by`: the expression is evaluated against a
+/// `{key, value}` object for each entry and matching entries are removed.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string evaluated against `{key, value}`
+/// * `object` - The object to filter
+///
+/// # Returns
+/// A new object without the matching entries.
+///
+/// # Example
+/// ```text
+/// omit_by('value == `null`', {"a": 1, "b": null}) -> {"a": 1}
+/// omit_by('starts_with(key, `"_"`)', {"_id": 1, "name": "x"}) -> {"name": "x"}
+/// ```
+pub struct OmitByFn {
+    signature: Signature,
+}
 
-    #[test]
-    fn test_any_expr_true() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"active": false}, {"active": true}]"#).unwrap();
-        let expr = runtime.compile("any_expr('active', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(result.as_boolean().unwrap());
+impl Default for OmitByFn {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    #[test]
-    fn test_any_expr_false() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"active": false}, {"active": false}]"#).unwrap();
-        let expr = runtime.compile("any_expr('active', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(!result.as_boolean().unwrap());
+impl OmitByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Object], None),
+        }
     }
+}
 
-    #[test]
-    fn test_all_expr_true() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"active": true}, {"active": true}]"#).unwrap();
-        let expr = runtime.compile("all_expr('active', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(result.as_boolean().unwrap());
+impl Function for OmitByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        filter_object_entries(args, ctx, false)
     }
+}
 
-    #[test]
-    fn test_all_expr_false() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"active": true}, {"active": false}]"#).unwrap();
+/// Shared implementation of `pick_by` / `omit_by`: keep entries whose
+/// predicate result is truthy when `keep` is true, falsy otherwise.
+fn filter_object_entries(
+    args: &[Rcvar],
+    ctx: &mut Context<'_>,
+    keep: bool,
+) -> Result<Rcvar, JmespathError> {
+    let obj = args[1].as_object().unwrap();
+
+    let name = if keep { "pick_by" } else { "omit_by" };
+    let compiled = compile_expr_arg(&args[0], ctx, &format!("Invalid expression in {}", name))?;
+
+    let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
+    for (key, value) in obj.iter() {
+        let mut entry: BTreeMap<String, Rcvar> = BTreeMap::new();
+        entry.insert("key".to_string(), Rc::new(Variable::String(key.clone())));
+        entry.insert("value".to_string(), value.clone());
+        let entry_var = Rc::new(Variable::Object(entry));
+
+        let matched = compiled.search(&entry_var).map_err(|e| {
+            JmespathError::new(ctx.expression, 0, ErrorReason::Parse(e.to_string()))
+        })?;
+
+        if is_truthy(&matched) == keep {
+            result.insert(key.clone(), value.clone());
+        }
+    }
+
+    Ok(Rc::new(Variable::Object(result)))
+}
+
+// =============================================================================
+// order_by(array, criteria) -> array
+// =============================================================================
+
+/// Sort an array by multiple criteria with direction control.
+///
+/// # Arguments
+/// * `array` - The array to sort
+/// * `criteria` - Array of [field, direction] pairs where direction is "asc" or "desc"
+///   Use JMESPath literal syntax with backticks: `` `[["field", "asc"]]` ``
+///
+/// # Returns
+/// A new sorted array.
+///
+/// # Example
+/// ```text
+/// order_by(@, `[["name", "asc"]]`)  // Sort by name ascending
+/// order_by(@, `[["age", "desc"], ["name", "asc"]]`)  // Sort by age desc, then name asc
+/// ```
+pub struct OrderByFn {
+    signature: Signature,
+}
+
+impl Default for OrderByFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for OrderByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+        let criteria = args[1].as_array().unwrap();
+
+        if arr.is_empty() {
+            return Ok(Rc::new(Variable::Array(vec![])));
+        }
+
+        // Parse criteria: each element should be [field, direction]
+        let mut sort_specs: Vec<(String, bool)> = Vec::new(); // (field, ascending)
+        for criterion in criteria {
+            let crit_arr = criterion.as_array().ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse("Each criterion must be an array [field, direction]".into()),
+                )
+            })?;
+
+            if crit_arr.len() < 2 {
+                return Err(JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse("Each criterion must have [field, direction]".into()),
+                ));
+            }
+
+            let field = crit_arr[0].as_string().ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse("Field name must be a string".into()),
+                )
+            })?;
+
+            let direction = crit_arr[1].as_string().ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse("Direction must be 'asc' or 'desc'".into()),
+                )
+            })?;
+
+            let ascending = match direction.to_lowercase().as_str() {
+                "asc" | "ascending" => true,
+                "desc" | "descending" => false,
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse("Direction must be 'asc' or 'desc'".into()),
+                    ));
+                }
+            };
+
+            sort_specs.push((field.to_string(), ascending));
+        }
+
+        // Clone and sort the array
+        let mut result: Vec<Rcvar> = arr.clone();
+        result.sort_by(|a, b| {
+            for (field, ascending) in &sort_specs {
+                let a_val = a
+                    .as_object()
+                    .and_then(|o| o.get(field))
+                    .cloned()
+                    .unwrap_or_else(|| Rc::new(Variable::Null));
+                let b_val = b
+                    .as_object()
+                    .and_then(|o| o.get(field))
+                    .cloned()
+                    .unwrap_or_else(|| Rc::new(Variable::Null));
+
+                let cmp = compare_values(&a_val, &b_val);
+                if cmp != std::cmp::Ordering::Equal {
+                    return if *ascending { cmp } else { cmp.reverse() };
+                }
+            }
+            std::cmp::Ordering::Equal
+        });
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// reduce_expr(expr, array, initial) -> any
+// =============================================================================
+
+/// Reduce an array to a single value using an expression.
+///
+/// The expression is evaluated with a special context where:
+/// - `accumulator` is the current accumulated value
+/// - `current` is the current element being processed
+/// - `index` is the current index (0-based)
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string. Use `accumulator` and `current` in the expression.
+/// * `array` - The array to reduce
+/// * `initial` - The initial value for the accumulator
+///
+/// # Returns
+/// The final accumulated value.
+///
+/// # Example
+/// ```text
+/// reduce_expr('accumulator + current', [1, 2, 3], `0`)  // Sum: 6
+/// reduce_expr('max([accumulator, current])', [3, 1, 4], `0`)  // Max: 4
+/// ```
+pub struct ReduceExprFn {
+    signature: Signature,
+}
+
+impl Default for ReduceExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReduceExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Any],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for ReduceExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+        let initial = args[2].clone();
+
+        if arr.is_empty() {
+            return Ok(initial);
+        }
+
+        // Compile the expression
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid reduce expression")?;
+
+        let mut accumulator = initial;
+
+        for (idx, item) in arr.iter().enumerate() {
+            // Create context object with accumulator, current, and index
+            let mut context_map: std::collections::BTreeMap<String, Rcvar> =
+                std::collections::BTreeMap::new();
+            context_map.insert("accumulator".to_string(), accumulator.clone());
+            context_map.insert("current".to_string(), item.clone());
+            context_map.insert(
+                "index".to_string(),
+                Rc::new(Variable::Number(serde_json::Number::from(idx as i64))),
+            );
+            let context_var = Rc::new(Variable::Object(context_map));
+
+            accumulator = compiled.search(&context_var).map_err(|e| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse(format!("Reduce expression evaluation error: {}", e)),
+                )
+            })?;
+        }
+
+        Ok(accumulator)
+    }
+}
+
+// =============================================================================
+// scan_expr(expr, array, initial) -> array
+// =============================================================================
+
+/// Scan (cumulative reduce) an array, returning all intermediate accumulated values.
+///
+/// Similar to reduce_expr, but returns an array of all intermediate results.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string. Use `accumulator` and `current` in the expression.
+/// * `array` - The array to scan
+/// * `initial` - The initial value for the accumulator
+///
+/// # Returns
+/// An array of all accumulated values (including each intermediate step).
+///
+/// # Example
+/// ```text
+/// scan_expr('accumulator + current', [1, 2, 3], `0`)  // Running sum: [1, 3, 6]
+/// ```
+pub struct ScanExprFn {
+    signature: Signature,
+}
+
+impl Default for ScanExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScanExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Any],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for ScanExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+        let initial = args[2].clone();
+
+        if arr.is_empty() {
+            return Ok(Rc::new(Variable::Array(vec![])));
+        }
+
+        // Compile the expression
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid scan expression")?;
+
+        let mut accumulator = initial;
+        let mut results: Vec<Rcvar> = Vec::with_capacity(arr.len());
+
+        for (idx, item) in arr.iter().enumerate() {
+            // Create context object with accumulator, current, and index
+            let mut context_map: std::collections::BTreeMap<String, Rcvar> =
+                std::collections::BTreeMap::new();
+            context_map.insert("accumulator".to_string(), accumulator.clone());
+            context_map.insert("current".to_string(), item.clone());
+            context_map.insert(
+                "index".to_string(),
+                Rc::new(Variable::Number(serde_json::Number::from(idx as i64))),
+            );
+            let context_var = Rc::new(Variable::Object(context_map));
+
+            accumulator = compiled.search(&context_var).map_err(|e| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse(format!("Scan expression evaluation error: {}", e)),
+                )
+            })?;
+
+            results.push(accumulator.clone());
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// window_expr(expr, array) -> array
+// =============================================================================
+
+/// Map over an array where the expression can see each element's neighbours.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string. Use `prev`, `current`, `next` and `index` in the expression.
+/// * `array` - The array to map over
+///
+/// # Returns
+/// An array with one result per element. `prev` is null for the first element
+/// and `next` is null for the last.
+///
+/// # Example
+/// ```text
+/// window_expr('prev && subtract(current, prev)', [1, 4, 9])  // Deltas: [null, 3, 5]
+/// window_expr('current == prev', [1, 1, 2])  // Repeats: [false, true, false]
+/// ```
+pub struct WindowExprFn {
+    signature: Signature,
+}
+
+impl Default for WindowExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for WindowExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid window expression")?;
+
+        let null = Rc::new(Variable::Null);
+        let mut results: Vec<Rcvar> = Vec::with_capacity(arr.len());
+        for (idx, item) in arr.iter().enumerate() {
+            let neighbour = |i: Option<usize>| {
+                i.and_then(|i| arr.get(i))
+                    .cloned()
+                    .unwrap_or_else(|| null.clone())
+            };
+            let mut context_map: std::collections::BTreeMap<String, Rcvar> =
+                std::collections::BTreeMap::new();
+            context_map.insert("prev".to_string(), neighbour(idx.checked_sub(1)));
+            context_map.insert("current".to_string(), item.clone());
+            context_map.insert("next".to_string(), neighbour(Some(idx + 1)));
+            context_map.insert(
+                "index".to_string(),
+                Rc::new(Variable::Number(serde_json::Number::from(idx as i64))),
+            );
+            let context_var = Rc::new(Variable::Object(context_map));
+
+            results.push(compiled.search(&context_var)?);
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// iterate_expr(expr, value, max_iters) -> array
+// fixpoint_expr(expr, value, max_iters?) -> any
+// =============================================================================
+
+/// Default iteration limit for `fixpoint_expr`.
+const FIXPOINT_MAX_ITERS: usize = 1000;
+
+/// Parse an iteration limit argument.
+fn max_iters_arg(arg: &Rcvar, name: &str, ctx: &Context<'_>) -> Result<usize, JmespathError> {
+    arg.as_number()
+        .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+        .map(|n| n as usize)
+        .ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!(
+                    "{}() max_iters must be a non-negative integer",
+                    name
+                )),
+            )
+        })
+}
+
+/// Apply an expression repeatedly, collecting each value along the way.
+///
+/// Starts with `value` and applies `expr` to the latest result up to
+/// `max_iters` times. Stops early once the expression returns `null` or the
+/// same value it was given, which makes it a natural fit for following
+/// `parent` links up to a root.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string applied to the previous value
+/// * `value` - The starting value
+/// * `max_iters` - The maximum number of applications
+///
+/// # Returns
+/// The sequence of values, starting with `value` (nulls are not included).
+///
+/// # Example
+/// ```text
+/// iterate_expr('[]', `[[1, [2]]]`, `10`)  // -> [[[1, [2]]], [1, [2]], [1, 2]]
+/// iterate_expr('parent', node, `100`)  // -> [node, node.parent, ...]
+/// ```
+pub struct IterateExprFn {
+    signature: Signature,
+}
+
+impl Default for IterateExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IterateExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Any, ArgumentType::Number],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for IterateExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let max_iters = max_iters_arg(&args[2], "iterate_expr", ctx)?;
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in iterate_expr")?;
+
+        let mut current = args[1].clone();
+        let mut results = vec![current.clone()];
+        for _ in 0..max_iters {
+            let next = compiled.search(current.clone())?;
+            if next.is_null() || next == current {
+                break;
+            }
+            results.push(next.clone());
+            current = next;
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+/// Apply an expression until its result stops changing.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string applied to the previous value
+/// * `value` - The starting value
+/// * `max_iters` - The maximum number of applications (default 1000)
+///
+/// # Returns
+/// The first value for which `expr` returns the value unchanged. It is an
+/// error if that does not happen within `max_iters` applications.
+///
+/// # Example
+/// ```text
+/// fixpoint_expr('[]', `[1, [2, [3]]]`)  // -> [1, 2, 3]
+/// ```
+pub struct FixpointExprFn {
+    signature: Signature,
+}
+
+impl Default for FixpointExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixpointExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Any],
+                Some(ArgumentType::Number),
+            ),
+        }
+    }
+}
+
+impl Function for FixpointExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if args.len() > 3 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("fixpoint_expr() takes 2 or 3 arguments".into()),
+            ));
+        }
+
+        let max_iters = match args.get(2) {
+            Some(arg) => max_iters_arg(arg, "fixpoint_expr", ctx)?,
+            None => FIXPOINT_MAX_ITERS,
+        };
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in fixpoint_expr")?;
+
+        let mut current = args[1].clone();
+        for _ in 0..max_iters {
+            let next = compiled.search(current.clone())?;
+            if next == current {
+                return Ok(current);
+            }
+            current = next;
+        }
+
+        Err(JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(format!(
+                "fixpoint_expr() did not converge within {} iterations",
+                max_iters
+            )),
+        ))
+    }
+}
+
+// =============================================================================
+// partial(fn_name, ...args) -> partial object
+// =============================================================================
+
+/// Create a partial function with some arguments pre-filled.
+///
+/// Returns an object that can be used with `apply()` to invoke the function
+/// with the remaining arguments. This enables currying and reusable function
+/// configurations.
+///
+/// # Arguments
+/// * `fn_name` - The name of the function to partially apply
+/// * `...args` - Zero or more arguments to pre-fill
+///
+/// # Returns
+/// A partial object: `{"__partial__": true, "fn": "fn_name", "args": [...]}`
+///
+/// # Examples
+///
+/// ## Basic Usage
+/// ```text
+/// partial('join', `"-"`)  // Create a dash-joiner
+/// // -> {"__partial__": true, "fn": "join", "args": ["-"]}
+/// ```
+///
+/// ## Reusable String Operations
+/// ```text
+/// // Create a comma-joiner for CSV-like output
+/// csv_joiner = partial('join', `","`)
+/// apply(csv_joiner, `["name", "age", "city"]`)  // -> "name,age,city"
+/// ```
+///
+/// ## Pre-configured Search
+/// ```text
+/// // Create a contains checker with pre-filled haystack
+/// has_hello = partial('contains', `"hello world"`)
+/// apply(has_hello, `"world"`)  // -> true
+/// apply(has_hello, `"xyz"`)    // -> false
+/// ```
+///
+/// ## Date Formatting
+/// ```text
+/// // Create a reusable ISO date formatter
+/// iso_formatter = partial('format_date', `"%Y-%m-%d"`)
+/// apply(iso_formatter, `"2024-01-15T10:30:00Z"`)  // -> "2024-01-15"
+/// ```
+pub struct PartialFn {
+    #[allow(dead_code)]
+    signature: Signature,
+}
+
+impl Default for PartialFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialFn {
+    pub fn new() -> Self {
+        Self {
+            // At least function name required, then variadic args
+            signature: Signature::new(vec![ArgumentType::String], Some(ArgumentType::Any)),
+        }
+    }
+}
+
+impl Function for PartialFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        if args.is_empty() {
+            return Err(JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("partial() requires at least a function name".into()),
+            ));
+        }
+
+        let fn_name = args[0].as_string().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(
+                    "partial() first argument must be a function name string".into(),
+                ),
+            )
+        })?;
+
+        // Collect the pre-filled arguments
+        let prefilled_args: Vec<serde_json::Value> =
+            args[1..].iter().map(variable_to_json).collect();
+
+        // Create the partial object
+        let mut partial_obj = serde_json::Map::new();
+        partial_obj.insert("__partial__".to_string(), serde_json::Value::Bool(true));
+        partial_obj.insert(
+            "fn".to_string(),
+            serde_json::Value::String(fn_name.to_string()),
+        );
+        partial_obj.insert("args".to_string(), serde_json::Value::Array(prefilled_args));
+
+        Ok(Rc::new(
+            Variable::from_json(&serde_json::to_string(&partial_obj).unwrap()).unwrap(),
+        ))
+    }
+}
+
+// =============================================================================
+// apply(partial_or_fn, ...args) -> result
+// =============================================================================
+
+/// Apply a partial function or regular function with arguments.
+///
+/// If the first argument is a partial object (from `partial()`), combines
+/// the pre-filled arguments with the provided arguments and invokes the function.
+/// If it's a string, treats it as a function name and invokes directly.
+///
+/// This function is the complement to `partial()` - use `partial()` to create
+/// reusable function configurations, then `apply()` to execute them.
+///
+/// # Arguments
+/// * `partial_or_fn` - Either a partial object or a function name string
+/// * `...args` - Additional arguments to pass to the function
+///
+/// # Returns
+/// The result of invoking the function with all arguments.
+///
+/// # Examples
+///
+/// ## Apply a Partial
+/// ```text
+/// // Create and apply a dash-joiner
+/// apply(partial('join', `"-"`), `["a", "b", "c"]`)  // -> "a-b-c"
+/// ```
+///
+/// ## Direct Function Call by Name
+/// ```text
+/// // Call any function by its string name
+/// apply('length', `"hello"`)  // -> 5
+/// apply('upper', `"hello"`)   // -> "HELLO"
+/// ```
+///
+/// ## Dynamic Function Dispatch
+/// ```text
+/// // Useful when the function name comes from data or configuration
+/// fn_name = 'sum'
+/// apply(fn_name, `[1, 2, 3, 4]`)  // -> 10
+/// ```
+///
+/// ## Combining with Partials
+/// ```text
+/// // Pre-configure a contains check, then apply multiple times
+/// checker = partial('contains', `"The quick brown fox"`)
+/// apply(checker, `"quick"`)  // -> true
+/// apply(checker, `"slow"`)   // -> false
+/// ```
+///
+/// ## Building Pipelines
+/// ```text
+/// // Create specialized validators
+/// email_pattern = partial('regex_match', `"^[a-z]+@[a-z]+\\.[a-z]+$"`)
+/// apply(email_pattern, `"test@example.com"`)  // -> true
+/// ```
+pub struct ApplyFn {
+    #[allow(dead_code)]
+    signature: Signature,
+}
+
+impl Default for ApplyFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApplyFn {
+    pub fn new() -> Self {
+        Self {
+            // First arg is partial or fn name, then variadic args
+            signature: Signature::new(vec![ArgumentType::Any], Some(ArgumentType::Any)),
+        }
+    }
+}
+
+impl Function for ApplyFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        if args.is_empty() {
+            return Err(JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("apply() requires at least one argument".into()),
+            ));
+        }
+
+        let first_arg = &args[0];
+        let additional_args = &args[1..];
+
+        // Check if it's a partial object
+        if let Some(obj) = first_arg.as_object() {
+            if obj.get("__partial__").map(|v| v.as_boolean()) == Some(Some(true)) {
+                // It's a partial - extract fn name and pre-filled args
+                let fn_name = obj.get("fn").and_then(|v| v.as_string()).ok_or_else(|| {
+                    JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse("Invalid partial object: missing 'fn' field".into()),
+                    )
+                })?;
+
+                let prefilled = obj.get("args").and_then(|v| v.as_array()).ok_or_else(|| {
+                    JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse("Invalid partial object: missing 'args' field".into()),
+                    )
+                })?;
+
+                // Build the full expression: fn_name(prefilled_args..., additional_args...)
+                return invoke_function(fn_name, prefilled, additional_args, ctx);
+            }
+        }
+
+        // If it's a string, treat as function name
+        if let Some(fn_name) = first_arg.as_string() {
+            return invoke_function(fn_name, &[], additional_args, ctx);
+        }
+
+        Err(JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(
+                "apply() first argument must be a partial object or function name string".into(),
+            ),
+        ))
+    }
+}
+
+/// Helper to invoke a function by name with pre-filled and additional arguments
+fn invoke_function(
+    fn_name: &str,
+    prefilled: &[Rcvar],
+    additional: &[Rcvar],
+    ctx: &mut Context<'_>,
+) -> Result<Rcvar, JmespathError> {
+    // Build the argument list for the expression
+    let mut all_args_json: Vec<String> = Vec::new();
+
+    // Add pre-filled args as literals
+    for arg in prefilled {
+        let json = variable_to_json(arg);
+        all_args_json.push(format!("`{}`", serde_json::to_string(&json).unwrap()));
+    }
+
+    // Add additional args as literals
+    for arg in additional {
+        let json = variable_to_json(arg);
+        all_args_json.push(format!("`{}`", serde_json::to_string(&json).unwrap()));
+    }
+
+    // Build and execute the expression
+    let expr_str = format!("{}({})", fn_name, all_args_json.join(", "));
+
+    let compiled = ctx
+        .runtime
+        .compile(&expr_str)
+        .map(NestedExpression::new)
+        .map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!(
+                    "Failed to compile function call '{}': {}",
+                    expr_str, e
+                )),
+            )
+        })?;
+
+    // Execute with null input since all args are literals
+    compiled.search(Rc::new(Variable::Null)).map_err(|e| {
+        JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(format!("Failed to execute '{}': {}", fn_name, e)),
+        )
+    })
+}
+
+// =============================================================================
+// split_when(expr, array) -> array of arrays
+// =============================================================================
+
+/// Split an array into chunks, starting a new chunk at each element where the expression is truthy.
+///
+/// The element that triggers the split becomes the first element of the new
+/// chunk. A truthy first element does not produce an empty leading chunk.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string evaluated against each element
+/// * `array` - The array to split
+///
+/// # Returns
+/// An array of non-empty chunks in original order.
+///
+/// # Example
+/// ```text
+/// split_when('type == `"login"`', [{"type": "login"}, {"type": "click"}, {"type": "login"}])
+///   -> [[{"type": "login"}, {"type": "click"}], [{"type": "login"}]]
+/// split_when('gap > `1800`', events)  // sessionize by idle gap
+/// ```
+pub struct SplitWhenFn {
+    signature: Signature,
+}
+
+impl Default for SplitWhenFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SplitWhenFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for SplitWhenFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in split_when")?;
+
+        let mut chunks: Vec<Rcvar> = Vec::new();
+        let mut current: Vec<Rcvar> = Vec::new();
+
+        for item in arr {
+            let result = compiled.search(item.clone())?;
+            if is_truthy(&result) && !current.is_empty() {
+                chunks.push(Rc::new(Variable::Array(std::mem::take(&mut current))));
+            }
+            current.push(item.clone());
+        }
+        if !current.is_empty() {
+            chunks.push(Rc::new(Variable::Array(current)));
+        }
+
+        Ok(Rc::new(Variable::Array(chunks)))
+    }
+}
+
+// =============================================================================
+// chunk_by(expr, array) -> array of arrays
+// =============================================================================
+
+/// Group adjacent elements that share the same expression result.
+///
+/// Unlike `group_by_expr`, only consecutive elements are grouped, so the
+/// same key can appear in several chunks and the original order is preserved.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that extracts a key from each element
+/// * `array` - The array to chunk
+///
+/// # Returns
+/// An array of chunks, each holding a run of elements with equal keys.
+///
+/// # Example
+/// ```text
+/// chunk_by('@ > `0`', [1, 2, -1, -2, 3]) -> [[1, 2], [-1, -2], [3]]
+/// chunk_by('user', events)  // runs of events by the same user
+/// ```
+pub struct ChunkByFn {
+    signature: Signature,
+}
+
+impl Default for ChunkByFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for ChunkByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in chunk_by")?;
+
+        let mut chunks: Vec<Rcvar> = Vec::new();
+        let mut current: Vec<Rcvar> = Vec::new();
+        let mut current_key: Option<Rcvar> = None;
+
+        for item in arr {
+            let key = compiled.search(item.clone())?;
+            if current_key.as_ref() != Some(&key) && !current.is_empty() {
+                chunks.push(Rc::new(Variable::Array(std::mem::take(&mut current))));
+            }
+            current.push(item.clone());
+            current_key = Some(key);
+        }
+        if !current.is_empty() {
+            chunks.push(Rc::new(Variable::Array(current)));
+        }
+
+        Ok(Rc::new(Variable::Array(chunks)))
+    }
+}
+
+// =============================================================================
+// take_while(expr, array) -> array
+// =============================================================================
+
+/// Take elements from the beginning of an array while the expression is truthy.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to process
+///
+/// # Returns
+/// A new array containing elements from the start until the predicate returns false.
+///
+/// # Example
+/// ```text
+/// take_while('@ < `4`', [1, 2, 3, 5, 1, 2]) -> [1, 2, 3]
+/// take_while('@ > `0`', [3, 2, 1, 0, -1]) -> [3, 2, 1]
+/// ```
+pub struct TakeWhileFn {
+    signature: Signature,
+}
+
+impl Default for TakeWhileFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TakeWhileFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for TakeWhileFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in take_while")?;
+
+        let mut results = Vec::new();
+        for item in arr {
+            let result = compiled.search(item.clone())?;
+            if is_truthy(&result) {
+                results.push(item.clone());
+            } else {
+                break;
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// drop_while(expr, array) -> array
+// =============================================================================
+
+/// Drop elements from the beginning of an array while the expression is truthy.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to process
+///
+/// # Returns
+/// A new array with leading elements removed until the predicate returns false.
+///
+/// # Example
+/// ```text
+/// drop_while('@ < `4`', [1, 2, 3, 5, 1, 2]) -> [5, 1, 2]
+/// drop_while('@ > `0`', [3, 2, 1, 0, -1]) -> [0, -1]
+/// ```
+pub struct DropWhileFn {
+    signature: Signature,
+}
+
+impl Default for DropWhileFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DropWhileFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for DropWhileFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in drop_while")?;
+
+        let mut dropping = true;
+        let mut results = Vec::new();
+        for item in arr {
+            if dropping {
+                let result = compiled.search(item.clone())?;
+                if !is_truthy(&result) {
+                    dropping = false;
+                    results.push(item.clone());
+                }
+            } else {
+                results.push(item.clone());
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// span_expr(expr, array) -> [prefix, rest]
+// =============================================================================
+
+/// Split an array at the first element for which the expression is falsy.
+///
+/// Equivalent to `[take_while(expr, array), drop_while(expr, array)]`, but the
+/// expression is evaluated only once per element up to the split point.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that returns a truthy/falsy value
+/// * `array` - The array to split
+///
+/// # Returns
+/// A two-element array: the longest prefix where the predicate holds, and
+/// the remaining elements.
+///
+/// # Example
+/// ```text
+/// span_expr('@ < `4`', [1, 2, 3, 5, 1]) -> [[1, 2, 3], [5, 1]]
+/// span_expr('@ > `9`', [1, 2]) -> [[], [1, 2]]
+/// ```
+pub struct SpanExprFn {
+    signature: Signature,
+}
+
+impl Default for SpanExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpanExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for SpanExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[1].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in span_expr")?;
+
+        let mut split = arr.len();
+        for (idx, item) in arr.iter().enumerate() {
+            if !is_truthy(&compiled.search(item.clone())?) {
+                split = idx;
+                break;
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(vec![
+            Rc::new(Variable::Array(arr[..split].to_vec())),
+            Rc::new(Variable::Array(arr[split..].to_vec())),
+        ])))
+    }
+}
+
+// =============================================================================
+// zip_with(expr, array1, array2) -> array
+// =============================================================================
+
+/// Zip two arrays together using a custom combiner expression.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression that receives `[element1, element2]` as input
+/// * `array1` - The first array
+/// * `array2` - The second array
+///
+/// # Returns
+/// A new array with elements combined using the expression.
+/// The result length is the minimum of the two input array lengths; use
+/// `zip_longest_with` to pad the shorter array instead.
+///
+/// # Example
+/// ```text
+/// zip_with('add([0], [1])', [1, 2, 3], [10, 20, 30]) -> [11, 22, 33]
+/// zip_with('[0] * [1]', [2, 3, 4], [5, 6, 7]) -> [10, 18, 28]
+/// ```
+pub struct ZipWithFn {
+    signature: Signature,
+}
+
+impl Default for ZipWithFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZipWithFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Array],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for ZipWithFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr1 = args[1].as_array().unwrap();
+        let arr2 = args[2].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in zip_with")?;
+
+        let min_len = arr1.len().min(arr2.len());
+        let mut results = Vec::with_capacity(min_len);
+
+        for i in 0..min_len {
+            // Create a pair array [element1, element2] as input to the expression
+            let pair = Rc::new(Variable::Array(vec![arr1[i].clone(), arr2[i].clone()]));
+            let result = compiled.search(pair)?;
+            results.push(result);
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// zip_longest_with(expr, array1, array2, fill) -> array
+// =============================================================================
+
+/// Zip two arrays together using a combiner expression, padding the shorter one.
+///
+/// Like `zip_with`, but instead of truncating to the shorter array, missing
+/// elements are replaced with `fill`.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression that receives `[element1, element2]` as input
+/// * `array1` - The first array
+/// * `array2` - The second array
+/// * `fill` - The value used in place of missing elements
+///
+/// # Returns
+/// A new array with elements combined using the expression.
+/// The result length is the maximum of the two input array lengths.
+///
+/// # Example
+/// ```text
+/// zip_longest_with('sum(@)', [1, 2, 3], [10], `0`) -> [11, 2, 3]
+/// zip_longest_with('@', ['a'], ['x', 'y'], `null`) -> [["a", "x"], [null, "y"]]
+/// ```
+pub struct ZipLongestWithFn {
+    signature: Signature,
+}
+
+impl Default for ZipLongestWithFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ZipLongestWithFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![
+                    expr_arg_type(),
+                    ArgumentType::Array,
+                    ArgumentType::Array,
+                    ArgumentType::Any,
+                ],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for ZipLongestWithFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr1 = args[1].as_array().unwrap();
+        let arr2 = args[2].as_array().unwrap();
+        let fill = &args[3];
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in zip_longest_with")?;
+
+        let max_len = arr1.len().max(arr2.len());
+        let mut results = Vec::with_capacity(max_len);
+
+        for i in 0..max_len {
+            let left = arr1.get(i).unwrap_or(fill).clone();
+            let right = arr2.get(i).unwrap_or(fill).clone();
+            let pair = Rc::new(Variable::Array(vec![left, right]));
+            results.push(compiled.search(pair)?);
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// difference_by(expr, array1, array2) -> array
+// =============================================================================
+
+/// Elements of the first array whose key does not appear in the second array.
+///
+/// Like `difference`, but elements are compared by the result of a key
+/// expression instead of by their whole value. Keys are compared by type as
+/// well as value, and an element whose key is null is never excluded.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that extracts the comparison key
+/// * `array1` - The array to take elements from
+/// * `array2` - The array of elements to exclude
+///
+/// # Returns
+/// Elements of `array1` (in order) whose key is not produced by any element of `array2`.
+///
+/// # Example
+/// ```text
+/// difference_by('id', [{"id": 1}, {"id": 2}], [{"id": 2, "x": true}]) -> [{"id": 1}]
+/// ```
+pub struct DifferenceByFn {
+    signature: Signature,
+}
+
+impl Default for DifferenceByFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DifferenceByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Array],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for DifferenceByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr1 = args[1].as_array().unwrap();
+        let arr2 = args[2].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in difference_by")?;
+
+        let exclude = collect_keys(&compiled, arr2)?;
+
+        let mut results = Vec::new();
+        for item in arr1 {
+            let key = match_key(&compiled.search(item.clone())?);
+            if key.is_none_or(|key| !exclude.contains(&key)) {
+                results.push(item.clone());
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// intersection_by(expr, array1, array2) -> array
+// =============================================================================
+
+/// Elements of the first array whose key also appears in the second array.
+///
+/// Like `intersection`, but elements are compared by the result of a key
+/// expression. Only the first element for each key is kept. Keys are
+/// compared by type as well as value, and elements whose key is null are
+/// dropped.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that extracts the comparison key
+/// * `array1` - The array to take elements from
+/// * `array2` - The array whose keys must match
+///
+/// # Returns
+/// Elements of `array1` (in order, unique by key) whose key is produced by an element of `array2`.
+///
+/// # Example
+/// ```text
+/// intersection_by('id', [{"id": 1}, {"id": 2}], [{"id": 2}, {"id": 3}]) -> [{"id": 2}]
+/// ```
+pub struct IntersectionByFn {
+    signature: Signature,
+}
+
+impl Default for IntersectionByFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntersectionByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Array],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for IntersectionByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr1 = args[1].as_array().unwrap();
+        let arr2 = args[2].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in intersection_by")?;
+
+        let include = collect_keys(&compiled, arr2)?;
+
+        let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut results = Vec::new();
+        for item in arr1 {
+            let Some(key) = match_key(&compiled.search(item.clone())?) else {
+                continue;
+            };
+            if include.contains(&key) && seen.insert(key) {
+                results.push(item.clone());
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// union_by(expr, array1, array2) -> array
+// =============================================================================
+
+/// Combine two arrays, keeping the first element for each distinct key.
+///
+/// Like `union`, but elements are compared by the result of a key expression.
+/// When both arrays contain an element with the same key, the one from the
+/// first array wins. Keys are compared by type as well as value, and
+/// elements whose key is null are all kept.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that extracts the comparison key
+/// * `array1` - The first array
+/// * `array2` - The second array
+///
+/// # Returns
+/// Elements of `array1` followed by elements of `array2`, unique by key.
+///
+/// # Example
+/// ```text
+/// union_by('id', [{"id": 1, "v": "a"}], [{"id": 1, "v": "b"}, {"id": 2}])
+///   -> [{"id": 1, "v": "a"}, {"id": 2}]
+/// ```
+pub struct UnionByFn {
+    signature: Signature,
+}
+
+impl Default for UnionByFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnionByFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Array],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for UnionByFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr1 = args[1].as_array().unwrap();
+        let arr2 = args[2].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in union_by")?;
+
+        let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut results = Vec::new();
+        for item in arr1.iter().chain(arr2.iter()) {
+            // Elements with a null key match nothing, so each is kept
+            let key = match_key(&compiled.search(item.clone())?);
+            if key.is_none_or(|key| seen.insert(key)) {
+                results.push(item.clone());
+            }
+        }
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+// =============================================================================
+// merge_by_expr(expr, array1, array2) -> array
+// =============================================================================
+
+/// Merge objects from two arrays that share the same key.
+///
+/// Each element of the first array is shallow-merged with the first element
+/// of the second array that has the same key, with fields from the second
+/// array taking precedence. Elements of the second array without a match
+/// are appended. Non-object elements are replaced by their match rather than
+/// merged. Keys are compared by type as well as value, and elements whose key
+/// is null are never matched.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string that extracts the join key
+/// * `array1` - The base array
+/// * `array2` - The array of updates
+///
+/// # Returns
+/// Elements of `array1` (in order, merged with their match) followed by the
+/// unmatched elements of `array2`.
+///
+/// # Example
+/// ```text
+/// merge_by_expr('id', [{"id": 1, "a": 1}], [{"id": 1, "b": 2}, {"id": 2}])
+///   -> [{"id": 1, "a": 1, "b": 2}, {"id": 2}]
+/// ```
+pub struct MergeByExprFn {
+    signature: Signature,
+}
+
+impl Default for MergeByExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MergeByExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Array, ArgumentType::Array],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for MergeByExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr1 = args[1].as_array().unwrap();
+        let arr2 = args[2].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in merge_by_expr")?;
+
+        // First occurrence in the second array wins for each key
+        let mut keys2 = Vec::with_capacity(arr2.len());
+        let mut updates: HashMap<String, usize> = HashMap::new();
+        for (idx, item) in arr2.iter().enumerate() {
+            let key = match_key(&compiled.search(item.clone())?);
+            if let Some(key) = &key {
+                updates.entry(key.clone()).or_insert(idx);
+            }
+            keys2.push(key);
+        }
+
+        let mut matched: std::collections::HashSet<String> = std::collections::HashSet::new();
+        let mut results = Vec::with_capacity(arr1.len() + arr2.len());
+        for item in arr1 {
+            let Some(key) = match_key(&compiled.search(item.clone())?) else {
+                results.push(item.clone());
+                continue;
+            };
+            let merged = match updates.get(&key) {
+                Some(&idx) => match (item.as_object(), arr2[idx].as_object()) {
+                    (Some(base), Some(update)) => {
+                        let mut fields = base.clone();
+                        fields.extend(update.iter().map(|(k, v)| (k.clone(), v.clone())));
+                        Rc::new(Variable::Object(fields))
+                    }
+                    _ => arr2[idx].clone(),
+                },
+                None => item.clone(),
+            };
+            matched.insert(key);
+            results.push(merged);
+        }
+
+        results.extend(
+            arr2.iter()
+                .zip(&keys2)
+                .filter(|(_, key)| key.as_ref().is_none_or(|k| !matched.contains(k)))
+                .map(|(item, _)| item.clone()),
+        );
+
+        Ok(Rc::new(Variable::Array(results)))
+    }
+}
+
+/// The key two elements are matched on: their JSON form, so `1` and `"1"`
+/// stay distinct. A null key (usually a missing field) matches nothing.
+fn match_key(value: &Rcvar) -> Option<String> {
+    (!value.is_null()).then(|| serde_json::to_string(&**value).unwrap_or_default())
+}
+
+/// Evaluate a key expression against every element and collect the
+/// [`match_key`] of each, skipping null keys.
+fn collect_keys(
+    compiled: &NestedExpression<'_>,
+    arr: &[Rcvar],
+) -> Result<std::collections::HashSet<String>, JmespathError> {
+    let mut keys = std::collections::HashSet::new();
+    for item in arr {
+        keys.extend(match_key(&compiled.search(item.clone())?));
+    }
+    Ok(keys)
+}
+
+// =============================================================================
+// array_diff(old, new, key_expr?) -> {added, removed, changed, unchanged}
+// =============================================================================
+
+/// Compare two arrays and report what was added, removed, changed and unchanged.
+///
+/// Without a key expression elements are compared by value, so `changed` is
+/// always empty. With a key expression, elements are matched by key and
+/// matched pairs whose contents differ are reported in `changed`. Keys are
+/// compared by type as well as value, and elements whose key is null are
+/// never matched, so they are reported as added or removed.
+///
+/// # Arguments
+/// * `old` - The original array
+/// * `new` - The updated array
+/// * `key_expr` - Optional JMESPath expression string that extracts an identity key from each element
+///
+/// # Returns
+/// An object with:
+/// * `added` - Elements of `new` with no match in `old`
+/// * `removed` - Elements of `old` with no match in `new`
+/// * `changed` - `{key, old, new, fields}` for matched elements whose contents differ;
+///   `fields` lists the top-level object fields that differ
+/// * `unchanged` - Matched elements that are identical
+///
+/// # Example
+/// ```text
+/// array_diff([{"id": 1, "v": "a"}, {"id": 2}], [{"id": 1, "v": "b"}, {"id": 3}], 'id')
+///   -> {"added": [{"id": 3}], "removed": [{"id": 2}],
+///       "changed": [{"key": 1, "old": {"id": 1, "v": "a"}, "new": {"id": 1, "v": "b"}, "fields": ["v"]}],
+///       "unchanged": []}
+/// ```
+pub struct ArrayDiffFn {
+    signature: Signature,
+}
+
+impl Default for ArrayDiffFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArrayDiffFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::Array, ArgumentType::Array],
+                Some(expr_arg_type()),
+            ),
+        }
+    }
+}
+
+impl Function for ArrayDiffFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if args.len() > 3 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("array_diff() takes 2 or 3 arguments".into()),
+            ));
+        }
+
+        let old = args[0].as_array().unwrap();
+        let new = args[1].as_array().unwrap();
+
+        let compiled = match args.get(2) {
+            Some(expr) => Some(compile_expr_arg(
+                expr,
+                ctx,
+                "Invalid expression in array_diff",
+            )?),
+            None => None,
+        };
+
+        // Identity of an element: its key when keyed, otherwise its full JSON
+        // value. Keyed elements with a null key have no identity and never match.
+        let identity = |item: &Rcvar| -> Result<(Option<String>, Rcvar), JmespathError> {
+            match &compiled {
+                Some(expr) => {
+                    let key = expr.search(item.clone())?;
+                    Ok((match_key(&key), key))
+                }
+                None => Ok((
+                    Some(serde_json::to_string(&variable_to_json(item)).unwrap_or_default()),
+                    item.clone(),
+                )),
+            }
+        };
+
+        // Unmatched old elements by identity, in order (duplicates are matched one-to-one)
+        let mut old_by_key: std::collections::HashMap<String, std::collections::VecDeque<usize>> =
+            std::collections::HashMap::new();
+        for (i, item) in old.iter().enumerate() {
+            if let (Some(key), _) = identity(item)? {
+                old_by_key.entry(key).or_default().push_back(i);
+            }
+        }
+
+        let mut matched = vec![false; old.len()];
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        let mut unchanged = Vec::new();
+
+        for item in new {
+            let (key, key_value) = identity(item)?;
+            let Some(i) = key
+                .and_then(|key| old_by_key.get_mut(&key))
+                .and_then(|q| q.pop_front())
+            else {
+                added.push(item.clone());
+                continue;
+            };
+            matched[i] = true;
+
+            let previous = &old[i];
+            if previous == item {
+                unchanged.push(item.clone());
+                continue;
+            }
+
+            let fields: Vec<Rcvar> = match (previous.as_object(), item.as_object()) {
+                (Some(a), Some(b)) => a
+                    .keys()
+                    .chain(b.keys().filter(|k| !a.contains_key(*k)))
+                    .filter(|k| a.get(*k) != b.get(*k))
+                    .map(|k| Rc::new(Variable::String(k.clone())))
+                    .collect(),
+                _ => vec![],
+            };
+
+            let mut entry = std::collections::BTreeMap::new();
+            entry.insert("key".to_string(), key_value);
+            entry.insert("old".to_string(), previous.clone());
+            entry.insert("new".to_string(), item.clone());
+            entry.insert("fields".to_string(), Rc::new(Variable::Array(fields)));
+            changed.push(Rc::new(Variable::Object(entry)));
+        }
+
+        let removed: Vec<Rcvar> = old
+            .iter()
+            .zip(&matched)
+            .filter(|(_, m)| !**m)
+            .map(|(item, _)| item.clone())
+            .collect();
+
+        let mut result = std::collections::BTreeMap::new();
+        result.insert("added".to_string(), Rc::new(Variable::Array(added)));
+        result.insert("removed".to_string(), Rc::new(Variable::Array(removed)));
+        result.insert("changed".to_string(), Rc::new(Variable::Array(changed)));
+        result.insert("unchanged".to_string(), Rc::new(Variable::Array(unchanged)));
+        Ok(Rc::new(Variable::Object(result)))
+    }
+}
+
+// =============================================================================
+// join_inner(left, right, key_expr) -> array
+// =============================================================================
+
+/// Join two arrays of objects on a key, keeping only matched pairs.
+///
+/// # Arguments
+/// * `left` - Array of objects
+/// * `right` - Array of objects
+/// * `key_expr` - A JMESPath expression string evaluated against elements of both arrays
+///
+/// # Returns
+/// One merged object per matching (left, right) pair, in left order. Fields
+/// from `right` override fields from `left` with the same name. Keys are
+/// compared by type as well as value, and null keys never match.
+///
+/// # Example
+/// ```text
+/// join_inner([{"id": 1, "name": "a"}, {"id": 2, "name": "b"}], [{"id": 1, "total": 9}], 'id')
+///   -> [{"id": 1, "name": "a", "total": 9}]
+/// ```
+pub struct JoinInnerFn {
+    signature: Signature,
+}
+
+impl Default for JoinInnerFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JoinInnerFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::Array, ArgumentType::Array, expr_arg_type()],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for JoinInnerFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        join_arrays(args, ctx, "join_inner", false)
+    }
+}
+
+// =============================================================================
+// join_left(left, right, key_expr) -> array
+// =============================================================================
+
+/// Join two arrays of objects on a key, keeping every left element.
+///
+/// # Arguments
+/// * `left` - Array of objects
+/// * `right` - Array of objects
+/// * `key_expr` - A JMESPath expression string evaluated against elements of both arrays
+///
+/// # Returns
+/// One merged object per matching (left, right) pair, in left order. Left
+/// elements without a match are included unchanged. Fields from `right`
+/// override fields from `left` with the same name. Keys are compared by type
+/// as well as value, and null keys never match.
+///
+/// # Example
+/// ```text
+/// join_left([{"id": 1, "name": "a"}, {"id": 2, "name": "b"}], [{"id": 1, "total": 9}], 'id')
+///   -> [{"id": 1, "name": "a", "total": 9}, {"id": 2, "name": "b"}]
+/// ```
+pub struct JoinLeftFn {
+    signature: Signature,
+}
+
+impl Default for JoinLeftFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JoinLeftFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::Array, ArgumentType::Array, expr_arg_type()],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for JoinLeftFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        join_arrays(args, ctx, "join_left", true)
+    }
+}
+
+/// Shared implementation of `join_inner` and `join_left`.
+fn join_arrays(
+    args: &[Rcvar],
+    ctx: &mut Context<'_>,
+    name: &str,
+    keep_unmatched: bool,
+) -> Result<Rcvar, JmespathError> {
+    let left = args[0].as_array().unwrap();
+    let right = args[1].as_array().unwrap();
+
+    let compiled = compile_expr_arg(&args[2], ctx, &format!("Invalid expression in {}", name))?;
+
+    let as_object = |item: &Rcvar| {
+        item.as_object().cloned().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!("{}() expects arrays of objects", name)),
+            )
+        })
+    };
+
+    // Index right elements by key, preserving their order for duplicate keys
+    let mut index: std::collections::HashMap<String, Vec<&Rcvar>> =
+        std::collections::HashMap::new();
+    for item in right {
+        if let Some(key) = match_key(&compiled.search(item.clone())?) {
+            index.entry(key).or_default().push(item);
+        }
+    }
+
+    let mut results = Vec::new();
+    for item in left {
+        let left_obj = as_object(item)?;
+        let key = match_key(&compiled.search(item.clone())?);
+        match key.and_then(|key| index.get(&key)) {
+            Some(matches) => {
+                for matched in matches {
+                    let mut merged = left_obj.clone();
+                    merged.extend(as_object(matched)?);
+                    results.push(Rc::new(Variable::Object(merged)));
+                }
+            }
+            None if keep_unmatched => results.push(item.clone()),
+            None => {}
+        }
+    }
+
+    Ok(Rc::new(Variable::Array(results)))
+}
+
+// =============================================================================
+// lookup(array, key_expr) -> object
+// =============================================================================
+
+/// Build an index object from an array, keyed by an expression.
+///
+/// # Arguments
+/// * `array` - The array to index
+/// * `key_expr` - A JMESPath expression string that extracts the key from each element
+///
+/// # Returns
+/// An object mapping each stringified key to its element. When several
+/// elements share a key, the last one wins.
+///
+/// # Example
+/// ```text
+/// lookup([{"id": "a", "v": 1}, {"id": "b", "v": 2}], 'id')
+///   -> {"a": {"id": "a", "v": 1}, "b": {"id": "b", "v": 2}}
+/// ```
+pub struct LookupFn {
+    signature: Signature,
+}
+
+impl Default for LookupFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LookupFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, expr_arg_type()], None),
+        }
+    }
+}
+
+impl Function for LookupFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+
+        let compiled = compile_expr_arg(&args[1], ctx, "Invalid expression in lookup")?;
+
+        let mut index = std::collections::BTreeMap::new();
+        for item in arr {
+            let key = value_to_string(&compiled.search(item.clone())?);
+            index.insert(key, item.clone());
+        }
+
+        Ok(Rc::new(Variable::Object(index)))
+    }
+}
+
+// =============================================================================
+// walk(expr, value) -> value (recursive transformation)
+// =============================================================================
+
+/// Recursively apply a transformation to every component of a data structure.
+///
+/// The transformation is applied bottom-up: for arrays and objects, children
+/// are transformed first, then the expression is applied to the result.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string to apply at each node
+/// * `value` - The value to walk
+///
+/// # Returns
+/// The transformed value.
+///
+/// # Example
+/// ```text
+/// walk('if(is_array(@), sort(@), @)', {a: [3, 1, 2]}) -> {a: [1, 2, 3]}
+/// walk('if(is_object(@), merge(@, {visited: `true`}), @)', data) -> all objects get visited: true
+/// ```
+pub struct WalkFn {
+    signature: Signature,
+}
+
+impl Default for WalkFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WalkFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![expr_arg_type(), ArgumentType::Any], None),
+        }
+    }
+}
+
+/// Recursively walk a value, applying the expression bottom-up
+fn walk_value(value: &Rcvar, compiled: &NestedExpression<'_>) -> Result<Rcvar, JmespathError> {
+    match &**value {
+        Variable::Array(arr) => {
+            // First, recursively walk all elements
+            let walked_elements: Result<Vec<Rcvar>, _> =
+                arr.iter().map(|elem| walk_value(elem, compiled)).collect();
+            let new_array = Rc::new(Variable::Array(walked_elements?));
+            // Then apply the expression to the array itself
+            compiled.search(new_array)
+        }
+        Variable::Object(obj) => {
+            // First, recursively walk all values
+            let walked_entries: Result<std::collections::BTreeMap<String, Rcvar>, _> = obj
+                .iter()
+                .map(|(k, v)| walk_value(v, compiled).map(|walked| (k.clone(), walked)))
+                .collect();
+            let new_object = Rc::new(Variable::Object(walked_entries?));
+            // Then apply the expression to the object itself
+            compiled.search(new_object)
+        }
+        // For scalars (string, number, bool, null), just apply the expression
+        _ => compiled.search(value.clone()),
+    }
+}
+
+impl Function for WalkFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in walk")?;
+        crate::limits::check_depth(ctx, &args[1..2])?;
+
+        walk_value(&args[1], &compiled)
+    }
+}
+
+// =============================================================================
+// eval(expr, value?) -> any
+// =============================================================================
+
+/// Evaluate a JMESPath expression that is itself stored as a string in the data.
+///
+/// This allows rule engines and configuration files to carry JMESPath snippets
+/// that are evaluated against the current document in a single query, instead
+/// of having the host application orchestrate a second search call.
+///
+/// The expression is compiled with the same runtime as the outer query, so all
+/// registered functions (and any restrictions applied to that runtime) apply.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string
+/// * `value` - The value to evaluate against (defaults to `null`; pass `@` for the current node)
+///
+/// # Returns
+/// The result of evaluating the expression.
+///
+/// # Example
+/// ```text
+/// eval('a.b', {a: {b: 1}}) -> 1
+/// eval(config.filter, @)  // evaluate an expression held in the document
+/// ```
+pub struct EvalFn {
+    signature: Signature,
+}
+
+impl Default for EvalFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EvalFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], Some(ArgumentType::Any)),
+        }
+    }
+}
+
+impl Function for EvalFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if args.len() > 2 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("eval() takes 1 or 2 arguments".into()),
+            ));
+        }
+
+        let expr_str = args[0].as_string().unwrap();
+        let value = args
+            .get(1)
+            .cloned()
+            .unwrap_or_else(|| Rc::new(Variable::Null));
+
+        let compiled = compile_nested(ctx, expr_str).map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!("Invalid expression in eval: {}", e)),
+            )
+        })?;
+
+        compiled.search(value)
+    }
+}
+
+// =============================================================================
+// pipe_expr(exprs, value) -> any
+// =============================================================================
+
+/// Run a value through a list of expressions, feeding each result to the next.
+///
+/// This lets multi-step transforms live in configuration as plain data. Each
+/// step is either an expression string, evaluated with the previous result as
+/// `@`, or a partial object (from `partial()` or `compose()`), applied with the
+/// previous result as its last argument.
+///
+/// # Arguments
+/// * `exprs` - An array of expression strings or partial objects
+/// * `value` - The starting value
+///
+/// # Returns
+/// The result of the last step, or `value` itself for an empty pipeline.
+///
+/// # Example
+/// ```text
+/// pipe_expr(['[?active]', 'sort_by(@, &name)', '[:5]'], users)
+/// ```
+pub struct PipeExprFn {
+    signature: Signature,
+}
+
+impl Default for PipeExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PipeExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::Any], None),
+        }
+    }
+}
+
+impl Function for PipeExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let steps = args[0].as_array().unwrap();
+        let mut value = args[1].clone();
+        for (i, step) in steps.iter().enumerate() {
+            value = match pipeline_step(step) {
+                Some(PipelineStep::Expr(expr_str)) => {
+                    let compiled = compile_nested(ctx, expr_str).map_err(|e| {
+                        JmespathError::new(
+                            ctx.expression,
+                            ctx.offset,
+                            ErrorReason::Parse(format!(
+                                "Invalid expression at pipe_expr step {}: {}",
+                                i, e
+                            )),
+                        )
+                    })?;
+                    compiled.search(value)?
+                }
+                Some(PipelineStep::Partial(fn_name, prefilled)) => {
+                    invoke_function(fn_name, prefilled, &[value], ctx)?
+                }
+                None => return Err(invalid_pipeline_step(i, ctx)),
+            };
+        }
+        Ok(value)
+    }
+}
+
+// =============================================================================
+// compose(exprs) -> partial object
+// =============================================================================
+
+/// Bundle a list of expressions into a reusable partial.
+///
+/// The result is a partial object for `pipe_expr`, so it can be run later with
+/// `apply()` or used as a step inside another pipeline. Every expression is
+/// compiled up front, so a typo is reported where the pipeline is built.
+///
+/// # Arguments
+/// * `exprs` - An array of expression strings or partial objects
+///
+/// # Returns
+/// A partial object: `{"__partial__": true, "fn": "pipe_expr", "args": [exprs]}`
+///
+/// # Example
+/// ```text
+/// apply(compose(['[?active]', 'length(@)']), users)  // -> number of active users
+/// ```
+pub struct ComposeFn {
+    signature: Signature,
+}
+
+impl Default for ComposeFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComposeFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for ComposeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let steps = args[0].as_array().unwrap();
+        for (i, step) in steps.iter().enumerate() {
+            match pipeline_step(step) {
+                Some(PipelineStep::Expr(expr_str)) => {
+                    compile_nested(ctx, expr_str).map_err(|e| {
+                        JmespathError::new(
+                            ctx.expression,
+                            ctx.offset,
+                            ErrorReason::Parse(format!(
+                                "Invalid expression at compose step {}: {}",
+                                i, e
+                            )),
+                        )
+                    })?;
+                }
+                Some(PipelineStep::Partial(..)) => {}
+                None => return Err(invalid_pipeline_step(i, ctx)),
+            }
+        }
+
+        let partial = serde_json::json!({
+            "__partial__": true,
+            "fn": "pipe_expr",
+            "args": [variable_to_json(&args[0])],
+        });
+        Ok(Rc::new(Variable::from_json(&partial.to_string()).unwrap()))
+    }
+}
+
+/// A single step of a `pipe_expr` pipeline.
+enum PipelineStep<'a> {
+    Expr(&'a str),
+    Partial(&'a str, &'a [Rcvar]),
+}
+
+fn pipeline_step(step: &Rcvar) -> Option<PipelineStep<'_>> {
+    if let Some(expr_str) = step.as_string() {
+        return Some(PipelineStep::Expr(expr_str));
+    }
+    let obj = step.as_object()?;
+    if obj.get("__partial__").and_then(|v| v.as_boolean()) != Some(true) {
+        return None;
+    }
+    let fn_name = obj.get("fn")?.as_string()?;
+    let prefilled = obj.get("args")?.as_array()?;
+    Some(PipelineStep::Partial(fn_name, prefilled))
+}
+
+fn invalid_pipeline_step(index: usize, ctx: &Context<'_>) -> JmespathError {
+    JmespathError::new(
+        ctx.expression,
+        ctx.offset,
+        ErrorReason::Parse(format!(
+            "Pipeline step {} must be an expression string or partial object",
+            index
+        )),
+    )
+}
+
+// =============================================================================
+// let_expr(bindings, expr, value) -> any
+// let_expr(name, bound_value, expr, value) -> any
+// =============================================================================
+
+thread_local! {
+    /// Variables bound by the `let_expr` calls currently being evaluated,
+    /// innermost last.
+    static LET_SCOPES: RefCell<Vec<std::collections::BTreeMap<String, Rcvar>>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Pops the innermost `let_expr` scope when dropped, even on error.
+struct LetScopeGuard;
+
+impl Drop for LetScopeGuard {
+    fn drop(&mut self) {
+        LET_SCOPES.with(|scopes| scopes.borrow_mut().pop());
+    }
+}
+
+/// Render a value as a JMESPath JSON literal. Quotes and backticks are
+/// escaped as unicode so the literal survives inside any string context.
+fn json_literal(value: &Variable) -> String {
+    let json = serde_json::to_string(value).unwrap_or_else(|_| "null".to_string());
+    format!(
+        "`{}`",
+        json.replace('\'', "\\u0027").replace('`', "\\u0060")
+    )
+}
+
+/// Index just past the quoted span that starts at `start`, honouring escapes.
+fn quoted_span_end(expr: &str, start: usize) -> usize {
+    let bytes = expr.as_bytes();
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Read an identifier starting at `start`.
+fn identifier_at(expr: &str, start: usize) -> &str {
+    let len = expr.as_bytes()[start..]
+        .iter()
+        .take_while(|b| is_ident_byte(**b))
+        .count();
+    &expr[start..start + len]
+}
+
+/// Replace every `$name` outside of string literals with the bound value.
+fn substitute_variables(
+    expr: &str,
+    scopes: &[std::collections::BTreeMap<String, Rcvar>],
+) -> Result<String, String> {
+    let bytes = expr.as_bytes();
+    let mut out = String::with_capacity(expr.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => {
+                let end = quoted_span_end(expr, i);
+                out.push_str(&expr[i..end]);
+                i = end;
+            }
+            b'$' => {
+                let name = identifier_at(expr, i + 1);
+                let value = scopes.iter().rev().find_map(|scope| scope.get(name));
+                match value {
+                    Some(value) if !name.is_empty() => out.push_str(&json_literal(value)),
+                    _ => return Err(format!("Undefined variable ${}", name)),
+                }
+                i += 1 + name.len();
+            }
+            _ => {
+                let ch = expr[i..].chars().next().unwrap();
+                out.push(ch);
+                i += ch.len_utf8();
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Evaluate an expression with variables bound to values.
+///
+/// `$name` references in `expr` are replaced by the bound values before it is
+/// compiled, so an intermediate result can be computed once and reused. The
+/// bindings are visible to nested `let_expr` calls as well, which is how
+/// [`expand_let`] implements JEP-11 `let ... in ...` scoping. Variables are not
+/// substituted inside string literals, so expression strings given to other
+/// functions such as `map_expr` do not see them.
+///
+/// # Arguments
+/// * `bindings` - An object of variable names to values, or a single name
+///   followed by its value
+/// * `expr` - A JMESPath expression string that may reference `$name`
+/// * `value` - The value to evaluate against
+///
+/// # Returns
+/// The result of evaluating the expression. Referencing an unbound variable
+/// is an error.
+///
+/// # Example
+/// ```text
+/// let_expr({limit: config.limit}, 'items[?size > $limit]', @)
+/// let_expr({min: `3`}, '[?length(@) > $min]', names)
+/// let_expr('min', `3`, '[?length(@) > $min]', names)
+/// ```
+pub struct LetExprFn {
+    signature: Signature,
+}
+
+impl Default for LetExprFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LetExprFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::Any, ArgumentType::Any, ArgumentType::Any],
+                Some(ArgumentType::Any),
+            ),
+        }
+    }
+}
+
+impl Function for LetExprFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let let_error =
+            |msg: String| JmespathError::new(ctx.expression, ctx.offset, ErrorReason::Parse(msg));
+
+        let (bindings, rest) = match (args.len(), args[0].as_object(), args[0].as_string()) {
+            (3, Some(obj), _) => (obj.clone(), &args[1..]),
+            (4, _, Some(name)) => {
+                let mut bindings = std::collections::BTreeMap::new();
+                bindings.insert(name.clone(), args[1].clone());
+                (bindings, &args[2..])
+            }
+            _ => {
+                return Err(let_error(
+                    "let_expr() takes (bindings, expr, value) or (name, value, expr, value)".into(),
+                ));
+            }
+        };
+        if let Some(bad) = bindings
+            .keys()
+            .find(|k| k.is_empty() || !k.bytes().all(is_ident_byte))
+        {
+            return Err(let_error(format!("Invalid variable name '{}'", bad)));
+        }
+        let expr_str = rest[0]
+            .as_string()
+            .ok_or_else(|| let_error("let_expr() expression must be a string".into()))?;
+
+        LET_SCOPES.with(|scopes| scopes.borrow_mut().push(bindings));
+        let _guard = LetScopeGuard;
+
+        let expanded = LET_SCOPES
+            .with(|scopes| substitute_variables(expr_str, &scopes.borrow()))
+            .map_err(|e| let_error(format!("{} in let_expr", e)))?;
+        let compiled = ctx
+            .runtime
+            .compile(&expanded)
+            .map(NestedExpression::new)
+            .map_err(|e| let_error(format!("Invalid expression in let_expr: {}", e)))?;
+        compiled.search(rest[1].clone())
+    }
+}
+
+/// Find where an expression that starts at `start` ends: at a `,` or an
+/// unmatched closing bracket at depth 0, at the end of input, or - when
+/// `stop_at_in` is set - at the `in` keyword.
+fn let_segment_end(expr: &str, start: usize, stop_at_in: bool) -> usize {
+    let bytes = expr.as_bytes();
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => {
+                i = quoted_span_end(expr, i);
+                continue;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => {
+                if depth == 0 {
+                    return i;
+                }
+                depth -= 1;
+            }
+            b',' if depth == 0 => return i,
+            b'i' if depth == 0 && stop_at_in && keyword_at(expr, i, "in") => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Whether `keyword` appears at `i` as a whole word.
+fn keyword_at(expr: &str, i: usize, keyword: &str) -> bool {
+    let bytes = expr.as_bytes();
+    expr[i..].starts_with(keyword)
+        && (i == 0 || !is_ident_byte(bytes[i - 1]) && bytes[i - 1] != b'$')
+        && bytes
+            .get(i + keyword.len())
+            .is_none_or(|b| !is_ident_byte(*b))
+}
+
+/// Rewrite JEP-11 `let $x = expr in body` syntax into `let_expr` calls.
+///
+/// The upstream parser does not understand `let`, so this preprocessing step
+/// turns each let expression into nested `let_expr(name, value, body, @)`
+/// calls that the stock runtime can compile. Bindings are evaluated against
+/// the current node and the body extends as far as possible, up to a `,` or
+/// closing bracket of the enclosing expression. Expressions without `let`
+/// are returned unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath::{Runtime, Variable};
+/// use jmespath_extensions::expression::{self, expand_let};
+///
+/// let mut runtime = Runtime::new();
+/// runtime.register_builtin_functions();
+/// expression::register(&mut runtime);
+///
+/// let query = expand_let("let $min = min_length in names[?length(@) >= $min]").unwrap();
+/// let data = Variable::from_json(r#"{"min_length": 3, "names": ["al", "bea", "cyd"]}"#).unwrap();
+/// let result = runtime.compile(&query).unwrap().search(&data).unwrap();
+/// assert_eq!(result.as_array().unwrap().len(), 2);
+/// ```
+///
+/// # Errors
+///
+/// Returns a parse error for a malformed `let` (missing `$name`, `=` or `in`).
+pub fn expand_let(expression: &str) -> Result<String, JmespathError> {
+    let error = |offset: usize, msg: &str| {
+        JmespathError::new(expression, offset, ErrorReason::Parse(msg.to_string()))
+    };
+    let bytes = expression.as_bytes();
+    let mut out = String::with_capacity(expression.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' | b'`' => {
+                let end = quoted_span_end(expression, i);
+                out.push_str(&expression[i..end]);
+                i = end;
+                continue;
+            }
+            b'l' if keyword_at(expression, i, "let")
+                && expression[i + 3..].trim_start().starts_with('$') =>
+            {
+                let mut bindings = Vec::new();
+                let mut pos = i + 3;
+                loop {
+                    pos += expression[pos..].len() - expression[pos..].trim_start().len();
+                    if bytes.get(pos) != Some(&b'$') {
+                        return Err(error(pos, "Expected $name in let binding"));
+                    }
+                    let name = identifier_at(expression, pos + 1);
+                    if name.is_empty() {
+                        return Err(error(pos, "Expected $name in let binding"));
+                    }
+                    pos += 1 + name.len();
+                    pos += expression[pos..].len() - expression[pos..].trim_start().len();
+                    if bytes.get(pos) != Some(&b'=') || bytes.get(pos + 1) == Some(&b'=') {
+                        return Err(error(pos, "Expected '=' in let binding"));
+                    }
+                    let end = let_segment_end(expression, pos + 1, true);
+                    let value = expand_let(expression[pos + 1..end].trim())?;
+                    bindings.push((name, value));
+                    pos = end;
+                    match bytes.get(pos) {
+                        Some(b',') => pos += 1,
+                        Some(b'i') => break,
+                        _ => return Err(error(pos, "Expected 'in' after let bindings")),
+                    }
+                }
+                let body_start = pos + 2;
+                let body_end = let_segment_end(expression, body_start, false);
+                let mut body = expand_let(expression[body_start..body_end].trim())?;
+                for (name, value) in bindings.into_iter().rev() {
+                    let body_literal = serde_json::to_string(&body)
+                        .unwrap()
+                        .replace('`', "\\u0060");
+                    body = format!("let_expr('{}', {}, `{}`, @)", name, value, body_literal);
+                }
+                out.push_str(&body);
+                i = body_end;
+                continue;
+            }
+            _ => {}
+        }
+        let ch = expression[i..].chars().next().unwrap();
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+    Ok(out)
+}
+
+// =============================================================================
+// define(name, expr, value?) -> any
+// call(name, value) -> any
+// =============================================================================
+
+/// Maximum nesting of `call` invocations, which bounds recursive definitions.
+const MAX_CALL_DEPTH: usize = 64;
+
+thread_local! {
+    /// Functions declared with `define`, by name.
+    static DEFINED_FUNCTIONS: RefCell<HashMap<String, (String, Ast)>> =
+        RefCell::new(HashMap::new());
+    /// Number of `call` invocations currently being evaluated.
+    static CALL_DEPTH: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Helpers declared with `define` and the current `call` depth, captured so
+/// that `parallel_map_expr` workers resolve `call` like the calling thread.
+#[cfg(feature = "parallel")]
+struct DefinitionsSnapshot {
+    functions: HashMap<String, (String, Ast)>,
+    depth: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl DefinitionsSnapshot {
+    fn capture() -> Self {
+        Self {
+            functions: DEFINED_FUNCTIONS.with(|fns| fns.borrow().clone()),
+            depth: CALL_DEPTH.with(|depth| depth.get()),
+        }
+    }
+
+    /// Install the captured state on the current thread until the returned
+    /// guard is dropped. Definitions made meanwhile are discarded with it.
+    fn enter(&self) -> DefinitionsGuard {
+        DefinitionsGuard(Self {
+            functions: DEFINED_FUNCTIONS.with(|fns| fns.replace(self.functions.clone())),
+            depth: CALL_DEPTH.with(|depth| depth.replace(self.depth)),
+        })
+    }
+}
+
+/// Restores the definitions a thread had before a snapshot was entered.
+#[cfg(feature = "parallel")]
+struct DefinitionsGuard(DefinitionsSnapshot);
+
+#[cfg(feature = "parallel")]
+impl Drop for DefinitionsGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.0.functions);
+        DEFINED_FUNCTIONS.with(|fns| *fns.borrow_mut() = previous);
+        CALL_DEPTH.with(|depth| depth.set(self.0.depth));
+    }
+}
+
+/// Forget every function declared with `define` on the current thread.
+///
+/// Definitions are discarded automatically when the next top-level search
+/// starts on the thread; call this to drop them sooner.
+pub fn clear_defined_functions() {
+    DEFINED_FUNCTIONS.with(|fns| fns.borrow_mut().clear());
+}
+
+/// Declare a named helper expression for later use with `call`.
+///
+/// Query files that repeat the same sub-expression can declare it once and
+/// refer to it by name. The expression is compiled when it is defined, so
+/// syntax errors are reported at the definition. Defining a name again
+/// replaces the previous definition.
+///
+/// `Context` belongs to the upstream `jmespath` crate and cannot carry extra
+/// state, so definitions are kept per thread until the top-level search that
+/// made them is over; see [`clear_defined_functions`].
+///
+/// # Arguments
+/// * `name` - The name to define
+/// * `expr` - A JMESPath expression string or `&expr` evaluated against the `call` argument
+/// * `value` - Optional value to return, so a definition can be piped into the rest of a query
+///
+/// # Returns
+/// `value`, or `null` when omitted.
+///
+/// # Example
+/// ```text
+/// define('double', 'multiply(@, `2`)', @) | map_expr('call(`"double"`, @)', items)
+/// ```
+pub struct DefineFn {
+    signature: Signature,
+}
+
+impl Default for DefineFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DefineFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::String, expr_arg_type()],
+                Some(ArgumentType::Any),
+            ),
+        }
+    }
+}
+
+impl Function for DefineFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if args.len() > 3 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("define takes at most 3 arguments".to_string()),
+            ));
+        }
+
+        let name = args[0].as_string().unwrap();
+        let compiled = compile_expr_arg(&args[1], ctx, "Invalid expression in define")?;
+        let definition = (compiled.as_str().to_string(), compiled.as_ast().clone());
+        DEFINED_FUNCTIONS.with(|fns| fns.borrow_mut().insert(name.clone(), definition));
+
+        Ok(args
+            .get(2)
+            .cloned()
+            .unwrap_or_else(|| Rc::new(Variable::Null)))
+    }
+}
+
+/// Restores the `call` depth when dropped, even on error.
+struct CallDepthGuard;
+
+impl Drop for CallDepthGuard {
+    fn drop(&mut self) {
+        CALL_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// Evaluate a helper declared with `define` against a value.
+///
+/// Definitions may call each other and themselves, up to 64 nested calls.
+///
+/// # Arguments
+/// * `name` - The name given to `define`
+/// * `value` - The value to evaluate the definition against
+///
+/// # Returns
+/// The result of the defined expression. Calling an undefined name is an error.
+///
+/// # Example
+/// ```text
+/// call('double', `21`)  // 42, after define('double', 'multiply(@, `2`)')
+/// ```
+pub struct CallFn {
+    signature: Signature,
+}
+
+impl Default for CallFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CallFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Any], None),
+        }
+    }
+}
+
+impl Function for CallFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        enter_search_scope(ctx);
+        let name = args[0].as_string().unwrap();
+        let call_error = |message: String| {
+            JmespathError::new(ctx.expression, ctx.offset, ErrorReason::Parse(message))
+        };
+
+        let (source, ast) = DEFINED_FUNCTIONS
+            .with(|fns| fns.borrow().get(name).cloned())
+            .ok_or_else(|| call_error(format!("Undefined function '{}' in call", name)))?;
+
+        if CALL_DEPTH.with(|depth| depth.get()) >= MAX_CALL_DEPTH {
+            return Err(call_error(format!(
+                "Maximum call depth of {} exceeded calling '{}'",
+                MAX_CALL_DEPTH, name
+            )));
+        }
+        CALL_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        let _guard = CallDepthGuard;
+
+        NestedExpression::new(Expression::new(source, ast, ctx.runtime)).search(args[1].clone())
+    }
+}
+
+// =============================================================================
+// evaluate_rules(rules, value) -> array
+// =============================================================================
+
+/// Evaluate a list of rules against a value and return the ones that match.
+///
+/// Each rule is an object with a `when` expression and optional `then`
+/// expression. Any other fields (`id`, `severity`, `message`, ...) are treated
+/// as metadata and copied into the result for matching rules.
+///
+/// # Arguments
+/// * `rules` - Array of rule objects: `{id, when: expr, then?: expr, severity?, ...}`
+/// * `value` - The value each rule is evaluated against
+///
+/// # Returns
+/// An array with one entry per rule whose `when` expression is truthy, in rule
+/// order. Each entry holds the rule's metadata plus an `output` field with the
+/// result of `then` (only when `then` is present).
+///
+/// # Example
+/// ```text
+/// evaluate_rules(
+///   [{id: 'high-cpu', when: 'cpu > `90`', then: 'cpu', severity: 'critical'},
+///    {id: 'low-disk', when: 'disk < `10`'}],
+///   {cpu: 95, disk: 50}
+/// ) -> [{id: 'high-cpu', severity: 'critical', output: 95}]
+/// ```
+pub struct EvaluateRulesFn {
+    signature: Signature,
+}
+
+impl Default for EvaluateRulesFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EvaluateRulesFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::Any], None),
+        }
+    }
+}
+
+impl Function for EvaluateRulesFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let rules = args[0].as_array().unwrap();
+        let value = &args[1];
+
+        let mut matched = Vec::new();
+
+        for (idx, rule) in rules.iter().enumerate() {
+            let rule_obj = rule.as_object().ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse(format!("Rule {} must be an object", idx)),
+                )
+            })?;
+
+            let when = rule_obj
+                .get("when")
+                .and_then(|w| w.as_string())
+                .ok_or_else(|| {
+                    JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse(format!(
+                            "Rule {} is missing a 'when' expression string",
+                            idx
+                        )),
+                    )
+                })?;
+
+            let condition = compile_nested(ctx, when).map_err(|e| {
+                JmespathError::new(
+                    ctx.expression,
+                    ctx.offset,
+                    ErrorReason::Parse(format!("Invalid 'when' expression in rule {}: {}", idx, e)),
+                )
+            })?;
+
+            if !is_truthy(&condition.search(value.clone())?) {
+                continue;
+            }
+
+            let mut result: std::collections::BTreeMap<String, Rcvar> = rule_obj
+                .iter()
+                .filter(|(k, _)| k.as_str() != "when" && k.as_str() != "then")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+
+            if let Some(then) = rule_obj.get("then") {
+                let then_str = then.as_string().ok_or_else(|| {
+                    JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse(format!(
+                            "Rule {} 'then' must be an expression string",
+                            idx
+                        )),
+                    )
+                })?;
+                let output = compile_nested(ctx, then_str).map_err(|e| {
+                    JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse(format!(
+                            "Invalid 'then' expression in rule {}: {}",
+                            idx, e
+                        )),
+                    )
+                })?;
+                result.insert("output".to_string(), output.search(value.clone())?);
+            }
+
+            matched.push(Rc::new(Variable::Object(result)));
+        }
+
+        Ok(Rc::new(Variable::Array(matched)))
+    }
+}
+
+// =============================================================================
+// ensure(expr, value, message?) -> value
+// =============================================================================
+
+/// Assert that an expression is truthy for a value, failing the query otherwise.
+///
+/// Use this to make pipelines fail loudly on malformed input instead of
+/// silently producing `null` further down the expression.
+///
+/// # Arguments
+/// * `expr` - A JMESPath expression string evaluated against `value`
+/// * `value` - The value to check and return
+/// * `message` - Optional error message (defaults to naming the failed expression)
+///
+/// # Returns
+/// `value` unchanged when the expression is truthy; otherwise an error.
+///
+/// # Example
+/// ```text
+/// ensure('length(@) > `0`', [1, 2], 'items must not be empty') -> [1, 2]
+/// ensure('length(@) > `0`', [], 'items must not be empty')     // error: items must not be empty
+/// ```
+pub struct EnsureFn {
+    signature: Signature,
+}
+
+impl Default for EnsureFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EnsureFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![expr_arg_type(), ArgumentType::Any],
+                Some(ArgumentType::String),
+            ),
+        }
+    }
+}
+
+impl Function for EnsureFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let compiled = compile_expr_arg(&args[0], ctx, "Invalid expression in ensure")?;
+
+        if is_truthy(&compiled.search(args[1].clone())?) {
+            return Ok(args[1].clone());
+        }
+
+        let message = match (args.get(2).and_then(|m| m.as_string()), args[0].as_string()) {
+            (Some(message), _) => message.clone(),
+            (None, Some(expr_str)) => format!("ensure failed: {}", expr_str),
+            (None, None) => "ensure failed: &expr".to_string(),
+        };
+        Err(JmespathError::new(
+            ctx.expression,
+            ctx.offset,
+            ErrorReason::Parse(message),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    #[test]
+    fn test_map_expr_field() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"name": "Alice"}, {"name": "Bob"}]"#).unwrap();
+        let expr = runtime.compile("map_expr('name', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0].as_string().unwrap(), "Alice");
+        assert_eq!(arr[1].as_string().unwrap(), "Bob");
+    }
+
+    #[test]
+    fn test_map_expr_transform() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"["hello", "world"]"#).unwrap();
+        let expr = runtime.compile("map_expr('length(@)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr[0].as_number().unwrap(), 5.0);
+        assert_eq!(arr[1].as_number().unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_filter_expr() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"age": 25}, {"age": 17}, {"age": 30}]"#).unwrap();
+        let expr = runtime.compile("filter_expr('age >= `18`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+    }
+
+    #[test]
+    fn test_filter_expr_empty() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
+        let expr = runtime.compile("filter_expr('@ > `10`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 0);
+    }
+
+    #[test]
+    fn test_any_expr_true() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"active": false}, {"active": true}]"#).unwrap();
+        let expr = runtime.compile("any_expr('active', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_any_expr_false() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"active": false}, {"active": false}]"#).unwrap();
+        let expr = runtime.compile("any_expr('active', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_all_expr_true() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"active": true}, {"active": true}]"#).unwrap();
+        let expr = runtime.compile("all_expr('active', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_all_expr_false() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"active": true}, {"active": false}]"#).unwrap();
+        let expr = runtime.compile("all_expr('active', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_all_expr_empty() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[]"#).unwrap();
         let expr = runtime.compile("all_expr('active', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(!result.as_boolean().unwrap());
+        assert!(result.as_boolean().unwrap()); // vacuous truth
+    }
+
+    #[test]
+    fn test_find_expr_found() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]"#)
+            .unwrap();
+        let expr = runtime.compile("find_expr('id == `2`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "Bob");
+    }
+
+    #[test]
+    fn test_find_expr_not_found() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"id": 1}, {"id": 2}]"#).unwrap();
+        let expr = runtime.compile("find_expr('id == `99`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn test_sort_by_expr_numbers() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"val": 3}, {"val": 1}, {"val": 2}]"#).unwrap();
+        let expr = runtime.compile("sort_by_expr('val', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("val")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            1.0
+        );
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("val")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            2.0
+        );
+        assert_eq!(
+            arr[2]
+                .as_object()
+                .unwrap()
+                .get("val")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            3.0
+        );
+    }
+
+    #[test]
+    fn test_sort_by_expr_strings() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"name": "Charlie"}, {"name": "Alice"}, {"name": "Bob"}]"#)
+                .unwrap();
+        let expr = runtime.compile("sort_by_expr('name', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Alice"
+        );
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Bob"
+        );
+        assert_eq!(
+            arr[2]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Charlie"
+        );
+    }
+
+    #[test]
+    fn test_find_index_expr_found() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#).unwrap();
+        let expr = runtime.compile("find_index_expr('id == `2`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_find_index_expr_not_found() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"id": 1}, {"id": 2}]"#).unwrap();
+        let expr = runtime.compile("find_index_expr('id == `99`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), -1.0);
+    }
+
+    #[test]
+    fn test_count_expr() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"active": true}, {"active": false}, {"active": true}]"#)
+                .unwrap();
+        let expr = runtime.compile("count_expr('active', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_count_expr_none() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
+        let expr = runtime.compile("count_expr('@ > `10`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_group_by_expr() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"type": "a", "val": 1}, {"type": "b", "val": 2}, {"type": "a", "val": 3}]"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("group_by_expr('type', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("a").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(obj.get("b").unwrap().as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_partition_expr() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
+        let expr = runtime.compile("partition_expr('@ > `3`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        let matches = arr[0].as_array().unwrap();
+        let non_matches = arr[1].as_array().unwrap();
+        assert_eq!(matches.len(), 2); // 4, 5
+        assert_eq!(non_matches.len(), 3); // 1, 2, 3
+    }
+
+    #[test]
+    fn test_top_k() {
+        let runtime = setup();
+        let expr = runtime.compile("top_k(@, `3`)").unwrap();
+        let data = Variable::from_json("[5, 2, 8, 1, 9, 3]").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result, Rc::new(Variable::from_json("[9, 8, 5]").unwrap()));
+    }
+
+    #[test]
+    fn test_top_k_with_key_expr_keeps_tie_order() {
+        let runtime = setup();
+        let expr = runtime.compile("top_k(@, `2`, 'score')[*].id").unwrap();
+        let data = Variable::from_json(
+            r#"[{"id": "a", "score": 1}, {"id": "b", "score": 7}, {"id": "c", "score": 7}, {"id": "d", "score": 3}]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"["b", "c"]"#).unwrap())
+        );
+
+        let expr = runtime.compile("top_k(@, `2`, &score)[*].id").unwrap();
+        assert_eq!(expr.search(&data).unwrap(), result);
+    }
+
+    #[test]
+    fn test_bottom_k() {
+        let runtime = setup();
+        let expr = runtime.compile("bottom_k(@, `2`)").unwrap();
+        let data = Variable::from_json("[5, 2, 8, 1, 9, 3]").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result, Rc::new(Variable::from_json("[1, 2]").unwrap()));
+    }
+
+    #[test]
+    fn test_top_k_larger_than_array() {
+        let runtime = setup();
+        let expr = runtime.compile("bottom_k(@, `10`, 'n')").unwrap();
+        let data = Variable::from_json(r#"[{"n": 2}, {"n": 1}]"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"[{"n": 1}, {"n": 2}]"#).unwrap())
+        );
+
+        let expr = runtime.compile("bottom_k(@, `10`, &n)").unwrap();
+        assert_eq!(expr.search(&data).unwrap(), result);
+    }
+
+    #[test]
+    fn test_top_k_negative_k() {
+        let runtime = setup();
+        let data = Variable::from_json("[1, 2]").unwrap();
+        for query in [
+            "top_k(@, `-1`)",
+            "top_k(@, to_number('inf'))",
+            "bottom_k(@, to_number('NaN'))",
+        ] {
+            let expr = runtime.compile(query).unwrap();
+            assert!(expr.search(&data).is_err(), "{}", query);
+        }
+
+        // A huge k is clamped to the array length
+        let expr = runtime.compile("top_k(@, `1e300`)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[2, 1]").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_map_expr_safe_captures_errors() {
+        let runtime = setup();
+        let expr = runtime.compile("map_expr_safe('abs(@)', @)").unwrap();
+        let data = Variable::from_json(r#"[-1, "x", 2]"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+
+        let first = arr[0].as_object().unwrap();
+        assert_eq!(first["ok"], Rc::new(Variable::Bool(true)));
+        assert_eq!(first["value"].as_number(), Some(1.0));
+
+        let second = arr[1].as_object().unwrap();
+        assert_eq!(second["ok"], Rc::new(Variable::Bool(false)));
+        assert!(second.contains_key("error"));
+        assert!(!second.contains_key("value"));
+
+        assert_eq!(arr[2].as_object().unwrap()["value"].as_number(), Some(2.0));
+    }
+
+    #[test]
+    fn test_map_expr_safe_invalid_expression() {
+        let runtime = setup();
+        let expr = runtime.compile("map_expr_safe('[[', @)").unwrap();
+        let data = Variable::from_json("[1]").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("aggregate(@, 'cat', {total: 'sum(_group[*].price)', n: 'length(_group)', top: 'max(_group[*].price)'})")
+            .unwrap();
+        let data = Variable::from_json(
+            r#"[{"cat": "a", "price": 1}, {"cat": "b", "price": 5}, {"cat": "a", "price": 2}]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{"a": {"n": 2, "top": 2, "total": 3}, "b": {"n": 1, "top": 5, "total": 5}}"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime
+            .compile(
+                "aggregate(@, &cat, {total: &sum(price), n: &length(_group), top: &max(price)})",
+            )
+            .unwrap();
+        assert_eq!(*expr.search(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_aggregate_column_shorthand() {
+        let runtime = setup();
+        let expr = runtime
+            .compile(
+                "aggregate(@, 'cat', {total: 'sum(price)', n: 'length(_group)', top: 'max(price)'})",
+            )
+            .unwrap();
+        let data = Variable::from_json(
+            r#"[{"cat": "a", "price": 1}, {"cat": "b", "price": 5}, {"cat": "a", "price": 2}, {"cat": "a"}]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{"a": {"n": 3, "top": 2, "total": 3}, "b": {"n": 1, "top": 5, "total": 5}}"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime
+            .compile("aggregate(@, 'cat', {bad: 'sum(cat)'})")
+            .unwrap();
+        assert!(expr.search(&data).is_err());
+
+        // The reserved key holds the group even when a field has its name
+        let data = Variable::from_json(r#"[{"cat": "a", "_group": 1}, {"cat": "a"}]"#).unwrap();
+        let expr = runtime
+            .compile("aggregate(@, 'cat', {n: 'length(_group)', first: '_group[0]._group'})")
+            .unwrap();
+        assert_eq!(
+            *expr.search(&data).unwrap(),
+            Variable::from_json(r#"{"a": {"n": 2, "first": 1}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_aggregate_rejects_non_string_aggregation() {
+        let runtime = setup();
+        let expr = runtime.compile("aggregate(@, 'cat', {n: `1`})").unwrap();
+        let data = Variable::from_json(r#"[{"cat": "a"}]"#).unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_array_diff_by_value() {
+        let runtime = setup();
+        let expr = runtime.compile("array_diff(old, new)").unwrap();
+        let data = Variable::from_json(r#"{"old": [1, 2, 2, 3], "new": [2, 3, 4]}"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{"added": [4], "removed": [1, 2], "changed": [], "unchanged": [2, 3]}"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_array_diff_by_key() {
+        let runtime = setup();
+        let expr = runtime.compile("array_diff(old, new, 'id')").unwrap();
+        let data = Variable::from_json(
+            r#"{
+                "old": [{"id": 1, "v": "a"}, {"id": 2, "v": "x"}, {"id": 4}],
+                "new": [{"id": 1, "v": "b", "extra": true}, {"id": 3}, {"id": 4}]
+            }"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{
+                "added": [{"id": 3}],
+                "removed": [{"id": 2, "v": "x"}],
+                "changed": [{
+                    "key": 1,
+                    "old": {"id": 1, "v": "a"},
+                    "new": {"id": 1, "v": "b", "extra": true},
+                    "fields": ["v", "extra"]
+                }],
+                "unchanged": [{"id": 4}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime.compile("array_diff(old, new, &id)").unwrap();
+        assert_eq!(*expr.search(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_array_diff_keys_are_typed() {
+        let runtime = setup();
+        let expr = runtime.compile("array_diff(old, new, 'id')").unwrap();
+        let data = Variable::from_json(
+            r#"{"old": [{"id": 1}, {"v": "a"}], "new": [{"id": "1"}, {"v": "b"}]}"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        // 1 and "1" are different keys, and missing keys pair with nothing
+        let expected = Variable::from_json(
+            r#"{
+                "added": [{"id": "1"}, {"v": "b"}],
+                "removed": [{"id": 1}, {"v": "a"}],
+                "changed": [],
+                "unchanged": []
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_join_inner_and_left() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{
+                "users": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}],
+                "orders": [{"id": 1, "total": 5}, {"id": 1, "total": 7}, {"id": 3, "total": 1}]
+            }"#,
+        )
+        .unwrap();
+
+        let expr = runtime.compile("join_inner(users, orders, 'id')").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"[{"id": 1, "name": "a", "total": 5}, {"id": 1, "name": "a", "total": 7}]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime.compile("join_left(users, orders, 'id')").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"[
+                {"id": 1, "name": "a", "total": 5},
+                {"id": 1, "name": "a", "total": 7},
+                {"id": 2, "name": "b"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime.compile("join_left(users, orders, &id)").unwrap();
+        assert_eq!(*expr.search(&data).unwrap(), expected);
+        let expr = runtime
+            .compile("length(join_inner(users, orders, &id))")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_number(), Some(2.0));
+    }
+
+    #[test]
+    fn test_join_keys_are_typed() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{
+                "users": [{"id": 1, "name": "a"}, {"name": "b"}],
+                "orders": [{"id": "1", "total": 5}, {"total": 7}, {"id": null, "total": 9}]
+            }"#,
+        )
+        .unwrap();
+        // "1" does not match 1, and a missing id matches nothing
+        let expr = runtime.compile("join_inner(users, orders, 'id')").unwrap();
+        assert_eq!(*expr.search(&data).unwrap(), Variable::Array(vec![]));
+        let expr = runtime.compile("join_left(users, orders, 'id')").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            data.as_object().unwrap()["users"]
+        );
+    }
+
+    #[test]
+    fn test_join_requires_objects() {
+        let runtime = setup();
+        let expr = runtime.compile("join_inner(`[1]`, `[1]`, '@')").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let runtime = setup();
+        let expr = runtime.compile("lookup(@, 'id')").unwrap();
+        let data = Variable::from_json(
+            r#"[{"id": "a", "v": 1}, {"id": "b", "v": 2}, {"id": "a", "v": 3}]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected =
+            Variable::from_json(r#"{"a": {"id": "a", "v": 3}, "b": {"id": "b", "v": 2}}"#).unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime.compile("lookup(@, &id)").unwrap();
+        assert_eq!(*expr.search(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_min_by_expr() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#)
+                .unwrap();
+        let expr = runtime.compile("min_by_expr('age', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "Bob");
+    }
+
+    #[test]
+    fn test_min_by_expr_empty() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime.compile("min_by_expr('age', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn test_max_by_expr() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#)
+                .unwrap();
+        let expr = runtime.compile("max_by_expr('age', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "Alice");
+    }
+
+    #[test]
+    fn test_dedup_by() {
+        let runtime = setup();
+        let expr = runtime.compile("dedup_by('state', @)[*].t").unwrap();
+        let data = Variable::from_json(
+            r#"[
+                {"state": "up", "t": 1},
+                {"state": "up", "t": 2},
+                {"state": "down", "t": 3},
+                {"state": "down", "t": 4},
+                {"state": "up", "t": 5}
+            ]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, Variable::from_json("[1, 3, 5]").unwrap());
+    }
+
+    #[test]
+    fn test_unique_by_expr() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"type": "a", "val": 1}, {"type": "b", "val": 2}, {"type": "a", "val": 3}]"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("unique_by_expr('type', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2); // First "a" and first "b"
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("val")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_flat_map_expr() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"tags": ["a", "b"]}, {"tags": ["c"]}, {"tags": ["d", "e"]}]"#)
+                .unwrap();
+        let expr = runtime.compile("flat_map_expr('tags', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 5);
+        assert_eq!(arr[0].as_string().unwrap(), "a");
+        assert_eq!(arr[4].as_string().unwrap(), "e");
+    }
+
+    #[test]
+    fn test_flat_map_expr_non_array() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[{"name": "Alice"}, {"name": "Bob"}]"#).unwrap();
+        let expr = runtime.compile("flat_map_expr('name', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0].as_string().unwrap(), "Alice");
+    }
+
+    #[test]
+    fn test_some_alias() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
+        let expr = runtime.compile("some('@ > `3`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_every_alias() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[2, 4, 6]"#).unwrap();
+        let expr = runtime.compile("every('@ > `0`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_reject() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
+        let expr = runtime.compile("reject('@ > `2`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2); // 1, 2
+        assert_eq!(arr[0].as_number().unwrap(), 1.0);
+        assert_eq!(arr[1].as_number().unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_reject_objects() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"active": true}, {"active": false}, {"active": true}]"#)
+                .unwrap();
+        let expr = runtime.compile("reject('active', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 1); // Only the inactive one
+    }
+
+    #[test]
+    fn test_map_keys() {
+        let runtime = setup();
+        // Use length to transform key to its length (as string)
+        let data = Variable::from_json(r#"{"abc": 1, "de": 2}"#).unwrap();
+        let expr = runtime.compile("map_keys('length(@)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        // "abc" -> 3, "de" -> 2 (converted to string keys)
+        assert!(obj.contains_key("3") || obj.contains_key("2"));
+    }
+
+    #[test]
+    fn test_map_values_add() {
+        let runtime = setup();
+        // Use sum to double values - sum of array with value twice
+        let data = Variable::from_json(r#"{"a": 1, "b": 2, "c": 3}"#).unwrap();
+        let expr = runtime.compile("map_values('sum(`[1]`)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        // Each value becomes 1 (sum of [1])
+        assert_eq!(obj.get("a").unwrap().as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_map_values_length() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"{"name": "alice", "city": "boston"}"#).unwrap();
+        let expr = runtime.compile("map_values('length(@)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("name").unwrap().as_number().unwrap(), 5.0); // "alice" = 5 chars
+        assert_eq!(obj.get("city").unwrap().as_number().unwrap(), 6.0); // "boston" = 6 chars
+    }
+
+    #[test]
+    #[cfg(feature = "string")]
+    fn test_map_values_with_string_fns() {
+        // Full integration test with string functions
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        crate::string::register(&mut runtime);
+
+        let data = Variable::from_json(r#"{"name": "alice", "city": "boston"}"#).unwrap();
+        let expr = runtime.compile("map_values('upper(@)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "ALICE");
+        assert_eq!(obj.get("city").unwrap().as_string().unwrap(), "BOSTON");
+    }
+
+    #[test]
+    #[cfg(feature = "string")]
+    fn test_map_keys_with_string_fns() {
+        // Full integration test with string functions
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        crate::string::register(&mut runtime);
+
+        let data = Variable::from_json(r#"{"hello": 1, "world": 2}"#).unwrap();
+        let expr = runtime.compile("map_keys('upper(@)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert!(obj.contains_key("HELLO"));
+        assert!(obj.contains_key("WORLD"));
+    }
+
+    #[test]
+    fn test_order_by_single_field_asc() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"name": "Charlie", "age": 30}, {"name": "Alice", "age": 25}, {"name": "Bob", "age": 35}]"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile(r#"order_by(@, `[["name", "asc"]]`)"#)
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Alice"
+        );
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Bob"
+        );
+        assert_eq!(
+            arr[2]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Charlie"
+        );
+    }
+
+    #[test]
+    fn test_order_by_single_field_desc() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"name": "Alice", "age": 25}, {"name": "Bob", "age": 35}, {"name": "Charlie", "age": 30}]"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile(r#"order_by(@, `[["age", "desc"]]`)"#)
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("age")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            35.0
+        );
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("age")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            30.0
+        );
+        assert_eq!(
+            arr[2]
+                .as_object()
+                .unwrap()
+                .get("age")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            25.0
+        );
+    }
+
+    #[test]
+    fn test_order_by_multiple_fields() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"dept": "sales", "name": "Bob"}, {"dept": "eng", "name": "Alice"}, {"dept": "sales", "name": "Alice"}]"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile(r#"order_by(@, `[["dept", "asc"], ["name", "asc"]]`)"#)
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        // eng comes first, then sales (sorted by dept)
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("dept")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "eng"
+        );
+        // Within sales, Alice comes before Bob
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Alice"
+        );
+        assert_eq!(
+            arr[2]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Bob"
+        );
+    }
+
+    #[test]
+    fn test_reduce_expr_sum() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
+        let expr = runtime
+            .compile("reduce_expr('sum([accumulator, current])', @, `0`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 15.0);
+    }
+
+    #[test]
+    fn test_reduce_expr_max() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[3, 1, 4, 1, 5, 9, 2, 6]"#).unwrap();
+        let expr = runtime
+            .compile("reduce_expr('max([accumulator, current])', @, `0`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 9.0);
+    }
+
+    #[test]
+    fn test_reduce_expr_empty() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime
+            .compile("reduce_expr('sum([accumulator, current])', @, `42`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 42.0); // Returns initial value
+    }
+
+    #[test]
+    fn test_fold_alias() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
+        let expr = runtime
+            .compile("fold('sum([accumulator, current])', @, `0`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 6.0);
+    }
+
+    #[test]
+    fn test_scan_expr_running_sum() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3, 4]"#).unwrap();
+        let expr = runtime
+            .compile("scan_expr('sum([accumulator, current])', @, `0`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        // Running sum: [1, 3, 6, 10]
+        assert_eq!(arr.len(), 4);
+        assert_eq!(arr[0].as_number().unwrap(), 1.0);
+        assert_eq!(arr[1].as_number().unwrap(), 3.0);
+        assert_eq!(arr[2].as_number().unwrap(), 6.0);
+        assert_eq!(arr[3].as_number().unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_scan_expr_empty() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime
+            .compile("scan_expr('sum([accumulator, current])', @, `0`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 0);
+    }
+
+    #[test]
+    fn test_reduce_expr_with_index() {
+        let runtime = setup();
+        // Access the index in the reduce expression
+        let data = Variable::from_json(r#"[10, 20, 30]"#).unwrap();
+        let expr = runtime
+            .compile("reduce_expr('sum([accumulator, index])', @, `0`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        // 0 + 1 + 2 = 3
+        assert_eq!(result.as_number().unwrap(), 3.0);
+    }
+
+    #[test]
+    fn test_count_by_objects() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"[{"type": "a"}, {"type": "b"}, {"type": "a"}, {"type": "a"}]"#)
+                .unwrap();
+        let expr = runtime.compile("count_by('type', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("a").unwrap().as_number().unwrap(), 3.0);
+        assert_eq!(obj.get("b").unwrap().as_number().unwrap(), 1.0);
     }
 
     #[test]
-    fn test_all_expr_empty() {
+    fn test_count_by_strings() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"["a", "b", "a", "c", "a"]"#).unwrap();
+        let expr = runtime.compile("count_by('@', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("a").unwrap().as_number().unwrap(), 3.0);
+        assert_eq!(obj.get("b").unwrap().as_number().unwrap(), 1.0);
+        assert_eq!(obj.get("c").unwrap().as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_count_by_empty() {
         let runtime = setup();
         let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime.compile("all_expr('active', @)").unwrap();
+        let expr = runtime.compile("count_by('type', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(result.as_boolean().unwrap()); // vacuous truth
+        let obj = result.as_object().unwrap();
+        assert!(obj.is_empty());
     }
 
     #[test]
-    fn test_find_expr_found() {
+    fn test_count_by_numbers() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]"#)
+        let data = Variable::from_json(r#"[1, 2, 1, 3, 1, 2]"#).unwrap();
+        let expr = runtime.compile("count_by('@', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("1").unwrap().as_number().unwrap(), 3.0);
+        assert_eq!(obj.get("2").unwrap().as_number().unwrap(), 2.0);
+        assert_eq!(obj.get("3").unwrap().as_number().unwrap(), 1.0);
+    }
+
+    // =============================================================================
+    // Partial application tests
+    // =============================================================================
+
+    #[test]
+    fn test_partial_creates_object() {
+        let runtime = setup();
+        let data = Variable::Null;
+        let expr = runtime.compile("partial('length')").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        assert!(obj.get("__partial__").unwrap().as_boolean().unwrap());
+        assert_eq!(obj.get("fn").unwrap().as_string().unwrap(), "length");
+        assert!(obj.get("args").unwrap().as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_partial_with_args() {
+        let runtime = setup();
+        let data = Variable::Null;
+        let expr = runtime
+            .compile("partial('contains', `\"hello world\"`)")
             .unwrap();
-        let expr = runtime.compile("find_expr('id == `2`', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "Bob");
+        assert!(obj.get("__partial__").unwrap().as_boolean().unwrap());
+        assert_eq!(obj.get("fn").unwrap().as_string().unwrap(), "contains");
+        let args = obj.get("args").unwrap().as_array().unwrap();
+        assert_eq!(args.len(), 1);
+        assert_eq!(args[0].as_string().unwrap(), "hello world");
     }
 
     #[test]
-    fn test_find_expr_not_found() {
+    fn test_apply_with_fn_name() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[{"id": 1}, {"id": 2}]"#).unwrap();
-        let expr = runtime.compile("find_expr('id == `99`', @)").unwrap();
+        let data = Variable::Null;
+        let expr = runtime.compile("apply('length', `\"hello\"`)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(result.is_null());
+        assert_eq!(result.as_number().unwrap(), 5.0);
     }
 
     #[test]
-    fn test_sort_by_expr_numbers() {
+    fn test_apply_with_partial() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[{"val": 3}, {"val": 1}, {"val": 2}]"#).unwrap();
-        let expr = runtime.compile("sort_by_expr('val', @)").unwrap();
+        let data = Variable::Null;
+        // Create partial with first arg, then apply with second arg
+        let expr = runtime
+            .compile("apply(partial('contains', `\"hello world\"`), `\"world\"`)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("val")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            1.0
-        );
-        assert_eq!(
-            arr[1]
-                .as_object()
-                .unwrap()
-                .get("val")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            2.0
-        );
-        assert_eq!(
-            arr[2]
-                .as_object()
-                .unwrap()
-                .get("val")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            3.0
-        );
+        assert!(result.as_boolean().unwrap());
     }
 
     #[test]
-    fn test_sort_by_expr_strings() {
+    fn test_apply_partial_not_found() {
         let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"name": "Charlie"}, {"name": "Alice"}, {"name": "Bob"}]"#)
-                .unwrap();
-        let expr = runtime.compile("sort_by_expr('name', @)").unwrap();
+        let data = Variable::Null;
+        let expr = runtime
+            .compile("apply(partial('contains', `\"hello world\"`), `\"xyz\"`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_partial_with_multiple_prefilled_args() {
+        let runtime = setup();
+        let data = Variable::Null;
+        // partial with 2 args pre-filled
+        let expr = runtime.compile("partial('join', `\"-\"`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let obj = result.as_object().unwrap();
+        let args = obj.get("args").unwrap().as_array().unwrap();
+        assert_eq!(args.len(), 1);
+        assert_eq!(args[0].as_string().unwrap(), "-");
+    }
+
+    #[test]
+    fn test_apply_partial_join() {
+        let runtime = setup();
+        let data = Variable::Null;
+        // Create a join with "-" separator, then apply to array
+        let expr = runtime
+            .compile("apply(partial('join', `\"-\"`), `[\"a\", \"b\", \"c\"]`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_string().unwrap(), "a-b-c");
+    }
+
+    // =========================================================================
+    // Pipeline pattern tests
+    // =========================================================================
+
+    #[test]
+    fn test_pipeline_filter_sort_products() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{
+                "products": [
+                    {"name": "A", "price": 30, "in_stock": true},
+                    {"name": "B", "price": 10, "in_stock": true},
+                    {"name": "C", "price": 20, "in_stock": false},
+                    {"name": "D", "price": 5, "in_stock": true}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile("products | filter_expr('in_stock', @) | sort_by_expr('price', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
         assert_eq!(
             arr[0]
                 .as_object()
@@ -2411,8 +6525,8 @@ mod tests {
                 .unwrap()
                 .as_string()
                 .unwrap(),
-            "Alice"
-        );
+            "D"
+        ); // $5
         assert_eq!(
             arr[1]
                 .as_object()
@@ -2421,1309 +6535,1647 @@ mod tests {
                 .unwrap()
                 .as_string()
                 .unwrap(),
-            "Bob"
-        );
+            "B"
+        ); // $10
+    }
+
+    #[test]
+    fn test_pipeline_funnel_errors() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{
+                "events": [
+                    {"level": "error", "timestamp": 1704067300, "message": "Disk full"},
+                    {"level": "info", "timestamp": 1704067200, "message": "Started"},
+                    {"level": "error", "timestamp": 1704067400, "message": "Connection lost"},
+                    {"level": "warn", "timestamp": 1704067350, "message": "High memory"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile(
+                r#"events | filter_expr('level == `"error"`', @) | sort_by_expr('timestamp', @)"#,
+            )
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        // Sorted by timestamp ascending
         assert_eq!(
-            arr[2]
+            arr[0]
                 .as_object()
                 .unwrap()
-                .get("name")
+                .get("message")
                 .unwrap()
                 .as_string()
                 .unwrap(),
-            "Charlie"
+            "Disk full"
         );
     }
 
     #[test]
-    fn test_find_index_expr_found() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#).unwrap();
-        let expr = runtime.compile("find_index_expr('id == `2`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 1.0);
-    }
-
-    #[test]
-    fn test_find_index_expr_not_found() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[{"id": 1}, {"id": 2}]"#).unwrap();
-        let expr = runtime.compile("find_index_expr('id == `99`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), -1.0);
-    }
-
-    #[test]
-    fn test_count_expr() {
-        let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"active": true}, {"active": false}, {"active": true}]"#)
-                .unwrap();
-        let expr = runtime.compile("count_expr('active', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 2.0);
-    }
-
-    #[test]
-    fn test_count_expr_none() {
+    fn test_pipeline_transactions_completed() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
-        let expr = runtime.compile("count_expr('@ > `10`', @)").unwrap();
+        let data = Variable::from_json(
+            r#"{
+                "transactions": [
+                    {"amount": 100, "status": "completed"},
+                    {"amount": 50, "status": "completed"},
+                    {"amount": 75, "status": "pending"},
+                    {"amount": 200, "status": "completed"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile(r#"transactions | filter_expr('status == `"completed"`', @) | map_expr('amount', @)"#)
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 0.0);
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0].as_number().unwrap(), 100.0);
+        assert_eq!(arr[1].as_number().unwrap(), 50.0);
+        assert_eq!(arr[2].as_number().unwrap(), 200.0);
     }
 
     #[test]
-    fn test_group_by_expr() {
+    fn test_pipeline_fork_join() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"[{"type": "a", "val": 1}, {"type": "b", "val": 2}, {"type": "a", "val": 3}]"#,
+            r#"{
+                "items": [
+                    {"name": "A", "price": 150},
+                    {"name": "B", "price": 50},
+                    {"name": "C", "price": 200},
+                    {"name": "D", "price": 25}
+                ]
+            }"#,
         )
         .unwrap();
-        let expr = runtime.compile("group_by_expr('type', @)").unwrap();
+        let expr = runtime
+            .compile(
+                r#"@.{
+                    expensive: items | filter_expr('price > `100`', @),
+                    cheap: items | filter_expr('price <= `100`', @)
+                }"#,
+            )
+            .unwrap();
         let result = expr.search(&data).unwrap();
         let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("a").unwrap().as_array().unwrap().len(), 2);
-        assert_eq!(obj.get("b").unwrap().as_array().unwrap().len(), 1);
+        assert_eq!(obj.get("expensive").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(obj.get("cheap").unwrap().as_array().unwrap().len(), 2);
     }
 
     #[test]
-    fn test_partition_expr() {
+    fn test_pipeline_nested_users() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
-        let expr = runtime.compile("partition_expr('@ > `3`', @)").unwrap();
+        let data = Variable::from_json(
+            r#"{
+                "users": [
+                    {"name": "Alice", "orders": [{"total": 100}, {"total": 50}]},
+                    {"name": "Bob", "orders": [{"total": 200}]},
+                    {"name": "Carol", "orders": []}
+                ]
+            }"#,
+        )
+        .unwrap();
+        // Filter users with orders, then map to get names
+        let expr = runtime
+            .compile("users | filter_expr('length(orders) > `0`', @) | map_expr('name', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        let matches = arr[0].as_array().unwrap();
-        let non_matches = arr[1].as_array().unwrap();
-        assert_eq!(matches.len(), 2); // 4, 5
-        assert_eq!(non_matches.len(), 3); // 1, 2, 3
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0].as_string().unwrap(), "Alice");
+        assert_eq!(arr[1].as_string().unwrap(), "Bob");
     }
 
     #[test]
-    fn test_min_by_expr() {
+    fn test_pipeline_rag_chunks() {
         let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#)
-                .unwrap();
-        let expr = runtime.compile("min_by_expr('age', @)").unwrap();
+        let data = Variable::from_json(
+            r#"{
+                "chunks": [
+                    {"content": "Redis is fast", "score": 0.9},
+                    {"content": "Redis is in-memory", "score": 0.85},
+                    {"content": "Unrelated content", "score": 0.5},
+                    {"content": "Redis supports modules", "score": 0.75}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile("chunks | filter_expr('score > `0.7`', @) | sort_by_expr('score', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "Bob");
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        // Sorted ascending by score
+        assert_eq!(
+            arr[0]
+                .as_object()
+                .unwrap()
+                .get("score")
+                .unwrap()
+                .as_number()
+                .unwrap(),
+            0.75
+        );
     }
 
+    // =========================================================================
+    // Additional reduce_expr/scan_expr tests
+    // =========================================================================
+
     #[test]
-    fn test_min_by_expr_empty() {
+    fn test_reduce_expr_product() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime.compile("min_by_expr('age', @)").unwrap();
+        // Test reduce with min (similar to existing max test but finds minimum)
+        let data = Variable::from_json(r#"[5, 3, 8, 1, 9]"#).unwrap();
+        let expr = runtime
+            .compile("reduce_expr('min([accumulator, current])', @, `100`)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(result.is_null());
+        assert_eq!(result.as_number().unwrap(), 1.0);
     }
 
     #[test]
-    fn test_max_by_expr() {
+    fn test_scan_expr_running_balance() {
         let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"name": "Alice", "age": 30}, {"name": "Bob", "age": 25}]"#)
-                .unwrap();
-        let expr = runtime.compile("max_by_expr('age', @)").unwrap();
+        // Test scan with running max - shows progressive maximum
+        let data = Variable::from_json(r#"[3, 1, 4, 1, 5, 9]"#).unwrap();
+        let expr = runtime
+            .compile("scan_expr('max([accumulator, current])', @, `0`)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "Alice");
+        let arr = result.as_array().unwrap();
+        // Running max: 3, 3, 4, 4, 5, 9
+        assert_eq!(arr[0].as_number().unwrap(), 3.0);
+        assert_eq!(arr[1].as_number().unwrap(), 3.0);
+        assert_eq!(arr[2].as_number().unwrap(), 4.0);
+        assert_eq!(arr[3].as_number().unwrap(), 4.0);
+        assert_eq!(arr[4].as_number().unwrap(), 5.0);
+        assert_eq!(arr[5].as_number().unwrap(), 9.0);
     }
 
+    // =========================================================================
+    // Additional order_by tests
+    // =========================================================================
+
     #[test]
-    fn test_unique_by_expr() {
+    fn test_order_by_three_fields() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"[{"type": "a", "val": 1}, {"type": "b", "val": 2}, {"type": "a", "val": 3}]"#,
+            r#"[
+                {"dept": "Engineering", "level": "senior", "name": "Charlie"},
+                {"dept": "Engineering", "level": "junior", "name": "Alice"},
+                {"dept": "Engineering", "level": "senior", "name": "Bob"},
+                {"dept": "Sales", "level": "senior", "name": "David"}
+            ]"#,
         )
         .unwrap();
-        let expr = runtime.compile("unique_by_expr('type', @)").unwrap();
+        let expr = runtime
+            .compile(r#"order_by(@, `[["dept", "asc"], ["level", "desc"], ["name", "asc"]]`)"#)
+            .unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2); // First "a" and first "b"
+        // Engineering seniors first (alphabetical), then Engineering juniors, then Sales
         assert_eq!(
             arr[0]
                 .as_object()
                 .unwrap()
-                .get("val")
+                .get("name")
                 .unwrap()
-                .as_number()
+                .as_string()
                 .unwrap(),
-            1.0
+            "Bob"
+        );
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("name")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "Charlie"
         );
     }
 
     #[test]
-    fn test_flat_map_expr() {
+    fn test_order_by_empty() {
         let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"tags": ["a", "b"]}, {"tags": ["c"]}, {"tags": ["d", "e"]}]"#)
-                .unwrap();
-        let expr = runtime.compile("flat_map_expr('tags', @)").unwrap();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime
+            .compile(r#"order_by(@, `[["name", "asc"]]`)"#)
+            .unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 5);
-        assert_eq!(arr[0].as_string().unwrap(), "a");
-        assert_eq!(arr[4].as_string().unwrap(), "e");
+        assert!(arr.is_empty());
     }
 
+    // =========================================================================
+    // Additional partition_expr tests
+    // =========================================================================
+
     #[test]
-    fn test_flat_map_expr_non_array() {
+    fn test_partition_expr_scores() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[{"name": "Alice"}, {"name": "Bob"}]"#).unwrap();
-        let expr = runtime.compile("flat_map_expr('name', @)").unwrap();
+        let data = Variable::from_json(r#"[85, 42, 91, 67, 55, 78, 33, 99]"#).unwrap();
+        let expr = runtime.compile("partition_expr('@ >= `60`', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
-        assert_eq!(arr[0].as_string().unwrap(), "Alice");
+        let passing = arr[0].as_array().unwrap();
+        let failing = arr[1].as_array().unwrap();
+        assert_eq!(passing.len(), 5); // 85, 91, 67, 78, 99
+        assert_eq!(failing.len(), 3); // 42, 55, 33
     }
 
     #[test]
-    fn test_some_alias() {
+    fn test_partition_expr_active() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
-        let expr = runtime.compile("some('@ > `3`', @)").unwrap();
+        let data =
+            Variable::from_json(r#"[{"active": true}, {"active": false}, {"active": true}]"#)
+                .unwrap();
+        let expr = runtime.compile("partition_expr('active', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(result.as_boolean().unwrap());
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr[0].as_array().unwrap().len(), 2);
+        assert_eq!(arr[1].as_array().unwrap().len(), 1);
     }
 
-    #[test]
-    fn test_every_alias() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[2, 4, 6]"#).unwrap();
-        let expr = runtime.compile("every('@ > `0`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(result.as_boolean().unwrap());
-    }
+    // =========================================================================
+    // Additional map_values/map_keys tests
+    // =========================================================================
 
     #[test]
-    fn test_reject() {
+    fn test_map_values_discount() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
-        let expr = runtime.compile("reject('@ > `2`', @)").unwrap();
+        // Test with string transformation since nested expressions don't have extension math functions
+        let data = Variable::from_json(r#"{"apple": "FRUIT", "banana": "ITEM"}"#).unwrap();
+        let expr = runtime.compile("map_values('length(@)', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2); // 1, 2
-        assert_eq!(arr[0].as_number().unwrap(), 1.0);
-        assert_eq!(arr[1].as_number().unwrap(), 2.0);
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("apple").unwrap().as_number().unwrap(), 5.0);
+        assert_eq!(obj.get("banana").unwrap().as_number().unwrap(), 4.0);
     }
 
-    #[test]
-    fn test_reject_objects() {
-        let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"active": true}, {"active": false}, {"active": true}]"#)
-                .unwrap();
-        let expr = runtime.compile("reject('active', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 1); // Only the inactive one
-    }
+    // =========================================================================
+    // pick_by/omit_by tests
+    // =========================================================================
 
     #[test]
-    fn test_map_keys() {
+    fn test_pick_by_value() {
         let runtime = setup();
-        // Use length to transform key to its length (as string)
-        let data = Variable::from_json(r#"{"abc": 1, "de": 2}"#).unwrap();
-        let expr = runtime.compile("map_keys('length(@)', @)").unwrap();
+        let data = Variable::from_json(r#"{"a": 1, "b": null, "c": 3}"#).unwrap();
+        let expr = runtime.compile("pick_by('value != `null`', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let obj = result.as_object().unwrap();
-        // "abc" -> 3, "de" -> 2 (converted to string keys)
-        assert!(obj.contains_key("3") || obj.contains_key("2"));
+        assert_eq!(obj.len(), 2);
+        assert!(obj.contains_key("a"));
+        assert!(obj.contains_key("c"));
+
+        let expr = runtime.compile("pick_by(&value, @)").unwrap();
+        assert_eq!(expr.search(&data).unwrap(), result);
     }
 
     #[test]
-    fn test_map_values_add() {
+    fn test_pick_by_key() {
         let runtime = setup();
-        // Use sum to double values - sum of array with value twice
-        let data = Variable::from_json(r#"{"a": 1, "b": 2, "c": 3}"#).unwrap();
-        let expr = runtime.compile("map_values('sum(`[1]`)', @)").unwrap();
+        let data = Variable::from_json(r#"{"x_a": 1, "x_b": 2, "y": 3}"#).unwrap();
+        let expr = runtime
+            .compile("pick_by('starts_with(key, `\"x_\"`)', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        // Each value becomes 1 (sum of [1])
-        assert_eq!(obj.get("a").unwrap().as_number().unwrap(), 1.0);
+        let keys: Vec<&String> = result.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["x_a", "x_b"]);
     }
 
     #[test]
-    fn test_map_values_length() {
+    fn test_omit_by_private_and_null() {
         let runtime = setup();
-        let data = Variable::from_json(r#"{"name": "alice", "city": "boston"}"#).unwrap();
-        let expr = runtime.compile("map_values('length(@)', @)").unwrap();
+        let data = Variable::from_json(r#"{"_id": 1, "name": "x", "note": null}"#).unwrap();
+        let expr = runtime
+            .compile("omit_by('starts_with(key, `\"_\"`) || value == `null`', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("name").unwrap().as_number().unwrap(), 5.0); // "alice" = 5 chars
-        assert_eq!(obj.get("city").unwrap().as_number().unwrap(), 6.0); // "boston" = 6 chars
+        let keys: Vec<&String> = result.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["name"]);
+
+        let expr = runtime
+            .compile("omit_by(&starts_with(key, '_') || value == `null`, @)")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap(), result);
     }
 
     #[test]
-    #[cfg(feature = "string")]
-    fn test_map_values_with_string_fns() {
-        // Full integration test with string functions
-        let mut runtime = Runtime::new();
-        runtime.register_builtin_functions();
-        register(&mut runtime);
-        crate::string::register(&mut runtime);
+    fn test_pick_by_empty_object() {
+        let runtime = setup();
+        let expr = runtime.compile("pick_by('value', `{}`)").unwrap();
+        let result = expr.search(Variable::Null).unwrap();
+        assert!(result.as_object().unwrap().is_empty());
+    }
 
-        let data = Variable::from_json(r#"{"name": "alice", "city": "boston"}"#).unwrap();
-        let expr = runtime.compile("map_values('upper(@)', @)").unwrap();
+    // =========================================================================
+    // Additional group_by_expr tests
+    // =========================================================================
+
+    #[test]
+    fn test_group_by_expr_type() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"type": "fruit", "name": "apple"}, {"type": "vegetable", "name": "carrot"}, {"type": "fruit", "name": "banana"}]"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("group_by_expr('type', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("name").unwrap().as_string().unwrap(), "ALICE");
-        assert_eq!(obj.get("city").unwrap().as_string().unwrap(), "BOSTON");
+        assert_eq!(obj.get("fruit").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(obj.get("vegetable").unwrap().as_array().unwrap().len(), 1);
     }
 
     #[test]
-    #[cfg(feature = "string")]
-    fn test_map_keys_with_string_fns() {
-        // Full integration test with string functions
-        let mut runtime = Runtime::new();
-        runtime.register_builtin_functions();
-        register(&mut runtime);
-        crate::string::register(&mut runtime);
-
-        let data = Variable::from_json(r#"{"hello": 1, "world": 2}"#).unwrap();
-        let expr = runtime.compile("map_keys('upper(@)', @)").unwrap();
+    fn test_group_by_expr_computed() {
+        let runtime = setup();
+        // Group strings by their length using built-in length function
+        let data = Variable::from_json(r#"["a", "bb", "ccc", "dd", "eee", "f"]"#).unwrap();
+        let expr = runtime
+            .compile("group_by_expr('to_string(length(@))', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
         let obj = result.as_object().unwrap();
-        assert!(obj.contains_key("HELLO"));
-        assert!(obj.contains_key("WORLD"));
+        assert!(obj.contains_key("1")); // "a", "f"
+        assert!(obj.contains_key("2")); // "bb", "dd"
+        assert!(obj.contains_key("3")); // "ccc", "eee"
+        assert_eq!(obj.get("1").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(obj.get("2").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(obj.get("3").unwrap().as_array().unwrap().len(), 2);
     }
 
+    // =========================================================================
+    // Additional unique_by_expr tests
+    // =========================================================================
+
     #[test]
-    fn test_order_by_single_field_asc() {
+    fn test_unique_by_expr_id() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"[{"name": "Charlie", "age": 30}, {"name": "Alice", "age": 25}, {"name": "Bob", "age": 35}]"#,
+            r#"[{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 1, "v": "c"}]"#,
         )
         .unwrap();
-        let expr = runtime
-            .compile(r#"order_by(@, `[["name", "asc"]]`)"#)
-            .unwrap();
+        let expr = runtime.compile("unique_by_expr('id', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        // Keeps first occurrence
         assert_eq!(
             arr[0]
                 .as_object()
                 .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Alice"
-        );
-        assert_eq!(
-            arr[1]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Bob"
-        );
-        assert_eq!(
-            arr[2]
-                .as_object()
-                .unwrap()
-                .get("name")
+                .get("v")
                 .unwrap()
                 .as_string()
                 .unwrap(),
-            "Charlie"
+            "a"
         );
     }
 
+    // =========================================================================
+    // Edge case tests
+    // =========================================================================
+
     #[test]
-    fn test_order_by_single_field_desc() {
+    fn test_any_expr_empty() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"[{"name": "Alice", "age": 25}, {"name": "Bob", "age": 35}, {"name": "Charlie", "age": 30}]"#,
-        )
-        .unwrap();
-        let expr = runtime
-            .compile(r#"order_by(@, `[["age", "desc"]]`)"#)
-            .unwrap();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime.compile("any_expr('@ > `0`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_max_by_expr_empty() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime.compile("max_by_expr('age', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn test_flat_map_expr_duplicate() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
+        // Duplicate each element
+        let expr = runtime.compile("flat_map_expr('[@, @]', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("age")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            35.0
-        );
-        assert_eq!(
-            arr[1]
-                .as_object()
-                .unwrap()
-                .get("age")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            30.0
-        );
-        assert_eq!(
-            arr[2]
-                .as_object()
-                .unwrap()
-                .get("age")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            25.0
-        );
+        assert_eq!(arr.len(), 6);
     }
 
     #[test]
-    fn test_order_by_multiple_fields() {
+    fn test_reject_greater_than() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"[{"dept": "sales", "name": "Bob"}, {"dept": "eng", "name": "Alice"}, {"dept": "sales", "name": "Alice"}]"#,
-        )
-        .unwrap();
-        let expr = runtime
-            .compile(r#"order_by(@, `[["dept", "asc"], ["name", "asc"]]`)"#)
-            .unwrap();
+        let data = Variable::from_json(r#"[1, 2, 3, 4, 5, 6]"#).unwrap();
+        let expr = runtime.compile("reject('@ > `3`', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        // eng comes first, then sales (sorted by dept)
-        assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("dept")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "eng"
-        );
-        // Within sales, Alice comes before Bob
-        assert_eq!(
-            arr[1]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Alice"
-        );
-        assert_eq!(
-            arr[2]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Bob"
-        );
+        assert_eq!(arr.len(), 3); // 1, 2, 3
+    }
+
+    #[test]
+    fn test_every_false_case() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, -1, 3]"#).unwrap();
+        let expr = runtime.compile("every('@ > `0`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(!result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_count_expr_all_match() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[5, 10, 15, 20]"#).unwrap();
+        let expr = runtime.compile("count_expr('@ > `0`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 4.0);
+    }
+
+    #[test]
+    fn test_find_expr_first_match() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 5, 10, 15]"#).unwrap();
+        let expr = runtime.compile("find_expr('@ > `3`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_find_index_expr_first_match() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"[1, 5, 10, 15]"#).unwrap();
+        let expr = runtime.compile("find_index_expr('@ > `3`', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1.0);
     }
 
     #[test]
-    fn test_reduce_expr_sum() {
+    fn test_split_when() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 4, 5]"#).unwrap();
-        let expr = runtime
-            .compile("reduce_expr('sum([accumulator, current])', @, `0`)")
-            .unwrap();
+        let expr = runtime.compile("split_when('start', @)").unwrap();
+        let data = Variable::from_json(
+            r#"[{"start": true, "n": 1}, {"n": 2}, {"start": true, "n": 3}, {"n": 4}, {"n": 5}]"#,
+        )
+        .unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 15.0);
+        let expected = Variable::from_json(
+            r#"[[{"start": true, "n": 1}, {"n": 2}], [{"start": true, "n": 3}, {"n": 4}, {"n": 5}]]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
     }
 
     #[test]
-    fn test_reduce_expr_max() {
+    fn test_split_when_no_splits() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[3, 1, 4, 1, 5, 9, 2, 6]"#).unwrap();
-        let expr = runtime
-            .compile("reduce_expr('max([accumulator, current])', @, `0`)")
-            .unwrap();
+        let expr = runtime.compile("split_when('@ > `10`', @)").unwrap();
+        let data = Variable::from_json("[1, 2, 3]").unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 9.0);
+        assert_eq!(*result, Variable::from_json("[[1, 2, 3]]").unwrap());
+
+        let data = Variable::from_json("[]").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, Variable::from_json("[]").unwrap());
     }
 
     #[test]
-    fn test_reduce_expr_empty() {
+    fn test_chunk_by() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime
-            .compile("reduce_expr('sum([accumulator, current])', @, `42`)")
-            .unwrap();
+        let expr = runtime.compile("chunk_by('@ > `0`', @)").unwrap();
+        let data = Variable::from_json("[1, 2, -1, -2, 3]").unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 42.0); // Returns initial value
+        assert_eq!(
+            *result,
+            Variable::from_json("[[1, 2], [-1, -2], [3]]").unwrap()
+        );
     }
 
     #[test]
-    fn test_fold_alias() {
+    fn test_take_while_basic() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
-        let expr = runtime
-            .compile("fold('sum([accumulator, current])', @, `0`)")
-            .unwrap();
+        let data = Variable::from_json(r#"[1, 2, 3, 5, 1, 2]"#).unwrap();
+        let expr = runtime.compile("take_while('@ < `4`', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 6.0);
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0].as_number().unwrap(), 1.0);
+        assert_eq!(arr[1].as_number().unwrap(), 2.0);
+        assert_eq!(arr[2].as_number().unwrap(), 3.0);
     }
 
     #[test]
-    fn test_scan_expr_running_sum() {
+    fn test_take_while_all_match() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 4]"#).unwrap();
-        let expr = runtime
-            .compile("scan_expr('sum([accumulator, current])', @, `0`)")
-            .unwrap();
+        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
+        let expr = runtime.compile("take_while('@ < `10`', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
-        // Running sum: [1, 3, 6, 10]
-        assert_eq!(arr.len(), 4);
-        assert_eq!(arr[0].as_number().unwrap(), 1.0);
-        assert_eq!(arr[1].as_number().unwrap(), 3.0);
-        assert_eq!(arr[2].as_number().unwrap(), 6.0);
-        assert_eq!(arr[3].as_number().unwrap(), 10.0);
+        assert_eq!(arr.len(), 3);
     }
 
     #[test]
-    fn test_scan_expr_empty() {
+    fn test_take_while_none_match() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime
-            .compile("scan_expr('sum([accumulator, current])', @, `0`)")
-            .unwrap();
+        let data = Variable::from_json(r#"[5, 6, 7]"#).unwrap();
+        let expr = runtime.compile("take_while('@ < `4`', @)").unwrap();
         let result = expr.search(&data).unwrap();
         let arr = result.as_array().unwrap();
         assert_eq!(arr.len(), 0);
     }
 
     #[test]
-    fn test_reduce_expr_with_index() {
+    fn test_drop_while_basic() {
         let runtime = setup();
-        // Access the index in the reduce expression
-        let data = Variable::from_json(r#"[10, 20, 30]"#).unwrap();
-        let expr = runtime
-            .compile("reduce_expr('sum([accumulator, index])', @, `0`)")
-            .unwrap();
+        let data = Variable::from_json(r#"[1, 2, 3, 5, 1, 2]"#).unwrap();
+        let expr = runtime.compile("drop_while('@ < `4`', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        // 0 + 1 + 2 = 3
-        assert_eq!(result.as_number().unwrap(), 3.0);
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0].as_number().unwrap(), 5.0);
+        assert_eq!(arr[1].as_number().unwrap(), 1.0);
+        assert_eq!(arr[2].as_number().unwrap(), 2.0);
     }
 
     #[test]
-    fn test_count_by_objects() {
+    fn test_drop_while_all_match() {
         let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"type": "a"}, {"type": "b"}, {"type": "a"}, {"type": "a"}]"#)
-                .unwrap();
-        let expr = runtime.compile("count_by('type', @)").unwrap();
+        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
+        let expr = runtime.compile("drop_while('@ < `10`', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("a").unwrap().as_number().unwrap(), 3.0);
-        assert_eq!(obj.get("b").unwrap().as_number().unwrap(), 1.0);
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 0);
     }
 
     #[test]
-    fn test_count_by_strings() {
+    fn test_drop_while_none_match() {
         let runtime = setup();
-        let data = Variable::from_json(r#"["a", "b", "a", "c", "a"]"#).unwrap();
-        let expr = runtime.compile("count_by('@', @)").unwrap();
+        let data = Variable::from_json(r#"[5, 6, 7]"#).unwrap();
+        let expr = runtime.compile("drop_while('@ < `4`', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("a").unwrap().as_number().unwrap(), 3.0);
-        assert_eq!(obj.get("b").unwrap().as_number().unwrap(), 1.0);
-        assert_eq!(obj.get("c").unwrap().as_number().unwrap(), 1.0);
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
     }
 
     #[test]
-    fn test_count_by_empty() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime.compile("count_by('type', @)").unwrap();
+    fn test_zip_with_add() {
+        let mut runtime = setup();
+        crate::math::register(&mut runtime);
+        let data = Variable::Null;
+        let expr = runtime
+            .compile("zip_with('add([0], [1])', `[1, 2, 3]`, `[10, 20, 30]`)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert!(obj.is_empty());
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0].as_number().unwrap(), 11.0);
+        assert_eq!(arr[1].as_number().unwrap(), 22.0);
+        assert_eq!(arr[2].as_number().unwrap(), 33.0);
     }
 
     #[test]
-    fn test_count_by_numbers() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 1, 3, 1, 2]"#).unwrap();
-        let expr = runtime.compile("count_by('@', @)").unwrap();
+    fn test_zip_with_unequal_lengths() {
+        let mut runtime = setup();
+        crate::math::register(&mut runtime);
+        let data = Variable::Null;
+        let expr = runtime
+            .compile("zip_with('add([0], [1])', `[1, 2, 3, 4, 5]`, `[10, 20]`)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("1").unwrap().as_number().unwrap(), 3.0);
-        assert_eq!(obj.get("2").unwrap().as_number().unwrap(), 2.0);
-        assert_eq!(obj.get("3").unwrap().as_number().unwrap(), 1.0);
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0].as_number().unwrap(), 11.0);
+        assert_eq!(arr[1].as_number().unwrap(), 22.0);
     }
 
-    // =============================================================================
-    // Partial application tests
-    // =============================================================================
+    #[test]
+    fn test_zip_with_multiply() {
+        let mut runtime = setup();
+        crate::math::register(&mut runtime);
+        let data = Variable::Null;
+        let expr = runtime
+            .compile("zip_with('multiply([0], [1])', `[2, 3, 4]`, `[5, 6, 7]`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+        assert_eq!(arr[0].as_number().unwrap(), 10.0);
+        assert_eq!(arr[1].as_number().unwrap(), 18.0);
+        assert_eq!(arr[2].as_number().unwrap(), 28.0);
+    }
+
+    // =========================================================================
+    // walk tests
+    // =========================================================================
 
     #[test]
-    fn test_partial_creates_object() {
+    fn test_walk_identity() {
         let runtime = setup();
-        let data = Variable::Null;
-        let expr = runtime.compile("partial('length')").unwrap();
+        let data = Variable::from_json(r#"{"a": [1, 2, 3], "b": {"c": 4}}"#).unwrap();
+        let expr = runtime.compile("walk('@', @)").unwrap();
         let result = expr.search(&data).unwrap();
+        // Identity should return the same structure
+        assert!(result.is_object());
         let obj = result.as_object().unwrap();
-        assert!(obj.get("__partial__").unwrap().as_boolean().unwrap());
-        assert_eq!(obj.get("fn").unwrap().as_string().unwrap(), "length");
-        assert!(obj.get("args").unwrap().as_array().unwrap().is_empty());
+        assert!(obj.contains_key("a"));
+        assert!(obj.contains_key("b"));
     }
 
     #[test]
-    fn test_partial_with_args() {
+    fn test_walk_type_of_all() {
+        let mut runtime = setup();
+        crate::type_conv::register(&mut runtime);
+        let data = Variable::from_json(r#"{"a": 5, "b": [1, 2]}"#).unwrap();
+        // type() works on everything - shows bottom-up processing
+        let expr = runtime.compile("walk('type(@)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        // After walking, everything becomes its type string, and the final result
+        // is type of the top-level result
+        assert_eq!(result.as_string().unwrap(), "object");
+    }
+
+    #[test]
+    fn test_walk_nested_arrays() {
         let runtime = setup();
-        let data = Variable::Null;
-        let expr = runtime
-            .compile("partial('contains', `\"hello world\"`)")
-            .unwrap();
+        // Use only arrays (no scalars inside) so length works at every level
+        let data = Variable::from_json(r#"[[[], []], [[]]]"#).unwrap();
+        // length works on arrays - get lengths at each level
+        let expr = runtime.compile("walk('length(@)', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert!(obj.get("__partial__").unwrap().as_boolean().unwrap());
-        assert_eq!(obj.get("fn").unwrap().as_string().unwrap(), "contains");
-        let args = obj.get("args").unwrap().as_array().unwrap();
-        assert_eq!(args.len(), 1);
-        assert_eq!(args[0].as_string().unwrap(), "hello world");
+        // Inner [] -> 0, outer arrays get lengths, top level has 2 elements
+        assert_eq!(result.as_number().unwrap(), 2.0);
     }
 
     #[test]
-    fn test_apply_with_fn_name() {
+    fn test_walk_scalar() {
+        let mut runtime = setup();
+        crate::math::register(&mut runtime);
+        let data = Variable::Number(serde_json::Number::from(5));
+        // Double the number
+        let expr = runtime.compile("walk('multiply(@, `2`)', @)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_walk_length_all() {
         let runtime = setup();
-        let data = Variable::Null;
-        let expr = runtime.compile("apply('length', `\"hello\"`)").unwrap();
+        let data = Variable::from_json(r#"{"items": ["a", "bb", "ccc"]}"#).unwrap();
+        // Get length of everything (works for strings, arrays, objects)
+        let expr = runtime.compile("walk('length(@)', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 5.0);
+        // Top level object has 1 key
+        assert_eq!(result.as_number().unwrap(), 1.0);
     }
 
     #[test]
-    fn test_apply_with_partial() {
+    fn test_walk_preserves_structure() {
         let runtime = setup();
-        let data = Variable::Null;
-        // Create partial with first arg, then apply with second arg
-        let expr = runtime
-            .compile("apply(partial('contains', `\"hello world\"`), `\"world\"`)")
-            .unwrap();
+        let data = Variable::from_json(r#"{"a": [1, {"b": 2}], "c": "hello"}"#).unwrap();
+        // Identity transform - should preserve structure
+        let expr = runtime.compile("walk('@', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(result.as_boolean().unwrap());
+
+        let obj = result.as_object().unwrap();
+        assert!(obj.contains_key("a"));
+        assert!(obj.contains_key("c"));
+        let arr = obj.get("a").unwrap().as_array().unwrap();
+        assert_eq!(arr.len(), 2);
     }
 
     #[test]
-    fn test_apply_partial_not_found() {
+    fn test_walk_empty_structures() {
         let runtime = setup();
-        let data = Variable::Null;
-        let expr = runtime
-            .compile("apply(partial('contains', `\"hello world\"`), `\"xyz\"`)")
-            .unwrap();
+
+        // Empty array
+        let data = Variable::from_json(r#"[]"#).unwrap();
+        let expr = runtime.compile("walk('@', @)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(!result.as_boolean().unwrap());
-    }
+        assert!(result.as_array().unwrap().is_empty());
 
-    #[test]
-    fn test_partial_with_multiple_prefilled_args() {
-        let runtime = setup();
-        let data = Variable::Null;
-        // partial with 2 args pre-filled
-        let expr = runtime.compile("partial('join', `\"-\"`)").unwrap();
+        // Empty object
+        let data = Variable::from_json(r#"{}"#).unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        let args = obj.get("args").unwrap().as_array().unwrap();
-        assert_eq!(args.len(), 1);
-        assert_eq!(args[0].as_string().unwrap(), "-");
+        assert!(result.as_object().unwrap().is_empty());
     }
 
     #[test]
-    fn test_apply_partial_join() {
+    fn test_eval_against_value() {
         let runtime = setup();
-        let data = Variable::Null;
-        // Create a join with "-" separator, then apply to array
-        let expr = runtime
-            .compile("apply(partial('join', `\"-\"`), `[\"a\", \"b\", \"c\"]`)")
-            .unwrap();
+        let data = Variable::from_json(r#"{"expr": "a.b", "doc": {"a": {"b": 42}}}"#).unwrap();
+        let expr = runtime.compile("eval(expr, doc)").unwrap();
         let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_string().unwrap(), "a-b-c");
+        assert_eq!(result.as_number().unwrap(), 42.0);
     }
 
-    // =========================================================================
-    // Pipeline pattern tests
-    // =========================================================================
-
     #[test]
-    fn test_pipeline_filter_sort_products() {
+    fn test_eval_current_node() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"{
-                "products": [
-                    {"name": "A", "price": 30, "in_stock": true},
-                    {"name": "B", "price": 10, "in_stock": true},
-                    {"name": "C", "price": 20, "in_stock": false},
-                    {"name": "D", "price": 5, "in_stock": true}
-                ]
-            }"#,
-        )
-        .unwrap();
-        let expr = runtime
-            .compile("products | filter_expr('in_stock', @) | sort_by_expr('price', @)")
-            .unwrap();
+        let data =
+            Variable::from_json(r#"{"rule": "length(items) > `1`", "items": [1, 2]}"#).unwrap();
+        let expr = runtime.compile("eval(rule, @)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "D"
-        ); // $5
-        assert_eq!(
-            arr[1]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "B"
-        ); // $10
+        assert!(result.as_boolean().unwrap());
     }
 
     #[test]
-    fn test_pipeline_funnel_errors() {
+    fn test_eval_uses_extension_functions() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"{
-                "events": [
-                    {"level": "error", "timestamp": 1704067300, "message": "Disk full"},
-                    {"level": "info", "timestamp": 1704067200, "message": "Started"},
-                    {"level": "error", "timestamp": 1704067400, "message": "Connection lost"},
-                    {"level": "warn", "timestamp": 1704067350, "message": "High memory"}
-                ]
-            }"#,
-        )
-        .unwrap();
-        let expr = runtime
-            .compile(
-                r#"events | filter_expr('level == `"error"`', @) | sort_by_expr('timestamp', @)"#,
-            )
-            .unwrap();
+        let data =
+            Variable::from_json(r#"{"check": "filter_expr('@ > `2`', @)", "items": [1, 2, 3, 4]}"#)
+                .unwrap();
+        let expr = runtime.compile("eval(check, items)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
-        // Sorted by timestamp ascending
-        assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("message")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Disk full"
-        );
+        assert_eq!(result.as_array().unwrap().len(), 2);
     }
 
     #[test]
-    fn test_pipeline_transactions_completed() {
+    fn test_eval_without_value() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"{
-                "transactions": [
-                    {"amount": 100, "status": "completed"},
-                    {"amount": 50, "status": "completed"},
-                    {"amount": 75, "status": "pending"},
-                    {"amount": 200, "status": "completed"}
-                ]
-            }"#,
-        )
-        .unwrap();
-        let expr = runtime
-            .compile(r#"transactions | filter_expr('status == `"completed"`', @) | map_expr('amount', @)"#)
-            .unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        assert_eq!(arr[0].as_number().unwrap(), 100.0);
-        assert_eq!(arr[1].as_number().unwrap(), 50.0);
-        assert_eq!(arr[2].as_number().unwrap(), 200.0);
+        let expr = runtime.compile("eval('@')").unwrap();
+        let result = expr.search(Variable::from_json("{}").unwrap()).unwrap();
+        assert!(result.is_null());
     }
 
     #[test]
-    fn test_pipeline_fork_join() {
+    fn test_eval_invalid_expression() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"{
-                "items": [
-                    {"name": "A", "price": 150},
-                    {"name": "B", "price": 50},
-                    {"name": "C", "price": 200},
-                    {"name": "D", "price": 25}
-                ]
-            }"#,
-        )
-        .unwrap();
-        let expr = runtime
-            .compile(
-                r#"@.{
-                    expensive: items | filter_expr('price > `100`', @),
-                    cheap: items | filter_expr('price <= `100`', @)
-                }"#,
-            )
-            .unwrap();
-        let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("expensive").unwrap().as_array().unwrap().len(), 2);
-        assert_eq!(obj.get("cheap").unwrap().as_array().unwrap().len(), 2);
+        let expr = runtime.compile("eval('[[', @)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
     }
 
     #[test]
-    fn test_pipeline_nested_users() {
+    fn test_difference_by() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"{
-                "users": [
-                    {"name": "Alice", "orders": [{"total": 100}, {"total": 50}]},
-                    {"name": "Bob", "orders": [{"total": 200}]},
-                    {"name": "Carol", "orders": []}
-                ]
-            }"#,
+            r#"{"a": [{"id": 1, "v": "x"}, {"id": 2}, {"id": 3}], "b": [{"id": 2, "v": "y"}]}"#,
         )
         .unwrap();
-        // Filter users with orders, then map to get names
-        let expr = runtime
-            .compile("users | filter_expr('length(orders) > `0`', @) | map_expr('name', @)")
-            .unwrap();
+        let expr = runtime.compile("difference_by('id', a, b)[*].id").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
-        assert_eq!(arr[0].as_string().unwrap(), "Alice");
-        assert_eq!(arr[1].as_string().unwrap(), "Bob");
+        assert_eq!(*result, Variable::from_json("[1, 3]").unwrap());
     }
 
     #[test]
-    fn test_pipeline_rag_chunks() {
+    fn test_intersection_by() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"{
-                "chunks": [
-                    {"content": "Redis is fast", "score": 0.9},
-                    {"content": "Redis is in-memory", "score": 0.85},
-                    {"content": "Unrelated content", "score": 0.5},
-                    {"content": "Redis supports modules", "score": 0.75}
-                ]
-            }"#,
+            r#"{"a": [{"id": 1}, {"id": 2, "n": 1}, {"id": 2, "n": 2}], "b": [{"id": 2}, {"id": 4}]}"#,
         )
         .unwrap();
-        let expr = runtime
-            .compile("chunks | filter_expr('score > `0.7`', @) | sort_by_expr('score', @)")
-            .unwrap();
+        let expr = runtime.compile("intersection_by('id', a, b)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        // Sorted ascending by score
         assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("score")
-                .unwrap()
-                .as_number()
-                .unwrap(),
-            0.75
+            *result,
+            Variable::from_json(r#"[{"id": 2, "n": 1}]"#).unwrap()
         );
     }
 
-    // =========================================================================
-    // Additional reduce_expr/scan_expr tests
-    // =========================================================================
-
-    #[test]
-    fn test_reduce_expr_product() {
-        let runtime = setup();
-        // Test reduce with min (similar to existing max test but finds minimum)
-        let data = Variable::from_json(r#"[5, 3, 8, 1, 9]"#).unwrap();
-        let expr = runtime
-            .compile("reduce_expr('min([accumulator, current])', @, `100`)")
-            .unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 1.0);
-    }
-
     #[test]
-    fn test_scan_expr_running_balance() {
+    fn test_union_by_prefers_first() {
         let runtime = setup();
-        // Test scan with running max - shows progressive maximum
-        let data = Variable::from_json(r#"[3, 1, 4, 1, 5, 9]"#).unwrap();
-        let expr = runtime
-            .compile("scan_expr('max([accumulator, current])', @, `0`)")
-            .unwrap();
+        let data = Variable::from_json(
+            r#"{"a": [{"id": 1, "v": "a"}], "b": [{"id": 1, "v": "b"}, {"id": 2, "v": "c"}]}"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("union_by('id', a, b)[*].v").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        // Running max: 3, 3, 4, 4, 5, 9
-        assert_eq!(arr[0].as_number().unwrap(), 3.0);
-        assert_eq!(arr[1].as_number().unwrap(), 3.0);
-        assert_eq!(arr[2].as_number().unwrap(), 4.0);
-        assert_eq!(arr[3].as_number().unwrap(), 4.0);
-        assert_eq!(arr[4].as_number().unwrap(), 5.0);
-        assert_eq!(arr[5].as_number().unwrap(), 9.0);
+        assert_eq!(*result, Variable::from_json(r#"["a", "c"]"#).unwrap());
     }
 
-    // =========================================================================
-    // Additional order_by tests
-    // =========================================================================
-
     #[test]
-    fn test_order_by_three_fields() {
+    fn test_keyed_set_operations_are_typed() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"[
-                {"dept": "Engineering", "level": "senior", "name": "Charlie"},
-                {"dept": "Engineering", "level": "junior", "name": "Alice"},
-                {"dept": "Engineering", "level": "senior", "name": "Bob"},
-                {"dept": "Sales", "level": "senior", "name": "David"}
-            ]"#,
+            r#"{"a": [{"id": 1}, {"id": "2"}, {"n": 1}], "b": [{"id": "1"}, {"id": 2}, {"n": 2}]}"#,
         )
         .unwrap();
-        let expr = runtime
-            .compile(r#"order_by(@, `[["dept", "asc"], ["level", "desc"], ["name", "asc"]]`)"#)
-            .unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        // Engineering seniors first (alphabetical), then Engineering juniors, then Sales
-        assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Bob"
-        );
-        assert_eq!(
-            arr[1]
-                .as_object()
-                .unwrap()
-                .get("name")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "Charlie"
-        );
+        let cases = [
+            // Neither 1/"1" nor "2"/2 match, and missing ids match nothing
+            (
+                "difference_by('id', a, b)",
+                r#"[{"id": 1}, {"id": "2"}, {"n": 1}]"#,
+            ),
+            ("intersection_by('id', a, b)", "[]"),
+            (
+                "union_by('id', a, b)",
+                r#"[{"id": 1}, {"id": "2"}, {"n": 1}, {"id": "1"}, {"id": 2}, {"n": 2}]"#,
+            ),
+            (
+                "intersection_by('id', a, `[{\"id\": 1}]`)",
+                r#"[{"id": 1}]"#,
+            ),
+        ];
+        for (expr, expected) in cases {
+            let compiled = runtime.compile(expr).unwrap();
+            assert_eq!(
+                *compiled.search(&data).unwrap(),
+                Variable::from_json(expected).unwrap(),
+                "{}",
+                expr
+            );
+        }
     }
 
     #[test]
-    fn test_order_by_empty() {
+    fn test_union_by_computed_key() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime
-            .compile(r#"order_by(@, `[["name", "asc"]]`)"#)
-            .unwrap();
+        let data =
+            Variable::from_json(r#"{"a": ["Apple", "banana"], "b": ["APPLE", "cherry"]}"#).unwrap();
+        let expr = runtime.compile("union_by('length(@)', a, b)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert!(arr.is_empty());
+        // "Apple" and "APPLE" share length 5; "banana" and "cherry" share length 6
+        assert_eq!(
+            *result,
+            Variable::from_json(r#"["Apple", "banana"]"#).unwrap()
+        );
     }
 
-    // =========================================================================
-    // Additional partition_expr tests
-    // =========================================================================
-
     #[test]
-    fn test_partition_expr_scores() {
+    fn test_evaluate_rules() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[85, 42, 91, 67, 55, 78, 33, 99]"#).unwrap();
-        let expr = runtime.compile("partition_expr('@ >= `60`', @)").unwrap();
+        let data = Variable::from_json(
+            r#"{
+                "rules": [
+                    {"id": "high-cpu", "when": "cpu > `90`", "then": "cpu", "severity": "critical"},
+                    {"id": "low-disk", "when": "disk < `10`", "severity": "warning"},
+                    {"id": "any-host", "when": "host", "message": "host present"}
+                ],
+                "metrics": {"cpu": 95, "disk": 50, "host": "web-1"}
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("evaluate_rules(rules, metrics)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        let passing = arr[0].as_array().unwrap();
-        let failing = arr[1].as_array().unwrap();
-        assert_eq!(passing.len(), 5); // 85, 91, 67, 78, 99
-        assert_eq!(failing.len(), 3); // 42, 55, 33
+        let expected = Variable::from_json(
+            r#"[
+                {"id": "high-cpu", "severity": "critical", "output": 95},
+                {"id": "any-host", "message": "host present"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
     }
 
     #[test]
-    fn test_partition_expr_active() {
+    fn test_evaluate_rules_none_match() {
         let runtime = setup();
-        let data =
-            Variable::from_json(r#"[{"active": true}, {"active": false}, {"active": true}]"#)
-                .unwrap();
-        let expr = runtime.compile("partition_expr('active', @)").unwrap();
+        let data = Variable::from_json(r#"{"rules": [{"id": "a", "when": "missing"}]}"#).unwrap();
+        let expr = runtime.compile("evaluate_rules(rules, @)").unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr[0].as_array().unwrap().len(), 2);
-        assert_eq!(arr[1].as_array().unwrap().len(), 1);
+        assert!(result.as_array().unwrap().is_empty());
     }
 
-    // =========================================================================
-    // Additional map_values/map_keys tests
-    // =========================================================================
-
     #[test]
-    fn test_map_values_discount() {
+    fn test_evaluate_rules_missing_when() {
         let runtime = setup();
-        // Test with string transformation since nested expressions don't have extension math functions
-        let data = Variable::from_json(r#"{"apple": "FRUIT", "banana": "ITEM"}"#).unwrap();
-        let expr = runtime.compile("map_values('length(@)', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("apple").unwrap().as_number().unwrap(), 5.0);
-        assert_eq!(obj.get("banana").unwrap().as_number().unwrap(), 4.0);
+        let data = Variable::from_json(r#"{"rules": [{"id": "a"}]}"#).unwrap();
+        let expr = runtime.compile("evaluate_rules(rules, @)").unwrap();
+        let err = expr.search(&data).unwrap_err();
+        assert!(err.to_string().contains("when"));
     }
 
-    // =========================================================================
-    // Additional group_by_expr tests
-    // =========================================================================
+    #[test]
+    fn test_ensure() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"{"items": [1, 2], "empty": []}"#).unwrap();
+
+        let expr = runtime
+            .compile("ensure('length(@) > `0`', items, 'items must not be empty')")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_array().unwrap().len(), 2);
+
+        let expr = runtime
+            .compile("ensure('length(@) > `0`', empty, 'items must not be empty')")
+            .unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("items must not be empty"), "{}", err);
+
+        let expr = runtime.compile("ensure('@', empty)").unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("ensure failed: @"), "{}", err);
+
+        let expr = runtime.compile("ensure(&length(@) > `0`, items)").unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_array().unwrap().len(), 2);
+        let expr = runtime.compile("ensure(&@, empty)").unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("ensure failed: &expr"), "{}", err);
+    }
 
     #[test]
-    fn test_group_by_expr_type() {
+    fn test_pipe_expr() {
         let runtime = setup();
         let data = Variable::from_json(
-            r#"[{"type": "fruit", "name": "apple"}, {"type": "vegetable", "name": "carrot"}, {"type": "fruit", "name": "banana"}]"#,
+            r#"{
+                "steps": ["filter_expr('age > `20`', @)", "sort_by_expr('name', @)", "[:2]", "[*].name"],
+                "users": [
+                    {"name": "cy", "age": 30},
+                    {"name": "al", "age": 25},
+                    {"name": "bo", "age": 18},
+                    {"name": "bea", "age": 41}
+                ]
+            }"#,
         )
         .unwrap();
-        let expr = runtime.compile("group_by_expr('type', @)").unwrap();
+        let expr = runtime.compile("pipe_expr(steps, users)").unwrap();
         let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert_eq!(obj.get("fruit").unwrap().as_array().unwrap().len(), 2);
-        assert_eq!(obj.get("vegetable").unwrap().as_array().unwrap().len(), 1);
+        let names: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(names, vec!["al", "bea"]);
     }
 
     #[test]
-    fn test_group_by_expr_computed() {
+    fn test_pipe_expr_empty_and_invalid() {
         let runtime = setup();
-        // Group strings by their length using built-in length function
-        let data = Variable::from_json(r#"["a", "bb", "ccc", "dd", "eee", "f"]"#).unwrap();
+        let expr = runtime.compile("pipe_expr(`[]`, `5`)").unwrap();
+        assert_eq!(expr.search(&Variable::Null).unwrap().as_number(), Some(5.0));
+
+        let expr = runtime.compile("pipe_expr(`[1]`, `5`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+
+        let expr = runtime.compile("pipe_expr(`[\"length(\"]`, `5`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_compose() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"{"items": [3, 1, 2]}"#).unwrap();
+
         let expr = runtime
-            .compile("group_by_expr('to_string(length(@))', @)")
+            .compile("apply(compose(`[\"sort(@)\", \"reverse(@)\", \"[0]\"]`), items)")
             .unwrap();
-        let result = expr.search(&data).unwrap();
-        let obj = result.as_object().unwrap();
-        assert!(obj.contains_key("1")); // "a", "f"
-        assert!(obj.contains_key("2")); // "bb", "dd"
-        assert!(obj.contains_key("3")); // "ccc", "eee"
-        assert_eq!(obj.get("1").unwrap().as_array().unwrap().len(), 2);
-        assert_eq!(obj.get("2").unwrap().as_array().unwrap().len(), 2);
-        assert_eq!(obj.get("3").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(expr.search(&data).unwrap().as_number(), Some(3.0));
+
+        // A composed pipeline can be a step of another pipeline
+        let expr = runtime
+            .compile("pipe_expr([compose(`[\"sort(@)\"]`), '[-1]'], items)")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_number(), Some(3.0));
+
+        // Invalid expressions are reported when composing
+        let expr = runtime.compile("compose(`[\"sort(\"]`)").unwrap();
+        assert!(expr.search(&data).is_err());
     }
 
-    // =========================================================================
-    // Additional unique_by_expr tests
-    // =========================================================================
+    #[test]
+    fn test_try_expr() {
+        let runtime = setup();
+        let expr = runtime.compile("try_expr('abs(@)', `-3`)").unwrap();
+        assert_eq!(expr.search(&Variable::Null).unwrap().as_number(), Some(3.0));
+
+        let expr = runtime.compile("try_expr('abs(@)', 'x')").unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+
+        let expr = runtime.compile("try_expr('abs(@)', 'x', `0`)").unwrap();
+        assert_eq!(expr.search(&Variable::Null).unwrap().as_number(), Some(0.0));
+
+        // Compile errors are not swallowed
+        let expr = runtime.compile("try_expr('abs(', `1`, `0`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
 
     #[test]
-    fn test_unique_by_expr_id() {
+    fn test_try_expr_inside_map_expr() {
         let runtime = setup();
-        let data = Variable::from_json(
-            r#"[{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 1, "v": "c"}]"#,
-        )
-        .unwrap();
-        let expr = runtime.compile("unique_by_expr('id', @)").unwrap();
+        let data = Variable::from_json(r#"[-1, "x", 2]"#).unwrap();
+        let expr = runtime
+            .compile("map_expr('try_expr(`\"abs(@)\"`, @, `0`)', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
-        // Keeps first occurrence
+        let values: Vec<f64> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_number().unwrap())
+            .collect();
+        assert_eq!(values, vec![1.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn test_memoize_expr() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"["aa", "b", "aa", "aa", "b"]"#).unwrap();
+        let expr = runtime
+            .compile("map_expr('memoize_expr(`\"length(@)\"`, @)', @)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let values: Vec<f64> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_number().unwrap())
+            .collect();
+        assert_eq!(values, vec![2.0, 1.0, 2.0, 2.0, 1.0]);
+
+        let expr = runtime
+            .compile("map_expr(&memoize_expr(&length(@), @), @)")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap(), result);
+    }
+
+    #[test]
+    fn test_memoize_expr_caches_by_input() {
+        let runtime = setup();
+        clear_memo_cache();
+        let expr = runtime.compile("memoize_expr('length(@)', 'abc')").unwrap();
+        assert_eq!(expr.search(&Variable::Null).unwrap().as_number(), Some(3.0));
+        MEMO_CACHE.with(|cache| assert_eq!(cache.borrow().len(), 1));
+        assert_eq!(expr.search(&Variable::Null).unwrap().as_number(), Some(3.0));
+        MEMO_CACHE.with(|cache| assert_eq!(cache.borrow().len(), 1));
+
+        let other = runtime
+            .compile("memoize_expr('length(@)', 'abcd')")
+            .unwrap();
         assert_eq!(
-            arr[0]
-                .as_object()
-                .unwrap()
-                .get("v")
-                .unwrap()
-                .as_string()
-                .unwrap(),
-            "a"
+            other.search(&Variable::Null).unwrap().as_number(),
+            Some(4.0)
         );
+        // A different top-level search starts with an empty cache
+        MEMO_CACHE.with(|cache| assert_eq!(cache.borrow().len(), 1));
+
+        clear_memo_cache();
+        MEMO_CACHE.with(|cache| assert!(cache.borrow().is_empty()));
+
+        let bad = runtime.compile("memoize_expr('length(', 'abc')").unwrap();
+        assert!(bad.search(&Variable::Null).is_err());
     }
 
-    // =========================================================================
-    // Edge case tests
-    // =========================================================================
+    #[test]
+    fn test_iterate_expr() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("iterate_expr('join(`\"\"`, [@, `\"a\"`])', 'a', `3`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let values: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(values, vec!["a", "aa", "aaa", "aaaa"]);
+
+        // Stops as soon as the value no longer changes
+        let expr = runtime
+            .compile("iterate_expr('[]', `[[1, [2]]]`, `10`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_array().unwrap().len(), 3);
+    }
 
     #[test]
-    fn test_any_expr_empty() {
+    fn test_iterate_expr_follows_chain() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime.compile("any_expr('@ > `0`', @)").unwrap();
+        let data = Variable::from_json(
+            r#"{"name": "leaf", "parent": {"name": "mid", "parent": {"name": "root", "parent": null}}}"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile("iterate_expr('parent', @, `100`)[*].name")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(!result.as_boolean().unwrap());
+        let names: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(names, vec!["leaf", "mid", "root"]);
     }
 
     #[test]
-    fn test_max_by_expr_empty() {
+    fn test_fixpoint_expr() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime.compile("max_by_expr('age', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(result.is_null());
+        let expr = runtime
+            .compile("fixpoint_expr('[]', `[1, [2, [3, [4]]]]`)")
+            .unwrap();
+        assert_eq!(
+            expr.search(&Variable::Null).unwrap(),
+            Rc::new(Variable::from_json("[1, 2, 3, 4]").unwrap())
+        );
+
+        // Never converges
+        let expr = runtime.compile("fixpoint_expr('[@]', `0`, `50`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+
+        let expr = runtime.compile("fixpoint_expr('@', `1`, `-1`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
     }
 
     #[test]
-    fn test_flat_map_expr_duplicate() {
+    fn test_let_expr_bindings_object() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
-        // Duplicate each element
-        let expr = runtime.compile("flat_map_expr('[@, @]', @)").unwrap();
+        let data =
+            Variable::from_json(r#"{"min": 3, "names": ["al", "bea", "cyd", "d"]}"#).unwrap();
+        let expr = runtime
+            .compile("let_expr({min: min}, 'names[?length(@) >= $min]', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 6);
+        assert_eq!(result.as_array().unwrap().len(), 2);
+
+        // Single-binding form; quotes inside bound strings survive
+        let expr = runtime
+            .compile("let_expr('s', `\"it's\"`, '[$s, length($s)]', @)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"["it's", 4]"#).unwrap())
+        );
     }
 
     #[test]
-    fn test_reject_greater_than() {
+    fn test_let_expr_errors() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 4, 5, 6]"#).unwrap();
-        let expr = runtime.compile("reject('@ > `3`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3); // 1, 2, 3
+        let data = Variable::from_json("{}").unwrap();
+        for bad in [
+            "let_expr({a: `1`}, '$b', @)",
+            "let_expr(`{\"a-b\": 1}`, '@', @)",
+            "let_expr(`1`, '@', @)",
+            "let_expr({a: `1`}, 'length(', @)",
+        ] {
+            let expr = runtime.compile(bad).unwrap();
+            assert!(expr.search(&data).is_err(), "{}", bad);
+        }
+        // The scope is released after an error
+        LET_SCOPES.with(|scopes| assert!(scopes.borrow().is_empty()));
     }
 
     #[test]
-    fn test_every_false_case() {
+    fn test_define_and_call() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, -1, 3]"#).unwrap();
-        let expr = runtime.compile("every('@ > `0`', @)").unwrap();
+        clear_defined_functions();
+        let data = Variable::from_json(r#"{"items": [[1, 2], [3], []]}"#).unwrap();
+
+        // define passes its value through so it can be piped
+        let expr = runtime
+            .compile("define('total', 'sum(@)', @) | map_expr('call(`\"total\"`, @)', items)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        assert!(!result.as_boolean().unwrap());
+        assert_eq!(*result, Variable::from_json("[3, 3, 0]").unwrap());
+
+        // Several definitions in one query; &expr works too
+        let expr = runtime
+            .compile(
+                "[define('total', 'sum(@)'), define('pair', &[@, @]), \
+                 call('pair', call('total', `[4, 5]`))][2]",
+            )
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, Variable::from_json("[9, 9]").unwrap());
+
+        clear_defined_functions();
+        let expr = runtime.compile("call('total', `[1]`)").unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("Undefined function 'total'"), "{}", err);
     }
 
     #[test]
-    fn test_count_expr_all_match() {
+    fn test_define_errors() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[5, 10, 15, 20]"#).unwrap();
-        let expr = runtime.compile("count_expr('@ > `0`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 4.0);
+        clear_defined_functions();
+        // Multi-select lists are not evaluated against null
+        let data = Variable::from_json("{}").unwrap();
+
+        let expr = runtime.compile("define('bad', 'length(')").unwrap();
+        assert!(expr.search(&data).is_err());
+
+        // Unbounded recursion stops at the depth limit and resets the counter
+        let expr = runtime
+            .compile("[define('loop', 'call(`\"loop\"`, @)'), call('loop', `1`)]")
+            .unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("Maximum call depth"), "{}", err);
+        CALL_DEPTH.with(|depth| assert_eq!(depth.get(), 0));
+        clear_defined_functions();
     }
 
     #[test]
-    fn test_find_expr_first_match() {
+    fn test_define_scoped_to_search() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 5, 10, 15]"#).unwrap();
-        let expr = runtime.compile("find_expr('@ > `3`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 5.0);
+        let data = Variable::from_json(r#"["ab", "c"]"#).unwrap();
+
+        let define = runtime.compile("define('size', 'length(@)', @)").unwrap();
+        define.search(&data).unwrap();
+        DEFINED_FUNCTIONS.with(|fns| assert!(fns.borrow().contains_key("size")));
+
+        // Two sequential searches do not share definitions
+        let call = runtime.compile("call('size', 'abc')").unwrap();
+        let err = call.search(&data).unwrap_err().to_string();
+        assert!(err.contains("Undefined function 'size'"), "{}", err);
+        DEFINED_FUNCTIONS.with(|fns| assert!(fns.borrow().is_empty()));
+
+        // Nested searches within one query still see them
+        let both = runtime
+            .compile("define('size', 'length(@)', @) | map_expr('call(`\"size\"`, @)', @)")
+            .unwrap();
+        assert_eq!(
+            both.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[2, 1]").unwrap())
+        );
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_find_index_expr_first_match() {
+    fn test_define_visible_on_parallel_workers() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 5, 10, 15]"#).unwrap();
-        let expr = runtime.compile("find_index_expr('@ > `3`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 1.0);
+        let items: Vec<u32> = (0..200).collect();
+        let data = Variable::from_json(&serde_json::to_string(&items).unwrap()).unwrap();
+
+        let query = |map: &str| {
+            format!(
+                "define('d', 'sum([@, @])', @) | {}('call(`\"d\"`, @)', @)",
+                map
+            )
+        };
+        let sequential = runtime.compile(&query("map_expr")).unwrap();
+        let parallel = runtime.compile(&query("parallel_map_expr")).unwrap();
+        let expected = sequential.search(&data).unwrap();
+        assert_eq!(parallel.search(&data).unwrap(), expected);
+
+        // The recursion bound carries over to the workers
+        let expr = runtime
+            .compile(
+                "define('loop', &parallel_map_expr(&call('loop', @), [@]), @) | call('loop', `1`)",
+            )
+            .unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("Maximum call depth"), "{}", err);
     }
 
     #[test]
-    fn test_take_while_basic() {
+    fn test_expand_let() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 5, 1, 2]"#).unwrap();
-        let expr = runtime.compile("take_while('@ < `4`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        assert_eq!(arr[0].as_number().unwrap(), 1.0);
-        assert_eq!(arr[1].as_number().unwrap(), 2.0);
-        assert_eq!(arr[2].as_number().unwrap(), 3.0);
+        let data = Variable::from_json(
+            r#"{"threshold": 2, "items": [{"n": 1}, {"n": 2}, {"n": 3}], "label": "big"}"#,
+        )
+        .unwrap();
+        let eval = |query: &str| {
+            let expanded = expand_let(query).unwrap();
+            runtime.compile(&expanded).unwrap().search(&data).unwrap()
+        };
+
+        assert_eq!(
+            eval("let $t = threshold in items[?n >= $t].n"),
+            Rc::new(Variable::from_json("[2, 3]").unwrap())
+        );
+        // Multiple bindings, nested lets and outer variables in inner scopes
+        assert_eq!(
+            eval(
+                "let $t = threshold, $l = label in let $c = length(items[?n > $t]) in [$l, $c, $t]"
+            ),
+            Rc::new(Variable::from_json(r#"["big", 1, 2]"#).unwrap())
+        );
+        // The body stops at the enclosing bracket
+        assert_eq!(
+            eval("[let $x = label in $x, 'after']"),
+            Rc::new(Variable::from_json(r#"["big", "after"]"#).unwrap())
+        );
+        // Strings mentioning let are left alone
+        assert_eq!(
+            expand_let("'let $x = 1 in $x'").unwrap(),
+            "'let $x = 1 in $x'"
+        );
+        assert_eq!(expand_let("outlet.inlet").unwrap(), "outlet.inlet");
+
+        assert!(expand_let("let $x in $x").is_err());
+        assert!(expand_let("let $x = `1`").is_err());
     }
 
     #[test]
-    fn test_take_while_all_match() {
+    fn test_window_expr() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
-        let expr = runtime.compile("take_while('@ < `10`', @)").unwrap();
+        let data = Variable::from_json("[1, 1, 2, 5]").unwrap();
+
+        let expr = runtime
+            .compile("window_expr('current == prev', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-    }
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json("[false, true, false, false]").unwrap())
+        );
 
-    #[test]
-    fn test_take_while_none_match() {
-        let runtime = setup();
-        let data = Variable::from_json(r#"[5, 6, 7]"#).unwrap();
-        let expr = runtime.compile("take_while('@ < `4`', @)").unwrap();
+        let expr = runtime
+            .compile("window_expr('[index, prev, next]', @)")
+            .unwrap();
         let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 0);
+        assert_eq!(
+            result,
+            Rc::new(
+                Variable::from_json("[[0, null, 1], [1, 1, 2], [2, 1, 5], [3, 2, null]]").unwrap()
+            )
+        );
+
+        let expr = runtime.compile("window_expr('current', `[]`)").unwrap();
+        assert!(expr.search(&data).unwrap().as_array().unwrap().is_empty());
     }
 
     #[test]
-    fn test_drop_while_basic() {
+    fn test_span_expr() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3, 5, 1, 2]"#).unwrap();
-        let expr = runtime.compile("drop_while('@ < `4`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        assert_eq!(arr[0].as_number().unwrap(), 5.0);
-        assert_eq!(arr[1].as_number().unwrap(), 1.0);
-        assert_eq!(arr[2].as_number().unwrap(), 2.0);
+        let data = Variable::from_json("[1, 2, 3, 5, 1]").unwrap();
+        let expr = runtime.compile("span_expr('@ < `4`', @)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[[1, 2, 3], [5, 1]]").unwrap())
+        );
+
+        let expr = runtime.compile("span_expr('@ > `0`', @)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[[1, 2, 3, 5, 1], []]").unwrap())
+        );
+
+        let expr = runtime.compile("span_expr('@ > `9`', @)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[[], [1, 2, 3, 5, 1]]").unwrap())
+        );
     }
 
     #[test]
-    fn test_drop_while_all_match() {
+    fn test_chunk_by_expr_alias() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[1, 2, 3]"#).unwrap();
-        let expr = runtime.compile("drop_while('@ < `10`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 0);
+        let data = Variable::from_json("[1, 2, -1, -2, 3]").unwrap();
+        let expr = runtime.compile("chunk_by_expr('@ > `0`', @)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[[1, 2], [-1, -2], [3]]").unwrap())
+        );
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_drop_while_none_match() {
+    fn test_parallel_map_expr() {
         let runtime = setup();
-        let data = Variable::from_json(r#"[5, 6, 7]"#).unwrap();
-        let expr = runtime.compile("drop_while('@ < `4`', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
+        let items: Vec<_> = (0..1000).map(|i| serde_json::json!({"n": i})).collect();
+        let data = Variable::from_json(&serde_json::to_string(&items).unwrap()).unwrap();
+        let parallel = runtime.compile("parallel_map_expr('[n, n]', @)").unwrap();
+        let sequential = runtime.compile("map_expr('[n, n]', @)").unwrap();
+        let result = parallel.search(&data).unwrap();
+        assert_eq!(result, sequential.search(&data).unwrap());
+        assert_eq!(
+            result.as_array().unwrap()[999],
+            Rc::new(Variable::from_json("[999, 999]").unwrap())
+        );
+
+        let by_ref = runtime.compile("parallel_map_expr(&[n, n], @)").unwrap();
+        assert_eq!(by_ref.search(&data).unwrap(), result);
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_zip_with_add() {
-        let mut runtime = setup();
-        crate::math::register(&mut runtime);
-        let data = Variable::Null;
+    fn test_parallel_map_expr_errors() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"["a", 1, "b"]"#).unwrap();
         let expr = runtime
-            .compile("zip_with('add([0], [1])', `[1, 2, 3]`, `[10, 20, 30]`)")
+            .compile("parallel_map_expr('length(@)', @)")
             .unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        assert_eq!(arr[0].as_number().unwrap(), 11.0);
-        assert_eq!(arr[1].as_number().unwrap(), 22.0);
-        assert_eq!(arr[2].as_number().unwrap(), 33.0);
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("element 1"), "{}", err);
+
+        let expr = runtime.compile("parallel_map_expr('[', @)").unwrap();
+        assert!(expr.search(&data).is_err());
     }
 
+    #[cfg(all(feature = "parallel", feature = "string"))]
     #[test]
-    fn test_zip_with_unequal_lengths() {
-        let mut runtime = setup();
-        crate::math::register(&mut runtime);
-        let data = Variable::Null;
-        let expr = runtime
-            .compile("zip_with('add([0], [1])', `[1, 2, 3, 4, 5]`, `[10, 20]`)")
+    fn test_parallel_map_expr_uses_calling_runtime() {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        crate::Extensions::builder()
+            .deny("upper")
+            .prefix("ext_")
+            .apply(&mut runtime)
             .unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 2);
-        assert_eq!(arr[0].as_number().unwrap(), 11.0);
-        assert_eq!(arr[1].as_number().unwrap(), 22.0);
-    }
+        let data = Variable::from_json(r#"["a", "b"]"#).unwrap();
 
-    #[test]
-    fn test_zip_with_multiply() {
-        let mut runtime = setup();
-        crate::math::register(&mut runtime);
-        let data = Variable::Null;
         let expr = runtime
-            .compile("zip_with('multiply([0], [1])', `[2, 3, 4]`, `[5, 6, 7]`)")
+            .compile("ext_parallel_map_expr('ext_lower(@)', @)")
             .unwrap();
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
-        assert_eq!(arr[0].as_number().unwrap(), 10.0);
-        assert_eq!(arr[1].as_number().unwrap(), 18.0);
-        assert_eq!(arr[2].as_number().unwrap(), 28.0);
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json(r#"["a", "b"]"#).unwrap())
+        );
+
+        // Denied and unprefixed names are not reachable from the workers
+        for inner in ["ext_upper(@)", "lower(@)"] {
+            let query = format!("ext_parallel_map_expr('{}', @)", inner);
+            let err = runtime.compile(&query).unwrap().search(&data).unwrap_err();
+            assert!(err.to_string().contains("element 0"), "{}", err);
+        }
     }
 
-    // =========================================================================
-    // walk tests
-    // =========================================================================
+    #[test]
+    fn test_expref_arguments() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"name": "b", "age": 30, "team": "x"}, {"name": "a", "age": 20, "team": "y"}]"#,
+        )
+        .unwrap();
+
+        let cases = [
+            ("map_expr(&name, @)", r#"["b", "a"]"#),
+            ("filter_expr(&age > `25`, @)[].name", r#"["b"]"#),
+            ("sort_by_expr(&name, @)[].name", r#"["a", "b"]"#),
+            ("keys(group_by_expr(&team, @))", r#"["x", "y"]"#),
+            (
+                "reduce_expr(&sum([accumulator, current.age]), @, `0`)",
+                "50",
+            ),
+        ];
+        for (expr, expected) in cases {
+            let compiled = runtime.compile(expr).unwrap();
+            assert_eq!(
+                compiled.search(&data).unwrap(),
+                Rc::new(Variable::from_json(expected).unwrap()),
+                "{}",
+                expr
+            );
+        }
+
+        // String and expref forms agree
+        let by_string = runtime.compile("map_expr('age', @)").unwrap();
+        let by_ref = runtime.compile("map_expr(&age, @)").unwrap();
+        assert_eq!(
+            by_string.search(&data).unwrap(),
+            by_ref.search(&data).unwrap()
+        );
+    }
 
     #[test]
-    fn test_walk_identity() {
+    fn test_expref_argument_rejects_other_types() {
         let runtime = setup();
-        let data = Variable::from_json(r#"{"a": [1, 2, 3], "b": {"c": 4}}"#).unwrap();
-        let expr = runtime.compile("walk('@', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        // Identity should return the same structure
-        assert!(result.is_object());
-        let obj = result.as_object().unwrap();
-        assert!(obj.contains_key("a"));
-        assert!(obj.contains_key("b"));
+        let data = Variable::from_json("[1, 2]").unwrap();
+        let expr = runtime.compile("map_expr(`1`, @)").unwrap();
+        assert!(expr.search(&data).is_err());
     }
 
     #[test]
-    fn test_walk_type_of_all() {
-        let mut runtime = setup();
-        crate::type_conv::register(&mut runtime);
-        let data = Variable::from_json(r#"{"a": 5, "b": [1, 2]}"#).unwrap();
-        // type() works on everything - shows bottom-up processing
-        let expr = runtime.compile("walk('type(@)', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        // After walking, everything becomes its type string, and the final result
-        // is type of the top-level result
-        assert_eq!(result.as_string().unwrap(), "object");
+    fn test_tap() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"{"items": [1, 2, 3]}"#).unwrap();
+        let expr = runtime.compile("tap(&length(@), items)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[1, 2, 3]").unwrap())
+        );
+
+        let expr = runtime.compile("tap('items', @)").unwrap();
+        assert_eq!(expr.search(&data).unwrap(), Rc::new(data.clone()));
+
+        // Errors in the side expression are not swallowed
+        let expr = runtime.compile("tap(&length(@), `1`)").unwrap();
+        assert!(expr.search(&data).is_err());
     }
 
     #[test]
-    fn test_walk_nested_arrays() {
+    fn test_zip_longest_with() {
         let runtime = setup();
-        // Use only arrays (no scalars inside) so length works at every level
-        let data = Variable::from_json(r#"[[[], []], [[]]]"#).unwrap();
-        // length works on arrays - get lengths at each level
-        let expr = runtime.compile("walk('length(@)', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        // Inner [] -> 0, outer arrays get lengths, top level has 2 elements
-        assert_eq!(result.as_number().unwrap(), 2.0);
+        let data = Variable::from_json(r#"{"a": [1, 2, 3], "b": [10]}"#).unwrap();
+        let expr = runtime
+            .compile("zip_longest_with(&sum(@), a, b, `0`)")
+            .unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[11, 2, 3]").unwrap())
+        );
+
+        let expr = runtime
+            .compile("zip_longest_with('@', b, a, `null`)")
+            .unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[[10, 1], [null, 2], [null, 3]]").unwrap())
+        );
     }
 
     #[test]
-    fn test_walk_scalar() {
-        let mut runtime = setup();
-        crate::math::register(&mut runtime);
-        let data = Variable::Number(serde_json::Number::from(5));
-        // Double the number
-        let expr = runtime.compile("walk('multiply(@, `2`)', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert_eq!(result.as_number().unwrap(), 10.0);
+    fn test_merge_by_expr() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{
+                "a": [{"id": 1, "x": 1, "y": 1}, {"id": 3, "x": 3}],
+                "b": [{"id": 2, "z": 2}, {"id": 1, "y": 9}, {"id": 1, "y": 8}]
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("merge_by_expr(&id, a, b)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(
+                Variable::from_json(
+                    r#"[{"id": 1, "x": 1, "y": 9}, {"id": 3, "x": 3}, {"id": 2, "z": 2}]"#
+                )
+                .unwrap()
+            )
+        );
     }
 
     #[test]
-    fn test_walk_length_all() {
+    fn test_merge_by_expr_keys_are_typed() {
         let runtime = setup();
-        let data = Variable::from_json(r#"{"items": ["a", "bb", "ccc"]}"#).unwrap();
-        // Get length of everything (works for strings, arrays, objects)
-        let expr = runtime.compile("walk('length(@)', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        // Top level object has 1 key
-        assert_eq!(result.as_number().unwrap(), 1.0);
+        let data = Variable::from_json(
+            r#"{
+                "a": [{"id": 1, "x": 1}, {"x": 2}],
+                "b": [{"id": "1", "y": 1}, {"y": 2}]
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("merge_by_expr(&id, a, b)").unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(
+                Variable::from_json(
+                    r#"[{"id": 1, "x": 1}, {"x": 2}, {"id": "1", "y": 1}, {"y": 2}]"#
+                )
+                .unwrap()
+            )
+        );
     }
 
     #[test]
-    fn test_walk_preserves_structure() {
+    fn test_pivot_expr() {
         let runtime = setup();
-        let data = Variable::from_json(r#"{"a": [1, {"b": 2}], "c": "hello"}"#).unwrap();
-        // Identity transform - should preserve structure
-        let expr = runtime.compile("walk('@', @)").unwrap();
-        let result = expr.search(&data).unwrap();
+        let data = Variable::from_json(
+            r#"[
+                {"region": "east", "quarter": "q1", "sales": 10},
+                {"region": "east", "quarter": "q1", "sales": 5},
+                {"region": "east", "quarter": "q2", "sales": 3},
+                {"region": "west", "quarter": "q2", "sales": 7}
+            ]"#,
+        )
+        .unwrap();
 
-        let obj = result.as_object().unwrap();
-        assert!(obj.contains_key("a"));
-        assert!(obj.contains_key("c"));
-        let arr = obj.get("a").unwrap().as_array().unwrap();
-        assert_eq!(arr.len(), 2);
+        let expr = runtime
+            .compile("pivot_expr(&region, &quarter, &sum([*].sales), @)")
+            .unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(
+                Variable::from_json(r#"{"east": {"q1": 15, "q2": 3}, "west": {"q2": 7}}"#).unwrap()
+            )
+        );
+
+        let expr = runtime
+            .compile("crosstab('quarter', 'region', 'length(@)', @)")
+            .unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(
+                Variable::from_json(r#"{"q1": {"east": 2}, "q2": {"east": 1, "west": 1}}"#)
+                    .unwrap()
+            )
+        );
     }
 
     #[test]
-    fn test_walk_empty_structures() {
+    fn test_compile_cache_lru() {
         let runtime = setup();
+        clear_compile_cache();
+        let cached = |key: &str| COMPILE_CACHE.with(|c| c.borrow().contains_key(key));
+        let len = || COMPILE_CACHE.with(|c| c.borrow().len());
+
+        let data = Variable::from_json(r#"{"a": {"b": 1}}"#).unwrap();
+        for _ in 0..3 {
+            let expr = compile_cached(&runtime, "a.b").unwrap();
+            assert_eq!(expr.search(&data).unwrap().as_number(), Some(1.0));
+        }
+        assert_eq!(len(), 1);
 
-        // Empty array
-        let data = Variable::from_json(r#"[]"#).unwrap();
-        let expr = runtime.compile("walk('@', @)").unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(result.as_array().unwrap().is_empty());
-
-        // Empty object
-        let data = Variable::from_json(r#"{}"#).unwrap();
-        let result = expr.search(&data).unwrap();
-        assert!(result.as_object().unwrap().is_empty());
+        for i in 1..COMPILE_CACHE_CAPACITY {
+            compile_cached(&runtime, &format!("f{}", i)).unwrap();
+        }
+        assert_eq!(len(), COMPILE_CACHE_CAPACITY);
+
+        // Touch the first entry so the next insert evicts f1 instead
+        compile_cached(&runtime, "a.b").unwrap();
+        compile_cached(&runtime, "overflow").unwrap();
+        assert_eq!(len(), COMPILE_CACHE_CAPACITY);
+        assert!(cached("a.b"));
+        assert!(cached("overflow"));
+        assert!(!cached("f1"));
+
+        // Parse errors are reported and not cached
+        assert!(compile_cached(&runtime, "[").is_err());
+        assert!(!cached("["));
+        clear_compile_cache();
+        assert_eq!(len(), 0);
     }
 }
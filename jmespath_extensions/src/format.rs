@@ -1,7 +1,9 @@
 //! CSV and TSV formatting functions.
 //!
 //! This module provides functions for formatting data as CSV/TSV strings,
-//! similar to jq's `@csv` and `@tsv` formatters.
+//! similar to jq's `@csv` and `@tsv` formatters, plus small text
+//! visualizations (`sparkline`, `bar`) and ANSI styling (`style`,
+//! `colorize_if`, `strip_ansi`) for terminal reports.
 //!
 //! Uses the [`csv`](https://docs.rs/csv) crate for RFC 4180 compliant output.
 //!
@@ -16,11 +18,13 @@
 //! format::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use csv::WriterBuilder;
 
-use crate::common::{ArgumentType, Context, Function, JmespathError, Rcvar, Runtime, Variable};
+use crate::common::{
+    ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
+};
 use crate::define_function;
 
 /// Register all format functions with the runtime.
@@ -29,6 +33,11 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("to_tsv", Box::new(ToTsvFn::new()));
     runtime.register_function("to_csv_rows", Box::new(ToCsvRowsFn::new()));
     runtime.register_function("to_csv_table", Box::new(ToCsvTableFn::new()));
+    runtime.register_function("sparkline", Box::new(SparklineFn::new()));
+    runtime.register_function("bar", Box::new(BarFn::new()));
+    runtime.register_function("style", Box::new(StyleFn::new()));
+    runtime.register_function("colorize_if", Box::new(ColorizeIfFn::new()));
+    runtime.register_function("strip_ansi", Box::new(StripAnsiFn::new()));
 }
 
 /// Convert a JMESPath Variable to a string suitable for CSV field.
@@ -272,6 +281,326 @@ impl Function for ToCsvTableFn {
     }
 }
 
+// =============================================================================
+// sparkline(array) -> string
+// =============================================================================
+
+/// Unicode block characters from lowest to highest.
+const SPARK_BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+define_function!(SparklineFn, vec![ArgumentType::Array], None);
+
+impl Function for SparklineFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+
+        // Nulls render as gaps; anything else must be a number
+        let mut values: Vec<Option<f64>> = Vec::with_capacity(arr.len());
+        for v in arr.iter() {
+            match v.as_ref() {
+                Variable::Null => values.push(None),
+                Variable::Number(n) => values.push(n.as_f64()),
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse("sparkline() expects an array of numbers".to_owned()),
+                    ));
+                }
+            }
+        }
+
+        let present = values.iter().flatten();
+        let min = present.clone().copied().fold(f64::INFINITY, f64::min);
+        let max = present.copied().fold(f64::NEG_INFINITY, f64::max);
+        // Halved so the range stays finite even for values near f64::MAX
+        let half_range = max / 2.0 - min / 2.0;
+
+        let last = SPARK_BLOCKS.len() - 1;
+        let line: String = values
+            .iter()
+            .map(|v| match v {
+                None => ' ',
+                Some(_) if half_range <= 0.0 => SPARK_BLOCKS[0],
+                Some(x) => {
+                    let position = (x / 2.0 - min / 2.0) / half_range;
+                    SPARK_BLOCKS[((position * last as f64).round() as usize).min(last)]
+                }
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::String(line)))
+    }
+}
+
+// =============================================================================
+// bar(value, max, width) -> string
+// =============================================================================
+
+/// Partial block characters in eighths (index 0 is empty).
+const BAR_EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Widest bar `bar()` will draw, in character cells.
+const MAX_BAR_WIDTH: usize = 10_000;
+
+define_function!(
+    BarFn,
+    vec![
+        ArgumentType::Number,
+        ArgumentType::Number,
+        ArgumentType::Number
+    ],
+    None
+);
+
+impl Function for BarFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let value = args[0].as_number().unwrap();
+        let max = args[1].as_number().unwrap();
+        let width = args[2].as_number().unwrap();
+
+        if max <= 0.0 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("bar() max must be greater than zero".to_owned()),
+            ));
+        }
+        if !(0.0..=MAX_BAR_WIDTH as f64).contains(&width) {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "bar() width must be between 0 and {}",
+                    MAX_BAR_WIDTH
+                )),
+            ));
+        }
+        let width = width as usize;
+        // Each cell is at most one three-byte block character
+        crate::limits::check_string_len(ctx, width.saturating_mul(3))?;
+
+        // Work in eighths of a cell so the bar end can use partial blocks
+        let cells = width.checked_mul(8).ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("bar() width is too large".to_owned()),
+            )
+        })?;
+        let ratio = (value / max).clamp(0.0, 1.0);
+        let eighths = (ratio * cells as f64).round() as usize;
+        let full = eighths / 8;
+        let partial = eighths % 8;
+
+        let mut bar = "█".repeat(full);
+        if partial > 0 {
+            bar.push(BAR_EIGHTHS[partial]);
+        }
+        // Pad to a fixed width so bars line up in columns
+        let used = full + usize::from(partial > 0);
+        bar.push_str(&" ".repeat(width - used));
+
+        Ok(Rc::new(Variable::String(bar)))
+    }
+}
+
+// =============================================================================
+// ANSI styling helpers
+// =============================================================================
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Standard color names in SGR order (30-37 foreground, 40-47 background).
+const ANSI_COLORS: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Translate a single style token into its SGR parameter(s).
+///
+/// Supports attributes (`bold`, `dim`, `italic`, `underline`, `blink`,
+/// `reverse`, `hidden`, `strikethrough`), the eight color names with optional
+/// `bright_` prefix, `#rrggbb` truecolor, and `on_` prefixed backgrounds.
+fn sgr_code(token: &str) -> Option<String> {
+    let attr = match token {
+        "bold" => Some(1),
+        "dim" => Some(2),
+        "italic" => Some(3),
+        "underline" => Some(4),
+        "blink" => Some(5),
+        "reverse" => Some(7),
+        "hidden" => Some(8),
+        "strikethrough" => Some(9),
+        _ => None,
+    };
+    if let Some(code) = attr {
+        return Some(code.to_string());
+    }
+
+    let (color, background) = match token.strip_prefix("on_") {
+        Some(rest) => (rest, true),
+        None => (token, false),
+    };
+
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let (r, g, b) = (channel(0)?, channel(2)?, channel(4)?);
+        let base = if background { 48 } else { 38 };
+        return Some(format!("{};2;{};{};{}", base, r, g, b));
+    }
+
+    let (name, bright) = match color.strip_prefix("bright_") {
+        Some(rest) => (rest, true),
+        None => (color, false),
+    };
+    let index = ANSI_COLORS.iter().position(|c| *c == name)?;
+    let base = match (background, bright) {
+        (false, false) => 30,
+        (false, true) => 90,
+        (true, false) => 40,
+        (true, true) => 100,
+    };
+    Some((base + index).to_string())
+}
+
+/// Wrap `text` in the ANSI escape sequence described by a space-separated style spec.
+fn apply_style(text: &str, spec: &str, ctx: &Context<'_>) -> Result<String, JmespathError> {
+    let mut codes = Vec::new();
+    for token in spec.split_whitespace() {
+        let code = sgr_code(&token.to_lowercase()).ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!("Unknown style '{}'", token)),
+            )
+        })?;
+        codes.push(code);
+    }
+
+    if codes.is_empty() {
+        return Ok(text.to_string());
+    }
+    Ok(format!("\x1b[{}m{}{}", codes.join(";"), text, ANSI_RESET))
+}
+
+// =============================================================================
+// style(string, spec) -> string
+// =============================================================================
+
+define_function!(
+    StyleFn,
+    vec![ArgumentType::String, ArgumentType::String],
+    None
+);
+
+impl Function for StyleFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let text = args[0].as_string().unwrap();
+        let spec = args[1].as_string().unwrap();
+
+        Ok(Rc::new(Variable::String(apply_style(text, spec, ctx)?)))
+    }
+}
+
+// =============================================================================
+// colorize_if(condition, string, spec) -> string
+// =============================================================================
+
+define_function!(
+    ColorizeIfFn,
+    vec![
+        ArgumentType::Any,
+        ArgumentType::String,
+        ArgumentType::String
+    ],
+    None
+);
+
+impl Function for ColorizeIfFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let text = args[1].as_string().unwrap();
+        let spec = args[2].as_string().unwrap();
+
+        // Validate the style even when it is not applied so typos surface early
+        let styled = apply_style(text, spec, ctx)?;
+        if args[0].is_truthy() {
+            Ok(Rc::new(Variable::String(styled)))
+        } else {
+            Ok(args[1].clone())
+        }
+    }
+}
+
+// =============================================================================
+// strip_ansi(string) -> string
+// =============================================================================
+
+define_function!(StripAnsiFn, vec![ArgumentType::String], None);
+
+impl Function for StripAnsiFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let text = args[0].as_string().unwrap();
+        Ok(Rc::new(Variable::String(strip_ansi(text))))
+    }
+}
+
+/// Remove ANSI escape sequences (CSI and OSC) from a string.
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek() {
+            // CSI: ESC [ params... final byte in @..~
+            Some('[') => {
+                chars.next();
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: ESC ] ... terminated by BEL or ESC \
+            Some(']') => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two-character escape such as ESC c
+            Some(_) => {
+                chars.next();
+            }
+            None => {}
+        }
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -482,4 +811,132 @@ mod tests {
         assert!(result.as_string().unwrap().contains("\"O'Brien, Jr.\""));
         assert!(result.as_string().unwrap().contains("\"said \"\"hi\"\"\""));
     }
+
+    // =========================================================================
+    // sparkline / bar tests
+    // =========================================================================
+
+    #[test]
+    fn test_sparkline() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("sparkline(@)").unwrap();
+        let data = Variable::from_json("[1, 2, 3, 4, 5, 6, 7, 8]").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_string().unwrap(), "▁▂▃▄▅▆▇█");
+    }
+
+    #[test]
+    fn test_sparkline_gaps_and_flat() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("sparkline(@)").unwrap();
+        let data = Variable::from_json("[0, null, 10]").unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_string().unwrap(), "▁ █");
+
+        let data = Variable::from_json("[3, 3, 3]").unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_string().unwrap(), "▁▁▁");
+
+        let data = Variable::from_json(r#"[1, "x"]"#).unwrap();
+        assert!(expr.search(&data).is_err());
+
+        // The span between the extremes overflows f64
+        let data = Variable::from_json("[1e308, 0, -1e308]").unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_string().unwrap(), "█▅▁");
+    }
+
+    #[test]
+    fn test_bar() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("bar(`50`, `100`, `10`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "█████     ");
+
+        let expr = runtime.compile("bar(`3`, `16`, `2`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "▍ ");
+
+        let expr = runtime.compile("bar(`200`, `100`, `4`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "████");
+    }
+
+    #[test]
+    fn test_bar_invalid_max() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("bar(`1`, `0`, `10`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_bar_width_out_of_range() {
+        let runtime = setup_runtime();
+        for query in ["bar(`1`, `2`, `-1`)", "bar(`1`, `2`, `1e300`)"] {
+            let expr = runtime.compile(query).unwrap();
+            assert!(expr.search(&Variable::Null).is_err(), "{}", query);
+        }
+    }
+
+    // =========================================================================
+    // ANSI styling tests
+    // =========================================================================
+
+    #[test]
+    fn test_style() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("style('hi', 'bold red')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "\x1b[1;31mhi\x1b[0m");
+
+        let expr = runtime
+            .compile("style('hi', 'bright_green on_#102030')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(
+            result.as_string().unwrap(),
+            "\x1b[92;48;2;16;32;48mhi\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_style_unknown_token() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("style('hi', 'bold purple')").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_colorize_if() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("[*].colorize_if(status == 'down', name, 'red')")
+            .unwrap();
+        let data = Variable::from_json(
+            r#"[{"name": "a", "status": "up"}, {"name": "b", "status": "down"}]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr[0].as_string().unwrap(), "a");
+        assert_eq!(arr[1].as_string().unwrap(), "\x1b[31mb\x1b[0m");
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("strip_ansi(@)").unwrap();
+        let data = Variable::String(
+            "\x1b[1;31mERROR\x1b[0m disk \x1b]0;title\x07full \x1b[2K".to_string(),
+        );
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_string().unwrap(), "ERROR disk full ");
+    }
+
+    #[test]
+    fn test_strip_ansi_roundtrip() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("strip_ansi(style('report', 'underline cyan'))")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "report");
+    }
 }
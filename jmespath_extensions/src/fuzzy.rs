@@ -16,7 +16,7 @@
 //! fuzzy::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use crate::common::Function;
 use crate::{ArgumentType, Context, JmespathError, Rcvar, Runtime, Variable, define_function};
@@ -16,11 +16,11 @@
 //! geo::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use geoutils::Location;
 
-use crate::common::Function;
+use crate::common::{ErrorReason, Function};
 use crate::{ArgumentType, Context, JmespathError, Rcvar, Runtime, Signature, Variable};
 
 /// Register all geo functions with the runtime.
@@ -29,6 +29,11 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("geo_distance_km", Box::new(GeoDistanceKmFn::new()));
     runtime.register_function("geo_distance_miles", Box::new(GeoDistanceMilesFn::new()));
     runtime.register_function("geo_bearing", Box::new(GeoBearingFn::new()));
+    runtime.register_function("point_in_polygon", Box::new(PointInPolygonFn::new()));
+    runtime.register_function("in_bbox", Box::new(InBboxFn::new()));
+    runtime.register_function("bbox", Box::new(BboxFn::new()));
+    runtime.register_function("nearest", Box::new(NearestFn::new()));
+    runtime.register_function("distance_matrix", Box::new(DistanceMatrixFn::new()));
 }
 
 // =============================================================================
@@ -238,6 +243,346 @@ impl Function for GeoBearingFn {
     }
 }
 
+// =============================================================================
+// Point helpers
+// =============================================================================
+
+/// Read a point given as `[lat, lon]` or an object with `lat` and `lon`/`lng` fields.
+fn parse_point(value: &Variable) -> Option<(f64, f64)> {
+    match value {
+        Variable::Array(arr) if arr.len() >= 2 => Some((arr[0].as_number()?, arr[1].as_number()?)),
+        Variable::Object(map) => {
+            let lat = map.get("lat")?.as_number()?;
+            let lon = map.get("lon").or_else(|| map.get("lng"))?.as_number()?;
+            Some((lat, lon))
+        }
+        _ => None,
+    }
+}
+
+fn point_arg(value: &Variable, ctx: &Context<'_>) -> Result<(f64, f64), JmespathError> {
+    parse_point(value).ok_or_else(|| {
+        JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse("Expected point as [lat, lon] or {lat, lon}".to_owned()),
+        )
+    })
+}
+
+fn number_var(n: f64) -> Rcvar {
+    Rc::new(Variable::Number(
+        serde_json::Number::from_f64(n).unwrap_or_else(|| serde_json::Number::from(0)),
+    ))
+}
+
+// =============================================================================
+// point_in_polygon(point, polygon) -> boolean
+// Ray casting over lat/lon treated as planar coordinates; the polygon is an
+// array of points and is closed implicitly.
+// =============================================================================
+
+pub struct PointInPolygonFn {
+    signature: Signature,
+}
+
+impl Default for PointInPolygonFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PointInPolygonFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Any, ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for PointInPolygonFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (lat, lon) = point_arg(&args[0], ctx)?;
+        let vertices = points_arg(&args[1], ctx)?;
+
+        if vertices.len() < 3 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Polygon must have at least 3 points".to_owned()),
+            ));
+        }
+
+        let mut inside = false;
+        let mut j = vertices.len() - 1;
+        for i in 0..vertices.len() {
+            let (lat_i, lon_i) = vertices[i];
+            let (lat_j, lon_j) = vertices[j];
+            if (lat_i > lat) != (lat_j > lat)
+                && lon < (lon_j - lon_i) * (lat - lat_i) / (lat_j - lat_i) + lon_i
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+
+        Ok(Rc::new(Variable::Bool(inside)))
+    }
+}
+
+// =============================================================================
+// in_bbox(point, bbox) -> boolean
+// bbox is {min_lat, min_lon, max_lat, max_lon} or [min_lat, min_lon, max_lat, max_lon].
+// A box with min_lon > max_lon wraps across the antimeridian.
+// =============================================================================
+
+pub struct InBboxFn {
+    signature: Signature,
+}
+
+impl Default for InBboxFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InBboxFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Any, ArgumentType::Any], None),
+        }
+    }
+}
+
+fn parse_bbox(value: &Variable) -> Option<[f64; 4]> {
+    match value {
+        Variable::Array(arr) if arr.len() == 4 => Some([
+            arr[0].as_number()?,
+            arr[1].as_number()?,
+            arr[2].as_number()?,
+            arr[3].as_number()?,
+        ]),
+        Variable::Object(map) => Some([
+            map.get("min_lat")?.as_number()?,
+            map.get("min_lon")?.as_number()?,
+            map.get("max_lat")?.as_number()?,
+            map.get("max_lon")?.as_number()?,
+        ]),
+        _ => None,
+    }
+}
+
+impl Function for InBboxFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (lat, lon) = point_arg(&args[0], ctx)?;
+        let [min_lat, min_lon, max_lat, max_lon] = parse_bbox(&args[1]).ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(
+                    "Expected bbox as {min_lat, min_lon, max_lat, max_lon} or a 4-element array"
+                        .to_owned(),
+                ),
+            )
+        })?;
+
+        let lat_ok = (min_lat..=max_lat).contains(&lat);
+        let lon_ok = if min_lon <= max_lon {
+            (min_lon..=max_lon).contains(&lon)
+        } else {
+            lon >= min_lon || lon <= max_lon
+        };
+
+        Ok(Rc::new(Variable::Bool(lat_ok && lon_ok)))
+    }
+}
+
+// =============================================================================
+// bbox(points) -> object | null
+// Bounding box {min_lat, min_lon, max_lat, max_lon}; null for no points.
+// =============================================================================
+
+pub struct BboxFn {
+    signature: Signature,
+}
+
+impl Default for BboxFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BboxFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for BboxFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let points = points_arg(&args[0], ctx)?;
+
+        if points.is_empty() {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        let mut bounds = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for (lat, lon) in points {
+            bounds[0] = bounds[0].min(lat);
+            bounds[1] = bounds[1].min(lon);
+            bounds[2] = bounds[2].max(lat);
+            bounds[3] = bounds[3].max(lon);
+        }
+
+        let map = ["min_lat", "min_lon", "max_lat", "max_lon"]
+            .into_iter()
+            .zip(bounds)
+            .map(|(key, value)| (key.to_string(), number_var(value)))
+            .collect();
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
+fn haversine_meters((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    Location::new(lat1, lon1)
+        .haversine_distance_to(&Location::new(lat2, lon2))
+        .meters()
+}
+
+fn points_arg(value: &Variable, ctx: &Context<'_>) -> Result<Vec<(f64, f64)>, JmespathError> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| point_arg(v, ctx))
+        .collect()
+}
+
+// =============================================================================
+// nearest(point, candidates, n?) -> array
+// The n (default 1) closest candidates, nearest first, each with a `distance`
+// field in meters. Array candidates are returned as {lat, lon, distance}.
+// =============================================================================
+
+pub struct NearestFn {
+    signature: Signature,
+}
+
+impl Default for NearestFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NearestFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::Any, ArgumentType::Array],
+                Some(ArgumentType::Number),
+            ),
+        }
+    }
+}
+
+impl Function for NearestFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let origin = point_arg(&args[0], ctx)?;
+        let candidates = args[1].as_array().unwrap();
+        let n = match args.get(2).and_then(|v| v.as_number()) {
+            Some(n) if n >= 0.0 => n as usize,
+            Some(_) => {
+                return Err(JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("nearest() count must be non-negative".to_owned()),
+                ));
+            }
+            None => 1,
+        };
+
+        let mut ranked = candidates
+            .iter()
+            .map(|c| point_arg(c, ctx).map(|p| (haversine_meters(origin, p), p, c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Stable sort keeps input order for equidistant candidates
+        ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let result = ranked
+            .into_iter()
+            .take(n)
+            .map(|(distance, (lat, lon), candidate)| {
+                let mut map = match candidate.as_ref() {
+                    Variable::Object(map) => map.clone(),
+                    _ => [("lat", lat), ("lon", lon)]
+                        .into_iter()
+                        .map(|(k, v)| (k.to_string(), number_var(v)))
+                        .collect(),
+                };
+                map.insert("distance".to_string(), number_var(distance));
+                Rc::new(Variable::Object(map))
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// distance_matrix(points_a, points_b) -> array
+// Row i holds the distances in meters from points_a[i] to every point in points_b.
+// =============================================================================
+
+pub struct DistanceMatrixFn {
+    signature: Signature,
+}
+
+impl Default for DistanceMatrixFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DistanceMatrixFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for DistanceMatrixFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let rows = points_arg(&args[0], ctx)?;
+        let cols = points_arg(&args[1], ctx)?;
+
+        let matrix = rows
+            .iter()
+            .map(|&a| {
+                let row = cols
+                    .iter()
+                    .map(|&b| number_var(haversine_meters(a, b)))
+                    .collect();
+                Rc::new(Variable::Array(row))
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(matrix)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,4 +667,132 @@ mod tests {
         let meters = result.as_number().unwrap();
         assert!(meters < 1.0); // Should be essentially 0
     }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{"zone": [[0, 0], [0, 10], [10, 10], [10, 0]], "concave": [[0, 0], [0, 10], [5, 5], [10, 10], [10, 0]]}"#,
+        )
+        .unwrap();
+        for (expr, expected) in [
+            ("point_in_polygon(`[5, 5]`, zone)", true),
+            ("point_in_polygon({lat: `2`, lng: `3`}, zone)", true),
+            ("point_in_polygon(`[15, 5]`, zone)", false),
+            ("point_in_polygon(`[5, 8]`, concave)", false),
+            ("point_in_polygon(`[2, 5]`, concave)", true),
+        ] {
+            let result = runtime.compile(expr).unwrap().search(&data).unwrap();
+            assert_eq!(result.as_boolean().unwrap(), expected, "{}", expr);
+        }
+        let expr = runtime
+            .compile("point_in_polygon(`[1, 1]`, `[[0, 0], [1, 1]]`)")
+            .unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_in_bbox() {
+        let runtime = setup();
+        let data = Variable::from_json("{}").unwrap();
+        for (expr, expected) in [
+            ("in_bbox(`[40.7, -74.0]`, `[40, -75, 41, -73]`)", true),
+            ("in_bbox(`[42, -74.0]`, `[40, -75, 41, -73]`)", false),
+            (
+                "in_bbox({lat: `40.5`, lon: `-74`}, {min_lat: `40`, min_lon: `-75`, max_lat: `41`, max_lon: `-73`})",
+                true,
+            ),
+            ("in_bbox(`[0, 179.5]`, `[-10, 170, 10, -170]`)", true),
+            ("in_bbox(`[0, 0]`, `[-10, 170, 10, -170]`)", false),
+        ] {
+            let result = runtime.compile(expr).unwrap().search(&data).unwrap();
+            assert_eq!(result.as_boolean().unwrap(), expected, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_bbox() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"[{"lat": 40.7, "lon": -74.0}, {"lat": 34.05, "lon": -118.24}, {"lat": 41.88, "lon": -87.63}]"#,
+        )
+        .unwrap();
+        let result = runtime.compile("bbox(@)").unwrap().search(&data).unwrap();
+        let map = result.as_object().unwrap();
+        assert_eq!(map.get("min_lat").unwrap().as_number().unwrap(), 34.05);
+        assert_eq!(map.get("min_lon").unwrap().as_number().unwrap(), -118.24);
+        assert_eq!(map.get("max_lat").unwrap().as_number().unwrap(), 41.88);
+        assert_eq!(map.get("max_lon").unwrap().as_number().unwrap(), -74.0);
+
+        let result = runtime
+            .compile("in_bbox(@[0], bbox(@))")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        assert_eq!(result.as_boolean(), Some(true));
+        let result = runtime
+            .compile("bbox(`[]`)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn test_nearest() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{"drivers": [
+                {"id": "la", "lat": 34.0522, "lon": -118.2437},
+                {"id": "chi", "lat": 41.8781, "lon": -87.6298},
+                {"id": "bos", "lat": 42.3601, "lon": -71.0589}
+            ]}"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile("nearest(`[40.7128, -74.0060]`, drivers, `2`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        let first = arr[0].as_object().unwrap();
+        assert_eq!(first.get("id").unwrap().as_string().unwrap(), "bos");
+        let km = first.get("distance").unwrap().as_number().unwrap() / 1000.0;
+        assert!(km > 290.0 && km < 320.0);
+        assert_eq!(
+            arr[1]
+                .as_object()
+                .unwrap()
+                .get("id")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "chi"
+        );
+
+        let expr = runtime
+            .compile("nearest(`[0, 0]`, `[[0, 2], [0, 1]]`)")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let only = result.as_array().unwrap()[0].as_object().unwrap().clone();
+        assert_eq!(only.get("lon").unwrap().as_number().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_distance_matrix() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("distance_matrix(`[[0, 0], [0, 1]]`, `[[0, 0], [0, 1], [1, 0]]`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let rows = result.as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        let row0 = rows[0].as_array().unwrap();
+        assert_eq!(row0.len(), 3);
+        assert_eq!(row0[0].as_number().unwrap(), 0.0);
+        let d01 = row0[1].as_number().unwrap();
+        let d10 = rows[1].as_array().unwrap()[0].as_number().unwrap();
+        assert!((d01 - d10).abs() < 1e-6);
+        assert!(d01 > 110_000.0 && d01 < 112_000.0);
+    }
 }
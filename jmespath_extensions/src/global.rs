@@ -0,0 +1,128 @@
+//! Shared, lazily-initialized runtime with every extension function registered.
+//!
+//! Building a [`Runtime`] and registering 300+ functions is cheap but not free,
+//! and it is easy to accidentally do it once per request. This module keeps a
+//! single process-wide runtime that is built on first use and can be shared
+//! across threads. The feature enables `sync`, so expressions compiled against
+//! it and the values they return are `Send + Sync` as well.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jmespath_extensions::{global_runtime, search};
+//!
+//! // One-shot convenience: parse JSON and evaluate against the shared runtime
+//! let result = search("length(@)", "[1, 2, 3]").unwrap();
+//! assert_eq!(result.as_number().unwrap(), 3.0);
+//!
+//! // Or grab the runtime itself to compile expressions once and reuse them
+//! let expr = global_runtime().compile("upper(@)").unwrap();
+//! # let _ = expr;
+//! ```
+
+use std::sync::LazyLock;
+
+use crate::common::{ErrorReason, JmespathError, Rcvar, Runtime, Variable};
+
+static GLOBAL_RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    let mut runtime = Runtime::new();
+    runtime.register_builtin_functions();
+    crate::register_all(&mut runtime);
+    runtime
+});
+
+/// Get the shared runtime with builtin and all enabled extension functions registered.
+///
+/// The runtime is built exactly once, on the first call, even when called
+/// concurrently from multiple threads. Subsequent calls return the same instance.
+pub fn global_runtime() -> &'static Runtime {
+    &GLOBAL_RUNTIME
+}
+
+/// Evaluate an expression against a JSON document using the shared runtime.
+///
+/// Invalid JSON input is reported as a `JmespathError` so callers only need
+/// to handle a single error type.
+pub fn search(expression: &str, json: &str) -> Result<Rcvar, JmespathError> {
+    let data = Variable::from_json(json).map_err(|e| {
+        JmespathError::new(
+            expression,
+            0,
+            ErrorReason::Parse(format!("Invalid JSON input: {}", e)),
+        )
+    })?;
+    search_variable(expression, &data)
+}
+
+/// Evaluate an expression against an already-parsed [`Variable`] using the shared runtime.
+pub fn search_variable(expression: &str, data: &Variable) -> Result<Rcvar, JmespathError> {
+    global_runtime().compile(expression)?.search(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_runtime_is_shared() {
+        let a = global_runtime() as *const Runtime;
+        let b = global_runtime() as *const Runtime;
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_global_runtime_across_threads() {
+        let handles: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| global_runtime() as *const Runtime as usize))
+            .collect();
+        let addrs: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(addrs.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn test_evaluate_on_multiple_threads() {
+        let expr = global_runtime().compile("length(@)").unwrap();
+        let results: Vec<Rcvar> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (1..=4)
+                .map(|n| {
+                    let expr = &expr;
+                    scope.spawn(move || {
+                        let json = serde_json::to_string(&vec![0; n]).unwrap();
+                        let shared = expr.search(Variable::from_json(&json).unwrap()).unwrap();
+                        assert_eq!(search("length(@)", &json).unwrap(), shared);
+                        shared
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let lengths: Vec<f64> = results.iter().map(|r| r.as_number().unwrap()).collect();
+        assert_eq!(lengths, vec![1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_search_json() {
+        let result = search("length(@)", "[1, 2, 3]").unwrap();
+        assert_eq!(result.as_number().unwrap(), 3.0);
+    }
+
+    #[cfg(feature = "string")]
+    #[test]
+    fn test_search_extension_function() {
+        let result = search("upper(name)", r#"{"name": "alice"}"#).unwrap();
+        assert_eq!(result.as_string().unwrap(), "ALICE");
+    }
+
+    #[test]
+    fn test_search_invalid_json() {
+        let err = search("@", "{not json").unwrap_err();
+        assert!(err.to_string().contains("Invalid JSON input"));
+    }
+
+    #[test]
+    fn test_search_variable() {
+        let data = Variable::from_json(r#"{"a": {"b": 1}}"#).unwrap();
+        let result = search_variable("a.b", &data).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1.0);
+    }
+}
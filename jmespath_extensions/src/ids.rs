@@ -1,4 +1,4 @@
-//! ID generation functions (nanoid, ulid).
+//! ID generation functions (nanoid, ulid, snowflake, ksuid, cuid2).
 //!
 //! This module provides ids functions for JMESPath queries.
 //!
@@ -16,16 +16,32 @@
 //! ids::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::UNIX_EPOCH;
 
-use crate::common::Function;
+use crate::common::RngSource;
+use crate::common::{ErrorReason, Function};
 use crate::{ArgumentType, Context, JmespathError, Rcvar, Runtime, Signature, Variable};
 
 /// Register all ID functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
-    runtime.register_function("nanoid", Box::new(NanoidFn::new()));
-    runtime.register_function("ulid", Box::new(UlidFn::new()));
+    register_with_source(runtime, RngSource::default());
+}
+
+/// Register all ID functions, drawing their random parts from `source`.
+pub fn register_with_source(runtime: &mut Runtime, source: RngSource) {
+    runtime.register_function("nanoid", Box::new(NanoidFn::with_source(source.clone())));
+    runtime.register_function("ulid", Box::new(UlidFn::with_source(source.clone())));
     runtime.register_function("ulid_timestamp", Box::new(UlidTimestampFn::new()));
+    runtime.register_function("snowflake", Box::new(SnowflakeFn::new()));
+    runtime.register_function("snowflake_timestamp", Box::new(SnowflakeTimestampFn::new()));
+    runtime.register_function("snowflake_parse", Box::new(SnowflakeParseFn::new()));
+    runtime.register_function("ksuid", Box::new(KsuidFn::with_source(source.clone())));
+    runtime.register_function("ksuid_timestamp", Box::new(KsuidTimestampFn::new()));
+    runtime.register_function("cuid2", Box::new(Cuid2Fn::with_source(source)));
 }
 
 // =============================================================================
@@ -34,6 +50,7 @@ pub fn register(runtime: &mut Runtime) {
 
 pub struct NanoidFn {
     signature: Signature,
+    rng: RngSource,
 }
 
 impl Default for NanoidFn {
@@ -44,8 +61,13 @@ impl Default for NanoidFn {
 
 impl NanoidFn {
     pub fn new() -> Self {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> Self {
         Self {
             signature: Signature::new(vec![], Some(ArgumentType::Number)),
+            rng,
         }
     }
 }
@@ -54,12 +76,16 @@ impl Function for NanoidFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let id = if args.is_empty() {
-            nanoid::nanoid!()
-        } else {
-            let size = args[0].as_number().unwrap_or(21.0) as usize;
-            nanoid::nanoid!(size)
-        };
+        let size = args
+            .first()
+            .and_then(|n| n.as_number())
+            .map_or(21, |n| n as usize);
+
+        // The alphabet has 64 symbols, so masking each byte is unbiased
+        let alphabet = &nanoid::alphabet::SAFE;
+        let mut bytes = vec![0u8; size];
+        self.rng.with(|rng| rng.fill_bytes(&mut bytes));
+        let id = bytes.iter().map(|b| alphabet[(*b & 63) as usize]).collect();
 
         Ok(Rc::new(Variable::String(id)))
     }
@@ -71,6 +97,7 @@ impl Function for NanoidFn {
 
 pub struct UlidFn {
     signature: Signature,
+    rng: RngSource,
 }
 
 impl Default for UlidFn {
@@ -81,8 +108,13 @@ impl Default for UlidFn {
 
 impl UlidFn {
     pub fn new() -> Self {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> Self {
         Self {
             signature: Signature::new(vec![], None),
+            rng,
         }
     }
 }
@@ -90,8 +122,12 @@ impl UlidFn {
 impl Function for UlidFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
-        let id = ulid::Ulid::new().to_string();
-        Ok(Rc::new(Variable::String(id)))
+
+        // 48 bits of milliseconds followed by 80 random bits
+        let mut random = [0u8; 16];
+        self.rng.with(|rng| rng.fill_bytes(&mut random[6..]));
+        let id = ulid::Ulid::from_parts(now_unix_ms(), u128::from_be_bytes(random));
+        Ok(Rc::new(Variable::String(id.to_string())))
     }
 }
 
@@ -134,6 +170,478 @@ impl Function for UlidTimestampFn {
     }
 }
 
+// =============================================================================
+// Snowflake IDs
+// 64-bit IDs laid out as 41 bits of milliseconds since an epoch, a 10-bit
+// worker ID and a 12-bit per-millisecond sequence (Twitter/Discord style).
+// IDs are exchanged as strings because they exceed JSON's safe integer range.
+// =============================================================================
+
+const TWITTER_EPOCH_MS: u64 = 1_288_834_974_657;
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+const SNOWFLAKE_WORKER_BITS: u64 = 10;
+const SNOWFLAKE_SEQUENCE_BITS: u64 = 12;
+
+/// Last timestamp and sequence handed out by `snowflake()` in this process.
+static SNOWFLAKE_STATE: Mutex<(u64, u64)> = Mutex::new((0, 0));
+
+fn id_error(ctx: &Context<'_>, msg: String) -> JmespathError {
+    JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg))
+}
+
+/// Read a snowflake given as a decimal string or an integer number.
+fn parse_snowflake(value: &Variable) -> Option<u64> {
+    match value {
+        Variable::String(s) => s.trim().parse().ok(),
+        Variable::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+/// Resolve the optional epoch: milliseconds, or 'twitter' / 'discord'.
+fn parse_epoch(value: Option<&Rcvar>, ctx: &Context<'_>) -> Result<u64, JmespathError> {
+    match value.map(|v| v.as_ref()) {
+        None | Some(Variable::Null) => Ok(TWITTER_EPOCH_MS),
+        Some(Variable::String(s)) => match s.as_str() {
+            "twitter" | "x" => Ok(TWITTER_EPOCH_MS),
+            "discord" => Ok(DISCORD_EPOCH_MS),
+            other => Err(id_error(
+                ctx,
+                format!(
+                    "Unknown snowflake epoch '{}': expected 'twitter', 'discord' or milliseconds",
+                    other
+                ),
+            )),
+        },
+        Some(Variable::Number(n)) => n
+            .as_u64()
+            .ok_or_else(|| id_error(ctx, "Snowflake epoch must be non-negative".to_owned())),
+        Some(_) => Err(id_error(
+            ctx,
+            "Snowflake epoch must be a number or string".to_owned(),
+        )),
+    }
+}
+
+fn u64_var(n: u64) -> Rcvar {
+    Rc::new(Variable::Number(serde_json::Number::from(n)))
+}
+
+// =============================================================================
+// snowflake_timestamp(id, epoch?) -> number (unix ms)
+// =============================================================================
+
+pub struct SnowflakeTimestampFn {
+    signature: Signature,
+}
+
+impl Default for SnowflakeTimestampFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnowflakeTimestampFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Any], Some(ArgumentType::Any)),
+        }
+    }
+}
+
+impl Function for SnowflakeTimestampFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let epoch = parse_epoch(args.get(1), ctx)?;
+
+        match parse_snowflake(&args[0]) {
+            Some(id) => Ok(u64_var(
+                (id >> (SNOWFLAKE_WORKER_BITS + SNOWFLAKE_SEQUENCE_BITS)) + epoch,
+            )),
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// snowflake_parse(id, epoch?) -> object {timestamp, worker_id, sequence}
+// =============================================================================
+
+pub struct SnowflakeParseFn {
+    signature: Signature,
+}
+
+impl Default for SnowflakeParseFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnowflakeParseFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Any], Some(ArgumentType::Any)),
+        }
+    }
+}
+
+impl Function for SnowflakeParseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let epoch = parse_epoch(args.get(1), ctx)?;
+
+        let Some(id) = parse_snowflake(&args[0]) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        let mut map = BTreeMap::new();
+        map.insert(
+            "timestamp".to_string(),
+            u64_var((id >> (SNOWFLAKE_WORKER_BITS + SNOWFLAKE_SEQUENCE_BITS)) + epoch),
+        );
+        map.insert(
+            "worker_id".to_string(),
+            u64_var((id >> SNOWFLAKE_SEQUENCE_BITS) & ((1 << SNOWFLAKE_WORKER_BITS) - 1)),
+        );
+        map.insert(
+            "sequence".to_string(),
+            u64_var(id & ((1 << SNOWFLAKE_SEQUENCE_BITS) - 1)),
+        );
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
+// =============================================================================
+// snowflake(worker_id, epoch?) -> string
+// =============================================================================
+
+pub struct SnowflakeFn {
+    signature: Signature,
+}
+
+impl Default for SnowflakeFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnowflakeFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Number], Some(ArgumentType::Any)),
+        }
+    }
+}
+
+impl Function for SnowflakeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let worker = args[0].as_number().unwrap();
+        let max_worker = ((1 << SNOWFLAKE_WORKER_BITS) - 1) as f64;
+        if worker.fract() != 0.0 || !(0.0..=max_worker).contains(&worker) {
+            return Err(id_error(
+                ctx,
+                format!(
+                    "Snowflake worker_id must be an integer from 0 to {}",
+                    max_worker
+                ),
+            ));
+        }
+        let epoch = parse_epoch(args.get(1), ctx)?;
+
+        let (timestamp, sequence) = {
+            let mut state = SNOWFLAKE_STATE.lock().unwrap_or_else(|e| e.into_inner());
+            let (last, seq) = *state;
+            // Never go backwards, and wait for the next millisecond once the
+            // sequence for the current one is exhausted
+            let mut now = now_unix_ms().max(last);
+            let next_seq = if now == last { seq + 1 } else { 0 };
+            let next = if next_seq >> SNOWFLAKE_SEQUENCE_BITS != 0 {
+                while now <= last {
+                    now = now_unix_ms();
+                }
+                (now, 0)
+            } else {
+                (now, next_seq)
+            };
+            *state = next;
+            next
+        };
+
+        let elapsed = timestamp
+            .checked_sub(epoch)
+            .ok_or_else(|| id_error(ctx, "Snowflake epoch is in the future".to_owned()))?;
+        let id = (elapsed << (SNOWFLAKE_WORKER_BITS + SNOWFLAKE_SEQUENCE_BITS))
+            | ((worker as u64) << SNOWFLAKE_SEQUENCE_BITS)
+            | sequence;
+        Ok(Rc::new(Variable::String(id.to_string())))
+    }
+}
+
+// =============================================================================
+// Base-N helpers for KSUID and CUID2
+// =============================================================================
+
+const BASE36_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE62_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Encode a big-endian unsigned integer in the given alphabet.
+fn encode_big(bytes: &[u8], alphabet: &[u8]) -> String {
+    let base = alphabet.len() as u32;
+    let mut digits = Vec::new();
+    let mut number: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+    while !number.is_empty() {
+        // Long division of the whole number by `base`
+        let mut remainder = 0u32;
+        let mut quotient = Vec::with_capacity(number.len());
+        for byte in &number {
+            let acc = (remainder << 8) | *byte as u32;
+            let q = (acc / base) as u8;
+            remainder = acc % base;
+            if !quotient.is_empty() || q != 0 {
+                quotient.push(q);
+            }
+        }
+        digits.push(alphabet[remainder as usize]);
+        number = quotient;
+    }
+    if digits.is_empty() {
+        digits.push(alphabet[0]);
+    }
+    digits.iter().rev().map(|&d| d as char).collect()
+}
+
+/// Decode a string in the given alphabet into `N` big-endian bytes.
+fn decode_big<const N: usize>(s: &str, alphabet: &[u8]) -> Option<[u8; N]> {
+    let base = alphabet.len() as u32;
+    let mut out = [0u8; N];
+    for c in s.bytes() {
+        let mut carry = alphabet.iter().position(|&a| a == c)? as u32;
+        for byte in out.iter_mut().rev() {
+            let acc = *byte as u32 * base + carry;
+            *byte = acc as u8;
+            carry = acc >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+    Some(out)
+}
+
+fn now_unix_ms() -> u64 {
+    crate::common::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// =============================================================================
+// ksuid() -> string
+// 27-character base62 K-Sortable Unique ID: a 32-bit timestamp (seconds since
+// 2014-05-13) followed by 128 random bits.
+// =============================================================================
+
+const KSUID_EPOCH_SECS: u64 = 1_400_000_000;
+const KSUID_ENCODED_LEN: usize = 27;
+
+pub struct KsuidFn {
+    signature: Signature,
+    rng: RngSource,
+}
+
+impl Default for KsuidFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KsuidFn {
+    pub fn new() -> Self {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> Self {
+        Self {
+            signature: Signature::new(vec![], None),
+            rng,
+        }
+    }
+}
+
+impl Function for KsuidFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let timestamp = (now_unix_ms() / 1000).saturating_sub(KSUID_EPOCH_SECS) as u32;
+        let mut payload = [0u8; 20];
+        payload[..4].copy_from_slice(&timestamp.to_be_bytes());
+        self.rng.with(|rng| rng.fill_bytes(&mut payload[4..]));
+
+        let encoded = encode_big(&payload, BASE62_ALPHABET);
+        Ok(Rc::new(Variable::String(format!(
+            "{:0>width$}",
+            encoded,
+            width = KSUID_ENCODED_LEN
+        ))))
+    }
+}
+
+// =============================================================================
+// ksuid_timestamp(id) -> number (unix seconds)
+// =============================================================================
+
+pub struct KsuidTimestampFn {
+    signature: Signature,
+}
+
+impl Default for KsuidTimestampFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KsuidTimestampFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for KsuidTimestampFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let id = args[0].as_string().unwrap();
+
+        if id.len() != KSUID_ENCODED_LEN {
+            return Ok(Rc::new(Variable::Null));
+        }
+        match decode_big::<20>(id, BASE62_ALPHABET) {
+            Some(payload) => {
+                let ts = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]);
+                Ok(u64_var(ts as u64 + KSUID_EPOCH_SECS))
+            }
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// cuid2(length?) -> string
+// Collision-resistant ID following the reference CUID2 construction: a random
+// leading letter, then a SHA3-512 hash of time, entropy, a process-wide counter
+// and a host fingerprint, in base36.
+// =============================================================================
+
+const CUID2_DEFAULT_LENGTH: usize = 24;
+const CUID2_MAX_LENGTH: usize = 32;
+
+/// The counter starts at a random value below this, as in the reference.
+const CUID2_COUNTER_START_RANGE: u64 = 476_782_367;
+
+static CUID2_COUNTER: LazyLock<AtomicU64> = LazyLock::new(|| {
+    AtomicU64::new(rand::Rng::gen_range(
+        &mut rand::thread_rng(),
+        0..CUID2_COUNTER_START_RANGE,
+    ))
+});
+
+static CUID2_FINGERPRINT: LazyLock<String> = LazyLock::new(|| {
+    let entropy = cuid2_entropy(&mut rand::thread_rng(), CUID2_MAX_LENGTH);
+    cuid2_hash(&format!("{}{}", std::process::id(), entropy))[..CUID2_MAX_LENGTH].to_string()
+});
+
+fn cuid2_entropy(rng: &mut dyn rand::RngCore, length: usize) -> String {
+    use rand::Rng;
+
+    (0..length)
+        .map(|_| BASE36_ALPHABET[rng.gen_range(0..36)] as char)
+        .collect()
+}
+
+/// Base36 SHA3-512 digest, dropping the first (biased) character.
+fn cuid2_hash(input: &str) -> String {
+    use sha3::{Digest, Sha3_512};
+
+    let digest = Sha3_512::digest(input.as_bytes());
+    encode_big(&digest, BASE36_ALPHABET)[1..].to_string()
+}
+
+pub struct Cuid2Fn {
+    signature: Signature,
+    rng: RngSource,
+}
+
+impl Default for Cuid2Fn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cuid2Fn {
+    pub fn new() -> Self {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> Self {
+        Self {
+            signature: Signature::new(vec![], Some(ArgumentType::Number)),
+            rng,
+        }
+    }
+}
+
+impl Function for Cuid2Fn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand::Rng;
+
+        self.signature.validate(args, ctx)?;
+        let length = match args.first().and_then(|v| v.as_number()) {
+            None => CUID2_DEFAULT_LENGTH,
+            Some(n) if n.fract() == 0.0 && (2.0..=CUID2_MAX_LENGTH as f64).contains(&n) => {
+                n as usize
+            }
+            Some(_) => {
+                return Err(id_error(
+                    ctx,
+                    format!(
+                        "cuid2() length must be an integer from 2 to {}",
+                        CUID2_MAX_LENGTH
+                    ),
+                ));
+            }
+        };
+
+        let seeded = self.rng.is_seeded();
+        let (first, entropy, count, fingerprint) = self.rng.with(|rng| {
+            let first = (b'a' + rng.gen_range(0..26u8)) as char;
+            let entropy = cuid2_entropy(rng, length);
+            // The process-wide counter and fingerprint would make a seeded
+            // source produce different IDs on every run, so draw them too
+            if seeded {
+                let count = rng.gen_range(0..CUID2_COUNTER_START_RANGE);
+                let fingerprint = cuid2_entropy(rng, CUID2_MAX_LENGTH);
+                (first, entropy, count, fingerprint)
+            } else {
+                let count = CUID2_COUNTER.fetch_add(1, Ordering::Relaxed);
+                (first, entropy, count, CUID2_FINGERPRINT.clone())
+            }
+        });
+        let time = encode_big(&now_unix_ms().to_be_bytes(), BASE36_ALPHABET);
+        let input = format!(
+            "{}{}{}{}",
+            time,
+            entropy,
+            encode_big(&count.to_be_bytes(), BASE36_ALPHABET),
+            fingerprint
+        );
+
+        let id = format!("{}{}", first, &cuid2_hash(&input)[1..length]);
+        Ok(Rc::new(Variable::String(id)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,6 +683,24 @@ mod tests {
         assert_ne!(id1.as_string().unwrap(), id2.as_string().unwrap());
     }
 
+    #[test]
+    fn test_nanoid_seeded_source() {
+        let seeded = |seed| {
+            let mut runtime = Runtime::new();
+            register_with_source(&mut runtime, RngSource::seeded(seed));
+            let expr = runtime.compile("[nanoid(), nanoid(`8`)]").unwrap();
+            expr.search(Variable::from_json("{}").unwrap()).unwrap()
+        };
+        assert_eq!(seeded(7), seeded(7));
+        assert_ne!(seeded(7), seeded(8));
+
+        let ids = seeded(7);
+        let ids = ids.as_array().unwrap();
+        assert_eq!(ids[0].as_string().unwrap().len(), 21);
+        assert_eq!(ids[1].as_string().unwrap().len(), 8);
+        assert_ne!(ids[0], ids[1]);
+    }
+
     #[test]
     fn test_ulid() {
         let runtime = setup();
@@ -232,4 +758,126 @@ mod tests {
         // All characters should be valid Base32
         assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
     }
+
+    #[test]
+    fn test_snowflake_timestamp_twitter() {
+        let runtime = setup();
+        // (id >> 22) + Twitter epoch
+        let expr = runtime
+            .compile("snowflake_timestamp('1212092628029698048')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1577820376771.0);
+    }
+
+    #[test]
+    fn test_snowflake_parse_discord() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("snowflake_parse('175928847299117063', 'discord')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let map = result.as_object().unwrap();
+        assert_eq!(
+            map.get("timestamp").unwrap().as_number().unwrap(),
+            1462015105796.0
+        );
+        assert_eq!(map.get("worker_id").unwrap().as_number().unwrap(), 32.0);
+        assert_eq!(map.get("sequence").unwrap().as_number().unwrap(), 7.0);
+
+        let expr = runtime.compile("snowflake_parse('abc')").unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_snowflake_generate_roundtrip() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("[snowflake(`42`), snowflake(`42`)]")
+            .unwrap();
+        let result = expr.search(Variable::from_json("{}").unwrap()).unwrap();
+        let ids: Vec<u64> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().parse().unwrap())
+            .collect();
+        assert!(ids[1] > ids[0]);
+
+        let data = Variable::String(ids[0].to_string());
+        let parsed = runtime.compile("snowflake_parse(@)").unwrap();
+        let result = parsed.search(&data).unwrap();
+        let map = result.as_object().unwrap();
+        assert_eq!(map.get("worker_id").unwrap().as_number().unwrap(), 42.0);
+        let now = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as f64;
+        let ts = map.get("timestamp").unwrap().as_number().unwrap();
+        assert!((now - ts).abs() < 60_000.0);
+
+        let expr = runtime.compile("snowflake(`1024`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_ksuid_format_and_timestamp() {
+        let runtime = setup();
+        let expr = runtime.compile("ksuid()").unwrap();
+        let id = expr.search(&Variable::Null).unwrap();
+        let id = id.as_string().unwrap();
+        assert_eq!(id.len(), 27);
+        assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
+
+        let data = Variable::String(id.clone());
+        let expr = runtime.compile("ksuid_timestamp(@)").unwrap();
+        let ts = expr.search(&data).unwrap().as_number().unwrap();
+        let now = (now_unix_ms() / 1000) as f64;
+        assert!((now - ts).abs() <= 2.0);
+    }
+
+    #[test]
+    fn test_ksuid_timestamp_known_value() {
+        let runtime = setup();
+        // Example from the segmentio/ksuid README
+        let expr = runtime
+            .compile("ksuid_timestamp('0ujtsYcgvSTl8PAuAdqWYSMnLOv')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 1507608047.0);
+
+        for bad in [
+            "'short'",
+            "'0ujtsYcgvSTl8PAuAdqWYSMnLO!'",
+            "'zzzzzzzzzzzzzzzzzzzzzzzzzzz'",
+        ] {
+            let expr = runtime
+                .compile(&format!("ksuid_timestamp({})", bad))
+                .unwrap();
+            assert!(expr.search(&Variable::Null).unwrap().is_null(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_cuid2() {
+        let runtime = setup();
+        let expr = runtime.compile("cuid2()").unwrap();
+        let a = expr.search(&Variable::Null).unwrap();
+        let b = expr.search(&Variable::Null).unwrap();
+        let a = a.as_string().unwrap();
+        assert_eq!(a.len(), 24);
+        assert!(a.starts_with(|c: char| c.is_ascii_lowercase()));
+        assert!(
+            a.chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        );
+        assert_ne!(a, b.as_string().unwrap());
+
+        let expr = runtime.compile("cuid2(`10`)").unwrap();
+        let short = expr.search(&Variable::Null).unwrap();
+        assert_eq!(short.as_string().unwrap().len(), 10);
+
+        let expr = runtime.compile("cuid2(`40`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
 }
@@ -0,0 +1,600 @@
+//! JsonLogic rule evaluation.
+//!
+//! This module provides a `jsonlogic(rule, data)` function that evaluates
+//! [JsonLogic](https://jsonlogic.com) rules, so rules stored as JSON (feature
+//! flags, eligibility checks, pricing tiers) can be applied from a query.
+//!
+//! For complete function reference with signatures and examples, see the
+//! [`functions`](crate::functions) module documentation or use `jpx --list-category jsonlogic`.
+//!
+//! # Supported operations
+//!
+//! - Data access: `var`, `missing`, `missing_some`
+//! - Logic: `if`, `?:`, `==`, `===`, `!=`, `!==`, `!`, `!!`, `and`, `or`
+//! - Numeric: `>`, `>=`, `<`, `<=` (including the three-argument "between" form),
+//!   `max`, `min`, `+`, `-`, `*`, `/`, `%`
+//! - Arrays: `map`, `filter`, `reduce`, `all`, `none`, `some`, `merge`, `in`
+//! - Strings: `in`, `cat`, `substr`
+//! - Misc: `log` (returns its argument)
+//!
+//! Truthiness follows the JsonLogic spec: `0`, `""`, `[]`, `null` and `false`
+//! are falsy; everything else is truthy.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jmespath::{Runtime, Variable};
+//! use jmespath_extensions::jsonlogic;
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! jsonlogic::register(&mut runtime);
+//!
+//! let expr = runtime.compile("jsonlogic(rule, user)").unwrap();
+//! let data = Variable::from_json(
+//!     r#"{"rule": {">=": [{"var": "age"}, 18]}, "user": {"age": 21}}"#,
+//! ).unwrap();
+//! assert!(expr.search(&data).unwrap().as_boolean().unwrap());
+//! ```
+
+use crate::common::Rc;
+use std::collections::BTreeMap;
+
+use crate::common::{ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable};
+use crate::define_function;
+use jmespath::Runtime;
+
+/// Register all JsonLogic functions with the runtime.
+pub fn register(runtime: &mut Runtime) {
+    runtime.register_function("jsonlogic", Box::new(JsonLogicFn::new()));
+}
+
+// =============================================================================
+// jsonlogic(rule, data) -> any
+// =============================================================================
+
+define_function!(
+    JsonLogicFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+impl Function for JsonLogicFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        apply(&args[0], &args[1]).map_err(|msg| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!("jsonlogic: {}", msg)),
+            )
+        })
+    }
+}
+
+type LogicResult = Result<Rcvar, String>;
+
+/// Evaluate a rule against data.
+///
+/// Objects with exactly one key are operations; arrays are evaluated
+/// element-wise; anything else is returned as a literal.
+fn apply(rule: &Rcvar, data: &Rcvar) -> LogicResult {
+    match rule.as_ref() {
+        Variable::Array(items) => Ok(Rc::new(Variable::Array(
+            items
+                .iter()
+                .map(|item| apply(item, data))
+                .collect::<Result<_, _>>()?,
+        ))),
+        Variable::Object(obj) if obj.len() == 1 => {
+            let (op, raw_args) = obj.iter().next().unwrap();
+            // A single non-array argument is shorthand for a one-element list
+            let args: Vec<Rcvar> = match raw_args.as_ref() {
+                Variable::Array(items) => items.clone(),
+                _ => vec![raw_args.clone()],
+            };
+            apply_op(op, &args, data)
+        }
+        _ => Ok(rule.clone()),
+    }
+}
+
+fn apply_op(op: &str, args: &[Rcvar], data: &Rcvar) -> LogicResult {
+    // Operations that control evaluation of their own arguments
+    match op {
+        "if" | "?:" => return apply_if(args, data),
+        "and" => {
+            let mut last = bool_var(true);
+            for arg in args {
+                last = apply(arg, data)?;
+                if !truthy(&last) {
+                    return Ok(last);
+                }
+            }
+            return Ok(last);
+        }
+        "or" => {
+            let mut last = bool_var(false);
+            for arg in args {
+                last = apply(arg, data)?;
+                if truthy(&last) {
+                    return Ok(last);
+                }
+            }
+            return Ok(last);
+        }
+        "map" | "filter" | "all" | "none" | "some" | "reduce" => {
+            return apply_array_op(op, args, data);
+        }
+        _ => {}
+    }
+
+    let values: Vec<Rcvar> = args
+        .iter()
+        .map(|arg| apply(arg, data))
+        .collect::<Result<_, _>>()?;
+    let arg = |i: usize| values.get(i).cloned().unwrap_or_else(null_var);
+
+    match op {
+        "var" => Ok(var_lookup(data, &arg(0)).unwrap_or_else(|| arg(1))),
+        "missing" => Ok(Rc::new(Variable::Array(missing_keys(data, &values)))),
+        "missing_some" => {
+            let min = to_number(&arg(0)).unwrap_or(0.0);
+            let keys = match arg(1).as_ref() {
+                Variable::Array(keys) => keys.clone(),
+                _ => vec![],
+            };
+            let missing = missing_keys(data, &keys);
+            if (keys.len() - missing.len()) as f64 >= min {
+                Ok(Rc::new(Variable::Array(vec![])))
+            } else {
+                Ok(Rc::new(Variable::Array(missing)))
+            }
+        }
+        "==" => Ok(bool_var(loose_equals(&arg(0), &arg(1)))),
+        "!=" => Ok(bool_var(!loose_equals(&arg(0), &arg(1)))),
+        "===" => Ok(bool_var(arg(0) == arg(1))),
+        "!==" => Ok(bool_var(arg(0) != arg(1))),
+        "!" => Ok(bool_var(!truthy(&arg(0)))),
+        "!!" => Ok(bool_var(truthy(&arg(0)))),
+        ">" | ">=" | "<" | "<=" => Ok(bool_var(compare_chain(op, &values))),
+        "max" | "min" => {
+            let nums = values.iter().map(to_number).collect::<Option<Vec<f64>>>();
+            Ok(match nums {
+                Some(nums) if !nums.is_empty() => number_var(if op == "max" {
+                    nums.into_iter().fold(f64::NEG_INFINITY, f64::max)
+                } else {
+                    nums.into_iter().fold(f64::INFINITY, f64::min)
+                }),
+                _ => null_var(),
+            })
+        }
+        "+" => {
+            let mut sum = 0.0;
+            for v in &values {
+                sum += to_number(v).ok_or("'+' expects numeric arguments")?;
+            }
+            Ok(number_var(sum))
+        }
+        "*" => {
+            let mut product = 1.0;
+            for v in &values {
+                product *= to_number(v).ok_or("'*' expects numeric arguments")?;
+            }
+            Ok(number_var(product))
+        }
+        "-" => {
+            let a = to_number(&arg(0)).ok_or("'-' expects numeric arguments")?;
+            if values.len() == 1 {
+                Ok(number_var(-a))
+            } else {
+                let b = to_number(&arg(1)).ok_or("'-' expects numeric arguments")?;
+                Ok(number_var(a - b))
+            }
+        }
+        "/" | "%" => {
+            let a = to_number(&arg(0)).ok_or_else(|| format!("'{}' expects numbers", op))?;
+            let b = to_number(&arg(1)).ok_or_else(|| format!("'{}' expects numbers", op))?;
+            if b == 0.0 {
+                return Ok(null_var());
+            }
+            Ok(number_var(if op == "/" { a / b } else { a % b }))
+        }
+        "merge" => {
+            let mut merged = Vec::new();
+            for v in &values {
+                match v.as_ref() {
+                    Variable::Array(items) => merged.extend(items.iter().cloned()),
+                    _ => merged.push(v.clone()),
+                }
+            }
+            Ok(Rc::new(Variable::Array(merged)))
+        }
+        "in" => Ok(bool_var(match arg(1).as_ref() {
+            Variable::Array(items) => items.contains(&arg(0)),
+            Variable::String(s) => s.contains(to_js_string(&arg(0)).as_str()),
+            _ => false,
+        })),
+        "cat" => Ok(Rc::new(Variable::String(
+            values.iter().map(to_js_string).collect(),
+        ))),
+        "substr" => {
+            let s: Vec<char> = to_js_string(&arg(0)).chars().collect();
+            let len = s.len() as i64;
+            let start = to_number(&arg(1)).unwrap_or(0.0) as i64;
+            let start = if start < 0 {
+                (len + start).max(0)
+            } else {
+                start.min(len)
+            };
+            let end = match values.get(2).and_then(to_number) {
+                None => len,
+                Some(n) if n < 0.0 => (len + n as i64).max(start),
+                Some(n) => (start + n as i64).min(len),
+            };
+            Ok(Rc::new(Variable::String(
+                s[start as usize..end.max(start) as usize].iter().collect(),
+            )))
+        }
+        "log" => Ok(arg(0)),
+        _ => Err(format!("Unrecognized operation '{}'", op)),
+    }
+}
+
+fn apply_if(args: &[Rcvar], data: &Rcvar) -> LogicResult {
+    let mut i = 0;
+    while i + 1 < args.len() {
+        if truthy(&apply(&args[i], data)?) {
+            return apply(&args[i + 1], data);
+        }
+        i += 2;
+    }
+    match args.get(i) {
+        Some(else_branch) => apply(else_branch, data),
+        None => Ok(null_var()),
+    }
+}
+
+fn apply_array_op(op: &str, args: &[Rcvar], data: &Rcvar) -> LogicResult {
+    let items = match args.first() {
+        Some(source) => match apply(source, data)?.as_ref() {
+            Variable::Array(items) => items.clone(),
+            _ => vec![],
+        },
+        None => vec![],
+    };
+    let logic = args.get(1).cloned().unwrap_or_else(null_var);
+
+    match op {
+        "map" => Ok(Rc::new(Variable::Array(
+            items
+                .iter()
+                .map(|item| apply(&logic, item))
+                .collect::<Result<_, _>>()?,
+        ))),
+        "filter" => {
+            let mut kept = Vec::new();
+            for item in items {
+                if truthy(&apply(&logic, &item)?) {
+                    kept.push(item);
+                }
+            }
+            Ok(Rc::new(Variable::Array(kept)))
+        }
+        "all" => {
+            if items.is_empty() {
+                return Ok(bool_var(false));
+            }
+            for item in &items {
+                if !truthy(&apply(&logic, item)?) {
+                    return Ok(bool_var(false));
+                }
+            }
+            Ok(bool_var(true))
+        }
+        "some" | "none" => {
+            for item in &items {
+                if truthy(&apply(&logic, item)?) {
+                    return Ok(bool_var(op == "some"));
+                }
+            }
+            Ok(bool_var(op == "none"))
+        }
+        "reduce" => {
+            let mut acc = match args.get(2) {
+                Some(initial) => apply(initial, data)?,
+                None => null_var(),
+            };
+            for item in items {
+                let mut scope = BTreeMap::new();
+                scope.insert("current".to_string(), item);
+                scope.insert("accumulator".to_string(), acc);
+                acc = apply(&logic, &Rc::new(Variable::Object(scope)))?;
+            }
+            Ok(acc)
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Resolve a `var` path (dot-separated string or number) against data.
+fn var_lookup(data: &Rcvar, path: &Rcvar) -> Option<Rcvar> {
+    let path = match path.as_ref() {
+        Variable::Null => return Some(data.clone()),
+        Variable::String(s) if s.is_empty() => return Some(data.clone()),
+        Variable::String(s) => s.clone(),
+        Variable::Number(n) => n.to_string(),
+        _ => return None,
+    };
+
+    let mut current = data.clone();
+    for part in path.split('.') {
+        let next = match current.as_ref() {
+            Variable::Object(obj) => obj.get(part).cloned(),
+            Variable::Array(arr) => part.parse::<usize>().ok().and_then(|i| arr.get(i).cloned()),
+            _ => None,
+        }?;
+        current = next;
+    }
+    // A present-but-null value falls back to the default like a missing one
+    (!current.is_null()).then_some(current)
+}
+
+fn missing_keys(data: &Rcvar, keys: &[Rcvar]) -> Vec<Rcvar> {
+    // `missing` also accepts a single array argument (e.g. from `merge`)
+    let keys: Vec<Rcvar> = match keys {
+        [single] if single.is_array() => single.as_array().unwrap().clone(),
+        _ => keys.to_vec(),
+    };
+    keys.into_iter()
+        .filter(|key| match var_lookup(data, key) {
+            None => true,
+            Some(v) => matches!(v.as_ref(), Variable::String(s) if s.is_empty()),
+        })
+        .collect()
+}
+
+fn compare_chain(op: &str, values: &[Rcvar]) -> bool {
+    let cmp = |a: &Rcvar, b: &Rcvar| -> bool {
+        let ordering = match (a.as_ref(), b.as_ref()) {
+            (Variable::String(x), Variable::String(y)) => Some(x.cmp(y)),
+            _ => match (to_number(a), to_number(b)) {
+                (Some(x), Some(y)) => x.partial_cmp(&y),
+                _ => None,
+            },
+        };
+        match ordering {
+            Some(o) => match op {
+                ">" => o.is_gt(),
+                ">=" => o.is_ge(),
+                "<" => o.is_lt(),
+                _ => o.is_le(),
+            },
+            None => false,
+        }
+    };
+    match values {
+        [a, b] => cmp(a, b),
+        // "between": {"<": [1, x, 10]}
+        [a, b, c] => cmp(a, b) && cmp(b, c),
+        _ => false,
+    }
+}
+
+/// JavaScript-style `==`.
+fn loose_equals(a: &Rcvar, b: &Rcvar) -> bool {
+    match (a.as_ref(), b.as_ref()) {
+        (Variable::Null, Variable::Null) => true,
+        (Variable::Null, _) | (_, Variable::Null) => false,
+        (Variable::String(x), Variable::String(y)) => x == y,
+        (Variable::Array(_), _) | (Variable::Object(_), _) => a == b,
+        (_, Variable::Array(_)) | (_, Variable::Object(_)) => false,
+        _ => match (to_number(a), to_number(b)) {
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        },
+    }
+}
+
+/// JsonLogic truthiness (JavaScript rules, except `[]` is falsy).
+fn truthy(value: &Rcvar) -> bool {
+    match value.as_ref() {
+        Variable::Null => false,
+        Variable::Bool(b) => *b,
+        Variable::Number(n) => n.as_f64().is_some_and(|f| f != 0.0 && !f.is_nan()),
+        Variable::String(s) => !s.is_empty(),
+        Variable::Array(a) => !a.is_empty(),
+        Variable::Object(_) | Variable::Expref(_) => true,
+    }
+}
+
+/// JavaScript-style `ToNumber` for primitive values.
+fn to_number(value: &Rcvar) -> Option<f64> {
+    match value.as_ref() {
+        Variable::Number(n) => n.as_f64(),
+        Variable::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Variable::Null => Some(0.0),
+        Variable::String(s) if s.trim().is_empty() => Some(0.0),
+        Variable::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn to_js_string(value: &Rcvar) -> String {
+    match value.as_ref() {
+        Variable::String(s) => s.clone(),
+        Variable::Null => "null".to_string(),
+        Variable::Bool(b) => b.to_string(),
+        Variable::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < 1e21 => format!("{}", f as i64),
+            _ => n.to_string(),
+        },
+        Variable::Array(items) => items.iter().map(to_js_string).collect::<Vec<_>>().join(","),
+        _ => "[object Object]".to_string(),
+    }
+}
+
+fn bool_var(b: bool) -> Rcvar {
+    Rc::new(Variable::Bool(b))
+}
+
+fn null_var() -> Rcvar {
+    Rc::new(Variable::Null)
+}
+
+fn number_var(n: f64) -> Rcvar {
+    Rc::new(
+        serde_json::Number::from_f64(n)
+            .map(Variable::Number)
+            .unwrap_or(Variable::Null),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    fn eval(rule: &str, data: &str) -> Variable {
+        let runtime = setup_runtime();
+        let input =
+            Variable::from_json(&format!(r#"{{"rule": {}, "data": {}}}"#, rule, data)).unwrap();
+        let expr = runtime.compile("jsonlogic(rule, data)").unwrap();
+        (*expr.search(&input).unwrap()).clone()
+    }
+
+    fn json(s: &str) -> Variable {
+        Variable::from_json(s).unwrap()
+    }
+
+    #[test]
+    fn test_jsonlogic_literals_and_var() {
+        assert_eq!(eval("true", "null"), json("true"));
+        assert_eq!(eval(r#"{"var": "a.b"}"#, r#"{"a": {"b": 3}}"#), json("3"));
+        assert_eq!(eval(r#"{"var": 1}"#, r#"["x", "y"]"#), json(r#""y""#));
+        assert_eq!(eval(r#"{"var": ["z", 7]}"#, "{}"), json("7"));
+        assert_eq!(eval(r#"{"var": ""}"#, "5"), json("5"));
+    }
+
+    #[test]
+    fn test_jsonlogic_logic_and_comparison() {
+        assert_eq!(eval(r#"{"==": [1, "1"]}"#, "null"), json("true"));
+        assert_eq!(eval(r#"{"===": [1, "1"]}"#, "null"), json("false"));
+        assert_eq!(
+            eval(r#"{"<": [1, {"var": "x"}, 10]}"#, r#"{"x": 5}"#),
+            json("true")
+        );
+        assert_eq!(eval(r#"{"and": [true, "a", 0]}"#, "null"), json("0"));
+        assert_eq!(eval(r#"{"or": [false, [], "x"]}"#, "null"), json(r#""x""#));
+        assert_eq!(eval(r#"{"!": [[]]}"#, "null"), json("true"));
+    }
+
+    #[test]
+    fn test_jsonlogic_if_chain() {
+        let rule = r#"{"if": [
+            {"<": [{"var": "temp"}, 0]}, "freezing",
+            {"<": [{"var": "temp"}, 100]}, "liquid",
+            "gas"
+        ]}"#;
+        assert_eq!(eval(rule, r#"{"temp": -5}"#), json(r#""freezing""#));
+        assert_eq!(eval(rule, r#"{"temp": 55}"#), json(r#""liquid""#));
+        assert_eq!(eval(rule, r#"{"temp": 200}"#), json(r#""gas""#));
+    }
+
+    #[test]
+    fn test_jsonlogic_arithmetic() {
+        assert_eq!(eval(r#"{"+": [1, "2", 3]}"#, "null"), json("6.0"));
+        assert_eq!(eval(r#"{"-": [2]}"#, "null"), json("-2.0"));
+        assert_eq!(eval(r#"{"%": [7, 3]}"#, "null"), json("1.0"));
+        assert_eq!(eval(r#"{"max": [1, 9, 3]}"#, "null"), json("9.0"));
+        assert_eq!(eval(r#"{"/": [1, 0]}"#, "null"), json("null"));
+    }
+
+    #[test]
+    fn test_jsonlogic_array_operations() {
+        let data = r#"{"xs": [1, 2, 3, 4]}"#;
+        assert_eq!(
+            eval(
+                r#"{"filter": [{"var": "xs"}, {">": [{"var": ""}, 2]}]}"#,
+                data
+            ),
+            json("[3, 4]")
+        );
+        assert_eq!(
+            eval(r#"{"map": [{"var": "xs"}, {"*": [{"var": ""}, 2]}]}"#, data),
+            json("[2.0, 4.0, 6.0, 8.0]")
+        );
+        assert_eq!(
+            eval(
+                r#"{"reduce": [{"var": "xs"}, {"+": [{"var": "current"}, {"var": "accumulator"}]}, 0]}"#,
+                data
+            ),
+            json("10.0")
+        );
+        assert_eq!(eval(r#"{"all": [[], true]}"#, "null"), json("false"));
+        assert_eq!(
+            eval(
+                r#"{"some": [{"var": "xs"}, {"==": [{"var": ""}, 3]}]}"#,
+                data
+            ),
+            json("true")
+        );
+        assert_eq!(
+            eval(r#"{"merge": [[1], 2, [3]]}"#, "null"),
+            json("[1, 2, 3]")
+        );
+    }
+
+    #[test]
+    fn test_jsonlogic_missing() {
+        let data = r#"{"a": 1, "c": ""}"#;
+        assert_eq!(
+            eval(r#"{"missing": ["a", "b", "c"]}"#, data),
+            json(r#"["b", "c"]"#)
+        );
+        assert_eq!(
+            eval(r#"{"missing_some": [1, ["a", "b"]]}"#, data),
+            json("[]")
+        );
+        assert_eq!(
+            eval(r#"{"missing_some": [2, ["a", "b"]]}"#, data),
+            json(r#"["b"]"#)
+        );
+    }
+
+    #[test]
+    fn test_jsonlogic_strings() {
+        assert_eq!(
+            eval(r#"{"cat": ["v", 1, "-", true]}"#, "null"),
+            json(r#""v1-true""#)
+        );
+        assert_eq!(
+            eval(r#"{"in": ["Spring", "Springfield"]}"#, "null"),
+            json("true")
+        );
+        assert_eq!(
+            eval(r#"{"substr": ["jsonlogic", -5]}"#, "null"),
+            json(r#""logic""#)
+        );
+        assert_eq!(
+            eval(r#"{"substr": ["jsonlogic", 1, -5]}"#, "null"),
+            json(r#""son""#)
+        );
+    }
+
+    #[test]
+    fn test_jsonlogic_unknown_operation() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("jsonlogic(`{\"nope\": [1]}`, `null`)")
+            .unwrap();
+        assert!(expr.search(Variable::Null).is_err());
+    }
+}
@@ -16,7 +16,7 @@
 //! jsonpatch::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use crate::common::{ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable};
 use crate::define_function;
@@ -27,6 +27,32 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("json_patch", Box::new(JsonPatchFn::new()));
     runtime.register_function("json_merge_patch", Box::new(JsonMergePatchFn::new()));
     runtime.register_function("json_diff", Box::new(JsonDiffFn::new()));
+    runtime.register_function("json_merge_diff", Box::new(JsonMergeDiffFn::new()));
+    runtime.register_function("json_patch_inverse", Box::new(JsonPatchInverseFn::new()));
+    runtime.register_function("json_patch_test", Box::new(JsonPatchTestFn::new()));
+}
+
+fn patch_error(ctx: &Context<'_>, msg: String) -> JmespathError {
+    JmespathError::new(ctx.expression, ctx.offset, ErrorReason::Parse(msg))
+}
+
+fn to_json(value: &Variable, ctx: &Context<'_>) -> Result<serde_json::Value, JmespathError> {
+    serde_json::to_value(value)
+        .map_err(|e| patch_error(ctx, format!("Failed to convert value: {}", e)))
+}
+
+fn from_json(value: &serde_json::Value, ctx: &Context<'_>) -> Result<Rcvar, JmespathError> {
+    Variable::from_json(&value.to_string())
+        .map(Rc::new)
+        .map_err(|e| patch_error(ctx, format!("Failed to convert result: {}", e)))
+}
+
+fn parse_patch(
+    value: serde_json::Value,
+    ctx: &Context<'_>,
+) -> Result<json_patch::Patch, JmespathError> {
+    serde_json::from_value(value)
+        .map_err(|e| patch_error(ctx, format!("Invalid JSON Patch format: {}", e)))
 }
 
 // =============================================================================
@@ -145,12 +171,70 @@ impl Function for JsonMergePatchFn {
 }
 
 // =============================================================================
-// json_diff(a, b) -> array (RFC 6902 JSON Patch)
+// json_merge_diff(a, b) -> any (RFC 7396 JSON Merge Patch)
+// Generate a JSON Merge Patch that transforms the first value into the second.
+// Removed keys become `null`. Merge patches cannot express setting a key to
+// `null`, so such keys are treated as removed.
+// See: https://datatracker.ietf.org/doc/html/rfc7396
+// =============================================================================
+
+fn merge_diff(a: &serde_json::Value, b: &serde_json::Value) -> serde_json::Value {
+    match (a, b) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            let mut patch = serde_json::Map::new();
+            for key in a.keys() {
+                if !b.contains_key(key) {
+                    patch.insert(key.clone(), serde_json::Value::Null);
+                }
+            }
+            for (key, new) in b {
+                match a.get(key) {
+                    Some(old) if old == new => {}
+                    Some(old) if old.is_object() && new.is_object() => {
+                        patch.insert(key.clone(), merge_diff(old, new));
+                    }
+                    _ => {
+                        patch.insert(key.clone(), new.clone());
+                    }
+                }
+            }
+            serde_json::Value::Object(patch)
+        }
+        _ => b.clone(),
+    }
+}
+
+define_function!(
+    JsonMergeDiffFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+impl Function for JsonMergeDiffFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let a = to_json(&args[0], ctx)?;
+        let b = to_json(&args[1], ctx)?;
+
+        from_json(&merge_diff(&a, &b), ctx)
+    }
+}
+
+// =============================================================================
+// json_diff(a, b, options?) -> array (RFC 6902 JSON Patch)
 // Generate a JSON Patch (RFC 6902) that transforms the first object into the second.
+// With `{moves: true}` a remove/add pair carrying the same value becomes a
+// `move`; with `{copies: true}` adding a value that already exists unchanged
+// elsewhere becomes a `copy`.
 // See: https://datatracker.ietf.org/doc/html/rfc6902
 // =============================================================================
 
-define_function!(JsonDiffFn, vec![ArgumentType::Any, ArgumentType::Any], None);
+define_function!(
+    JsonDiffFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    Some(ArgumentType::Object)
+);
 
 impl Function for JsonDiffFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
@@ -177,7 +261,7 @@ impl Function for JsonDiffFn {
         let patch = json_patch::diff(&a_json, &b_json);
 
         // Convert patch to JSON value
-        let patch_json = serde_json::to_value(&patch).map_err(|e| {
+        let mut patch_json = serde_json::to_value(&patch).map_err(|e| {
             JmespathError::new(
                 ctx.expression,
                 ctx.offset,
@@ -185,6 +269,31 @@ impl Function for JsonDiffFn {
             )
         })?;
 
+        let option = |name: &str| {
+            args.get(2)
+                .and_then(|o| o.as_object())
+                .and_then(|o| o.get(name))
+                .and_then(|v| v.as_boolean())
+                .unwrap_or(false)
+        };
+        if let serde_json::Value::Array(ops) = &patch_json {
+            let mut ops = ops.clone();
+            if option("moves") {
+                ops = detect_moves(ops, &a_json);
+            }
+            if option("copies") {
+                ops = detect_copies(ops, &a_json, &b_json);
+            }
+            // Only keep the rewritten patch if it still produces `b`
+            let candidate = serde_json::Value::Array(ops);
+            if let Ok(rewritten) = serde_json::from_value::<json_patch::Patch>(candidate.clone()) {
+                let mut check = a_json.clone();
+                if json_patch::patch(&mut check, &rewritten).is_ok() && check == b_json {
+                    patch_json = candidate;
+                }
+            }
+        }
+
         // Convert back to Variable
         let var = Variable::from_json(&patch_json.to_string()).map_err(|e| {
             JmespathError::new(
@@ -198,6 +307,243 @@ impl Function for JsonDiffFn {
     }
 }
 
+/// Turn each `remove` whose old value is later (or earlier) re-added verbatim
+/// elsewhere into a single `move`.
+fn detect_moves(
+    ops: Vec<serde_json::Value>,
+    original: &serde_json::Value,
+) -> Vec<serde_json::Value> {
+    let removed: Vec<Option<&serde_json::Value>> = ops
+        .iter()
+        .map(|op| match op["op"].as_str() {
+            Some("remove") => op["path"].as_str().and_then(|p| original.pointer(p)),
+            _ => None,
+        })
+        .collect();
+
+    let mut used = vec![false; ops.len()];
+    let mut moves: Vec<Option<String>> = vec![None; ops.len()];
+    for (i, op) in ops.iter().enumerate() {
+        if op["op"] != "add" {
+            continue;
+        }
+        let source = (0..ops.len()).find(|&j| !used[j] && removed[j] == Some(&op["value"]));
+        if let Some(j) = source {
+            used[j] = true;
+            moves[i] = ops[j]["path"].as_str().map(String::from);
+        }
+    }
+
+    ops.into_iter()
+        .enumerate()
+        .filter(|(i, _)| !used[*i])
+        .map(|(i, op)| match &moves[i] {
+            Some(from) => serde_json::json!({"op": "move", "from": from, "path": op["path"]}),
+            None => op,
+        })
+        .collect()
+}
+
+/// Collect the JSON pointers of every value in a document.
+fn collect_pointers(value: &serde_json::Value, prefix: String, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let token = key.replace('~', "~0").replace('/', "~1");
+                collect_pointers(child, format!("{}/{}", prefix, token), out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (i, child) in items.iter().enumerate() {
+                collect_pointers(child, format!("{}/{}", prefix, i), out);
+            }
+        }
+        _ => {}
+    }
+    out.push(prefix);
+}
+
+/// Turn `add` operations of a structured value that is present, unchanged, in
+/// both documents into a `copy` from that location.
+fn detect_copies(
+    ops: Vec<serde_json::Value>,
+    original: &serde_json::Value,
+    target: &serde_json::Value,
+) -> Vec<serde_json::Value> {
+    let mut pointers = Vec::new();
+    collect_pointers(original, String::new(), &mut pointers);
+
+    ops.into_iter()
+        .map(|op| {
+            let value = &op["value"];
+            let worth_copying = match value {
+                serde_json::Value::Object(m) => !m.is_empty(),
+                serde_json::Value::Array(a) => !a.is_empty(),
+                _ => false,
+            };
+            if op["op"] != "add" || !worth_copying {
+                return op;
+            }
+            let path = op["path"].as_str().unwrap_or_default();
+            let source = pointers.iter().find(|p| {
+                !p.is_empty()
+                    && original.pointer(p) == Some(value)
+                    && target.pointer(p) == Some(value)
+                    && !path.starts_with(p.as_str())
+            });
+            match source {
+                Some(from) => serde_json::json!({"op": "copy", "from": from, "path": path}),
+                None => op,
+            }
+        })
+        .collect()
+}
+
+// =============================================================================
+// json_patch_inverse(patch, original) -> array
+// Build the patch that undoes `patch`: applying it to the patched document
+// gives back `original`. `test` operations have no effect and are dropped.
+// =============================================================================
+
+/// Split a JSON pointer into its parent pointer and last token.
+fn split_pointer(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(i) => (&path[..i], &path[i + 1..]),
+        None => ("", path),
+    }
+}
+
+/// For an `add` (or `copy`/`move` target) at `path`, the operation that undoes
+/// it against `doc` as it was before: remove the inserted member or array
+/// element, or restore the value it replaced.
+fn undo_insert(doc: &serde_json::Value, path: &str) -> serde_json::Value {
+    let (parent, token) = split_pointer(path);
+    if let Some(serde_json::Value::Array(items)) = doc.pointer(parent) {
+        // Array inserts shift later elements, so undo by removing the new index
+        let index = if token == "-" {
+            items.len().to_string()
+        } else {
+            token.to_string()
+        };
+        return serde_json::json!({"op": "remove", "path": format!("{}/{}", parent, index)});
+    }
+    match doc.pointer(path) {
+        Some(old) => serde_json::json!({"op": "replace", "path": path, "value": old}),
+        None => serde_json::json!({"op": "remove", "path": path}),
+    }
+}
+
+define_function!(
+    JsonPatchInverseFn,
+    vec![ArgumentType::Array, ArgumentType::Any],
+    None
+);
+
+impl Function for JsonPatchInverseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let ops = match to_json(&args[0], ctx)? {
+            serde_json::Value::Array(ops) => ops,
+            _ => unreachable!("signature guarantees an array"),
+        };
+        let mut doc = to_json(&args[1], ctx)?;
+
+        let mut inverse: Vec<serde_json::Value> = Vec::new();
+        for (i, op) in ops.iter().enumerate() {
+            let path = op["path"].as_str().unwrap_or_default();
+            let from = op["from"].as_str().unwrap_or_default();
+            let mut undo = match op["op"].as_str() {
+                Some("add") | Some("copy") => vec![undo_insert(&doc, path)],
+                Some("remove") | Some("replace") => match doc.pointer(path) {
+                    Some(old) => {
+                        let kind = if op["op"] == "remove" {
+                            "add"
+                        } else {
+                            "replace"
+                        };
+                        vec![serde_json::json!({"op": kind, "path": path, "value": old})]
+                    }
+                    None => Vec::new(),
+                },
+                Some("move") => {
+                    // Move back, then restore whatever the move overwrote
+                    let (parent, _) = split_pointer(path);
+                    let landed = match doc.pointer(parent) {
+                        Some(serde_json::Value::Array(items)) if path.ends_with("/-") => {
+                            // The source is removed first, so the end index shrinks
+                            let len = items.len()
+                                - usize::from(from.starts_with(&format!("{}/", parent)));
+                            format!("{}/{}", parent, len)
+                        }
+                        _ => path.to_string(),
+                    };
+                    let mut ops =
+                        vec![serde_json::json!({"op": "move", "from": landed, "path": from})];
+                    if !matches!(doc.pointer(parent), Some(serde_json::Value::Array(_)))
+                        && let Some(old) = doc.pointer(path)
+                    {
+                        ops.push(serde_json::json!({"op": "add", "path": path, "value": old}));
+                    }
+                    ops
+                }
+                _ => Vec::new(),
+            };
+
+            // Step the working document forward so later operations see the right state
+            let single = parse_patch(serde_json::Value::Array(vec![op.clone()]), ctx)?;
+            json_patch::patch(&mut doc, &single)
+                .map_err(|e| patch_error(ctx, format!("Failed to apply operation {}: {}", i, e)))?;
+
+            undo.reverse();
+            inverse.extend(undo);
+        }
+        inverse.reverse();
+
+        from_json(&serde_json::Value::Array(inverse), ctx)
+    }
+}
+
+// =============================================================================
+// json_patch_test(doc, patch) -> object
+// Dry-run a patch. Returns {valid: true, ...} or the failing operation's
+// index, op, path and error.
+// =============================================================================
+
+define_function!(
+    JsonPatchTestFn,
+    vec![ArgumentType::Any, ArgumentType::Array],
+    None
+);
+
+impl Function for JsonPatchTestFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let mut doc = to_json(&args[0], ctx)?;
+        let patch_json = to_json(&args[1], ctx)?;
+        let patch = parse_patch(patch_json.clone(), ctx)?;
+
+        let report = match json_patch::patch(&mut doc, &patch) {
+            Ok(()) => serde_json::json!({
+                "valid": true,
+                "index": null,
+                "op": null,
+                "path": null,
+                "error": null,
+            }),
+            Err(e) => serde_json::json!({
+                "valid": false,
+                "index": e.operation,
+                "op": patch_json[e.operation]["op"],
+                "path": e.path.to_string(),
+                "error": e.kind.to_string(),
+            }),
+        };
+        from_json(&report, ctx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -375,4 +721,129 @@ mod tests {
         assert_eq!(obj.get("x").unwrap().as_number().unwrap() as i64, 2);
         assert_eq!(obj.get("y").unwrap().as_number().unwrap() as i64, 3);
     }
+
+    fn json_eval(runtime: &Runtime, expr: &str, data: &str) -> serde_json::Value {
+        let data = Variable::from_json(data).unwrap();
+        let result = runtime.compile(expr).unwrap().search(&data).unwrap();
+        serde_json::to_value(&*result).unwrap()
+    }
+
+    #[test]
+    fn test_json_patch_inverse_roundtrip() {
+        let runtime = setup_runtime();
+        let original = r#"{"a": 1, "b": {"c": [1, 2, 3]}, "d": "x", "e": true}"#;
+        let patch = r#"[
+            {"op": "add", "path": "/f", "value": 5},
+            {"op": "replace", "path": "/a", "value": 2},
+            {"op": "remove", "path": "/d"},
+            {"op": "add", "path": "/b/c/1", "value": 9},
+            {"op": "add", "path": "/b/c/-", "value": 7},
+            {"op": "move", "from": "/e", "path": "/a"},
+            {"op": "copy", "from": "/b", "path": "/g"},
+            {"op": "test", "path": "/f", "value": 5}
+        ]"#;
+        let data = format!(r#"{{"doc": {}, "patch": {}}}"#, original, patch);
+
+        let undone = json_eval(
+            &runtime,
+            "json_patch(json_patch(doc, patch), json_patch_inverse(patch, doc))",
+            &data,
+        );
+        let expected: serde_json::Value = serde_json::from_str(original).unwrap();
+        assert_eq!(undone, expected);
+    }
+
+    #[test]
+    fn test_json_patch_inverse_ops() {
+        let runtime = setup_runtime();
+        let data = r#"{"doc": {"a": 1}, "patch": [{"op": "replace", "path": "/a", "value": 2}, {"op": "add", "path": "/b", "value": 3}]}"#;
+        let inverse = json_eval(&runtime, "json_patch_inverse(patch, doc)", data);
+        assert_eq!(
+            inverse,
+            serde_json::json!([
+                {"op": "remove", "path": "/b"},
+                {"op": "replace", "path": "/a", "value": 1}
+            ])
+        );
+
+        let data = r#"{"doc": {"a": 1}, "patch": [{"op": "remove", "path": "/missing"}]}"#;
+        let expr = runtime.compile("json_patch_inverse(patch, doc)").unwrap();
+        assert!(expr.search(Variable::from_json(data).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_json_patch_test() {
+        let runtime = setup_runtime();
+        let data = r#"{"doc": {"a": 1}, "patch": [{"op": "add", "path": "/b", "value": 2}, {"op": "test", "path": "/a", "value": 5}]}"#;
+        let report = json_eval(&runtime, "json_patch_test(doc, patch)", data);
+        assert_eq!(report["valid"], false);
+        assert_eq!(report["index"], 1);
+        assert_eq!(report["op"], "test");
+        assert_eq!(report["path"], "/a");
+        assert_eq!(report["error"], "value did not match");
+
+        let data = r#"{"doc": {"a": 1}, "patch": [{"op": "remove", "path": "/a"}]}"#;
+        let report = json_eval(&runtime, "json_patch_test(doc, patch)", data);
+        assert_eq!(report["valid"], true);
+        assert_eq!(report["index"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_json_diff_moves_and_copies() {
+        let runtime = setup_runtime();
+        let data = r#"{"a": {"old": {"x": 1, "y": [1, 2]}, "keep": {"k": 1}}, "b": {"new": {"x": 1, "y": [1, 2]}, "keep": {"k": 1}, "dup": {"k": 1}}}"#;
+
+        let plain = json_eval(&runtime, "json_diff(a, b)", data);
+        assert!(
+            plain
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|op| op["op"] != "move")
+        );
+
+        let diff = json_eval(
+            &runtime,
+            "json_diff(a, b, `{\"moves\": true, \"copies\": true}`)",
+            data,
+        );
+        let ops = diff.as_array().unwrap();
+        assert!(ops.contains(&serde_json::json!({"op": "move", "from": "/old", "path": "/new"})));
+        assert!(ops.contains(&serde_json::json!({"op": "copy", "from": "/keep", "path": "/dup"})));
+        assert!(ops.iter().all(|op| op["op"] != "remove"));
+
+        // The rewritten patch still transforms a into b
+        let applied = json_eval(
+            &runtime,
+            "json_patch(a, json_diff(a, b, `{\"moves\": true, \"copies\": true}`))",
+            data,
+        );
+        assert_eq!(applied, json_eval(&runtime, "b", data));
+    }
+
+    #[test]
+    fn test_json_merge_diff() {
+        let runtime = setup_runtime();
+        let data = r#"{"a": {"name": "app", "replicas": 2, "labels": {"env": "dev", "tier": "web"}, "old": true}, "b": {"name": "app", "replicas": 3, "labels": {"env": "prod", "tier": "web"}, "ports": [80]}}"#;
+
+        let diff = json_eval(&runtime, "json_merge_diff(a, b)", data);
+        assert_eq!(
+            diff,
+            serde_json::json!({"old": null, "replicas": 3, "labels": {"env": "prod"}, "ports": [80]})
+        );
+
+        // Round-trips through json_merge_patch
+        let applied = json_eval(&runtime, "json_merge_patch(a, json_merge_diff(a, b))", data);
+        assert_eq!(applied, json_eval(&runtime, "b", data));
+
+        // Identical documents produce an empty patch; non-objects are replaced wholesale
+        assert_eq!(
+            json_eval(&runtime, "json_merge_diff(a, a)", data),
+            serde_json::json!({})
+        );
+        assert_eq!(
+            json_eval(&runtime, "json_merge_diff(a.name, b.replicas)", data),
+            serde_json::json!(3)
+        );
+    }
 }
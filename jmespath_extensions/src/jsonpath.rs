@@ -0,0 +1,156 @@
+//! JSONPath (RFC 9535) query functions.
+//!
+//! This module provides jsonpath functions for JMESPath queries, so selectors
+//! written for other tools can be evaluated from inside a JMESPath expression.
+//!
+//! For complete function reference with signatures and examples, see the
+//! [`functions`](crate::functions) module documentation or use `jpx --list-category jsonpath`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jmespath::{Runtime, Variable};
+//! use jmespath_extensions::jsonpath;
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! jsonpath::register(&mut runtime);
+//!
+//! let expr = runtime.compile("jsonpath(@, '$.books[?@.price < 10].title')").unwrap();
+//! let data = Variable::from_json(r#"{"books": [{"title": "A", "price": 8}, {"title": "B", "price": 12}]}"#).unwrap();
+//! let result = expr.search(&data).unwrap();
+//! assert_eq!(result.as_array().unwrap().len(), 1);
+//! ```
+
+use crate::common::Rc;
+
+use crate::common::{ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable};
+use crate::define_function;
+use jmespath::Runtime;
+use serde_json_path::JsonPath;
+
+/// Register all JSONPath functions with the runtime.
+pub fn register(runtime: &mut Runtime) {
+    runtime.register_function("jsonpath", Box::new(JsonPathFn::new()));
+}
+
+// =============================================================================
+// jsonpath(value, path) -> array (RFC 9535)
+// Evaluate a JSONPath query and return every matched node in document order.
+// See: https://datatracker.ietf.org/doc/html/rfc9535
+// =============================================================================
+
+define_function!(
+    JsonPathFn,
+    vec![ArgumentType::Any, ArgumentType::String],
+    None
+);
+
+impl Function for JsonPathFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let path_str = args[1].as_string().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse("Expected string JSONPath argument".to_owned()),
+            )
+        })?;
+
+        let path = JsonPath::parse(path_str).map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!("Invalid JSONPath '{}': {}", path_str, e)),
+            )
+        })?;
+
+        let value: serde_json::Value = serde_json::to_value(&*args[0]).map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                ctx.offset,
+                ErrorReason::Parse(format!("Failed to convert value: {}", e)),
+            )
+        })?;
+
+        let matches = path
+            .query(&value)
+            .all()
+            .into_iter()
+            .map(|node| {
+                Variable::try_from(node.clone()).map(Rc::new).map_err(|e| {
+                    JmespathError::new(
+                        ctx.expression,
+                        ctx.offset,
+                        ErrorReason::Parse(format!("Failed to convert result: {}", e)),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Rc::new(Variable::Array(matches)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    fn store() -> Variable {
+        Variable::from_json(
+            r#"{"store": {"book": [
+                {"title": "Sayings", "price": 8.95},
+                {"title": "Sword", "price": 12.99},
+                {"title": "Moby Dick", "price": 8.99}
+            ], "bicycle": {"price": 19.95}}}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_jsonpath_filter() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("jsonpath(@, '$.store.book[?(@.price<10)].title')")
+            .unwrap();
+        let result = expr.search(store()).unwrap();
+        let titles: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(titles, vec!["Sayings", "Moby Dick"]);
+    }
+
+    #[test]
+    fn test_jsonpath_descendant() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("jsonpath(@, '$..price')").unwrap();
+        let result = expr.search(store()).unwrap();
+        assert_eq!(result.as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_jsonpath_no_match() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("jsonpath(@, '$.missing')").unwrap();
+        let result = expr.search(store()).unwrap();
+        assert!(result.as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_jsonpath_invalid() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("jsonpath(@, 'store.book')").unwrap();
+        assert!(expr.search(store()).is_err());
+    }
+}
@@ -128,6 +128,23 @@
 //! # fn main() {}
 //! ```
 //!
+//! ## Shared Runtime
+//!
+//! For services that evaluate many queries, the `global` feature provides a
+//! runtime that is built once and shared across threads:
+//!
+//! ```rust
+//! # #[cfg(feature = "global")]
+//! # fn main() {
+//! use jmespath_extensions::search;
+//!
+//! let result = search("sum(@)", "[1, 2, 3]").unwrap();
+//! assert_eq!(result.as_number().unwrap(), 6.0);
+//! # }
+//! # #[cfg(not(feature = "global"))]
+//! # fn main() {}
+//! ```
+//!
 //! ## Feature Flags
 //!
 //! This crate uses feature flags to control which functions are included.
@@ -158,13 +175,23 @@
 //! | `geo` | geoutils | [Geospatial functions](geo/index.html) |
 //! | `semver` | semver | [Semantic versioning](semver_fns/index.html) |
 //! | `network` | ipnetwork | [Network/IP functions](network/index.html) |
-//! | `ids` | nanoid, ulid | [ID generation](ids/index.html) |
+//! | `ids` | nanoid, ulid, rand, sha3 | [ID generation](ids/index.html) |
 //! | `text` | none | [Text analysis](text/index.html) |
-//! | `duration` | none | [Duration parsing](duration/index.html) |
+//! | `duration` | chrono | [Duration parsing](duration/index.html) |
 //! | `color` | none | [Color manipulation](color/index.html) |
-//! | `computing` | none | [Computing utilities](computing/index.html) |
+//! | `computing` | base64, hex | [Computing utilities](computing/index.html) |
 //! | `jsonpatch` | json-patch | [JSON Patch functions](jsonpatch/index.html) |
+//! | `jsonpath` | serde_json_path | [JSONPath queries](jsonpath/index.html) |
+//! | `jsonlogic` | none | [JsonLogic rule evaluation](jsonlogic/index.html) |
+//! | `decimal` | rust_decimal | [Decimal arithmetic](decimal/index.html) |
+//! | `domain` | publicsuffix | [Domain name parsing](domain/index.html) |
+//! | `phone` | phonenumber | [Phone number parsing](phone/index.html) |
+//! | `language` | whatlang, isolang | Language detection (`detect_language` in [text](text/index.html)) |
 //! | `multi-match` | aho-corasick | [Multi-pattern matching](multi_match/index.html) |
+//! | `global` | none | [Shared lazily-initialized runtime](global/index.html) (enables `sync`) |
+//! | `sync` | none | `Rcvar` is `Arc<Variable>`, so runtimes, compiled expressions and results are `Send + Sync` (opt-in, enabled by `global`) |
+//! | `parallel` | rayon | `parallel_map_expr` in [expression](expression/index.html) (opt-in, enables `sync`) |
+//! | `plugins` | wasmi | [Load functions from WebAssembly modules](plugins/index.html) (opt-in) |
 //!
 //! ### Using Specific Features
 //!
@@ -204,10 +231,15 @@
 //! - [`network`] - Network/IP (`ip_to_int`, `int_to_ip`, `cidr_contains`, `cidr_network`, `is_private_ip`)
 //! - [`ids`] - ID generation (`nanoid`, `ulid`, `ulid_timestamp`)
 //! - [`text`] - Text analysis (`word_count`, `char_count`, `reading_time`, `word_frequencies`)
-//! - [`duration`] - Duration parsing (`parse_duration`, `format_duration`)
-//! - [`color`] - Color manipulation (`hex_to_rgb`, `rgb_to_hex`, `lighten`, `darken`, `color_mix`)
+//! - [`duration`] - Duration parsing (`parse_duration`, `format_duration`, `parse_iso_duration`)
+//! - [`color`] - Color manipulation (`hex_to_rgb`, `rgb_to_hex`, `rgb_to_oklch`, `lighten`, `darken`, `color_mix`)
 //! - [`computing`] - Computing utilities (`parse_bytes`, `format_bytes`, `bit_and`, `bit_or`, `bit_xor`)
 //! - [`jsonpatch`] - JSON Patch (RFC 6902) and Merge Patch (RFC 7396) (`json_patch`, `json_merge_patch`, `json_diff`)
+//! - [`jsonpath`] - JSONPath (RFC 9535) queries (`jsonpath`)
+//! - [`jsonlogic`] - JsonLogic rule evaluation (`jsonlogic`)
+//! - [`decimal`] - Decimal arithmetic (`dec_add`, `dec_sub`, `dec_mul`, `dec_div`, `dec_round`)
+//! - [`domain`] - Domain names via the public suffix list (`domain_parse`)
+//! - [`phone`] - Phone number parsing and E.164 formatting (`parse_phone`, `format_phone`)
 //!
 #![doc = include_str!(concat!(env!("OUT_DIR"), "/quick_reference.md"))]
 //!
@@ -253,6 +285,9 @@ pub mod common;
 // Function registry for runtime control
 pub mod registry;
 
+// Resource limits for untrusted queries
+pub mod limits;
+
 /// Complete function reference - auto-generated from `functions.toml`
 #[doc = include_str!(concat!(env!("OUT_DIR"), "/function_docs.md"))]
 pub mod functions {}
@@ -341,12 +376,41 @@ pub mod computing;
 #[cfg(feature = "jsonpatch")]
 pub mod jsonpatch;
 
+#[cfg(feature = "jsonpath")]
+pub mod jsonpath;
+
+#[cfg(feature = "jsonlogic")]
+pub mod jsonlogic;
+
+#[cfg(feature = "decimal")]
+pub mod decimal;
+
+#[cfg(feature = "domain")]
+pub mod domain;
+
+#[cfg(feature = "phone")]
+pub mod phone;
+
 #[cfg(feature = "multi-match")]
 pub mod multi_match;
 
 #[cfg(feature = "format")]
 pub mod format;
 
+// Shared runtime helpers
+#[cfg(feature = "global")]
+pub mod global;
+
+#[cfg(feature = "global")]
+pub use global::{global_runtime, search, search_variable};
+
+// WebAssembly plugins
+#[cfg(feature = "plugins")]
+pub mod plugins;
+
+pub use limits::Limits;
+pub use registry::{Extensions, ExtensionsBuilder};
+
 /// Register all available extension functions with a JMESPath runtime.
 ///
 /// This function registers all functions enabled by the current feature flags.
@@ -457,6 +521,19 @@ pub fn register_all(runtime: &mut Runtime) {
     #[cfg(feature = "jsonpatch")]
     jsonpatch::register(runtime);
 
+    #[cfg(feature = "jsonpath")]
+    jsonpath::register(runtime);
+
+    #[cfg(feature = "jsonlogic")]
+    jsonlogic::register(runtime);
+
+    #[cfg(feature = "decimal")]
+    decimal::register(runtime);
+    #[cfg(feature = "domain")]
+    domain::register(runtime);
+    #[cfg(feature = "phone")]
+    phone::register(runtime);
+
     #[cfg(feature = "multi-match")]
     multi_match::register(runtime);
 
@@ -464,6 +541,225 @@ pub fn register_all(runtime: &mut Runtime) {
     format::register(runtime);
 }
 
+/// Re-register the random functions with a single generator seeded from `seed`.
+///
+/// Call this after [`register_all`] to make `random`, `shuffle`, `sample`,
+/// the `random_*` distributions, `uuid` and `nanoid` reproducible: the same
+/// expressions evaluated in the same order produce the same values. All of
+/// these functions draw from one shared generator.
+///
+/// Only functions already registered on `runtime` under their own names are
+/// replaced; denied functions stay absent. For a prefixed runtime, seed it
+/// with [`ExtensionsBuilder::random_seed`] instead.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath::{Runtime, Variable};
+/// use jmespath_extensions::{register_all, register_random_with_seed};
+///
+/// # #[cfg(feature = "rand")]
+/// # {
+/// let draw = || {
+///     let mut runtime = Runtime::new();
+///     runtime.register_builtin_functions();
+///     register_all(&mut runtime);
+///     register_random_with_seed(&mut runtime, 42);
+///     let expr = runtime.compile("[random(), shuffle(`[1, 2, 3, 4]`)]").unwrap();
+///     expr.search(Variable::from_json("{}").unwrap()).unwrap()
+/// };
+/// let first = draw();
+/// assert!(!first.is_null());
+/// assert_eq!(first, draw());
+/// # }
+/// ```
+#[cfg(any(feature = "rand", feature = "ids"))]
+pub fn register_random_with_seed(runtime: &mut Runtime, seed: u64) {
+    let source = common::RngSource::seeded(seed);
+    let mut seeded = Runtime::new();
+
+    #[cfg(feature = "ids")]
+    seeded.register_function(
+        "nanoid",
+        Box::new(ids::NanoidFn::with_source(source.clone())),
+    );
+    #[cfg(feature = "rand")]
+    random::register_with_source(&mut seeded, source);
+
+    for name in registry::names_registered_on(&seeded) {
+        if runtime.get_function(name).is_none() {
+            continue;
+        }
+        if let Some(function) = seeded.deregister_function(name) {
+            runtime.register_function(name, function);
+        }
+    }
+}
+
+/// Register only the functions documented under `category`.
+///
+/// Lets embedders expose a vetted subset of functions instead of everything
+/// [`register_all`] provides. [`Category::Standard`](registry::Category::Standard)
+/// registers the builtin JMESPath functions. Categories whose feature is not
+/// enabled register nothing.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath::Runtime;
+/// use jmespath_extensions::register_category;
+/// use jmespath_extensions::registry::Category;
+///
+/// let mut runtime = Runtime::new();
+/// register_category(&mut runtime, Category::Standard);
+/// # #[cfg(feature = "string")]
+/// register_category(&mut runtime, Category::String);
+///
+/// # #[cfg(feature = "string")]
+/// assert!(runtime.get_function("upper").is_some());
+/// # #[cfg(feature = "hash")]
+/// assert!(runtime.get_function("md5").is_none());
+/// ```
+pub fn register_category(runtime: &mut Runtime, category: registry::Category) {
+    if category == registry::Category::Standard {
+        runtime.register_builtin_functions();
+        return;
+    }
+    if !category.is_available() {
+        return;
+    }
+
+    let mut scratch = Runtime::new();
+    registry::register_category_module(&mut scratch, category);
+    for name in registry::names_registered_on(&scratch) {
+        // Undocumented names (legacy spellings) follow their module
+        if registry::category_of(name).is_some_and(|c| c != category) {
+            continue;
+        }
+        if let Some(function) = scratch.deregister_function(name) {
+            runtime.register_function(name, function);
+        }
+    }
+}
+
+/// Register only the named functions.
+///
+/// Names may be builtin functions, extension functions or aliases. Where an
+/// extension replaces a builtin of the same name (as [`register_all`] does),
+/// the extension is registered.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath::Runtime;
+/// use jmespath_extensions::register_functions;
+///
+/// let mut runtime = Runtime::new();
+/// register_functions(&mut runtime, &["length"]).unwrap();
+/// # #[cfg(feature = "string")]
+/// register_functions(&mut runtime, &["upper"]).unwrap();
+/// assert!(runtime.get_function("length").is_some());
+/// assert!(runtime.get_function("lower").is_none());
+///
+/// assert!(register_functions(&mut runtime, &["no_such_function"]).is_err());
+/// ```
+///
+/// # Errors
+///
+/// Returns every name that is not provided by the builtins or the enabled
+/// features, and leaves the runtime unchanged.
+pub fn register_functions(
+    runtime: &mut Runtime,
+    names: &[&str],
+) -> Result<(), registry::UnknownFunctionsError> {
+    let mut scratch = Runtime::new();
+    scratch.register_builtin_functions();
+    register_all(&mut scratch);
+
+    let unknown: Vec<String> = names
+        .iter()
+        .filter(|name| scratch.get_function(name).is_none())
+        .map(|name| name.to_string())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(registry::UnknownFunctionsError { names: unknown });
+    }
+
+    for name in names {
+        if let Some(function) = scratch.deregister_function(name) {
+            runtime.register_function(name, function);
+        }
+    }
+    Ok(())
+}
+
+/// Register all available extension functions and report what was installed.
+///
+/// Unlike [`register_all`], this inspects the runtime first so embedders that
+/// compose several function sources can see which names were newly added,
+/// which replaced an existing function, and which were left alone.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath::Runtime;
+/// use jmespath_extensions::register_all_checked;
+/// use jmespath_extensions::registry::OnConflict;
+///
+/// let mut runtime = Runtime::new();
+/// runtime.register_builtin_functions();
+///
+/// // Keep any function that is already registered (including builtins)
+/// let report = register_all_checked(&mut runtime, OnConflict::Skip).unwrap();
+/// println!("installed {} functions", report.registered.len());
+/// # #[cfg(feature = "type")]
+/// assert!(report.skipped.contains(&"to_string"));
+/// ```
+///
+/// # Errors
+///
+/// With [`OnConflict::Error`](registry::OnConflict::Error), returns the conflicting names and leaves the
+/// runtime unchanged if any extension name is already registered.
+pub fn register_all_checked(
+    runtime: &mut Runtime,
+    on_conflict: registry::OnConflict,
+) -> Result<registry::RegistrationReport, registry::RegistrationError> {
+    use registry::{OnConflict, RegistrationError, RegistrationReport};
+
+    let mut report = RegistrationReport::default();
+    let mut conflicts = Vec::new();
+    for name in registry::provided_names() {
+        if runtime.get_function(name).is_some() {
+            conflicts.push(name);
+        } else {
+            report.registered.push(name);
+        }
+    }
+
+    match on_conflict {
+        OnConflict::Error if !conflicts.is_empty() => {
+            return Err(RegistrationError { conflicts });
+        }
+        OnConflict::Skip => {
+            let existing: Vec<_> = conflicts
+                .iter()
+                .filter_map(|name| runtime.deregister_function(name).map(|f| (*name, f)))
+                .collect();
+            register_all(runtime);
+            for (name, function) in existing {
+                runtime.register_function(name, function);
+            }
+            report.skipped = conflicts;
+        }
+        _ => {
+            register_all(runtime);
+            report.overridden = conflicts;
+        }
+    }
+
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -483,4 +779,269 @@ mod tests {
             assert_eq!(result.as_string().unwrap(), "HELLO");
         }
     }
+
+    #[cfg(feature = "string")]
+    #[test]
+    fn test_register_all_checked_fresh_runtime() {
+        let mut runtime = Runtime::new();
+        let report = register_all_checked(&mut runtime, registry::OnConflict::Error).unwrap();
+        assert!(!report.registered.is_empty());
+        assert!(report.skipped.is_empty());
+        assert!(report.overridden.is_empty());
+        for name in &report.registered {
+            assert!(runtime.get_function(name).is_some(), "{} missing", name);
+        }
+    }
+
+    // `env` and `parallel` gate individual functions inside their categories
+    #[cfg(all(feature = "env", feature = "parallel"))]
+    #[test]
+    fn test_documented_names_are_registered() {
+        let mut runtime = Runtime::new();
+        register_all(&mut runtime);
+        let mut registry = registry::FunctionRegistry::new();
+        registry.register_all();
+        let mut missing = Vec::new();
+        for info in registry.functions().filter(|info| !info.is_standard) {
+            for name in std::iter::once(&info.name).chain(info.aliases) {
+                if runtime.get_function(name).is_none() {
+                    missing.push(*name);
+                }
+            }
+        }
+        assert!(
+            missing.is_empty(),
+            "documented but not registered: {:?}",
+            missing
+        );
+    }
+
+    #[cfg(feature = "type")]
+    #[test]
+    fn test_register_all_checked_conflicts() {
+        use registry::OnConflict;
+
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        let err = register_all_checked(&mut runtime, OnConflict::Error).unwrap_err();
+        assert!(err.conflicts.contains(&"to_string"));
+        // Runtime untouched: extension-only functions are not installed
+        assert!(runtime.get_function("type_of").is_none());
+
+        let report = register_all_checked(&mut runtime, OnConflict::Override).unwrap();
+        assert!(report.overridden.contains(&"to_string"));
+        assert!(report.registered.contains(&"type_of"));
+    }
+
+    #[cfg(feature = "string")]
+    #[test]
+    fn test_register_all_checked_skip_keeps_existing() {
+        use registry::OnConflict;
+
+        let mut runtime = Runtime::new();
+        runtime.register_function(
+            "upper",
+            Box::new(|_: &[Rcvar], _: &mut Context<'_>| {
+                Ok(common::rcvar(Variable::String("custom".to_string())))
+            }),
+        );
+        let report = register_all_checked(&mut runtime, OnConflict::Skip).unwrap();
+        assert_eq!(report.skipped, vec!["upper"]);
+
+        let expr = runtime.compile("upper('x')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "custom");
+    }
+
+    define_extension_fn! {
+        /// Join strings with a dash.
+        struct DashJoinFn {
+            name: "dash_join",
+            args: [ArgumentType::String],
+            variadic: Some(ArgumentType::String),
+            signature: "string, ...string -> string",
+            description: "Join strings with a dash",
+            example: "dash_join('a', 'b') -> 'a-b'",
+        }
+        fn evaluate(args, _ctx) {
+            let parts: Vec<&str> = args.iter().filter_map(|a| a.as_string().map(String::as_str)).collect();
+            Ok(common::rcvar(Variable::String(parts.join("-"))))
+        }
+    }
+
+    #[test]
+    fn test_define_extension_fn() {
+        use registry::{Category, FunctionRegistry};
+
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        DashJoinFn::register(&mut runtime);
+
+        let expr = runtime.compile("dash_join('a', 'b', 'c')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "a-b-c");
+
+        // Signature is validated before the body runs
+        let expr = runtime.compile("dash_join(`1`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+
+        let mut registry = FunctionRegistry::new();
+        registry.register_all();
+        registry.register_custom(DashJoinFn::info());
+        let info = registry.get_function(DashJoinFn::NAME).unwrap();
+        assert_eq!(info.category, Category::Custom);
+        assert!(!info.is_standard);
+        assert_eq!(
+            registry
+                .functions_in_category(Category::Custom)
+                .map(|f| f.name)
+                .collect::<Vec<_>>(),
+            vec!["dash_join"]
+        );
+    }
+
+    #[cfg(all(feature = "rand", feature = "uuid"))]
+    #[test]
+    fn test_register_category_is_exact() {
+        use registry::Category;
+
+        let mut runtime = Runtime::new();
+        register_category(&mut runtime, Category::Uuid);
+        assert!(runtime.get_function("uuid").is_some());
+        // Same module, different category
+        assert!(runtime.get_function("random").is_none());
+        assert!(runtime.get_function("length").is_none());
+    }
+
+    #[cfg(feature = "expression")]
+    #[test]
+    fn test_register_functions_aliases() {
+        let mut runtime = Runtime::new();
+        register_functions(&mut runtime, &["chunk_by_expr", "map_expr"]).unwrap();
+        assert!(runtime.get_function("chunk_by_expr").is_some());
+        assert!(runtime.get_function("chunk_by").is_none());
+
+        let err = register_functions(&mut runtime, &["nope", "filter_expr", "nada"]).unwrap_err();
+        assert_eq!(err.names, vec!["nope", "nada"]);
+        assert!(runtime.get_function("filter_expr").is_none());
+    }
+
+    #[cfg(all(feature = "expression", feature = "string"))]
+    #[test]
+    fn test_extensions_builder() {
+        use registry::Category;
+
+        let mut runtime = Runtime::new();
+        let installed = Extensions::builder()
+            .allow(Category::Expression)
+            .allow(Category::String)
+            .deny("chunk_by")
+            .deny("lower")
+            .prefix("ext_")
+            .apply(&mut runtime)
+            .unwrap();
+
+        assert!(installed.contains(&"ext_upper".to_string()));
+        assert!(runtime.get_function("ext_map_expr").is_some());
+        assert!(runtime.get_function("map_expr").is_none());
+        assert!(runtime.get_function("ext_lower").is_none());
+        // Denying the canonical name also drops its aliases
+        assert!(runtime.get_function("ext_chunk_by").is_none());
+        assert!(runtime.get_function("ext_chunk_by_expr").is_none());
+        // Categories that were not allowed are left out
+        assert!(
+            !installed
+                .iter()
+                .any(|n| n == "ext_abs_diff" || n == "ext_md5")
+        );
+    }
+
+    #[test]
+    fn test_extensions_builder_rejects_unknown_deny() {
+        let mut runtime = Runtime::new();
+        let err = Extensions::builder()
+            .deny("uppr")
+            .deny("no_such_fn")
+            .deny("upper")
+            .apply(&mut runtime)
+            .unwrap_err();
+        assert_eq!(err.names, vec!["no_such_fn", "uppr"]);
+        assert!(runtime.get_function("upper").is_none());
+        assert!(runtime.get_function("length").is_none());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_extensions_builder_deny_category() {
+        use registry::Category;
+
+        let mut runtime = Runtime::new();
+        Extensions::builder()
+            .deny_category(Category::Rand)
+            .apply(&mut runtime)
+            .unwrap();
+        assert!(runtime.get_function("random").is_none());
+        assert!(runtime.get_function("shuffle").is_none());
+        #[cfg(feature = "string")]
+        assert!(runtime.get_function("upper").is_some());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_register_random_with_seed_keeps_registered_names() {
+        let mut runtime = Runtime::new();
+        Extensions::builder()
+            .deny("random")
+            .apply(&mut runtime)
+            .unwrap();
+        register_random_with_seed(&mut runtime, 7);
+        assert!(runtime.get_function("random").is_none());
+        assert!(runtime.get_function("shuffle").is_some());
+
+        let draw = |seed| {
+            let mut runtime = Runtime::new();
+            Extensions::builder()
+                .prefix("ext_")
+                .random_seed(seed)
+                .apply(&mut runtime)
+                .unwrap();
+            assert!(runtime.get_function("shuffle").is_none());
+            let expr = runtime
+                .compile("ext_shuffle(`[1, 2, 3, 4, 5, 6]`)")
+                .unwrap();
+            expr.search(Variable::Null).unwrap()
+        };
+        assert_eq!(draw(7), draw(7));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_sync_shares_runtime_and_values_across_threads() {
+        use std::sync::Arc;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Runtime>();
+        assert_send_sync::<Rcvar>();
+        assert_send_sync::<jmespath::Expression<'static>>();
+
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register_all(&mut runtime);
+        let runtime = Arc::new(runtime);
+        let data: Rcvar = Arc::new(Variable::from_json(r#"["a", "b", "c"]"#).unwrap());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let runtime = Arc::clone(&runtime);
+                let data = Arc::clone(&data);
+                std::thread::spawn(move || {
+                    let expr = runtime.compile("length(@)").unwrap();
+                    expr.search(data).unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().as_number().unwrap(), 3.0);
+        }
+    }
 }
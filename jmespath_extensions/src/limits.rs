@@ -0,0 +1,479 @@
+//! Resource limits for evaluating untrusted queries.
+//!
+//! A JMESPath query supplied by a user can ask for a lot of work: a huge
+//! `range()`, a `cartesian()` of two large arrays, a `walk()` over a deeply
+//! nested document, or a result far larger than the input. [`Limits`] bounds
+//! these so a host application can evaluate queries it did not write.
+//!
+//! Limits are active for the duration of a [`search_with_limits`] call on the
+//! current thread, and on the workers `parallel_map_expr` hands elements to:
+//!
+//! - `max_array_len` is checked before allocating by the functions whose
+//!   output size follows from their arguments (`range`, `cartesian`,
+//!   `rle_decode`, `resample`, `cidr_hosts`, `cidr_split`), and `max_depth`
+//!   by the functions that recurse through their input (`walk`,
+//!   `deep_merge`).
+//! - `max_eval_time` is checked on every function call once the runtime has
+//!   been prepared with [`install`], which also applies `max_array_len` to
+//!   every array a function returns. Functions that evaluate an expression
+//!   per element also check it before each element.
+//! - `max_result_bytes` is checked against the serialized final result, and
+//!   by `repeat`, the padding functions and `bar` before building a string.
+//!
+//! # Example
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use jmespath::{Runtime, Variable};
+//! use jmespath_extensions::limits::{self, Limits};
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! jmespath_extensions::register_all(&mut runtime);
+//! limits::install(&mut runtime);
+//!
+//! let limits = Limits::new()
+//!     .max_eval_time(Duration::from_millis(100))
+//!     .max_array_len(1_000)
+//!     .max_result_bytes(64 * 1024);
+//!
+//! let data = Variable::from_json("[1, 2, 3]").unwrap();
+//! let expr = runtime.compile("length(@)").unwrap();
+//! let result = limits::search_with_limits(&expr, &data, &limits).unwrap();
+//! assert_eq!(result.as_number(), Some(3.0));
+//!
+//! # #[cfg(feature = "array")]
+//! # {
+//! let expr = runtime.compile("range(`0`, `5000`)").unwrap();
+//! assert!(limits::search_with_limits(&expr, &data, &limits).is_err());
+//! # }
+//! ```
+
+use std::cell::RefCell;
+use std::io;
+use std::time::{Duration, Instant};
+
+use jmespath::{Expression, ToJmespath};
+
+#[cfg(any(feature = "object", feature = "expression"))]
+use crate::common::Variable;
+use crate::common::{Context, ErrorReason, Function, JmespathError, Rcvar, Runtime};
+
+/// Bounds applied while evaluating a query with [`search_with_limits`].
+///
+/// Every limit is optional; [`Limits::new`] imposes none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_eval_time: Option<Duration>,
+    max_depth: Option<usize>,
+    max_result_bytes: Option<usize>,
+    max_array_len: Option<usize>,
+}
+
+impl Limits {
+    /// Create a set of limits with nothing bounded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail once evaluation has run for longer than `duration`.
+    pub fn max_eval_time(mut self, duration: Duration) -> Self {
+        self.max_eval_time = Some(duration);
+        self
+    }
+
+    /// Fail when a recursive function would descend more than `depth` levels.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Fail when the serialized result is larger than `bytes`.
+    pub fn max_result_bytes(mut self, bytes: usize) -> Self {
+        self.max_result_bytes = Some(bytes);
+        self
+    }
+
+    /// Fail when a function would produce an array longer than `len`.
+    pub fn max_array_len(mut self, len: usize) -> Self {
+        self.max_array_len = Some(len);
+        self
+    }
+}
+
+#[derive(Clone)]
+struct ActiveLimits {
+    limits: Limits,
+    deadline: Option<Instant>,
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<ActiveLimits>> = const { RefCell::new(None) };
+}
+
+/// Restores the previously active limits when a search finishes.
+struct ActiveGuard(Option<ActiveLimits>);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        ACTIVE.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+/// The limits active on the calling thread, captured so that work handed to
+/// other threads runs under the same limits and deadline.
+#[cfg(feature = "parallel")]
+#[derive(Clone)]
+pub(crate) struct ActiveSnapshot(Option<ActiveLimits>);
+
+#[cfg(feature = "parallel")]
+impl ActiveSnapshot {
+    /// Capture the limits active on the current thread.
+    pub(crate) fn capture() -> Self {
+        Self(ACTIVE.with(|a| a.borrow().clone()))
+    }
+
+    /// Make the captured limits active on the current thread until the
+    /// returned guard is dropped.
+    pub(crate) fn enter(&self) -> impl Drop {
+        ActiveGuard(ACTIVE.with(|a| a.replace(self.0.clone())))
+    }
+}
+
+/// Evaluate `expression` against `data` with `limits` enforced.
+///
+/// # Errors
+///
+/// Returns the evaluation error, or an error describing the first limit
+/// that was exceeded.
+pub fn search_with_limits<T: ToJmespath>(
+    expression: &Expression<'_>,
+    data: T,
+    limits: &Limits,
+) -> Result<Rcvar, JmespathError> {
+    let active = ActiveLimits {
+        limits: limits.clone(),
+        deadline: limits.max_eval_time.map(|d| Instant::now() + d),
+    };
+    let _guard = ActiveGuard(ACTIVE.with(|a| a.borrow_mut().replace(active)));
+
+    let result = expression.search(data)?;
+
+    let limit_error =
+        |message: String| JmespathError::new(expression.as_str(), 0, ErrorReason::Parse(message));
+    if let Some(max) = limits.max_eval_time {
+        if ACTIVE.with(|a| a.borrow().as_ref().and_then(|a| a.deadline)) < Some(Instant::now()) {
+            return Err(limit_error(format!(
+                "Limit exceeded: evaluation took longer than {:?}",
+                max
+            )));
+        }
+    }
+    if let Some(max) = limits.max_result_bytes {
+        let mut counter = ByteCounter { written: 0, max };
+        if serde_json::to_writer(&mut counter, &*result).is_err() {
+            return Err(limit_error(format!(
+                "Limit exceeded: result is larger than {} bytes",
+                max
+            )));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Writer that counts bytes and fails as soon as `max` is exceeded, so an
+/// oversized result is never fully serialized.
+struct ByteCounter {
+    written: usize,
+    max: usize,
+}
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.written += buf.len();
+        if self.written > self.max {
+            return Err(io::Error::other("result too large"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn limit_exceeded(ctx: &Context<'_>, message: String) -> JmespathError {
+    JmespathError::new(
+        ctx.expression,
+        ctx.offset,
+        ErrorReason::Parse(format!("Limit exceeded: {}", message)),
+    )
+}
+
+/// The configured `max_eval_time`, if the active evaluation has passed its
+/// deadline.
+fn expired_eval_time() -> Option<Duration> {
+    ACTIVE.with(|active| {
+        active
+            .borrow()
+            .as_ref()
+            .and_then(|a| a.deadline.map(|d| (d, a.limits.max_eval_time)))
+            .filter(|(deadline, _)| Instant::now() > *deadline)
+            .and_then(|(_, max)| max)
+    })
+}
+
+/// Fail if the active evaluation has passed its deadline.
+pub(crate) fn check_deadline(ctx: &Context<'_>) -> Result<(), JmespathError> {
+    match expired_eval_time() {
+        Some(max) => Err(limit_exceeded(
+            ctx,
+            format!("evaluation took longer than {:?}", max),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fail if the active evaluation has passed its deadline, before a function
+/// evaluates `expression` against another element.
+///
+/// Functions that apply an expression per element call this from their
+/// loop, so a slow query stops mid-array rather than after it.
+#[cfg(any(feature = "expression", feature = "rand"))]
+pub(crate) fn check_nested_deadline(expression: &Expression<'_>) -> Result<(), JmespathError> {
+    match expired_eval_time() {
+        Some(max) => Err(JmespathError::new(
+            expression.as_str(),
+            0,
+            ErrorReason::Parse(format!(
+                "Limit exceeded: evaluation took longer than {:?}",
+                max
+            )),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fail if an array of `len` elements would exceed the active `max_array_len`.
+pub(crate) fn check_array_len(ctx: &Context<'_>, len: usize) -> Result<(), JmespathError> {
+    let max = ACTIVE.with(|a| a.borrow().as_ref().and_then(|a| a.limits.max_array_len));
+    match max {
+        Some(max) if len > max => Err(limit_exceeded(
+            ctx,
+            format!("array of {} elements exceeds {}", len, max),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fail if a string of `len` bytes would exceed the active `max_result_bytes`.
+#[cfg(any(feature = "string", feature = "format"))]
+pub(crate) fn check_string_len(ctx: &Context<'_>, len: usize) -> Result<(), JmespathError> {
+    let max = ACTIVE.with(|a| a.borrow().as_ref().and_then(|a| a.limits.max_result_bytes));
+    match max {
+        Some(max) if len > max => Err(limit_exceeded(
+            ctx,
+            format!("string of {} bytes exceeds {}", len, max),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fail if any of `values` is nested deeper than the active `max_depth`.
+///
+/// Recursive functions descend once per level of nesting in their input, so
+/// checking the input up front bounds the recursion without threading a
+/// counter through every helper.
+#[cfg(any(feature = "object", feature = "expression"))]
+pub(crate) fn check_depth(ctx: &Context<'_>, values: &[Rcvar]) -> Result<(), JmespathError> {
+    let Some(max) = ACTIVE.with(|a| a.borrow().as_ref().and_then(|a| a.limits.max_depth)) else {
+        return Ok(());
+    };
+    if values.iter().any(|v| exceeds_depth(v, max)) {
+        return Err(limit_exceeded(
+            ctx,
+            format!("input is nested deeper than {} levels", max),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(any(feature = "object", feature = "expression"))]
+fn exceeds_depth(value: &Variable, remaining: usize) -> bool {
+    match value {
+        Variable::Array(items) => {
+            remaining == 0 || items.iter().any(|v| exceeds_depth(v, remaining - 1))
+        }
+        Variable::Object(fields) => {
+            remaining == 0 || fields.values().any(|v| exceeds_depth(v, remaining - 1))
+        }
+        _ => false,
+    }
+}
+
+/// Wraps a function so every call checks the deadline and the length of any
+/// array it returns.
+struct LimitedFn {
+    inner: Box<dyn Function>,
+}
+
+impl Function for LimitedFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        check_deadline(ctx)?;
+        let result = self.inner.evaluate(args, ctx)?;
+        if let Some(items) = result.as_array() {
+            check_array_len(ctx, items.len())?;
+        }
+        Ok(result)
+    }
+}
+
+/// Wrap every builtin and extension function registered on `runtime` so that
+/// limits are checked on each call.
+///
+/// Outside [`search_with_limits`] the wrappers only cost a thread-local
+/// lookup. Functions registered after this call, and custom functions not
+/// provided by this crate, are not wrapped.
+pub fn install(runtime: &mut Runtime) {
+    for name in crate::registry::names_registered_on(runtime)
+        .into_iter()
+        .chain(crate::registry::standard_names())
+    {
+        if let Some(inner) = runtime.deregister_function(name) {
+            runtime.register_function(name, Box::new(LimitedFn { inner }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::Variable;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        crate::register_all(&mut runtime);
+        install(&mut runtime);
+        runtime
+    }
+
+    #[test]
+    fn test_no_limits() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("length(@)").unwrap();
+        let data = Variable::from_json("[1, 2, 3]").unwrap();
+        let result = search_with_limits(&expr, &data, &Limits::new()).unwrap();
+        assert_eq!(result.as_number(), Some(3.0));
+    }
+
+    #[test]
+    fn test_max_result_bytes() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("@").unwrap();
+        let data = Variable::from_json(r#"["aaaaaaaaaa", "bbbbbbbbbb"]"#).unwrap();
+        assert!(search_with_limits(&expr, &data, &Limits::new().max_result_bytes(100)).is_ok());
+        let err = search_with_limits(&expr, &data, &Limits::new().max_result_bytes(10))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("larger than 10 bytes"), "{}", err);
+    }
+
+    #[test]
+    fn test_max_eval_time() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("length(@)").unwrap();
+        let data = Variable::from_json("[1]").unwrap();
+        let limits = Limits::new().max_eval_time(Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(1));
+        let err = search_with_limits(&expr, &data, &limits).unwrap_err();
+        assert!(err.to_string().contains("longer than"), "{}", err);
+    }
+
+    #[cfg(feature = "expression")]
+    #[test]
+    fn test_max_eval_time_per_element() {
+        // Without install() only the per-element check can stop the search
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        crate::register_all(&mut runtime);
+        let expr = runtime.compile("map_expr('@', @)").unwrap();
+        let data = Variable::from_json("[1, 2, 3]").unwrap();
+        let limits = Limits::new().max_eval_time(Duration::ZERO);
+        let err = search_with_limits(&expr, &data, &limits).unwrap_err();
+        assert!(err.to_string().contains("longer than"), "{}", err);
+    }
+
+    #[cfg(all(feature = "parallel", feature = "array"))]
+    #[test]
+    fn test_limits_apply_on_parallel_workers() {
+        // Without install() the checks can only fire on the worker threads
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        crate::register_all(&mut runtime);
+        let data = Variable::from_json("[1, 2, 3, 4]").unwrap();
+
+        let expr = runtime
+            .compile("parallel_map_expr(&range(`0`, `50`), @)")
+            .unwrap();
+        assert!(expr.search(&data).is_ok());
+        let err = search_with_limits(&expr, &data, &Limits::new().max_array_len(10)).unwrap_err();
+        assert!(err.to_string().contains("exceeds 10"), "{}", err);
+
+        let expr = runtime.compile("parallel_map_expr(&@, @)").unwrap();
+        let limits = Limits::new().max_eval_time(Duration::ZERO);
+        let err = search_with_limits(&expr, &data, &limits).unwrap_err();
+        assert!(err.to_string().contains("longer than"), "{}", err);
+    }
+
+    #[cfg(feature = "string")]
+    #[test]
+    fn test_max_result_bytes_before_allocating() {
+        let runtime = setup_runtime();
+        let limits = Limits::new().max_result_bytes(100);
+        for query in ["repeat('ab', `1e12`)", "pad_left('a', `1e12`, '-')"] {
+            let expr = runtime.compile(query).unwrap();
+            let err = search_with_limits(&expr, Variable::Null, &limits).unwrap_err();
+            assert!(err.to_string().contains("exceeds 100"), "{}", err);
+        }
+    }
+
+    #[cfg(feature = "array")]
+    #[test]
+    fn test_max_array_len() {
+        let runtime = setup_runtime();
+        let data = Variable::Null;
+        let limits = Limits::new().max_array_len(10);
+
+        let expr = runtime.compile("range(`0`, `5`)").unwrap();
+        assert!(search_with_limits(&expr, &data, &limits).is_ok());
+        let expr = runtime.compile("range(`0`, `50`)").unwrap();
+        assert!(search_with_limits(&expr, &data, &limits).is_err());
+        // Checked before the output is allocated
+        let expr = runtime.compile("rle_decode(`[[1, 50]]`)").unwrap();
+        let err = search_with_limits(&expr, &data, &limits).unwrap_err();
+        assert!(err.to_string().contains("exceeds 10"), "{}", err);
+        let expr = runtime
+            .compile("cartesian(`[1, 2, 3, 4]`, `[1, 2, 3]`)")
+            .unwrap();
+        assert!(search_with_limits(&expr, &data, &limits).is_err());
+
+        // Limits only apply inside search_with_limits
+        assert!(expr.search(&data).is_ok());
+    }
+
+    #[cfg(feature = "object")]
+    #[test]
+    fn test_max_depth() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": {"c": {"d": 1}}}}"#).unwrap();
+        let expr = runtime.compile("deep_merge(@, @)").unwrap();
+        assert!(search_with_limits(&expr, &data, &Limits::new().max_depth(8)).is_ok());
+        let err = search_with_limits(&expr, &data, &Limits::new().max_depth(2))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("nested deeper than 2"), "{}", err);
+    }
+}
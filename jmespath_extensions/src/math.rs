@@ -16,10 +16,11 @@
 //! math::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
+    custom_error, exact_integer, integer_arg,
 };
 use crate::define_function;
 
@@ -34,10 +35,13 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("sqrt", Box::new(SqrtFn::new()));
     runtime.register_function("log", Box::new(LogFn::new()));
     runtime.register_function("clamp", Box::new(ClampFn::new()));
+    runtime.register_function("lerp", Box::new(LerpFn::new()));
+    runtime.register_function("map_range", Box::new(MapRangeFn::new()));
     runtime.register_function("median", Box::new(MedianFn::new()));
     runtime.register_function("percentile", Box::new(PercentileFn::new()));
     runtime.register_function("variance", Box::new(VarianceFn::new()));
     runtime.register_function("stddev", Box::new(StddevFn::new()));
+    runtime.register_function("describe", Box::new(DescribeFn::new()));
     runtime.register_function("sin", Box::new(SinFn::new()));
     runtime.register_function("cos", Box::new(CosFn::new()));
     runtime.register_function("tan", Box::new(TanFn::new()));
@@ -52,6 +56,12 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("subtract", Box::new(SubtractFn::new()));
     runtime.register_function("multiply", Box::new(MultiplyFn::new()));
     runtime.register_function("divide", Box::new(DivideFn::new()));
+    runtime.register_function("checked_add", Box::new(CheckedAddFn::new()));
+    runtime.register_function("checked_sub", Box::new(CheckedSubFn::new()));
+    runtime.register_function("checked_mul", Box::new(CheckedMulFn::new()));
+    runtime.register_function("checked_add_or_null", Box::new(CheckedAddOrNullFn::new()));
+    runtime.register_function("checked_sub_or_null", Box::new(CheckedSubOrNullFn::new()));
+    runtime.register_function("checked_mul_or_null", Box::new(CheckedMulOrNullFn::new()));
     runtime.register_function("mode", Box::new(ModeFn::new()));
     runtime.register_function("to_fixed", Box::new(ToFixedFn::new()));
     runtime.register_function("format_number", Box::new(FormatNumberFn::new()));
@@ -64,6 +74,24 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("ewma", Box::new(EwmaFn::new()));
     runtime.register_function("covariance", Box::new(CovarianceFn::new()));
     runtime.register_function("standardize", Box::new(StandardizeFn::new()));
+    runtime.register_function("dot", Box::new(DotFn::new()));
+    runtime.register_function("vector_add", Box::new(VectorAddFn::new()));
+    runtime.register_function("vector_scale", Box::new(VectorScaleFn::new()));
+    runtime.register_function("norm", Box::new(NormFn::new()));
+    runtime.register_function("cosine_similarity", Box::new(CosineSimilarityFn::new()));
+    runtime.register_function("matmul", Box::new(MatmulFn::new()));
+    runtime.register_function("cumsum", Box::new(CumsumFn::new()));
+    runtime.register_function("cumprod", Box::new(CumprodFn::new()));
+    runtime.register_function("cummax", Box::new(CummaxFn::new()));
+    runtime.register_function("cummin", Box::new(CumminFn::new()));
+    runtime.register_function("interpolate", Box::new(InterpolateFn::new()));
+    runtime.register_function("fill_forward", Box::new(FillForwardFn::new()));
+    runtime.register_function("resample", Box::new(ResampleFn::new()));
+    runtime.register_function("gcd", Box::new(GcdFn::new()));
+    runtime.register_function("lcm", Box::new(LcmFn::new()));
+    runtime.register_function("is_prime", Box::new(IsPrimeFn::new()));
+    runtime.register_function("factorial", Box::new(FactorialFn::new()));
+    runtime.register_function("fibonacci", Box::new(FibonacciFn::new()));
 }
 
 // =============================================================================
@@ -392,6 +420,69 @@ impl Function for ClampFn {
     }
 }
 
+// =============================================================================
+// lerp(a, b, t) -> number
+// Linear interpolation; t outside 0..1 extrapolates beyond a and b.
+// =============================================================================
+
+define_function!(
+    LerpFn,
+    vec![
+        ArgumentType::Number,
+        ArgumentType::Number,
+        ArgumentType::Number
+    ],
+    None
+);
+
+impl Function for LerpFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let a = args[0].as_number().unwrap();
+        let b = args[1].as_number().unwrap();
+        let t = args[2].as_number().unwrap();
+
+        Ok(number_var(a + (b - a) * t))
+    }
+}
+
+// =============================================================================
+// map_range(x, in_min, in_max, out_min, out_max) -> number
+// Linearly rescale x from one range to another (not clamped).
+// =============================================================================
+
+define_function!(
+    MapRangeFn,
+    vec![
+        ArgumentType::Number,
+        ArgumentType::Number,
+        ArgumentType::Number,
+        ArgumentType::Number,
+        ArgumentType::Number
+    ],
+    None
+);
+
+impl Function for MapRangeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let [x, in_min, in_max, out_min, out_max] =
+            std::array::from_fn(|i| args[i].as_number().unwrap());
+        if in_max == in_min {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("map_range() input range must not be empty".to_owned()),
+            ));
+        }
+
+        let t = (x - in_min) / (in_max - in_min);
+        Ok(number_var(out_min + (out_max - out_min) * t))
+    }
+}
+
 // =============================================================================
 // median(array) -> number
 // =============================================================================
@@ -571,6 +662,82 @@ impl Function for StddevFn {
     }
 }
 
+// =============================================================================
+// describe(array) -> object
+// Summary statistics in one traversal: {count, min, max, mean, median,
+// stddev, p25, p75, nulls}. Percentiles use the same linear interpolation as
+// percentile() and stddev is the population standard deviation.
+// =============================================================================
+
+define_function!(DescribeFn, vec![ArgumentType::Array], None);
+
+/// Linearly interpolated quantile of an already sorted, non-empty slice.
+fn sorted_quantile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = rank - lower as f64;
+    sorted[lower] * (1.0 - fraction) + sorted[upper] * fraction
+}
+
+impl Function for DescribeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+
+        // Welford's algorithm keeps mean and variance stable in a single pass
+        let mut numbers = Vec::with_capacity(arr.len());
+        let mut nulls = 0usize;
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+        for value in arr {
+            match value.as_ref() {
+                Variable::Number(n) => {
+                    let x = n.as_f64().unwrap_or(0.0);
+                    numbers.push(x);
+                    let delta = x - mean;
+                    mean += delta / numbers.len() as f64;
+                    m2 += delta * (x - mean);
+                }
+                Variable::Null => nulls += 1,
+                _ => {}
+            }
+        }
+
+        let count = numbers.len();
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(
+            "count".to_string(),
+            Rc::new(Variable::Number(serde_json::Number::from(count))),
+        );
+        map.insert(
+            "nulls".to_string(),
+            Rc::new(Variable::Number(serde_json::Number::from(nulls))),
+        );
+
+        let names = ["min", "max", "mean", "median", "stddev", "p25", "p75"];
+        let values = (count > 0).then(|| {
+            numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            [
+                numbers[0],
+                numbers[count - 1],
+                mean,
+                sorted_quantile(&numbers, 0.5),
+                (m2 / count as f64).sqrt(),
+                sorted_quantile(&numbers, 0.25),
+                sorted_quantile(&numbers, 0.75),
+            ]
+        });
+        for (i, name) in names.into_iter().enumerate() {
+            let value = values.map_or_else(|| Rc::new(Variable::Null), |v| number_var(v[i]));
+            map.insert(name.to_string(), value);
+        }
+
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
 // =============================================================================
 // Trigonometric functions
 // =============================================================================
@@ -924,6 +1091,115 @@ impl Function for DivideFn {
     }
 }
 
+// =============================================================================
+// checked_add(a, b) / checked_sub(a, b) / checked_mul(a, b) -> number
+// checked_add_or_null(a, b) / checked_sub_or_null(a, b) / checked_mul_or_null(a, b)
+// Exact 64-bit integer arithmetic that errors (or returns null) instead of
+// silently rounding through f64.
+// =============================================================================
+
+fn checked_op(
+    args: &[Rcvar],
+    ctx: &Context<'_>,
+    name: &str,
+    op: fn(i64, i64) -> Option<i64>,
+    or_null: bool,
+) -> Result<Rcvar, JmespathError> {
+    let result = exact_integer(&args[0])
+        .and_then(|a| exact_integer(&args[1]).map(|b| (a, b)))
+        .and_then(|(a, b)| {
+            op(a, b).ok_or_else(|| format!("integer overflow computing {} and {}", a, b))
+        });
+
+    match result {
+        Ok(n) => Ok(Rc::new(Variable::Number(serde_json::Number::from(n)))),
+        Err(_) if or_null => Ok(Rc::new(Variable::Null)),
+        Err(msg) => Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!("{}(): {}", name, msg)),
+        )),
+    }
+}
+
+define_function!(
+    CheckedAddFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for CheckedAddFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        checked_op(args, ctx, "checked_add", i64::checked_add, false)
+    }
+}
+
+define_function!(
+    CheckedSubFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for CheckedSubFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        checked_op(args, ctx, "checked_sub", i64::checked_sub, false)
+    }
+}
+
+define_function!(
+    CheckedMulFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for CheckedMulFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        checked_op(args, ctx, "checked_mul", i64::checked_mul, false)
+    }
+}
+
+define_function!(
+    CheckedAddOrNullFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for CheckedAddOrNullFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        checked_op(args, ctx, "checked_add_or_null", i64::checked_add, true)
+    }
+}
+
+define_function!(
+    CheckedSubOrNullFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for CheckedSubOrNullFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        checked_op(args, ctx, "checked_sub_or_null", i64::checked_sub, true)
+    }
+}
+
+define_function!(
+    CheckedMulOrNullFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for CheckedMulOrNullFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        checked_op(args, ctx, "checked_mul_or_null", i64::checked_mul, true)
+    }
+}
+
 // =============================================================================
 // mode(array) -> any (most common value)
 // =============================================================================
@@ -1684,111 +1960,811 @@ impl Function for StandardizeFn {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use jmespath::Runtime;
+// =============================================================================
+// Vector math helpers
+// =============================================================================
 
-    fn setup_runtime() -> Runtime {
-        let mut runtime = Runtime::new();
-        runtime.register_builtin_functions();
-        register(&mut runtime);
-        runtime
-    }
+/// Convert an array argument into a numeric vector, rejecting non-numeric elements.
+fn to_vector(value: &Rcvar, ctx: &Context<'_>) -> Result<Vec<f64>, JmespathError> {
+    let arr = value.as_array().ok_or_else(|| {
+        JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse("Expected array argument".to_owned()),
+        )
+    })?;
+
+    arr.iter()
+        .map(|v| {
+            v.as_number().ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("Vector elements must be numbers".to_owned()),
+                )
+            })
+        })
+        .collect()
+}
 
-    #[test]
-    #[allow(clippy::approx_constant)]
-    fn test_round() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("round(`3.14159`, `2`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert!((result.as_number().unwrap() - 3.14_f64).abs() < 0.001);
-    }
+/// Convert two array arguments into numeric vectors of the same length.
+fn to_vector_pair(
+    a: &Rcvar,
+    b: &Rcvar,
+    ctx: &Context<'_>,
+) -> Result<(Vec<f64>, Vec<f64>), JmespathError> {
+    let a = to_vector(a, ctx)?;
+    let b = to_vector(b, ctx)?;
+    if a.len() != b.len() {
+        return Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "Vectors must have the same length ({} != {})",
+                a.len(),
+                b.len()
+            )),
+        ));
+    }
+    Ok((a, b))
+}
 
-    #[test]
-    fn test_sqrt() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("sqrt(`16`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap() as i64, 4);
-    }
+fn number_var(n: f64) -> Rcvar {
+    Rc::new(
+        serde_json::Number::from_f64(n)
+            .map(Variable::Number)
+            .unwrap_or(Variable::Null),
+    )
+}
 
-    #[test]
-    fn test_clamp() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("clamp(`5`, `0`, `3`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap() as i64, 3);
-    }
+fn vector_var(values: impl IntoIterator<Item = f64>) -> Rcvar {
+    Rc::new(Variable::Array(
+        values.into_iter().map(number_var).collect(),
+    ))
+}
 
-    #[test]
-    fn test_add() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("add(`1`, `2`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap() as i64, 3);
-    }
+fn dot_product(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
 
-    #[test]
-    fn test_subtract() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("subtract(`10`, `3`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap() as i64, 7);
-    }
+// =============================================================================
+// dot(a, b) -> number
+// =============================================================================
 
-    #[test]
-    fn test_multiply() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("multiply(`4`, `5`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap() as i64, 20);
-    }
+define_function!(DotFn, vec![ArgumentType::Array, ArgumentType::Array], None);
 
-    #[test]
-    fn test_divide() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("divide(`10`, `4`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap(), 2.5);
+impl Function for DotFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (a, b) = to_vector_pair(&args[0], &args[1], ctx)?;
+        Ok(number_var(dot_product(&a, &b)))
     }
+}
 
-    #[test]
-    fn test_mode_numbers() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("mode(`[1, 2, 2, 3]`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_number().unwrap() as i64, 2);
-    }
+// =============================================================================
+// vector_add(a, b) -> array
+// =============================================================================
 
-    #[test]
-    fn test_mode_strings() {
-        let runtime = setup_runtime();
-        let expr = runtime
-            .compile("mode(`[\"a\", \"b\", \"a\", \"c\"]`)")
-            .unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_string().unwrap(), "a");
-    }
+define_function!(
+    VectorAddFn,
+    vec![ArgumentType::Array, ArgumentType::Array],
+    None
+);
 
-    #[test]
-    fn test_mode_empty() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("mode(`[]`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert!(result.is_null());
+impl Function for VectorAddFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (a, b) = to_vector_pair(&args[0], &args[1], ctx)?;
+        Ok(vector_var(a.iter().zip(&b).map(|(x, y)| x + y)))
     }
+}
 
-    #[test]
-    fn test_to_fixed() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("to_fixed(`3.14159`, `2`)").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        assert_eq!(result.as_string().unwrap(), "3.14");
+// =============================================================================
+// vector_scale(a, k) -> array
+// =============================================================================
+
+define_function!(
+    VectorScaleFn,
+    vec![ArgumentType::Array, ArgumentType::Number],
+    None
+);
+
+impl Function for VectorScaleFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let a = to_vector(&args[0], ctx)?;
+        let k = args[1].as_number().unwrap();
+        Ok(vector_var(a.iter().map(|x| x * k)))
     }
+}
 
-    #[test]
-    fn test_to_fixed_padding() {
+// =============================================================================
+// norm(a) -> number (Euclidean / L2 norm)
+// =============================================================================
+
+define_function!(NormFn, vec![ArgumentType::Array], None);
+
+impl Function for NormFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let a = to_vector(&args[0], ctx)?;
+        Ok(number_var(dot_product(&a, &a).sqrt()))
+    }
+}
+
+// =============================================================================
+// cosine_similarity(a, b) -> number | null
+// Returns null when either vector has zero magnitude
+// =============================================================================
+
+define_function!(
+    CosineSimilarityFn,
+    vec![ArgumentType::Array, ArgumentType::Array],
+    None
+);
+
+impl Function for CosineSimilarityFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (a, b) = to_vector_pair(&args[0], &args[1], ctx)?;
+
+        let denom = dot_product(&a, &a).sqrt() * dot_product(&b, &b).sqrt();
+        if denom.abs() < f64::EPSILON {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        Ok(number_var(dot_product(&a, &b) / denom))
+    }
+}
+
+// =============================================================================
+// matmul(a, b) -> array
+// Matrix product of an m x n matrix with an n x p matrix (rows as arrays).
+// A plain vector as the second argument is treated as a column vector.
+// =============================================================================
+
+define_function!(
+    MatmulFn,
+    vec![ArgumentType::Array, ArgumentType::Array],
+    None
+);
+
+/// Convert an array of equal-length numeric arrays into rows.
+fn to_matrix(value: &Rcvar, ctx: &Context<'_>) -> Result<Vec<Vec<f64>>, JmespathError> {
+    let rows = value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|row| to_vector(row, ctx))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(first) = rows.first()
+        && rows.iter().any(|r| r.len() != first.len())
+    {
+        return Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse("Matrix rows must have the same length".to_owned()),
+        ));
+    }
+    Ok(rows)
+}
+
+impl Function for MatmulFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let a = to_matrix(&args[0], ctx)?;
+
+        let b_is_vector = args[1]
+            .as_array()
+            .unwrap()
+            .first()
+            .is_some_and(|v| v.is_number());
+        let b = if b_is_vector {
+            to_vector(&args[1], ctx)?
+                .into_iter()
+                .map(|x| vec![x])
+                .collect()
+        } else {
+            to_matrix(&args[1], ctx)?
+        };
+
+        let inner = a.first().map_or(0, |row| row.len());
+        if inner != b.len() {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "Incompatible matrix shapes: {} columns vs {} rows",
+                    inner,
+                    b.len()
+                )),
+            ));
+        }
+        let cols = b.first().map_or(0, |row| row.len());
+
+        let product = a.iter().map(|row| {
+            (0..cols)
+                .map(|j| {
+                    row.iter()
+                        .zip(&b)
+                        .map(|(x, b_row)| x * b_row[j])
+                        .sum::<f64>()
+                })
+                .collect::<Vec<f64>>()
+        });
+
+        if b_is_vector {
+            Ok(vector_var(product.map(|row| row[0])))
+        } else {
+            Ok(Rc::new(Variable::Array(product.map(vector_var).collect())))
+        }
+    }
+}
+
+// =============================================================================
+// cumsum(array) / cumprod(array) / cummax(array) / cummin(array) -> array
+// Running totals, products, maxima and minima
+// =============================================================================
+
+/// Apply `op` cumulatively over a numeric array argument.
+fn cumulative(
+    args: &[Rcvar],
+    ctx: &Context<'_>,
+    op: fn(f64, f64) -> f64,
+) -> Result<Rcvar, JmespathError> {
+    let values = to_vector(&args[0], ctx)?;
+    let running = values.iter().scan(None, |acc: &mut Option<f64>, &x| {
+        let next = acc.map_or(x, |a| op(a, x));
+        *acc = Some(next);
+        Some(next)
+    });
+    Ok(vector_var(running))
+}
+
+define_function!(CumsumFn, vec![ArgumentType::Array], None);
+
+impl Function for CumsumFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        cumulative(args, ctx, |a, b| a + b)
+    }
+}
+
+define_function!(CumprodFn, vec![ArgumentType::Array], None);
+
+impl Function for CumprodFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        cumulative(args, ctx, |a, b| a * b)
+    }
+}
+
+define_function!(CummaxFn, vec![ArgumentType::Array], None);
+
+impl Function for CummaxFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        cumulative(args, ctx, f64::max)
+    }
+}
+
+define_function!(CumminFn, vec![ArgumentType::Array], None);
+
+impl Function for CumminFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        cumulative(args, ctx, f64::min)
+    }
+}
+
+// =============================================================================
+// interpolate(array) -> array
+// Fill nulls linearly between the nearest numeric neighbours
+// =============================================================================
+
+define_function!(InterpolateFn, vec![ArgumentType::Array], None);
+
+impl Function for InterpolateFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+
+        let mut values: Vec<Option<f64>> = Vec::with_capacity(arr.len());
+        for v in arr.iter() {
+            match v.as_ref() {
+                Variable::Null => values.push(None),
+                Variable::Number(n) => values.push(n.as_f64()),
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse(
+                            "interpolate() expects an array of numbers and nulls".to_owned(),
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Leading and trailing gaps have only one neighbour and stay null
+        let mut prev: Option<usize> = None;
+        for i in 0..values.len() {
+            if values[i].is_none() {
+                continue;
+            }
+            if let Some(p) = prev
+                && i - p > 1
+            {
+                let (start, end) = (values[p].unwrap(), values[i].unwrap());
+                let span = (i - p) as f64;
+                for (j, slot) in values.iter_mut().enumerate().take(i).skip(p + 1) {
+                    *slot = Some(start + (end - start) * (j - p) as f64 / span);
+                }
+            }
+            prev = Some(i);
+        }
+
+        let result: Vec<Rcvar> = values
+            .into_iter()
+            .map(|v| v.map_or_else(|| Rc::new(Variable::Null), number_var))
+            .collect();
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// fill_forward(array) -> array
+// Replace nulls with the last non-null value
+// =============================================================================
+
+define_function!(FillForwardFn, vec![ArgumentType::Array], None);
+
+impl Function for FillForwardFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let arr = args[0].as_array().unwrap();
+
+        let mut last: Option<Rcvar> = None;
+        let result: Vec<Rcvar> = arr
+            .iter()
+            .map(|v| {
+                if v.is_null() {
+                    last.clone().unwrap_or_else(|| v.clone())
+                } else {
+                    last = Some(v.clone());
+                    v.clone()
+                }
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// resample(pairs, interval, agg) -> array
+// Bucket [timestamp, value] pairs into fixed intervals and aggregate each bucket
+// =============================================================================
+
+/// Most buckets `resample()` will emit, counting the empty ones it fills in.
+const MAX_RESAMPLE_BUCKETS: usize = 1_000_000;
+
+define_function!(
+    ResampleFn,
+    vec![
+        ArgumentType::Array,
+        ArgumentType::Number,
+        ArgumentType::String
+    ],
+    None
+);
+
+impl Function for ResampleFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let pairs = args[0].as_array().unwrap();
+        let interval = args[1].as_number().unwrap();
+        let agg = args[2].as_string().unwrap();
+
+        if interval <= 0.0 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("resample() interval must be greater than zero".to_owned()),
+            ));
+        }
+        if !matches!(
+            agg.as_str(),
+            "mean" | "sum" | "min" | "max" | "count" | "first" | "last"
+        ) {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "resample() agg must be one of mean, sum, min, max, count, first, last (got '{}')",
+                    agg
+                )),
+            ));
+        }
+
+        // Bucket index -> values, in input order within each bucket
+        let mut buckets: std::collections::BTreeMap<i64, Vec<f64>> =
+            std::collections::BTreeMap::new();
+        for pair in pairs.iter() {
+            let (t, v) = match pair.as_array().map(|p| p.as_slice()) {
+                Some([t, v]) => (t.as_number(), v.as_ref()),
+                _ => (None, &Variable::Null),
+            };
+            let t = t.ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse(
+                        "resample() expects [timestamp, value] pairs with numeric timestamps"
+                            .to_owned(),
+                    ),
+                )
+            })?;
+            let bucket = buckets.entry((t / interval).floor() as i64).or_default();
+            // Null values still mark the bucket as present but are not aggregated
+            if let Some(v) = v.as_number() {
+                bucket.push(v);
+            }
+        }
+
+        let (Some(&first), Some(&last)) = (buckets.keys().next(), buckets.keys().next_back())
+        else {
+            return Ok(Rc::new(Variable::Array(vec![])));
+        };
+
+        // Emit every bucket between the first and last so the series is regular
+        let span = (i128::from(last) - i128::from(first) + 1).min(usize::MAX as i128) as usize;
+        if span > MAX_RESAMPLE_BUCKETS {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "resample() would produce {} buckets (limit {}); use a larger interval",
+                    span, MAX_RESAMPLE_BUCKETS
+                )),
+            ));
+        }
+        crate::limits::check_array_len(ctx, span)?;
+        let result: Vec<Rcvar> = (first..=last)
+            .map(|b| {
+                let values = buckets.get(&b).map(|v| v.as_slice()).unwrap_or(&[]);
+                let value = match agg.as_str() {
+                    "count" => Some(values.len() as f64),
+                    _ if values.is_empty() => None,
+                    "sum" => Some(values.iter().sum()),
+                    "mean" => Some(values.iter().sum::<f64>() / values.len() as f64),
+                    "min" => values.iter().copied().reduce(f64::min),
+                    "max" => values.iter().copied().reduce(f64::max),
+                    "first" => values.first().copied(),
+                    _ => values.last().copied(),
+                };
+                Rc::new(Variable::Array(vec![
+                    number_var(b as f64 * interval),
+                    value.map_or_else(|| Rc::new(Variable::Null), number_var),
+                ]))
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// Number theory helpers
+// =============================================================================
+
+/// Read an argument that must be a non-negative whole number.
+fn non_negative_arg(value: &Rcvar, ctx: &Context<'_>) -> Result<u64, JmespathError> {
+    let n = integer_arg(value, ctx)?;
+    u64::try_from(n).map_err(|_| {
+        JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse("Expected non-negative integer argument".to_owned()),
+        )
+    })
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Deterministic Miller-Rabin; these bases are exact for every 64-bit integer.
+fn is_prime_u64(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+
+    let mul_mod = |a: u64, b: u64| ((a as u128 * b as u128) % n as u128) as u64;
+    let pow_mod = |mut base: u64, mut exp: u64| {
+        let mut result = 1;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = mul_mod(result, base);
+            }
+            base = mul_mod(base, base);
+            exp >>= 1;
+        }
+        result
+    };
+
+    let d = (n - 1) >> (n - 1).trailing_zeros();
+    BASES.iter().all(|&a| {
+        let mut x = pow_mod(a, d);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        let mut e = d;
+        while e < n - 1 {
+            x = mul_mod(x, x);
+            e <<= 1;
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// Return an exact integer when it fits in u64, otherwise the nearest f64.
+fn integer_result(exact: Option<u64>, approx: f64) -> Rcvar {
+    match exact {
+        Some(n) => Rc::new(Variable::Number(serde_json::Number::from(n))),
+        None => number_var(approx),
+    }
+}
+
+// =============================================================================
+// gcd(a, b) -> number
+// =============================================================================
+
+define_function!(
+    GcdFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for GcdFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let a = integer_arg(&args[0], ctx)?.unsigned_abs();
+        let b = integer_arg(&args[1], ctx)?.unsigned_abs();
+        Ok(integer_result(Some(gcd_u64(a, b)), 0.0))
+    }
+}
+
+// =============================================================================
+// lcm(a, b) -> number
+// =============================================================================
+
+define_function!(
+    LcmFn,
+    vec![ArgumentType::Number, ArgumentType::Number],
+    None
+);
+
+impl Function for LcmFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let a = integer_arg(&args[0], ctx)?.unsigned_abs();
+        let b = integer_arg(&args[1], ctx)?.unsigned_abs();
+        if a == 0 || b == 0 {
+            return Ok(integer_result(Some(0), 0.0));
+        }
+        let lcm = (a / gcd_u64(a, b)).checked_mul(b);
+        Ok(integer_result(
+            lcm,
+            a as f64 / gcd_u64(a, b) as f64 * b as f64,
+        ))
+    }
+}
+
+// =============================================================================
+// is_prime(n) -> boolean
+// =============================================================================
+
+define_function!(IsPrimeFn, vec![ArgumentType::Number], None);
+
+impl Function for IsPrimeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if let Variable::Number(n) = &*args[0]
+            && let Some(n) = n.as_u64()
+        {
+            return Ok(Rc::new(Variable::Bool(is_prime_u64(n))));
+        }
+        // Non-integers and negatives are simply not prime
+        let f = args[0].as_number().unwrap_or(0.0);
+        if f.fract() != 0.0 || f < 2.0 {
+            return Ok(Rc::new(Variable::Bool(false)));
+        }
+        // Above 2^53 the float has already been rounded to a neighbouring value
+        let n = exact_integer(&args[0])
+            .map_err(|msg| custom_error(ctx, &format!("is_prime(): {}", msg)))?;
+        Ok(Rc::new(Variable::Bool(is_prime_u64(n as u64))))
+    }
+}
+
+// =============================================================================
+// factorial(n) -> number
+// Exact up to 20!, approximate (f64) up to 170!.
+// =============================================================================
+
+define_function!(FactorialFn, vec![ArgumentType::Number], None);
+
+impl Function for FactorialFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = non_negative_arg(&args[0], ctx)?;
+        if n > 170 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("factorial() argument must be at most 170".to_owned()),
+            ));
+        }
+        let exact = (1..=n).try_fold(1u64, |acc, k| acc.checked_mul(k));
+        let approx = (1..=n).fold(1.0, |acc, k| acc * k as f64);
+        Ok(integer_result(exact, approx))
+    }
+}
+
+// =============================================================================
+// fibonacci(n) -> number
+// Exact up to F(93), approximate (f64) up to F(1476).
+// =============================================================================
+
+define_function!(FibonacciFn, vec![ArgumentType::Number], None);
+
+impl Function for FibonacciFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let n = non_negative_arg(&args[0], ctx)?;
+        if n > 1476 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("fibonacci() argument must be at most 1476".to_owned()),
+            ));
+        }
+        let (mut a, mut b) = (Some(0u64), Some(1u64));
+        let (mut fa, mut fb) = (0.0_f64, 1.0_f64);
+        for _ in 0..n {
+            (a, b) = (b, a.zip(b).and_then(|(x, y)| x.checked_add(y)));
+            (fa, fb) = (fb, fa + fb);
+        }
+        Ok(integer_result(a, fa))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jmespath::Runtime;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_round() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("round(`3.14159`, `2`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert!((result.as_number().unwrap() - 3.14_f64).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("sqrt(`16`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 4);
+    }
+
+    #[test]
+    fn test_clamp() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("clamp(`5`, `0`, `3`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 3);
+    }
+
+    #[test]
+    fn test_add() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("add(`1`, `2`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 3);
+    }
+
+    #[test]
+    fn test_subtract() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("subtract(`10`, `3`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 7);
+    }
+
+    #[test]
+    fn test_multiply() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("multiply(`4`, `5`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 20);
+    }
+
+    #[test]
+    fn test_divide() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("divide(`10`, `4`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 2.5);
+    }
+
+    #[test]
+    fn test_mode_numbers() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("mode(`[1, 2, 2, 3]`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap() as i64, 2);
+    }
+
+    #[test]
+    fn test_mode_strings() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("mode(`[\"a\", \"b\", \"a\", \"c\"]`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "a");
+    }
+
+    #[test]
+    fn test_mode_empty() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("mode(`[]`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert!(result.is_null());
+    }
+
+    #[test]
+    fn test_to_fixed() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("to_fixed(`3.14159`, `2`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "3.14");
+    }
+
+    #[test]
+    fn test_to_fixed_padding() {
         let runtime = setup_runtime();
         let expr = runtime.compile("to_fixed(`3.1`, `3`)").unwrap();
         let result = expr.search(&Variable::Null).unwrap();
@@ -1990,4 +2966,410 @@ mod tests {
         // Last value: (50-30)/14.14 ≈ 1.41
         assert!((arr[4].as_number().unwrap() - 1.414).abs() < 0.01);
     }
+
+    #[test]
+    fn test_dot() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("dot(`[1, 2, 3]`, `[4, 5, 6]`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 32.0);
+    }
+
+    #[test]
+    fn test_dot_length_mismatch() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("dot(`[1, 2]`, `[1, 2, 3]`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_vector_add_and_scale() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("vector_scale(vector_add(`[1, 2]`, `[3, 4]`), `0.5`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let arr: Vec<f64> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_number().unwrap())
+            .collect();
+        assert_eq!(arr, vec![2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_vector_rejects_non_numbers() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("norm(`[1, \"a\"]`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_norm() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("norm(`[3, 4]`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("cosine_similarity(`[1, 0]`, `[1, 1]`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert!((result.as_number().unwrap() - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-10);
+
+        let expr = runtime
+            .compile("cosine_similarity(`[0, 0]`, `[1, 1]`)")
+            .unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_matmul() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("matmul(`[[1, 2], [3, 4]]`, `[[5, 6], [7, 8]]`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let rows: Vec<Vec<f64>> = result.as_array().unwrap().iter().map(numbers).collect();
+        assert_eq!(rows, vec![vec![19.0, 22.0], vec![43.0, 50.0]]);
+
+        let expr = runtime
+            .compile("matmul(`[[1, 0, 2], [0, 1, 0]]`, `[1, 2, 3]`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(numbers(&result), vec![7.0, 2.0]);
+    }
+
+    #[test]
+    fn test_matmul_shape_mismatch() {
+        let runtime = setup_runtime();
+        for expr in [
+            "matmul(`[[1, 2]]`, `[[1, 2]]`)",
+            "matmul(`[[1, 2], [3]]`, `[[1], [2]]`)",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(&Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    fn numbers(result: &Rcvar) -> Vec<f64> {
+        result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_number().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_cumulative_functions() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[3, 1, 4, 1, 5]").unwrap();
+        let cases = [
+            ("cumsum(@)", vec![3.0, 4.0, 8.0, 9.0, 14.0]),
+            ("cumprod(@)", vec![3.0, 3.0, 12.0, 12.0, 60.0]),
+            ("cummax(@)", vec![3.0, 3.0, 4.0, 4.0, 5.0]),
+            ("cummin(@)", vec![3.0, 1.0, 1.0, 1.0, 1.0]),
+        ];
+        for (query, expected) in cases {
+            let result = runtime.compile(query).unwrap().search(&data).unwrap();
+            assert_eq!(numbers(&result), expected, "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_cumsum_empty_and_invalid() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("cumsum(`[]`)").unwrap();
+        assert!(numbers(&expr.search(&Variable::Null).unwrap()).is_empty());
+
+        let expr = runtime.compile("cumsum(`[1, null]`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("interpolate(@)").unwrap();
+        let data = Variable::from_json("[null, 1, null, null, 4, null]").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json("[null, 1.0, 2.0, 3.0, 4.0, null]").unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_interpolate_rejects_strings() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("interpolate(@)").unwrap();
+        let data = Variable::from_json(r#"[1, "x", 3]"#).unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_fill_forward() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("fill_forward(@)").unwrap();
+        let data = Variable::from_json(r#"[null, 1, null, "a", null]"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"[null, 1, 1, "a", "a"]"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_resample() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("resample(@, `60`, 'mean')").unwrap();
+        let data = Variable::from_json("[[0, 1], [30, 3], [150, 10], [179, null]]").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json("[[0.0, 2.0], [60.0, null], [120.0, 10.0]]").unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime.compile("resample(@, `60`, 'count')").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json("[[0.0, 2.0], [60.0, 0.0], [120.0, 1.0]]").unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_resample_invalid() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[[0, 1]]").unwrap();
+        let expr = runtime.compile("resample(@, `60`, 'median')").unwrap();
+        assert!(expr.search(&data).is_err());
+        let expr = runtime.compile("resample(@, `0`, 'sum')").unwrap();
+        assert!(expr.search(&data).is_err());
+        let expr = runtime
+            .compile("resample(`[[\"x\", 1]]`, `60`, 'sum')")
+            .unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+        // Two points far apart would need a bucket for every interval between them
+        let expr = runtime
+            .compile("resample(`[[0, 1], [1e300, 2]]`, `1`, 'sum')")
+            .unwrap();
+        let err = expr.search(&Variable::Null).unwrap_err().to_string();
+        assert!(err.contains("buckets"), "{}", err);
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        let runtime = setup_runtime();
+        let cases = [
+            ("gcd(`12`, `18`)", 6.0),
+            ("gcd(`-12`, `18`)", 6.0),
+            ("gcd(`0`, `5`)", 5.0),
+            ("lcm(`4`, `6`)", 12.0),
+            ("lcm(`-3`, `5`)", 15.0),
+            ("lcm(`0`, `5`)", 0.0),
+        ];
+        for (expr, expected) in cases {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.as_number().unwrap(), expected, "{}", expr);
+        }
+        let expr = runtime.compile("gcd(`1.5`, `3`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("is_prime(@)").unwrap();
+        for (n, expected) in [
+            ("2", true),
+            ("97", true),
+            ("1", false),
+            ("0", false),
+            ("-7", false),
+            ("7.5", false),
+            ("561", false),
+            ("2147483647", true),
+            ("9007199254740881", true),
+            ("18446744073709551557", true),
+        ] {
+            let data = Variable::from_json(n).unwrap();
+            let result = expr.search(&data).unwrap();
+            assert_eq!(result.as_boolean().unwrap(), expected, "{}", n);
+        }
+        // Floats beyond 2^53 have already been rounded
+        for n in ["1e17", "1e300"] {
+            let data = Variable::from_json(n).unwrap();
+            assert!(expr.search(&data).is_err(), "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_factorial_fibonacci() {
+        let runtime = setup_runtime();
+        let cases = [
+            ("factorial(`0`)", 1.0),
+            ("factorial(`5`)", 120.0),
+            ("factorial(`20`)", 2_432_902_008_176_640_000.0),
+            ("fibonacci(`0`)", 0.0),
+            ("fibonacci(`1`)", 1.0),
+            ("fibonacci(`10`)", 55.0),
+            ("fibonacci(`93`)", 12_200_160_415_121_876_738.0),
+        ];
+        for (expr, expected) in cases {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.as_number().unwrap(), expected, "{}", expr);
+        }
+        let result = runtime
+            .compile("factorial(`25`)")
+            .unwrap()
+            .search(&Variable::Null)
+            .unwrap();
+        assert!((result.as_number().unwrap() / 1.551_121_004_333_098_6e25 - 1.0).abs() < 1e-12);
+        for expr in ["factorial(`-1`)", "factorial(`171`)", "fibonacci(`2.5`)"] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(&Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_lerp_map_range() {
+        let runtime = setup_runtime();
+        let cases = [
+            ("lerp(`0`, `10`, `0.25`)", 2.5),
+            ("lerp(`10`, `20`, `1.5`)", 25.0),
+            ("map_range(`5`, `0`, `10`, `0`, `100`)", 50.0),
+            ("map_range(`75`, `0`, `100`, `1`, `5`)", 4.0),
+            ("map_range(`2`, `0`, `10`, `100`, `0`)", 80.0),
+        ];
+        for (expr, expected) in cases {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.as_number().unwrap(), expected, "{}", expr);
+        }
+        let expr = runtime
+            .compile("map_range(`1`, `3`, `3`, `0`, `1`)")
+            .unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_checked_arithmetic_is_exact() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"big": 9007199254740993}"#).unwrap();
+        let expr = runtime.compile("checked_add(big, `1`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.to_string(), "9007199254740994");
+        let expr = runtime.compile("checked_mul(`-6`, `7`)").unwrap();
+        assert_eq!(expr.search(&Variable::Null).unwrap().to_string(), "-42");
+        let expr = runtime.compile("checked_sub(`3`, `5`)").unwrap();
+        assert_eq!(expr.search(&Variable::Null).unwrap().to_string(), "-2");
+    }
+
+    #[test]
+    fn test_checked_arithmetic_errors() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(
+            r#"{"max": 9223372036854775807, "min": -9223372036854775808, "huge": 1e300}"#,
+        )
+        .unwrap();
+        for expr in [
+            "checked_add(max, `1`)",
+            "checked_sub(min, `1`)",
+            "checked_mul(max, `2`)",
+            "checked_add(`1.5`, `1`)",
+            "checked_add(huge, `1`)",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            let err = compiled.search(&data).unwrap_err();
+            assert!(err.to_string().contains("checked_"), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_checked_arithmetic_or_null() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"max": 9223372036854775807}"#).unwrap();
+        for expr in [
+            "checked_add_or_null(max, `1`)",
+            "checked_sub_or_null(`0.5`, `1`)",
+            "checked_mul_or_null(max, max)",
+        ] {
+            let result = runtime.compile(expr).unwrap().search(&data).unwrap();
+            assert!(result.is_null(), "{}", expr);
+        }
+        let result = runtime
+            .compile("checked_mul_or_null(`6`, `7`)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        assert_eq!(result.as_number().unwrap(), 42.0);
+    }
+
+    #[test]
+    fn test_describe() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("describe(@)").unwrap();
+        let data = Variable::from_json("[1, 2, null, 3, 4, \"x\", 5, null]").unwrap();
+        let result = expr.search(&data).unwrap();
+        let stats = result.as_object().unwrap();
+        let get = |k: &str| stats.get(k).unwrap().as_number().unwrap();
+        assert_eq!(get("count"), 5.0);
+        assert_eq!(get("nulls"), 2.0);
+        assert_eq!(get("min"), 1.0);
+        assert_eq!(get("max"), 5.0);
+        assert_eq!(get("mean"), 3.0);
+        assert_eq!(get("median"), 3.0);
+        assert_eq!(get("p25"), 2.0);
+        assert_eq!(get("p75"), 4.0);
+        assert!((get("stddev") - 2.0_f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_describe_matches_individual_functions() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[3.5, 1, 8, 2.25, 9, 4]").unwrap();
+        let describe = runtime
+            .compile("describe(@)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        for (key, expr) in [
+            ("median", "median(@)"),
+            ("stddev", "stddev(@)"),
+            ("p25", "percentile(@, `25`)"),
+            ("p75", "percentile(@, `75`)"),
+        ] {
+            let expected = runtime.compile(expr).unwrap().search(&data).unwrap();
+            let actual = describe.as_object().unwrap().get(key).unwrap();
+            assert!(
+                (actual.as_number().unwrap() - expected.as_number().unwrap()).abs() < 1e-12,
+                "{}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_describe_empty() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[null]").unwrap();
+        let result = runtime
+            .compile("describe(@)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        let stats = result.as_object().unwrap();
+        assert_eq!(stats.get("count").unwrap().as_number().unwrap(), 0.0);
+        assert_eq!(stats.get("nulls").unwrap().as_number().unwrap(), 1.0);
+        assert!(stats.get("mean").unwrap().is_null());
+    }
 }
@@ -16,7 +16,7 @@
 //! multi_match::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use aho_corasick::AhoCorasick;
 
@@ -16,13 +16,13 @@
 //! network::register(&mut runtime);
 //! ```
 
-use std::net::Ipv4Addr;
-use std::rc::Rc;
+use crate::common::Rc;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 
 use ipnetwork::{IpNetwork, Ipv4Network};
 
-use crate::common::Function;
+use crate::common::{ErrorReason, Function, custom_error, exact_integer};
 use crate::{ArgumentType, Context, JmespathError, Rcvar, Runtime, Signature, Variable};
 
 /// Register all network functions with the runtime.
@@ -34,6 +34,18 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("cidr_broadcast", Box::new(CidrBroadcastFn::new()));
     runtime.register_function("cidr_prefix", Box::new(CidrPrefixFn::new()));
     runtime.register_function("is_private_ip", Box::new(IsPrivateIpFn::new()));
+    runtime.register_function("cidr_split", Box::new(CidrSplitFn::new()));
+    runtime.register_function("cidr_supernet", Box::new(CidrSupernetFn::new()));
+    runtime.register_function("range_to_cidrs", Box::new(RangeToCidrsFn::new()));
+    runtime.register_function("cidr_overlaps", Box::new(CidrOverlapsFn::new()));
+    runtime.register_function("ipv6_expand", Box::new(Ipv6ExpandFn::new()));
+    runtime.register_function("ipv6_compress", Box::new(Ipv6CompressFn::new()));
+    runtime.register_function("is_ipv4_mapped", Box::new(IsIpv4MappedFn::new()));
+    runtime.register_function("ip_version", Box::new(IpVersionFn::new()));
+    runtime.register_function("ip_add", Box::new(IpAddFn::new()));
+    runtime.register_function("ip_diff", Box::new(IpDiffFn::new()));
+    runtime.register_function("cidr_hosts", Box::new(CidrHostsFn::new()));
+    runtime.register_function("ptr_name", Box::new(PtrNameFn::new()));
 }
 
 // =============================================================================
@@ -291,6 +303,651 @@ impl Function for IsPrivateIpFn {
     }
 }
 
+// =============================================================================
+// Address arithmetic helpers
+// IPv4 and IPv6 addresses are handled uniformly as integers plus a family
+// width (32 or 128 bits).
+// =============================================================================
+
+/// Largest number of subnets `cidr_split` will return.
+const MAX_SPLIT_SUBNETS: u128 = 65_536;
+
+/// Largest number of addresses `cidr_hosts` will return.
+const MAX_CIDR_HOSTS: u128 = 65_536;
+
+fn ip_to_u128(ip: IpAddr) -> (u128, u8) {
+    match ip {
+        IpAddr::V4(v4) => (u32::from(v4) as u128, 32),
+        IpAddr::V6(v6) => (u128::from(v6), 128),
+    }
+}
+
+fn u128_to_ip(value: u128, bits: u8) -> IpAddr {
+    if bits == 32 {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(value))
+    }
+}
+
+/// Number of addresses covered by `host_bits`, or `None` for the whole IPv6 space.
+fn block_size(host_bits: u8) -> Option<u128> {
+    1u128.checked_shl(host_bits as u32)
+}
+
+/// Last address of the block starting at `network`.
+fn block_end(network: u128, prefix: u8, bits: u8) -> u128 {
+    block_size(bits - prefix).map_or(u128::MAX, |size| network + (size - 1))
+}
+
+/// Parse a CIDR into its network address (host bits cleared), prefix and family width.
+fn parse_cidr(s: &str) -> Option<(u128, u8, u8)> {
+    let network = IpNetwork::from_str(s).ok()?;
+    let (value, bits) = ip_to_u128(network.network());
+    Some((value, network.prefix(), bits))
+}
+
+fn format_cidr(network: u128, prefix: u8, bits: u8) -> String {
+    format!("{}/{}", u128_to_ip(network, bits), prefix)
+}
+
+fn string_array(items: impl IntoIterator<Item = String>) -> Rcvar {
+    Rc::new(Variable::Array(
+        items
+            .into_iter()
+            .map(|s| Rc::new(Variable::String(s)))
+            .collect(),
+    ))
+}
+
+// =============================================================================
+// cidr_split(cidr, new_prefix) -> array
+// =============================================================================
+
+pub struct CidrSplitFn {
+    signature: Signature,
+}
+
+impl Default for CidrSplitFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CidrSplitFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Number], None),
+        }
+    }
+}
+
+impl Function for CidrSplitFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let cidr_str = args[0].as_string().unwrap();
+        let new_prefix = args[1].as_number().unwrap();
+
+        let Some((network, prefix, bits)) = parse_cidr(cidr_str) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+        if new_prefix.fract() != 0.0 || new_prefix < prefix as f64 || new_prefix > bits as f64 {
+            return Ok(Rc::new(Variable::Null));
+        }
+        let new_prefix = new_prefix as u8;
+
+        // A /0 split into /128s is 2^128 subnets, which does not fit in a u128
+        let count = 1u128
+            .checked_shl(u32::from(new_prefix - prefix))
+            .filter(|count| *count <= MAX_SPLIT_SUBNETS)
+            .ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse(format!(
+                        "cidr_split() would produce 2^{} subnets (limit {})",
+                        new_prefix - prefix,
+                        MAX_SPLIT_SUBNETS
+                    )),
+                )
+            })?;
+        crate::limits::check_array_len(ctx, count as usize)?;
+
+        // new_prefix > 0 whenever count > 1, so the step never overflows
+        let step = block_size(bits - new_prefix).unwrap_or(0);
+        Ok(string_array((0..count).map(|i| {
+            format_cidr(network + i * step, new_prefix, bits)
+        })))
+    }
+}
+
+// =============================================================================
+// cidr_supernet(cidrs) -> string
+// Smallest single CIDR covering every input (all of the same family).
+// =============================================================================
+
+pub struct CidrSupernetFn {
+    signature: Signature,
+}
+
+impl Default for CidrSupernetFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CidrSupernetFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for CidrSupernetFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let cidrs = args[0].as_array().unwrap();
+
+        let mut family = None;
+        let mut low = u128::MAX;
+        let mut high = 0u128;
+        for cidr in cidrs {
+            let Some((network, prefix, bits)) = cidr.as_string().and_then(|s| parse_cidr(s)) else {
+                return Ok(Rc::new(Variable::Null));
+            };
+            if *family.get_or_insert(bits) != bits {
+                return Ok(Rc::new(Variable::Null));
+            }
+            low = low.min(network);
+            high = high.max(block_end(network, prefix, bits));
+        }
+        let Some(bits) = family else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        // The supernet prefix is the run of leading bits shared by both ends
+        let differing = 128 - (low ^ high).leading_zeros() as u8;
+        let prefix = bits - differing;
+        let network = low & !(block_size(differing).map_or(u128::MAX, |size| size - 1));
+
+        Ok(Rc::new(Variable::String(format_cidr(
+            network, prefix, bits,
+        ))))
+    }
+}
+
+// =============================================================================
+// range_to_cidrs(start_ip, end_ip) -> array
+// Minimal list of CIDR blocks exactly covering an inclusive address range.
+// =============================================================================
+
+pub struct RangeToCidrsFn {
+    signature: Signature,
+}
+
+impl Default for RangeToCidrsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RangeToCidrsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for RangeToCidrsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (Ok(start_ip), Ok(end_ip)) = (
+            args[0].as_string().unwrap().parse::<IpAddr>(),
+            args[1].as_string().unwrap().parse::<IpAddr>(),
+        ) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+        let (mut start, bits) = ip_to_u128(start_ip);
+        let (end, end_bits) = ip_to_u128(end_ip);
+        if bits != end_bits || start > end {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        let mut blocks = Vec::new();
+        loop {
+            // Largest block that is aligned at `start` and does not pass `end`
+            let aligned = start.trailing_zeros().min(bits as u32) as u8;
+            let fits = match (end - start).checked_add(1) {
+                Some(remaining) => (127 - remaining.leading_zeros()) as u8,
+                None => 128,
+            };
+            let host_bits = aligned.min(fits);
+            blocks.push(format_cidr(start, bits - host_bits, bits));
+
+            match block_size(host_bits).and_then(|size| start.checked_add(size)) {
+                Some(next) if next <= end => start = next,
+                _ => break,
+            }
+        }
+
+        Ok(string_array(blocks))
+    }
+}
+
+// =============================================================================
+// cidr_overlaps(a, b) -> bool
+// =============================================================================
+
+pub struct CidrOverlapsFn {
+    signature: Signature,
+}
+
+impl Default for CidrOverlapsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CidrOverlapsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for CidrOverlapsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (Some(a), Some(b)) = (
+            parse_cidr(args[0].as_string().unwrap()),
+            parse_cidr(args[1].as_string().unwrap()),
+        ) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        let overlaps =
+            a.2 == b.2 && a.0 <= block_end(b.0, b.1, b.2) && b.0 <= block_end(a.0, a.1, a.2);
+        Ok(Rc::new(Variable::Bool(overlaps)))
+    }
+}
+
+// =============================================================================
+// IPv6 notation helpers
+// =============================================================================
+
+/// Parse an IPv6 address, keeping any zone suffix (e.g. `%eth0`) separate.
+fn parse_ipv6_with_zone(s: &str) -> Option<(Ipv6Addr, Option<&str>)> {
+    let (addr, zone) = match s.split_once('%') {
+        Some((addr, zone)) if !zone.is_empty() => (addr, Some(zone)),
+        Some(_) => return None,
+        None => (s, None),
+    };
+    Some((Ipv6Addr::from_str(addr).ok()?, zone))
+}
+
+fn with_zone(addr: String, zone: Option<&str>) -> String {
+    match zone {
+        Some(zone) => format!("{}%{}", addr, zone),
+        None => addr,
+    }
+}
+
+// =============================================================================
+// ipv6_expand(addr) -> string
+// =============================================================================
+
+pub struct Ipv6ExpandFn {
+    signature: Signature,
+}
+
+impl Default for Ipv6ExpandFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ipv6ExpandFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for Ipv6ExpandFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+
+        match parse_ipv6_with_zone(s) {
+            Some((addr, zone)) => {
+                let expanded = addr
+                    .segments()
+                    .iter()
+                    .map(|seg| format!("{:04x}", seg))
+                    .collect::<Vec<_>>()
+                    .join(":");
+                Ok(Rc::new(Variable::String(with_zone(expanded, zone))))
+            }
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// ipv6_compress(addr) -> string (RFC 5952 canonical form)
+// =============================================================================
+
+pub struct Ipv6CompressFn {
+    signature: Signature,
+}
+
+impl Default for Ipv6CompressFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ipv6CompressFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for Ipv6CompressFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+
+        // Ipv6Addr's Display already implements RFC 5952 (lowercase, longest
+        // zero run collapsed, dotted quad for IPv4-mapped addresses)
+        match parse_ipv6_with_zone(s) {
+            Some((addr, zone)) => Ok(Rc::new(Variable::String(with_zone(addr.to_string(), zone)))),
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// is_ipv4_mapped(addr) -> bool
+// =============================================================================
+
+pub struct IsIpv4MappedFn {
+    signature: Signature,
+}
+
+impl Default for IsIpv4MappedFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IsIpv4MappedFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for IsIpv4MappedFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+
+        match IpAddr::from_str(s) {
+            Ok(IpAddr::V6(addr)) => Ok(Rc::new(Variable::Bool(addr.to_ipv4_mapped().is_some()))),
+            Ok(IpAddr::V4(_)) => Ok(Rc::new(Variable::Bool(false))),
+            Err(_) => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// ip_version(addr) -> number (4 or 6)
+// =============================================================================
+
+pub struct IpVersionFn {
+    signature: Signature,
+}
+
+impl Default for IpVersionFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpVersionFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for IpVersionFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+
+        let version = match IpAddr::from_str(s) {
+            Ok(IpAddr::V4(_)) => 4,
+            Ok(IpAddr::V6(_)) => 6,
+            Err(_) if parse_ipv6_with_zone(s).is_some() => 6,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+        Ok(Rc::new(Variable::Number(serde_json::Number::from(version))))
+    }
+}
+
+// =============================================================================
+// ip_add(ip, n) -> string
+// =============================================================================
+
+pub struct IpAddFn {
+    signature: Signature,
+}
+
+impl Default for IpAddFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpAddFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Number], None),
+        }
+    }
+}
+
+impl Function for IpAddFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let n = exact_integer(&args[1])
+            .map_err(|msg| custom_error(ctx, &format!("ip_add(): offset {}", msg)))?;
+        let Ok(ip) = IpAddr::from_str(args[0].as_string().unwrap()) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        // Null when the result would fall outside the address family
+        let (value, bits) = ip_to_u128(ip);
+        let max = block_end(0, 0, bits);
+        let result = if n >= 0 {
+            value.checked_add(n as u128).filter(|v| *v <= max)
+        } else {
+            value.checked_sub(n.unsigned_abs() as u128)
+        };
+
+        Ok(result.map_or_else(
+            || Rc::new(Variable::Null),
+            |v| Rc::new(Variable::String(u128_to_ip(v, bits).to_string())),
+        ))
+    }
+}
+
+// =============================================================================
+// ip_diff(a, b) -> number (b - a)
+// =============================================================================
+
+pub struct IpDiffFn {
+    signature: Signature,
+}
+
+impl Default for IpDiffFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpDiffFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for IpDiffFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (Ok(a), Ok(b)) = (
+            IpAddr::from_str(args[0].as_string().unwrap()),
+            IpAddr::from_str(args[1].as_string().unwrap()),
+        ) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+        let ((a, a_bits), (b, b_bits)) = (ip_to_u128(a), ip_to_u128(b));
+        if a_bits != b_bits {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        let magnitude = a.abs_diff(b);
+        let number = match i64::try_from(magnitude) {
+            Ok(m) if b >= a => serde_json::Number::from(m),
+            Ok(m) => serde_json::Number::from(-m),
+            // Very large IPv6 distances can only be approximated
+            Err(_) => {
+                let approx = magnitude as f64;
+                serde_json::Number::from_f64(if b >= a { approx } else { -approx }).unwrap()
+            }
+        };
+        Ok(Rc::new(Variable::Number(number)))
+    }
+}
+
+// =============================================================================
+// cidr_hosts(cidr, limit) -> array
+// Usable host addresses: IPv4 blocks larger than /31 exclude the network and
+// broadcast addresses; IPv6 blocks include every address.
+// =============================================================================
+
+pub struct CidrHostsFn {
+    signature: Signature,
+}
+
+impl Default for CidrHostsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CidrHostsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Number], None),
+        }
+    }
+}
+
+impl Function for CidrHostsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let limit = args[1].as_number().unwrap();
+        let Some((network, prefix, bits)) = parse_cidr(args[0].as_string().unwrap()) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+        if limit < 0.0 {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        let (mut first, mut last) = (network, block_end(network, prefix, bits));
+        if bits == 32 && prefix < 31 {
+            first += 1;
+            last -= 1;
+        }
+
+        let count = (limit as u128).min((last - first).saturating_add(1));
+        if count > MAX_CIDR_HOSTS {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "cidr_hosts() would produce {} addresses (limit {})",
+                    count, MAX_CIDR_HOSTS
+                )),
+            ));
+        }
+        crate::limits::check_array_len(ctx, count as usize)?;
+
+        let hosts = (first..=last)
+            .take(count as usize)
+            .map(|v| u128_to_ip(v, bits).to_string());
+        Ok(string_array(hosts))
+    }
+}
+
+// =============================================================================
+// ptr_name(ip) -> string (reverse DNS name)
+// =============================================================================
+
+pub struct PtrNameFn {
+    signature: Signature,
+}
+
+impl Default for PtrNameFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PtrNameFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for PtrNameFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let name = match IpAddr::from_str(args[0].as_string().unwrap()) {
+            Ok(IpAddr::V4(v4)) => {
+                let [a, b, c, d] = v4.octets();
+                format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+            }
+            Ok(IpAddr::V6(v6)) => {
+                let nibbles: Vec<String> = format!("{:032x}", u128::from(v6))
+                    .chars()
+                    .rev()
+                    .map(String::from)
+                    .collect();
+                format!("{}.ip6.arpa", nibbles.join("."))
+            }
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+        Ok(Rc::new(Variable::String(name)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -406,4 +1063,269 @@ mod tests {
         let result = expr.search(&data).unwrap();
         assert!(!result.as_boolean().unwrap());
     }
+
+    fn strings(result: &Rcvar) -> Vec<String> {
+        result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_cidr_split() {
+        let runtime = setup();
+        let expr = runtime.compile("cidr_split('10.0.0.0/24', `26`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(
+            strings(&result),
+            vec![
+                "10.0.0.0/26",
+                "10.0.0.64/26",
+                "10.0.0.128/26",
+                "10.0.0.192/26"
+            ]
+        );
+
+        let expr = runtime
+            .compile("cidr_split('2001:db8::/32', `34`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(strings(&result)[3], "2001:db8:c000::/34");
+
+        let expr = runtime.compile("cidr_split('10.0.0.0/24', `16`)").unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+        let expr = runtime.compile("cidr_split('10.0.0.0/8', `32`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+        let expr = runtime.compile("cidr_split('::/0', `128`)").unwrap();
+        assert!(expr.search(&Variable::Null).is_err());
+    }
+
+    #[test]
+    fn test_cidr_supernet() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"["10.0.1.0/24", "10.0.2.0/24", "10.0.3.128/25"]"#).unwrap();
+        let expr = runtime.compile("cidr_supernet(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result.as_string().unwrap(), "10.0.0.0/22");
+
+        let data = Variable::from_json(r#"["10.0.0.0/24", "2001:db8::/32"]"#).unwrap();
+        assert!(expr.search(&data).unwrap().is_null());
+        let data = Variable::from_json(r#"["192.168.0.0/16"]"#).unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap().as_string().unwrap(),
+            "192.168.0.0/16"
+        );
+    }
+
+    #[test]
+    fn test_range_to_cidrs() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("range_to_cidrs('10.0.0.5', '10.0.0.20')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(
+            strings(&result),
+            vec![
+                "10.0.0.5/32",
+                "10.0.0.6/31",
+                "10.0.0.8/29",
+                "10.0.0.16/30",
+                "10.0.0.20/32"
+            ]
+        );
+
+        let expr = runtime
+            .compile("range_to_cidrs('0.0.0.0', '255.255.255.255')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(strings(&result), vec!["0.0.0.0/0"]);
+
+        let expr = runtime
+            .compile("range_to_cidrs('::', 'ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff')")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(strings(&result), vec!["::/0"]);
+
+        let expr = runtime
+            .compile("range_to_cidrs('10.0.0.9', '10.0.0.1')")
+            .unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_cidr_overlaps() {
+        let runtime = setup();
+        for (expr, expected) in [
+            ("cidr_overlaps('10.0.0.0/16', '10.0.5.0/24')", true),
+            ("cidr_overlaps('10.0.0.0/24', '10.0.1.0/24')", false),
+            ("cidr_overlaps('2001:db8::/32', '2001:db8:1::/48')", true),
+            ("cidr_overlaps('10.0.0.0/8', '::/0')", false),
+        ] {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.as_boolean().unwrap(), expected, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_ipv6_expand_compress() {
+        let runtime = setup();
+        for (expr, expected) in [
+            (
+                "ipv6_expand('2001:db8::1')",
+                "2001:0db8:0000:0000:0000:0000:0000:0001",
+            ),
+            (
+                "ipv6_expand('fe80::1%eth0')",
+                "fe80:0000:0000:0000:0000:0000:0000:0001%eth0",
+            ),
+            (
+                "ipv6_compress('2001:0DB8:0000:0000:0000:0000:0000:0001')",
+                "2001:db8::1",
+            ),
+            ("ipv6_compress('2001:db8:0:0:1:0:0:1')", "2001:db8::1:0:0:1"),
+            (
+                "ipv6_compress('0:0:0:0:0:ffff:c000:0280')",
+                "::ffff:192.0.2.128",
+            ),
+        ] {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.as_string().unwrap(), expected, "{}", expr);
+        }
+        let expr = runtime.compile("ipv6_expand('10.0.0.1')").unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_mixed_notation_equality() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("ipv6_compress('2001:DB8:0::0:1') == ipv6_compress('2001:db8::0001')")
+            .unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_is_ipv4_mapped_and_ip_version() {
+        let runtime = setup();
+        for (expr, expected) in [
+            ("is_ipv4_mapped('::ffff:10.0.0.1')", "true"),
+            ("is_ipv4_mapped('2001:db8::1')", "false"),
+            ("is_ipv4_mapped('10.0.0.1')", "false"),
+            ("is_ipv4_mapped('nope')", "null"),
+            ("ip_version('10.0.0.1')", "4"),
+            ("ip_version('::1')", "6"),
+            ("ip_version('fe80::1%eth0')", "6"),
+            ("ip_version('300.1.1.1')", "null"),
+        ] {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.to_string(), expected, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_ip_add_and_diff() {
+        let runtime = setup();
+        for (expr, expected) in [
+            ("ip_add('10.0.0.255', `1`)", r#""10.0.1.0""#),
+            ("ip_add('10.0.1.0', `-1`)", r#""10.0.0.255""#),
+            ("ip_add('2001:db8::ffff', `1`)", r#""2001:db8::1:0""#),
+            ("ip_add('255.255.255.255', `1`)", "null"),
+            ("ip_add('0.0.0.0', `-1`)", "null"),
+            ("ip_diff('10.0.0.1', '10.0.1.1')", "256"),
+            ("ip_diff('10.0.1.1', '10.0.0.1')", "-256"),
+            ("ip_diff('10.0.0.1', '::1')", "null"),
+        ] {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap();
+            assert_eq!(result.to_string(), expected, "{}", expr);
+        }
+        for expr in [
+            "ip_add('10.0.0.1', `1.5`)",
+            "ip_add('::', `1e20`)",
+            "ip_add('::', `-1e300`)",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(&Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_cidr_hosts() {
+        let runtime = setup();
+        let expr = runtime
+            .compile("cidr_hosts('192.168.1.0/29', `100`)")
+            .unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(
+            strings(&result),
+            vec![
+                "192.168.1.1",
+                "192.168.1.2",
+                "192.168.1.3",
+                "192.168.1.4",
+                "192.168.1.5",
+                "192.168.1.6"
+            ]
+        );
+
+        let expr = runtime.compile("cidr_hosts('10.0.0.0/8', `2`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(strings(&result), vec!["10.0.0.1", "10.0.0.2"]);
+
+        let expr = runtime.compile("cidr_hosts('10.0.0.4/31', `10`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(strings(&result), vec!["10.0.0.4", "10.0.0.5"]);
+
+        let expr = runtime.compile("cidr_hosts('2001:db8::/64', `2`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(strings(&result), vec!["2001:db8::", "2001:db8::1"]);
+
+        // Huge limits are capped by the block size, then by a fixed maximum
+        let expr = runtime
+            .compile("length(cidr_hosts('10.0.0.0/16', `1e12`))")
+            .unwrap();
+        assert_eq!(
+            expr.search(&Variable::Null).unwrap().as_number(),
+            Some(65534.0)
+        );
+        let expr = runtime
+            .compile("cidr_hosts('0.0.0.0/0', `4294967296`)")
+            .unwrap();
+        let err = expr.search(&Variable::Null).unwrap_err();
+        assert!(err.to_string().contains("limit 65536"), "{}", err);
+    }
+
+    #[test]
+    fn test_ptr_name() {
+        let runtime = setup();
+        let expr = runtime.compile("ptr_name('192.0.2.10')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "10.2.0.192.in-addr.arpa");
+
+        let expr = runtime.compile("ptr_name('2001:db8::567:89ab')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(
+            result.as_string().unwrap(),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+    }
 }
@@ -16,8 +16,8 @@
 //! object::register(&mut runtime);
 //! ```
 
-use std::collections::{BTreeMap, HashSet};
-use std::rc::Rc;
+use crate::common::Rc;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
@@ -36,13 +36,19 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("unflatten_keys", Box::new(UnflattenKeysFn::new()));
     runtime.register_function("deep_merge", Box::new(DeepMergeFn::new()));
     runtime.register_function("deep_equals", Box::new(DeepEqualsFn::new()));
+    runtime.register_function("deep_equal", Box::new(DeepEqualsFn::new()));
     runtime.register_function("deep_diff", Box::new(DeepDiffFn::new()));
+    runtime.register_function("deep_diff_paths", Box::new(DeepDiffPathsFn::new()));
     runtime.register_function("get", Box::new(GetFn::new()));
     runtime.register_function("has", Box::new(HasFn::new()));
     runtime.register_function("defaults", Box::new(DefaultsFn::new()));
     runtime.register_function("defaults_deep", Box::new(DefaultsDeepFn::new()));
     runtime.register_function("set_path", Box::new(SetPathFn::new()));
     runtime.register_function("delete_path", Box::new(DeletePathFn::new()));
+    runtime.register_function("pointer_get", Box::new(PointerGetFn::new()));
+    runtime.register_function("pointer_set", Box::new(PointerSetFn::new()));
+    runtime.register_function("pointer_remove", Box::new(PointerRemoveFn::new()));
+    runtime.register_function("pointer_exists", Box::new(PointerExistsFn::new()));
     runtime.register_function("paths", Box::new(PathsFn::new()));
     runtime.register_function("leaves", Box::new(LeavesFn::new()));
     runtime.register_function("leaves_with_paths", Box::new(LeavesWithPathsFn::new()));
@@ -288,32 +294,132 @@ impl Function for RenameKeysFn {
 }
 
 // =============================================================================
-// flatten_keys(object, separator?) -> object
+// flatten_keys(object, separator_or_options?) -> object
 // =============================================================================
 
 define_function!(
     FlattenKeysFn,
     vec![ArgumentType::Object],
-    Some(ArgumentType::String)
+    Some(ArgumentType::Any)
 );
 
-fn flatten_object(
-    obj: &BTreeMap<String, Rcvar>,
-    prefix: &str,
-    separator: &str,
+/// How arrays are treated by `flatten_keys` / `unflatten_keys`.
+#[derive(Clone, Copy, PartialEq)]
+enum ArrayKeyMode {
+    /// Arrays are leaf values (the default).
+    Keep,
+    /// Array indices become path segments: `a.0.b`.
+    Index,
+    /// Array indices use bracket notation: `a[0].b`.
+    Bracket,
+}
+
+/// Options shared by `flatten_keys` and `unflatten_keys` so the two round-trip.
+struct FlattenOptions {
+    separator: String,
+    arrays: ArrayKeyMode,
+    max_depth: Option<usize>,
+}
+
+impl FlattenOptions {
+    /// Parse the optional second argument: either a separator string or an
+    /// options object `{separator, arrays, max_depth}`.
+    fn from_args(args: &[Rcvar], ctx: &Context<'_>) -> Result<Self, JmespathError> {
+        let err = |msg: String| JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg));
+        let mut opts = FlattenOptions {
+            separator: ".".to_string(),
+            arrays: ArrayKeyMode::Keep,
+            max_depth: None,
+        };
+
+        match args.get(1).map(|v| v.as_ref()) {
+            None | Some(Variable::Null) => {}
+            Some(Variable::String(sep)) => opts.separator = sep.clone(),
+            Some(Variable::Object(map)) => {
+                if let Some(sep) = map.get("separator") {
+                    opts.separator = sep
+                        .as_string()
+                        .ok_or_else(|| err("separator must be a string".to_owned()))?
+                        .clone();
+                }
+                if let Some(mode) = map.get("arrays") {
+                    opts.arrays = match mode.as_string().map(|s| s.as_str()) {
+                        Some("keep") => ArrayKeyMode::Keep,
+                        Some("index") => ArrayKeyMode::Index,
+                        Some("bracket") => ArrayKeyMode::Bracket,
+                        _ => {
+                            return Err(err(
+                                "arrays must be one of 'keep', 'index', 'bracket'".to_owned()
+                            ));
+                        }
+                    };
+                }
+                if let Some(depth) = map.get("max_depth") {
+                    let depth = depth
+                        .as_number()
+                        .filter(|d| *d >= 1.0)
+                        .ok_or_else(|| err("max_depth must be a number >= 1".to_owned()))?;
+                    opts.max_depth = Some(depth as usize);
+                }
+            }
+            Some(_) => {
+                return Err(err("Expected separator string or options object".to_owned()));
+            }
+        }
+
+        if opts.separator.is_empty() {
+            return Err(err("separator must not be empty".to_owned()));
+        }
+        Ok(opts)
+    }
+
+    /// Escape a key so it survives a round trip: backslashes, the separator,
+    /// and (in bracket mode) `[` are prefixed with a backslash.
+    fn escape_key(&self, key: &str) -> String {
+        let mut escaped = key.replace('\\', "\\\\");
+        escaped = escaped.replace(&self.separator, &format!("\\{}", self.separator));
+        if self.arrays == ArrayKeyMode::Bracket {
+            escaped = escaped.replace('[', "\\[");
+        }
+        escaped
+    }
+}
+
+fn flatten_value(
+    value: &Rcvar,
+    prefix: Option<&str>,
+    depth: usize,
+    opts: &FlattenOptions,
     result: &mut BTreeMap<String, Rcvar>,
 ) {
-    for (k, v) in obj.iter() {
-        let new_key = if prefix.is_empty() {
-            k.clone()
-        } else {
-            format!("{}{}{}", prefix, separator, k)
-        };
+    let can_descend = prefix.is_none() || opts.max_depth.is_none_or(|max| depth < max);
 
-        if let Some(nested) = v.as_object() {
-            flatten_object(nested, &new_key, separator, result);
-        } else {
-            result.insert(new_key, v.clone());
+    match value.as_ref() {
+        Variable::Object(obj) if can_descend && (prefix.is_none() || !obj.is_empty()) => {
+            for (k, v) in obj.iter() {
+                let segment = opts.escape_key(k);
+                let new_key = match prefix {
+                    None => segment,
+                    Some(p) => format!("{}{}{}", p, opts.separator, segment),
+                };
+                flatten_value(v, Some(&new_key), depth + 1, opts, result);
+            }
+        }
+        Variable::Array(arr)
+            if can_descend && opts.arrays != ArrayKeyMode::Keep && !arr.is_empty() =>
+        {
+            // Top-level values are always objects, so prefix is set here
+            let p = prefix.unwrap_or_default();
+            for (idx, v) in arr.iter().enumerate() {
+                let new_key = match opts.arrays {
+                    ArrayKeyMode::Bracket => format!("{}[{}]", p, idx),
+                    _ => format!("{}{}{}", p, opts.separator, idx),
+                };
+                flatten_value(v, Some(&new_key), depth + 1, opts, result);
+            }
+        }
+        _ => {
+            result.insert(prefix.unwrap_or_default().to_string(), value.clone());
         }
     }
 }
@@ -322,58 +428,159 @@ impl Function for FlattenKeysFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
 
-        let obj = args[0].as_object().ok_or_else(|| {
-            JmespathError::new(
-                ctx.expression,
-                0,
-                ErrorReason::Parse("Expected object argument".to_owned()),
-            )
-        })?;
-
-        let default_sep = ".".to_string();
-        let separator = args
-            .get(1)
-            .and_then(|s| s.as_string().map(|s| s.to_string()))
-            .unwrap_or(default_sep);
+        let opts = FlattenOptions::from_args(args, ctx)?;
 
         let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
-        flatten_object(obj, "", &separator, &mut result);
+        flatten_value(&args[0], None, 0, &opts, &mut result);
 
         Ok(Rc::new(Variable::Object(result)))
     }
 }
 
 // =============================================================================
-// unflatten_keys(object, separator?) -> object
+// unflatten_keys(object, separator_or_options?) -> object
 // =============================================================================
 
 define_function!(
     UnflattenKeysFn,
     vec![ArgumentType::Object],
-    Some(ArgumentType::String)
+    Some(ArgumentType::Any)
 );
 
-fn insert_nested(obj: &mut BTreeMap<String, Rcvar>, parts: &[&str], value: Rcvar) {
-    if parts.is_empty() {
-        return;
+/// One segment of a flattened key.
+enum KeySegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Split a flattened key into segments, honoring backslash escapes and, in
+/// bracket mode, `[n]` index suffixes.
+fn split_flat_key(key: &str, opts: &FlattenOptions) -> Vec<KeySegment> {
+    let sep = opts.separator.as_str();
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut after_index = false;
+    let mut rest = key;
+
+    while !rest.is_empty() {
+        if let Some(escaped) = rest.strip_prefix('\\') {
+            if let Some(after) = escaped.strip_prefix(sep) {
+                current.push_str(sep);
+                rest = after;
+            } else if let Some(c) = escaped.chars().next() {
+                current.push(c);
+                rest = &escaped[c.len_utf8()..];
+            } else {
+                current.push('\\');
+                rest = escaped;
+            }
+            after_index = false;
+        } else if let Some(after) = rest.strip_prefix(sep) {
+            if !(after_index && current.is_empty()) {
+                segments.push(KeySegment::Key(std::mem::take(&mut current)));
+            }
+            after_index = false;
+            rest = after;
+        } else if let Some((idx, after)) = (opts.arrays == ArrayKeyMode::Bracket)
+            .then(|| parse_bracket_index(rest))
+            .flatten()
+        {
+            if !current.is_empty() || (!after_index && segments.is_empty()) {
+                segments.push(KeySegment::Key(std::mem::take(&mut current)));
+            }
+            segments.push(KeySegment::Index(idx));
+            after_index = true;
+            rest = after;
+        } else {
+            let c = rest.chars().next().unwrap();
+            current.push(c);
+            rest = &rest[c.len_utf8()..];
+            after_index = false;
+        }
+    }
+    if !(after_index && current.is_empty()) {
+        segments.push(KeySegment::Key(current));
     }
 
-    if parts.len() == 1 {
-        obj.insert(parts[0].to_string(), value);
-        return;
+    if opts.arrays == ArrayKeyMode::Index {
+        for segment in segments.iter_mut().skip(1) {
+            if let KeySegment::Key(k) = segment
+                && let Ok(idx) = k.parse::<usize>()
+            {
+                *segment = KeySegment::Index(idx);
+            }
+        }
     }
+    segments
+}
 
-    let key = parts[0].to_string();
-    let rest = &parts[1..];
+/// Parse a leading `[digits]`, returning the index and the remaining input.
+fn parse_bracket_index(s: &str) -> Option<(usize, &str)> {
+    let inner = s.strip_prefix('[')?;
+    let end = inner.find(']')?;
+    let digits = &inner[..end];
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((digits.parse().ok()?, &inner[end + 1..]))
+}
 
-    let nested = obj
-        .entry(key.clone())
-        .or_insert_with(|| Rc::new(Variable::Object(BTreeMap::new())));
+/// Intermediate tree used to rebuild nested values from flattened keys.
+enum UnflattenNode {
+    Leaf(Rcvar),
+    Map(BTreeMap<String, UnflattenNode>),
+    List(BTreeMap<usize, UnflattenNode>),
+}
 
-    if let Some(nested_obj) = nested.as_object() {
-        let mut new_obj = nested_obj.clone();
-        insert_nested(&mut new_obj, rest, value);
-        *obj.get_mut(&key).unwrap() = Rc::new(Variable::Object(new_obj));
+impl UnflattenNode {
+    fn insert(&mut self, segments: &[KeySegment], value: Rcvar) {
+        let Some((first, rest)) = segments.split_first() else {
+            *self = UnflattenNode::Leaf(value);
+            return;
+        };
+        let child = match first {
+            KeySegment::Key(k) => {
+                if !matches!(self, UnflattenNode::Map(_)) {
+                    *self = UnflattenNode::Map(BTreeMap::new());
+                }
+                let UnflattenNode::Map(map) = self else {
+                    unreachable!()
+                };
+                map.entry(k.clone())
+                    .or_insert_with(|| UnflattenNode::Map(BTreeMap::new()))
+            }
+            KeySegment::Index(i) => {
+                if !matches!(self, UnflattenNode::List(_)) {
+                    *self = UnflattenNode::List(BTreeMap::new());
+                }
+                let UnflattenNode::List(list) = self else {
+                    unreachable!()
+                };
+                list.entry(*i)
+                    .or_insert_with(|| UnflattenNode::Map(BTreeMap::new()))
+            }
+        };
+        child.insert(rest, value);
+    }
+
+    fn into_variable(self) -> Rcvar {
+        match self {
+            UnflattenNode::Leaf(v) => v,
+            UnflattenNode::Map(map) => Rc::new(Variable::Object(
+                map.into_iter()
+                    .map(|(k, node)| (k, node.into_variable()))
+                    .collect(),
+            )),
+            UnflattenNode::List(list) => {
+                let len = list.keys().next_back().map_or(0, |max| max + 1);
+                let mut arr: Vec<Rcvar> =
+                    std::iter::repeat_n(Rc::new(Variable::Null), len).collect();
+                for (i, node) in list {
+                    arr[i] = node.into_variable();
+                }
+                Rc::new(Variable::Array(arr))
+            }
+        }
     }
 }
 
@@ -389,57 +596,191 @@ impl Function for UnflattenKeysFn {
             )
         })?;
 
-        let default_sep = ".".to_string();
-        let separator = args
-            .get(1)
-            .and_then(|s| s.as_string().map(|s| s.to_string()))
-            .unwrap_or(default_sep);
-
-        let mut result: BTreeMap<String, Rcvar> = BTreeMap::new();
+        let opts = FlattenOptions::from_args(args, ctx)?;
 
+        let mut root = UnflattenNode::Map(BTreeMap::new());
         for (key, value) in obj.iter() {
-            let parts: Vec<&str> = key.split(&separator).collect();
-            insert_nested(&mut result, &parts, value.clone());
+            let segments = split_flat_key(key, &opts);
+            root.insert(&segments, value.clone());
         }
 
-        Ok(Rc::new(Variable::Object(result)))
+        Ok(root.into_variable())
     }
 }
 
 // =============================================================================
-// deep_merge(obj1, obj2) -> object
+// deep_merge(obj1, obj2, options?) -> object
 // =============================================================================
 
 define_function!(
     DeepMergeFn,
     vec![ArgumentType::Object, ArgumentType::Object],
-    None
+    Some(ArgumentType::Object)
 );
 
+/// How `deep_merge` combines two arrays found at the same key.
+enum ArrayMergeStrategy {
+    /// The overlay array wins (the default).
+    Replace,
+    /// Base elements followed by overlay elements.
+    Concat,
+    /// Like concat, but overlay elements already in the base are skipped.
+    Union,
+    /// Object elements with the same value for the key field are deep merged.
+    MergeByKey(String),
+}
+
+/// Parsed `deep_merge` options: a default array strategy plus per-field
+/// overrides keyed by the name of the object key holding the array.
+struct MergeOptions {
+    arrays: ArrayMergeStrategy,
+    fields: BTreeMap<String, ArrayMergeStrategy>,
+}
+
+impl MergeOptions {
+    fn parse(options: &BTreeMap<String, Rcvar>, ctx: &Context<'_>) -> Result<Self, JmespathError> {
+        let arrays = parse_array_strategy(options, ctx)?;
+        let mut fields = BTreeMap::new();
+        if let Some(field_opts) = options.get("fields") {
+            let field_opts = field_opts.as_object().ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("fields must be an object".to_owned()),
+                )
+            })?;
+            for (field, spec) in field_opts {
+                let strategy = match spec.as_ref() {
+                    Variable::String(_) => {
+                        let mut wrapped = BTreeMap::new();
+                        wrapped.insert("arrays".to_string(), spec.clone());
+                        parse_array_strategy(&wrapped, ctx)?
+                    }
+                    Variable::Object(obj) => parse_array_strategy(obj, ctx)?,
+                    _ => {
+                        return Err(JmespathError::new(
+                            ctx.expression,
+                            0,
+                            ErrorReason::Parse(format!(
+                                "fields.{} must be a strategy string or object",
+                                field
+                            )),
+                        ));
+                    }
+                };
+                fields.insert(field.clone(), strategy);
+            }
+        }
+        Ok(MergeOptions { arrays, fields })
+    }
+}
+
+/// Read `{arrays, key}` from an options object.
+fn parse_array_strategy(
+    options: &BTreeMap<String, Rcvar>,
+    ctx: &Context<'_>,
+) -> Result<ArrayMergeStrategy, JmespathError> {
+    let err = |msg: &str| JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg.to_owned()));
+    let Some(name) = options.get("arrays") else {
+        return Ok(ArrayMergeStrategy::Replace);
+    };
+    match name.as_string().map(|s| s.as_str()) {
+        Some("replace") => Ok(ArrayMergeStrategy::Replace),
+        Some("concat") => Ok(ArrayMergeStrategy::Concat),
+        Some("union") => Ok(ArrayMergeStrategy::Union),
+        Some("merge_by_key") => {
+            let key = options
+                .get("key")
+                .and_then(|k| k.as_string())
+                .ok_or_else(|| err("merge_by_key strategy requires a string key"))?;
+            Ok(ArrayMergeStrategy::MergeByKey(key.clone()))
+        }
+        _ => Err(err(
+            "arrays must be one of 'replace', 'concat', 'union', 'merge_by_key'",
+        )),
+    }
+}
+
 fn deep_merge_objects(
     base: &BTreeMap<String, Rcvar>,
     overlay: &BTreeMap<String, Rcvar>,
+    opts: &MergeOptions,
 ) -> BTreeMap<String, Rcvar> {
     let mut result = base.clone();
 
     for (key, overlay_value) in overlay {
-        if let Some(base_value) = result.get(key) {
-            if let (Some(base_obj), Some(overlay_obj)) =
-                (base_value.as_object(), overlay_value.as_object())
-            {
-                let merged = deep_merge_objects(base_obj, overlay_obj);
-                result.insert(key.clone(), Rc::new(Variable::Object(merged)));
-            } else {
-                result.insert(key.clone(), overlay_value.clone());
-            }
-        } else {
-            result.insert(key.clone(), overlay_value.clone());
-        }
+        let merged = match result.get(key) {
+            Some(base_value) => match (base_value.as_ref(), overlay_value.as_ref()) {
+                (Variable::Object(base_obj), Variable::Object(overlay_obj)) => Rc::new(
+                    Variable::Object(deep_merge_objects(base_obj, overlay_obj, opts)),
+                ),
+                (Variable::Array(base_arr), Variable::Array(overlay_arr)) => {
+                    let strategy = opts.fields.get(key).unwrap_or(&opts.arrays);
+                    Rc::new(Variable::Array(merge_arrays(
+                        base_arr,
+                        overlay_arr,
+                        strategy,
+                        opts,
+                    )))
+                }
+                _ => overlay_value.clone(),
+            },
+            None => overlay_value.clone(),
+        };
+        result.insert(key.clone(), merged);
     }
 
     result
 }
 
+fn merge_arrays(
+    base: &[Rcvar],
+    overlay: &[Rcvar],
+    strategy: &ArrayMergeStrategy,
+    opts: &MergeOptions,
+) -> Vec<Rcvar> {
+    match strategy {
+        ArrayMergeStrategy::Replace => overlay.to_vec(),
+        ArrayMergeStrategy::Concat => base.iter().chain(overlay).cloned().collect(),
+        ArrayMergeStrategy::Union => {
+            let mut result = base.to_vec();
+            for item in overlay {
+                if !result.contains(item) {
+                    result.push(item.clone());
+                }
+            }
+            result
+        }
+        ArrayMergeStrategy::MergeByKey(field) => {
+            let key_of = |item: &Rcvar| item.as_object().and_then(|o| o.get(field)).cloned();
+            let mut result = base.to_vec();
+            for item in overlay {
+                let position = key_of(item).and_then(|k| {
+                    result
+                        .iter()
+                        .position(|existing| key_of(existing).as_ref() == Some(&k))
+                });
+                match position {
+                    Some(idx) => {
+                        let (Some(base_obj), Some(overlay_obj)) =
+                            (result[idx].as_object(), item.as_object())
+                        else {
+                            unreachable!("keyed elements are objects");
+                        };
+                        result[idx] = Rc::new(Variable::Object(deep_merge_objects(
+                            base_obj,
+                            overlay_obj,
+                            opts,
+                        )));
+                    }
+                    None => result.push(item.clone()),
+                }
+            }
+            result
+        }
+    }
+}
+
 impl Function for DeepMergeFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
         self.signature.validate(args, ctx)?;
@@ -460,7 +801,17 @@ impl Function for DeepMergeFn {
             )
         })?;
 
-        let merged = deep_merge_objects(obj1, obj2);
+        let opts = match args.get(2).and_then(|o| o.as_object()) {
+            Some(options) => MergeOptions::parse(options, ctx)?,
+            None => MergeOptions {
+                arrays: ArrayMergeStrategy::Replace,
+                fields: BTreeMap::new(),
+            },
+        };
+
+        crate::limits::check_depth(ctx, &args[..2])?;
+
+        let merged = deep_merge_objects(obj1, obj2, &opts);
         Ok(Rc::new(Variable::Object(merged)))
     }
 }
@@ -575,6 +926,72 @@ impl Function for DeepDiffFn {
     }
 }
 
+// =============================================================================
+// deep_diff_paths(a, b) -> array of {path, op, left, right} differences
+// =============================================================================
+
+define_function!(
+    DeepDiffPathsFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    None
+);
+
+/// Walk two values in parallel, recording every leaf-level difference as a
+/// JSON pointer with the value on each side (null when the side is missing).
+///
+/// `op` tells a missing side (`added`/`removed`) apart from an explicit null.
+fn collect_diff_paths(
+    left: Option<&Rcvar>,
+    right: Option<&Rcvar>,
+    path: String,
+    diffs: &mut Vec<Rcvar>,
+) {
+    match (left.map(|v| v.as_ref()), right.map(|v| v.as_ref())) {
+        (Some(Variable::Object(a)), Some(Variable::Object(b))) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let escaped_key = key.replace('~', "~0").replace('/', "~1");
+                collect_diff_paths(
+                    a.get(key),
+                    b.get(key),
+                    format!("{}/{}", path, escaped_key),
+                    diffs,
+                );
+            }
+        }
+        (Some(Variable::Array(a)), Some(Variable::Array(b))) => {
+            for idx in 0..a.len().max(b.len()) {
+                collect_diff_paths(a.get(idx), b.get(idx), format!("{}/{}", path, idx), diffs);
+            }
+        }
+        _ => {
+            let op = match (left, right) {
+                (Some(l), Some(r)) if l == r => return,
+                (Some(_), Some(_)) => "changed",
+                (Some(_), None) => "removed",
+                (None, _) => "added",
+            };
+            let null = || Rc::new(Variable::Null);
+            let mut entry: BTreeMap<String, Rcvar> = BTreeMap::new();
+            entry.insert("path".to_string(), Rc::new(Variable::String(path)));
+            entry.insert("op".to_string(), Rc::new(Variable::String(op.to_string())));
+            entry.insert("left".to_string(), left.cloned().unwrap_or_else(null));
+            entry.insert("right".to_string(), right.cloned().unwrap_or_else(null));
+            diffs.push(Rc::new(Variable::Object(entry)));
+        }
+    }
+}
+
+impl Function for DeepDiffPathsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let mut diffs = Vec::new();
+        collect_diff_paths(Some(&args[0]), Some(&args[1]), String::new(), &mut diffs);
+        Ok(Rc::new(Variable::Array(diffs)))
+    }
+}
+
 // =============================================================================
 // get(object, path, default?) -> value at path or default
 // =============================================================================
@@ -1124,6 +1541,189 @@ fn delete_at_path(value: &Rcvar, parts: &[String]) -> Rcvar {
     }
 }
 
+// =============================================================================
+// pointer_get(value, pointer) -> value at RFC 6901 JSON pointer, or null
+// pointer_exists(value, pointer) -> boolean
+// pointer_set(value, pointer, new) -> new value with pointer target replaced
+// pointer_remove(value, pointer) -> new value with pointer target removed
+// =============================================================================
+
+/// Parse a JSON pointer strictly per RFC 6901.
+///
+/// Unlike `parse_json_pointer`, a non-empty pointer must start with `/`, and
+/// `"/"` addresses the empty-string key rather than the whole document.
+fn parse_strict_pointer(pointer: &str, ctx: &Context<'_>) -> Result<Vec<String>, JmespathError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let rest = pointer.strip_prefix('/').ok_or_else(|| {
+        JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "Invalid JSON pointer '{}': must be empty or start with '/'",
+                pointer
+            )),
+        )
+    })?;
+    Ok(rest
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Parse an RFC 6901 array index token (no sign, no leading zeros).
+fn parse_pointer_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn resolve_pointer(value: &Rcvar, parts: &[String]) -> Option<Rcvar> {
+    let mut current = value.clone();
+    for part in parts {
+        let next = match current.as_ref() {
+            Variable::Object(obj) => obj.get(part).cloned(),
+            Variable::Array(arr) => parse_pointer_index(part).and_then(|i| arr.get(i).cloned()),
+            _ => None,
+        }?;
+        current = next;
+    }
+    Some(current)
+}
+
+fn pointer_arg(args: &[Rcvar], ctx: &Context<'_>) -> Result<Vec<String>, JmespathError> {
+    let pointer = args[1].as_string().ok_or_else(|| {
+        JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse("Expected string pointer argument".to_owned()),
+        )
+    })?;
+    parse_strict_pointer(pointer, ctx)
+}
+
+define_function!(
+    PointerGetFn,
+    vec![ArgumentType::Any, ArgumentType::String],
+    None
+);
+
+impl Function for PointerGetFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let parts = pointer_arg(args, ctx)?;
+        Ok(resolve_pointer(&args[0], &parts).unwrap_or_else(|| Rc::new(Variable::Null)))
+    }
+}
+
+define_function!(
+    PointerExistsFn,
+    vec![ArgumentType::Any, ArgumentType::String],
+    None
+);
+
+impl Function for PointerExistsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let parts = pointer_arg(args, ctx)?;
+        Ok(Rc::new(Variable::Bool(
+            resolve_pointer(&args[0], &parts).is_some(),
+        )))
+    }
+}
+
+define_function!(
+    PointerSetFn,
+    vec![ArgumentType::Any, ArgumentType::String, ArgumentType::Any],
+    None
+);
+
+impl Function for PointerSetFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let parts = pointer_arg(args, ctx)?;
+        pointer_set_at(&args[0], &parts, args[2].clone(), ctx)
+    }
+}
+
+/// Set the pointer target, indexing arrays per RFC 6901.
+///
+/// Array tokens must be an existing index, or the array length or `"-"` as
+/// the final token to append, as in JSON Patch `add`.
+fn pointer_set_at(
+    value: &Rcvar,
+    parts: &[String],
+    new_value: Rcvar,
+    ctx: &Context<'_>,
+) -> Result<Rcvar, JmespathError> {
+    let Some((token, remaining)) = parts.split_first() else {
+        return Ok(new_value);
+    };
+    let Variable::Array(arr) = value.as_ref() else {
+        // Missing keys and scalars are replaced by objects, as in set_path
+        let mut obj = match value.as_ref() {
+            Variable::Object(obj) => obj.clone(),
+            _ => BTreeMap::new(),
+        };
+        let child = obj
+            .get(token)
+            .cloned()
+            .unwrap_or_else(|| Rc::new(Variable::Null));
+        obj.insert(
+            token.clone(),
+            pointer_set_at(&child, remaining, new_value, ctx)?,
+        );
+        return Ok(Rc::new(Variable::Object(obj)));
+    };
+
+    let index = if token == "-" {
+        Some(arr.len())
+    } else {
+        parse_pointer_index(token)
+    };
+    let mut new_arr = arr.clone();
+    match index {
+        Some(i) if i < arr.len() => {
+            new_arr[i] = pointer_set_at(&arr[i], remaining, new_value, ctx)?;
+        }
+        Some(i) if i == arr.len() && remaining.is_empty() => new_arr.push(new_value),
+        _ => {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "pointer_set: invalid index '{}' for array of length {}",
+                    token,
+                    arr.len()
+                )),
+            ));
+        }
+    }
+    Ok(Rc::new(Variable::Array(new_arr)))
+}
+
+define_function!(
+    PointerRemoveFn,
+    vec![ArgumentType::Any, ArgumentType::String],
+    None
+);
+
+impl Function for PointerRemoveFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let parts = pointer_arg(args, ctx)?;
+        if resolve_pointer(&args[0], &parts).is_none() {
+            return Ok(args[0].clone());
+        }
+        Ok(delete_at_path(&args[0], &parts))
+    }
+}
+
 // =============================================================================
 // paths(value) -> array of all JSON pointer paths in the value
 // =============================================================================
@@ -1382,6 +1982,198 @@ mod tests {
         assert!(result_obj.contains_key("a"));
     }
 
+    #[test]
+    fn test_flatten_keys_default() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": {"c": 1}, "d": [1, 2]}, "e": {}}"#).unwrap();
+        let expr = runtime.compile("flatten_keys(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"{"a.b.c": 1, "a.d": [1, 2], "e": {}}"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_flatten_keys_separator_string() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": 1}}"#).unwrap();
+        let expr = runtime.compile("flatten_keys(@, '/')").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.as_object().unwrap().contains_key("a/b"));
+    }
+
+    #[test]
+    fn test_flatten_keys_array_modes() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": [{"b": 1}, 2]}"#).unwrap();
+
+        let expr = runtime
+            .compile("flatten_keys(@, {arrays: 'index'})")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"{"a.0.b": 1, "a.1": 2}"#).unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime
+            .compile("flatten_keys(@, {arrays: 'bracket'})")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"{"a[0].b": 1, "a[1]": 2}"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_flatten_keys_max_depth() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": {"c": 1}}, "x": 1}"#).unwrap();
+        let expr = runtime
+            .compile("flatten_keys(@, {max_depth: `2`})")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"{"a.b": {"c": 1}, "x": 1}"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_flatten_keys_escapes_separator() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"example.com": {"port": 80}}"#).unwrap();
+        let expr = runtime.compile("flatten_keys(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(
+            result
+                .as_object()
+                .unwrap()
+                .contains_key(r"example\.com.port")
+        );
+    }
+
+    #[test]
+    fn test_unflatten_keys_basic() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a.b": 1, "a.c": 2, "d": 3}"#).unwrap();
+        let expr = runtime.compile("unflatten_keys(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"{"a": {"b": 1, "c": 2}, "d": 3}"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_flatten_unflatten_roundtrip() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(
+            r#"{"hosts": {"example.com": {"tags": ["a", "b"], "ports": [{"n": 80}]}},
+                "back\\slash": 1, "br[0]": {"x": []}}"#,
+        )
+        .unwrap();
+        for opts in [
+            "`null`",
+            "'::'",
+            "{arrays: 'index'}",
+            "{arrays: 'bracket'}",
+            "{arrays: 'bracket', separator: '/', max_depth: `3`}",
+        ] {
+            let expr = runtime
+                .compile(&format!("unflatten_keys(flatten_keys(@, {0}), {0})", opts))
+                .unwrap();
+            let result = expr.search(&data).unwrap();
+            assert_eq!(*result, data, "round trip failed for {}", opts);
+        }
+    }
+
+    #[test]
+    fn test_flatten_keys_invalid_options() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": 1}"#).unwrap();
+        for opts in ["{arrays: 'nope'}", "{max_depth: `0`}", "`5`", "''"] {
+            let expr = runtime
+                .compile(&format!("flatten_keys(@, {})", opts))
+                .unwrap();
+            assert!(expr.search(&data).is_err(), "expected error for {}", opts);
+        }
+    }
+
+    #[test]
+    fn test_deep_merge_default_replaces_arrays() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(
+            r#"{"a": {"x": {"n": 1}, "l": [1, 2]}, "b": {"x": {"m": 2}, "l": [3]}}"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("deep_merge(a, b)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(r#"{"x": {"m": 2, "n": 1}, "l": [3]}"#).unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_deep_merge_array_strategies() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"l": [1, 2]}, "b": {"l": [2, 3]}}"#).unwrap();
+        let cases = [
+            ("replace", "[2, 3]"),
+            ("concat", "[1, 2, 2, 3]"),
+            ("union", "[1, 2, 3]"),
+        ];
+        for (strategy, expected) in cases {
+            let expr = runtime
+                .compile(&format!("deep_merge(a, b, {{arrays: '{}'}}).l", strategy))
+                .unwrap();
+            let result = expr.search(&data).unwrap();
+            assert_eq!(
+                *result,
+                Variable::from_json(expected).unwrap(),
+                "{}",
+                strategy
+            );
+        }
+    }
+
+    #[test]
+    fn test_deep_merge_by_key_per_field() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(
+            r#"{
+                "base": {"containers": [{"name": "app", "image": "v1", "env": ["A"]}, {"name": "side", "image": "s1"}], "args": ["x"]},
+                "patch": {"containers": [{"name": "app", "image": "v2", "env": ["B"]}, {"name": "new", "image": "n1"}], "args": ["y"]}
+            }"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile(
+                "deep_merge(base, patch, {arrays: 'concat', fields: {containers: {arrays: 'merge_by_key', key: 'name'}}})",
+            )
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{
+                "containers": [
+                    {"name": "app", "image": "v2", "env": ["A", "B"]},
+                    {"name": "side", "image": "s1"},
+                    {"name": "new", "image": "n1"}
+                ],
+                "args": ["x", "y"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_deep_merge_invalid_options() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {}, "b": {}}"#).unwrap();
+        for opts in [
+            "{arrays: 'zip'}",
+            "{arrays: 'merge_by_key'}",
+            "{fields: {l: `1`}}",
+        ] {
+            let expr = runtime
+                .compile(&format!("deep_merge(a, b, {})", opts))
+                .unwrap();
+            assert!(expr.search(&data).is_err(), "expected error for {}", opts);
+        }
+    }
+
     #[test]
     fn test_deep_equals_objects() {
         let runtime = setup_runtime();
@@ -1501,6 +2293,69 @@ mod tests {
         assert!(diff.get("changed").unwrap().as_object().unwrap().is_empty());
     }
 
+    #[test]
+    fn test_deep_equal_alias() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"x": [1, 2]}, "b": {"x": [1, 2]}}"#).unwrap();
+        let expr = runtime.compile("deep_equal(a, b)").unwrap();
+        assert!(expr.search(&data).unwrap().as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_deep_diff_paths() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(
+            r#"{"a": {"x": 1, "y": [1, 2], "z": "same", "a/b": 1},
+                "b": {"x": 2, "y": [1], "z": "same", "new": true}}"#,
+        )
+        .unwrap();
+        let expr = runtime.compile("deep_diff_paths(a, b)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"[
+                {"path": "/a~1b", "op": "removed", "left": 1, "right": null},
+                {"path": "/new", "op": "added", "left": null, "right": true},
+                {"path": "/x", "op": "changed", "left": 1, "right": 2},
+                {"path": "/y/1", "op": "removed", "left": 2, "right": null}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_deep_diff_paths_null_vs_missing() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"k": null}, "b": {}}"#).unwrap();
+        let expr = runtime.compile("deep_diff_paths(a, b)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"[{"path": "/k", "op": "removed", "left": null, "right": null}]"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+
+        let expr = runtime.compile("deep_diff_paths(b, a)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let entry = result.as_array().unwrap()[0].as_object().unwrap();
+        assert_eq!(entry.get("op").unwrap().as_string().unwrap(), "added");
+    }
+
+    #[test]
+    fn test_deep_diff_paths_scalars() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": 1, "b": "1"}"#).unwrap();
+        let expr = runtime.compile("deep_diff_paths(a, b)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 1);
+        let entry = arr[0].as_object().unwrap();
+        assert_eq!(entry.get("path").unwrap().as_string().unwrap(), "");
+
+        let expr = runtime.compile("deep_diff_paths(a, a)").unwrap();
+        assert!(expr.search(&data).unwrap().as_array().unwrap().is_empty());
+    }
+
     #[test]
     fn test_get_nested() {
         let runtime = setup_runtime();
@@ -1712,6 +2567,120 @@ mod tests {
         assert_eq!(items[1].as_number().unwrap(), 3.0);
     }
 
+    #[test]
+    fn test_pointer_get() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": [10, 20]}, "x/y": 1, "": 2}"#).unwrap();
+        let get = |p: &str| {
+            runtime
+                .compile(&format!("pointer_get(@, '{}')", p))
+                .unwrap()
+                .search(&data)
+                .unwrap()
+        };
+        assert_eq!(get("/a/b/1").as_number().unwrap(), 20.0);
+        assert_eq!(get("/x~1y").as_number().unwrap(), 1.0);
+        assert_eq!(get("/").as_number().unwrap(), 2.0);
+        assert!(get("").is_object());
+        assert!(get("/a/b/5").is_null());
+        assert!(get("/a/b/01").is_null());
+        assert!(get("/missing").is_null());
+    }
+
+    #[test]
+    fn test_pointer_get_invalid_pointer() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": 1}"#).unwrap();
+        let expr = runtime.compile("pointer_get(@, 'a')").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_pointer_exists() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": null}}"#).unwrap();
+        let expr = runtime.compile("pointer_exists(@, '/a/b')").unwrap();
+        assert!(expr.search(&data).unwrap().as_boolean().unwrap());
+        let expr = runtime.compile("pointer_exists(@, '/a/c')").unwrap();
+        assert!(!expr.search(&data).unwrap().as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_pointer_set() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": [1, 2]}}"#).unwrap();
+        let expr = runtime.compile("pointer_set(@, '/a/b/0', `5`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"{"a": {"b": [5, 2]}}"#).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_pointer_set_append() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": [1, 2]}"#).unwrap();
+        let expr = runtime.compile("pointer_set(@, '/a/-', `3`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"{"a": [1, 2, 3]}"#).unwrap())
+        );
+        let expr = runtime.compile("pointer_set(@, '/a/2', `3`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"{"a": [1, 2, 3]}"#).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_pointer_set_invalid_index() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": [1, 2]}"#).unwrap();
+        for pointer in ["/a/3", "/a/01", "/a/+1", "/a/x", "/a/-/b", "/a/2/b"] {
+            let expr = runtime
+                .compile(&format!("pointer_set(@, '{}', `3`)", pointer))
+                .unwrap();
+            assert!(expr.search(&data).is_err(), "{}", pointer);
+        }
+    }
+
+    #[test]
+    fn test_pointer_remove() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": {"b": 1, "c": 2}, "d": [1, 2]}"#).unwrap();
+        let expr = runtime.compile("pointer_remove(@, '/a/b')").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result,
+            Rc::new(Variable::from_json(r#"{"a": {"c": 2}, "d": [1, 2]}"#).unwrap())
+        );
+        let expr = runtime.compile("pointer_remove(@, '/d/0')").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result
+                .as_object()
+                .unwrap()
+                .get("d")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_pointer_remove_missing_is_noop() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"a": 1}"#).unwrap();
+        let expr = runtime.compile("pointer_remove(@, '/b/c')").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(result, Rc::new(data));
+    }
+
     #[test]
     fn test_paths_basic() {
         let runtime = setup_runtime();
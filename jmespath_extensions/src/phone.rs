@@ -0,0 +1,243 @@
+//! Phone number parsing and formatting functions.
+//!
+//! This module provides phone functions for JMESPath queries, backed by the
+//! [`phonenumber`](https://docs.rs/phonenumber) port of Google's libphonenumber
+//! metadata. Numbers written without a leading `+` need a default region
+//! (an ISO 3166-1 alpha-2 code such as `"US"` or `"GB"`) to be parsed.
+//!
+//! For complete function reference with signatures and examples, see the
+//! [`functions`](crate::functions) module documentation or use `jpx --list-category phone`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jmespath::{Runtime, Variable};
+//! use jmespath_extensions::phone;
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! phone::register(&mut runtime);
+//!
+//! let expr = runtime.compile("parse_phone('(201) 555-0123', 'US').e164").unwrap();
+//! let result = expr.search(&Variable::Null).unwrap();
+//! assert_eq!(result.as_string().unwrap(), "+12015550123");
+//! ```
+
+use crate::common::Rc;
+use std::collections::BTreeMap;
+
+use phonenumber::{Mode, PhoneNumber, country};
+
+use crate::common::{ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Variable};
+use crate::define_function;
+use jmespath::Runtime;
+
+/// Register all phone functions with the runtime.
+pub fn register(runtime: &mut Runtime) {
+    runtime.register_function("parse_phone", Box::new(ParsePhoneFn::new()));
+    runtime.register_function("format_phone", Box::new(FormatPhoneFn::new()));
+}
+
+fn phone_error(ctx: &Context<'_>, msg: String) -> JmespathError {
+    JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg))
+}
+
+/// Parse an optional default region argument ("US", "gb", ...).
+fn region_arg(
+    value: Option<&Rcvar>,
+    ctx: &Context<'_>,
+) -> Result<Option<country::Id>, JmespathError> {
+    match value.map(|v| v.as_ref()) {
+        None | Some(Variable::Null) => Ok(None),
+        Some(Variable::String(s)) => s
+            .trim()
+            .to_uppercase()
+            .parse()
+            .map(Some)
+            .map_err(|_| phone_error(ctx, format!("Unknown region '{}'", s))),
+        Some(_) => Err(phone_error(ctx, "Region must be a string".to_owned())),
+    }
+}
+
+fn parse_number(text: &str, region: Option<country::Id>) -> Option<PhoneNumber> {
+    phonenumber::parse(region, text).ok()
+}
+
+// =============================================================================
+// parse_phone(number, default_region?) -> object
+// Returns {country, national, e164, valid}, where `country` is the ISO region
+// the number belongs to (null for non-geographic numbers). Unparseable input
+// returns null; `valid` reports whether the number matches the region's plan.
+// =============================================================================
+
+define_function!(
+    ParsePhoneFn,
+    vec![ArgumentType::String],
+    Some(ArgumentType::Any)
+);
+
+impl Function for ParsePhoneFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let region = region_arg(args.get(1), ctx)?;
+        let Some(number) = parse_number(args[0].as_string().unwrap(), region) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        let country = number.country().id().map_or(Variable::Null, |id| {
+            Variable::String(id.as_ref().to_string())
+        });
+
+        let mut map = BTreeMap::new();
+        map.insert("country".to_string(), Rc::new(country));
+        map.insert(
+            "national".to_string(),
+            Rc::new(Variable::String(number.national().to_string())),
+        );
+        map.insert(
+            "e164".to_string(),
+            Rc::new(Variable::String(
+                number.format().mode(Mode::E164).to_string(),
+            )),
+        );
+        map.insert(
+            "valid".to_string(),
+            Rc::new(Variable::Bool(phonenumber::is_valid(&number))),
+        );
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
+// =============================================================================
+// format_phone(number, style, default_region?) -> string
+// Styles: e164, international, national, rfc3966. Unparseable input returns null.
+// =============================================================================
+
+define_function!(
+    FormatPhoneFn,
+    vec![ArgumentType::String, ArgumentType::String],
+    Some(ArgumentType::Any)
+);
+
+impl Function for FormatPhoneFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let style = args[1].as_string().unwrap();
+        let mode = match style.to_lowercase().as_str() {
+            "e164" => Mode::E164,
+            "international" => Mode::International,
+            "national" => Mode::National,
+            "rfc3966" => Mode::Rfc3966,
+            _ => {
+                return Err(phone_error(
+                    ctx,
+                    format!(
+                        "Unknown phone format '{}': expected e164, international, national or rfc3966",
+                        style
+                    ),
+                ));
+            }
+        };
+
+        let region = region_arg(args.get(2), ctx)?;
+        match parse_number(args[0].as_string().unwrap(), region) {
+            Some(number) => Ok(Rc::new(Variable::String(
+                number.format().mode(mode).to_string(),
+            ))),
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    fn eval(expr: &str) -> Rcvar {
+        let runtime = setup_runtime();
+        runtime
+            .compile(expr)
+            .unwrap()
+            .search(Variable::Null)
+            .unwrap()
+    }
+
+    fn field(result: &Rcvar, key: &str) -> Rcvar {
+        result.as_object().unwrap().get(key).unwrap().clone()
+    }
+
+    #[test]
+    fn test_parse_phone_with_region() {
+        let result = eval("parse_phone('(201) 555-0123', 'US')");
+        assert_eq!(field(&result, "country").as_string().unwrap(), "US");
+        assert_eq!(
+            field(&result, "national").as_string().unwrap(),
+            "2015550123"
+        );
+        assert_eq!(field(&result, "e164").as_string().unwrap(), "+12015550123");
+        assert!(field(&result, "valid").as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_parse_phone_international() {
+        let result = eval("parse_phone('+44 20 7946 0958')");
+        assert_eq!(field(&result, "country").as_string().unwrap(), "GB");
+        assert_eq!(field(&result, "e164").as_string().unwrap(), "+442079460958");
+    }
+
+    #[test]
+    fn test_parse_phone_invalid() {
+        assert!(eval("parse_phone('not a number', 'US')").is_null());
+        let result = eval("parse_phone('+1 555 0000', 'US')");
+        assert!(!field(&result, "valid").as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_format_phone_styles() {
+        assert_eq!(
+            eval("format_phone('+12015550123', 'national')")
+                .as_string()
+                .unwrap(),
+            "(201) 555-0123"
+        );
+        assert_eq!(
+            eval("format_phone('+12015550123', 'international')")
+                .as_string()
+                .unwrap(),
+            "+1 201-555-0123"
+        );
+        assert_eq!(
+            eval("format_phone('020 7946 0958', 'e164', 'gb')")
+                .as_string()
+                .unwrap(),
+            "+442079460958"
+        );
+        assert_eq!(
+            eval("format_phone('+12015550123', 'rfc3966')")
+                .as_string()
+                .unwrap(),
+            "tel:+1-201-555-0123"
+        );
+    }
+
+    #[test]
+    fn test_format_phone_errors() {
+        let runtime = setup_runtime();
+        for expr in [
+            "format_phone('+12015550123', 'fancy')",
+            "parse_phone('2015550123', 'ZZ')",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
+}
@@ -0,0 +1,553 @@
+//! Load extension functions from WebAssembly modules.
+//!
+//! A plugin is a `.wasm` module that describes the functions it provides in a
+//! JSON manifest and implements each one as an exported entrypoint. Loaded
+//! functions are registered on a [`Runtime`] like any other extension, and
+//! their metadata can be added to a [`FunctionRegistry`] so they are listed
+//! and described alongside the built-in functions.
+//!
+//! This feature is opt-in (not in `full`) because it runs third-party code.
+//! Plugins execute in the `wasmi` interpreter with no imports, so they cannot
+//! reach the filesystem, network or host memory. Each call is metered: it
+//! fails once it has executed [`MAX_FUEL_PER_CALL`] units of fuel, and a
+//! plugin's linear memory cannot grow beyond [`MAX_MEMORY_BYTES`].
+//!
+//! # Plugin ABI
+//!
+//! Strings cross the boundary as UTF-8 JSON in the plugin's linear memory. A
+//! `(ptr, len)` pair returned to the host is packed into an `i64` as
+//! `(ptr << 32) | len`. A plugin exports:
+//!
+//! - `memory`: its linear memory
+//! - `jmespath_alloc(len: i32) -> i32`: reserve `len` bytes for the host to
+//!   write arguments into
+//! - `jmespath_manifest() -> i64`: the manifest, for example
+//!
+//! ```json
+//! {"functions": [{
+//!     "name": "acme_score",
+//!     "export": "score",
+//!     "args": ["object"],
+//!     "variadic": "number",
+//!     "signature": "object, ...number -> number",
+//!     "description": "Score an account",
+//!     "example": "acme_score(account) -> 42"
+//! }]}
+//! ```
+//!
+//!   `args` and `variadic` use the type names `any`, `null`, `string`,
+//!   `number`, `boolean`, `array` and `object`, and are validated by the host
+//!   before the plugin is called. Only `name` and `export` are required.
+//! - one `(ptr: i32, len: i32) -> i64` entrypoint per function, called with
+//!   the arguments as a JSON array and returning `{"ok": <result>}` or
+//!   `{"error": "<message>"}`
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jmespath::Runtime;
+//! use jmespath_extensions::plugins::Plugin;
+//! use jmespath_extensions::registry::FunctionRegistry;
+//!
+//! let plugin = Plugin::from_file("acme_functions.wasm").unwrap();
+//!
+//! let mut runtime = Runtime::new();
+//! runtime.register_builtin_functions();
+//! jmespath_extensions::register_all(&mut runtime);
+//! plugin.register(&mut runtime);
+//!
+//! let mut registry = FunctionRegistry::new();
+//! registry.register_all();
+//! plugin.register_metadata(&mut registry);
+//! ```
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use wasmi::{
+    Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+};
+
+use crate::common::{
+    ArgumentType, Context, Function, JmespathError, Rc, Rcvar, Runtime, Signature, Variable,
+    custom_error,
+};
+use crate::registry::{Category, FunctionInfo, FunctionRegistry};
+
+/// Fuel (roughly, executed instructions) available to each plugin call.
+pub const MAX_FUEL_PER_CALL: u64 = 100_000_000;
+
+/// Largest linear memory a plugin may have, in bytes.
+pub const MAX_MEMORY_BYTES: usize = 64 << 20;
+
+/// Error returned when a plugin cannot be loaded.
+#[derive(Debug)]
+pub enum PluginError {
+    /// The plugin file could not be read
+    Io(std::io::Error),
+    /// The module failed to compile or instantiate, or lacks a required export
+    Wasm(String),
+    /// The manifest is missing or malformed
+    Manifest(String),
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::Io(e) => write!(f, "failed to read plugin: {}", e),
+            PluginError::Wasm(msg) => write!(f, "invalid plugin module: {}", msg),
+            PluginError::Manifest(msg) => write!(f, "invalid plugin manifest: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PluginError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PluginError {
+    fn from(e: std::io::Error) -> Self {
+        PluginError::Io(e)
+    }
+}
+
+/// An instantiated plugin module, shared by the functions it provides.
+struct Instance {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+}
+
+impl Instance {
+    /// Copy `input` into plugin memory and run `entry` on it.
+    fn call(&mut self, entry: TypedFunc<(i32, i32), i64>, input: &[u8]) -> Result<Vec<u8>, String> {
+        let len = i32::try_from(input.len()).map_err(|_| "arguments too large".to_string())?;
+        refuel(&mut self.store).map_err(|e| e.to_string())?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|e| e.to_string())?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|e| e.to_string())?;
+        let packed = entry
+            .call(&mut self.store, (ptr, len))
+            .map_err(|e| e.to_string())?;
+        self.read_packed(packed)
+    }
+
+    /// Read the bytes at a packed `(ptr << 32) | len` location.
+    ///
+    /// The range is checked against the plugin's memory before copying, so a
+    /// bogus length cannot make the host allocate more than that memory holds.
+    fn read_packed(&self, packed: i64) -> Result<Vec<u8>, String> {
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xFFFF_FFFF) as usize;
+        self.memory
+            .data(&self.store)
+            .get(ptr..ptr.saturating_add(len))
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| format!("returned range {}+{} is out of bounds", ptr, len))
+    }
+}
+
+/// A function provided by a plugin.
+struct PluginFunction {
+    info: FunctionInfo,
+    signature: Signature,
+    entry: TypedFunc<(i32, i32), i64>,
+}
+
+/// A loaded WebAssembly plugin.
+///
+/// Function metadata is leaked so it can sit alongside the `'static`
+/// metadata of the built-in functions; load each plugin once and register
+/// it on as many runtimes as needed.
+pub struct Plugin {
+    instance: Arc<Mutex<Instance>>,
+    functions: Vec<PluginFunction>,
+}
+
+impl Plugin {
+    /// Load a plugin from a `.wasm` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Load a plugin from the bytes of a WebAssembly module.
+    pub fn from_bytes(wasm: &[u8]) -> Result<Self, PluginError> {
+        let wasm_err = |e: wasmi::Error| PluginError::Wasm(e.to_string());
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(wasm_err)?;
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .build();
+        let mut store = Store::new(&engine, limits);
+        store.limiter(|limits| limits);
+        refuel(&mut store).map_err(wasm_err)?;
+        let instance = Linker::<StoreLimits>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(wasm_err)?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| PluginError::Wasm("missing export 'memory'".to_string()))?;
+        let alloc = typed_export(&instance, &store, "jmespath_alloc")?;
+        let manifest_fn: TypedFunc<(), i64> = typed_export(&instance, &store, "jmespath_manifest")?;
+
+        let mut instance_state = Instance {
+            store,
+            memory,
+            alloc,
+        };
+        refuel(&mut instance_state.store).map_err(wasm_err)?;
+        let packed = manifest_fn
+            .call(&mut instance_state.store, ())
+            .map_err(wasm_err)?;
+        let manifest = instance_state
+            .read_packed(packed)
+            .map_err(PluginError::Manifest)?;
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&manifest).map_err(|e| PluginError::Manifest(e.to_string()))?;
+
+        let entries = manifest
+            .get("functions")
+            .and_then(|f| f.as_array())
+            .ok_or_else(|| PluginError::Manifest("expected a 'functions' array".to_string()))?;
+
+        let mut functions = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let field = |key: &str| entry.get(key).and_then(|v| v.as_str());
+            let name = field("name")
+                .ok_or_else(|| PluginError::Manifest("function without a 'name'".to_string()))?;
+            let export = field("export").ok_or_else(|| {
+                PluginError::Manifest(format!("function '{}' has no 'export'", name))
+            })?;
+
+            let args = match entry.get("args") {
+                None => Vec::new(),
+                Some(args) => args
+                    .as_array()
+                    .ok_or_else(|| {
+                        PluginError::Manifest(format!("'args' of '{}' must be an array", name))
+                    })?
+                    .iter()
+                    .map(|t| parse_type(t.as_str().unwrap_or_default(), name))
+                    .collect::<Result<Vec<_>, _>>()?,
+            };
+            let variadic = field("variadic").map(|t| parse_type(t, name)).transpose()?;
+
+            let entry = typed_export(&instance, &instance_state.store, export)?;
+
+            let info = FunctionInfo {
+                name: leak(name),
+                category: Category::Custom,
+                description: leak(field("description").unwrap_or_default()),
+                signature: leak(field("signature").unwrap_or_default()),
+                example: leak(field("example").unwrap_or_default()),
+                is_standard: false,
+                jep: None,
+                aliases: &[],
+                features: &[],
+            };
+            functions.push(PluginFunction {
+                info,
+                signature: Signature::new(args, variadic),
+                entry,
+            });
+        }
+
+        Ok(Plugin {
+            instance: Arc::new(Mutex::new(instance_state)),
+            functions,
+        })
+    }
+
+    /// Metadata for each function the plugin provides.
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionInfo> {
+        self.functions.iter().map(|f| &f.info)
+    }
+
+    /// Register the plugin's functions on `runtime`.
+    pub fn register(&self, runtime: &mut Runtime) {
+        for function in &self.functions {
+            runtime.register_function(
+                function.info.name,
+                Box::new(PluginFn {
+                    name: function.info.name,
+                    signature: function.signature.clone(),
+                    entry: function.entry,
+                    instance: self.instance.clone(),
+                }),
+            );
+        }
+    }
+
+    /// Add the plugin's function metadata to `registry`.
+    pub fn register_metadata(&self, registry: &mut FunctionRegistry) {
+        for info in self.functions() {
+            registry.register_custom(info.clone());
+        }
+    }
+}
+
+/// Reset the fuel budget before running plugin code.
+fn refuel(store: &mut Store<StoreLimits>) -> Result<(), wasmi::Error> {
+    store.set_fuel(MAX_FUEL_PER_CALL)?;
+    Ok(())
+}
+
+fn typed_export<Params, Results>(
+    instance: &wasmi::Instance,
+    store: &Store<StoreLimits>,
+    name: &str,
+) -> Result<TypedFunc<Params, Results>, PluginError>
+where
+    Params: wasmi::WasmParams,
+    Results: wasmi::WasmResults,
+{
+    instance
+        .get_typed_func(store, name)
+        .map_err(|e| PluginError::Wasm(format!("export '{}': {}", name, e)))
+}
+
+fn leak(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
+
+fn parse_type(name: &str, function: &str) -> Result<ArgumentType, PluginError> {
+    Ok(match name {
+        "any" => ArgumentType::Any,
+        "null" => ArgumentType::Null,
+        "string" => ArgumentType::String,
+        "number" => ArgumentType::Number,
+        "boolean" => ArgumentType::Bool,
+        "array" => ArgumentType::Array,
+        "object" => ArgumentType::Object,
+        other => {
+            return Err(PluginError::Manifest(format!(
+                "unknown argument type '{}' for '{}'",
+                other, function
+            )));
+        }
+    })
+}
+
+/// A function implemented by a plugin entrypoint.
+struct PluginFn {
+    name: &'static str,
+    signature: Signature,
+    entry: TypedFunc<(i32, i32), i64>,
+    instance: Arc<Mutex<Instance>>,
+}
+
+impl Function for PluginFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let args: Vec<&Variable> = args.iter().map(|a| &**a).collect();
+        let input = serde_json::to_vec(&args)
+            .map_err(|e| custom_error(ctx, &format!("{}: {}", self.name, e)))?;
+
+        // A trap mid-call leaves the store usable, so recover a poisoned lock
+        let output = self
+            .instance
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .call(self.entry, &input)
+            .map_err(|e| custom_error(ctx, &format!("{}: plugin failed: {}", self.name, e)))?;
+
+        let mut response: serde_json::Value = serde_json::from_slice(&output).map_err(|e| {
+            custom_error(
+                ctx,
+                &format!("{}: invalid plugin response: {}", self.name, e),
+            )
+        })?;
+        if let Some(message) = response.get("error") {
+            let message = message
+                .as_str()
+                .map_or_else(|| message.to_string(), String::from);
+            return Err(custom_error(ctx, &format!("{}: {}", self.name, message)));
+        }
+        let value = response
+            .get_mut("ok")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| {
+                custom_error(
+                    ctx,
+                    &format!(
+                        "{}: plugin response has neither 'ok' nor 'error'",
+                        self.name
+                    ),
+                )
+            })?;
+        let value: Variable = serde_json::from_value(value)
+            .map_err(|e| custom_error(ctx, &format!("{}: {}", self.name, e)))?;
+        Ok(Rc::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin with `echo(any)`, which returns its argument, and
+    /// `fail()`, which always returns an error.
+    fn test_plugin() -> Vec<u8> {
+        let manifest = concat!(
+            r#"{"functions": ["#,
+            r#"{"name": "wasm_echo", "export": "echo", "args": ["any"], "#,
+            r#""signature": "any -> any", "description": "Return the argument"}, "#,
+            r#"{"name": "wasm_fail", "export": "fail"}"#,
+            r#"]}"#
+        );
+        let wat = format!(
+            r#"(module
+              (memory (export "memory") 1)
+              (global $heap (mut i32) (i32.const 1024))
+              (data (i32.const 0) "{manifest}")
+              (data (i32.const 512) "{{\"error\":\"boom\"}}")
+              (data (i32.const 600) "{{\"ok\":")
+              (func (export "jmespath_alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $heap))
+                (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                (local.get $ptr))
+              (func (export "jmespath_manifest") (result i64)
+                (i64.const {len}))
+              ;; Rewrite `[x]` as `{{"ok":x}}`
+              (func (export "echo") (param $ptr i32) (param $len i32) (result i64)
+                (local $out i32) (local $n i32)
+                (local.set $n (i32.sub (local.get $len) (i32.const 2)))
+                (local.set $out (global.get $heap))
+                (global.set $heap (i32.add (local.get $out) (i32.add (local.get $n) (i32.const 7))))
+                (memory.copy (local.get $out) (i32.const 600) (i32.const 6))
+                (memory.copy (i32.add (local.get $out) (i32.const 6))
+                             (i32.add (local.get $ptr) (i32.const 1))
+                             (local.get $n))
+                (i32.store8 (i32.add (local.get $out) (i32.add (local.get $n) (i32.const 6)))
+                            (i32.const 125))
+                (i64.or (i64.shl (i64.extend_i32_u (local.get $out)) (i64.const 32))
+                        (i64.extend_i32_u (i32.add (local.get $n) (i32.const 7)))))
+              (func (export "fail") (param i32 i32) (result i64)
+                (i64.const {fail})))"#,
+            manifest = manifest.replace('"', "\\\""),
+            len = manifest.len(),
+            fail = (512i64 << 32) | 16,
+        );
+        wat::parse_str(wat).unwrap()
+    }
+
+    fn setup_runtime() -> (Runtime, Plugin) {
+        let plugin = Plugin::from_bytes(&test_plugin()).unwrap();
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        plugin.register(&mut runtime);
+        (runtime, plugin)
+    }
+
+    #[test]
+    fn test_plugin_function() {
+        let (runtime, _) = setup_runtime();
+        let data = Variable::from_json(r#"{"a": [1, "two", null]}"#).unwrap();
+        let expr = runtime.compile("wasm_echo(a)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(*result, Variable::from_json(r#"[1, "two", null]"#).unwrap());
+
+        // Signature comes from the manifest
+        let expr = runtime.compile("wasm_echo(a, a)").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_plugin_error() {
+        let (runtime, _) = setup_runtime();
+        let expr = runtime.compile("wasm_fail()").unwrap();
+        let err = expr.search(&Variable::Null).unwrap_err().to_string();
+        assert!(err.contains("wasm_fail: boom"), "{}", err);
+    }
+
+    #[test]
+    fn test_plugin_metadata() {
+        let (_, plugin) = setup_runtime();
+        let mut registry = FunctionRegistry::new();
+        plugin.register_metadata(&mut registry);
+        let info = registry.get_function("wasm_echo").unwrap();
+        assert_eq!(info.category, Category::Custom);
+        assert_eq!(info.description, "Return the argument");
+        assert!(registry.get_function("wasm_fail").is_some());
+    }
+
+    /// A plugin whose functions misbehave: `spin` never returns and `huge`
+    /// claims a 4 GiB result.
+    fn hostile_plugin(memory_pages: u32) -> Vec<u8> {
+        let manifest = concat!(
+            r#"{"functions": ["#,
+            r#"{"name": "wasm_spin", "export": "spin"}, "#,
+            r#"{"name": "wasm_huge", "export": "huge"}"#,
+            r#"]}"#
+        );
+        let wat = format!(
+            r#"(module
+              (memory (export "memory") {memory_pages})
+              (data (i32.const 0) "{manifest}")
+              (func (export "jmespath_alloc") (param i32) (result i32)
+                (i32.const 1024))
+              (func (export "jmespath_manifest") (result i64)
+                (i64.const {len}))
+              (func (export "spin") (param i32 i32) (result i64)
+                (loop $forever (br $forever))
+                (i64.const 0))
+              (func (export "huge") (param i32 i32) (result i64)
+                (i64.const 0xFFFFFFFF)))"#,
+            manifest = manifest.replace('"', "\\\""),
+            len = manifest.len(),
+        );
+        wat::parse_str(wat).unwrap()
+    }
+
+    #[test]
+    fn test_plugin_resource_limits() {
+        let plugin = Plugin::from_bytes(&hostile_plugin(1)).unwrap();
+        let mut runtime = Runtime::new();
+        plugin.register(&mut runtime);
+
+        // Runs out of fuel instead of hanging
+        let expr = runtime.compile("wasm_spin()").unwrap();
+        let err = expr.search(&Variable::Null).unwrap_err().to_string();
+        assert!(err.contains("fuel"), "{}", err);
+
+        // The claimed length is checked before anything is allocated
+        let expr = runtime.compile("wasm_huge()").unwrap();
+        let err = expr.search(&Variable::Null).unwrap_err().to_string();
+        assert!(err.contains("out of bounds"), "{}", err);
+
+        // 2048 pages is 128 MiB, over the memory limit
+        assert!(matches!(
+            Plugin::from_bytes(&hostile_plugin(2048)),
+            Err(PluginError::Wasm(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_plugin() {
+        assert!(matches!(
+            Plugin::from_bytes(b"not wasm"),
+            Err(PluginError::Wasm(_))
+        ));
+        let no_manifest = wat::parse_str(r#"(module (memory (export "memory") 1))"#).unwrap();
+        assert!(matches!(
+            Plugin::from_bytes(&no_manifest),
+            Err(PluginError::Wasm(_))
+        ));
+    }
+}
@@ -16,36 +16,84 @@
 //! random::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
-
-#[cfg(feature = "rand")]
-use crate::common::ErrorReason;
-use crate::common::{Context, Function, JmespathError, Rcvar, Runtime, Variable};
+use crate::common::Rc;
 
 #[cfg(feature = "uuid")]
-use crate::define_function;
+use crate::common::Signature;
+use crate::common::{Context, Function, JmespathError, Rcvar, Runtime, Variable};
+#[cfg(feature = "rand")]
+use crate::common::{ErrorReason, NestedExpression};
 
 /// Register all random functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
     #[cfg(feature = "rand")]
-    {
-        runtime.register_function("random", Box::new(RandomFn::new()));
-        runtime.register_function("shuffle", Box::new(ShuffleFn::new()));
-        runtime.register_function("sample", Box::new(SampleFn::new()));
-    }
+    register_with_source(runtime, RngSource::default());
+
+    #[cfg(all(feature = "uuid", not(feature = "rand")))]
+    runtime.register_function("uuid", Box::new(UuidFn::new()));
+}
+
+/// Register all random functions, drawing from `source` instead of the
+/// thread RNG.
+///
+/// Functions called with an explicit seed argument keep using that seed.
+/// See [`register_random_with_seed`](crate::register_random_with_seed) for
+/// the usual way to make a runtime reproducible.
+#[cfg(feature = "rand")]
+pub fn register_with_source(runtime: &mut Runtime, source: RngSource) {
+    runtime.register_function("random", Box::new(RandomFn::with_source(source.clone())));
+    runtime.register_function("shuffle", Box::new(ShuffleFn::with_source(source.clone())));
+    runtime.register_function("sample", Box::new(SampleFn::with_source(source.clone())));
+    runtime.register_function("shuffle_seeded", Box::new(ShuffleSeededFn::new()));
+    runtime.register_function("sample_seeded", Box::new(SampleSeededFn::new()));
+    runtime.register_function(
+        "weighted_sample",
+        Box::new(WeightedSampleFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "weighted_choice",
+        Box::new(WeightedChoiceFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "random_choice",
+        Box::new(RandomChoiceFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "stratified_sample",
+        Box::new(StratifiedSampleFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "random_normal",
+        Box::new(RandomNormalFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "random_int",
+        Box::new(RandomIntFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "random_exponential",
+        Box::new(RandomExponentialFn::with_source(source.clone())),
+    );
+    runtime.register_function(
+        "random_poisson",
+        Box::new(RandomPoissonFn::with_source(source.clone())),
+    );
     #[cfg(feature = "uuid")]
-    {
-        runtime.register_function("uuid", Box::new(UuidFn::new()));
-    }
+    runtime.register_function("uuid", Box::new(UuidFn::with_source(source)));
 }
 
+#[cfg(feature = "rand")]
+pub use crate::common::{RngSource, clear_rng_provider, set_rng_provider};
+
 // =============================================================================
 // random() -> number (0.0 to 1.0)
 // random(min, max) -> number in range [min, max)
 // =============================================================================
 
 #[cfg(feature = "rand")]
-pub struct RandomFn;
+pub struct RandomFn {
+    rng: RngSource,
+}
 
 #[cfg(feature = "rand")]
 impl Default for RandomFn {
@@ -57,7 +105,11 @@ impl Default for RandomFn {
 #[cfg(feature = "rand")]
 impl RandomFn {
     pub fn new() -> RandomFn {
-        RandomFn
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> RandomFn {
+        RandomFn { rng }
     }
 }
 
@@ -75,11 +127,9 @@ impl Function for RandomFn {
             ));
         }
 
-        let mut rng = rand::thread_rng();
-
         let value: f64 = if args.is_empty() {
             // random() - return 0.0 to 1.0
-            rng.gen_range(0.0..1.0)
+            self.rng.with(|rng| rng.gen_range(0.0..1.0))
         } else {
             // random(min, max) - return min to max
             let min = args[0].as_number().ok_or_else(|| {
@@ -96,7 +146,7 @@ impl Function for RandomFn {
                     ErrorReason::Parse("Expected number for max".to_owned()),
                 )
             })?;
-            rng.gen_range(min..max)
+            self.rng.with(|rng| rng.gen_range(min..max))
         };
 
         Ok(Rc::new(Variable::Number(
@@ -111,7 +161,9 @@ impl Function for RandomFn {
 // =============================================================================
 
 #[cfg(feature = "rand")]
-pub struct ShuffleFn;
+pub struct ShuffleFn {
+    rng: RngSource,
+}
 
 #[cfg(feature = "rand")]
 impl Default for ShuffleFn {
@@ -123,7 +175,11 @@ impl Default for ShuffleFn {
 #[cfg(feature = "rand")]
 impl ShuffleFn {
     pub fn new() -> ShuffleFn {
-        ShuffleFn
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> ShuffleFn {
+        ShuffleFn { rng }
     }
 }
 
@@ -147,26 +203,11 @@ impl Function for ShuffleFn {
             )
         })?;
 
-        use rand::SeedableRng;
         use rand::seq::SliceRandom;
 
         let mut result: Vec<Rcvar> = arr.clone();
-
-        if args.len() == 2 {
-            // Deterministic shuffle with seed
-            let seed = args[1].as_number().ok_or_else(|| {
-                JmespathError::new(
-                    ctx.expression,
-                    0,
-                    ErrorReason::Parse("Expected number for seed".to_owned()),
-                )
-            })? as u64;
-            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
-            result.shuffle(&mut rng);
-        } else {
-            // Random shuffle
-            result.shuffle(&mut rand::thread_rng());
-        }
+        let seed = parse_seed(args.get(1), ctx)?;
+        with_rng(seed, &self.rng, |rng| result.shuffle(rng));
 
         Ok(Rc::new(Variable::Array(result)))
     }
@@ -178,7 +219,9 @@ impl Function for ShuffleFn {
 // =============================================================================
 
 #[cfg(feature = "rand")]
-pub struct SampleFn;
+pub struct SampleFn {
+    rng: RngSource,
+}
 
 #[cfg(feature = "rand")]
 impl Default for SampleFn {
@@ -190,7 +233,11 @@ impl Default for SampleFn {
 #[cfg(feature = "rand")]
 impl SampleFn {
     pub fn new() -> SampleFn {
-        SampleFn
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> SampleFn {
+        SampleFn { rng }
     }
 }
 
@@ -222,94 +269,1172 @@ impl Function for SampleFn {
             )
         })? as usize;
 
-        use rand::SeedableRng;
         use rand::seq::SliceRandom;
 
-        let sample: Vec<Rcvar> = if args.len() == 3 {
-            // Deterministic sample with seed
-            let seed = args[2].as_number().ok_or_else(|| {
+        let seed = parse_seed(args.get(2), ctx)?;
+        let sample: Vec<Rcvar> = with_rng(seed, &self.rng, |rng| {
+            arr.choose_multiple(rng, n.min(arr.len()))
+                .cloned()
+                .collect()
+        });
+
+        Ok(Rc::new(Variable::Array(sample)))
+    }
+}
+
+// =============================================================================
+// shuffle_seeded(array, seed) -> array (deterministically shuffled)
+// sample_seeded(array, n, seed) -> array (deterministic sample)
+//
+// Explicit-seed variants of shuffle/sample: the seed is required, so a missing
+// argument is an error instead of silently falling back to thread randomness.
+// =============================================================================
+
+#[cfg(feature = "rand")]
+pub struct ShuffleSeededFn;
+
+#[cfg(feature = "rand")]
+impl Default for ShuffleSeededFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl ShuffleSeededFn {
+    pub fn new() -> ShuffleSeededFn {
+        ShuffleSeededFn
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for ShuffleSeededFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        if args.len() != 2 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("shuffle_seeded() takes 2 arguments".to_owned()),
+            ));
+        }
+        ShuffleFn::new().evaluate(args, ctx)
+    }
+}
+
+#[cfg(feature = "rand")]
+pub struct SampleSeededFn;
+
+#[cfg(feature = "rand")]
+impl Default for SampleSeededFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl SampleSeededFn {
+    pub fn new() -> SampleSeededFn {
+        SampleSeededFn
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for SampleSeededFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        if args.len() != 3 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("sample_seeded() takes 3 arguments".to_owned()),
+            ));
+        }
+        SampleFn::new().evaluate(args, ctx)
+    }
+}
+
+// =============================================================================
+// weighted_sample(array, weight_expr, n) -> array (sample without replacement)
+// weighted_sample(array, weight_expr, n, seed) -> array (deterministic)
+// =============================================================================
+
+#[cfg(feature = "rand")]
+pub struct WeightedSampleFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for WeightedSampleFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl WeightedSampleFn {
+    pub fn new() -> WeightedSampleFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> WeightedSampleFn {
+        WeightedSampleFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for WeightedSampleFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand::seq::SliceRandom;
+
+        // Manual validation: 3 or 4 arguments
+        if args.len() < 3 || args.len() > 4 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("weighted_sample() takes 3 or 4 arguments".to_owned()),
+            ));
+        }
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let expr_str = args[1].as_string().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected expression string for weights".to_owned()),
+            )
+        })?;
+
+        let n = args[2].as_number().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected number argument".to_owned()),
+            )
+        })? as usize;
+
+        let seed = parse_seed(args.get(3), ctx)?;
+
+        let compiled = ctx
+            .runtime
+            .compile(expr_str)
+            .map(NestedExpression::new)
+            .map_err(|e| {
                 JmespathError::new(
                     ctx.expression,
                     0,
-                    ErrorReason::Parse("Expected number for seed".to_owned()),
+                    ErrorReason::Parse(format!("Invalid expression in weighted_sample: {}", e)),
                 )
-            })? as u64;
-            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
-            arr.choose_multiple(&mut rng, n.min(arr.len()))
-                .cloned()
-                .collect()
-        } else {
-            // Random sample
-            arr.choose_multiple(&mut rand::thread_rng(), n.min(arr.len()))
-                .cloned()
-                .collect()
-        };
+            })?;
 
-        Ok(Rc::new(Variable::Array(sample)))
+        let mut weights = Vec::with_capacity(arr.len());
+        for item in arr {
+            let weight = compiled.search(item.clone())?;
+            match weight.as_number() {
+                Some(w) if w >= 0.0 && w.is_finite() => weights.push(w),
+                _ => {
+                    return Err(JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse(
+                            "weighted_sample weights must be non-negative numbers".to_owned(),
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Zero-weight items are never selected, so cap n at the number of candidates
+        let candidates = weights.iter().filter(|w| **w > 0.0).count();
+        let indices: Vec<usize> = (0..arr.len()).collect();
+
+        let chosen: Vec<usize> = with_rng(seed, &self.rng, |rng| {
+            indices
+                .choose_multiple_weighted(rng, n.min(candidates), |i| weights[*i])
+                .map(|iter| iter.copied().collect())
+        })
+        .map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!("Invalid weights for weighted_sample: {}", e)),
+            )
+        })?;
+
+        Ok(Rc::new(Variable::Array(
+            chosen.into_iter().map(|i| arr[i].clone()).collect(),
+        )))
     }
 }
 
 // =============================================================================
-// uuid() -> string (UUID v4)
+// weighted_choice(values, weights) -> any (one value, picked by weight)
 // =============================================================================
 
-#[cfg(feature = "uuid")]
-define_function!(UuidFn, vec![], None);
+#[cfg(feature = "rand")]
+pub struct WeightedChoiceFn {
+    rng: RngSource,
+}
 
-#[cfg(feature = "uuid")]
-impl Function for UuidFn {
+#[cfg(feature = "rand")]
+impl Default for WeightedChoiceFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl WeightedChoiceFn {
+    pub fn new() -> WeightedChoiceFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> WeightedChoiceFn {
+        WeightedChoiceFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for WeightedChoiceFn {
     fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
-        self.signature.validate(args, ctx)?;
+        use rand::distributions::{Distribution, WeightedIndex};
 
-        let id = uuid::Uuid::new_v4();
-        Ok(Rc::new(Variable::String(id.to_string())))
+        if args.len() != 2 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("weighted_choice() takes 2 arguments".to_owned()),
+            ));
+        }
+
+        let (values, weights) = match (args[0].as_array(), args[1].as_array()) {
+            (Some(values), Some(weights)) => (values, weights),
+            _ => {
+                return Err(JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("Expected array arguments".to_owned()),
+                ));
+            }
+        };
+        if values.len() != weights.len() {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(
+                    "weighted_choice() values and weights must have the same length".to_owned(),
+                ),
+            ));
+        }
+        if values.is_empty() {
+            return Ok(Rc::new(Variable::Null));
+        }
+
+        let weights = weights
+            .iter()
+            .map(|w| w.as_number().filter(|w| *w >= 0.0 && w.is_finite()))
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse(
+                        "weighted_choice weights must be non-negative numbers".to_owned(),
+                    ),
+                )
+            })?;
+        let dist = WeightedIndex::new(&weights).map_err(|e| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!("Invalid weights for weighted_choice: {}", e)),
+            )
+        })?;
+
+        let index = self.rng.with(|rng| dist.sample(rng));
+        Ok(values[index].clone())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use jmespath::Runtime;
+// =============================================================================
+// random_choice(array) -> any (one element, or null for an empty array)
+// random_choice(array, n) -> array (n distinct elements, without replacement)
+// =============================================================================
 
-    fn setup_runtime() -> Runtime {
-        let mut runtime = Runtime::new();
-        runtime.register_builtin_functions();
-        register(&mut runtime);
-        runtime
+#[cfg(feature = "rand")]
+pub struct RandomChoiceFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for RandomChoiceFn {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    #[cfg(feature = "rand")]
-    #[test]
-    fn test_random() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("random()").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        let value = result.as_number().unwrap();
-        assert!((0.0..1.0).contains(&value));
+#[cfg(feature = "rand")]
+impl RandomChoiceFn {
+    pub fn new() -> RandomChoiceFn {
+        Self::with_source(RngSource::default())
     }
 
-    #[cfg(feature = "rand")]
-    #[test]
-    fn test_shuffle() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("shuffle(@)").unwrap();
-        let data = Variable::Array(vec![
-            Rc::new(Variable::Number(serde_json::Number::from(1))),
-            Rc::new(Variable::Number(serde_json::Number::from(2))),
-            Rc::new(Variable::Number(serde_json::Number::from(3))),
-        ]);
-        let result = expr.search(&data).unwrap();
-        let arr = result.as_array().unwrap();
-        assert_eq!(arr.len(), 3);
+    pub fn with_source(rng: RngSource) -> RandomChoiceFn {
+        RandomChoiceFn { rng }
     }
+}
 
-    #[cfg(feature = "uuid")]
-    #[test]
-    fn test_uuid() {
-        let runtime = setup_runtime();
-        let expr = runtime.compile("uuid()").unwrap();
-        let result = expr.search(&Variable::Null).unwrap();
-        let uuid_str = result.as_string().unwrap();
-        assert_eq!(uuid_str.len(), 36); // UUID format: xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx
+#[cfg(feature = "rand")]
+impl Function for RandomChoiceFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand::seq::SliceRandom;
+
+        if args.is_empty() || args.len() > 2 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("random_choice() takes 1 or 2 arguments".to_owned()),
+            ));
+        }
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        match args.get(1) {
+            None => Ok(self
+                .rng
+                .with(|rng| arr.choose(rng).cloned())
+                .unwrap_or_else(|| Rc::new(Variable::Null))),
+            Some(n) => {
+                let n = n.as_number().filter(|n| *n >= 0.0).ok_or_else(|| {
+                    JmespathError::new(
+                        ctx.expression,
+                        0,
+                        ErrorReason::Parse(
+                            "random_choice() n must be a non-negative number".to_owned(),
+                        ),
+                    )
+                })? as usize;
+                let chosen = self.rng.with(|rng| {
+                    arr.choose_multiple(rng, n.min(arr.len()))
+                        .cloned()
+                        .collect()
+                });
+                Ok(Rc::new(Variable::Array(chosen)))
+            }
+        }
+    }
+}
+
+// =============================================================================
+// stratified_sample(array, group_expr, n_per_group) -> array
+// stratified_sample(array, group_expr, n_per_group, seed) -> array (deterministic)
+// =============================================================================
+
+#[cfg(feature = "rand")]
+pub struct StratifiedSampleFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for StratifiedSampleFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl StratifiedSampleFn {
+    pub fn new() -> StratifiedSampleFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> StratifiedSampleFn {
+        StratifiedSampleFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for StratifiedSampleFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand::seq::SliceRandom;
+
+        // Manual validation: 3 or 4 arguments
+        if args.len() < 3 || args.len() > 4 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("stratified_sample() takes 3 or 4 arguments".to_owned()),
+            ));
+        }
+
+        let arr = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected array argument".to_owned()),
+            )
+        })?;
+
+        let expr_str = args[1].as_string().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected expression string for groups".to_owned()),
+            )
+        })?;
+
+        let n = args[2].as_number().ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected number argument".to_owned()),
+            )
+        })? as usize;
+
+        let seed = parse_seed(args.get(3), ctx)?;
+
+        let compiled = ctx
+            .runtime
+            .compile(expr_str)
+            .map(NestedExpression::new)
+            .map_err(|e| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse(format!("Invalid expression in stratified_sample: {}", e)),
+                )
+            })?;
+
+        // Group by key, preserving the order in which groups first appear
+        let mut groups: Vec<(String, Vec<Rcvar>)> = Vec::new();
+        for item in arr {
+            let key = serde_json::to_string(&*compiled.search(item.clone())?).unwrap_or_default();
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, members)) => members.push(item.clone()),
+                None => groups.push((key, vec![item.clone()])),
+            }
+        }
+
+        let sample: Vec<Rcvar> = with_rng(seed, &self.rng, |rng| {
+            groups
+                .iter()
+                .flat_map(|(_, members)| {
+                    members
+                        .choose_multiple(rng, n.min(members.len()))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        });
+
+        Ok(Rc::new(Variable::Array(sample)))
+    }
+}
+
+// =============================================================================
+// random_normal(mean, std, seed?) -> number (Gaussian distribution)
+// =============================================================================
+
+#[cfg(feature = "rand")]
+pub struct RandomNormalFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for RandomNormalFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RandomNormalFn {
+    pub fn new() -> RandomNormalFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> RandomNormalFn {
+        RandomNormalFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for RandomNormalFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand_distr::{Distribution, Normal};
+
+        check_arity(args, 2, "random_normal", ctx)?;
+        let mean = number_arg(&args[0], "mean", ctx)?;
+        let std = number_arg(&args[1], "std", ctx)?;
+        let normal = Normal::new(mean, std)
+            .ok()
+            .filter(|_| std >= 0.0)
+            .ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse("random_normal() std must be non-negative".to_owned()),
+                )
+            })?;
+        let seed = parse_seed(args.get(2), ctx)?;
+
+        Ok(number_var(with_rng(seed, &self.rng, |rng| {
+            normal.sample(rng)
+        })))
+    }
+}
+
+// =============================================================================
+// random_int(min, max, seed?) -> integer in range [min, max]
+// =============================================================================
+
+#[cfg(feature = "rand")]
+pub struct RandomIntFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for RandomIntFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RandomIntFn {
+    pub fn new() -> RandomIntFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> RandomIntFn {
+        RandomIntFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for RandomIntFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand::Rng;
+
+        check_arity(args, 2, "random_int", ctx)?;
+        let min = number_arg(&args[0], "min", ctx)?.ceil() as i64;
+        let max = number_arg(&args[1], "max", ctx)?.floor() as i64;
+        if min > max {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("random_int() min must not be greater than max".to_owned()),
+            ));
+        }
+        let seed = parse_seed(args.get(2), ctx)?;
+
+        let value = with_rng(seed, &self.rng, |rng| rng.gen_range(min..=max));
+        Ok(Rc::new(Variable::Number(serde_json::Number::from(value))))
+    }
+}
+
+// =============================================================================
+// random_exponential(lambda, seed?) -> number (exponential distribution)
+// =============================================================================
+
+#[cfg(feature = "rand")]
+pub struct RandomExponentialFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for RandomExponentialFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RandomExponentialFn {
+    pub fn new() -> RandomExponentialFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> RandomExponentialFn {
+        RandomExponentialFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for RandomExponentialFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand_distr::{Distribution, Exp};
+
+        check_arity(args, 1, "random_exponential", ctx)?;
+        let lambda = positive_rate(&args[0], "random_exponential", ctx)?;
+        let exp = Exp::new(lambda).map_err(|_| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("random_exponential() lambda must be positive".to_owned()),
+            )
+        })?;
+        let seed = parse_seed(args.get(1), ctx)?;
+
+        Ok(number_var(with_rng(seed, &self.rng, |rng| exp.sample(rng))))
+    }
+}
+
+// =============================================================================
+// random_poisson(lambda, seed?) -> integer (Poisson distribution)
+// =============================================================================
+
+/// Largest `lambda` accepted by `random_poisson` (2^53). Larger means cannot
+/// be sampled reliably, and samples could not be returned as exact integers.
+#[cfg(feature = "rand")]
+const MAX_POISSON_LAMBDA: f64 = 9_007_199_254_740_992.0;
+
+/// Sample a Poisson distribution with mean `lambda`, at most 2^53.
+#[cfg(feature = "rand")]
+pub struct RandomPoissonFn {
+    rng: RngSource,
+}
+
+#[cfg(feature = "rand")]
+impl Default for RandomPoissonFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl RandomPoissonFn {
+    pub fn new() -> RandomPoissonFn {
+        Self::with_source(RngSource::default())
+    }
+
+    pub fn with_source(rng: RngSource) -> RandomPoissonFn {
+        RandomPoissonFn { rng }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl Function for RandomPoissonFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        use rand_distr::{Distribution, Poisson};
+
+        check_arity(args, 1, "random_poisson", ctx)?;
+        let lambda = positive_rate(&args[0], "random_poisson", ctx)?;
+        if lambda > MAX_POISSON_LAMBDA {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "random_poisson() lambda must be at most {}",
+                    MAX_POISSON_LAMBDA
+                )),
+            ));
+        }
+        let poisson = Poisson::new(lambda).map_err(|_| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("random_poisson() lambda must be positive".to_owned()),
+            )
+        })?;
+        let seed = parse_seed(args.get(1), ctx)?;
+
+        // Poisson samples are whole numbers returned as f64
+        let value = with_rng(seed, &self.rng, |rng| poisson.sample(rng)) as u64;
+        Ok(Rc::new(Variable::Number(serde_json::Number::from(value))))
+    }
+}
+
+/// Validate `required` arguments plus an optional trailing seed.
+#[cfg(feature = "rand")]
+fn check_arity(
+    args: &[Rcvar],
+    required: usize,
+    name: &str,
+    ctx: &Context<'_>,
+) -> Result<(), JmespathError> {
+    if args.len() < required || args.len() > required + 1 {
+        return Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!(
+                "{}() takes {} or {} arguments",
+                name,
+                required,
+                required + 1
+            )),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "rand")]
+fn number_arg(arg: &Rcvar, name: &str, ctx: &Context<'_>) -> Result<f64, JmespathError> {
+    arg.as_number().ok_or_else(|| {
+        JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!("Expected number for {}", name)),
+        )
+    })
+}
+
+/// Parse a rate parameter, which must be a finite positive number.
+#[cfg(feature = "rand")]
+fn positive_rate(arg: &Rcvar, name: &str, ctx: &Context<'_>) -> Result<f64, JmespathError> {
+    let lambda = number_arg(arg, "lambda", ctx)?;
+    if !(lambda.is_finite() && lambda > 0.0) {
+        return Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(format!("{}() lambda must be positive", name)),
+        ));
+    }
+    Ok(lambda)
+}
+
+#[cfg(feature = "rand")]
+fn number_var(value: f64) -> Rcvar {
+    Rc::new(Variable::Number(
+        serde_json::Number::from_f64(value).unwrap_or_else(|| serde_json::Number::from(0)),
+    ))
+}
+
+/// Parse an optional seed argument.
+#[cfg(feature = "rand")]
+fn parse_seed(arg: Option<&Rcvar>, ctx: &Context<'_>) -> Result<Option<u64>, JmespathError> {
+    arg.map(|seed| {
+        seed.as_number().map(|n| n as u64).ok_or_else(|| {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("Expected number for seed".to_owned()),
+            )
+        })
+    })
+    .transpose()
+}
+
+/// Run `f` with a fresh RNG when a seed is given, or the function's source otherwise.
+#[cfg(feature = "rand")]
+fn with_rng<T>(
+    seed: Option<u64>,
+    source: &RngSource,
+    f: impl FnOnce(&mut dyn rand::RngCore) -> T,
+) -> T {
+    match seed {
+        Some(seed) => RngSource::seeded(seed).with(f),
+        None => source.with(f),
+    }
+}
+
+// =============================================================================
+// uuid() -> string (UUID v4)
+// =============================================================================
+
+#[cfg(feature = "uuid")]
+pub struct UuidFn {
+    signature: Signature,
+    #[cfg(feature = "rand")]
+    rng: RngSource,
+}
+
+#[cfg(feature = "uuid")]
+impl Default for UuidFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl UuidFn {
+    pub fn new() -> UuidFn {
+        UuidFn {
+            signature: Signature::new(vec![], None),
+            #[cfg(feature = "rand")]
+            rng: RngSource::default(),
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    pub fn with_source(rng: RngSource) -> UuidFn {
+        UuidFn {
+            signature: Signature::new(vec![], None),
+            rng,
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Function for UuidFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        #[cfg(feature = "rand")]
+        let id = {
+            let mut bytes = [0u8; 16];
+            self.rng.with(|rng| rng.fill_bytes(&mut bytes));
+            uuid::Builder::from_random_bytes(bytes).into_uuid()
+        };
+        #[cfg(not(feature = "rand"))]
+        let id = uuid::Uuid::new_v4();
+
+        Ok(Rc::new(Variable::String(id.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jmespath::Runtime;
+
+    fn setup_runtime() -> Runtime {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        register(&mut runtime);
+        runtime
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("random()").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let value = result.as_number().unwrap();
+        assert!((0.0..1.0).contains(&value));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_shuffle() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("shuffle(@)").unwrap();
+        let data = Variable::Array(vec![
+            Rc::new(Variable::Number(serde_json::Number::from(1))),
+            Rc::new(Variable::Number(serde_json::Number::from(2))),
+            Rc::new(Variable::Number(serde_json::Number::from(3))),
+        ]);
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("uuid()").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let uuid_str = result.as_string().unwrap();
+        assert_eq!(uuid_str.len(), 36); // UUID format: xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_weighted_sample_skips_zero_weights() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("weighted_sample(@, 'w', `5`)").unwrap();
+        let data = Variable::from_json(
+            r#"[{"id": 1, "w": 0}, {"id": 2, "w": 5}, {"id": 3, "w": 1}, {"id": 4, "w": 0}]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 2);
+        for item in arr {
+            let w = item.as_object().unwrap()["w"].as_number().unwrap();
+            assert!(w > 0.0);
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_weighted_sample_seeded_is_deterministic() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("weighted_sample(@, '@', `3`, `42`)")
+            .unwrap();
+        let data = Variable::from_json("[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]").unwrap();
+        let first = expr.search(&data).unwrap();
+        let second = expr.search(&data).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.as_array().unwrap().len(), 3);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_weighted_sample_negative_weight() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("weighted_sample(@, '@', `1`)").unwrap();
+        let data = Variable::from_json("[1, -2]").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_stratified_sample() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("stratified_sample(@, 'label', `2`, `7`)")
+            .unwrap();
+        let data = Variable::from_json(
+            r#"[
+                {"label": "a", "i": 1}, {"label": "a", "i": 2}, {"label": "a", "i": 3},
+                {"label": "b", "i": 4},
+                {"label": "c", "i": 5}, {"label": "c", "i": 6}, {"label": "c", "i": 7}
+            ]"#,
+        )
+        .unwrap();
+        let result = expr.search(&data).unwrap();
+        let labels: Vec<String> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_object().unwrap()["label"].as_string().unwrap().clone())
+            .collect();
+        assert_eq!(labels, vec!["a", "a", "b", "c", "c"]);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_shuffle_seeded_matches_shuffle_with_seed() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[1, 2, 3, 4, 5, 6, 7, 8]").unwrap();
+        let seeded = runtime
+            .compile("shuffle_seeded(@, `99`)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        let plain = runtime
+            .compile("shuffle(@, `99`)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        assert_eq!(seeded, plain);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_sample_seeded_deterministic() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("sample_seeded(@, `3`, `7`)").unwrap();
+        let data = Variable::from_json("[1, 2, 3, 4, 5, 6, 7, 8]").unwrap();
+        let first = expr.search(&data).unwrap();
+        assert_eq!(first, expr.search(&data).unwrap());
+        assert_eq!(first.as_array().unwrap().len(), 3);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_seeded_variants_require_seed() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[1, 2, 3]").unwrap();
+        let expr = runtime.compile("shuffle_seeded(@)").unwrap();
+        assert!(expr.search(&data).is_err());
+        let expr = runtime.compile("sample_seeded(@, `2`)").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_normal_seeded() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("[random_normal(`100`, `15`, `42`), random_normal(`100`, `15`, `42`)]")
+            .unwrap();
+        let data = Variable::from_json("{}").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr[0], arr[1]);
+        let expr = runtime.compile("random_normal(`5`, `0`)").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_number().unwrap(), 5.0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_int_inclusive_range() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("random_int(`1`, `6`)").unwrap();
+        for _ in 0..50 {
+            let value = expr.search(&Variable::Null).unwrap().as_number().unwrap();
+            assert!((1.0..=6.0).contains(&value));
+            assert_eq!(value.fract(), 0.0);
+        }
+        let expr = runtime.compile("random_int(`3`, `3`, `7`)").unwrap();
+        assert_eq!(
+            expr.search(&Variable::Null).unwrap().as_number().unwrap(),
+            3.0
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_exponential_and_poisson() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("random_exponential(`0.5`)").unwrap();
+        for _ in 0..20 {
+            assert!(expr.search(&Variable::Null).unwrap().as_number().unwrap() >= 0.0);
+        }
+        let expr = runtime.compile("random_poisson(`4`, `42`)").unwrap();
+        let first = expr.search(&Variable::Null).unwrap();
+        assert_eq!(first, expr.search(&Variable::Null).unwrap());
+        let value = first.as_number().unwrap();
+        assert!(value >= 0.0 && value.fract() == 0.0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_distribution_invalid_params() {
+        let runtime = setup_runtime();
+        for expr in [
+            "random_normal(`0`, `-1`)",
+            "random_int(`5`, `1`)",
+            "random_exponential(`0`)",
+            "random_poisson(`-2`)",
+            "random_poisson()",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(&Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_poisson_large_lambda() {
+        let runtime = setup_runtime();
+        for expr in ["random_poisson(`1e308`)", "random_poisson(`1e20`)"] {
+            let err = runtime
+                .compile(expr)
+                .unwrap()
+                .search(&Variable::Null)
+                .unwrap_err();
+            assert!(err.to_string().contains("at most"), "{}: {}", expr, err);
+        }
+
+        let expr = runtime
+            .compile("random_poisson(`9007199254740992`, `7`)")
+            .unwrap();
+        let value = expr.search(&Variable::Null).unwrap().as_number().unwrap();
+        assert!(value > 9.0e15 && value < u64::MAX as f64, "{}", value);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_seeded_source_is_reproducible() {
+        let seeded = |seed| {
+            let mut runtime = Runtime::new();
+            register_with_source(&mut runtime, RngSource::seeded(seed));
+            let expr = runtime
+                .compile("[random(), random_int(`1`, `100`), shuffle(`[1, 2, 3, 4, 5]`), sample(`[1, 2, 3, 4, 5]`, `2`)]")
+                .unwrap();
+            // Evaluate twice: the second draw continues the same sequence
+            let data = Variable::from_json("{}").unwrap();
+            (expr.search(&data).unwrap(), expr.search(&data).unwrap())
+        };
+        let (first, second) = seeded(42);
+        assert_eq!(seeded(42), (first.clone(), second.clone()));
+        assert_ne!(first, second);
+        assert_ne!(seeded(43).0, first);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_explicit_seed_overrides_source() {
+        let mut runtime = Runtime::new();
+        register_with_source(&mut runtime, RngSource::seeded(1));
+        let seeded = runtime
+            .compile("shuffle(`[1, 2, 3, 4, 5, 6]`, `9`)")
+            .unwrap();
+        let plain_runtime = setup_runtime();
+        let plain = plain_runtime
+            .compile("shuffle(`[1, 2, 3, 4, 5, 6]`, `9`)")
+            .unwrap();
+        assert_eq!(
+            seeded.search(&Variable::Null).unwrap(),
+            plain.search(&Variable::Null).unwrap()
+        );
+    }
+
+    #[cfg(all(feature = "rand", feature = "uuid"))]
+    #[test]
+    fn test_uuid_seeded() {
+        let seeded = |seed| {
+            let mut runtime = Runtime::new();
+            register_with_source(&mut runtime, RngSource::seeded(seed));
+            let expr = runtime.compile("uuid()").unwrap();
+            expr.search(&Variable::Null).unwrap()
+        };
+        let id = seeded(5);
+        assert_eq!(id, seeded(5));
+        let id = id.as_string().unwrap();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_weighted_choice() {
+        let runtime = setup_runtime();
+        let expr = runtime
+            .compile("weighted_choice(`[\"a\", \"b\", \"c\"]`, `[0, 3, 0]`)")
+            .unwrap();
+        for _ in 0..10 {
+            let result = expr.search(&Variable::Null).unwrap();
+            assert_eq!(result.as_string().unwrap(), "b");
+        }
+
+        let empty = runtime.compile("weighted_choice(`[]`, `[]`)").unwrap();
+        assert!(empty.search(&Variable::Null).unwrap().is_null());
+
+        for bad in [
+            "weighted_choice(`[1, 2]`, `[1]`)",
+            "weighted_choice(`[1, 2]`, `[1, -1]`)",
+            "weighted_choice(`[1, 2]`, `[0, 0]`)",
+        ] {
+            let expr = runtime.compile(bad).unwrap();
+            assert!(expr.search(&Variable::Null).is_err(), "{}", bad);
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_choice() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json("[1, 2, 3, 4, 5]").unwrap();
+
+        let one = runtime.compile("random_choice(@)").unwrap();
+        let value = one.search(&data).unwrap().as_number().unwrap();
+        assert!((1.0..=5.0).contains(&value));
+
+        let many = runtime.compile("random_choice(@, `3`)").unwrap();
+        let result = many.search(&data).unwrap();
+        let mut picked: Vec<i64> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_number().unwrap() as i64)
+            .collect();
+        picked.sort();
+        picked.dedup();
+        assert_eq!(picked.len(), 3);
+
+        let all = runtime.compile("length(random_choice(@, `10`))").unwrap();
+        assert_eq!(all.search(&data).unwrap().as_number(), Some(5.0));
+
+        let empty = runtime.compile("random_choice(`[]`)").unwrap();
+        assert!(empty.search(&Variable::Null).unwrap().is_null());
     }
 }
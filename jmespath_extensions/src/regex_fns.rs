@@ -16,10 +16,13 @@
 //! regex_fns::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::common::Rc;
 
 use crate::common::{
-    ArgumentType, Context, Function, JmespathError, Rcvar, Runtime, Variable, custom_error,
+    ArgumentType, Context, Function, JmespathError, LruCache, Rcvar, Runtime, Variable,
+    custom_error,
 };
 use crate::define_function;
 
@@ -32,6 +35,35 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("regex_replace", Box::new(RegexReplaceFn::new()));
 }
 
+/// Maximum number of compiled patterns kept by [`cached_regex`] on each thread.
+const REGEX_CACHE_CAPACITY: usize = 128;
+
+thread_local! {
+    static REGEX_CACHE: RefCell<LruCache<Regex>> =
+        RefCell::new(LruCache::new(REGEX_CACHE_CAPACITY));
+}
+
+/// Compile `pattern`, reusing a recently compiled `Regex` for the same pattern
+/// on this thread.
+///
+/// Every regex-using function goes through this cache, so a pattern applied
+/// inside `map_expr` over a large array is compiled once. Flags are part of
+/// the pattern (`(?i)...`), so the pattern string is the whole key. Invalid
+/// patterns are not cached.
+pub fn cached_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(re) = REGEX_CACHE.with(|cache| cache.borrow_mut().get(pattern)) {
+        return Ok(re);
+    }
+    let re = Regex::new(pattern)?;
+    REGEX_CACHE.with(|cache| cache.borrow_mut().insert(pattern, re.clone()));
+    Ok(re)
+}
+
+/// Drop every compiled pattern cached on the current thread.
+pub fn clear_regex_cache() {
+    REGEX_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
 // =============================================================================
 // regex_match(string, pattern) -> boolean
 // =============================================================================
@@ -50,7 +82,7 @@ impl Function for RegexMatchFn {
         let input = args[0].as_string().unwrap();
         let pattern = args[1].as_string().unwrap();
 
-        let re = Regex::new(pattern)
+        let re = cached_regex(pattern)
             .map_err(|e| custom_error(ctx, &format!("Invalid regex pattern: {e}")))?;
 
         Ok(Rc::new(Variable::Bool(re.is_match(input))))
@@ -75,7 +107,7 @@ impl Function for RegexExtractFn {
         let input = args[0].as_string().unwrap();
         let pattern = args[1].as_string().unwrap();
 
-        let re = Regex::new(pattern)
+        let re = cached_regex(pattern)
             .map_err(|e| custom_error(ctx, &format!("Invalid regex pattern: {e}")))?;
 
         let matches: Vec<Rcvar> = re
@@ -115,7 +147,7 @@ impl Function for RegexReplaceFn {
         let pattern = args[1].as_string().unwrap();
         let replacement = args[2].as_string().unwrap();
 
-        let re = Regex::new(pattern)
+        let re = cached_regex(pattern)
             .map_err(|e| custom_error(ctx, &format!("Invalid regex pattern: {e}")))?;
 
         let result = re.replace_all(input, replacement);
@@ -169,4 +201,23 @@ mod tests {
         let result = expr.search(&data).unwrap();
         assert_eq!(result.as_string().unwrap(), "abcXdefX");
     }
+
+    #[test]
+    fn test_regex_cache() {
+        clear_regex_cache();
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"["a1", "b2", "c"]"#).unwrap();
+        let expr = runtime
+            .compile(r"[regex_match([0], '\d'), regex_match([2], '\d')]")
+            .unwrap();
+        assert_eq!(
+            expr.search(&data).unwrap(),
+            Rc::new(Variable::from_json("[true, false]").unwrap())
+        );
+        assert!(REGEX_CACHE.with(|c| c.borrow().contains_key(r"\d")));
+        assert_eq!(REGEX_CACHE.with(|c| c.borrow().len()), 1);
+
+        assert!(cached_regex("(").is_err());
+        assert!(!REGEX_CACHE.with(|c| c.borrow().contains_key("(")));
+    }
 }
@@ -93,7 +93,15 @@ pub enum Category {
     Computing,
     MultiMatch,
     Jsonpatch,
+    Jsonpath,
+    Jsonlogic,
+    Decimal,
+    Domain,
+    Phone,
     Format,
+    /// Functions defined outside this crate with
+    /// [`define_extension_fn!`](crate::define_extension_fn)
+    Custom,
 }
 
 impl Category {
@@ -129,7 +137,13 @@ impl Category {
             Category::Computing,
             Category::MultiMatch,
             Category::Jsonpatch,
+            Category::Jsonpath,
+            Category::Jsonlogic,
+            Category::Decimal,
+            Category::Domain,
+            Category::Phone,
             Category::Format,
+            Category::Custom,
         ]
     }
 
@@ -165,7 +179,13 @@ impl Category {
             Category::Computing => "computing",
             Category::MultiMatch => "multi-match",
             Category::Jsonpatch => "jsonpatch",
+            Category::Jsonpath => "jsonpath",
+            Category::Jsonlogic => "jsonlogic",
+            Category::Decimal => "decimal",
+            Category::Domain => "domain",
+            Category::Phone => "phone",
             Category::Format => "format",
+            Category::Custom => "custom",
         }
     }
 
@@ -174,6 +194,8 @@ impl Category {
         match self {
             // Standard functions are always available
             Category::Standard => true,
+            // Custom functions are provided by the caller
+            Category::Custom => true,
             #[cfg(feature = "string")]
             Category::String => true,
             #[cfg(feature = "array")]
@@ -230,6 +252,16 @@ impl Category {
             Category::MultiMatch => true,
             #[cfg(feature = "jsonpatch")]
             Category::Jsonpatch => true,
+            #[cfg(feature = "jsonpath")]
+            Category::Jsonpath => true,
+            #[cfg(feature = "jsonlogic")]
+            Category::Jsonlogic => true,
+            #[cfg(feature = "decimal")]
+            Category::Decimal => true,
+            #[cfg(feature = "domain")]
+            Category::Domain => true,
+            #[cfg(feature = "phone")]
+            Category::Phone => true,
             #[cfg(feature = "format")]
             Category::Format => true,
             #[allow(unreachable_patterns)]
@@ -249,12 +281,15 @@ pub enum Feature {
     Fp,
     /// JEP-aligned functions
     Jep,
-    /// Format output functions (CSV, TSV)
+    /// Format output functions (CSV, TSV, sparklines)
     #[allow(non_camel_case_types)]
     format,
     /// Environment variable access (opt-in for security)
     #[allow(non_camel_case_types)]
     env,
+    /// Parallel evaluation on a thread pool (opt-in)
+    #[allow(non_camel_case_types)]
+    parallel,
 }
 
 impl Feature {
@@ -267,6 +302,7 @@ impl Feature {
             Feature::Jep,
             Feature::format,
             Feature::env,
+            Feature::parallel,
         ]
     }
 
@@ -279,6 +315,7 @@ impl Feature {
             Feature::Jep => "jep",
             Feature::format => "format",
             Feature::env => "env",
+            Feature::parallel => "parallel",
         }
     }
 }
@@ -346,7 +383,8 @@ impl FunctionRegistry {
 
     /// Register all functions in a category
     pub fn register_category(&mut self, category: Category) -> &mut Self {
-        if !category.is_available() {
+        // Custom functions have no built-in metadata; see `register_custom`
+        if !category.is_available() || category == Category::Custom {
             return self;
         }
 
@@ -358,6 +396,21 @@ impl FunctionRegistry {
         self
     }
 
+    /// Add metadata for a function defined outside this crate
+    ///
+    /// The function then shows up in [`functions`](Self::functions) and
+    /// [`get_function`](Self::get_function) alongside the built-in ones, so
+    /// tooling that lists or describes functions picks it up. This records
+    /// metadata only; register the implementation on the runtime separately.
+    ///
+    /// See [`define_extension_fn!`](crate::define_extension_fn) for
+    /// generating both from one definition.
+    pub fn register_custom(&mut self, info: FunctionInfo) -> &mut Self {
+        self.categories.insert(info.category);
+        self.registered.insert(info.name, info);
+        self
+    }
+
     /// Disable a specific function (for ACLs)
     pub fn disable_function(&mut self, name: &str) -> &mut Self {
         self.disabled.insert(name.to_string());
@@ -463,7 +516,6 @@ impl FunctionRegistry {
         }
     }
 
-    #[allow(unused_variables)]
     fn apply_category(&self, runtime: &mut Runtime, category: Category) {
         // Check which functions in this category are enabled
         let enabled_in_category: HashSet<&str> = self
@@ -478,71 +530,365 @@ impl FunctionRegistry {
         // Register the category, but we need to handle disabled functions
         // For now, we register all and rely on a wrapper for disabled check
         // TODO: More granular registration
-        match category {
-            #[cfg(feature = "string")]
-            Category::String => crate::string::register(runtime),
-            #[cfg(feature = "array")]
-            Category::Array => crate::array::register(runtime),
-            #[cfg(feature = "object")]
-            Category::Object => crate::object::register(runtime),
-            #[cfg(feature = "math")]
-            Category::Math => crate::math::register(runtime),
-            #[cfg(feature = "type")]
-            Category::Type => crate::type_conv::register(runtime),
-            #[cfg(feature = "utility")]
-            Category::Utility => crate::utility::register(runtime),
-            #[cfg(feature = "validation")]
-            Category::Validation => crate::validation::register(runtime),
-            #[cfg(feature = "path")]
-            Category::Path => crate::path::register(runtime),
-            #[cfg(feature = "expression")]
-            Category::Expression => crate::expression::register(runtime),
-            #[cfg(feature = "text")]
-            Category::Text => crate::text::register(runtime),
-            #[cfg(feature = "hash")]
-            Category::Hash => crate::hash::register(runtime),
-            #[cfg(feature = "encoding")]
-            Category::Encoding => crate::encoding::register(runtime),
-            #[cfg(feature = "regex")]
-            Category::Regex => crate::regex_fns::register(runtime),
-            #[cfg(feature = "url")]
-            Category::Url => crate::url_fns::register(runtime),
-            #[cfg(feature = "uuid")]
-            Category::Uuid => crate::random::register(runtime),
-            #[cfg(feature = "rand")]
-            Category::Rand => crate::random::register(runtime),
-            #[cfg(feature = "datetime")]
-            Category::Datetime => crate::datetime::register(runtime),
-            #[cfg(feature = "fuzzy")]
-            Category::Fuzzy => crate::fuzzy::register(runtime),
-            #[cfg(feature = "phonetic")]
-            Category::Phonetic => crate::phonetic::register(runtime),
-            #[cfg(feature = "geo")]
-            Category::Geo => crate::geo::register(runtime),
-            #[cfg(feature = "semver")]
-            Category::Semver => crate::semver_fns::register(runtime),
-            #[cfg(feature = "network")]
-            Category::Network => crate::network::register(runtime),
-            #[cfg(feature = "ids")]
-            Category::Ids => crate::ids::register(runtime),
-            #[cfg(feature = "duration")]
-            Category::Duration => crate::duration::register(runtime),
-            #[cfg(feature = "color")]
-            Category::Color => crate::color::register(runtime),
-            #[cfg(feature = "computing")]
-            Category::Computing => crate::computing::register(runtime),
-            #[cfg(feature = "multi-match")]
-            Category::MultiMatch => crate::multi_match::register(runtime),
-            #[cfg(feature = "jsonpatch")]
-            Category::Jsonpatch => crate::jsonpatch::register(runtime),
-            #[cfg(feature = "format")]
-            Category::Format => crate::format::register(runtime),
-            #[allow(unreachable_patterns)]
-            _ => {}
+        register_category_module(runtime, category);
+    }
+}
+
+/// Run the `register` function of the module backing `category`.
+///
+/// Modules may register names documented under a sibling category (the
+/// random module backs both `Rand` and `Uuid`); callers that need an exact
+/// category filter by [`category_of`].
+#[allow(unused_variables)]
+pub(crate) fn register_category_module(runtime: &mut Runtime, category: Category) {
+    match category {
+        #[cfg(feature = "string")]
+        Category::String => crate::string::register(runtime),
+        #[cfg(feature = "array")]
+        Category::Array => crate::array::register(runtime),
+        #[cfg(feature = "object")]
+        Category::Object => crate::object::register(runtime),
+        #[cfg(feature = "math")]
+        Category::Math => crate::math::register(runtime),
+        #[cfg(feature = "type")]
+        Category::Type => crate::type_conv::register(runtime),
+        #[cfg(feature = "utility")]
+        Category::Utility => crate::utility::register(runtime),
+        #[cfg(feature = "validation")]
+        Category::Validation => crate::validation::register(runtime),
+        #[cfg(feature = "path")]
+        Category::Path => crate::path::register(runtime),
+        #[cfg(feature = "expression")]
+        Category::Expression => crate::expression::register(runtime),
+        #[cfg(feature = "text")]
+        Category::Text => crate::text::register(runtime),
+        #[cfg(feature = "hash")]
+        Category::Hash => crate::hash::register(runtime),
+        #[cfg(feature = "encoding")]
+        Category::Encoding => crate::encoding::register(runtime),
+        #[cfg(feature = "regex")]
+        Category::Regex => crate::regex_fns::register(runtime),
+        #[cfg(feature = "url")]
+        Category::Url => crate::url_fns::register(runtime),
+        #[cfg(feature = "uuid")]
+        Category::Uuid => crate::random::register(runtime),
+        #[cfg(feature = "rand")]
+        Category::Rand => crate::random::register(runtime),
+        #[cfg(feature = "datetime")]
+        Category::Datetime => crate::datetime::register(runtime),
+        #[cfg(feature = "fuzzy")]
+        Category::Fuzzy => crate::fuzzy::register(runtime),
+        #[cfg(feature = "phonetic")]
+        Category::Phonetic => crate::phonetic::register(runtime),
+        #[cfg(feature = "geo")]
+        Category::Geo => crate::geo::register(runtime),
+        #[cfg(feature = "semver")]
+        Category::Semver => crate::semver_fns::register(runtime),
+        #[cfg(feature = "network")]
+        Category::Network => crate::network::register(runtime),
+        #[cfg(feature = "ids")]
+        Category::Ids => crate::ids::register(runtime),
+        #[cfg(feature = "duration")]
+        Category::Duration => crate::duration::register(runtime),
+        #[cfg(feature = "color")]
+        Category::Color => crate::color::register(runtime),
+        #[cfg(feature = "computing")]
+        Category::Computing => crate::computing::register(runtime),
+        #[cfg(feature = "multi-match")]
+        Category::MultiMatch => crate::multi_match::register(runtime),
+        #[cfg(feature = "jsonpatch")]
+        Category::Jsonpatch => crate::jsonpatch::register(runtime),
+        #[cfg(feature = "jsonpath")]
+        Category::Jsonpath => crate::jsonpath::register(runtime),
+        #[cfg(feature = "jsonlogic")]
+        Category::Jsonlogic => crate::jsonlogic::register(runtime),
+        #[cfg(feature = "decimal")]
+        Category::Decimal => crate::decimal::register(runtime),
+        #[cfg(feature = "domain")]
+        Category::Domain => crate::domain::register(runtime),
+        #[cfg(feature = "phone")]
+        Category::Phone => crate::phone::register(runtime),
+        #[cfg(feature = "format")]
+        Category::Format => crate::format::register(runtime),
+        #[allow(unreachable_patterns)]
+        _ => {}
+    }
+}
+
+/// Metadata for a function, looked up by name or alias.
+fn function_info(name: &str) -> Option<&'static FunctionInfo> {
+    generated::FUNCTIONS
+        .iter()
+        .find(|f| f.name == name || f.aliases.contains(&name))
+}
+
+/// The documented category of a function, looked up by name or alias.
+pub(crate) fn category_of(name: &str) -> Option<Category> {
+    function_info(name).map(|f| f.category)
+}
+
+/// Entry point for the [`ExtensionsBuilder`] API.
+///
+/// # Example
+///
+/// ```
+/// use jmespath::Runtime;
+/// use jmespath_extensions::Extensions;
+/// use jmespath_extensions::registry::Category;
+///
+/// let mut runtime = Runtime::new();
+/// runtime.register_builtin_functions();
+/// Extensions::builder()
+///     .allow(Category::String)
+///     .allow(Category::Rand)
+///     .deny("random")
+///     .prefix("ext_")
+///     .apply(&mut runtime)
+///     .unwrap();
+///
+/// # #[cfg(feature = "string")]
+/// assert!(runtime.get_function("ext_upper").is_some());
+/// assert!(runtime.get_function("upper").is_none());
+/// assert!(runtime.get_function("ext_random").is_none());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Extensions;
+
+impl Extensions {
+    /// Start configuring which extension functions to register.
+    pub fn builder() -> ExtensionsBuilder {
+        ExtensionsBuilder::default()
+    }
+}
+
+/// Configures a filtered, optionally prefixed set of extension functions.
+///
+/// With no [`allow`](Self::allow) calls every enabled category is included.
+/// Denying a function by its canonical name also denies its aliases.
+/// Builtin JMESPath functions are not managed by the builder; register them
+/// with `runtime.register_builtin_functions()`.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionsBuilder {
+    allowed: Vec<Category>,
+    denied: HashSet<String>,
+    denied_categories: HashSet<Category>,
+    prefix: String,
+    #[cfg(any(feature = "rand", feature = "ids"))]
+    random_seed: Option<u64>,
+}
+
+impl ExtensionsBuilder {
+    /// Include the functions of `category`.
+    pub fn allow(mut self, category: Category) -> Self {
+        if !self.allowed.contains(&category) {
+            self.allowed.push(category);
+        }
+        self
+    }
+
+    /// Exclude a single function by name or alias.
+    pub fn deny(mut self, name: &str) -> Self {
+        self.denied.insert(name.to_string());
+        self
+    }
+
+    /// Exclude every function of `category`, even if it was allowed.
+    pub fn deny_category(mut self, category: Category) -> Self {
+        self.denied_categories.insert(category);
+        self
+    }
+
+    /// Register every function as `prefix` followed by its name.
+    ///
+    /// Expression functions that look up other functions by name at runtime
+    /// (`partial`, `apply`, `compose`) expect the prefixed names.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Seed the random functions from `seed`, as
+    /// [`register_random_with_seed`](crate::register_random_with_seed) does,
+    /// before they are prefixed.
+    #[cfg(any(feature = "rand", feature = "ids"))]
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Whether `name` survives the deny lists.
+    fn permits(&self, name: &str) -> bool {
+        if self.denied.contains(name) {
+            return false;
+        }
+        match function_info(name) {
+            Some(info) => {
+                !self.denied.contains(info.name) && !self.denied_categories.contains(&info.category)
+            }
+            None => true,
+        }
+    }
+
+    /// Register the configured functions with `runtime`.
+    ///
+    /// Returns the names as registered, including any prefix, sorted. Fails
+    /// without modifying `runtime` if a denied name is not a known function,
+    /// so a typo in a deny list cannot leave the function exposed.
+    pub fn apply(&self, runtime: &mut Runtime) -> Result<Vec<String>, UnknownFunctionsError> {
+        let mut unknown: Vec<String> = self
+            .denied
+            .iter()
+            .filter(|name| {
+                function_info(name).is_none()
+                    && !generated_names::REGISTERED_NAMES.contains(&name.as_str())
+            })
+            .cloned()
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(UnknownFunctionsError { names: unknown });
+        }
+
+        let mut scratch = Runtime::new();
+        if self.allowed.is_empty() {
+            crate::register_all(&mut scratch);
+        } else {
+            for category in &self.allowed {
+                if *category != Category::Standard {
+                    crate::register_category(&mut scratch, *category);
+                }
+            }
         }
+        #[cfg(any(feature = "rand", feature = "ids"))]
+        if let Some(seed) = self.random_seed {
+            crate::register_random_with_seed(&mut scratch, seed);
+        }
+
+        let mut installed = Vec::new();
+        for name in names_registered_on(&scratch) {
+            if !self.permits(name) {
+                continue;
+            }
+            if let Some(function) = scratch.deregister_function(name) {
+                let registered_name = format!("{}{}", self.prefix, name);
+                runtime.register_function(&registered_name, function);
+                installed.push(registered_name);
+            }
+        }
+        installed.sort();
+        Ok(installed)
+    }
+}
+
+/// How to handle extension functions whose names are already registered
+/// on the target runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnConflict {
+    /// Replace the existing function (the behavior of [`register_all`](crate::register_all))
+    #[default]
+    Override,
+    /// Keep the existing function and skip the extension
+    Skip,
+    /// Fail without modifying the runtime
+    Error,
+}
+
+/// Summary of what a checked registration actually installed.
+///
+/// Every name provided by the enabled features appears in exactly one of the
+/// three lists. Each list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistrationReport {
+    /// Names that were not previously registered and are now installed
+    pub registered: Vec<&'static str>,
+    /// Names that were already registered and were left untouched
+    pub skipped: Vec<&'static str>,
+    /// Names that were already registered and have been replaced
+    pub overridden: Vec<&'static str>,
+}
+
+impl RegistrationReport {
+    /// Total number of names considered
+    pub fn total(&self) -> usize {
+        self.registered.len() + self.skipped.len() + self.overridden.len()
+    }
+}
+
+/// Error returned by a checked registration using [`OnConflict::Error`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationError {
+    /// Names that were already registered on the runtime
+    pub conflicts: Vec<&'static str>,
+}
+
+impl std::fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} function(s) already registered: {}",
+            self.conflicts.len(),
+            self.conflicts.join(", ")
+        )
     }
 }
 
+impl std::error::Error for RegistrationError {}
+
+/// Error returned by [`register_functions`](crate::register_functions) when
+/// a requested name is not provided by the enabled features, and by
+/// [`ExtensionsBuilder::apply`] when a denied name is not a known function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFunctionsError {
+    /// The names that were not recognized
+    pub names: Vec<String>,
+}
+
+impl std::fmt::Display for UnknownFunctionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} unknown function(s): {}",
+            self.names.len(),
+            self.names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownFunctionsError {}
+
+/// Extension function names provided by the enabled features.
+///
+/// The list generated by build.rs covers every feature, so names from disabled
+/// features are filtered out by registering into a scratch runtime.
+pub(crate) fn provided_names() -> Vec<&'static str> {
+    let mut scratch = Runtime::new();
+    crate::register_all(&mut scratch);
+    names_registered_on(&scratch)
+}
+
+/// Extension function names currently registered on `runtime`.
+pub(crate) fn names_registered_on(runtime: &Runtime) -> Vec<&'static str> {
+    generated_names::REGISTERED_NAMES
+        .iter()
+        .copied()
+        .filter(|name| runtime.get_function(name).is_some())
+        .collect()
+}
+
+/// Names of the JMESPath builtin functions.
+pub(crate) fn standard_names() -> impl Iterator<Item = &'static str> {
+    generated::FUNCTIONS
+        .iter()
+        .filter(|f| f.is_standard)
+        .map(|f| f.name)
+}
+
+/// Metadata for every documented function, regardless of registration state.
+#[cfg(feature = "utility")]
+pub(crate) fn function_metadata() -> &'static [FunctionInfo] {
+    generated::FUNCTIONS
+}
+
 /// Get function metadata for a category (from generated data)
 fn get_category_functions(category: Category) -> Vec<FunctionInfo> {
     generated::FUNCTIONS
@@ -556,3 +902,8 @@ fn get_category_functions(category: Category) -> Vec<FunctionInfo> {
 mod generated {
     include!(concat!(env!("OUT_DIR"), "/registry_data.rs"));
 }
+
+// Include the extension function names collected by build.rs
+mod generated_names {
+    include!(concat!(env!("OUT_DIR"), "/registered_names.rs"));
+}
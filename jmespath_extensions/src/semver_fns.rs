@@ -1,6 +1,9 @@
 //! Semantic versioning functions.
 //!
-//! This module provides semver_fns functions for JMESPath queries.
+//! This module provides semver_fns functions for JMESPath queries. Besides strict
+//! semver it understands [CalVer](https://calver.org/) and Python
+//! [PEP 440](https://peps.python.org/pep-0440/) versions, which show up in SBOMs
+//! and requirements exports.
 //!
 //! For complete function reference with signatures and examples, see the
 //! [`functions`](crate::functions) module documentation or use `jpx --list-category semver_fns`.
@@ -16,12 +19,15 @@
 //! semver_fns::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
+use std::collections::BTreeMap;
 
-use semver_crate::{Version, VersionReq};
+use semver_crate::{BuildMetadata, Comparator, Op, Prerelease, Version, VersionReq};
 
 use crate::common::Function;
-use crate::{ArgumentType, Context, JmespathError, Rcvar, Runtime, Signature, Variable};
+use crate::{
+    ArgumentType, Context, ErrorReason, JmespathError, Rcvar, Runtime, Signature, Variable,
+};
 
 /// Register all semver functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
@@ -32,6 +38,144 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("semver_compare", Box::new(SemverCompareFn::new()));
     runtime.register_function("semver_satisfies", Box::new(SemverSatisfiesFn::new()));
     runtime.register_function("semver_is_valid", Box::new(SemverIsValidFn::new()));
+    runtime.register_function(
+        "semver_max_satisfying",
+        Box::new(SemverMaxSatisfyingFn::new()),
+    );
+    runtime.register_function(
+        "semver_min_satisfying",
+        Box::new(SemverMinSatisfyingFn::new()),
+    );
+    runtime.register_function("semver_sort", Box::new(SemverSortFn::new()));
+    runtime.register_function("semver_intersects", Box::new(SemverIntersectsFn::new()));
+    runtime.register_function("semver_diff", Box::new(SemverDiffFn::new()));
+    runtime.register_function("calver_parse", Box::new(CalverParseFn::new()));
+    runtime.register_function("pep440_parse", Box::new(Pep440ParseFn::new()));
+    runtime.register_function("pep440_compare", Box::new(Pep440CompareFn::new()));
+    runtime.register_function("semver_bump", Box::new(SemverBumpFn::new()));
+    runtime.register_function("semver_with", Box::new(SemverWithFn::new()));
+}
+
+fn semver_error(ctx: &Context<'_>, msg: String) -> JmespathError {
+    JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg))
+}
+
+/// Parse every string in an array that is a valid version, keeping the original text.
+fn parse_versions(values: &[Rcvar]) -> Vec<(Version, &str)> {
+    values
+        .iter()
+        .filter_map(|v| v.as_string())
+        .filter_map(|s| Version::parse(s).ok().map(|v| (v, s.as_str())))
+        .collect()
+}
+
+/// Pick the lowest or highest version from `versions` matching `req_str`.
+fn pick_satisfying(versions: &[Rcvar], req_str: &str, highest: bool) -> Rcvar {
+    let req = match VersionReq::parse(req_str) {
+        Ok(r) => r,
+        Err(_) => return Rc::new(Variable::Null),
+    };
+    let matching = parse_versions(versions)
+        .into_iter()
+        .filter(|(v, _)| req.matches(v));
+    let picked = if highest {
+        matching.max_by(|a, b| a.0.cmp(&b.0))
+    } else {
+        matching.min_by(|a, b| a.0.cmp(&b.0))
+    };
+    match picked {
+        Some((_, s)) => Rc::new(Variable::String(s.to_string())),
+        None => Rc::new(Variable::Null),
+    }
+}
+
+/// One end of a version interval: the version and whether it is included.
+type Bound = Option<(Version, bool)>;
+
+fn version(major: u64, minor: u64, patch: u64) -> Version {
+    Version::new(major, minor, patch)
+}
+
+/// The interval of versions a single comparator admits, as (lower, upper).
+/// Pre-release tags only take part in ordering; the rule that `^1.2.3` does
+/// not match `1.3.0-beta` is not modelled.
+fn comparator_bounds(c: &Comparator) -> (Bound, Bound) {
+    let mut floor = version(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0));
+    floor.pre = c.pre.clone();
+    // The first version past the most specific component given
+    let next = match (c.minor, c.patch) {
+        (None, _) => version(c.major + 1, 0, 0),
+        (Some(minor), None) => version(c.major, minor + 1, 0),
+        (Some(minor), Some(patch)) => version(c.major, minor, patch + 1),
+    };
+    let exact = c.minor.is_some() && c.patch.is_some();
+
+    match c.op {
+        Op::Exact | Op::Wildcard if exact => (Some((floor.clone(), true)), Some((floor, true))),
+        Op::Exact | Op::Wildcard => (Some((floor, true)), Some((next, false))),
+        Op::Greater if exact => (Some((floor, false)), None),
+        Op::Greater => (Some((next, true)), None),
+        Op::GreaterEq => (Some((floor, true)), None),
+        Op::Less => (None, Some((floor, false))),
+        Op::LessEq if exact => (None, Some((floor, true))),
+        Op::LessEq => (None, Some((next, false))),
+        Op::Tilde => {
+            let upper = match c.minor {
+                Some(minor) => version(c.major, minor + 1, 0),
+                None => version(c.major + 1, 0, 0),
+            };
+            (Some((floor, true)), Some((upper, false)))
+        }
+        Op::Caret => {
+            // Bump the left-most non-zero component that was given
+            let upper = match (c.major, c.minor, c.patch) {
+                (0, Some(0), Some(patch)) => version(0, 0, patch + 1),
+                (0, Some(0), None) => version(0, 1, 0),
+                (0, Some(minor), _) => version(0, minor + 1, 0),
+                (major, _, _) => version(major + 1, 0, 0),
+            };
+            (Some((floor, true)), Some((upper, false)))
+        }
+        _ => (None, None),
+    }
+}
+
+/// The interval admitted by a whole requirement (comparators are ANDed).
+fn req_bounds(req: &VersionReq) -> (Bound, Bound) {
+    let mut lower: Bound = None;
+    let mut upper: Bound = None;
+    for c in &req.comparators {
+        let (lo, hi) = comparator_bounds(c);
+        lower = tighter(lower, lo, true);
+        upper = tighter(upper, hi, false);
+    }
+    (lower, upper)
+}
+
+/// Combine two bounds on the same side, keeping the more restrictive one.
+fn tighter(a: Bound, b: Bound, is_lower: bool) -> Bound {
+    match (a, b) {
+        (None, x) | (x, None) => x,
+        (Some((va, ia)), Some((vb, ib))) => {
+            let a_wins = match va.cmp(&vb) {
+                std::cmp::Ordering::Equal => !ia,
+                std::cmp::Ordering::Greater => is_lower,
+                std::cmp::Ordering::Less => !is_lower,
+            };
+            if a_wins {
+                Some((va, ia))
+            } else {
+                Some((vb, ib))
+            }
+        }
+    }
+}
+
+fn interval_is_empty(lower: &Bound, upper: &Bound) -> bool {
+    match (lower, upper) {
+        (Some((lo, lo_inc)), Some((hi, hi_inc))) => lo > hi || (lo == hi && !(*lo_inc && *hi_inc)),
+        _ => false,
+    }
 }
 
 // =============================================================================
@@ -310,6 +454,852 @@ impl Function for SemverIsValidFn {
     }
 }
 
+// =============================================================================
+// semver_max_satisfying(versions, requirement) -> string
+// =============================================================================
+
+pub struct SemverMaxSatisfyingFn {
+    signature: Signature,
+}
+
+impl Default for SemverMaxSatisfyingFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverMaxSatisfyingFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for SemverMaxSatisfyingFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let versions = args[0].as_array().unwrap();
+        let req_str = args[1].as_string().unwrap();
+        Ok(pick_satisfying(versions, req_str, true))
+    }
+}
+
+// =============================================================================
+// semver_min_satisfying(versions, requirement) -> string
+// =============================================================================
+
+pub struct SemverMinSatisfyingFn {
+    signature: Signature,
+}
+
+impl Default for SemverMinSatisfyingFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverMinSatisfyingFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for SemverMinSatisfyingFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let versions = args[0].as_array().unwrap();
+        let req_str = args[1].as_string().unwrap();
+        Ok(pick_satisfying(versions, req_str, false))
+    }
+}
+
+// =============================================================================
+// semver_sort(versions) -> array
+// Valid versions in ascending semver order, followed by anything that does
+// not parse, in its original order.
+// =============================================================================
+
+pub struct SemverSortFn {
+    signature: Signature,
+}
+
+impl Default for SemverSortFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverSortFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for SemverSortFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let values = args[0].as_array().unwrap();
+
+        let mut valid = Vec::new();
+        let mut invalid = Vec::new();
+        for value in values {
+            match value.as_string().and_then(|s| Version::parse(s).ok()) {
+                Some(v) => valid.push((v, value.clone())),
+                None => invalid.push(value.clone()),
+            }
+        }
+        valid.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let sorted = valid.into_iter().map(|(_, v)| v).chain(invalid).collect();
+        Ok(Rc::new(Variable::Array(sorted)))
+    }
+}
+
+// =============================================================================
+// semver_intersects(requirement_a, requirement_b) -> bool
+// =============================================================================
+
+pub struct SemverIntersectsFn {
+    signature: Signature,
+}
+
+impl Default for SemverIntersectsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverIntersectsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for SemverIntersectsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let a = match VersionReq::parse(args[0].as_string().unwrap()) {
+            Ok(r) => r,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+        let b = match VersionReq::parse(args[1].as_string().unwrap()) {
+            Ok(r) => r,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+
+        let (a_lower, a_upper) = req_bounds(&a);
+        let (b_lower, b_upper) = req_bounds(&b);
+        let lower = tighter(a_lower, b_lower, true);
+        let upper = tighter(a_upper, b_upper, false);
+        Ok(Rc::new(Variable::Bool(!interval_is_empty(&lower, &upper))))
+    }
+}
+
+// =============================================================================
+// semver_diff(v1, v2) -> string ("major", "minor", "patch", "prerelease" or null)
+// =============================================================================
+
+pub struct SemverDiffFn {
+    signature: Signature,
+}
+
+impl Default for SemverDiffFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverDiffFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for SemverDiffFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let v1 = match Version::parse(args[0].as_string().unwrap()) {
+            Ok(v) => v,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+        let v2 = match Version::parse(args[1].as_string().unwrap()) {
+            Ok(v) => v,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+
+        // Build metadata does not affect precedence, so it is not a difference
+        let diff = if v1.major != v2.major {
+            "major"
+        } else if v1.minor != v2.minor {
+            "minor"
+        } else if v1.patch != v2.patch {
+            "patch"
+        } else if v1.pre != v2.pre {
+            "prerelease"
+        } else {
+            return Ok(Rc::new(Variable::Null));
+        };
+        Ok(Rc::new(Variable::String(diff.to_string())))
+    }
+}
+
+// =============================================================================
+// CalVer
+// =============================================================================
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Take a run of ASCII digits (at most `max` of them) from the front of `s`.
+fn take_digits(s: &str, max: usize) -> Option<(u64, &str, usize)> {
+    let len = s
+        .bytes()
+        .take(max)
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    if len == 0 {
+        return None;
+    }
+    Some((s[..len].parse().ok()?, &s[len..], len))
+}
+
+/// Parse a CalVer string against a format such as `YYYY.0M.MICRO`. Supported
+/// tokens are YYYY, YY, 0Y, MM, 0M, WW, 0W, DD, 0D, MAJOR, MINOR, MICRO and
+/// MODIFIER (the rest of the string); any other character must match literally.
+fn parse_calver(input: &str, format: &str) -> Option<BTreeMap<String, serde_json::Value>> {
+    const TOKENS: [&str; 13] = [
+        "YYYY", "YY", "0Y", "MM", "0M", "WW", "0W", "DD", "0D", "MAJOR", "MINOR", "MICRO",
+        "MODIFIER",
+    ];
+    let mut fields: BTreeMap<String, serde_json::Value> = BTreeMap::new();
+    let mut rest = input;
+    let mut fmt = format;
+
+    while !fmt.is_empty() {
+        let Some(token) = TOKENS.iter().find(|t| fmt.starts_with(**t)) else {
+            let c = fmt.chars().next()?;
+            rest = rest.strip_prefix(c)?;
+            fmt = &fmt[c.len_utf8()..];
+            continue;
+        };
+        fmt = &fmt[token.len()..];
+
+        if *token == "MODIFIER" {
+            if rest.is_empty() {
+                return None;
+            }
+            fields.insert("modifier".to_string(), rest.into());
+            rest = "";
+            continue;
+        }
+
+        let (max, padded) = match *token {
+            "YYYY" => (4, true),
+            "0Y" | "0M" | "0W" | "0D" => (2, true),
+            "YY" | "MM" | "WW" | "DD" => (2, false),
+            _ => (usize::MAX, false),
+        };
+        let (value, remaining, len) = take_digits(rest, max)?;
+        if padded && len != max {
+            return None;
+        }
+        rest = remaining;
+
+        let (key, value) = match *token {
+            "YYYY" => ("year", value),
+            "YY" | "0Y" => ("year", 2000 + value),
+            "MM" | "0M" if (1..=12).contains(&value) => ("month", value),
+            "WW" | "0W" if (1..=53).contains(&value) => ("week", value),
+            "DD" | "0D" if value >= 1 => ("day", value),
+            "MAJOR" => ("major", value),
+            "MINOR" => ("minor", value),
+            "MICRO" => ("micro", value),
+            _ => return None,
+        };
+        fields.insert(key.to_string(), value.into());
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+
+    if let (Some(year), Some(month), Some(day)) = (
+        fields.get("year").and_then(|v| v.as_u64()),
+        fields.get("month").and_then(|v| v.as_u64()),
+        fields.get("day").and_then(|v| v.as_u64()),
+    ) && day > days_in_month(year, month)
+    {
+        return None;
+    }
+    Some(fields)
+}
+
+// =============================================================================
+// calver_parse(s, format?) -> object
+// Without a format, accepts YYYY.MM or YY.MM, an optional numeric MICRO and an
+// optional "-modifier" (e.g. "2024.04.1-beta").
+// =============================================================================
+
+pub struct CalverParseFn {
+    signature: Signature,
+}
+
+impl Default for CalverParseFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CalverParseFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], Some(ArgumentType::String)),
+        }
+    }
+}
+
+impl Function for CalverParseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap().trim();
+
+        let parsed = match args.get(1).and_then(|f| f.as_string()) {
+            Some(format) => parse_calver(s, format),
+            None => {
+                let (version, modifier) = match s.split_once('-') {
+                    Some((v, m)) => (v, Some(m)),
+                    None => (s, None),
+                };
+                let year = if version.split('.').next().is_some_and(|y| y.len() == 4) {
+                    "YYYY"
+                } else {
+                    "YY"
+                };
+                let format = match version.split('.').count() {
+                    2 => format!("{}.MM", year),
+                    3 => format!("{}.MM.MICRO", year),
+                    _ => return Ok(Rc::new(Variable::Null)),
+                };
+                let format = match modifier {
+                    Some(_) => format + "-MODIFIER",
+                    None => format,
+                };
+                parse_calver(s, &format)
+            }
+        };
+
+        match parsed {
+            Some(fields) => {
+                let obj = serde_json::Value::Object(fields.into_iter().collect());
+                Ok(Rc::new(Variable::from_json(&obj.to_string()).unwrap()))
+            }
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// PEP 440
+// =============================================================================
+
+/// A parsed PEP 440 version.
+struct Pep440 {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<(&'static str, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+    local: Option<Vec<String>>,
+}
+
+/// Strip an optional `.`, `-` or `_` separator.
+fn strip_separator(s: &str) -> &str {
+    s.strip_prefix(['.', '-', '_']).unwrap_or(s)
+}
+
+/// Match one of the (spelling, normalized) labels after an optional separator,
+/// followed by an optional separator and an optional number (default 0).
+fn take_labelled<'a>(
+    s: &'a str,
+    labels: &[(&str, &'static str)],
+) -> Option<(&'static str, u64, &'a str)> {
+    let after_sep = strip_separator(s);
+    let (label, rest) = labels
+        .iter()
+        .find_map(|(spelling, label)| after_sep.strip_prefix(spelling).map(|r| (*label, r)))?;
+    let after_num_sep = strip_separator(rest);
+    match take_digits(after_num_sep, usize::MAX) {
+        Some((n, rest, _)) => Some((label, n, rest)),
+        None => Some((label, 0, rest)),
+    }
+}
+
+fn parse_pep440(input: &str) -> Option<Pep440> {
+    let s = input.trim().to_ascii_lowercase();
+    let s = s.strip_prefix('v').unwrap_or(&s);
+
+    let (public, local) = match s.split_once('+') {
+        Some((public, local)) => {
+            let segments: Vec<String> = local.split(['.', '-', '_']).map(String::from).collect();
+            if segments
+                .iter()
+                .any(|seg| seg.is_empty() || !seg.chars().all(|c| c.is_ascii_alphanumeric()))
+            {
+                return None;
+            }
+            (public, Some(segments))
+        }
+        None => (s, None),
+    };
+
+    let (epoch, mut rest) = match public.split_once('!') {
+        Some((epoch, rest)) => (epoch.parse().ok()?, rest),
+        None => (0, public),
+    };
+
+    let mut release = Vec::new();
+    loop {
+        let (n, remaining, _) = take_digits(rest, usize::MAX)?;
+        release.push(n);
+        rest = remaining;
+        match rest.strip_prefix('.') {
+            Some(next) if next.starts_with(|c: char| c.is_ascii_digit()) => rest = next,
+            _ => break,
+        }
+    }
+
+    // Longer spellings first so "alpha" is not read as "a" + "lpha"
+    let pre = take_labelled(
+        rest,
+        &[
+            ("alpha", "a"),
+            ("beta", "b"),
+            ("preview", "rc"),
+            ("pre", "rc"),
+            ("rc", "rc"),
+            ("a", "a"),
+            ("b", "b"),
+            ("c", "rc"),
+        ],
+    )
+    .map(|(label, n, remaining)| {
+        rest = remaining;
+        (label, n)
+    });
+
+    // "1.0-1" is an implicit post release
+    let post = match rest
+        .strip_prefix('-')
+        .and_then(|r| take_digits(r, usize::MAX))
+    {
+        Some((n, remaining, _)) => {
+            rest = remaining;
+            Some(n)
+        }
+        None => take_labelled(rest, &[("post", "post"), ("rev", "post"), ("r", "post")]).map(
+            |(_, n, remaining)| {
+                rest = remaining;
+                n
+            },
+        ),
+    };
+
+    let dev = take_labelled(rest, &[("dev", "dev")]).map(|(_, n, remaining)| {
+        rest = remaining;
+        n
+    });
+
+    if !rest.is_empty() {
+        return None;
+    }
+    Some(Pep440 {
+        epoch,
+        release,
+        pre,
+        post,
+        dev,
+        local,
+    })
+}
+
+impl Pep440 {
+    fn normalized(&self) -> String {
+        let mut out = String::new();
+        if self.epoch != 0 {
+            out.push_str(&format!("{}!", self.epoch));
+        }
+        let release: Vec<String> = self.release.iter().map(|n| n.to_string()).collect();
+        out.push_str(&release.join("."));
+        if let Some((label, n)) = self.pre {
+            out.push_str(&format!("{}{}", label, n));
+        }
+        if let Some(n) = self.post {
+            out.push_str(&format!(".post{}", n));
+        }
+        if let Some(n) = self.dev {
+            out.push_str(&format!(".dev{}", n));
+        }
+        if let Some(local) = &self.local {
+            out.push_str(&format!("+{}", local.join(".")));
+        }
+        out
+    }
+
+    /// Sort key following the PEP 440 ordering rules.
+    fn cmp_key(&self) -> impl Ord {
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        enum Stage {
+            // X.Y.devN sorts before X.YaN
+            DevOnly,
+            Pre(u8, u64),
+            Final,
+        }
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        enum LocalSegment {
+            Alpha(String),
+            Numeric(u64),
+        }
+
+        // Trailing zeros are insignificant: 1.0 == 1.0.0
+        let mut release = self.release.clone();
+        while release.len() > 1 && release.last() == Some(&0) {
+            release.pop();
+        }
+        let pre = match (self.pre, self.post, self.dev) {
+            (None, None, Some(_)) => Stage::DevOnly,
+            (None, _, _) => Stage::Final,
+            (Some((label, n)), _, _) => {
+                let rank = match label {
+                    "a" => 0,
+                    "b" => 1,
+                    _ => 2,
+                };
+                Stage::Pre(rank, n)
+            }
+        };
+        let local = self.local.as_ref().map(|segments| {
+            segments
+                .iter()
+                .map(|seg| match seg.parse() {
+                    Ok(n) => LocalSegment::Numeric(n),
+                    Err(_) => LocalSegment::Alpha(seg.clone()),
+                })
+                .collect::<Vec<_>>()
+        });
+
+        (
+            self.epoch,
+            release,
+            pre,
+            self.post,
+            (self.dev.is_none(), self.dev),
+            local,
+        )
+    }
+}
+
+// =============================================================================
+// pep440_parse(s) -> object
+// =============================================================================
+
+pub struct Pep440ParseFn {
+    signature: Signature,
+}
+
+impl Default for Pep440ParseFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pep440ParseFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for Pep440ParseFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+
+        match parse_pep440(s) {
+            Some(v) => {
+                let obj = serde_json::json!({
+                    "epoch": v.epoch,
+                    "release": v.release,
+                    "pre": v.pre.map(|(label, n)| format!("{}{}", label, n)),
+                    "post": v.post,
+                    "dev": v.dev,
+                    "local": v.local.as_ref().map(|l| l.join(".")),
+                    "normalized": v.normalized(),
+                });
+                Ok(Rc::new(Variable::from_json(&obj.to_string()).unwrap()))
+            }
+            None => Ok(Rc::new(Variable::Null)),
+        }
+    }
+}
+
+// =============================================================================
+// pep440_compare(v1, v2) -> number (-1, 0, 1)
+// =============================================================================
+
+pub struct Pep440CompareFn {
+    signature: Signature,
+}
+
+impl Default for Pep440CompareFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pep440CompareFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for Pep440CompareFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let (Some(v1), Some(v2)) = (
+            parse_pep440(args[0].as_string().unwrap()),
+            parse_pep440(args[1].as_string().unwrap()),
+        ) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        let result = match v1.cmp_key().cmp(&v2.cmp_key()) {
+            std::cmp::Ordering::Less => -1,
+            std::cmp::Ordering::Equal => 0,
+            std::cmp::Ordering::Greater => 1,
+        };
+        Ok(Rc::new(Variable::Number(serde_json::Number::from(result))))
+    }
+}
+
+// =============================================================================
+// semver_bump(version, part, preid?) -> string
+// Follows npm semantics: bumping a pre-release of the target version releases
+// it (1.3.0-rc.1 bumped by minor is 1.3.0). Build metadata is dropped.
+// =============================================================================
+
+pub struct SemverBumpFn {
+    signature: Signature,
+}
+
+impl Default for SemverBumpFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverBumpFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![ArgumentType::String, ArgumentType::String],
+                Some(ArgumentType::String),
+            ),
+        }
+    }
+}
+
+/// The next pre-release tag: bump the trailing number of `current`, or start
+/// a new `<preid>.0` sequence. `None` when the trailing number is already
+/// `u64::MAX`.
+fn next_prerelease(current: &Prerelease, preid: Option<&str>) -> Option<String> {
+    let same_id = preid.is_none_or(|id| current.as_str().starts_with(&format!("{}.", id)));
+    if !current.is_empty() && same_id {
+        let mut parts: Vec<String> = current.as_str().split('.').map(String::from).collect();
+        match parts.last().and_then(|p| p.parse::<u64>().ok()) {
+            Some(n) => *parts.last_mut().unwrap() = n.checked_add(1)?.to_string(),
+            None => parts.push("0".to_string()),
+        }
+        return Some(parts.join("."));
+    }
+    Some(match preid {
+        Some(id) => format!("{}.0", id),
+        None => "0".to_string(),
+    })
+}
+
+impl Function for SemverBumpFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let part = args[1].as_string().unwrap();
+        let preid = args.get(2).and_then(|p| p.as_string()).map(|s| s.as_str());
+        let mut v = match Version::parse(args[0].as_string().unwrap()) {
+            Ok(v) => v,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+        let is_pre = !v.pre.is_empty();
+        let overflow = |field: &str| {
+            semver_error(
+                ctx,
+                format!(
+                    "Cannot bump {}: it is already the largest supported value",
+                    field
+                ),
+            )
+        };
+        let bump = |n: u64, field: &str| n.checked_add(1).ok_or_else(|| overflow(field));
+
+        match part.as_str() {
+            "major" => {
+                if !(is_pre && v.minor == 0 && v.patch == 0) {
+                    v.major = bump(v.major, "major")?;
+                }
+                v.minor = 0;
+                v.patch = 0;
+                v.pre = Prerelease::EMPTY;
+            }
+            "minor" => {
+                if !(is_pre && v.patch == 0) {
+                    v.minor = bump(v.minor, "minor")?;
+                }
+                v.patch = 0;
+                v.pre = Prerelease::EMPTY;
+            }
+            "patch" => {
+                if !is_pre {
+                    v.patch = bump(v.patch, "patch")?;
+                }
+                v.pre = Prerelease::EMPTY;
+            }
+            "prerelease" => {
+                if !is_pre {
+                    v.patch = bump(v.patch, "patch")?;
+                }
+                let pre = next_prerelease(&v.pre, preid).ok_or_else(|| overflow("prerelease"))?;
+                v.pre = Prerelease::new(&pre).map_err(|e| {
+                    semver_error(ctx, format!("Invalid prerelease '{}': {}", pre, e))
+                })?;
+            }
+            "release" => v.pre = Prerelease::EMPTY,
+            other => {
+                return Err(semver_error(
+                    ctx,
+                    format!(
+                        "Unknown version part '{}': expected major, minor, patch, prerelease or release",
+                        other
+                    ),
+                ));
+            }
+        }
+        v.build = BuildMetadata::EMPTY;
+        Ok(Rc::new(Variable::String(v.to_string())))
+    }
+}
+
+// =============================================================================
+// semver_with(version, fields) -> string
+// Replace any of major, minor, patch, prerelease and build. A null or empty
+// prerelease/build removes it.
+// =============================================================================
+
+pub struct SemverWithFn {
+    signature: Signature,
+}
+
+impl Default for SemverWithFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SemverWithFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Object], None),
+        }
+    }
+}
+
+impl Function for SemverWithFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let mut v = match Version::parse(args[0].as_string().unwrap()) {
+            Ok(v) => v,
+            Err(_) => return Ok(Rc::new(Variable::Null)),
+        };
+
+        for (key, value) in args[1].as_object().unwrap() {
+            match key.as_str() {
+                "major" | "minor" | "patch" => {
+                    // u64::MAX rounds up to 2^64, the first value that does not fit
+                    let n = value
+                        .as_number()
+                        .filter(|n| *n >= 0.0 && n.fract() == 0.0 && *n < u64::MAX as f64)
+                        .ok_or_else(|| {
+                            semver_error(
+                                ctx,
+                                format!("'{}' must be a non-negative integer below 2^64", key),
+                            )
+                        })? as u64;
+                    match key.as_str() {
+                        "major" => v.major = n,
+                        "minor" => v.minor = n,
+                        _ => v.patch = n,
+                    }
+                }
+                "prerelease" | "build" => {
+                    let text = match &**value {
+                        Variable::Null => "",
+                        Variable::String(s) => s.as_str(),
+                        _ => {
+                            return Err(semver_error(
+                                ctx,
+                                format!("'{}' must be a string or null", key),
+                            ));
+                        }
+                    };
+                    let invalid = |e: semver_crate::Error| {
+                        semver_error(ctx, format!("Invalid {}: {}", key, e))
+                    };
+                    if key == "prerelease" {
+                        v.pre = Prerelease::new(text).map_err(invalid)?;
+                    } else {
+                        v.build = BuildMetadata::new(text).map_err(invalid)?;
+                    }
+                }
+                other => {
+                    return Err(semver_error(
+                        ctx,
+                        format!(
+                            "Unknown field '{}': expected major, minor, patch, prerelease or build",
+                            other
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(Rc::new(Variable::String(v.to_string())))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -442,4 +1432,287 @@ mod tests {
         let result = expr.search(&data).unwrap();
         assert!(!result.as_boolean().unwrap());
     }
+
+    #[test]
+    fn test_semver_max_min_satisfying() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{"versions": ["1.2.3", "1.10.0", "2.0.0", "1.9.9", "junk", 3]}"#,
+        )
+        .unwrap();
+        let expr = runtime
+            .compile("semver_max_satisfying(versions, '^1.2')")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_string().unwrap(), "1.10.0");
+        let expr = runtime
+            .compile("semver_min_satisfying(versions, '>=1.5')")
+            .unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_string().unwrap(), "1.9.9");
+        let expr = runtime
+            .compile("semver_max_satisfying(versions, '^3')")
+            .unwrap();
+        assert!(expr.search(&data).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_semver_sort() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"["1.10.0", "bad", "1.2.0", "1.2.0-rc.1", "0.9.0"]"#).unwrap();
+        let expr = runtime.compile("semver_sort(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let sorted: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(
+            sorted,
+            vec!["0.9.0", "1.2.0-rc.1", "1.2.0", "1.10.0", "bad"]
+        );
+    }
+
+    #[test]
+    fn test_semver_intersects() {
+        let runtime = setup();
+        for (a, b, expected) in [
+            ("^1.2", "~1.4.0", true),
+            ("^1.2", "^2", false),
+            (">=1.0.0, <1.5.0", ">1.4.9", true),
+            ("<1.5.0", ">=1.5.0", false),
+            ("<=1.5.0", ">=1.5.0", true),
+            ("^0.2.3", "0.3", false),
+            ("*", "=4.5.6", true),
+            ("1.2.*", ">1.2", false),
+        ] {
+            let expr = runtime
+                .compile(&format!("semver_intersects('{}', '{}')", a, b))
+                .unwrap();
+            let result = expr.search(Variable::Null).unwrap();
+            assert_eq!(result.as_boolean(), Some(expected), "{} / {}", a, b);
+        }
+        let expr = runtime.compile("semver_intersects('^1', 'nope')").unwrap();
+        assert!(expr.search(Variable::Null).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_semver_diff() {
+        let runtime = setup();
+        for (a, b, expected) in [
+            ("1.2.3", "2.0.0", Some("major")),
+            ("1.2.3", "1.3.0", Some("minor")),
+            ("1.2.3", "1.2.4", Some("patch")),
+            ("1.2.3-rc.1", "1.2.3", Some("prerelease")),
+            ("1.2.3", "1.2.3+build.5", None),
+            ("1.2.3", "nope", None),
+        ] {
+            let expr = runtime
+                .compile(&format!("semver_diff('{}', '{}')", a, b))
+                .unwrap();
+            let result = expr.search(Variable::Null).unwrap();
+            assert_eq!(
+                result.as_string().map(|s| s.as_str()),
+                expected,
+                "{} / {}",
+                a,
+                b
+            );
+        }
+    }
+
+    fn eval(runtime: &Runtime, expr: &str) -> Rcvar {
+        runtime
+            .compile(expr)
+            .unwrap()
+            .search(Variable::Null)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_calver_parse_auto() {
+        let runtime = setup();
+        let result = eval(&runtime, "calver_parse('2024.04.1-beta')");
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("year").unwrap().as_number(), Some(2024.0));
+        assert_eq!(obj.get("month").unwrap().as_number(), Some(4.0));
+        assert_eq!(obj.get("micro").unwrap().as_number(), Some(1.0));
+        assert_eq!(obj.get("modifier").unwrap().as_string().unwrap(), "beta");
+
+        let result = eval(&runtime, "calver_parse('24.10')");
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("year").unwrap().as_number(), Some(2024.0));
+        assert_eq!(obj.get("month").unwrap().as_number(), Some(10.0));
+
+        assert!(eval(&runtime, "calver_parse('2024.13')").is_null());
+        assert!(eval(&runtime, "calver_parse('1.2.3.4')").is_null());
+    }
+
+    #[test]
+    fn test_calver_parse_format() {
+        let runtime = setup();
+        let result = eval(&runtime, "calver_parse('2024.02.29', 'YYYY.0M.0D')");
+        assert_eq!(
+            result.as_object().unwrap().get("day").unwrap().as_number(),
+            Some(29.0)
+        );
+        assert!(eval(&runtime, "calver_parse('2023.02.29', 'YYYY.0M.0D')").is_null());
+        // 0M requires zero padding
+        assert!(eval(&runtime, "calver_parse('2024.2.1', 'YYYY.0M.0D')").is_null());
+
+        let result = eval(&runtime, "calver_parse('24w07.3', 'YYw0W.MINOR')");
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("week").unwrap().as_number(), Some(7.0));
+        assert_eq!(obj.get("minor").unwrap().as_number(), Some(3.0));
+    }
+
+    #[test]
+    fn test_pep440_parse() {
+        let runtime = setup();
+        let result = eval(
+            &runtime,
+            "pep440_parse('1!2.0.0-Alpha_3.post-2.dev4+Ubuntu-1')",
+        );
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("epoch").unwrap().as_number(), Some(1.0));
+        assert_eq!(obj.get("pre").unwrap().as_string().unwrap(), "a3");
+        assert_eq!(obj.get("post").unwrap().as_number(), Some(2.0));
+        assert_eq!(obj.get("dev").unwrap().as_number(), Some(4.0));
+        assert_eq!(obj.get("local").unwrap().as_string().unwrap(), "ubuntu.1");
+        assert_eq!(
+            obj.get("normalized").unwrap().as_string().unwrap(),
+            "1!2.0.0a3.post2.dev4+ubuntu.1"
+        );
+
+        let result = eval(&runtime, "pep440_parse('v1.0-1')");
+        assert_eq!(
+            result
+                .as_object()
+                .unwrap()
+                .get("normalized")
+                .unwrap()
+                .as_string()
+                .unwrap(),
+            "1.0.post1"
+        );
+
+        for invalid in ["1.0.0-", "abc", "1..2", "1.0+", "1.0+a..b"] {
+            let expr = format!("pep440_parse('{}')", invalid);
+            assert!(eval(&runtime, &expr).is_null(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_pep440_compare_ordering() {
+        let runtime = setup();
+        // Ascending order from the PEP 440 specification examples
+        let ordered = [
+            "1.0.dev456",
+            "1.0a1",
+            "1.0a2.dev456",
+            "1.0a12.dev456",
+            "1.0a12",
+            "1.0b1.dev456",
+            "1.0b2",
+            "1.0b2.post345.dev456",
+            "1.0b2.post345",
+            "1.0rc1.dev456",
+            "1.0rc1",
+            "1.0",
+            "1.0+abc.5",
+            "1.0+abc.7",
+            "1.0+5",
+            "1.0.post456.dev34",
+            "1.0.post456",
+            "1.1.dev1",
+            "1!0.1",
+        ];
+        for pair in ordered.windows(2) {
+            let expr = format!("pep440_compare('{}', '{}')", pair[0], pair[1]);
+            assert_eq!(eval(&runtime, &expr).as_number(), Some(-1.0), "{}", expr);
+        }
+        assert_eq!(
+            eval(&runtime, "pep440_compare('1.0', '1.0.0')").as_number(),
+            Some(0.0)
+        );
+        assert!(eval(&runtime, "pep440_compare('1.0', 'nope')").is_null());
+    }
+
+    #[test]
+    fn test_semver_bump() {
+        let runtime = setup();
+        for (version, part, expected) in [
+            ("1.2.3", "major", "2.0.0"),
+            ("1.2.3", "minor", "1.3.0"),
+            ("1.2.3+build.1", "patch", "1.2.4"),
+            ("2.0.0-rc.1", "major", "2.0.0"),
+            ("1.3.0-rc.1", "minor", "1.3.0"),
+            ("1.3.1-rc.1", "minor", "1.4.0"),
+            ("1.2.4-rc.1", "patch", "1.2.4"),
+            ("1.2.3", "prerelease", "1.2.4-0"),
+            ("1.2.4-rc.1", "prerelease", "1.2.4-rc.2"),
+            ("1.2.4-beta", "prerelease", "1.2.4-beta.0"),
+            ("1.2.4-rc.1", "release", "1.2.4"),
+        ] {
+            let expr = format!("semver_bump('{}', '{}')", version, part);
+            assert_eq!(
+                eval(&runtime, &expr).as_string().unwrap(),
+                expected,
+                "{}",
+                expr
+            );
+        }
+        assert_eq!(
+            eval(&runtime, "semver_bump('1.2.3', 'prerelease', 'rc')")
+                .as_string()
+                .unwrap(),
+            "1.2.4-rc.0"
+        );
+        assert_eq!(
+            eval(&runtime, "semver_bump('1.2.4-beta.3', 'prerelease', 'rc')")
+                .as_string()
+                .unwrap(),
+            "1.2.4-rc.0"
+        );
+        assert!(eval(&runtime, "semver_bump('nope', 'major')").is_null());
+        for expr in [
+            "semver_bump('1.2.3', 'huge')",
+            "semver_bump('18446744073709551615.0.0', 'major')",
+            "semver_bump('1.18446744073709551615.0', 'minor')",
+            "semver_bump('1.2.18446744073709551615', 'patch')",
+            "semver_bump('1.2.3-rc.18446744073709551615', 'prerelease')",
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_semver_with() {
+        let runtime = setup();
+        let with = |expr: &str| eval(&runtime, expr).as_string().unwrap().clone();
+        assert_eq!(
+            with(r#"semver_with('1.2.3', `{"prerelease": "rc.1"}`)"#),
+            "1.2.3-rc.1"
+        );
+        assert_eq!(
+            with(r#"semver_with('1.2.3-rc.1+abc', `{"prerelease": null, "build": "sha.5"}`)"#),
+            "1.2.3+sha.5"
+        );
+        assert_eq!(
+            with(r#"semver_with('1.2.3', `{"major": 4, "patch": 0}`)"#),
+            "4.2.0"
+        );
+
+        for expr in [
+            r#"semver_with('1.2.3', `{"prerelease": "bad..tag"}`)"#,
+            r#"semver_with('1.2.3', `{"minor": -1}`)"#,
+            r#"semver_with('1.2.3', `{"minor": 1.5}`)"#,
+            r#"semver_with('1.2.3', `{"major": 1e20}`)"#,
+            r#"semver_with('1.2.3', `{"epoch": 1}`)"#,
+        ] {
+            let compiled = runtime.compile(expr).unwrap();
+            assert!(compiled.search(Variable::Null).is_err(), "{}", expr);
+        }
+    }
 }
@@ -16,7 +16,7 @@
 //! string::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
@@ -319,6 +319,10 @@ impl Function for PadLeftFn {
         let result = if s.len() >= width {
             s.to_string()
         } else {
+            crate::limits::check_string_len(
+                ctx,
+                (width - s.len()).saturating_mul(pad.len_utf8()) + s.len(),
+            )?;
             format!("{}{}", pad.to_string().repeat(width - s.len()), s)
         };
 
@@ -372,6 +376,10 @@ impl Function for PadRightFn {
         let result = if s.len() >= width {
             s.to_string()
         } else {
+            crate::limits::check_string_len(
+                ctx,
+                (width - s.len()).saturating_mul(pad.len_utf8()) + s.len(),
+            )?;
             format!("{}{}", s, pad.to_string().repeat(width - s.len()))
         };
 
@@ -531,6 +539,7 @@ impl Function for RepeatFn {
             )
         })?;
 
+        crate::limits::check_string_len(ctx, s.len().saturating_mul(count))?;
         Ok(Rc::new(Variable::String(s.repeat(count))))
     }
 }
@@ -1952,7 +1961,7 @@ impl Function for RedactFn {
             "[REDACTED]".to_string()
         };
 
-        let re = regex::Regex::new(pattern).map_err(|e| {
+        let re = crate::regex_fns::cached_regex(pattern).map_err(|e| {
             JmespathError::new(
                 ctx.expression,
                 0,
@@ -2125,6 +2134,10 @@ impl Function for CenterFn {
         }
 
         let total_padding = width - char_count;
+        crate::limits::check_string_len(
+            ctx,
+            total_padding.saturating_mul(pad_char.len_utf8()) + s.len(),
+        )?;
         let left_padding = total_padding / 2;
         let right_padding = total_padding - left_padding;
 
@@ -16,11 +16,14 @@
 //! text::register(&mut runtime);
 //! ```
 
-use std::collections::BTreeMap;
-use std::rc::Rc;
+use crate::common::Rc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::LazyLock;
 
 use crate::common::Function;
-use crate::{ArgumentType, Context, JmespathError, Rcvar, Runtime, Signature, Variable};
+use crate::{
+    ArgumentType, Context, ErrorReason, JmespathError, Rcvar, Runtime, Signature, Variable,
+};
 
 /// Register all text functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
@@ -38,6 +41,16 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("ngrams", Box::new(NgramsFn::new()));
     runtime.register_function("bigrams", Box::new(BigramsFn::new()));
     runtime.register_function("trigrams", Box::new(TrigramsFn::new()));
+    runtime.register_function("char_ngrams", Box::new(CharNgramsFn::new()));
+    runtime.register_function("shingles", Box::new(ShinglesFn::new()));
+    runtime.register_function("tf_idf", Box::new(TfIdfFn::new()));
+    runtime.register_function("corpus_stats", Box::new(CorpusStatsFn::new()));
+    runtime.register_function("bm25_score", Box::new(Bm25ScoreFn::new()));
+    runtime.register_function("keywords", Box::new(KeywordsFn::new()));
+    runtime.register_function("summarize", Box::new(SummarizeFn::new()));
+    runtime.register_function("sentiment", Box::new(SentimentFn::new()));
+    #[cfg(feature = "language")]
+    runtime.register_function("detect_language", Box::new(DetectLanguageFn::new()));
 }
 
 // Average reading speed in words per minute
@@ -327,18 +340,10 @@ impl Function for WordFrequenciesFn {
         self.signature.validate(args, ctx)?;
         let s = args[0].as_string().unwrap();
 
+        // Normalize: lowercase and remove punctuation
         let mut freq: BTreeMap<String, usize> = BTreeMap::new();
-        for word in s.split_whitespace() {
-            // Normalize: lowercase and remove punctuation
-            let normalized: String = word
-                .chars()
-                .filter(|c| c.is_alphanumeric())
-                .collect::<String>()
-                .to_lowercase();
-
-            if !normalized.is_empty() {
-                *freq.entry(normalized).or_insert(0) += 1;
-            }
+        for term in tokenize(s) {
+            *freq.entry(term).or_insert(0) += 1;
         }
 
         let obj: serde_json::Map<String, serde_json::Value> = freq
@@ -396,18 +401,7 @@ impl Function for NgramsFn {
         }
 
         let result = match ngram_type {
-            "char" => {
-                // Character n-grams
-                let chars: Vec<char> = s.chars().collect();
-                if chars.len() < n {
-                    vec![]
-                } else {
-                    chars
-                        .windows(n)
-                        .map(|w| Rc::new(Variable::String(w.iter().collect())))
-                        .collect()
-                }
-            }
+            "char" => char_ngrams(s, n),
             _ => {
                 // Word n-grams (default)
                 let words: Vec<&str> = s.split_whitespace().collect();
@@ -528,6 +522,848 @@ impl Function for TrigramsFn {
     }
 }
 
+/// Overlapping character n-grams of `s` (empty if `s` is shorter than `n`).
+fn char_ngrams(s: &str, n: usize) -> Vec<Rcvar> {
+    let chars: Vec<char> = s.chars().collect();
+    if n == 0 || chars.len() < n {
+        return vec![];
+    }
+    chars
+        .windows(n)
+        .map(|w| Rc::new(Variable::String(w.iter().collect())))
+        .collect()
+}
+
+// =============================================================================
+// char_ngrams(s, n) -> array
+// Character n-grams; shorthand for ngrams(s, n, 'char').
+// =============================================================================
+
+pub struct CharNgramsFn {
+    signature: Signature,
+}
+
+impl Default for CharNgramsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CharNgramsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Number], None),
+        }
+    }
+}
+
+impl Function for CharNgramsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+        let n = args[1].as_number().unwrap() as usize;
+
+        Ok(Rc::new(Variable::Array(char_ngrams(s, n))))
+    }
+}
+
+// =============================================================================
+// shingles(tokens, n) -> array
+// Distinct w-shingles: each run of n consecutive tokens joined with a space,
+// in order of first appearance. Useful for Jaccard similarity of documents.
+// =============================================================================
+
+pub struct ShinglesFn {
+    signature: Signature,
+}
+
+impl Default for ShinglesFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShinglesFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array, ArgumentType::Number], None),
+        }
+    }
+}
+
+impl Function for ShinglesFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let n = args[1].as_number().unwrap() as usize;
+
+        let tokens: Vec<String> = args[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token| match &**token {
+                Variable::String(s) => s.clone(),
+                other => serde_json::to_string(other).unwrap_or_default(),
+            })
+            .collect();
+
+        if n == 0 || tokens.len() < n {
+            return Ok(Rc::new(Variable::Array(vec![])));
+        }
+
+        let mut seen = HashSet::new();
+        let result: Vec<Rcvar> = tokens
+            .windows(n)
+            .map(|w| w.join(" "))
+            .filter(|shingle| seen.insert(shingle.clone()))
+            .map(|shingle| Rc::new(Variable::String(shingle)))
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+/// Split text into lowercase alphanumeric terms, the same normalization used
+/// by word_frequencies.
+fn tokenize(s: &str) -> Vec<String> {
+    s.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        })
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+fn term_counts(terms: &[String]) -> BTreeMap<&str, usize> {
+    let mut counts = BTreeMap::new();
+    for term in terms {
+        *counts.entry(term.as_str()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Tokenize every string in an array of documents.
+fn tokenize_docs(
+    docs: &Rcvar,
+    name: &str,
+    ctx: &Context<'_>,
+) -> Result<Vec<Vec<String>>, JmespathError> {
+    docs.as_array()
+        .unwrap()
+        .iter()
+        .map(|doc| {
+            doc.as_string().map(|s| tokenize(s)).ok_or_else(|| {
+                JmespathError::new(
+                    ctx.expression,
+                    0,
+                    ErrorReason::Parse(format!("{}() expects an array of strings", name)),
+                )
+            })
+        })
+        .collect()
+}
+
+fn number_var(n: f64) -> Rcvar {
+    Rc::new(Variable::Number(
+        serde_json::Number::from_f64(n).unwrap_or_else(|| serde_json::Number::from(0)),
+    ))
+}
+
+// =============================================================================
+// tf_idf(docs) -> array
+// Per-document term weights: term frequency (count / document length) times
+// smoothed inverse document frequency ln((1 + N) / (1 + df)) + 1.
+// =============================================================================
+
+pub struct TfIdfFn {
+    signature: Signature,
+}
+
+impl Default for TfIdfFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TfIdfFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for TfIdfFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let docs = tokenize_docs(&args[0], "tf_idf", ctx)?;
+
+        let mut doc_freq: BTreeMap<&str, usize> = BTreeMap::new();
+        for terms in &docs {
+            for term in term_counts(terms).into_keys() {
+                *doc_freq.entry(term).or_insert(0) += 1;
+            }
+        }
+
+        let n = docs.len() as f64;
+        let result: Vec<Rcvar> = docs
+            .iter()
+            .map(|terms| {
+                let len = terms.len() as f64;
+                let weights: BTreeMap<String, Rcvar> = term_counts(terms)
+                    .into_iter()
+                    .map(|(term, count)| {
+                        let idf = ((1.0 + n) / (1.0 + doc_freq[term] as f64)).ln() + 1.0;
+                        (term.to_string(), number_var(count as f64 / len * idf))
+                    })
+                    .collect();
+                Rc::new(Variable::Object(weights))
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+// =============================================================================
+// corpus_stats(docs) -> object
+// Corpus statistics for bm25_score: {doc_count, avg_doc_length, doc_freq}.
+// =============================================================================
+
+pub struct CorpusStatsFn {
+    signature: Signature,
+}
+
+impl Default for CorpusStatsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CorpusStatsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::Array], None),
+        }
+    }
+}
+
+impl Function for CorpusStatsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let docs = tokenize_docs(&args[0], "corpus_stats", ctx)?;
+
+        let mut doc_freq: BTreeMap<String, Rcvar> = BTreeMap::new();
+        let mut counts: BTreeMap<&str, u64> = BTreeMap::new();
+        for terms in &docs {
+            for term in term_counts(terms).into_keys() {
+                *counts.entry(term).or_insert(0) += 1;
+            }
+        }
+        for (term, count) in counts {
+            doc_freq.insert(
+                term.to_string(),
+                Rc::new(Variable::Number(serde_json::Number::from(count))),
+            );
+        }
+
+        let total: usize = docs.iter().map(|terms| terms.len()).sum();
+        let avg = if docs.is_empty() {
+            0.0
+        } else {
+            total as f64 / docs.len() as f64
+        };
+
+        let mut stats = BTreeMap::new();
+        stats.insert(
+            "doc_count".to_string(),
+            Rc::new(Variable::Number(serde_json::Number::from(docs.len()))),
+        );
+        stats.insert("avg_doc_length".to_string(), number_var(avg));
+        stats.insert("doc_freq".to_string(), Rc::new(Variable::Object(doc_freq)));
+        Ok(Rc::new(Variable::Object(stats)))
+    }
+}
+
+// =============================================================================
+// bm25_score(query, doc, corpus_stats) -> number
+// Okapi BM25 relevance of `doc` for `query`, using statistics from
+// corpus_stats(). k1 (default 1.2) and b (default 0.75) may be set on the
+// stats object.
+// =============================================================================
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+pub struct Bm25ScoreFn {
+    signature: Signature,
+}
+
+impl Default for Bm25ScoreFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bm25ScoreFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(
+                vec![
+                    ArgumentType::String,
+                    ArgumentType::String,
+                    ArgumentType::Object,
+                ],
+                None,
+            ),
+        }
+    }
+}
+
+impl Function for Bm25ScoreFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let query = tokenize(args[0].as_string().unwrap());
+        let doc = tokenize(args[1].as_string().unwrap());
+        let stats = args[2].as_object().unwrap();
+
+        let stats_error = || {
+            JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(
+                    "bm25_score() expects corpus stats {doc_count, avg_doc_length, doc_freq}"
+                        .to_owned(),
+                ),
+            )
+        };
+        let doc_count = stats
+            .get("doc_count")
+            .and_then(|v| v.as_number())
+            .ok_or_else(stats_error)?;
+        let avg_len = stats
+            .get("avg_doc_length")
+            .and_then(|v| v.as_number())
+            .ok_or_else(stats_error)?;
+        let doc_freq = stats
+            .get("doc_freq")
+            .and_then(|v| v.as_object())
+            .ok_or_else(stats_error)?;
+        let k1 = stats
+            .get("k1")
+            .and_then(|v| v.as_number())
+            .unwrap_or(BM25_K1);
+        let b = stats.get("b").and_then(|v| v.as_number()).unwrap_or(BM25_B);
+
+        let counts = term_counts(&doc);
+        let length_norm = if avg_len > 0.0 {
+            1.0 - b + b * doc.len() as f64 / avg_len
+        } else {
+            1.0
+        };
+
+        let mut seen = HashSet::new();
+        let score: f64 = query
+            .iter()
+            .filter(|term| seen.insert(term.as_str()))
+            .filter_map(|term| {
+                let tf = *counts.get(term.as_str())? as f64;
+                let df = doc_freq
+                    .get(term)
+                    .and_then(|v| v.as_number())
+                    .unwrap_or(0.0);
+                let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+                Some(idf * tf * (k1 + 1.0) / (tf + k1 * length_norm))
+            })
+            .sum();
+
+        Ok(number_var(score))
+    }
+}
+
+/// Common English function words, used as phrase delimiters by keywords() and
+/// ignored when scoring sentences in summarize().
+const STOP_WORDS: &[&str] = &[
+    "a",
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "also",
+    "am",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "by",
+    "can",
+    "could",
+    "did",
+    "do",
+    "does",
+    "doing",
+    "down",
+    "during",
+    "each",
+    "few",
+    "for",
+    "from",
+    "further",
+    "had",
+    "has",
+    "have",
+    "having",
+    "he",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "i",
+    "if",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "itself",
+    "just",
+    "may",
+    "me",
+    "might",
+    "more",
+    "most",
+    "must",
+    "my",
+    "myself",
+    "no",
+    "nor",
+    "not",
+    "now",
+    "of",
+    "off",
+    "on",
+    "once",
+    "only",
+    "or",
+    "other",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "same",
+    "shall",
+    "she",
+    "should",
+    "so",
+    "some",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "to",
+    "too",
+    "under",
+    "until",
+    "up",
+    "very",
+    "was",
+    "we",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "why",
+    "will",
+    "with",
+    "would",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+
+// =============================================================================
+// keywords(s, n?) -> array
+// RAKE (Rapid Automatic Keyword Extraction): candidate phrases are runs of
+// words between stop words and punctuation; each word scores degree/frequency
+// and a phrase scores the sum of its words. Returns the top n (default 10)
+// phrases as [{phrase, score}], highest first.
+// =============================================================================
+
+const DEFAULT_KEYWORDS: usize = 10;
+
+pub struct KeywordsFn {
+    signature: Signature,
+}
+
+impl Default for KeywordsFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeywordsFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], Some(ArgumentType::Number)),
+        }
+    }
+}
+
+/// Split text into RAKE candidate phrases (lists of normalized words).
+fn candidate_phrases(s: &str) -> Vec<Vec<String>> {
+    let mut phrases = Vec::new();
+    for fragment in
+        s.split(|c: char| !(c.is_alphanumeric() || c.is_whitespace() || c == '\'' || c == '-'))
+    {
+        let mut current = Vec::new();
+        for word in tokenize(fragment) {
+            if STOP_WORDS.contains(&word.as_str()) || word.chars().all(|c| c.is_numeric()) {
+                if !current.is_empty() {
+                    phrases.push(std::mem::take(&mut current));
+                }
+            } else {
+                current.push(word);
+            }
+        }
+        if !current.is_empty() {
+            phrases.push(current);
+        }
+    }
+    phrases
+}
+
+impl Function for KeywordsFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+        let n = args
+            .get(1)
+            .and_then(|v| v.as_number())
+            .map(|n| n.max(0.0) as usize)
+            .unwrap_or(DEFAULT_KEYWORDS);
+
+        let phrases = candidate_phrases(s);
+
+        let mut frequency: HashMap<&str, f64> = HashMap::new();
+        let mut degree: HashMap<&str, f64> = HashMap::new();
+        for phrase in &phrases {
+            for word in phrase {
+                *frequency.entry(word).or_insert(0.0) += 1.0;
+                *degree.entry(word).or_insert(0.0) += phrase.len() as f64;
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut scored: Vec<(String, f64)> = phrases
+            .iter()
+            .map(|phrase| {
+                let score = phrase
+                    .iter()
+                    .map(|w| degree[w.as_str()] / frequency[w.as_str()])
+                    .sum();
+                (phrase.join(" "), score)
+            })
+            .filter(|(phrase, _)| seen.insert(phrase.clone()))
+            .collect();
+        // Stable sort keeps first-appearance order for ties
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let result: Vec<Rcvar> = scored
+            .into_iter()
+            .take(n)
+            .map(|(phrase, score)| {
+                let mut map = BTreeMap::new();
+                map.insert("phrase".to_string(), Rc::new(Variable::String(phrase)));
+                map.insert("score".to_string(), number_var(score));
+                Rc::new(Variable::Object(map))
+            })
+            .collect();
+
+        Ok(Rc::new(Variable::Array(result)))
+    }
+}
+
+/// Split text into trimmed sentences ending at '.', '!' or '?' followed by
+/// whitespace (or the end of the text).
+fn split_sentences(s: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at_boundary = chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if matches!(c, '.' | '!' | '?') && at_boundary {
+            let end = i + c.len_utf8();
+            sentences.push(s[start..end].trim());
+            start = end;
+        }
+    }
+    sentences.push(s[start..].trim());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
+
+// =============================================================================
+// summarize(s, sentences) -> string
+// Extractive summary: scores each sentence by the normalized frequency of its
+// non-stop words and returns the top sentences in their original order.
+// =============================================================================
+
+pub struct SummarizeFn {
+    signature: Signature,
+}
+
+impl Default for SummarizeFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SummarizeFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String, ArgumentType::Number], None),
+        }
+    }
+}
+
+impl Function for SummarizeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+        let count = args[1].as_number().unwrap().max(0.0) as usize;
+
+        let sentences = split_sentences(s);
+        if sentences.len() <= count {
+            return Ok(Rc::new(Variable::String(sentences.join(" "))));
+        }
+
+        let content_words = |sentence: &str| -> Vec<String> {
+            tokenize(sentence)
+                .into_iter()
+                .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+                .collect()
+        };
+
+        let mut frequency: HashMap<String, f64> = HashMap::new();
+        for sentence in &sentences {
+            for word in content_words(sentence) {
+                *frequency.entry(word).or_insert(0.0) += 1.0;
+            }
+        }
+        let max = frequency.values().cloned().fold(0.0, f64::max);
+
+        // Average rather than sum so long sentences are not always chosen
+        let mut ranked: Vec<(usize, f64)> = sentences
+            .iter()
+            .enumerate()
+            .map(|(i, sentence)| {
+                let words = content_words(sentence);
+                let score = if words.is_empty() || max == 0.0 {
+                    0.0
+                } else {
+                    words.iter().map(|w| frequency[w] / max).sum::<f64>() / words.len() as f64
+                };
+                (i, score)
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut chosen: Vec<usize> = ranked.into_iter().take(count).map(|(i, _)| i).collect();
+        chosen.sort_unstable();
+        let summary: Vec<&str> = chosen.into_iter().map(|i| sentences[i]).collect();
+
+        Ok(Rc::new(Variable::String(summary.join(" "))))
+    }
+}
+
+static SENTIMENT_LEXICON: LazyLock<HashMap<&'static str, i64>> = LazyLock::new(|| {
+    include_str!("../data/sentiment_lexicon.txt")
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (word, score) = line.split_once('\t')?;
+            Some((word, score.parse().ok()?))
+        })
+        .collect()
+});
+
+/// Words that flip the polarity of the term that follows them ("not good").
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "dont", "doesnt", "didnt", "isnt", "wasnt", "arent", "cant", "cannot",
+    "wont", "couldnt", "shouldnt", "hardly", "without",
+];
+
+// =============================================================================
+// sentiment(s) -> object
+// Lexicon-based sentiment: {score, comparative, positive_words, negative_words}.
+// score sums word scores (-5..5), comparative divides by the token count, and a
+// preceding negation ("not", "never", ...) flips a word's polarity.
+// =============================================================================
+
+pub struct SentimentFn {
+    signature: Signature,
+}
+
+impl Default for SentimentFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SentimentFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+impl Function for SentimentFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let tokens = tokenize(args[0].as_string().unwrap());
+
+        let mut score = 0i64;
+        let mut positive = Vec::new();
+        let mut negative = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let Some(&word_score) = SENTIMENT_LEXICON.get(token.as_str()) else {
+                continue;
+            };
+            let negated = i > 0 && NEGATIONS.contains(&tokens[i - 1].as_str());
+            let word_score = if negated { -word_score } else { word_score };
+
+            score += word_score;
+            let word = Rc::new(Variable::String(token.clone()));
+            if word_score > 0 {
+                positive.push(word);
+            } else {
+                negative.push(word);
+            }
+        }
+
+        let comparative = if tokens.is_empty() {
+            0.0
+        } else {
+            score as f64 / tokens.len() as f64
+        };
+
+        let mut map = BTreeMap::new();
+        map.insert(
+            "score".to_string(),
+            Rc::new(Variable::Number(serde_json::Number::from(score))),
+        );
+        map.insert("comparative".to_string(), number_var(comparative));
+        map.insert(
+            "positive_words".to_string(),
+            Rc::new(Variable::Array(positive)),
+        );
+        map.insert(
+            "negative_words".to_string(),
+            Rc::new(Variable::Array(negative)),
+        );
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
+// =============================================================================
+// detect_language(s) -> object
+// Trigram-based language detection (whatlang). Returns {language, confidence,
+// reliable}, where language is an ISO 639-1 code ("en"), falling back to
+// ISO 639-3 for languages without a two-letter code. Null if undetectable.
+// =============================================================================
+
+#[cfg(feature = "language")]
+pub struct DetectLanguageFn {
+    signature: Signature,
+}
+
+#[cfg(feature = "language")]
+impl Default for DetectLanguageFn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "language")]
+impl DetectLanguageFn {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::new(vec![ArgumentType::String], None),
+        }
+    }
+}
+
+#[cfg(feature = "language")]
+impl Function for DetectLanguageFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        let s = args[0].as_string().unwrap();
+
+        let Some(info) = whatlang::detect(s) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        let code = info.lang().code();
+        let language = isolang::Language::from_639_3(code)
+            .and_then(|lang| lang.to_639_1())
+            .unwrap_or(code);
+
+        let mut map = BTreeMap::new();
+        map.insert(
+            "language".to_string(),
+            Rc::new(Variable::String(language.to_string())),
+        );
+        map.insert(
+            "confidence".to_string(),
+            number_var((info.confidence() * 100.0).round() / 100.0),
+        );
+        map.insert(
+            "reliable".to_string(),
+            Rc::new(Variable::Bool(info.is_reliable())),
+        );
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -750,4 +1586,271 @@ mod tests {
         let arr = result.as_array().unwrap();
         assert_eq!(arr.len(), 0);
     }
+
+    #[test]
+    fn test_char_ngrams() {
+        let runtime = setup();
+        let data = Variable::from_json(r#""héllo""#).unwrap();
+        let expr = runtime.compile("char_ngrams(@, `2`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(arr, vec!["hé", "él", "ll", "lo"]);
+
+        let expr = runtime.compile("char_ngrams(@, `9`)").unwrap();
+        assert!(expr.search(&data).unwrap().as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shingles() {
+        let runtime = setup();
+        let data =
+            Variable::from_json(r#"["a", "rose", "is", "a", "rose", "is", "a", "rose"]"#).unwrap();
+        let expr = runtime.compile("shingles(@, `3`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr: Vec<&str> = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(arr, vec!["a rose is", "rose is a", "is a rose"]);
+
+        let expr = runtime.compile("shingles(@, `0`)").unwrap();
+        assert!(expr.search(&data).unwrap().as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tf_idf() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"["the cat sat", "the dog sat down"]"#).unwrap();
+        let expr = runtime.compile("tf_idf(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let docs = result.as_array().unwrap();
+        assert_eq!(docs.len(), 2);
+
+        let first = docs[0].as_object().unwrap();
+        // "the" appears in every document: idf = ln(3/3) + 1 = 1
+        let the = first.get("the").unwrap().as_number().unwrap();
+        assert!((the - 1.0 / 3.0).abs() < 1e-9);
+        // "cat" appears in one: idf = ln(3/2) + 1
+        let cat = first.get("cat").unwrap().as_number().unwrap();
+        assert!((cat - ((1.5f64).ln() + 1.0) / 3.0).abs() < 1e-9);
+        assert!(cat > the);
+        assert!(first.get("dog").is_none());
+    }
+
+    #[test]
+    fn test_corpus_stats() {
+        let runtime = setup();
+        let data = Variable::from_json(r#"["The cat.", "the dog sat down"]"#).unwrap();
+        let expr = runtime.compile("corpus_stats(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let stats = result.as_object().unwrap();
+        assert_eq!(stats.get("doc_count").unwrap().as_number(), Some(2.0));
+        assert_eq!(stats.get("avg_doc_length").unwrap().as_number(), Some(3.0));
+        let df = stats.get("doc_freq").unwrap().as_object().unwrap();
+        assert_eq!(df.get("the").unwrap().as_number(), Some(2.0));
+        assert_eq!(df.get("cat").unwrap().as_number(), Some(1.0));
+    }
+
+    #[test]
+    fn test_bm25_score() {
+        let runtime = setup();
+        let data = Variable::from_json(
+            r#"{"docs": ["the quick brown fox", "the lazy dog", "quick quick fox jumps"]}"#,
+        )
+        .unwrap();
+        let scores: Vec<f64> = (0..3)
+            .map(|i| {
+                let query = format!("bm25_score('quick fox', docs[{}], corpus_stats(docs))", i);
+                let expr = runtime.compile(&query).unwrap();
+                expr.search(&data).unwrap().as_number().unwrap()
+            })
+            .collect();
+        assert_eq!(scores[1], 0.0);
+        assert!(scores[2] > scores[0]);
+        assert!(scores[0] > 0.0);
+
+        let expr = runtime.compile("bm25_score('fox', 'fox', `{}`)").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_keywords() {
+        let runtime = setup();
+        let data = Variable::String(
+            "Compatibility of systems of linear constraints over the set of natural numbers. \
+             Criteria of compatibility of a system of linear Diophantine equations are considered."
+                .to_string(),
+        );
+        let expr = runtime.compile("keywords(@, `3`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let arr = result.as_array().unwrap();
+        assert_eq!(arr.len(), 3);
+
+        let phrases: Vec<&str> = arr
+            .iter()
+            .map(|k| {
+                k.as_object()
+                    .unwrap()
+                    .get("phrase")
+                    .unwrap()
+                    .as_string()
+                    .unwrap()
+                    .as_str()
+            })
+            .collect();
+        assert_eq!(phrases[0], "linear diophantine equations");
+        assert!(phrases.contains(&"linear constraints"));
+
+        let scores: Vec<f64> = arr
+            .iter()
+            .map(|k| {
+                k.as_object()
+                    .unwrap()
+                    .get("score")
+                    .unwrap()
+                    .as_number()
+                    .unwrap()
+            })
+            .collect();
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn test_keywords_empty() {
+        let runtime = setup();
+        let expr = runtime.compile("keywords('the and of')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert!(result.as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let runtime = setup();
+        let data = Variable::String(
+            "Rust is a systems programming language. Rust guarantees memory safety without a \
+             garbage collector. The weather was nice yesterday. Memory safety in Rust comes from \
+             ownership and borrowing."
+                .to_string(),
+        );
+
+        let expr = runtime.compile("summarize(@, `2`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result.as_string().unwrap(),
+            "Rust guarantees memory safety without a garbage collector. \
+             Memory safety in Rust comes from ownership and borrowing."
+        );
+
+        let expr = runtime.compile("summarize(@, `10`)").unwrap();
+        let result = expr.search(&data).unwrap();
+        assert!(result.as_string().unwrap().starts_with("Rust is a systems"));
+        assert!(result.as_string().unwrap().ends_with("borrowing."));
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(
+            split_sentences("Version 1.5 is out! Is it stable? Yes"),
+            vec!["Version 1.5 is out!", "Is it stable?", "Yes"]
+        );
+        assert!(split_sentences("   ").is_empty());
+    }
+
+    #[test]
+    fn test_sentiment() {
+        let runtime = setup();
+        let expr = runtime.compile("sentiment(@)").unwrap();
+
+        let result = expr
+            .search(Variable::String("Great product, I love it!".to_string()))
+            .unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("score").unwrap().as_number(), Some(6.0));
+        assert_eq!(obj.get("comparative").unwrap().as_number(), Some(1.2));
+        assert_eq!(
+            obj.get("positive_words").unwrap().as_array().unwrap().len(),
+            2
+        );
+        assert!(
+            obj.get("negative_words")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+
+        let result = expr
+            .search(Variable::String("Not good. Slow and buggy.".to_string()))
+            .unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj.get("score").unwrap().as_number(), Some(-7.0));
+        let negative: Vec<&str> = obj
+            .get("negative_words")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| w.as_string().unwrap().as_str())
+            .collect();
+        assert_eq!(negative, vec!["good", "slow", "buggy"]);
+
+        let result = expr.search(Variable::String(String::new())).unwrap();
+        assert_eq!(
+            result
+                .as_object()
+                .unwrap()
+                .get("score")
+                .unwrap()
+                .as_number(),
+            Some(0.0)
+        );
+    }
+
+    #[cfg(feature = "language")]
+    #[test]
+    fn test_detect_language() {
+        let runtime = setup();
+        let expr = runtime.compile("detect_language(@)").unwrap();
+        for (text, code) in [
+            (
+                "The quick brown fox jumps over the lazy dog and runs away into the forest",
+                "en",
+            ),
+            (
+                "Der schnelle braune Fuchs springt über den faulen Hund und läuft in den Wald",
+                "de",
+            ),
+            (
+                "El rápido zorro marrón salta sobre el perro perezoso y corre hacia el bosque",
+                "es",
+            ),
+            (
+                "Быстрая коричневая лиса прыгает через ленивую собаку и убегает в лес",
+                "ru",
+            ),
+        ] {
+            let result = expr.search(Variable::String(text.to_string())).unwrap();
+            let obj = result.as_object().unwrap();
+            assert_eq!(
+                obj.get("language").unwrap().as_string().unwrap(),
+                code,
+                "{}",
+                text
+            );
+            let confidence = obj.get("confidence").unwrap().as_number().unwrap();
+            assert!((0.0..=1.0).contains(&confidence));
+        }
+
+        let result = expr
+            .search(Variable::String("1234 !!".to_string()))
+            .unwrap();
+        assert!(result.is_null());
+    }
 }
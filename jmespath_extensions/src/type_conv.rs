@@ -16,7 +16,8 @@
 //! type_conv::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::common::{ArgumentType, Context, Function, JmespathError, Rcvar, Runtime, Variable};
 use crate::define_function;
@@ -36,6 +37,7 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("is_empty", Box::new(IsEmptyFn::new()));
     runtime.register_function("is_blank", Box::new(IsBlankFn::new()));
     runtime.register_function("is_json", Box::new(IsJsonFn::new()));
+    runtime.register_function("infer_schema", Box::new(InferSchemaFn::new()));
 }
 
 // =============================================================================
@@ -279,6 +281,198 @@ impl Function for IsJsonFn {
     }
 }
 
+// =============================================================================
+// infer_schema(any) -> object (JSON Schema draft 2020-12)
+// =============================================================================
+
+define_function!(InferSchemaFn, vec![ArgumentType::Any], None);
+
+impl Function for InferSchemaFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let mut schema = match SchemaShape::of(&args[0]).to_schema() {
+            Variable::Object(obj) => obj,
+            _ => BTreeMap::new(),
+        };
+        schema.insert(
+            "$schema".to_string(),
+            Rc::new(Variable::String(
+                "https://json-schema.org/draft/2020-12/schema".to_string(),
+            )),
+        );
+
+        Ok(Rc::new(Variable::Object(schema)))
+    }
+}
+
+/// Accumulated shape of one or more sample values.
+///
+/// Merging shapes is how array items become a union: scalar types are
+/// collected, objects merge their properties (keeping only keys present in
+/// every sample as `required`), and arrays merge their item shapes.
+#[derive(Default)]
+struct SchemaShape {
+    scalars: BTreeSet<&'static str>,
+    object: Option<ObjectShape>,
+    array: Option<Box<SchemaShape>>,
+}
+
+struct ObjectShape {
+    properties: BTreeMap<String, SchemaShape>,
+    required: BTreeSet<String>,
+}
+
+impl SchemaShape {
+    fn of(value: &Variable) -> Self {
+        let mut shape = SchemaShape::default();
+        match value {
+            Variable::Null => {
+                shape.scalars.insert("null");
+            }
+            Variable::Bool(_) => {
+                shape.scalars.insert("boolean");
+            }
+            Variable::Number(n) => {
+                let is_integer =
+                    n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0);
+                shape
+                    .scalars
+                    .insert(if is_integer { "integer" } else { "number" });
+            }
+            Variable::String(_) | Variable::Expref(_) => {
+                shape.scalars.insert("string");
+            }
+            Variable::Array(items) => {
+                let mut item_shape = SchemaShape::default();
+                for item in items {
+                    item_shape.merge(SchemaShape::of(item));
+                }
+                shape.array = Some(Box::new(item_shape));
+            }
+            Variable::Object(obj) => {
+                shape.object = Some(ObjectShape {
+                    properties: obj
+                        .iter()
+                        .map(|(k, v)| (k.clone(), SchemaShape::of(v)))
+                        .collect(),
+                    required: obj.keys().cloned().collect(),
+                });
+            }
+        }
+        shape
+    }
+
+    fn is_empty(&self) -> bool {
+        self.scalars.is_empty() && self.object.is_none() && self.array.is_none()
+    }
+
+    fn merge(&mut self, other: SchemaShape) {
+        if self.is_empty() {
+            *self = other;
+            return;
+        }
+        self.scalars.extend(other.scalars);
+        if self.scalars.contains("number") {
+            self.scalars.remove("integer");
+        }
+
+        self.object = match (self.object.take(), other.object) {
+            (Some(mut a), Some(b)) => {
+                a.required = a.required.intersection(&b.required).cloned().collect();
+                for (key, shape) in b.properties {
+                    a.properties.entry(key).or_default().merge(shape);
+                }
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+
+        self.array = match (self.array.take(), other.array) {
+            (Some(mut a), Some(b)) => {
+                a.merge(*b);
+                Some(a)
+            }
+            (a, b) => a.or(b),
+        };
+    }
+
+    fn to_schema(&self) -> Variable {
+        let mut branches: Vec<BTreeMap<String, Rcvar>> = Vec::new();
+
+        if !self.scalars.is_empty() {
+            let mut branch = BTreeMap::new();
+            let type_value = if self.scalars.len() == 1 {
+                Variable::String(self.scalars.iter().next().unwrap().to_string())
+            } else {
+                Variable::Array(
+                    self.scalars
+                        .iter()
+                        .map(|t| Rc::new(Variable::String(t.to_string())))
+                        .collect(),
+                )
+            };
+            branch.insert("type".to_string(), Rc::new(type_value));
+            branches.push(branch);
+        }
+
+        if let Some(obj) = &self.object {
+            let mut branch = BTreeMap::new();
+            branch.insert(
+                "type".to_string(),
+                Rc::new(Variable::String("object".to_string())),
+            );
+            branch.insert(
+                "properties".to_string(),
+                Rc::new(Variable::Object(
+                    obj.properties
+                        .iter()
+                        .map(|(k, s)| (k.clone(), Rc::new(s.to_schema())))
+                        .collect(),
+                )),
+            );
+            branch.insert(
+                "required".to_string(),
+                Rc::new(Variable::Array(
+                    obj.required
+                        .iter()
+                        .map(|k| Rc::new(Variable::String(k.clone())))
+                        .collect(),
+                )),
+            );
+            branches.push(branch);
+        }
+
+        if let Some(items) = &self.array {
+            let mut branch = BTreeMap::new();
+            branch.insert(
+                "type".to_string(),
+                Rc::new(Variable::String("array".to_string())),
+            );
+            branch.insert("items".to_string(), Rc::new(items.to_schema()));
+            branches.push(branch);
+        }
+
+        match branches.len() {
+            0 => Variable::Object(BTreeMap::new()),
+            1 => Variable::Object(branches.pop().unwrap()),
+            _ => {
+                let mut schema = BTreeMap::new();
+                schema.insert(
+                    "anyOf".to_string(),
+                    Rc::new(Variable::Array(
+                        branches
+                            .into_iter()
+                            .map(|b| Rc::new(Variable::Object(b)))
+                            .collect(),
+                    )),
+                );
+                Variable::Object(schema)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,4 +510,61 @@ mod tests {
         let result = expr.search(Variable::String("hello".to_string())).unwrap();
         assert!(!result.as_boolean().unwrap());
     }
+
+    #[test]
+    fn test_infer_schema_object() {
+        let runtime = setup_runtime();
+        let data =
+            Variable::from_json(r#"{"id": 1, "name": "a", "score": 1.5, "tags": ["x"]}"#).unwrap();
+        let expr = runtime.compile("infer_schema(@)").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "name": {"type": "string"},
+                    "score": {"type": "number"},
+                    "tags": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["id", "name", "score", "tags"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_infer_schema_array_union() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"[{"a": 1, "b": "x"}, {"a": 2.5, "c": null}, "str", 3]"#)
+            .unwrap();
+        let expr = runtime.compile("infer_schema(@).items").unwrap();
+        let result = expr.search(&data).unwrap();
+        let expected = Variable::from_json(
+            r#"{"anyOf": [
+                {"type": ["integer", "string"]},
+                {
+                    "type": "object",
+                    "properties": {
+                        "a": {"type": "number"},
+                        "b": {"type": "string"},
+                        "c": {"type": "null"}
+                    },
+                    "required": ["a"]
+                }
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(*result, expected);
+    }
+
+    #[test]
+    fn test_infer_schema_empty_array() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("infer_schema(@).items").unwrap();
+        let result = expr.search(Variable::Array(vec![])).unwrap();
+        assert!(result.as_object().unwrap().is_empty());
+    }
 }
@@ -16,8 +16,8 @@
 //! url_fns::register(&mut runtime);
 //! ```
 
+use crate::common::Rc;
 use std::collections::BTreeMap;
-use std::rc::Rc;
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
@@ -16,12 +16,14 @@
 //! utility::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
+use std::sync::{Arc, RwLock};
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
 };
 use crate::define_function;
+use crate::registry::{FunctionInfo, function_metadata};
 
 /// Register all utility functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
@@ -30,10 +32,16 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("default", Box::new(DefaultFn::new()));
     runtime.register_function("if", Box::new(IfFn::new()));
     runtime.register_function("coalesce", Box::new(CoalesceFn::new()));
+    runtime.register_function("assert_type", Box::new(AssertTypeFn::new()));
+    runtime.register_function("require", Box::new(RequireFn::new()));
     runtime.register_function("json_encode", Box::new(JsonEncodeFn::new()));
     runtime.register_function("json_decode", Box::new(JsonDecodeFn::new()));
     runtime.register_function("json_pointer", Box::new(JsonPointerFn::new()));
+    runtime.register_function("sort_keys_deep", Box::new(SortKeysDeepFn::new()));
+    runtime.register_function("canonical_json", Box::new(CanonicalJsonFn::new()));
     runtime.register_function("pretty", Box::new(PrettyFn::new()));
+    runtime.register_function("trace", Box::new(TraceFn::new()));
+    runtime.register_function("help", Box::new(HelpFn::new()));
     #[cfg(feature = "env")]
     {
         runtime.register_function("env", Box::new(EnvFn::new()));
@@ -59,7 +67,7 @@ impl Function for NowFn {
             }
         }
 
-        let timestamp = std::time::SystemTime::now()
+        let timestamp = crate::common::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
@@ -88,7 +96,7 @@ impl Function for NowMsFn {
             }
         }
 
-        let timestamp = std::time::SystemTime::now()
+        let timestamp = crate::common::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_millis() as u64)
             .unwrap_or(0);
@@ -167,6 +175,94 @@ impl Function for CoalesceFn {
     }
 }
 
+// =============================================================================
+// assert_type(value, type, message?) -> value, or error if the type differs
+// `type` is a type name (as returned by type_of) or an array of accepted names.
+// =============================================================================
+
+const TYPE_NAMES: &[&str] = &["string", "number", "boolean", "null", "array", "object"];
+
+define_function!(
+    AssertTypeFn,
+    vec![ArgumentType::Any, ArgumentType::Any],
+    Some(ArgumentType::String)
+);
+
+impl Function for AssertTypeFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let expected: Vec<&str> = match &*args[1] {
+            Variable::String(s) => vec![s.as_str()],
+            Variable::Array(items) => items
+                .iter()
+                .filter_map(|item| item.as_string().map(|s| s.as_str()))
+                .collect(),
+            _ => vec![],
+        };
+        if expected.is_empty() || expected.iter().any(|t| !TYPE_NAMES.contains(t)) {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse(format!(
+                    "assert_type() expects a type name or array of type names ({})",
+                    TYPE_NAMES.join(", ")
+                )),
+            ));
+        }
+
+        let actual = args[0].get_type().to_string();
+        if expected.contains(&actual.as_str()) {
+            return Ok(args[0].clone());
+        }
+
+        let message = match args.get(2).and_then(|m| m.as_string()) {
+            Some(message) => message.clone(),
+            None => format!(
+                "assert_type failed: expected {}, got {}",
+                expected.join(" or "),
+                actual
+            ),
+        };
+        Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(message),
+        ))
+    }
+}
+
+// =============================================================================
+// require(value, message?) -> value, or error if null
+// =============================================================================
+
+define_function!(
+    RequireFn,
+    vec![ArgumentType::Any],
+    Some(ArgumentType::String)
+);
+
+impl Function for RequireFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if !args[0].is_null() {
+            return Ok(args[0].clone());
+        }
+
+        let message = args
+            .get(1)
+            .and_then(|m| m.as_string())
+            .cloned()
+            .unwrap_or_else(|| "require failed: value is null".to_owned());
+        Err(JmespathError::new(
+            ctx.expression,
+            0,
+            ErrorReason::Parse(message),
+        ))
+    }
+}
+
 // =============================================================================
 // json_encode(any) -> string
 // =============================================================================
@@ -309,6 +405,210 @@ fn pretty_print_json(json: &str, indent_size: usize) -> String {
     result
 }
 
+// =============================================================================
+// trace(any, label?) -> any
+// =============================================================================
+
+/// Callback receiving the label and value of every `trace()` call.
+pub type TraceSink = Arc<dyn Fn(Option<&str>, &Variable) + Send + Sync>;
+
+static TRACE_SINK: RwLock<Option<TraceSink>> = RwLock::new(None);
+
+/// Route `trace()` output to `sink`.
+///
+/// Without a sink `trace()` returns its value without logging anything, so a
+/// library never writes to the host's stderr uninvited. The sink is
+/// process-wide and applies to every runtime.
+///
+/// # Example
+///
+/// ```rust
+/// use jmespath_extensions::utility::{clear_trace_sink, set_trace_sink};
+///
+/// set_trace_sink(|label, value| match label {
+///     Some(label) => eprintln!("[trace] {}: {}", label, value),
+///     None => eprintln!("[trace] {}", value),
+/// });
+/// // ... evaluate queries ...
+/// clear_trace_sink();
+/// ```
+pub fn set_trace_sink<F>(sink: F)
+where
+    F: Fn(Option<&str>, &Variable) + Send + Sync + 'static,
+{
+    *TRACE_SINK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(sink));
+}
+
+/// Restore the default `trace()` behavior of logging nothing.
+pub fn clear_trace_sink() {
+    *TRACE_SINK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+define_function!(TraceFn, vec![ArgumentType::Any], Some(ArgumentType::String));
+
+impl Function for TraceFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let label = args.get(1).and_then(|l| l.as_string().map(String::as_str));
+
+        // Clone the sink out so a sink that itself traces cannot deadlock
+        let sink = TRACE_SINK.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(sink) = sink {
+            sink(label, &args[0]);
+        }
+
+        Ok(args[0].clone())
+    }
+}
+
+// =============================================================================
+// sort_keys_deep(any) -> any
+// =============================================================================
+
+define_function!(SortKeysDeepFn, vec![ArgumentType::Any], None);
+
+impl Function for SortKeysDeepFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+        Ok(sort_keys_deep(&args[0]))
+    }
+}
+
+/// Rebuild a value with every object's keys in sorted order, recursively.
+///
+/// Objects are currently backed by an ordered map, so this mostly makes the
+/// ordering guarantee explicit for callers that hash or diff encoded output.
+fn sort_keys_deep(value: &Rcvar) -> Rcvar {
+    match value.as_ref() {
+        Variable::Object(obj) => Rc::new(Variable::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), sort_keys_deep(v)))
+                .collect(),
+        )),
+        Variable::Array(arr) => Rc::new(Variable::Array(arr.iter().map(sort_keys_deep).collect())),
+        _ => value.clone(),
+    }
+}
+
+// =============================================================================
+// canonical_json(any) -> string (RFC 8785 JSON Canonicalization Scheme)
+// =============================================================================
+
+define_function!(CanonicalJsonFn, vec![ArgumentType::Any], None);
+
+impl Function for CanonicalJsonFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let mut out = String::new();
+        write_canonical(&args[0], &mut out)
+            .map_err(|msg| JmespathError::new(ctx.expression, 0, ErrorReason::Parse(msg)))?;
+
+        Ok(Rc::new(Variable::String(out)))
+    }
+}
+
+/// Serialize per RFC 8785: no whitespace, object keys ordered by UTF-16 code
+/// units, and numbers formatted the way ECMAScript's `Number.toString` does.
+fn write_canonical(value: &Variable, out: &mut String) -> Result<(), String> {
+    match value {
+        Variable::Null => out.push_str("null"),
+        Variable::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Variable::Number(n) => {
+            let f = n
+                .as_f64()
+                .ok_or_else(|| format!("Cannot canonicalize number {}", n))?;
+            out.push_str(&format_es_number(f)?);
+        }
+        Variable::String(s) => write_canonical_string(s, out),
+        Variable::Array(arr) => {
+            out.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(']');
+        }
+        Variable::Object(obj) => {
+            let mut entries: Vec<(&String, &Rcvar)> = obj.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            out.push('{');
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_string(k, out);
+                out.push(':');
+                write_canonical(v, out)?;
+            }
+            out.push('}');
+        }
+        Variable::Expref(_) => return Err("Cannot canonicalize an expression reference".to_owned()),
+    }
+    Ok(())
+}
+
+fn write_canonical_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Format a finite f64 like ECMAScript `Number.prototype.toString`.
+fn format_es_number(f: f64) -> Result<String, String> {
+    if !f.is_finite() {
+        return Err("Cannot canonicalize a non-finite number".to_owned());
+    }
+    if f == 0.0 {
+        return Ok("0".to_string());
+    }
+
+    // `{:e}` yields the shortest round-trip digits, e.g. "-1.2345e3"
+    let sci = format!("{:e}", f.abs());
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exp.parse::<i32>().unwrap() + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat((-n) as usize), digits)
+    } else {
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        if k == 1 {
+            format!("{}e{}{}", digits, sign, (n - 1).abs())
+        } else {
+            format!(
+                "{}.{}e{}{}",
+                &digits[..1],
+                &digits[1..],
+                sign,
+                (n - 1).abs()
+            )
+        }
+    };
+
+    Ok(if f < 0.0 { format!("-{}", body) } else { body })
+}
+
 // =============================================================================
 // json_decode(string) -> any
 // =============================================================================
@@ -448,6 +748,100 @@ impl Function for GetEnvFn {
     }
 }
 
+// =============================================================================
+// help(name?) -> object | array
+// Function documentation from the registry metadata
+// =============================================================================
+
+define_function!(HelpFn, vec![], Some(ArgumentType::String));
+
+fn str_var(s: &str) -> Rcvar {
+    Rc::new(Variable::String(s.to_string()))
+}
+
+fn function_help(info: &FunctionInfo) -> Rcvar {
+    let mut obj = std::collections::BTreeMap::new();
+    obj.insert("name".to_string(), str_var(info.name));
+    obj.insert("category".to_string(), str_var(info.category.name()));
+    obj.insert("description".to_string(), str_var(info.description));
+    obj.insert("signature".to_string(), str_var(info.signature));
+    obj.insert("example".to_string(), str_var(info.example));
+    obj.insert(
+        "is_standard".to_string(),
+        Rc::new(Variable::Bool(info.is_standard)),
+    );
+    obj.insert(
+        "jep".to_string(),
+        info.jep
+            .map(str_var)
+            .unwrap_or_else(|| Rc::new(Variable::Null)),
+    );
+    obj.insert(
+        "aliases".to_string(),
+        Rc::new(Variable::Array(
+            info.aliases.iter().map(|a| str_var(a)).collect(),
+        )),
+    );
+    obj.insert(
+        "features".to_string(),
+        Rc::new(Variable::Array(
+            info.features.iter().map(|f| str_var(f.name())).collect(),
+        )),
+    );
+    Rc::new(Variable::Object(obj))
+}
+
+impl Function for HelpFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        if args.len() > 1 {
+            return Err(JmespathError::new(
+                ctx.expression,
+                0,
+                ErrorReason::Parse("help() takes 0 or 1 arguments".to_owned()),
+            ));
+        }
+
+        let available = function_metadata()
+            .iter()
+            .filter(|info| info.category.is_available());
+
+        match args.first() {
+            // help(name): full metadata for one function (or one of its aliases)
+            Some(name) => {
+                let name = name.as_string().unwrap();
+                Ok(available
+                    .clone()
+                    .find(|info| info.name == name)
+                    .or_else(|| {
+                        available
+                            .clone()
+                            .find(|info| info.aliases.contains(&name.as_str()))
+                    })
+                    .map(function_help)
+                    .unwrap_or_else(|| Rc::new(Variable::Null)))
+            }
+            // help(): summary of every function, sorted by name
+            None => {
+                let mut infos: Vec<&FunctionInfo> = available.collect();
+                infos.sort_by_key(|info| info.name);
+                let list = infos
+                    .into_iter()
+                    .map(|info| {
+                        let mut obj = std::collections::BTreeMap::new();
+                        obj.insert("name".to_string(), str_var(info.name));
+                        obj.insert("category".to_string(), str_var(info.category.name()));
+                        obj.insert("description".to_string(), str_var(info.description));
+                        Rc::new(Variable::Object(obj))
+                    })
+                    .collect();
+                Ok(Rc::new(Variable::Array(list)))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -472,6 +866,51 @@ mod tests {
         assert_eq!(result.as_string().unwrap(), "value");
     }
 
+    #[test]
+    fn test_assert_type() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"items": [1, 2], "name": "x"}"#).unwrap();
+
+        let expr = runtime.compile("assert_type(items, 'array')").unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_array().unwrap().len(), 2);
+
+        let expr = runtime
+            .compile("assert_type(missing, ['string', 'null'])")
+            .unwrap();
+        assert!(expr.search(&data).unwrap().is_null());
+
+        let expr = runtime.compile("assert_type(name, 'array')").unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("expected array, got string"), "{}", err);
+
+        let expr = runtime
+            .compile("assert_type(name, 'number', 'name must be numeric')")
+            .unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("name must be numeric"), "{}", err);
+
+        let expr = runtime.compile("assert_type(name, 'text')").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
+    #[test]
+    fn test_require() {
+        let runtime = setup_runtime();
+        let data = Variable::from_json(r#"{"id": 0}"#).unwrap();
+
+        let expr = runtime.compile("require(id, 'id is required')").unwrap();
+        assert_eq!(expr.search(&data).unwrap().as_number(), Some(0.0));
+
+        let expr = runtime
+            .compile("require(user, 'user is required')")
+            .unwrap();
+        let err = expr.search(&data).unwrap_err().to_string();
+        assert!(err.contains("user is required"), "{}", err);
+
+        let expr = runtime.compile("require(user)").unwrap();
+        assert!(expr.search(&data).is_err());
+    }
+
     #[test]
     fn test_if() {
         let runtime = setup_runtime();
@@ -562,6 +1001,83 @@ mod tests {
         assert_eq!(result.as_number().unwrap(), 1.0);
     }
 
+    #[test]
+    fn test_sort_keys_deep() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("json_encode(sort_keys_deep(@))").unwrap();
+        let data =
+            Variable::from_json(r#"{"b": [{"z": 1, "a": 2}], "a": {"d": 1, "c": 2}}"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result.as_string().unwrap(),
+            r#"{"a":{"c":2,"d":1},"b":[{"a":2,"z":1}]}"#
+        );
+    }
+
+    #[test]
+    fn test_canonical_json_structure() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("canonical_json(@)").unwrap();
+        let data =
+            Variable::from_json(r#"{"b": [1.0, "x\n", null, true], "a": {"y": 2.50}}"#).unwrap();
+        let result = expr.search(&data).unwrap();
+        assert_eq!(
+            result.as_string().unwrap(),
+            r#"{"a":{"y":2.5},"b":[1,"x\n",null,true]}"#
+        );
+    }
+
+    #[test]
+    fn test_canonical_json_utf16_key_order() {
+        // RFC 8785 section 3.2.3 sorting example
+        let runtime = setup_runtime();
+        let data = Variable::from_json(
+            r#"{"€": 1, "\r": 2, "😀": 3, "דּ": 4, "1": 5, "ö": 6, "\u0080": 7}"#,
+        )
+        .unwrap();
+        let encoded = runtime
+            .compile("canonical_json(@)")
+            .unwrap()
+            .search(&data)
+            .unwrap();
+        let s = encoded.as_string().unwrap();
+        let positions: Vec<usize> = [
+            "\\r",
+            "\"1\"",
+            "\u{80}",
+            "\u{f6}",
+            "\u{20ac}",
+            "\u{1f600}",
+            "\u{fb33}",
+        ]
+        .iter()
+        .map(|k| s.find(k).unwrap())
+        .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", s);
+    }
+
+    #[test]
+    fn test_canonical_json_numbers() {
+        let cases = [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (123.456, "123.456"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (1e-6, "0.000001"),
+            (1e-7, "1e-7"),
+            (3.3e-9, "3.3e-9"),
+            (9007199254740993.0, "9007199254740992"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(format_es_number(input).unwrap(), expected, "{}", input);
+        }
+    }
+
     #[test]
     fn test_pretty_default_indent() {
         let runtime = setup_runtime();
@@ -621,4 +1137,69 @@ mod tests {
         let result = expr.search(&Variable::Null).unwrap();
         assert!(result.is_null());
     }
+
+    #[cfg(feature = "string")]
+    #[test]
+    fn test_help_function() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("help('upper')").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let obj = result.as_object().unwrap();
+        assert_eq!(obj["name"].as_string().unwrap(), "upper");
+        assert_eq!(obj["category"].as_string().unwrap(), "string");
+        assert!(obj.contains_key("signature"));
+        assert!(obj.contains_key("example"));
+    }
+
+    #[cfg(feature = "expression")]
+    #[test]
+    fn test_help_alias_and_unknown() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("help('fold').name").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        assert_eq!(result.as_string().unwrap(), "reduce_expr");
+
+        let expr = runtime.compile("help('no_such_function')").unwrap();
+        assert!(expr.search(&Variable::Null).unwrap().is_null());
+    }
+
+    #[test]
+    fn test_help_list() {
+        let runtime = setup_runtime();
+        let expr = runtime.compile("help()").unwrap();
+        let result = expr.search(&Variable::Null).unwrap();
+        let list = result.as_array().unwrap();
+        #[cfg(feature = "full")]
+        assert!(list.len() > 100);
+        assert!(
+            list.iter()
+                .any(|f| f.as_object().unwrap()["name"].as_string().unwrap() == "help")
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let runtime = setup_runtime();
+        let seen: Arc<std::sync::Mutex<Vec<String>>> = Arc::default();
+        let captured = seen.clone();
+        set_trace_sink(move |label, value| {
+            captured.lock().unwrap().push(format!(
+                "{}={}",
+                label.unwrap_or("-"),
+                serde_json::to_string(value).unwrap()
+            ));
+        });
+
+        let data = Variable::from_json(r#"{"items": [1, 2, 3]}"#).unwrap();
+        let expr = runtime
+            .compile("trace(items, 'before') | length(trace(@))")
+            .unwrap();
+        let result = expr.search(&data).unwrap();
+        clear_trace_sink();
+
+        assert_eq!(result.as_number().unwrap(), 3.0);
+        let seen = seen.lock().unwrap();
+        assert!(seen.contains(&"before=[1,2,3]".to_string()));
+        assert!(seen.contains(&"-=[1,2,3]".to_string()));
+    }
 }
@@ -16,7 +16,8 @@
 //! validation::register(&mut runtime);
 //! ```
 
-use std::rc::Rc;
+use crate::common::Rc;
+use std::collections::BTreeMap;
 
 use crate::common::{
     ArgumentType, Context, ErrorReason, Function, JmespathError, Rcvar, Runtime, Variable,
@@ -24,7 +25,7 @@ use crate::common::{
 use crate::define_function;
 
 #[cfg(feature = "regex")]
-use regex::Regex;
+use crate::regex_fns::cached_regex;
 
 /// Register all validation functions with the runtime.
 pub fn register(runtime: &mut Runtime) {
@@ -38,12 +39,25 @@ pub fn register(runtime: &mut Runtime) {
     runtime.register_function("is_ipv4", Box::new(IsIpv4Fn::new()));
     runtime.register_function("is_ipv6", Box::new(IsIpv6Fn::new()));
     runtime.register_function("luhn_check", Box::new(LuhnCheckFn::new()));
+    runtime.register_function("luhn_valid", Box::new(LuhnCheckFn::new()));
+    runtime.register_function("luhn_checksum", Box::new(LuhnChecksumFn::new()));
+    runtime.register_function("is_isbn", Box::new(IsIsbnFn::new()));
+    runtime.register_function("is_ean", Box::new(IsEanFn::new()));
+    runtime.register_function("is_iban", Box::new(IsIbanFn::new()));
     runtime.register_function("is_credit_card", Box::new(IsCreditCardFn::new()));
     runtime.register_function("is_jwt", Box::new(IsJwtFn::new()));
     runtime.register_function("is_iso_date", Box::new(IsIsoDateFn::new()));
     runtime.register_function("is_json", Box::new(IsJsonFn::new()));
     runtime.register_function("is_base64", Box::new(IsBase64Fn::new()));
     runtime.register_function("is_hex", Box::new(IsHexFn::new()));
+    runtime.register_function("is_hostname", Box::new(IsHostnameFn::new()));
+    runtime.register_function("is_port", Box::new(IsPortFn::new()));
+    runtime.register_function("is_latlong", Box::new(IsLatLongFn::new()));
+    runtime.register_function("is_slug", Box::new(IsSlugFn::new()));
+    runtime.register_function("is_cron", Box::new(IsCronFn::new()));
+    runtime.register_function("is_hex_color", Box::new(IsHexColorFn::new()));
+    runtime.register_function("password_entropy", Box::new(PasswordEntropyFn::new()));
+    runtime.register_function("password_strength", Box::new(PasswordStrengthFn::new()));
 }
 
 // =============================================================================
@@ -66,7 +80,7 @@ impl Function for IsEmailFn {
             )
         })?;
 
-        let email_re = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
+        let email_re = cached_regex(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
         Ok(Rc::new(Variable::Bool(email_re.is_match(s))))
     }
 }
@@ -91,7 +105,7 @@ impl Function for IsUrlFn {
             )
         })?;
 
-        let url_re = Regex::new(r"^https?://[^\s/$.?#].[^\s]*$").unwrap();
+        let url_re = cached_regex(r"^https?://[^\s/$.?#].[^\s]*$").unwrap();
         Ok(Rc::new(Variable::Bool(url_re.is_match(s))))
     }
 }
@@ -116,7 +130,7 @@ impl Function for IsUuidFn {
             )
         })?;
 
-        let uuid_re = Regex::new(
+        let uuid_re = cached_regex(
             r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
         )
         .unwrap();
@@ -172,6 +186,7 @@ impl Function for IsIpv6Fn {
 
 // =============================================================================
 // luhn_check(string) -> boolean - Generic Luhn algorithm check
+// Also registered as luhn_valid()
 // =============================================================================
 
 define_function!(LuhnCheckFn, vec![ArgumentType::String], None);
@@ -222,6 +237,169 @@ fn luhn_validate(s: &str) -> bool {
     sum % 10 == 0
 }
 
+// =============================================================================
+// luhn_checksum(string) -> number - Luhn check digit to append
+// =============================================================================
+
+define_function!(LuhnChecksumFn, vec![ArgumentType::String], None);
+
+impl Function for LuhnChecksumFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let Some(digits) = check_digits(s) else {
+            return Ok(Rc::new(Variable::Null));
+        };
+
+        // Compute as if a zero check digit were appended, then pick the digit
+        // that brings the sum to a multiple of 10.
+        let sum: u32 = digits
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, &d)| {
+                if i % 2 == 0 {
+                    let doubled = d * 2;
+                    if doubled > 9 { doubled - 9 } else { doubled }
+                } else {
+                    d
+                }
+            })
+            .sum();
+        Ok(Rc::new(Variable::Number(serde_json::Number::from(
+            (10 - sum % 10) % 10,
+        ))))
+    }
+}
+
+/// Digits of an identifier, ignoring space and dash separators.
+/// Returns None if any other character is present or there are no digits.
+fn check_digits(s: &str) -> Option<Vec<u32>> {
+    let digits: Option<Vec<u32>> = s
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_digit(10))
+        .collect();
+    digits.filter(|d| !d.is_empty())
+}
+
+/// GS1 mod-10 check (EAN-8, UPC-A, EAN-13, GTIN-14): weights 3,1,3,... from the
+/// rightmost payload digit.
+fn gs1_valid(digits: &[u32]) -> bool {
+    let Some((check, payload)) = digits.split_last() else {
+        return false;
+    };
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 0 { d * 3 } else { d })
+        .sum();
+    (10 - sum % 10) % 10 == *check
+}
+
+// =============================================================================
+// is_isbn(string) -> boolean - Validate ISBN-10 or ISBN-13 check digit
+// =============================================================================
+
+define_function!(IsIsbnFn, vec![ArgumentType::String], None);
+
+impl Function for IsIsbnFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let chars: Vec<char> = s.chars().filter(|c| *c != ' ' && *c != '-').collect();
+
+        let valid = match chars.len() {
+            10 => {
+                // Weights 10..1; a trailing X stands for 10
+                let values: Option<Vec<u32>> = chars
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| match c {
+                        'X' | 'x' if i == 9 => Some(10),
+                        _ => c.to_digit(10),
+                    })
+                    .collect();
+                values.is_some_and(|v| {
+                    v.iter()
+                        .enumerate()
+                        .map(|(i, d)| (10 - i as u32) * d)
+                        .sum::<u32>()
+                        % 11
+                        == 0
+                })
+            }
+            13 => {
+                let digits: Option<Vec<u32>> = chars.iter().map(|c| c.to_digit(10)).collect();
+                digits
+                    .is_some_and(|d| (d[..3] == [9, 7, 8] || d[..3] == [9, 7, 9]) && gs1_valid(&d))
+            }
+            _ => false,
+        };
+
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// is_ean(string) -> boolean - Validate EAN-8, UPC-A, EAN-13 or GTIN-14
+// =============================================================================
+
+define_function!(IsEanFn, vec![ArgumentType::String], None);
+
+impl Function for IsEanFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let valid =
+            check_digits(s).is_some_and(|d| matches!(d.len(), 8 | 12 | 13 | 14) && gs1_valid(&d));
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// is_iban(string) -> boolean - Validate IBAN format and mod-97 checksum
+// =============================================================================
+
+define_function!(IsIbanFn, vec![ArgumentType::String], None);
+
+impl Function for IsIbanFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let iban: Vec<char> = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        let well_formed = (15..=34).contains(&iban.len())
+            && iban[..2].iter().all(|c| c.is_ascii_uppercase())
+            && iban[2..4].iter().all(|c| c.is_ascii_digit())
+            && iban[4..].iter().all(|c| c.is_ascii_alphanumeric());
+        if !well_formed {
+            return Ok(Rc::new(Variable::Bool(false)));
+        }
+
+        // Move the country code and check digits to the end, expand letters to
+        // 10..35 and reduce mod 97 incrementally.
+        let remainder = iban[4..].iter().chain(&iban[..4]).fold(0u32, |acc, c| {
+            let value = c.to_digit(36).unwrap();
+            if value < 10 {
+                (acc * 10 + value) % 97
+            } else {
+                (acc * 100 + value) % 97
+            }
+        });
+        Ok(Rc::new(Variable::Bool(remainder == 1)))
+    }
+}
+
 // =============================================================================
 // is_credit_card(string) -> boolean - Validate credit card number
 // =============================================================================
@@ -275,7 +453,7 @@ impl Function for IsPhoneFn {
 
         // Basic phone pattern: optional + followed by digits, spaces, dashes, parens
         // Minimum 7 digits for a valid phone number
-        let phone_re = Regex::new(r"^\+?[\d\s\-\(\)\.]{7,}$").unwrap();
+        let phone_re = cached_regex(r"^\+?[\d\s\-\(\)\.]{7,}$").unwrap();
         if !phone_re.is_match(s) {
             return Ok(Rc::new(Variable::Bool(false)));
         }
@@ -430,6 +608,391 @@ impl Function for IsHexFn {
     }
 }
 
+// =============================================================================
+// is_hostname(string) -> boolean - Validate RFC 1123 hostname
+// =============================================================================
+
+define_function!(IsHostnameFn, vec![ArgumentType::String], None);
+
+impl Function for IsHostnameFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let host = s.strip_suffix('.').unwrap_or(s);
+        let labels: Vec<&str> = host.split('.').collect();
+
+        let valid = !host.is_empty()
+            && host.len() <= 253
+            && labels.iter().all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+            // An all-numeric final label would make dotted IPv4 addresses valid
+            && !labels.last().unwrap().chars().all(|c| c.is_ascii_digit());
+
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// is_port(any) -> boolean - Validate TCP/UDP port number (1-65535)
+// =============================================================================
+
+define_function!(IsPortFn, vec![ArgumentType::Any], None);
+
+impl Function for IsPortFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let port = match &*args[0] {
+            Variable::Number(n) => n.as_u64(),
+            Variable::String(s) if s.chars().all(|c| c.is_ascii_digit()) => s.parse().ok(),
+            _ => None,
+        };
+        Ok(Rc::new(Variable::Bool(
+            port.is_some_and(|p| (1..=65535).contains(&p)),
+        )))
+    }
+}
+
+// =============================================================================
+// is_latlong(any) -> boolean - Validate "lat,long" string or [lat, long] pair
+// =============================================================================
+
+define_function!(IsLatLongFn, vec![ArgumentType::Any], None);
+
+impl Function for IsLatLongFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let pair = match &*args[0] {
+            Variable::String(s) => {
+                let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+                match parts.as_slice() {
+                    [lat, lon] => lat.parse::<f64>().ok().zip(lon.parse::<f64>().ok()),
+                    _ => None,
+                }
+            }
+            Variable::Array(items) if items.len() == 2 => {
+                items[0].as_number().zip(items[1].as_number())
+            }
+            _ => None,
+        };
+
+        let valid = pair.is_some_and(|(lat, lon)| {
+            (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)
+        });
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// is_slug(string) -> boolean - Lowercase alphanumerics separated by single hyphens
+// =============================================================================
+
+define_function!(IsSlugFn, vec![ArgumentType::String], None);
+
+impl Function for IsSlugFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let valid = s.split('-').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        });
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// is_cron(string) -> boolean - Validate 5-field cron expression
+// =============================================================================
+
+const CRON_MONTHS: &[&str] = &[
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const CRON_DAYS: &[&str] = &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Parse a single cron value, accepting month/day names where `names` is given.
+fn cron_value(s: &str, min: u32, max: u32, names: &[&str]) -> Option<u32> {
+    let value = match s.parse::<u32>() {
+        Ok(n) => n,
+        Err(_) => {
+            let upper = s.to_ascii_uppercase();
+            names.iter().position(|n| *n == upper)? as u32 + min
+        }
+    };
+    (min..=max).contains(&value).then_some(value)
+}
+
+/// Validate one cron field: comma-separated `*`, values or ranges, each with an optional `/step`.
+fn cron_field_valid(field: &str, min: u32, max: u32, names: &[&str]) -> bool {
+    field.split(',').all(|item| {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (item, None),
+        };
+        let step_ok = step.is_none_or(|s| s.parse::<u32>().is_ok_and(|n| n > 0));
+        let range_ok = range == "*"
+            || match range.split_once('-') {
+                Some((lo, hi)) => cron_value(lo, min, max, names)
+                    .zip(cron_value(hi, min, max, names))
+                    .is_some_and(|(lo, hi)| lo <= hi),
+                None => cron_value(range, min, max, names).is_some(),
+            };
+        step_ok && range_ok
+    })
+}
+
+define_function!(IsCronFn, vec![ArgumentType::String], None);
+
+impl Function for IsCronFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap().trim();
+        if s.starts_with('@') {
+            let valid = matches!(
+                s,
+                "@yearly"
+                    | "@annually"
+                    | "@monthly"
+                    | "@weekly"
+                    | "@daily"
+                    | "@midnight"
+                    | "@hourly"
+                    | "@reboot"
+            );
+            return Ok(Rc::new(Variable::Bool(valid)));
+        }
+
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let valid = fields.len() == 5
+            && cron_field_valid(fields[0], 0, 59, &[])
+            && cron_field_valid(fields[1], 0, 23, &[])
+            && cron_field_valid(fields[2], 1, 31, &[])
+            && cron_field_valid(fields[3], 1, 12, CRON_MONTHS)
+            // 7 is accepted as an alias for Sunday
+            && cron_field_valid(fields[4], 0, 7, CRON_DAYS);
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// is_hex_color(string) -> boolean - Validate #rgb, #rgba, #rrggbb or #rrggbbaa
+// =============================================================================
+
+define_function!(IsHexColorFn, vec![ArgumentType::String], None);
+
+impl Function for IsHexColorFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let s = args[0].as_string().unwrap();
+        let valid = s.strip_prefix('#').is_some_and(|hex| {
+            matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        });
+        Ok(Rc::new(Variable::Bool(valid)))
+    }
+}
+
+// =============================================================================
+// password_entropy(string) -> number - Estimated entropy in bits
+// =============================================================================
+
+define_function!(PasswordEntropyFn, vec![ArgumentType::String], None);
+
+impl Function for PasswordEntropyFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let entropy = password_entropy(args[0].as_string().unwrap());
+        Ok(Rc::new(Variable::Number(
+            serde_json::Number::from_f64(entropy).unwrap_or_else(|| serde_json::Number::from(0)),
+        )))
+    }
+}
+
+/// Character-pool entropy: length * log2(size of the character classes used).
+fn password_entropy(s: &str) -> f64 {
+    let mut pool = 0u32;
+    if s.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if s.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if s.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if s.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+        pool += 33;
+    }
+    if !s.is_ascii() {
+        pool += 100;
+    }
+
+    let length = s.chars().count();
+    if pool == 0 || length == 0 {
+        return 0.0;
+    }
+    let bits = length as f64 * (pool as f64).log2();
+    (bits * 100.0).round() / 100.0
+}
+
+// =============================================================================
+// password_strength(string) -> object - Score (0-4) with reasons
+// =============================================================================
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "password",
+    "password1",
+    "qwerty",
+    "qwerty123",
+    "abc123",
+    "111111",
+    "123123",
+    "letmein",
+    "welcome",
+    "monkey",
+    "dragon",
+    "iloveyou",
+    "admin",
+    "administrator",
+    "login",
+    "passw0rd",
+    "p@ssw0rd",
+    "master",
+    "sunshine",
+    "football",
+    "baseball",
+    "princess",
+    "trustno1",
+    "changeme",
+    "secret",
+    "root",
+];
+
+/// Length of the longest run of identical characters.
+fn longest_repeat(chars: &[char]) -> usize {
+    chars
+        .chunk_by(|a, b| a == b)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Length of the longest run of consecutive ascending or descending code
+/// points ("abc", "4321").
+fn longest_sequence(chars: &[char]) -> usize {
+    let mut longest = usize::from(!chars.is_empty());
+    for step in [1i64, -1] {
+        let mut run = 1;
+        for pair in chars.windows(2) {
+            if pair[1] as i64 - pair[0] as i64 == step && pair[0].is_ascii_alphanumeric() {
+                run += 1;
+                longest = longest.max(run);
+            } else {
+                run = 1;
+            }
+        }
+    }
+    longest
+}
+
+define_function!(PasswordStrengthFn, vec![ArgumentType::String], None);
+
+impl Function for PasswordStrengthFn {
+    fn evaluate(&self, args: &[Rcvar], ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        self.signature.validate(args, ctx)?;
+
+        let password = args[0].as_string().unwrap();
+        let chars: Vec<char> = password.chars().collect();
+        let entropy = password_entropy(password);
+
+        let mut reasons = Vec::new();
+        if chars.len() < 8 {
+            reasons.push("shorter than 8 characters");
+        }
+        if !chars.iter().any(|c| c.is_lowercase()) {
+            reasons.push("no lowercase letters");
+        }
+        if !chars.iter().any(|c| c.is_uppercase()) {
+            reasons.push("no uppercase letters");
+        }
+        if !chars.iter().any(|c| c.is_ascii_digit()) {
+            reasons.push("no digits");
+        }
+        if chars.iter().all(|c| c.is_alphanumeric()) {
+            reasons.push("no symbols");
+        }
+        let repeated = longest_repeat(&chars) >= 3;
+        if repeated {
+            reasons.push("repeated characters");
+        }
+        let sequential = longest_sequence(&chars) >= 3;
+        if sequential {
+            reasons.push("sequential characters");
+        }
+        let common = COMMON_PASSWORDS.contains(&password.to_lowercase().as_str());
+        if common {
+            reasons.push("common password");
+        }
+
+        // Entropy bands, then penalties for predictable patterns
+        let mut score: u64 = match entropy {
+            e if e < 28.0 => 0,
+            e if e < 36.0 => 1,
+            e if e < 60.0 => 2,
+            e if e < 80.0 => 3,
+            _ => 4,
+        };
+        if repeated || sequential {
+            score = score.saturating_sub(1);
+        }
+        if chars.len() < 8 {
+            score = score.min(1);
+        }
+        if common {
+            score = 0;
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert(
+            "score".to_string(),
+            Rc::new(Variable::Number(serde_json::Number::from(score))),
+        );
+        map.insert(
+            "entropy".to_string(),
+            Rc::new(Variable::Number(
+                serde_json::Number::from_f64(entropy)
+                    .unwrap_or_else(|| serde_json::Number::from(0)),
+            )),
+        );
+        map.insert(
+            "reasons".to_string(),
+            Rc::new(Variable::Array(
+                reasons
+                    .into_iter()
+                    .map(|r| Rc::new(Variable::String(r.to_string())))
+                    .collect(),
+            )),
+        );
+        Ok(Rc::new(Variable::Object(map)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -683,4 +1246,298 @@ mod tests {
         let result = expr.search(&data).unwrap();
         assert!(!result.as_boolean().unwrap());
     }
+
+    fn check(expr: &str, input: &str) -> Rcvar {
+        let runtime = setup_runtime();
+        let expr = runtime.compile(expr).unwrap();
+        expr.search(Variable::String(input.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_luhn_valid_alias() {
+        assert!(
+            check("luhn_valid(@)", "4532 0151 1283 0366")
+                .as_boolean()
+                .unwrap()
+        );
+        assert!(
+            !check("luhn_valid(@)", "4532015112830367")
+                .as_boolean()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_luhn_checksum() {
+        assert_eq!(
+            check("luhn_checksum(@)", "7992739871").as_number(),
+            Some(3.0)
+        );
+        assert_eq!(
+            check("luhn_checksum(@)", "453201511283036").as_number(),
+            Some(6.0)
+        );
+        assert_eq!(check("luhn_checksum(@)", "0").as_number(), Some(0.0));
+        assert!(check("luhn_checksum(@)", "12a4").is_null());
+        assert!(check("luhn_checksum(@)", "").is_null());
+    }
+
+    #[test]
+    fn test_is_isbn() {
+        for valid in [
+            "0-306-40615-2",
+            "080442957X",
+            "978-0-306-40615-7",
+            "9791032305690",
+        ] {
+            assert!(
+                check("is_isbn(@)", valid).as_boolean().unwrap(),
+                "{}",
+                valid
+            );
+        }
+        for invalid in [
+            "0-306-40615-3",
+            "X804429570",
+            "978-0-306-40615-8",
+            "4006381333931",
+        ] {
+            assert!(
+                !check("is_isbn(@)", invalid).as_boolean().unwrap(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_ean() {
+        for valid in [
+            "4006381333931",
+            "73513537",
+            "036000291452",
+            "10614141000415",
+        ] {
+            assert!(check("is_ean(@)", valid).as_boolean().unwrap(), "{}", valid);
+        }
+        for invalid in [
+            "4006381333932",
+            "1234567",
+            "40063813339311",
+            "400638133393a",
+        ] {
+            assert!(
+                !check("is_ean(@)", invalid).as_boolean().unwrap(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_iban() {
+        for valid in [
+            "GB82 WEST 1234 5698 7654 32",
+            "DE89370400440532013000",
+            "fr1420041010050500013m02606",
+        ] {
+            assert!(
+                check("is_iban(@)", valid).as_boolean().unwrap(),
+                "{}",
+                valid
+            );
+        }
+        for invalid in [
+            "GB82 WEST 1234 5698 7654 33",
+            "GB82",
+            "1282WEST12345698765432",
+            "GB82-WEST-1234",
+        ] {
+            assert!(
+                !check("is_iban(@)", invalid).as_boolean().unwrap(),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    fn assert_all(expr: &str, inputs: &[&str], expected: bool) {
+        for input in inputs {
+            let result = check(expr, input);
+            assert_eq!(result.as_boolean(), Some(expected), "{} on {}", expr, input);
+        }
+    }
+
+    #[test]
+    fn test_is_hostname() {
+        assert_all(
+            "is_hostname(@)",
+            &[
+                "localhost",
+                "api.example.com",
+                "xn--bcher-kva.de",
+                "a-b.c0.io.",
+            ],
+            true,
+        );
+        assert_all(
+            "is_hostname(@)",
+            &[
+                "",
+                "-bad.com",
+                "bad-.com",
+                "a..b",
+                "under_score.com",
+                "192.168.1.1",
+            ],
+            false,
+        );
+    }
+
+    #[test]
+    fn test_is_port() {
+        let runtime = setup_runtime();
+        for (expr, expected) in [
+            ("is_port(`8080`)", true),
+            ("is_port('443')", true),
+            ("is_port(`65535`)", true),
+            ("is_port(`0`)", false),
+            ("is_port(`65536`)", false),
+            ("is_port(`80.5`)", false),
+            ("is_port('-1')", false),
+        ] {
+            let result = runtime
+                .compile(expr)
+                .unwrap()
+                .search(Variable::Null)
+                .unwrap();
+            assert_eq!(result.as_boolean(), Some(expected), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_is_latlong() {
+        assert_all(
+            "is_latlong(@)",
+            &["40.7128,-74.0060", "-90, 180", "0,0"],
+            true,
+        );
+        assert_all(
+            "is_latlong(@)",
+            &["91,0", "0,-181", "40.7", "north,east"],
+            false,
+        );
+
+        let runtime = setup_runtime();
+        let result = runtime
+            .compile("is_latlong(`[51.5, -0.12]`)")
+            .unwrap()
+            .search(Variable::Null)
+            .unwrap();
+        assert!(result.as_boolean().unwrap());
+    }
+
+    #[test]
+    fn test_is_slug() {
+        assert_all("is_slug(@)", &["hello-world", "post-2024", "a"], true);
+        assert_all(
+            "is_slug(@)",
+            &[
+                "",
+                "Hello-World",
+                "double--hyphen",
+                "-leading",
+                "with space",
+            ],
+            false,
+        );
+    }
+
+    #[test]
+    fn test_is_cron() {
+        assert_all(
+            "is_cron(@)",
+            &[
+                "* * * * *",
+                "*/15 9-17 * * MON-FRI",
+                "0 0 1,15 jan,jul 0",
+                "30 4 * * 7",
+                "@daily",
+            ],
+            true,
+        );
+        assert_all(
+            "is_cron(@)",
+            &[
+                "* * * *",
+                "60 * * * *",
+                "* 24 * * *",
+                "* * 0 * *",
+                "*/0 * * * *",
+                "5-1 * * * *",
+                "@often",
+            ],
+            false,
+        );
+    }
+
+    #[test]
+    fn test_is_hex_color() {
+        assert_all(
+            "is_hex_color(@)",
+            &["#fff", "#FFFA", "#1e90ff", "#1e90ff80"],
+            true,
+        );
+        assert_all("is_hex_color(@)", &["fff", "#ff", "#12345", "#ggg"], false);
+    }
+
+    #[test]
+    fn test_password_entropy() {
+        assert_eq!(check("password_entropy(@)", "").as_number(), Some(0.0));
+        // 8 lowercase characters: 8 * log2(26)
+        assert_eq!(
+            check("password_entropy(@)", "abcdefgh").as_number(),
+            Some(37.6)
+        );
+        // lower + upper + digits + symbols: 95-character pool
+        assert_eq!(
+            check("password_entropy(@)", "aB3$").as_number(),
+            Some(26.28)
+        );
+    }
+
+    #[test]
+    fn test_password_strength() {
+        let strength = |pw: &str| {
+            let result = check("password_strength(@)", pw);
+            let obj = result.as_object().unwrap();
+            let score = obj.get("score").unwrap().as_number().unwrap();
+            let reasons: Vec<String> = obj
+                .get("reasons")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r.as_string().unwrap().clone())
+                .collect();
+            (score, reasons)
+        };
+
+        let (score, reasons) = strength("password");
+        assert_eq!(score, 0.0);
+        assert!(reasons.contains(&"common password".to_string()));
+
+        let (score, reasons) = strength("Tr0ub4dor&3xK!q9");
+        assert_eq!(score, 4.0);
+        assert!(reasons.is_empty());
+
+        let (score, reasons) = strength("aaa-Bcd-1234-xyz");
+        assert!(score < 4.0);
+        assert!(reasons.contains(&"repeated characters".to_string()));
+        assert!(reasons.contains(&"sequential characters".to_string()));
+
+        let (score, reasons) = strength("Ab1!");
+        assert!(score <= 1.0);
+        assert!(reasons.contains(&"shorter than 8 characters".to_string()));
+    }
 }
@@ -0,0 +1,77 @@
+//! Deterministic mode: pinned clock and RNG providers.
+//!
+//! The providers are process-wide, so these checks live in their own test
+//! binary and run as a single test to avoid racing other tests.
+
+#![cfg(all(feature = "datetime", feature = "utility"))]
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use jmespath::{Runtime, Variable};
+use jmespath_extensions::common::{clear_clock_provider, set_clock_provider};
+use jmespath_extensions::register_all;
+
+fn search(runtime: &Runtime, expr: &str) -> String {
+    let result = runtime
+        .compile(expr)
+        .unwrap()
+        .search(Variable::Null)
+        .unwrap();
+    serde_json::to_string(&*result).unwrap()
+}
+
+#[test]
+fn pinned_clock_and_rng() {
+    let mut runtime = Runtime::new();
+    runtime.register_builtin_functions();
+    register_all(&mut runtime);
+
+    set_clock_provider(|| UNIX_EPOCH + Duration::from_millis(1_700_000_000_123));
+    assert_eq!(search(&runtime, "now()"), "1700000000.0");
+    assert_eq!(search(&runtime, "now_millis()"), "1700000000123.0");
+    assert_eq!(search(&runtime, "now_ms()"), "1700000000123");
+    assert_eq!(
+        search(&runtime, "format_date(now(), '%Y-%m-%d')"),
+        "\"2023-11-14\""
+    );
+    #[cfg(feature = "ids")]
+    assert_eq!(
+        search(&runtime, "ulid_timestamp(ulid())"),
+        "1700000000123.0"
+    );
+    clear_clock_provider();
+    assert_ne!(search(&runtime, "now()"), "1700000000.0");
+
+    #[cfg(feature = "rand")]
+    {
+        use jmespath_extensions::random::{RngSource, clear_rng_provider, set_rng_provider};
+
+        let draw = |runtime: &Runtime| {
+            set_rng_provider(RngSource::seeded(7));
+            (
+                search(runtime, "random()"),
+                search(runtime, "uuid()"),
+                search(runtime, "shuffle(`[1, 2, 3, 4, 5, 6, 7, 8]`)"),
+            )
+        };
+        assert_eq!(draw(&runtime), draw(&runtime));
+        clear_rng_provider();
+    }
+
+    #[cfg(feature = "ids")]
+    {
+        use jmespath_extensions::common::{RngSource, clear_rng_provider, set_rng_provider};
+
+        set_clock_provider(|| UNIX_EPOCH + Duration::from_millis(1_700_000_000_123));
+        let draw = |runtime: &Runtime| {
+            ["nanoid()", "ulid()", "ksuid()", "cuid2()"].map(|expr| search(runtime, expr))
+        };
+        set_rng_provider(RngSource::seeded(7));
+        let first = draw(&runtime);
+        set_rng_provider(RngSource::seeded(7));
+        assert_eq!(first, draw(&runtime));
+        clear_rng_provider();
+        assert_ne!(first, draw(&runtime));
+        clear_clock_provider();
+    }
+}
@@ -0,0 +1,150 @@
+//! `jpx agg` - SQL-ish aggregation shorthand.
+//!
+//! Expands `--group-by` and `--agg` options into the equivalent JMESPath
+//! expression so analysts don't have to compose `aggregate` calls by hand:
+//!
+//! ```text
+//! jpx agg --group-by type --agg 'count,sum(amount)'
+//!   => aggregate(@, 'type', {count: 'length(_group)', sum_amount: 'sum(_group[*].amount)'})
+//! ```
+
+use anyhow::{Result, anyhow};
+use clap::Args;
+
+/// Arguments for the `agg` subcommand
+#[derive(Args, Debug)]
+pub struct AggArgs {
+    /// Input file (reads from stdin if not provided)
+    #[arg(short, long)]
+    pub file: Option<String>,
+
+    /// Expression to group rows by (omit to aggregate the whole array)
+    #[arg(short, long, value_name = "EXPR")]
+    pub group_by: Option<String>,
+
+    /// Comma-separated aggregates: count, count(field), sum(field), avg(field), min(field), max(field)
+    #[arg(short, long, value_name = "AGGREGATES")]
+    pub agg: String,
+
+    /// Print the generated JMESPath expression to stderr
+    #[arg(long)]
+    pub show_query: bool,
+}
+
+impl AggArgs {
+    /// Build the JMESPath expression for these options.
+    pub fn to_query(&self) -> Result<String> {
+        match &self.group_by {
+            Some(group_by) => {
+                // aggregate() exposes each group's elements under `_group`
+                let aggregates = parse_aggregates(&self.agg, "_group")?;
+                let fields: Vec<String> = aggregates
+                    .iter()
+                    .map(|(name, expr)| format!("{}: {}", name, raw_string(expr)))
+                    .collect();
+                Ok(format!(
+                    "aggregate(@, {}, {{{}}})",
+                    raw_string(group_by),
+                    fields.join(", ")
+                ))
+            }
+            None => {
+                let aggregates = parse_aggregates(&self.agg, "@")?;
+                let fields: Vec<String> = aggregates
+                    .iter()
+                    .map(|(name, expr)| format!("{}: {}", name, expr))
+                    .collect();
+                Ok(format!("{{{}}}", fields.join(", ")))
+            }
+        }
+    }
+}
+
+/// Parse `count,sum(amount)` into `(output_name, expression)` pairs over the
+/// array of rows at `rows`.
+fn parse_aggregates(spec: &str, rows: &str) -> Result<Vec<(String, String)>> {
+    let mut aggregates = Vec::new();
+
+    for item in split_top_level(spec) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+
+        let (func, field) = match item.split_once('(') {
+            Some((func, rest)) => {
+                let field = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| anyhow!("Unbalanced parentheses in aggregate '{}'", item))?
+                    .trim();
+                (func.trim(), Some(field).filter(|f| !f.is_empty()))
+            }
+            None => (item, None),
+        };
+
+        let expr = match (func, field) {
+            ("count", None) => format!("length({})", rows),
+            ("count", Some(field)) => format!("length({}[*].{})", rows, field),
+            ("sum" | "avg" | "min" | "max", Some(field)) => {
+                format!("{}({}[*].{})", func, rows, field)
+            }
+            ("sum" | "avg" | "min" | "max", None) => {
+                return Err(anyhow!(
+                    "Aggregate '{}' requires a field, e.g. {}(amount)",
+                    func,
+                    func
+                ));
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Unknown aggregate '{}'. Supported: count, sum, avg, min, max",
+                    func
+                ));
+            }
+        };
+
+        let name = match field {
+            Some(field) => format!("{}_{}", func, sanitize(field)),
+            None => func.to_string(),
+        };
+        aggregates.push((name, expr));
+    }
+
+    if aggregates.is_empty() {
+        return Err(anyhow!("--agg requires at least one aggregate"));
+    }
+    Ok(aggregates)
+}
+
+/// Split on commas that are not inside parentheses.
+fn split_top_level(spec: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in spec.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&spec[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&spec[start..]);
+    parts
+}
+
+/// Turn a field path into an identifier usable as an output key.
+fn sanitize(field: &str) -> String {
+    field
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Quote a string as a JMESPath raw string literal.
+fn raw_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -0,0 +1,69 @@
+//! On-disk result cache for `--cache-dir`.
+//!
+//! Results are keyed by a SHA-256 hash of the jpx version, the evaluation
+//! flags that affect output, the expression chain, and the raw input text,
+//! so any change to one of them results in a cache miss.
+
+use anyhow::{Context, Result};
+use jmespath::{Rcvar, Variable};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// A single cache slot for one (input, expressions) combination.
+pub struct CacheEntry {
+    path: PathBuf,
+}
+
+impl CacheEntry {
+    /// Compute the cache slot for the given input and expression chain.
+    pub fn new(dir: &str, input: &str, expressions: &[String], strict: bool, slurp: bool) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update([strict as u8, slurp as u8]);
+        // Length-prefix each part so ["ab", "c"] and ["a", "bc"] hash differently
+        for expression in expressions {
+            hasher.update((expression.len() as u64).to_le_bytes());
+            hasher.update(expression.as_bytes());
+        }
+        hasher.update((input.len() as u64).to_le_bytes());
+        hasher.update(input.as_bytes());
+
+        let key: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        Self {
+            path: Path::new(dir).join(format!("{}.json", key)),
+        }
+    }
+
+    /// Path of the cache file for this entry.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the cached result, if present and readable.
+    pub fn load(&self) -> Option<Rcvar> {
+        let contents = std::fs::read_to_string(&self.path).ok()?;
+        Variable::from_json(&contents).ok().map(Rcvar::new)
+    }
+
+    /// Store a result, writing to a temporary file first so readers never see a partial entry.
+    pub fn store(&self, result: &Variable) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create cache dir: {}", dir.display()))?;
+        }
+
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_string(result)?)
+            .with_context(|| format!("Failed to write cache file: {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write cache file: {}", self.path.display()))?;
+        Ok(())
+    }
+}
@@ -1,15 +1,17 @@
+mod agg;
+mod cache;
 mod repl;
 
 use anyhow::{Context, Result};
-use clap::{CommandFactory, Parser, ValueEnum, builder::styling};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, builder::styling};
 use clap_complete::{Shell, generate};
 use jmespath::ast::Ast;
-use jmespath::{Runtime, Variable};
+use jmespath::{Rcvar, Runtime, Variable};
 use jmespath_extensions::register_all;
 use jmespath_extensions::registry::{Category, FunctionRegistry};
+use jmespath_extensions::utility::set_trace_sink;
 use std::fs::File;
 use std::io::{self, Read, Write};
-use std::rc::Rc;
 use std::time::Instant;
 
 // Cargo-style help coloring
@@ -76,10 +78,14 @@ enum ColorMode {
     "  echo '{\"ts\": \"2024-01-15\"}' | jpx 'format_date(ts, \"%B %d, %Y\")'\n",
     "  jpx -n 'now()'\n",
     "  cat data.json | jpx -e 'items[*].name' -e 'sort(@)'\n",
+    "  jpx agg -f orders.json --group-by region --agg 'count,sum(amount)'\n",
     "\nVersion: ", env!("CARGO_PKG_VERSION"),
     "\nDocumentation: https://docs.rs/jmespath_extensions"
 ))]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// JMESPath expression(s) to evaluate (multiple expressions are chained)
     #[arg(short = 'e', long = "expression", conflicts_with = "query_file")]
     expressions: Vec<String>,
@@ -164,12 +170,31 @@ struct Args {
     /// Load a demo dataset (use with --repl)
     #[arg(long, value_name = "NAME")]
     demo: Option<String>,
+
+    /// Cache results in this directory, keyed by a hash of the input, expression(s) and jpx version
+    /// Re-running the same query on unchanged input returns the cached result.
+    /// Not used with --null-input; results of non-deterministic functions are cached too.
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<String>,
+}
+
+/// Subcommands
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Group and aggregate an array with SQL-like shorthand
+    Agg(agg::AggArgs),
 }
 
 fn main() -> Result<()> {
     let mut args = Args::parse();
     apply_env_defaults(&mut args);
 
+    // trace() logs nothing unless the host installs a sink
+    set_trace_sink(|label, value| match label {
+        Some(label) => eprintln!("[trace] {}: {}", label, value),
+        None => eprintln!("[trace] {}", value),
+    });
+
     // Handle shell completions
     if let Some(shell) = args.completions {
         let mut cmd = Args::command();
@@ -202,8 +227,17 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Get expressions from positional arg, -e flags, or file
-    let expressions: Vec<String> = if let Some(query_path) = &args.query_file {
+    // Get expressions from a subcommand, positional arg, -e flags, or file
+    let expressions: Vec<String> = if let Some(Command::Agg(agg)) = args.command.take() {
+        let query = agg.to_query()?;
+        if agg.show_query {
+            eprintln!("{}", query);
+        }
+        if agg.file.is_some() {
+            args.file = agg.file;
+        }
+        vec![query]
+    } else if let Some(query_path) = &args.query_file {
         vec![
             std::fs::read_to_string(query_path)
                 .with_context(|| format!("Failed to read query file: {}", query_path))?
@@ -241,13 +275,11 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Get input data
-    let data = if args.null_input {
-        // Null input mode - don't read anything
-        Variable::Null
+    // Read raw input (None in null input mode)
+    let input = if args.null_input {
+        None
     } else {
-        // Read input JSON
-        let input = match &args.file {
+        Some(match &args.file {
             Some(path) => std::fs::read_to_string(path)
                 .with_context(|| format!("Failed to read file: {}", path))?,
             None => {
@@ -257,82 +289,57 @@ fn main() -> Result<()> {
                     .context("Failed to read from stdin")?;
                 buf
             }
-        };
-
-        if args.slurp {
-            // Slurp mode - parse multiple JSON values into an array
-            parse_slurp(&input)?
-        } else {
-            // Normal mode - parse single JSON value
-            Variable::from_json(&input)
-                .map_err(|e| anyhow::anyhow!("Failed to parse JSON input: {}", e))?
-        }
+        })
     };
 
-    // Create runtime with extensions (unless strict mode)
-    let mut runtime = Runtime::new();
-    runtime.register_builtin_functions();
-    if !args.strict {
-        register_all(&mut runtime);
-    }
-
-    // Verbose mode: show input info
-    if args.verbose {
-        if args.strict {
-            eprintln!("Mode: strict (standard JMESPath only)");
-        }
-        eprintln!("Input: {}", describe_value(&Rc::new(data.clone())));
-        if expressions.len() > 1 {
-            eprintln!("Expressions: {} (chained)", expressions.len());
-        }
-        eprintln!();
-    }
-
-    // Compile and execute expression(s)
-    let start = Instant::now();
-    let mut result: Rc<Variable> = Rc::new(data.clone());
+    // Look up a cached result before doing any parsing or evaluation
+    let cache_entry = match (&args.cache_dir, &input) {
+        (Some(dir), Some(input)) => Some(cache::CacheEntry::new(
+            dir,
+            input,
+            &expressions,
+            args.strict,
+            args.slurp,
+        )),
+        _ => None,
+    };
 
-    for (i, expression) in expressions.iter().enumerate() {
-        if args.verbose {
-            eprintln!("[{}] Expression: {}", i + 1, expression);
+    let cached = cache_entry.as_ref().and_then(|entry| entry.load());
+    let result = match cached {
+        Some(result) => {
+            if args.verbose {
+                eprintln!(
+                    "Cache hit: {}",
+                    cache_entry.as_ref().unwrap().path().display()
+                );
+                eprintln!();
+            }
+            result
         }
+        None => {
+            let data = match &input {
+                // Null input mode - use null as the input value
+                None => Variable::Null,
+                // Slurp mode - parse multiple JSON values into an array
+                Some(input) if args.slurp => parse_slurp(input)?,
+                // Normal mode - parse single JSON value
+                Some(input) => Variable::from_json(input)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse JSON input: {}", e))?,
+            };
 
-        let expr = runtime
-            .compile(expression)
-            .with_context(|| format!("Failed to compile expression: {}", expression))?;
+            let result = evaluate(&args, &expressions, data)?;
 
-        let step_start = Instant::now();
-        result = match expr.search(&result) {
-            Ok(r) => r,
-            Err(e) => {
-                let err_msg = e.to_string();
-                if args.strict && err_msg.contains("undefined function") {
-                    return Err(anyhow::anyhow!(
-                        "{}\n\nHint: You are using --strict mode which only allows standard JMESPath functions.\nRemove --strict or unset JPX_STRICT to use extension functions.",
-                        err_msg
-                    ));
+            if let Some(entry) = &cache_entry {
+                // A failed cache write should never fail the query itself
+                if let Err(e) = entry.store(&result)
+                    && !args.quiet
+                {
+                    eprintln!("Warning: failed to write cache: {}", e);
                 }
-                return Err(anyhow::anyhow!("Failed to evaluate expression: {}", e));
             }
-        };
-        let step_elapsed = step_start.elapsed();
-
-        if args.verbose {
-            eprintln!("[{}] Result: {}", i + 1, describe_value(&result));
-            eprintln!(
-                "[{}] Time: {:.3}ms",
-                i + 1,
-                step_elapsed.as_secs_f64() * 1000.0
-            );
-            eprintln!();
+            result
         }
-    }
-
-    let total_elapsed = start.elapsed();
-    if args.verbose {
-        eprintln!("Total time: {:.3}ms", total_elapsed.as_secs_f64() * 1000.0);
-        eprintln!();
-    }
+    };
 
     // Output result
     if result.is_null() {
@@ -397,6 +404,83 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Compile and evaluate the expression chain against the input data.
+fn evaluate(args: &Args, expressions: &[String], data: Variable) -> Result<Rcvar> {
+    // Create runtime with extensions (unless strict mode)
+    let mut runtime = Runtime::new();
+    runtime.register_builtin_functions();
+    if !args.strict {
+        register_all(&mut runtime);
+    }
+
+    // Verbose mode: show input info
+    if args.verbose {
+        if args.strict {
+            eprintln!("Mode: strict (standard JMESPath only)");
+        }
+        eprintln!("Input: {}", describe_value(&Rcvar::new(data.clone())));
+        if expressions.len() > 1 {
+            eprintln!("Expressions: {} (chained)", expressions.len());
+        }
+        eprintln!();
+    }
+
+    // Compile and execute expression(s)
+    let start = Instant::now();
+    let mut result: Rcvar = Rcvar::new(data);
+
+    for (i, expression) in expressions.iter().enumerate() {
+        if args.verbose {
+            eprintln!("[{}] Expression: {}", i + 1, expression);
+        }
+
+        // Rewrite JEP-11 `let` expressions into let_expr() calls
+        let expanded = if args.strict {
+            expression.to_string()
+        } else {
+            jmespath_extensions::expression::expand_let(expression)
+                .with_context(|| format!("Failed to compile expression: {}", expression))?
+        };
+        let expr = runtime
+            .compile(&expanded)
+            .with_context(|| format!("Failed to compile expression: {}", expression))?;
+
+        let step_start = Instant::now();
+        result = match expr.search(&result) {
+            Ok(r) => r,
+            Err(e) => {
+                let err_msg = e.to_string();
+                if args.strict && err_msg.contains("undefined function") {
+                    return Err(anyhow::anyhow!(
+                        "{}\n\nHint: You are using --strict mode which only allows standard JMESPath functions.\nRemove --strict or unset JPX_STRICT to use extension functions.",
+                        err_msg
+                    ));
+                }
+                return Err(anyhow::anyhow!("Failed to evaluate expression: {}", e));
+            }
+        };
+        let step_elapsed = step_start.elapsed();
+
+        if args.verbose {
+            eprintln!("[{}] Result: {}", i + 1, describe_value(&result));
+            eprintln!(
+                "[{}] Time: {:.3}ms",
+                i + 1,
+                step_elapsed.as_secs_f64() * 1000.0
+            );
+            eprintln!();
+        }
+    }
+
+    let total_elapsed = start.elapsed();
+    if args.verbose {
+        eprintln!("Total time: {:.3}ms", total_elapsed.as_secs_f64() * 1000.0);
+        eprintln!();
+    }
+
+    Ok(result)
+}
+
 /// Parse multiple JSON values from input into an array
 fn parse_slurp(input: &str) -> Result<Variable> {
     use serde_json::Deserializer;
@@ -522,7 +606,7 @@ fn describe_function(registry: &FunctionRegistry, func_name: &str) -> Result<()>
 }
 
 /// Describe a Variable value for verbose output
-fn describe_value(value: &Rc<Variable>) -> String {
+fn describe_value(value: &Rcvar) -> String {
     match value.as_ref() {
         Variable::Null => "null".to_string(),
         Variable::Bool(b) => format!("bool ({})", b),
@@ -6,7 +6,7 @@
 #![allow(clippy::collapsible_if)]
 
 use anyhow::{Context, Result};
-use jmespath::{Runtime, Variable};
+use jmespath::{Rcvar, Runtime, Variable};
 use jmespath_extensions::register_all;
 use jmespath_extensions::registry::{Category, FunctionRegistry};
 use rustyline::completion::{Completer, Pair};
@@ -508,7 +508,7 @@ pub fn suggest_queries(var: &Variable) -> Vec<Suggestion> {
 }
 
 fn suggest_for_object(
-    obj: &std::collections::BTreeMap<String, Rc<Variable>>,
+    obj: &std::collections::BTreeMap<String, Rcvar>,
     prefix: &str,
     suggestions: &mut Vec<Suggestion>,
 ) {
@@ -706,7 +706,7 @@ fn suggest_for_object(
     }
 }
 
-fn suggest_for_array(arr: &[Rc<Variable>], prefix: &str, suggestions: &mut Vec<Suggestion>) {
+fn suggest_for_array(arr: &[Rcvar], prefix: &str, suggestions: &mut Vec<Suggestion>) {
     let path = if prefix.is_empty() {
         "@".to_string()
     } else {
@@ -856,7 +856,7 @@ fn looks_like_url(s: &str) -> bool {
 
 /// Advanced suggestions for objects with arrays
 fn suggest_advanced_object(
-    obj: &std::collections::BTreeMap<String, Rc<Variable>>,
+    obj: &std::collections::BTreeMap<String, Rcvar>,
     suggestions: &mut Vec<Suggestion>,
 ) {
     // Find arrays of objects for advanced patterns
@@ -1006,7 +1006,7 @@ fn suggest_advanced_object(
 }
 
 /// Advanced suggestions for top-level arrays
-fn suggest_advanced_array(arr: &[Rc<Variable>], suggestions: &mut Vec<Suggestion>) {
+fn suggest_advanced_array(arr: &[Rcvar], suggestions: &mut Vec<Suggestion>) {
     if arr.is_empty() {
         return;
     }
@@ -163,6 +163,24 @@ mod extension_functions {
         let result = run_query(r#"[1, 2, 3, 4, 5]"#, "filter_expr('@ > `3`', @)");
         assert_eq!(result, "[\n  4,\n  5\n]");
     }
+    #[test]
+    fn test_let_expression() {
+        // JEP-11 let syntax is rewritten into let_expr() calls
+        let result = run_query(
+            r#"{"min": 3, "xs": [1, 3, 5]}"#,
+            "let $min = min in xs[?@ >= $min]",
+        );
+        assert_eq!(result, "[\n  3,\n  5\n]");
+    }
+
+    #[test]
+    fn test_define_and_call() {
+        let result = run_query(
+            r#"[[1, 2], [3]]"#,
+            "define('size', 'length(@)', @) | map_expr('call(`\"size\"`, @)', @)",
+        );
+        assert_eq!(result, "[\n  2,\n  1\n]");
+    }
 }
 
 mod file_operations {
@@ -286,6 +304,38 @@ mod cli_options {
         assert!(result.contains("array"));
     }
 
+    #[test]
+    fn test_cache_dir() {
+        let testdata = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/users.json");
+        let cache_dir = std::env::temp_dir().join(format!("jpx-cache-test-{}", std::process::id()));
+
+        let run = || {
+            jpx_cmd()
+                .arg("-v")
+                .arg("--cache-dir")
+                .arg(&cache_dir)
+                .arg("-f")
+                .arg(testdata)
+                .arg("length(@)")
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .spawn()
+                .expect("Failed to spawn jpx")
+                .wait_with_output()
+                .expect("Failed to wait")
+        };
+
+        let first = run();
+        assert_eq!(String::from_utf8_lossy(&first.stdout).trim(), "5");
+        assert!(!String::from_utf8_lossy(&first.stderr).contains("Cache hit"));
+
+        let second = run();
+        assert_eq!(String::from_utf8_lossy(&second.stdout).trim(), "5");
+        assert!(String::from_utf8_lossy(&second.stderr).contains("Cache hit"));
+
+        let _ = std::fs::remove_dir_all(&cache_dir);
+    }
+
     #[test]
     fn test_version() {
         let output = jpx_cmd()
@@ -301,6 +351,60 @@ mod cli_options {
     }
 }
 
+mod agg_command {
+    use super::*;
+
+    fn run_agg(args: &[&str]) -> std::process::Output {
+        let testdata = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/users.json");
+        jpx_cmd()
+            .arg("agg")
+            .arg("-f")
+            .arg(testdata)
+            .args(args)
+            .arg("--show-query")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("Failed to spawn jpx")
+            .wait_with_output()
+            .expect("Failed to wait")
+    }
+
+    #[test]
+    fn test_agg_group_by() {
+        let output = run_agg(&["--group-by", "department", "--agg", "count,avg(salary)"]);
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let result: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(result["Engineering"]["count"], 3);
+        assert_eq!(result["Marketing"]["count"], 1);
+        assert!(result["Sales"]["avg_salary"].is_number());
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(
+            stderr.trim(),
+            "aggregate(@, 'department', {count: 'length(_group)', avg_salary: 'avg(_group[*].salary)'})"
+        );
+    }
+
+    #[test]
+    fn test_agg_without_group_by() {
+        let output = run_agg(&["--agg", "count,max(salary)"]);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let result: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(result["count"], 5);
+        assert!(result["max_salary"].is_number());
+    }
+
+    #[test]
+    fn test_agg_unknown_aggregate() {
+        let output = run_agg(&["--agg", "median(salary)"]);
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown aggregate"));
+    }
+}
+
 mod error_handling {
     use super::*;
 